@@ -0,0 +1,36 @@
+//! Benchmark of the event layout repulsion pass
+//!
+//! Compares the all-pairs repulsion against the spatial hash grid version at
+//! 500 events.
+//!
+//! Run with: cargo bench --bench event_layout --package cim-domain-bevy
+
+use bevy::math::Vec3;
+use cim_domain_bevy::nats_event_visualization::{repulsion_forces_grid, repulsion_forces_naive};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rand::{Rng, SeedableRng};
+
+const EVENT_COUNT: usize = 500;
+
+/// Event positions spread like freshly spawned events
+fn event_positions(count: usize) -> Vec<Vec3> {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+    (0..count)
+        .map(|_| Vec3::new(rng.gen_range(-10.0..10.0), rng.gen_range(0.0..10.0), rng.gen_range(-10.0..10.0)))
+        .collect()
+}
+
+fn repulsion_benchmark(c: &mut Criterion) {
+    let positions = event_positions(EVENT_COUNT);
+    let mut group = c.benchmark_group("event_repulsion_500");
+    group.bench_function("naive", |b| {
+        b.iter(|| repulsion_forces_naive(black_box(&positions)))
+    });
+    group.bench_function("grid", |b| {
+        b.iter(|| repulsion_forces_grid(black_box(&positions)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, repulsion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,73 @@
+//! Benchmark of region queries on the spatial index
+//!
+//! Compares a linear scan over every node against the R-tree behind
+//! `SpatialIndexProjection` at 10,000 nodes.
+//!
+//! Run with: cargo bench --bench spatial_index --package cim-domain-bevy
+
+use bevy::math::Vec3;
+use cim_contextgraph::NodeId;
+use cim_domain_bevy::projections::SpatialIndexProjection;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rand::{Rng, SeedableRng};
+
+const NODE_COUNT: usize = 10_000;
+const QUERY_COUNT: usize = 200;
+
+/// Nodes strewn over a wide, shallow slab
+fn node_positions(rng: &mut impl Rng, count: usize) -> Vec<(NodeId, Vec3)> {
+    (0..count)
+        .map(|_| {
+            let position = Vec3::new(
+                rng.gen_range(-500.0..500.0),
+                rng.gen_range(-500.0..500.0),
+                rng.gen_range(-50.0..50.0),
+            );
+            (NodeId::new(), position)
+        })
+        .collect()
+}
+
+/// Boxes fifty units across, through the full depth of the slab
+fn query_regions(rng: &mut impl Rng, count: usize) -> Vec<(Vec3, Vec3)> {
+    (0..count)
+        .map(|_| {
+            let min = Vec3::new(rng.gen_range(-500.0..450.0), rng.gen_range(-500.0..450.0), -50.0);
+            (min, min + Vec3::new(50.0, 50.0, 100.0))
+        })
+        .collect()
+}
+
+fn region_query_benchmark(c: &mut Criterion) {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+    let nodes = node_positions(&mut rng, NODE_COUNT);
+    let queries = query_regions(&mut rng, QUERY_COUNT);
+    let mut index = SpatialIndexProjection::default();
+    for (node_id, position) in &nodes {
+        index.insert(*node_id, *position);
+    }
+
+    let mut group = c.benchmark_group("region_queries_10k");
+    group.bench_function("linear", |b| {
+        b.iter(|| {
+            for (min, max) in black_box(&queries) {
+                let hits: Vec<NodeId> = nodes.iter()
+                    .filter(|(_, position)| position.cmpge(*min).all() && position.cmple(*max).all())
+                    .map(|(node_id, _)| *node_id)
+                    .collect();
+                black_box(hits);
+            }
+        })
+    });
+    group.bench_function("rtree", |b| {
+        b.iter(|| {
+            for (min, max) in black_box(&queries) {
+                black_box(index.query_region(*min, *max));
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, region_query_benchmark);
+criterion_main!(benches);
@@ -40,8 +40,10 @@ fn setup(mut commands: Commands, mut create_node: EventWriter<CreateNodeVisual>)
     // Create a sample node
     create_node.send(CreateNodeVisual {
         node_id: uuid::Uuid::new_v4(),
+        graph_id: GraphId::new(),
         position: Vec3::ZERO,
         label: "Sample Node".to_string(),
+        metadata: serde_json::Value::Null,
     });
 }
 
@@ -10,6 +10,8 @@
 
 use bevy::prelude::*;
 use cim_domain_bevy::*;
+use cim_domain_bevy::morphisms::NodeEntityMap;
+use cim_domain_bevy::value_objects::NodeMetadata;
 use cim_domain_graph::{
     aggregate::business_graph::Graph,
     deployment::{
@@ -43,10 +45,6 @@ impl Default for DeploymentDemoState {
     }
 }
 
-/// Map node IDs to Bevy entities
-#[derive(Resource, Default)]
-struct NodeEntityMap(HashMap<NodeId, Entity>);
-
 /// Component for deployment node visuals
 #[derive(Component)]
 struct DeploymentNodeVisual {
@@ -68,14 +66,13 @@ fn main() {
             }),
             ..default()
         }))
-        .add_plugins(CimVizPlugin::default())
+        .add_plugins((CimVizPlugin::default(), PickingPlugin, NodeShapePlugin, StatisticsHudPlugin))
         .insert_resource(DeploymentDemoState::default())
-        .insert_resource(NodeEntityMap::default())
         .add_systems(Startup, (setup_scene, create_deployment_graph))
         .add_systems(Update, (
             visualize_deployment_nodes,
             visualize_deployment_edges,
-            handle_node_selection,
+            handle_node_selection.in_set(PickingSet::Selection),
             update_metadata_display,
             handle_keyboard_input,
         ))
@@ -141,7 +138,8 @@ fn setup_scene(
             - Click nodes to select\n\
             - Press 'M' to toggle metadata\n\
             - Press 'L' to change layout\n\
-            - Press 'Space' to add random node"
+            - Press 'Space' to add random node\n\
+            - Press 'F3' to toggle statistics"
         ),
         Node {
             position_type: PositionType::Absolute,
@@ -362,77 +360,86 @@ fn visualize_deployment_nodes(
     state: Res<DeploymentDemoState>,
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
+    mut shape_meshes: ResMut<ShapeMeshCache>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut node_map: ResMut<NodeEntityMap>,
-    existing_nodes: Query<Entity, With<DeploymentNodeVisual>>,
+    edges: Query<(Entity, &EdgeVisual)>,
+    mut graph_sync: Local<Option<GraphSync>>,
 ) {
-    // Remove existing nodes
-    for entity in existing_nodes.iter() {
-        commands.entity(entity).despawn_recursive();
-    }
-    node_map.0.clear();
-    
-    // Create node visuals
+    let graph_sync = graph_sync.get_or_insert_with(|| GraphSync::new(state.graph.id()));
+
+    // Lay the nodes out in a circle
     let nodes = state.graph.nodes();
     let node_count = nodes.len();
-    
-    for (i, (node_id, node)) in nodes.iter().enumerate() {
-        // Calculate position in a circle
-        let angle = (i as f32 / node_count as f32) * std::f32::consts::TAU;
-        let radius = 10.0;
-        let x = angle.cos() * radius;
-        let z = angle.sin() * radius;
-        
+    let desired: Vec<(NodeId, Vec3, NodeMetadata)> = nodes.iter()
+        .enumerate()
+        .map(|(i, (node_id, node))| {
+            let angle = (i as f32 / node_count as f32) * std::f32::consts::TAU;
+            let radius = 10.0;
+            let position = Vec3::new(angle.cos() * radius, 0.0, angle.sin() * radius);
+
+            let label = node.metadata.get("deployment")
+                .and_then(|deployment_data| serde_json::from_value::<DeploymentNodeType>(deployment_data.clone()).ok())
+                .map(|node_type| node_type.name().to_string())
+                .unwrap_or_default();
+            let metadata = NodeMetadata {
+                label,
+                tags: vec![node.node_type.clone()],
+                ..default()
+            };
+            (*node_id, position, metadata)
+        })
+        .collect();
+
+    // Only nodes that are new get spawned; existing ones keep their
+    // selection and are moved in place
+    let report = graph_sync.sync_graph(&mut commands, &mut node_map, &edges, &desired);
+
+    for (node_id, entity) in report.spawned {
+        let Some(node) = nodes.get(&node_id) else {
+            continue;
+        };
+
         // Get node color based on type
         let (color, shape) = match node.node_type.as_str() {
-            "LoadBalancer" => (Color::srgb(0.2, 0.7, 0.9), NodeShape::Cube),
-            "Service" => (Color::srgb(0.2, 0.9, 0.2), NodeShape::Sphere),
-            "Database" => (Color::srgb(0.9, 0.9, 0.2), NodeShape::Cylinder),
-            "MessageBus" => (Color::srgb(0.9, 0.2, 0.9), NodeShape::Torus),
-            "Agent" => (Color::srgb(0.9, 0.5, 0.2), NodeShape::Cone),
-            _ => (Color::srgb(0.5, 0.5, 0.5), NodeShape::Sphere),
-        };
-        
-        // Create mesh based on shape
-        let mesh = match shape {
-            NodeShape::Sphere => meshes.add(Sphere::new(1.0).mesh()),
-            NodeShape::Cube => meshes.add(Cuboid::new(2.0, 2.0, 2.0).mesh()),
-            NodeShape::Cylinder => meshes.add(Cylinder::new(1.0, 2.0).mesh()),
-            NodeShape::Torus => meshes.add(Torus::new(0.8, 0.3).mesh()),
-            NodeShape::Cone => meshes.add(Cone::new(1.0, 2.0).mesh()),
+            "LoadBalancer" => (Color::srgb(0.2, 0.7, 0.9), NodeShape::Square),
+            "Service" => (Color::srgb(0.2, 0.9, 0.2), NodeShape::Circle),
+            "Database" => (Color::srgb(0.9, 0.9, 0.2), NodeShape::Hexagon),
+            "MessageBus" => (Color::srgb(0.9, 0.2, 0.9), NodeShape::Diamond),
+            "Agent" => (Color::srgb(0.9, 0.5, 0.2), NodeShape::Triangle),
+            _ => (Color::srgb(0.5, 0.5, 0.5), NodeShape::Circle),
         };
-        
-        let entity = commands.spawn((
+
+        // Nodes of the same shape share one unit mesh, sized by the transform
+        let mesh = shape_meshes.shape_to_mesh(shape, &mut meshes);
+        let desired_node = desired.iter().find(|(id, _, _)| *id == node_id);
+        let position = desired_node.map_or(Vec3::ZERO, |(_, position, _)| *position);
+
+        commands.entity(entity).insert((
             Mesh3d(mesh),
+            Transform::from_translation(position).with_scale(Vec3::splat(2.0)),
             MeshMaterial3d(materials.add(StandardMaterial {
                 base_color: color,
                 metallic: 0.3,
                 perceptual_roughness: 0.5,
                 ..default()
             })),
-            Transform::from_xyz(x, 0.0, z),
             DeploymentNodeVisual {
-                node_id: *node_id,
+                node_id,
                 node_type: node.node_type.clone(),
             },
-            NodeVisual {
-                node_id: *node_id,
-                graph_id: state.graph.id(),
-            },
-        )).id();
-        
-        // Add label
-        if let Some(deployment_data) = node.metadata.get("deployment") {
-            if let Ok(node_type) = serde_json::from_value::<DeploymentNodeType>(deployment_data.clone()) {
-                let name = node_type.name();
-                commands.spawn((
-                    Text::new(name),
-                    Transform::from_xyz(x, 2.5, z),
+        ));
+
+        // Add label as a child, so it goes away with the node
+        if let Some((_, _, metadata)) = desired_node {
+            if !metadata.label.is_empty() {
+                commands.entity(entity).with_child((
+                    Text::new(metadata.label.clone()),
+                    // In the node's space, which is scaled by 2
+                    Transform::from_xyz(0.0, 1.25, 0.0).with_scale(Vec3::splat(0.5)),
                 ));
             }
         }
-        
-        node_map.0.insert(*node_id, entity);
     }
 }
 
@@ -444,8 +451,8 @@ fn visualize_deployment_edges(
 ) {
     for edge in state.graph.edges().values() {
         if let (Some(&from_entity), Some(&to_entity)) = (
-            node_map.0.get(&edge.source_id),
-            node_map.0.get(&edge.target_id),
+            node_map.get(&edge.source_id),
+            node_map.get(&edge.target_id),
         ) {
             if let (Ok(from_transform), Ok(to_transform)) = (
                 transforms.get(from_entity),
@@ -477,34 +484,13 @@ fn visualize_deployment_edges(
 
 fn handle_node_selection(
     mut state: ResMut<DeploymentDemoState>,
-    mouse_button_input: Res<ButtonInput<MouseButton>>,
-    windows: Query<&Window>,
-    cameras: Query<(&Camera, &GlobalTransform), With<GraphCamera>>,
-    nodes: Query<(&Transform, &DeploymentNodeVisual)>,
+    mut clicked_events: EventReader<NodeClicked>,
+    nodes: Query<&DeploymentNodeVisual>,
 ) {
-    if !mouse_button_input.just_pressed(MouseButton::Left) {
-        return;
-    }
-    
-    let Ok(window) = windows.get_single() else { return };
-    let Some(cursor_position) = window.cursor_position() else { return };
-    let Ok((camera, camera_transform)) = cameras.get_single() else { return };
-    
-    if let Ok(ray) = camera.viewport_to_world(camera_transform, cursor_position) {
-        let mut closest_distance = f32::MAX;
-        let mut selected = None;
-        
-        for (transform, node_visual) in nodes.iter() {
-            let distance = ray.closest_point_to_point(transform.translation);
-            let dist_to_node = distance.distance(transform.translation);
-            
-            if dist_to_node < 2.0 && dist_to_node < closest_distance {
-                closest_distance = dist_to_node;
-                selected = Some(node_visual.node_id);
-            }
+    for event in clicked_events.read() {
+        if let Ok(node_visual) = nodes.get(event.entity) {
+            state.selected_node = Some(node_visual.node_id);
         }
-        
-        state.selected_node = selected;
     }
 }
 
@@ -574,12 +560,3 @@ fn handle_keyboard_input(
         state.show_metadata = !state.show_metadata;
     }
 }
-
-#[derive(Clone, Copy)]
-enum NodeShape {
-    Sphere,
-    Cube,
-    Cylinder,
-    Torus,
-    Cone,
-}
\ No newline at end of file
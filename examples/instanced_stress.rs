@@ -0,0 +1,116 @@
+//! Instanced Rendering Stress Test
+//!
+//! Spawns a few thousand nodes and compares individual against instanced
+//! rendering. The overlay shows the number of mesh entities, which is the
+//! number of draw calls the nodes cost.
+//!
+//! Controls:
+//! - I: Toggle between individual and instanced rendering
+//!
+//! Run with: cargo run --release --example instanced_stress --package cim-domain-bevy
+
+use bevy::prelude::*;
+use cim_domain_bevy::*;
+use cim_contextgraph::{NodeId, ContextGraphId as GraphId};
+
+const NODE_COUNT: usize = 2000;
+
+/// Marker for the stats overlay
+#[derive(Component)]
+struct StatsText;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(Window {
+                title: "CIM Instanced Rendering Stress Test".to_string(),
+                resolution: (1200.0, 800.0).into(),
+                ..default()
+            }),
+            ..default()
+        }))
+        .add_plugins(InstancingPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, (toggle_render_mode, orbit_nodes, update_stats))
+        .run();
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(0.0, 60.0, 90.0).looking_at(Vec3::ZERO, Vec3::Y),
+        GraphCamera,
+    ));
+    commands.spawn((
+        DirectionalLight {
+            illuminance: 10000.0,
+            ..default()
+        },
+        Transform::from_rotation(Quat::from_euler(EulerRot::XYZ, -0.8, 0.4, 0.0)),
+    ));
+
+    let graph_id = GraphId::new();
+    let shapes = [NodeShape::Circle, NodeShape::Square, NodeShape::Diamond, NodeShape::Triangle, NodeShape::Hexagon];
+    let side = (NODE_COUNT as f32).cbrt().ceil() as usize;
+    for i in 0..NODE_COUNT {
+        let position = Vec3::new(
+            (i % side) as f32,
+            ((i / side) % side) as f32,
+            (i / (side * side)) as f32,
+        ) * 4.0 - Vec3::splat(side as f32 * 2.0);
+        commands.spawn((
+            NodeVisualBundle::new(NodeId::new(), graph_id, position),
+            InstancedShape {
+                shape: shapes[i % shapes.len()],
+                color: Color::hsl((i * 37 % 360) as f32, 0.7, 0.55),
+                size: 1.2,
+            },
+        ));
+    }
+
+    commands.spawn((
+        Text::new(""),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            left: Val::Px(10.0),
+            ..default()
+        },
+        StatsText,
+    ));
+}
+
+fn toggle_render_mode(keyboard: Res<ButtonInput<KeyCode>>, mut mode: ResMut<RenderMode>) {
+    if keyboard.just_pressed(KeyCode::KeyI) {
+        *mode = match *mode {
+            RenderMode::Individual => RenderMode::Instanced,
+            RenderMode::Instanced => RenderMode::Individual,
+        };
+    }
+}
+
+/// Keep the nodes moving so batches are rebuilt every frame
+fn orbit_nodes(time: Res<Time>, mut nodes: Query<&mut Transform, With<InstancedShape>>) {
+    let rotation = Quat::from_rotation_y(time.delta_secs() * 0.2);
+    for mut transform in nodes.iter_mut() {
+        transform.translation = rotation * transform.translation;
+    }
+}
+
+fn update_stats(
+    mode: Res<RenderMode>,
+    time: Res<Time>,
+    meshes: Query<(), With<Mesh3d>>,
+    mut text: Query<&mut Text, With<StatsText>>,
+) {
+    let Ok(mut text) = text.single_mut() else {
+        return;
+    };
+    text.0 = format!(
+        "Mode: {:?} (I to toggle)\nNodes: {}\nMesh entities (draw calls): {}\nFrame time: {:.1} ms",
+        *mode,
+        NODE_COUNT,
+        meshes.iter().count(),
+        time.delta_secs() * 1000.0,
+    );
+}
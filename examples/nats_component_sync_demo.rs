@@ -77,7 +77,7 @@ fn setup(
                 ..default()
             },
             Transform::from_xyz(i as f32 * 2.0 - 2.0, 0.0, 0.0),
-            NatsSyncedEntity { entity_id },
+            NatsSyncedEntity { entity_id, version: 0 },
             Name::new(format!("Synced Entity {}", i)),
         ));
     }
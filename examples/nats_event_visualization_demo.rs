@@ -5,9 +5,10 @@
 //! events as they occur in the system.
 
 use bevy::prelude::*;
-use cim_domain_bevy::{NatsEventVisualizationPlugin, EventVisualizationCommand};
+use cim_domain_bevy::{NatsEventVisualizationPlugin, EventVisualizationCommand, RetentionPolicy};
 use async_nats::Client;
 use std::sync::Arc;
+use std::time::Duration;
 
 #[tokio::main]
 async fn main() {
@@ -31,8 +32,11 @@ async fn main() {
         .add_plugins(DefaultPlugins)
         .add_plugins(NatsEventVisualizationPlugin {
             nats_client,
-            max_events: 200,
-            retention_seconds: 600, // Keep events for 10 minutes
+            retention: RetentionPolicy {
+                max_count: 200,
+                max_age: Duration::from_secs(600), // 10 minutes
+            },
+            domain_colors: Default::default(),
         })
         .add_systems(Update, (
             camera_controls,
@@ -5,15 +5,21 @@
 //! - Filtering UI for domain, event type, and search
 //! - Live statistics display
 //! - Event correlation visualization
+//! - Connection indicator, red while the NATS bridge is down
 
 use bevy::prelude::*;
 use cim_domain_bevy::{
     NatsEventVisualizationPlugin, 
     EventVisualizationUIPlugin,
+    InspectorPlugin,
     DomainEventReceived,
+    RetentionPolicy,
+    BridgeStatus,
+    BridgeStatusChanged,
 };
 use async_nats::Client;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::runtime::Runtime;
 
 fn main() {
@@ -51,13 +57,18 @@ fn main() {
         }))
         .add_plugins(NatsEventVisualizationPlugin {
             nats_client: nats_client.clone(),
-            max_events: 200,
-            retention_seconds: 600, // 10 minutes
+            retention: RetentionPolicy {
+                max_count: 200,
+                max_age: Duration::from_secs(600), // 10 minutes
+            },
+            domain_colors: Default::default(),
         })
         .add_plugins(EventVisualizationUIPlugin)
-        .add_systems(Startup, setup_demo_instructions)
+        .add_plugins(InspectorPlugin)
+        .add_systems(Startup, (setup_demo_instructions, setup_connection_indicator))
         .add_systems(Update, (
             handle_demo_controls,
+            update_connection_indicator,
             generate_demo_events.run_if(resource_exists::<DemoMode>),
         ))
         .run();
@@ -115,6 +126,48 @@ fn setup_demo_instructions(mut commands: Commands) {
     commands.insert_resource(DemoMode::default());
 }
 
+/// Marker for the NATS connection indicator
+#[derive(Component)]
+struct ConnectionIndicator;
+
+/// Setup the connection indicator in the top right corner
+fn setup_connection_indicator(mut commands: Commands) {
+    commands.spawn((
+        ConnectionIndicator,
+        Text::new("● NATS: connecting"),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.9, 0.7, 0.2)),
+        Node {
+            position_type: PositionType::Absolute,
+            right: Val::Px(320.0),
+            top: Val::Px(10.0),
+            ..default()
+        },
+    ));
+}
+
+/// Show the bridge status on the indicator, red while disconnected
+fn update_connection_indicator(
+    mut status_changed: EventReader<BridgeStatusChanged>,
+    mut indicator: Query<(&mut Text, &mut TextColor), With<ConnectionIndicator>>,
+) {
+    let Some(BridgeStatusChanged { status }) = status_changed.read().last() else {
+        return;
+    };
+    let (label, color) = match status {
+        BridgeStatus::Connected => ("● NATS: connected".to_string(), Color::srgb(0.2, 0.8, 0.3)),
+        BridgeStatus::Disconnected => ("● NATS: disconnected".to_string(), Color::srgb(0.9, 0.1, 0.1)),
+        BridgeStatus::Error(message) => (format!("● NATS: {}", message), Color::srgb(0.9, 0.1, 0.1)),
+    };
+    for (mut text, mut text_color) in &mut indicator {
+        text.0 = label.clone();
+        text_color.0 = color;
+    }
+}
+
 /// Handle demo controls
 fn handle_demo_controls(
     keyboard: Res<ButtonInput<KeyCode>>,
@@ -15,6 +15,7 @@ fn main() {
         .add_plugins(CimVizPlugin::default())
         .insert_resource(NodeMap::default())
         .insert_resource(GraphCreated(false))
+        .insert_resource(DemoGraph(GraphId::new()))
         .add_systems(Startup, setup)
         .add_systems(
             Update,
@@ -36,6 +37,10 @@ struct NodeMap {
 #[derive(Resource, PartialEq)]
 struct GraphCreated(bool);
 
+/// The one graph every node and edge of the demo belongs to
+#[derive(Resource)]
+struct DemoGraph(GraphId);
+
 fn setup(mut commands: Commands) {
     // Camera
     commands.spawn((
@@ -65,10 +70,12 @@ fn create_initial_graph(
     mut create_node: EventWriter<CreateNodeVisual>,
     mut create_edge: EventWriter<CreateEdgeVisual>,
     mut created: ResMut<GraphCreated>,
+    graph: Res<DemoGraph>,
 ) {
     created.0 = true;
 
     // Create a simple triangle of nodes
+    let graph_id = graph.0;
     let node1 = NodeId::new();
     let node2 = NodeId::new();
     let node3 = NodeId::new();
@@ -76,42 +83,54 @@ fn create_initial_graph(
     // Send node creation events
     create_node.send(CreateNodeVisual {
         node_id: node1,
+        graph_id,
         position: Vec3::new(-3.0, 0.0, 0.0),
         label: "Node 1".to_string(),
+        metadata: serde_json::Value::Null,
     });
 
     create_node.send(CreateNodeVisual {
         node_id: node2,
+        graph_id,
         position: Vec3::new(3.0, 0.0, 0.0),
         label: "Node 2".to_string(),
+        metadata: serde_json::Value::Null,
     });
 
     create_node.send(CreateNodeVisual {
         node_id: node3,
+        graph_id,
         position: Vec3::new(0.0, 0.0, -3.0),
         label: "Node 3".to_string(),
+        metadata: serde_json::Value::Null,
     });
 
     // Create edges
     create_edge.send(CreateEdgeVisual {
         edge_id: EdgeId::new(),
+        graph_id,
         source_node_id: node1,
         target_node_id: node2,
         relationship: EdgeRelationship::Custom("connects".to_string()),
+        weight: None,
     });
 
     create_edge.send(CreateEdgeVisual {
         edge_id: EdgeId::new(),
+        graph_id,
         source_node_id: node2,
         target_node_id: node3,
         relationship: EdgeRelationship::Custom("connects".to_string()),
+        weight: None,
     });
 
     create_edge.send(CreateEdgeVisual {
         edge_id: EdgeId::new(),
+        graph_id,
         source_node_id: node3,
         target_node_id: node1,
         relationship: EdgeRelationship::Custom("connects".to_string()),
+        weight: None,
     });
 
     info!("Created initial graph with 3 nodes and 3 edges");
@@ -123,6 +142,7 @@ fn handle_node_creation(
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut node_map: ResMut<NodeMap>,
+    graph: Res<DemoGraph>,
 ) {
     for event in events.read() {
         info!(
@@ -132,7 +152,7 @@ fn handle_node_creation(
 
         let entity = commands
             .spawn((
-                NodeVisualBundle::new(event.node_id, GraphId::new(), event.position),
+                NodeVisualBundle::new(event.node_id, graph.0, event.position),
                 Mesh3d(meshes.add(Sphere::new(0.5).mesh())),
                 MeshMaterial3d(materials.add(StandardMaterial {
                     base_color: Color::srgb(0.3, 0.7, 0.9),
@@ -149,6 +169,7 @@ fn handle_edge_creation(
     mut commands: Commands,
     mut events: EventReader<VisualEdgeCreated>,
     node_map: Res<NodeMap>,
+    graph: Res<DemoGraph>,
 ) {
     for event in events.read() {
         info!(
@@ -158,7 +179,7 @@ fn handle_edge_creation(
 
         commands.spawn(EdgeVisualBundle::new(
             event.edge_id,
-            GraphId::new(),
+            graph.0,
             event.source_entity,
             event.target_entity,
         ));
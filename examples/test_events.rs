@@ -9,19 +9,26 @@ fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
         .add_plugins(CimVizPlugin::default())
+        .insert_resource(TestGraph(cim_contextgraph::ContextGraphId::new()))
         .add_systems(Startup, setup)
         .add_systems(Update, test_events)
         .run();
 }
 
-fn setup(mut commands: Commands, mut create_node: EventWriter<CreateNodeVisual>) {
+/// The graph every test node is created in
+#[derive(Resource)]
+struct TestGraph(cim_contextgraph::ContextGraphId);
+
+fn setup(mut commands: Commands, mut create_node: EventWriter<CreateNodeVisual>, graph: Res<TestGraph>) {
     println!("Setup: Creating test node");
 
     // Create a test node visual
     create_node.send(CreateNodeVisual {
         node_id: uuid::Uuid::new_v4(),
+        graph_id: graph.0,
         position: Vec3::ZERO,
         label: "Test Node".to_string(),
+        metadata: serde_json::Value::Null,
     });
 }
 
@@ -30,6 +37,7 @@ fn test_events(
     mut node_clicked: EventReader<NodeClicked>,
     keyboard: Res<ButtonInput<KeyCode>>,
     mut create_node: EventWriter<CreateNodeVisual>,
+    graph: Res<TestGraph>,
 ) {
     for event in node_created.read() {
         println!("Node visual created: {:?}", event.node_id);
@@ -44,8 +52,10 @@ fn test_events(
 
         create_node.send(CreateNodeVisual {
             node_id: uuid::Uuid::new_v4(),
+            graph_id: graph.0,
             position: Vec3::new(1.0, 2.0, 3.0),
             label: "Dynamic Node".to_string(),
+            metadata: serde_json::Value::Null,
         });
     }
 }
@@ -6,6 +6,7 @@
 //! 3. Connect to a domain layer through the bridge
 
 use bevy::prelude::*;
+use bevy::render::primitives::Aabb;
 use cim_contextgraph::{ContextGraphId as GraphId, EdgeId, NodeId};
 use cim_domain_bevy::*;
 
@@ -132,60 +133,32 @@ fn handle_edge_creation(
 fn handle_mouse_clicks(
     mouse_button: Res<ButtonInput<MouseButton>>,
     windows: Query<&Window>,
-    camera: Query<(&Camera, &GlobalTransform), With<GraphCamera>>,
-    nodes: Query<(Entity, &NodeVisual, &Transform)>,
+    cameras: Query<(&Camera, &GlobalTransform), With<GraphCamera>>,
+    nodes: Query<(Entity, &NodeVisual, &GlobalTransform, Option<&Aabb>)>,
     mut node_click_events: EventWriter<NodeClicked>,
 ) {
     if !mouse_button.just_pressed(MouseButton::Left) {
         return;
     }
 
-    let Ok(window) = windows.get_single() else {
+    // Cast a ray from the cursor with the same helpers the picking plugin uses
+    let Some(ray) = cursor_position(windows.iter()).and_then(|cursor| cursor_ray(cameras.iter(), cursor)) else {
         return;
     };
-    let Ok((camera, camera_transform)) = camera.get_single() else {
-        return;
-    };
-
-    if let Some(cursor_position) = window.cursor_position() {
-        // Convert screen coordinates to world ray
-        if let Ok(ray) = camera.viewport_to_world(camera_transform, cursor_position) {
-            let origin = ray.origin;
-            let direction = ray.direction;
-
-            // Simple sphere intersection test
-            let mut clicked_node = None;
-            let mut min_distance = f32::MAX;
-
-            for (entity, node_visual, transform) in nodes.iter() {
-                let sphere_center = transform.translation;
-                let sphere_radius = 0.5; // Match the sphere mesh size
-
-                // Ray-sphere intersection
-                let oc = origin - sphere_center;
-                let a = direction.dot(direction);
-                let b = 2.0 * oc.dot(direction);
-                let c = oc.dot(oc) - sphere_radius * sphere_radius;
-                let discriminant = b * b - 4.0 * a * c;
-
-                if discriminant >= 0.0 {
-                    let sqrt_discriminant = discriminant.sqrt();
-                    let t = (-b - sqrt_discriminant) / (2.0 * a);
-                    if t > 0.0 && t < min_distance {
-                        min_distance = t;
-                        clicked_node = Some((entity, node_visual, origin + direction * t));
-                    }
-                }
-            }
-
-            if let Some((entity, node_visual, hit_point)) = clicked_node {
-                // Send node clicked event
-                node_click_events.send(NodeClicked {
-                    entity,
-                    node_id: node_visual.node_id,
-                });
-            }
-        }
+    let hit = picking::nearest_node_hit(
+        ray,
+        nodes.iter().map(|(entity, _, transform, aabb)| (entity, transform, aabb)),
+    );
+
+    if let Some((entity, _)) = hit {
+        let Ok((_, node_visual, _, _)) = nodes.get(entity) else {
+            return;
+        };
+        // Send node clicked event
+        node_click_events.write(NodeClicked {
+            entity,
+            node_id: node_visual.node_id,
+        });
     }
 }
 
@@ -209,7 +182,9 @@ fn create_node_example(mut create_node: EventWriter<CreateNodeVisual>) {
     // Create a node at a specific position
     create_node.send(CreateNodeVisual {
         node_id: uuid::Uuid::new_v4(),
+        graph_id: GraphId::new(),
         position: Vec3::new(5.0, 0.0, 5.0),
         label: "Alice".to_string(),
+        metadata: serde_json::Value::Null,
     });
 }
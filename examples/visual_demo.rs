@@ -10,17 +10,8 @@
 
 use bevy::prelude::*;
 use cim_domain_bevy::*;
+use cim_domain_bevy::morphisms::NodeEntityMap;
 use cim_contextgraph::{NodeId, EdgeId, ContextGraphId as GraphId};
-use std::collections::HashMap;
-
-/// Animated transition component
-#[derive(Component)]
-struct AnimatedTransition {
-    start_position: Vec3,
-    target_position: Vec3,
-    progress: f32,
-    duration: f32,
-}
 
 /// Camera controller component
 #[derive(Component)]
@@ -29,10 +20,6 @@ struct CameraController {
     pub default_target: Vec3,
 }
 
-/// Node material handle storage
-#[derive(Component)]
-struct NodeMaterial(Handle<StandardMaterial>);
-
 fn main() {
     App::new()
         .add_plugins(DefaultPlugins.set(WindowPlugin {
@@ -43,19 +30,17 @@ fn main() {
             }),
             ..default()
         }))
-        .add_plugins(CimVizPlugin::default())
+        .add_plugins((CimVizPlugin::default(), PickingPlugin, HoverPlugin, OutlinePlugin, GraphExportPlugin, AnimationPlugin, StatisticsHudPlugin))
         .insert_resource(DemoState::default())
-        .insert_resource(NodeEntityMap::default())
         .add_systems(Startup, (setup_scene, create_demo_graph))
         .add_systems(Update, (
             handle_node_creation,
             handle_edge_creation,
-            handle_mouse_interaction,
+            handle_mouse_interaction.in_set(PickingSet::Selection),
             handle_keyboard_input,
-            animate_nodes,
-            render_edges,
             update_info_text,
         ))
+        .add_systems(Update, handle_save_load)
         .run();
 }
 
@@ -69,12 +54,6 @@ struct DemoState {
     edge_count: usize,
 }
 
-/// Map of node IDs to entities
-#[derive(Resource, Default)]
-struct NodeEntityMap {
-    map: HashMap<NodeId, Entity>,
-}
-
 /// Info text marker
 #[derive(Component)]
 struct InfoText;
@@ -112,7 +91,7 @@ fn setup_scene(
 
     // Info text
     commands.spawn((
-        Text::new("CIM Graph Demo\nNodes: 0\nEdges: 0\n\nPress SPACE to add nodes\nClick nodes to select\nPress D to delete selected"),
+        Text::new("CIM Graph Demo\nNodes: 0\nEdges: 0\n\nPress SPACE to add nodes\nClick nodes to select\nPress D to delete selected\nPress S to save, L to load\nPress X to export DOT/SVG\nPress F3 for statistics"),
         TextFont {
             font_size: 18.0,
             ..default()
@@ -164,8 +143,10 @@ fn create_demo_graph(
 
         create_node.send(CreateNodeVisual {
             node_id,
+            graph_id,
             position: *pos,
             label: format!("Node {i + 1}"),
+            metadata: serde_json::Value::Null,
         });
     }
 
@@ -173,20 +154,21 @@ fn create_demo_graph(
     for i in 1..node_ids.len() {
         create_edge.send(CreateEdgeVisual {
             edge_id: uuid::Uuid::new_v4(),
+            graph_id,
             source_node_id: node_ids[0],
             target_node_id: node_ids[i],
             relationship: EdgeRelationship::Custom("Connection".to_string()),
+            weight: None,
         });
     }
 }
 
-/// Handle node creation events
+/// Give the node visuals spawned by the plugin a mesh and a drop-in animation
 fn handle_node_creation(
     mut commands: Commands,
     mut create_events: EventReader<VisualNodeCreated>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
-    mut node_map: ResMut<NodeEntityMap>,
     mut demo_state: ResMut<DemoState>,
 ) {
     for event in create_events.read() {
@@ -197,20 +179,18 @@ fn handle_node_creation(
             ..default()
         });
 
-        let entity = commands.spawn((
-            NodeVisualBundle::new(event.node_id, GraphId::new(), event.position),
+        commands.entity(event.entity).insert((
             Mesh3d(meshes.add(Sphere::new(0.5).mesh())),
-            MeshMaterial3d(material.clone()),
-            NodeMaterial(material),
+            MeshMaterial3d(material),
             AnimatedTransition {
                 start_position: event.position + Vec3::Y * 10.0,
                 target_position: event.position,
                 progress: 0.0,
                 duration: 1.0,
+                easing: Easing::EaseOutBounce,
             },
-        )).id();
+        ));
 
-        node_map.map.insert(event.node_id, entity);
         demo_state.node_count += 1;
     }
 }
@@ -221,11 +201,15 @@ fn handle_edge_creation(
     mut create_events: EventReader<VisualEdgeCreated>,
     mut demo_state: ResMut<DemoState>,
 ) {
+    // Edges only exist once the demo graph does
+    let Some(graph_id) = demo_state.current_graph_id else {
+        return;
+    };
     for event in create_events.read() {
         commands.spawn(
             EdgeVisualBundle::new(
                 event.edge_id,
-                GraphId::new(),
+                graph_id,
                 event.source_entity,
                 event.target_entity,
             )
@@ -234,80 +218,34 @@ fn handle_edge_creation(
     }
 }
 
-/// Handle mouse interaction
+/// Handle mouse interaction reported by the picking plugin; hovered and
+/// selected nodes are highlighted and outlined by the hover and outline
+/// plugins
 fn handle_mouse_interaction(
-    mouse_button: Res<ButtonInput<MouseButton>>,
-    windows: Query<&Window>,
-    camera: Query<(&Camera, &GlobalTransform), With<GraphCamera>>,
-    nodes: Query<(Entity, &NodeVisual, &Transform, &NodeMaterial)>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut commands: Commands,
+    mut hovered_events: EventReader<NodeHovered>,
+    mut unhovered_events: EventReader<NodeUnhovered>,
+    mut clicked_events: EventReader<NodeClicked>,
+    node_map: Res<NodeEntityMap>,
     mut demo_state: ResMut<DemoState>,
-    mut node_click_events: EventWriter<NodeClicked>,
 ) {
-    let Ok(window) = windows.get_single() else { return };
-    let Ok((camera, camera_transform)) = camera.get_single() else { return };
-
-    if let Some(cursor_position) = window.cursor_position() {
-        if let Ok(ray) = camera.viewport_to_world(camera_transform, cursor_position) {
-            let mut hovered_node = None;
-
-            // Check for node intersection
-            for (entity, node_visual, transform, node_material) in nodes.iter() {
-                let sphere_center = transform.translation;
-                let sphere_radius = 0.6;
-
-                let ray_origin = ray.origin;
-                let ray_direction = ray.direction.as_vec3();
-                let to_sphere = sphere_center - ray_origin;
-                let t = to_sphere.dot(ray_direction).max(0.0);
-                let closest_point = ray_origin + ray_direction * t;
-                let distance = (closest_point - sphere_center).length();
-
-                if distance < sphere_radius {
-                    hovered_node = Some(node_visual.node_id);
-
-                    // Update material for hover effect
-                    if let Some(material) = materials.get_mut(&node_material.0) {
-                        if demo_state.hovering_node != hovered_node {
-                            material.base_color = Color::srgb(0.4, 0.8, 0.4);
-                        }
-                    }
-
-                    // Handle click
-                    if mouse_button.just_pressed(MouseButton::Left) {
-                        demo_state.selected_node = Some(node_visual.node_id);
-
-                        node_click_events.send(NodeClicked {
-                            entity,
-                            node_id: node_visual.node_id,
-                        });
-
-                        // Update material for selection
-                        if let Some(material) = materials.get_mut(&node_material.0) {
-                            material.base_color = Color::srgb(0.8, 0.4, 0.4);
-                        }
-                    }
-
-                    break;
-                }
-            }
+    for event in unhovered_events.read() {
+        if demo_state.hovering_node == Some(event.node_id) {
+            demo_state.hovering_node = None;
+        }
+    }
 
-            // Reset hover state for non-hovered nodes
-            if demo_state.hovering_node != hovered_node {
-                if let Some(old_node_id) = demo_state.hovering_node {
-                    if let Some(&entity) = node_map.map.get(&old_node_id) {
-                        if let Ok((_, _, _, node_material)) = nodes.get(entity) {
-                            if let Some(material) = materials.get_mut(&node_material.0) {
-                                if demo_state.selected_node != Some(old_node_id) {
-                                    material.base_color = Color::srgb(0.3, 0.7, 0.3);
-                                }
-                            }
-                        }
-                    }
-                }
-                demo_state.hovering_node = hovered_node;
-            }
+    for event in hovered_events.read() {
+        demo_state.hovering_node = Some(event.node_id);
+    }
+
+    for event in clicked_events.read() {
+        let previous = demo_state.selected_node.and_then(|node_id| node_map.get(&node_id));
+        if let Some(&previous) = previous {
+            commands.entity(previous).remove::<Selected>();
         }
+        commands.entity(event.entity).insert(Selected);
+        demo_state.selected_node = Some(event.node_id);
     }
 }
 
@@ -319,18 +257,22 @@ fn handle_keyboard_input(
     mut remove_node: EventWriter<RemoveNodeVisual>,
 ) {
     if keyboard.just_pressed(KeyCode::Space) {
-        // Add a new node at a random position
-        let pos = Vec3::new(
-            (rand::random::<f32>() - 0.5) * 20.0,
-            0.0,
-            (rand::random::<f32>() - 0.5) * 20.0,
-        );
+        // Add a new node to the demo graph at a random position
+        if let Some(graph_id) = demo_state.current_graph_id {
+            let pos = Vec3::new(
+                (rand::random::<f32>() - 0.5) * 20.0,
+                0.0,
+                (rand::random::<f32>() - 0.5) * 20.0,
+            );
 
-        create_node.send(CreateNodeVisual {
-            node_id: uuid::Uuid::new_v4(),
-            position: pos,
-            label: "Dynamic Node".to_string(),
-        });
+            create_node.send(CreateNodeVisual {
+                node_id: uuid::Uuid::new_v4(),
+                graph_id,
+                position: pos,
+                label: "Dynamic Node".to_string(),
+                metadata: serde_json::Value::Null,
+            });
+        }
     }
 
     if keyboard.just_pressed(KeyCode::KeyD) {
@@ -344,40 +286,45 @@ fn handle_keyboard_input(
     }
 }
 
-/// Animate nodes dropping in
-fn animate_nodes(
-    mut query: Query<(&mut Transform, &mut AnimatedTransition)>,
-    time: Res<Time>,
-) {
-    for (mut transform, mut transition) in query.iter_mut() {
-        if transition.progress < 1.0 {
-            transition.progress += time.delta_secs() / transition.duration;
-            transition.progress = transition.progress.min(1.0);
-
-            // Smooth easing
-            let t = ease_out_cubic(transition.progress);
-            transform.translation = transition.start_position.lerp(transition.target_position, t);
+/// File the demo graph is saved to and loaded from
+const SNAPSHOT_PATH: &str = "visual_demo_graph.json";
+
+/// Save the current graph with S, replace it with the saved one with L
+fn handle_save_load(world: &mut World) {
+    let keyboard = world.resource::<ButtonInput<KeyCode>>();
+    let (save, load) = (keyboard.just_pressed(KeyCode::KeyS), keyboard.just_pressed(KeyCode::KeyL));
+    let Some(graph_id) = world.resource::<DemoState>().current_graph_id else {
+        return;
+    };
+
+    if save {
+        match serialize_graph(world, graph_id).save(SNAPSHOT_PATH) {
+            Ok(()) => info!("Saved graph to {SNAPSHOT_PATH}"),
+            Err(error) => error!("Failed to save graph: {error}"),
         }
     }
-}
 
-/// Render edges
-fn render_edges(
-    mut gizmos: Gizmos,
-    edges: Query<&EdgeVisual>,
-    nodes: Query<&Transform>,
-) {
-    for edge in edges.iter() {
-        if let (Ok(source_transform), Ok(target_transform)) = (
-            nodes.get(edge.source_entity),
-            nodes.get(edge.target_entity),
-        ) {
-            gizmos.line(
-                source_transform.translation,
-                target_transform.translation,
-                Color::srgb(0.6, 0.6, 0.6),
-            );
+    if load {
+        let snapshot = match GraphSnapshot::load(SNAPSHOT_PATH) {
+            Ok(snapshot) => snapshot,
+            Err(error) => {
+                error!("Failed to load graph: {error}");
+                return;
+            }
+        };
+
+        // Clear the current graph before restoring the saved one
+        let mut visuals = world.query_filtered::<Entity, Or<(With<NodeVisual>, With<EdgeVisual>)>>();
+        let entities: Vec<Entity> = visuals.iter(world).collect();
+        for entity in entities {
+            world.despawn(entity);
         }
+
+        let mut commands = world.commands();
+        spawn_from_snapshot(&mut commands, &snapshot);
+        world.flush();
+        world.resource_mut::<DemoState>().current_graph_id = Some(snapshot.graph_id);
+        info!("Loaded graph from {SNAPSHOT_PATH}");
     }
 }
 
@@ -400,11 +347,6 @@ fn update_info_text(
     }
 }
 
-/// Easing function for smooth animation
-fn ease_out_cubic(t: f32) -> f32 {
-    1.0 - (1.0 - t).powi(3)
-}
-
 // Add rand for demo purposes
 mod rand {
     pub fn random<T>() -> T
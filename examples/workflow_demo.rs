@@ -8,6 +8,7 @@
 
 use bevy::prelude::*;
 use cim_domain_bevy::*;
+use cim_domain_bevy::morphisms::NodeEntityMap;
 use std::collections::HashMap;
 use cim_contextgraph::{NodeId, EdgeId, ContextGraphId as GraphId};
 
@@ -24,9 +25,14 @@ fn main() {
             }),
             ..default()
         }))
-        .add_plugins(CimVizPlugin::default())
+        .add_plugins((CimVizPlugin::default(), EnvironmentPlugin))
+        // The shared grid stands in for a ground plane under the workflow
+        .insert_resource(EnvironmentConfig {
+            grid_cells: 50,
+            grid_isometry: Isometry3d::new(Vec3::new(0.0, -2.0, 0.0), Quat::from_rotation_x(std::f32::consts::FRAC_PI_2)),
+            ..default()
+        })
         .insert_resource(WorkflowDemo::default())
-        .insert_resource(NodeEntityMap::default())
         .add_systems(Startup, (setup_scene, create_workflow))
         .add_systems(
             Update,
@@ -47,6 +53,7 @@ struct WorkflowDemo {
     workflow_state: WorkflowState,
     current_step: usize,
     node_states: HashMap<NodeId, NodeState>,
+    node_labels: HashMap<NodeId, String>,
     animation_timer: Timer,
 }
 
@@ -82,11 +89,6 @@ enum WorkflowNodeType {
     End,
 }
 
-#[derive(Resource, Default)]
-struct NodeEntityMap {
-    map: HashMap<NodeId, Entity>,
-}
-
 #[derive(Component)]
 struct EdgeLine {
     label: String,
@@ -98,11 +100,7 @@ struct NodeMaterial {
     state: NodeState,
 }
 
-fn setup_scene(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-) {
+fn setup_scene(mut commands: Commands) {
     // Camera
     commands.spawn((
         Camera3d::default(),
@@ -118,16 +116,6 @@ fn setup_scene(
         Transform::from_rotation(Quat::from_rotation_x(-0.3)),
     ));
 
-    // Ground plane
-    commands.spawn((
-        Mesh3d(meshes.add(Plane3d::default().mesh().size(50.0, 50.0))),
-        MeshMaterial3d(materials.add(StandardMaterial {
-            base_color: Color::srgb(0.1, 0.1, 0.1),
-            ..default()
-        })),
-        Transform::from_xyz(0.0, -2.0, 0.0),
-    ));
-
     println!("Scene setup complete");
 }
 
@@ -166,13 +154,16 @@ fn create_workflow(
         };
 
         demo.node_states.insert(node_id, initial_state);
+        demo.node_labels.insert(node_id, name.to_string());
 
         println!("Creating node: {} ({:?})", name, node_type);
 
         create_node.send(CreateNodeVisual {
             node_id,
+            graph_id: demo.graph_id,
             position,
             label: name.to_string(),
+            metadata: serde_json::Value::Null,
         });
     }
 
@@ -193,9 +184,11 @@ fn create_workflow(
         
         create_edge.send(CreateEdgeVisual {
             edge_id: EdgeId::new(),
+            graph_id: demo.graph_id,
             source_node_id: node_ids[from],
             target_node_id: node_ids[to],
             relationship: EdgeRelationship::Custom(label.to_string()),
+            weight: None,
         });
     }
     
@@ -203,10 +196,10 @@ fn create_workflow(
     println!("\n=== Workflow Created Successfully ===\n");
 }
 
+/// Give the node visuals spawned by the plugin their workflow shape and state
 fn handle_node_creation(
     mut commands: Commands,
-    mut create_events: EventReader<CreateNodeVisual>,
-    mut node_map: ResMut<NodeEntityMap>,
+    mut create_events: EventReader<VisualNodeCreated>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     demo: Res<WorkflowDemo>,
@@ -240,23 +233,18 @@ fn handle_node_creation(
             ..default()
         });
 
-        let entity = commands
-            .spawn((
-                Mesh3d(mesh),
-                MeshMaterial3d(material),
-                NodeVisualBundle::new(event.node_id, demo.graph_id, event.position),
-                WorkflowNode {
-                    node_type,
-                    state,
-                    label: event.label.clone(),
-                },
-                NodeMaterial { state },
-            ))
-            .id();
-
-        node_map.map.insert(event.node_id, entity);
-        
-        println!("Created visual for node: {} at position {:?}", event.label, event.position);
+        let label = demo.node_labels.get(&event.node_id).cloned().unwrap_or_default();
+        println!("Created visual for node: {} at position {:?}", label, event.position);
+        commands.entity(event.entity).insert((
+            Mesh3d(mesh),
+            MeshMaterial3d(material),
+            WorkflowNode {
+                node_type,
+                state,
+                label,
+            },
+            NodeMaterial { state },
+        ));
     }
 }
 
@@ -270,8 +258,8 @@ fn handle_edge_creation(
 ) {
     for event in create_events.read() {
         if let (Some(&source_entity), Some(&target_entity)) = (
-            node_map.map.get(&event.source_node_id),
-            node_map.map.get(&event.target_node_id),
+            node_map.get(&event.source_node_id),
+            node_map.get(&event.target_node_id),
         ) {
             let label = match &event.relationship {
                 EdgeRelationship::Custom(s) => s.clone(),
@@ -290,6 +278,7 @@ fn handle_edge_creation(
                 MeshMaterial3d(material),
                 EdgeVisualBundle::new(event.edge_id, demo.graph_id, source_entity, target_entity),
                 EdgeLine { label: label.clone() },
+                EdgeLabel { text: label.clone() },
             ));
             
             println!("Created edge: {}", label);
@@ -5,6 +5,7 @@
 
 use bevy::prelude::*;
 use cim_contextgraph::NodeId;
+use cim_domain_bevy::NodeStatus;
 use std::collections::HashMap;
 
 fn main() {
@@ -34,7 +35,8 @@ struct WorkflowNode {
     id: NodeId,
     name: String,
     node_type: NodeType,
-    status: NodeStatus,
+    /// `None` while the step is pending
+    status: Option<NodeStatus>,
 }
 
 #[derive(Debug, Clone)]
@@ -52,13 +54,6 @@ enum NodeType {
     End,
 }
 
-#[derive(Debug, Clone, PartialEq)]
-enum NodeStatus {
-    Pending,
-    Active,
-    Completed,
-}
-
 fn setup_workflow(mut workflow: ResMut<WorkflowState>) {
     println!("📋 Setting up Document Approval Workflow\n");
 
@@ -77,56 +72,56 @@ fn setup_workflow(mut workflow: ResMut<WorkflowState>) {
         id: start_id,
         name: "Start".to_string(),
         node_type: NodeType::Start,
-        status: NodeStatus::Completed,
+        status: Some(NodeStatus::Completed),
     });
 
     workflow.nodes.insert(submit_id, WorkflowNode {
         id: submit_id,
         name: "Submit Document".to_string(),
         node_type: NodeType::Process,
-        status: NodeStatus::Active,
+        status: Some(NodeStatus::Active),
     });
 
     workflow.nodes.insert(review_id, WorkflowNode {
         id: review_id,
         name: "Review Document".to_string(),
         node_type: NodeType::Process,
-        status: NodeStatus::Pending,
+        status: None,
     });
 
     workflow.nodes.insert(decision_id, WorkflowNode {
         id: decision_id,
         name: "Decision".to_string(),
         node_type: NodeType::Decision,
-        status: NodeStatus::Pending,
+        status: None,
     });
 
     workflow.nodes.insert(revise_id, WorkflowNode {
         id: revise_id,
         name: "Revise Document".to_string(),
         node_type: NodeType::Process,
-        status: NodeStatus::Pending,
+        status: None,
     });
 
     workflow.nodes.insert(approve_id, WorkflowNode {
         id: approve_id,
         name: "Approve".to_string(),
         node_type: NodeType::Process,
-        status: NodeStatus::Pending,
+        status: None,
     });
 
     workflow.nodes.insert(reject_id, WorkflowNode {
         id: reject_id,
         name: "Reject".to_string(),
         node_type: NodeType::Process,
-        status: NodeStatus::Pending,
+        status: None,
     });
 
     workflow.nodes.insert(end_id, WorkflowNode {
         id: end_id,
         name: "End".to_string(),
         node_type: NodeType::End,
-        status: NodeStatus::Pending,
+        status: None,
     });
 
     // Add edges
@@ -189,7 +184,8 @@ fn setup_workflow(mut workflow: ResMut<WorkflowState>) {
     // Print initial workflow structure
     println!("Workflow Nodes:");
     for node in workflow.nodes.values() {
-        println!("  • {} ({:?}) - Status: {:?}", node.name, node.node_type, node.status);
+        let status = node.status.map_or("Pending".to_string(), |status| format!("{status:?}"));
+        println!("  • {} ({:?}) - Status: {}", node.name, node.node_type, status);
     }
 
     println!("\nWorkflow Edges:");
@@ -234,7 +230,7 @@ fn simulate_workflow_progress(
             for node in workflow.nodes.values() {
                 if node.name == step_name {
                     node_to_activate = Some(node.id);
-                } else if node.status == NodeStatus::Active {
+                } else if node.status == Some(NodeStatus::Active) {
                     nodes_to_complete.push(node.id);
                 }
             }
@@ -242,7 +238,7 @@ fn simulate_workflow_progress(
             // Activate the new node
             if let Some(node_id) = node_to_activate {
                 if let Some(node) = workflow.nodes.get_mut(&node_id) {
-                    node.status = NodeStatus::Active;
+                    node.status = Some(NodeStatus::Active);
                     println!("▶️  Processing: {} - {}", step_name, message);
                 }
             }
@@ -250,7 +246,7 @@ fn simulate_workflow_progress(
             // Complete the previous active nodes
             for node_id in nodes_to_complete {
                 if let Some(node) = workflow.nodes.get_mut(&node_id) {
-                    node.status = NodeStatus::Completed;
+                    node.status = Some(NodeStatus::Completed);
                     workflow.completed_steps.push(node_id);
                 }
             }
@@ -273,9 +269,9 @@ fn print_workflow_status(
     
     // Print status every 60 frames (approximately once per second)
     if *frame_count % 60 == 0 {
-        let active_count = workflow.nodes.values().filter(|n| n.status == NodeStatus::Active).count();
-        let completed_count = workflow.nodes.values().filter(|n| n.status == NodeStatus::Completed).count();
-        let pending_count = workflow.nodes.values().filter(|n| n.status == NodeStatus::Pending).count();
+        let active_count = workflow.nodes.values().filter(|n| n.status == Some(NodeStatus::Active)).count();
+        let completed_count = workflow.nodes.values().filter(|n| n.status == Some(NodeStatus::Completed)).count();
+        let pending_count = workflow.nodes.values().filter(|n| n.status.is_none()).count();
         
         println!("\n📊 Workflow Status:");
         println!("  Active: {}", active_count);
@@ -285,7 +281,7 @@ fn print_workflow_status(
         if active_count > 0 {
             println!("\n  Currently active steps:");
             for node in workflow.nodes.values() {
-                if node.status == NodeStatus::Active {
+                if node.status == Some(NodeStatus::Active) {
                     println!("    • {}", node.name);
                 }
             }
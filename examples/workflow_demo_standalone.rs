@@ -1,9 +1,11 @@
 //! Standalone Workflow Visualization Demo
 //!
 //! This demo shows the workflow visualization without using CimVizPlugin
-//! to isolate rendering issues.
+//! to isolate rendering issues. The only crate plugin it adds is
+//! EnvironmentPlugin, whose grid stands in for a ground plane.
 
 use bevy::prelude::*;
+use cim_domain_bevy::{EnvironmentConfig, EnvironmentPlugin};
 
 fn main() {
     App::new()
@@ -16,6 +18,13 @@ fn main() {
             ..default()
         }))
         .insert_resource(WorkflowState::default())
+        .add_plugins(EnvironmentPlugin)
+        // The shared grid stands in for a ground plane under the workflow
+        .insert_resource(EnvironmentConfig {
+            grid_cells: 30,
+            grid_isometry: Isometry3d::new(Vec3::new(0.0, -1.0, 0.0), Quat::from_rotation_x(std::f32::consts::FRAC_PI_2)),
+            ..default()
+        })
         .add_systems(Startup, setup_workflow)
         .add_systems(Update, (animate_workflow, handle_input))
         .run();
@@ -58,16 +67,6 @@ fn setup_workflow(
         affects_lightmapped_meshes: false,
     });
 
-    // Ground plane
-    commands.spawn((
-        Mesh3d(meshes.add(Plane3d::default().mesh().size(30.0, 30.0))),
-        MeshMaterial3d(materials.add(StandardMaterial {
-            base_color: Color::srgb(0.2, 0.2, 0.2),
-            ..default()
-        })),
-        Transform::from_xyz(0.0, -1.0, 0.0),
-    ));
-
     println!("Creating workflow nodes...");
 
     // Define workflow nodes - raised above the ground
@@ -3,6 +3,7 @@
 //! A proper workflow visualization with nodes, edges, and animations.
 
 use bevy::prelude::*;
+use cim_domain_bevy::{EnvironmentConfig, EnvironmentPlugin};
 
 fn main() {
     App::new()
@@ -14,6 +15,13 @@ fn main() {
             affects_lightmapped_meshes: false,
         })
         .insert_resource(WorkflowState::default())
+        .add_plugins(EnvironmentPlugin)
+        // The shared grid stands in for a ground plane under the workflow
+        .insert_resource(EnvironmentConfig {
+            grid_cells: 30,
+            grid_isometry: Isometry3d::new(Vec3::new(0.0, -2.0, 0.0), Quat::from_rotation_x(std::f32::consts::FRAC_PI_2)),
+            ..default()
+        })
         .add_systems(Startup, setup)
         .add_systems(Update, (
             rotate_nodes,
@@ -70,17 +78,6 @@ fn setup(
         Transform::from_rotation(Quat::from_euler(EulerRot::XYZ, -0.5, -0.5, 0.0)),
     ));
 
-    // Ground plane
-    commands.spawn((
-        Mesh3d(meshes.add(Plane3d::default().mesh().size(30.0, 30.0))),
-        MeshMaterial3d(materials.add(StandardMaterial {
-            base_color: Color::srgb(0.1, 0.1, 0.15),
-            perceptual_roughness: 0.9,
-            ..default()
-        })),
-        Transform::from_xyz(0.0, -2.0, 0.0),
-    ));
-
     println!("\n=== WORKFLOW VISUALIZATION ===");
     println!("Creating workflow nodes...");
 
@@ -4,6 +4,11 @@
 
 use bevy::prelude::*;
 use bevy::input::mouse::{MouseMotion, MouseWheel};
+use cim_contextgraph::{NodeId, ContextGraphId as GraphId};
+use cim_domain_bevy::{GraphCamera, NodeStatus, NodeVisual, StatusBadge, StatusBadgePlugin};
+
+/// Height above a node's center where its status badge is anchored
+const BADGE_ANCHOR_HEIGHT: f32 = 1.2;
 
 fn main() {
     App::new()
@@ -16,11 +21,13 @@ fn main() {
         })
         .insert_resource(WorkflowState::default())
         .insert_resource(CameraState::default())
+        .add_plugins(StatusBadgePlugin)
         .add_systems(Startup, (setup_scene, setup_ui))
         .add_systems(Update, (
             camera_controls,
             rotate_nodes,
             animate_workflow,
+            show_status_badges,
             update_ui,
             handle_input,
         ))
@@ -45,37 +52,37 @@ impl Default for WorkflowState {
                 NodeInfo {
                     name: "Start".to_string(),
                     description: "Workflow begins when a document is uploaded to the system".to_string(),
-                    status: NodeStatus::Pending,
+                    status: None,
                 },
                 NodeInfo {
                     name: "Submit".to_string(),
                     description: "Document is submitted for review with metadata and categorization".to_string(),
-                    status: NodeStatus::Pending,
+                    status: None,
                 },
                 NodeInfo {
                     name: "Review".to_string(),
                     description: "Automated and manual review processes check document compliance".to_string(),
-                    status: NodeStatus::Pending,
+                    status: None,
                 },
                 NodeInfo {
                     name: "Decision".to_string(),
                     description: "AI-powered decision engine evaluates document based on business rules".to_string(),
-                    status: NodeStatus::Pending,
+                    status: None,
                 },
                 NodeInfo {
                     name: "Approved".to_string(),
                     description: "Document approved! Notification sent and document archived".to_string(),
-                    status: NodeStatus::Pending,
+                    status: None,
                 },
                 NodeInfo {
                     name: "Rejected".to_string(),
                     description: "Document rejected. Reasons provided and sender notified".to_string(),
-                    status: NodeStatus::Pending,
+                    status: None,
                 },
                 NodeInfo {
                     name: "Revise".to_string(),
                     description: "Document sent back for revision with specific feedback".to_string(),
-                    status: NodeStatus::Pending,
+                    status: None,
                 },
             ],
         }
@@ -86,14 +93,8 @@ impl Default for WorkflowState {
 struct NodeInfo {
     name: String,
     description: String,
-    status: NodeStatus,
-}
-
-#[derive(Clone, Copy, PartialEq)]
-enum NodeStatus {
-    Pending,
-    Active,
-    Completed,
+    /// `None` while the step is pending
+    status: Option<NodeStatus>,
 }
 
 #[derive(Resource)]
@@ -125,6 +126,13 @@ struct WorkflowEdge;
 #[derive(Component)]
 struct MainCamera;
 
+/// Carries the status badge of the workflow step at `index`, on top of
+/// the step's node so the badge isn't hidden inside its mesh
+#[derive(Component)]
+struct StatusAnchor {
+    index: usize,
+}
+
 #[derive(Component)]
 struct StatusText;
 
@@ -144,6 +152,7 @@ fn setup_scene(
         Camera3d::default(),
         Transform::from_xyz(0.0, 10.0, 25.0).looking_at(Vec3::ZERO, Vec3::Y),
         MainCamera,
+        GraphCamera,
     ));
 
     // Add some directional light
@@ -179,6 +188,7 @@ fn setup_scene(
     ];
 
     // Create nodes
+    let graph_id = GraphId::new();
     for (i, (name, position, node_type)) in nodes.iter().enumerate() {
         let (mesh, color) = match node_type {
             NodeType::Start => (
@@ -217,7 +227,14 @@ fn setup_scene(
                 index: i,
                 name: name.to_string(),
             },
-        ));
+        )).with_children(|node| {
+            node.spawn((
+                StatusAnchor { index: i },
+                NodeVisual { node_id: NodeId::new(), graph_id },
+                Transform::from_translation(Vec3::Y * BADGE_ANCHOR_HEIGHT),
+                Visibility::default(),
+            ));
+        });
     }
 
     // Create edges
@@ -379,9 +396,10 @@ fn update_ui(
         let mut list = String::new();
         for (i, node) in workflow.nodes.iter().enumerate() {
             let status_icon = match node.status {
-                NodeStatus::Completed => "✅",
-                NodeStatus::Active => "▶️",
-                NodeStatus::Pending => "⏳",
+                Some(NodeStatus::Completed) => "✅",
+                Some(NodeStatus::Active) => "▶️",
+                Some(NodeStatus::Failed) => "❌",
+                None => "⏳",
             };
             
             let highlight = if i == workflow.current_step { ">>> " } else { "    " };
@@ -477,6 +495,23 @@ fn camera_controls(
     camera_transform.look_at(Vec3::ZERO, Vec3::Y);
 }
 
+/// Mirror each step's status into the badge above its node
+fn show_status_badges(
+    mut commands: Commands,
+    workflow: Res<WorkflowState>,
+    anchors: Query<(Entity, &StatusAnchor)>,
+) {
+    if !workflow.is_changed() {
+        return;
+    }
+    for (entity, anchor) in anchors.iter() {
+        match workflow.nodes.get(anchor.index).and_then(|node| node.status) {
+            Some(state) => { commands.entity(entity).insert(StatusBadge { state }); }
+            None => { commands.entity(entity).remove::<StatusBadge>(); }
+        }
+    }
+}
+
 fn rotate_nodes(
     time: Res<Time>,
     mut query: Query<&mut Transform, (With<WorkflowNode>, Without<MainCamera>)>,
@@ -502,7 +537,7 @@ fn animate_workflow(
         // Update node statuses
         let current = workflow.current_step;
         if current < workflow.nodes.len() {
-            workflow.nodes[current].status = NodeStatus::Completed;
+            workflow.nodes[current].status = Some(NodeStatus::Completed);
         }
 
         // Reset previous node color
@@ -520,7 +555,7 @@ fn animate_workflow(
         // Update new node status
         let new_current = workflow.current_step;
         if new_current < workflow.nodes.len() {
-            workflow.nodes[new_current].status = NodeStatus::Active;
+            workflow.nodes[new_current].status = Some(NodeStatus::Active);
         }
 
         // Highlight current node
@@ -535,9 +570,9 @@ fn animate_workflow(
         // Reset if completed
         if workflow.current_step == 0 {
             for node in workflow.nodes.iter_mut() {
-                node.status = NodeStatus::Pending;
+                node.status = None;
             }
-            workflow.nodes[0].status = NodeStatus::Active;
+            workflow.nodes[0].status = Some(NodeStatus::Active);
         }
     }
 }
@@ -554,7 +589,7 @@ fn handle_input(
         if workflow.is_running {
             let current = workflow.current_step;
             if current < workflow.nodes.len() {
-                workflow.nodes[current].status = NodeStatus::Active;
+                workflow.nodes[current].status = Some(NodeStatus::Active);
             }
         }
     }
@@ -565,7 +600,7 @@ fn handle_input(
         workflow.timer.reset();
 
         for node in workflow.nodes.iter_mut() {
-            node.status = NodeStatus::Pending;
+            node.status = None;
         }
 
         for (_, material_handle) in nodes.iter() {
@@ -3,11 +3,18 @@
 //! Shows the actual domain events being generated as the workflow progresses.
 
 use bevy::prelude::*;
+use cim_contextgraph::{NodeId, ContextGraphId as GraphId};
+use cim_domain_bevy::{
+    EnvironmentConfig, EnvironmentPlugin, GraphCamera, NodeStatus, NodeVisual, StatusBadge, StatusBadgePlugin,
+};
 use bevy::input::mouse::{MouseMotion, MouseWheel};
 use serde::{Serialize, Deserialize};
 use std::collections::VecDeque;
 use uuid::Uuid;
 
+/// Height above a node's center where its status badge is anchored
+const BADGE_ANCHOR_HEIGHT: f32 = 1.5;
+
 fn main() {
     App::new()
         .add_plugins(DefaultPlugins.set(ImagePlugin::default_nearest()))
@@ -19,12 +26,21 @@ fn main() {
         })
         .insert_resource(WorkflowState::default())
         .insert_resource(CameraState::default())
+        .add_plugins(StatusBadgePlugin)
         .insert_resource(EventStream::default())
+        .add_plugins(EnvironmentPlugin)
+        // The shared grid stands in for a ground plane under the workflow
+        .insert_resource(EnvironmentConfig {
+            grid_cells: 50,
+            grid_isometry: Isometry3d::new(Vec3::new(0.0, -2.0, 0.0), Quat::from_rotation_x(std::f32::consts::FRAC_PI_2)),
+            ..default()
+        })
         .add_systems(Startup, (setup_scene, setup_ui))
         .add_systems(Update, (
             camera_controls,
             rotate_nodes,
             animate_workflow,
+            show_status_badges,
             update_ui,
             handle_input,
         ))
@@ -130,37 +146,37 @@ impl Default for WorkflowState {
                 NodeInfo {
                     name: "Start".to_string(),
                     description: "Workflow initialization".to_string(),
-                    status: NodeStatus::Pending,
+                    status: None,
                 },
                 NodeInfo {
                     name: "Submit".to_string(),
                     description: "Document submission and validation".to_string(),
-                    status: NodeStatus::Pending,
+                    status: None,
                 },
                 NodeInfo {
                     name: "Review".to_string(),
                     description: "Automated compliance review".to_string(),
-                    status: NodeStatus::Pending,
+                    status: None,
                 },
                 NodeInfo {
                     name: "Decision".to_string(),
                     description: "AI-powered decision making".to_string(),
-                    status: NodeStatus::Pending,
+                    status: None,
                 },
                 NodeInfo {
                     name: "Approved".to_string(),
                     description: "Document approved and archived".to_string(),
-                    status: NodeStatus::Pending,
+                    status: None,
                 },
                 NodeInfo {
                     name: "Rejected".to_string(),
                     description: "Document rejected with reasons".to_string(),
-                    status: NodeStatus::Pending,
+                    status: None,
                 },
                 NodeInfo {
                     name: "Revise".to_string(),
                     description: "Revision requested with feedback".to_string(),
-                    status: NodeStatus::Pending,
+                    status: None,
                 },
             ],
         }
@@ -171,14 +187,8 @@ impl Default for WorkflowState {
 struct NodeInfo {
     name: String,
     description: String,
-    status: NodeStatus,
-}
-
-#[derive(Clone, Copy, PartialEq)]
-enum NodeStatus {
-    Pending,
-    Active,
-    Completed,
+    /// `None` while the step is pending
+    status: Option<NodeStatus>,
 }
 
 #[derive(Resource)]
@@ -210,6 +220,13 @@ struct WorkflowEdge;
 #[derive(Component)]
 struct MainCamera;
 
+/// Carries the status badge of the workflow step at `index`, on top of
+/// the step's node so the badge isn't hidden inside its mesh
+#[derive(Component)]
+struct StatusAnchor {
+    index: usize,
+}
+
 #[derive(Component)]
 struct EventListText;
 
@@ -229,6 +246,7 @@ fn setup_scene(
         Camera3d::default(),
         Transform::from_xyz(0.0, 12.0, 30.0).looking_at(Vec3::ZERO, Vec3::Y),
         MainCamera,
+        GraphCamera,
     ));
 
     // Lighting
@@ -241,17 +259,6 @@ fn setup_scene(
         Transform::from_rotation(Quat::from_euler(EulerRot::XYZ, -0.5, -0.5, 0.0)),
     ));
 
-    // Ground
-    commands.spawn((
-        Mesh3d(meshes.add(Plane3d::default().mesh().size(50.0, 50.0))),
-        MeshMaterial3d(materials.add(StandardMaterial {
-            base_color: Color::srgb(0.1, 0.1, 0.15),
-            perceptual_roughness: 0.9,
-            ..default()
-        })),
-        Transform::from_xyz(0.0, -2.0, 0.0),
-    ));
-
     // Workflow nodes
     let nodes = vec![
         ("Start", Vec3::new(-15.0, 0.0, 0.0), NodeType::Start),
@@ -264,6 +271,7 @@ fn setup_scene(
     ];
 
     // Create nodes
+    let graph_id = GraphId::new();
     for (i, (name, position, node_type)) in nodes.iter().enumerate() {
         let (mesh, color) = match node_type {
             NodeType::Start => (
@@ -302,7 +310,14 @@ fn setup_scene(
                 index: i,
                 name: name.to_string(),
             },
-        ));
+        )).with_children(|node| {
+            node.spawn((
+                StatusAnchor { index: i },
+                NodeVisual { node_id: NodeId::new(), graph_id },
+                Transform::from_translation(Vec3::Y * BADGE_ANCHOR_HEIGHT),
+                Visibility::default(),
+            ));
+        });
     }
 
     // Create edges
@@ -627,6 +642,23 @@ fn camera_controls(
     camera_transform.look_at(Vec3::ZERO, Vec3::Y);
 }
 
+/// Mirror each step's status into the badge above its node
+fn show_status_badges(
+    mut commands: Commands,
+    workflow: Res<WorkflowState>,
+    anchors: Query<(Entity, &StatusAnchor)>,
+) {
+    if !workflow.is_changed() {
+        return;
+    }
+    for (entity, anchor) in anchors.iter() {
+        match workflow.nodes.get(anchor.index).and_then(|node| node.status) {
+            Some(state) => { commands.entity(entity).insert(StatusBadge { state }); }
+            None => { commands.entity(entity).remove::<StatusBadge>(); }
+        }
+    }
+}
+
 fn rotate_nodes(
     time: Res<Time>,
     mut query: Query<&mut Transform, (With<WorkflowNode>, Without<MainCamera>)>,
@@ -715,7 +747,7 @@ fn animate_workflow(
         // Update node statuses
         let current = workflow.current_step;
         if current < workflow.nodes.len() {
-            workflow.nodes[current].status = NodeStatus::Completed;
+            workflow.nodes[current].status = Some(NodeStatus::Completed);
         }
 
         // Reset previous node color
@@ -740,7 +772,7 @@ fn animate_workflow(
         // Update new node status
         let new_current = workflow.current_step;
         if new_current < workflow.nodes.len() {
-            workflow.nodes[new_current].status = NodeStatus::Active;
+            workflow.nodes[new_current].status = Some(NodeStatus::Active);
         }
 
         // Highlight current node
@@ -757,9 +789,9 @@ fn animate_workflow(
             workflow.workflow_id = Uuid::new_v4();
             workflow.document_id = Uuid::new_v4();
             for node in workflow.nodes.iter_mut() {
-                node.status = NodeStatus::Pending;
+                node.status = None;
             }
-            workflow.nodes[0].status = NodeStatus::Active;
+            workflow.nodes[0].status = Some(NodeStatus::Active);
         }
     }
 }
@@ -777,7 +809,7 @@ fn handle_input(
         if workflow.is_running {
             let current = workflow.current_step;
             if current < workflow.nodes.len() {
-                workflow.nodes[current].status = NodeStatus::Active;
+                workflow.nodes[current].status = Some(NodeStatus::Active);
             }
         }
     }
@@ -791,7 +823,7 @@ fn handle_input(
         event_stream.events.clear();
 
         for node in workflow.nodes.iter_mut() {
-            node.status = NodeStatus::Pending;
+            node.status = None;
         }
 
         for (_, material_handle) in nodes.iter() {
@@ -3,11 +3,19 @@
 //! This demo shows a 3D visualization of a workflow with animated state changes.
 
 use bevy::prelude::*;
+use cim_domain_bevy::{EnvironmentConfig, EnvironmentPlugin};
 
 fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
         .insert_resource(WorkflowState::default())
+        .add_plugins(EnvironmentPlugin)
+        // The shared grid stands in for a ground plane under the workflow
+        .insert_resource(EnvironmentConfig {
+            grid_cells: 30,
+            grid_isometry: Isometry3d::new(Vec3::new(0.0, -1.0, 0.0), Quat::from_rotation_x(std::f32::consts::FRAC_PI_2)),
+            ..default()
+        })
         .add_systems(Startup, setup)
         .add_systems(Update, (animate_workflow, handle_input))
         .run();
@@ -58,16 +66,6 @@ fn setup(
         Transform::from_xyz(4.0, 8.0, 4.0),
     ));
 
-    // Ground plane
-    commands.spawn((
-        Mesh3d(meshes.add(Plane3d::default().mesh().size(30.0, 30.0))),
-        MeshMaterial3d(materials.add(StandardMaterial {
-            base_color: Color::srgb(0.2, 0.2, 0.2),
-            ..default()
-        })),
-        Transform::from_xyz(0.0, -1.0, 0.0),
-    ));
-
     println!("Creating workflow nodes...");
 
     // Workflow nodes
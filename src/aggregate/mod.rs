@@ -2,60 +2,67 @@
 //!
 //! In ECS, aggregates are entities with specific component compositions
 //! that enforce business rules and invariants.
+//!
+//! The node and edge aggregates are the visual bundles the rest of the crate
+//! works with, plus their style, so nodes created through the command
+//! handlers are picked, laid out and synced like any other.
 
-use crate::events::*;
-use crate::value_objects::*;
+use crate::components::{EdgeStyle, EdgeVisualBundle, GraphVisual, LayoutType, NodeStyle, NodeVisualBundle};
+use crate::value_objects::{CanvasState, EdgeCurve, NodeMetadata, NodeVisualStyle, RenderSettings, SourceNode, TargetNode, Viewport};
 use bevy::prelude::*;
+use cim_contextgraph::{NodeId, EdgeId, ContextGraphId as GraphId};
 
 /// Visual Node Aggregate - An entity that represents a visual node
 /// Components define the state, systems enforce the rules
 #[derive(Bundle)]
 pub struct VisualNodeAggregate {
-    pub node_id: NodeId,
-    pub position: Position,
-    pub visual: NodeVisual,
-    pub interaction_state: InteractionState,
+    pub node: NodeVisualBundle,
+    pub style: NodeStyle,
     pub metadata: NodeMetadata,
 }
 
 /// Visual Edge Aggregate - An entity that represents a visual edge
 #[derive(Bundle)]
 pub struct VisualEdgeAggregate {
-    pub edge_id: EdgeId,
+    pub edge: EdgeVisualBundle,
     pub source: SourceNode,
     pub target: TargetNode,
-    pub visual: EdgeVisual,
+    pub style: EdgeStyle,
     pub curve: EdgeCurve,
 }
 
 /// Graph Canvas Aggregate - The root aggregate for visual graph
 #[derive(Bundle)]
 pub struct GraphCanvasAggregate {
-    pub graph_id: GraphId,
+    pub graph: GraphVisual,
     pub canvas_state: CanvasState,
     pub viewport: Viewport,
     pub render_settings: RenderSettings,
 }
 
 impl VisualNodeAggregate {
-    pub fn new(node_id: NodeId, position: Position) -> Self {
+    pub fn new(node_id: NodeId, graph_id: GraphId, position: Vec3) -> Self {
         Self {
-            node_id,
-            position,
-            visual: NodeVisual::default(),
-            interaction_state: InteractionState::default(),
+            node: NodeVisualBundle::new(node_id, graph_id, position),
+            style: NodeStyle::default(),
             metadata: NodeMetadata::default(),
         }
     }
+
+    /// The node drawn in `style` instead of the default [`NodeStyle`]
+    pub fn with_style(mut self, style: NodeVisualStyle) -> Self {
+        self.style = style.into();
+        self
+    }
 }
 
 impl VisualEdgeAggregate {
-    pub fn new(edge_id: EdgeId, source: Entity, target: Entity) -> Self {
+    pub fn new(edge_id: EdgeId, graph_id: GraphId, source: Entity, target: Entity) -> Self {
         Self {
-            edge_id,
+            edge: EdgeVisualBundle::new(edge_id, graph_id, source, target),
             source: SourceNode(source),
             target: TargetNode(target),
-            visual: EdgeVisual::default(),
+            style: EdgeStyle::default(),
             curve: EdgeCurve::default(),
         }
     }
@@ -64,10 +71,43 @@ impl VisualEdgeAggregate {
 impl GraphCanvasAggregate {
     pub fn new(graph_id: GraphId) -> Self {
         Self {
-            graph_id,
+            graph: GraphVisual {
+                graph_id,
+                layout_type: LayoutType::ForceDirected,
+            },
             canvas_state: CanvasState::default(),
             viewport: Viewport::default(),
             render_settings: RenderSettings::default(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::NodeShape;
+    use crate::morphisms::{NodeEntityMap, NodeEntityMapPlugin};
+
+    #[test]
+    fn test_node_aggregate_is_a_mapped_node_visual() {
+        let mut app = App::new();
+        app.add_plugins(NodeEntityMapPlugin);
+
+        let node_id = NodeId::new();
+        let style = NodeVisualStyle {
+            color: Color::srgb(0.0, 1.0, 0.0),
+            size: 1.5,
+            shape: NodeShape::Hexagon,
+            border_color: Color::NONE,
+            border_width: 0.0,
+        };
+        let entity = app.world_mut()
+            .spawn(VisualNodeAggregate::new(node_id, GraphId::new(), Vec3::X).with_style(style.clone()))
+            .id();
+        app.update();
+
+        assert_eq!(app.world().resource::<NodeEntityMap>().get(&node_id), Some(&entity));
+        assert_eq!(app.world().get::<Transform>(entity).unwrap().translation, Vec3::X);
+        assert_eq!(NodeVisualStyle::from(app.world().get::<NodeStyle>(entity).unwrap().clone()), style);
+    }
+}
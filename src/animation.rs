@@ -0,0 +1,130 @@
+//! Animation: Interpolating entities and expiring temporary visuals
+//!
+//! [`AnimatedTransition`] moves an entity's [`Transform`] from its start to
+//! its target position over `duration` seconds, shaped by its
+//! [`Easing`](crate::easing::Easing). The component is removed
+//! and [`AnimationCompleted`] is sent once the entity arrives.
+//! [`TemporaryVisual`] entities are despawned when their lifetime runs out.
+
+use bevy::prelude::*;
+use crate::components::{AnimatedTransition, TemporaryVisual};
+
+/// Event: An entity's [`AnimatedTransition`] reached its target
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct AnimationCompleted {
+    pub entity: Entity,
+}
+
+/// Plugin that drives [`AnimatedTransition`] and [`TemporaryVisual`]
+pub struct AnimationPlugin;
+
+impl Plugin for AnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AnimationCompleted>()
+            .add_systems(Update, (animate_transitions, despawn_expired_visuals));
+    }
+}
+
+/// System that advances [`AnimatedTransition`]s along their easing and
+/// removes them when they complete
+pub fn animate_transitions(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Transform, &mut AnimatedTransition)>,
+    mut completed: EventWriter<AnimationCompleted>,
+) {
+    for (entity, mut transform, mut transition) in query.iter_mut() {
+        transition.progress = if transition.duration > 0.0 {
+            (transition.progress + time.delta_secs() / transition.duration).min(1.0)
+        } else {
+            1.0
+        };
+
+        let t = transition.easing.apply(transition.progress);
+        transform.translation = transition.start_position.lerp(transition.target_position, t);
+
+        if transition.progress >= 1.0 {
+            transform.translation = transition.target_position;
+            commands.entity(entity).remove::<AnimatedTransition>();
+            completed.write(AnimationCompleted { entity });
+        }
+    }
+}
+
+/// System that despawns [`TemporaryVisual`]s whose lifetime has finished
+pub fn despawn_expired_visuals(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut TemporaryVisual)>,
+) {
+    for (entity, mut visual) in query.iter_mut() {
+        if visual.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::easing::Easing;
+    use bevy::time::TimeUpdateStrategy;
+    use std::time::Duration;
+
+    #[test]
+    fn test_transition_ends_at_target() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(100)))
+            .add_plugins(AnimationPlugin);
+
+        let target = Vec3::new(4.0, -2.0, 1.0);
+        let entity = app.world_mut().spawn((
+            Transform::default(),
+            AnimatedTransition {
+                start_position: Vec3::ZERO,
+                target_position: target,
+                progress: 0.0,
+                duration: 0.25,
+                easing: Easing::default(),
+            },
+        )).id();
+
+        app.update();
+        app.update();
+        let halfway = app.world().get::<Transform>(entity).unwrap().translation;
+        assert!(halfway != Vec3::ZERO && halfway != target);
+        assert!(app.world().get::<AnimatedTransition>(entity).is_some());
+
+        let mut completed = Vec::new();
+        for _ in 0..3 {
+            app.update();
+            completed.extend(app.world().resource::<Events<AnimationCompleted>>()
+                .iter_current_update_events()
+                .cloned());
+        }
+        assert_eq!(app.world().get::<Transform>(entity).unwrap().translation, target);
+        assert!(app.world().get::<AnimatedTransition>(entity).is_none());
+        assert_eq!(completed, vec![AnimationCompleted { entity }]);
+    }
+
+    #[test]
+    fn test_temporary_visual_despawns_after_lifetime() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(100)))
+            .add_plugins(AnimationPlugin);
+
+        let entity = app.world_mut().spawn(TemporaryVisual {
+            lifetime: Timer::from_seconds(0.25, TimerMode::Once),
+        }).id();
+
+        // The first update has no delta
+        for _ in 0..3 {
+            app.update();
+        }
+        assert!(app.world().get_entity(entity).is_ok());
+        app.update();
+        assert!(app.world().get_entity(entity).is_err());
+    }
+}
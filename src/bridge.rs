@@ -1,16 +1,60 @@
 //! Bridge between domain events and Bevy ECS
 
 use bevy::prelude::*;
-use crate::events::VisualizationCommand;
+use crate::events::{RequestDomainCommand, VisualizationCommand};
 use crossbeam_channel::{Receiver, Sender, bounded};
 
 /// Error types for bridge operations
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, thiserror::Error)]
 pub enum BridgeError {
     /// Channel is disconnected
+    #[error("channel disconnected")]
     ChannelDisconnected,
     /// Channel is full
+    #[error("channel full")]
     ChannelFull,
+    /// The event source could not be subscribed to
+    #[error("subscription failed: {0}")]
+    Subscription(String),
+}
+
+/// Connection state of an async event source feeding the app, as last
+/// reported through [`BridgeStatusChanged`]
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub enum BridgeStatus {
+    Connected,
+    Disconnected,
+    Error(String),
+}
+
+impl From<BridgeError> for BridgeStatus {
+    fn from(error: BridgeError) -> Self {
+        match error {
+            BridgeError::ChannelDisconnected => Self::Disconnected,
+            error => Self::Error(error.to_string()),
+        }
+    }
+}
+
+/// Event: The bridge's connection state changed
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct BridgeStatusChanged {
+    pub status: BridgeStatus,
+}
+
+/// Make `status` the current [`BridgeStatus`], sending a
+/// [`BridgeStatusChanged`] if it differs. Returns whether it did.
+pub fn report_bridge_status(
+    current: &mut ResMut<BridgeStatus>,
+    status: BridgeStatus,
+    changed: &mut EventWriter<BridgeStatusChanged>,
+) -> bool {
+    if **current == status {
+        return false;
+    }
+    **current = status.clone();
+    changed.write(BridgeStatusChanged { status });
+    true
 }
 
 /// Bridge between async domain layer and sync Bevy ECS
@@ -83,12 +127,13 @@ pub fn process_domain_events(
     }
 }
 
-/// System that sends visualization commands to the domain
+/// System that sends requested commands to the domain. Commands received
+/// from the domain are not sent back.
 pub fn send_visualization_commands(
-    mut viz_commands: EventReader<VisualizationCommand>,
+    mut requests: EventReader<RequestDomainCommand>,
     bridge: Res<AsyncSyncBridge>,
 ) {
-    for cmd in viz_commands.read() {
+    for RequestDomainCommand(cmd) in requests.read() {
         println!("🌉 Bridge: Sending visualization command: {cmd:?}");
         if let Err(e) = bridge.send_command(cmd.clone()) {
             eprintln!("Failed to send visualization command: {e:?}");
@@ -99,7 +144,7 @@ pub fn send_visualization_commands(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use cim_contextgraph::NodeId;
+    use cim_contextgraph::{ContextGraphId as GraphId, NodeId};
 
     #[test]
     fn test_bridge_creation() {
@@ -108,8 +153,10 @@ mod tests {
         // Test sending visualization command
         let event = VisualizationCommand::CreateNode(crate::events::CreateNodeVisual {
             node_id: NodeId::new(),
+            graph_id: GraphId::new(),
             position: Vec3::ZERO,
             label: "Test".to_string(),
+            metadata: serde_json::Value::Null,
         });
 
         bridge.domain_sender().send(event).unwrap();
@@ -118,4 +165,24 @@ mod tests {
         let received = bridge.receive_domain_events();
         assert_eq!(received.len(), 1);
     }
+
+    #[test]
+    fn test_domain_commands_are_not_sent_back() {
+        let mut app = App::new();
+        app.insert_resource(AsyncSyncBridge::new(16))
+            .add_event::<VisualizationCommand>()
+            .add_event::<RequestDomainCommand>()
+            .add_systems(Update, (process_domain_events, send_visualization_commands).chain());
+        let remove = |node_id| VisualizationCommand::RemoveNode(crate::events::RemoveNodeVisual { node_id });
+
+        let bridge = app.world().resource::<AsyncSyncBridge>();
+        bridge.domain_sender().send(remove(NodeId::new())).unwrap();
+        app.update();
+        assert_eq!(app.world().resource::<Events<VisualizationCommand>>().len(), 1);
+        assert!(app.world().resource::<AsyncSyncBridge>().command_receiver().is_empty());
+
+        app.world_mut().send_event(RequestDomainCommand(remove(NodeId::new())));
+        app.update();
+        assert_eq!(app.world().resource::<AsyncSyncBridge>().command_receiver().len(), 1);
+    }
 }
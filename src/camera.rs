@@ -0,0 +1,311 @@
+//! Camera Animation: Smooth transitions for the graph camera
+//!
+//! Consumes [`FocusCamera`] and [`ResetCamera`] and animates every
+//! [`GraphCamera`] towards the requested view. The in-flight transition is
+//! stored as a [`CameraAnimation`] component on the camera, and the view to
+//! return to on reset as [`CameraHome`]. New transitions are eased with the
+//! [`CameraEasing`] resource.
+
+use bevy::prelude::*;
+use crate::components::GraphCamera;
+use crate::easing::Easing;
+use crate::events::{FocusCamera, ResetCamera};
+
+/// Padding added around the focused entities' bounding sphere
+const FOCUS_PADDING: f32 = 1.0;
+
+/// Distance along the view direction used when the look-at target can't be
+/// inferred from the camera transform
+const DEFAULT_LOOK_DISTANCE: f32 = 10.0;
+
+/// The view a camera returns to on `ResetCamera`
+#[derive(Component, Debug, Clone, Copy)]
+pub struct CameraHome {
+    pub position: Vec3,
+    pub target: Vec3,
+}
+
+/// Easing of camera transitions started from now on
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CameraEasing(pub Easing);
+
+/// An in-progress camera transition
+#[derive(Component, Debug, Clone)]
+pub struct CameraAnimation {
+    pub from_position: Vec3,
+    pub from_target: Vec3,
+    pub to_position: Vec3,
+    pub to_target: Vec3,
+    pub elapsed: f32,
+    pub duration: f32,
+    pub easing: Easing,
+}
+
+impl CameraAnimation {
+    /// Eased position and look-at target at the current elapsed time
+    pub fn sample(&self) -> (Vec3, Vec3) {
+        let t = if self.duration > 0.0 {
+            self.easing.apply(self.elapsed / self.duration)
+        } else {
+            1.0
+        };
+        (
+            self.from_position.lerp(self.to_position, t),
+            self.from_target.lerp(self.to_target, t),
+        )
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+}
+
+/// Plugin that animates the graph camera on focus and reset commands
+pub struct CameraAnimationPlugin;
+
+impl Plugin for CameraAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<FocusCamera>()
+            .add_event::<ResetCamera>()
+            .init_resource::<CameraEasing>()
+            .add_systems(
+                Update,
+                (
+                    capture_camera_home,
+                    handle_focus_camera,
+                    handle_reset_camera,
+                    animate_camera,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Bounding sphere (center, radius) around a set of points
+pub fn bounding_sphere(points: &[Vec3]) -> Option<(Vec3, f32)> {
+    let first = *points.first()?;
+    let (min, max) = points.iter().fold((first, first), |(min, max), point| {
+        (min.min(*point), max.max(*point))
+    });
+    let center = (min + max) * 0.5;
+    let radius = points.iter()
+        .map(|point| point.distance(center))
+        .fold(0.0, f32::max);
+    Some((center, radius))
+}
+
+/// Best guess of what a camera is looking at: where its view ray meets the
+/// ground plane, or a fixed distance ahead if it never does
+fn look_target(transform: &Transform) -> Vec3 {
+    let forward = transform.forward().as_vec3();
+    if forward.y < -f32::EPSILON {
+        let distance = -transform.translation.y / forward.y;
+        if distance > 0.0 {
+            return transform.translation + forward * distance;
+        }
+    }
+    transform.translation + forward * DEFAULT_LOOK_DISTANCE
+}
+
+/// Current look-at target, following an in-flight animation if there is one
+fn current_target(transform: &Transform, animation: Option<&CameraAnimation>) -> Vec3 {
+    animation
+        .map(|animation| animation.sample().1)
+        .unwrap_or_else(|| look_target(transform))
+}
+
+/// Remember the starting view of each graph camera
+fn capture_camera_home(
+    mut commands: Commands,
+    cameras: Query<(Entity, &Transform), (With<GraphCamera>, Without<CameraHome>)>,
+) {
+    for (entity, transform) in cameras.iter() {
+        commands.entity(entity).insert(CameraHome {
+            position: transform.translation,
+            target: look_target(transform),
+        });
+    }
+}
+
+/// Frame the bounding sphere of the focused entities, or recenter on the
+/// focused point
+fn handle_focus_camera(
+    mut commands: Commands,
+    mut events: EventReader<FocusCamera>,
+    easing: Res<CameraEasing>,
+    targets: Query<&GlobalTransform>,
+    cameras: Query<(Entity, &Transform, Option<&Projection>, Option<&CameraAnimation>), With<GraphCamera>>,
+) {
+    for event in events.read() {
+        if let Some(point) = event.target_point {
+            for (entity, transform, _, animation) in cameras.iter() {
+                let (from_position, from_target) = (transform.translation, current_target(transform, animation));
+                commands.entity(entity).insert(CameraAnimation {
+                    from_position,
+                    from_target,
+                    to_position: point + (from_position - from_target),
+                    to_target: point,
+                    elapsed: 0.0,
+                    duration: event.transition_duration,
+                    easing: easing.0,
+                });
+            }
+            continue;
+        }
+
+        let points: Vec<Vec3> = event.target_entities.iter()
+            .filter_map(|entity| targets.get(*entity).ok())
+            .map(|transform| transform.translation())
+            .collect();
+        let Some((center, radius)) = bounding_sphere(&points) else {
+            continue;
+        };
+
+        for (entity, transform, projection, animation) in cameras.iter() {
+            let fov = match projection {
+                Some(Projection::Perspective(perspective)) => perspective.fov,
+                _ => std::f32::consts::FRAC_PI_4,
+            };
+            let distance = (radius + FOCUS_PADDING) / (fov * 0.5).sin();
+            let (from_position, from_target) = (transform.translation, current_target(transform, animation));
+            let direction = (from_position - from_target).try_normalize().unwrap_or(Vec3::Z);
+
+            commands.entity(entity).insert(CameraAnimation {
+                from_position,
+                from_target,
+                to_position: center + direction * distance,
+                to_target: center,
+                elapsed: 0.0,
+                duration: event.transition_duration,
+                easing: easing.0,
+            });
+        }
+    }
+}
+
+/// Animate back to each camera's stored home view
+fn handle_reset_camera(
+    mut commands: Commands,
+    mut events: EventReader<ResetCamera>,
+    easing: Res<CameraEasing>,
+    cameras: Query<(Entity, &Transform, &CameraHome, Option<&CameraAnimation>), With<GraphCamera>>,
+) {
+    for event in events.read() {
+        for (entity, transform, home, animation) in cameras.iter() {
+            commands.entity(entity).insert(CameraAnimation {
+                from_position: transform.translation,
+                from_target: current_target(transform, animation),
+                to_position: home.position,
+                to_target: home.target,
+                elapsed: 0.0,
+                duration: event.transition_duration,
+                easing: easing.0,
+            });
+        }
+    }
+}
+
+/// Advance camera animations and drop them once they complete
+fn animate_camera(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut cameras: Query<(Entity, &mut Transform, &mut CameraAnimation), With<GraphCamera>>,
+) {
+    for (entity, mut transform, mut animation) in cameras.iter_mut() {
+        animation.elapsed += time.delta_secs();
+        let (position, target) = animation.sample();
+        transform.translation = position;
+        if position != target {
+            transform.look_at(target, Vec3::Y);
+        }
+
+        if animation.is_finished() {
+            commands.entity(entity).remove::<CameraAnimation>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounding_sphere_contains_points() {
+        let points = [Vec3::new(-2.0, 0.0, 0.0), Vec3::new(4.0, 0.0, 0.0), Vec3::new(1.0, 3.0, 0.0)];
+        let (center, radius) = bounding_sphere(&points).unwrap();
+
+        assert_eq!(center, Vec3::new(1.0, 1.5, 0.0));
+        assert!(points.iter().all(|point| point.distance(center) <= radius + 1e-5));
+        assert!(bounding_sphere(&[]).is_none());
+    }
+
+    #[test]
+    fn test_animation_reaches_destination() {
+        let mut animation = CameraAnimation {
+            from_position: Vec3::ZERO,
+            from_target: Vec3::NEG_Z,
+            to_position: Vec3::new(10.0, 0.0, 0.0),
+            to_target: Vec3::new(10.0, 0.0, -1.0),
+            elapsed: 0.0,
+            duration: 0.5,
+            easing: Easing::default(),
+        };
+        assert_eq!(animation.sample().0, Vec3::ZERO);
+
+        animation.elapsed = 0.6;
+        assert!(animation.is_finished());
+        assert_eq!(animation.sample(), (animation.to_position, animation.to_target));
+    }
+
+    #[test]
+    fn test_focus_and_reset_animate_graph_camera() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugins(CameraAnimationPlugin);
+
+        let home = Transform::from_xyz(0.0, 10.0, 10.0).looking_at(Vec3::ZERO, Vec3::Y);
+        let camera = app.world_mut().spawn((home, GraphCamera)).id();
+        let node = app.world_mut()
+            .spawn(GlobalTransform::from_translation(Vec3::new(20.0, 0.0, 0.0)))
+            .id();
+        app.update();
+
+        app.world_mut().send_event(FocusCamera {
+            target_entities: vec![node],
+            target_point: None,
+            transition_duration: 0.0,
+        });
+        app.update();
+        let focused = *app.world().get::<Transform>(camera).unwrap();
+        assert!(focused.translation.distance(Vec3::new(20.0, 0.0, 0.0)) > 0.0);
+        assert!(focused.forward().dot((Vec3::new(20.0, 0.0, 0.0) - focused.translation).normalize()) > 0.999);
+
+        app.world_mut().send_event(ResetCamera { transition_duration: 0.0 });
+        app.update();
+        let reset = app.world().get::<Transform>(camera).unwrap();
+        assert!(reset.translation.distance(home.translation) < 1e-4);
+        assert!(app.world().get::<CameraAnimation>(camera).is_none());
+    }
+
+    #[test]
+    fn test_focus_on_point_keeps_camera_offset() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugins(CameraAnimationPlugin);
+
+        let start = Transform::from_xyz(0.0, 10.0, 10.0).looking_at(Vec3::ZERO, Vec3::Y);
+        let camera = app.world_mut().spawn((start, GraphCamera)).id();
+        app.update();
+
+        let point = Vec3::new(5.0, 0.0, -3.0);
+        app.world_mut().send_event(FocusCamera {
+            target_entities: Vec::new(),
+            target_point: Some(point),
+            transition_duration: 0.0,
+        });
+        app.update();
+        let focused = app.world().get::<Transform>(camera).unwrap();
+        assert!(focused.translation.distance(start.translation + point) < 1e-4);
+        assert!(focused.forward().dot(start.forward().as_vec3()) > 0.999);
+    }
+}
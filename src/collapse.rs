@@ -0,0 +1,383 @@
+//! Collapsing: Folding a subgraph into a single meta-node
+//!
+//! [`CollapseSelected`] replaces the selected nodes with one meta-node at
+//! their centroid. The members and the edges between them are hidden with
+//! [`CollapsedMember`], which also keeps them out of the layouts and picking,
+//! and every edge from a member to the rest of the graph is rerouted to the
+//! meta-node. The meta-node carries a [`Collapsed`] naming what it stands
+//! for, while the members' positions and the rerouted edges' original ends
+//! are kept in [`CollapsedSubgraphs`].
+//!
+//! [`ExpandNode`] on a meta-node undoes all of that: members return to their
+//! saved positions, rerouted edges to their original ends, and the
+//! meta-node is despawned. A meta-node despawned any other way gives its
+//! members and edges back the same way. Meta-nodes can be collapsed into
+//! further meta-nodes and are expanded one level at a time.
+//!
+//! Collapsing and expanding are reported with [`SubgraphCollapsed`] and
+//! [`SubgraphExpanded`] rather than as nodes created and deleted, so undo
+//! history and the domain don't take the meta-node for a graph edit.
+
+use bevy::prelude::*;
+use cim_contextgraph::{EdgeId, NodeId};
+use std::collections::HashMap;
+use crate::components::{EdgeVisual, NodeShape, NodeStyle, NodeVisual, NodeVisualBundle, Selected};
+use crate::culling::{CollapsedMember, CullingPlugin};
+use crate::events::NodeMetadataChanged;
+use crate::morphisms::{NodeEntityMap, NodeEntityMapPlugin};
+use crate::value_objects::NodeMetadata;
+
+/// Size of a meta-node, next to the default node size of 1
+const META_NODE_SIZE: f32 = 1.5;
+
+/// Command: Fold the selected nodes into one meta-node
+#[derive(Event, Debug, Clone, Default)]
+pub struct CollapseSelected;
+
+/// Command: Unfold a meta-node into the nodes it stands for
+#[derive(Event, Debug, Clone)]
+pub struct ExpandNode {
+    pub node_id: NodeId,
+}
+
+/// Event: Nodes were folded into the meta-node `node_id`
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct SubgraphCollapsed {
+    pub entity: Entity,
+    pub node_id: NodeId,
+    pub members: Vec<NodeId>,
+}
+
+/// Event: The meta-node `node_id` was expanded or despawned, and its members
+/// are shown again
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct SubgraphExpanded {
+    pub node_id: NodeId,
+    pub members: Vec<NodeId>,
+}
+
+/// The nodes and internal edges a meta-node stands for
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct Collapsed {
+    pub members: Vec<NodeId>,
+    pub internal_edges: Vec<EdgeId>,
+}
+
+/// What collapsing changed, to be put back on expanding
+#[derive(Debug, Clone)]
+struct SavedSubgraph {
+    /// The meta-node's id and its members'
+    node_id: NodeId,
+    members: Vec<NodeId>,
+    /// Member entities and where they were
+    positions: Vec<(Entity, Vec3)>,
+    /// Internal edge entities
+    internal_edges: Vec<Entity>,
+    /// Rerouted edge entities with their original source and target
+    rerouted: Vec<(Entity, Entity, Entity)>,
+}
+
+/// Saved state of every collapsed meta-node
+#[derive(Resource, Debug, Default)]
+pub struct CollapsedSubgraphs {
+    subgraphs: HashMap<Entity, SavedSubgraph>,
+}
+
+impl CollapsedSubgraphs {
+    pub fn len(&self) -> usize {
+        self.subgraphs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.subgraphs.is_empty()
+    }
+}
+
+/// Plugin for collapsing subgraphs into meta-nodes and expanding them
+pub struct CollapsePlugin;
+
+impl Plugin for CollapsePlugin {
+    fn build(&self, app: &mut App) {
+        // Meta-nodes are found by their node id on expanding
+        if !app.is_plugin_added::<NodeEntityMapPlugin>() {
+            app.add_plugins(NodeEntityMapPlugin);
+        }
+        // Members are hidden through their CollapsedMember marker
+        if !app.is_plugin_added::<CullingPlugin>() {
+            app.add_plugins(CullingPlugin);
+        }
+
+        app.init_resource::<CollapsedSubgraphs>()
+            .add_event::<CollapseSelected>()
+            .add_event::<ExpandNode>()
+            .add_event::<SubgraphCollapsed>()
+            .add_event::<SubgraphExpanded>()
+            .add_event::<NodeMetadataChanged>()
+            .add_systems(Update, (collapse_selected, expand_nodes, restore_despawned_meta_nodes).chain());
+    }
+}
+
+/// Selected nodes that aren't already folded into a meta-node
+type SelectedNode = (With<Selected>, Without<CollapsedMember>);
+
+/// System that folds the selected nodes into a meta-node. Fewer than two
+/// selected nodes are left alone, as are selected nodes of other graphs
+/// than the first one's.
+pub fn collapse_selected(
+    mut commands: Commands,
+    mut events: EventReader<CollapseSelected>,
+    mut subgraphs: ResMut<CollapsedSubgraphs>,
+    selected: Query<(Entity, &NodeVisual, &Transform), SelectedNode>,
+    mut edges: Query<(Entity, &mut EdgeVisual), Without<CollapsedMember>>,
+    mut collapsed: EventWriter<SubgraphCollapsed>,
+    mut metadata_changed: EventWriter<NodeMetadataChanged>,
+) {
+    if events.read().count() == 0 {
+        return;
+    }
+    let Some(graph_id) = selected.iter().map(|(_, node, _)| node.graph_id).next() else {
+        return;
+    };
+    let mut members: Vec<(Entity, NodeId, Vec3)> = selected.iter()
+        .filter(|(_, node, _)| node.graph_id == graph_id)
+        .map(|(entity, node, transform)| (entity, node.node_id, transform.translation))
+        .collect();
+    if members.len() < 2 {
+        return;
+    }
+    members.sort_by_key(|(entity, ..)| *entity);
+
+    let centroid = members.iter().map(|(.., position)| *position).sum::<Vec3>() / members.len() as f32;
+    let node_id = NodeId::new();
+    let metadata = NodeMetadata {
+        label: format!("{} nodes", members.len()),
+        ..default()
+    };
+    let meta_node = commands.spawn((
+        NodeVisualBundle::new(node_id, graph_id, centroid),
+        NodeStyle { shape: NodeShape::Hexagon, size: META_NODE_SIZE, ..default() },
+        metadata.clone(),
+    )).id();
+
+    let is_member = |entity: Entity| members.iter().any(|(member, ..)| *member == entity);
+    let member_ids: Vec<NodeId> = members.iter().map(|(_, node_id, _)| *node_id).collect();
+    let mut saved = SavedSubgraph {
+        node_id,
+        members: member_ids.clone(),
+        positions: members.iter().map(|(entity, _, position)| (*entity, *position)).collect(),
+        internal_edges: Vec::new(),
+        rerouted: Vec::new(),
+    };
+    let mut internal_edges = Vec::new();
+    for (entity, mut edge) in edges.iter_mut() {
+        match (is_member(edge.source_entity), is_member(edge.target_entity)) {
+            (true, true) => {
+                commands.entity(entity).insert(CollapsedMember { meta_node });
+                saved.internal_edges.push(entity);
+                internal_edges.push(edge.edge_id);
+            }
+            (false, false) => {}
+            (source_inside, _) => {
+                saved.rerouted.push((entity, edge.source_entity, edge.target_entity));
+                if source_inside {
+                    edge.source_entity = meta_node;
+                } else {
+                    edge.target_entity = meta_node;
+                }
+            }
+        }
+    }
+    for (entity, ..) in &members {
+        commands.entity(*entity)
+            .insert(CollapsedMember { meta_node })
+            .remove::<Selected>();
+    }
+
+    commands.entity(meta_node).insert(Collapsed {
+        members: member_ids.clone(),
+        internal_edges,
+    });
+    subgraphs.subgraphs.insert(meta_node, saved);
+    collapsed.write(SubgraphCollapsed { entity: meta_node, node_id, members: member_ids });
+    metadata_changed.write(NodeMetadataChanged { entity: meta_node, node_id, metadata });
+}
+
+/// Show a meta-node's members and internal edges again where they were
+/// collapsed from, and put rerouted edges back on their original ends
+fn restore_subgraph(
+    commands: &mut Commands,
+    saved: SavedSubgraph,
+    transforms: &mut Query<&mut Transform>,
+    edges: &mut Query<&mut EdgeVisual>,
+) -> SubgraphExpanded {
+    for (entity, position) in saved.positions {
+        if let Ok(mut transform) = transforms.get_mut(entity) {
+            transform.translation = position;
+        }
+        if let Ok(mut entity) = commands.get_entity(entity) {
+            entity.remove::<CollapsedMember>();
+        }
+    }
+    for entity in saved.internal_edges {
+        if let Ok(mut entity) = commands.get_entity(entity) {
+            entity.remove::<CollapsedMember>();
+        }
+    }
+    for (entity, source, target) in saved.rerouted {
+        if let Ok(mut edge) = edges.get_mut(entity) {
+            edge.source_entity = source;
+            edge.target_entity = target;
+        }
+    }
+    SubgraphExpanded { node_id: saved.node_id, members: saved.members }
+}
+
+/// System that unfolds meta-nodes, putting their members back where they
+/// were collapsed from
+pub fn expand_nodes(
+    mut commands: Commands,
+    mut events: EventReader<ExpandNode>,
+    mut subgraphs: ResMut<CollapsedSubgraphs>,
+    node_map: Res<NodeEntityMap>,
+    mut transforms: Query<&mut Transform>,
+    mut edges: Query<&mut EdgeVisual>,
+    mut expanded: EventWriter<SubgraphExpanded>,
+) {
+    for event in events.read() {
+        let Some(&meta_node) = node_map.get(&event.node_id) else {
+            continue;
+        };
+        let Some(saved) = subgraphs.subgraphs.remove(&meta_node) else {
+            continue;
+        };
+
+        expanded.write(restore_subgraph(&mut commands, saved, &mut transforms, &mut edges));
+        commands.entity(meta_node).despawn();
+    }
+}
+
+/// System that gives back the members of meta-nodes despawned, or stripped
+/// of [`Collapsed`], without being expanded
+pub fn restore_despawned_meta_nodes(
+    mut commands: Commands,
+    mut removed: RemovedComponents<Collapsed>,
+    mut subgraphs: ResMut<CollapsedSubgraphs>,
+    mut transforms: Query<&mut Transform>,
+    mut edges: Query<&mut EdgeVisual>,
+    mut expanded: EventWriter<SubgraphExpanded>,
+) {
+    for meta_node in removed.read() {
+        if let Some(saved) = subgraphs.subgraphs.remove(&meta_node) {
+            expanded.write(restore_subgraph(&mut commands, saved, &mut transforms, &mut edges));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cim_contextgraph::ContextGraphId as GraphId;
+
+    #[test]
+    fn test_collapse_then_expand_restores_the_graph() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, CollapsePlugin));
+
+        let graph_id = GraphId::new();
+        let positions = [Vec3::ZERO, Vec3::X * 2.0, Vec3::Y * 4.0, Vec3::new(6.0, 6.0, 0.0)];
+        let nodes: Vec<Entity> = positions.iter()
+            .map(|position| app.world_mut().spawn(NodeVisualBundle::new(NodeId::new(), graph_id, *position)).id())
+            .collect();
+        let mut spawn_edge = |source: usize, target: usize| {
+            app.world_mut().spawn(EdgeVisual {
+                edge_id: EdgeId::new(),
+                graph_id,
+                source_entity: nodes[source],
+                target_entity: nodes[target],
+                weight: 1.0,
+            }).id()
+        };
+        let internal = spawn_edge(0, 1);
+        let external = spawn_edge(2, 1);
+        spawn_edge(2, 3);
+        app.world_mut().entity_mut(nodes[0]).insert(Selected);
+        app.world_mut().entity_mut(nodes[1]).insert(Selected);
+
+        let counts = |app: &mut App| {
+            let world = app.world_mut();
+            let nodes = world.query_filtered::<(), (With<NodeVisual>, Without<CollapsedMember>)>().iter(world).count();
+            let edges = world.query_filtered::<(), (With<EdgeVisual>, Without<CollapsedMember>)>().iter(world).count();
+            (nodes, edges)
+        };
+        assert_eq!(counts(&mut app), (4, 3));
+
+        app.world_mut().send_event(CollapseSelected);
+        app.update();
+        assert_eq!(counts(&mut app), (3, 2));
+        let (meta_node, collapsed) = app.world_mut().query::<(Entity, &Collapsed)>().single(app.world()).unwrap();
+        assert_eq!(collapsed.members.len(), 2);
+        assert_eq!(collapsed.internal_edges, vec![app.world().get::<EdgeVisual>(internal).unwrap().edge_id]);
+        assert_eq!(app.world().get::<EdgeVisual>(external).unwrap().target_entity, meta_node);
+        assert_eq!(app.world().get::<Transform>(meta_node).unwrap().translation, Vec3::X);
+        // The meta-node is mapped at the start of the next frame
+        app.update();
+
+        // Members moved while hidden come back where they were collapsed
+        app.world_mut().get_mut::<Transform>(nodes[0]).unwrap().translation = Vec3::splat(100.0);
+        let node_id = app.world().get::<NodeVisual>(meta_node).unwrap().node_id;
+        app.world_mut().send_event(ExpandNode { node_id });
+        app.update();
+        assert_eq!(counts(&mut app), (4, 3));
+        assert!(app.world().get_entity(meta_node).is_err());
+        assert_eq!(app.world().get::<EdgeVisual>(external).unwrap().target_entity, nodes[1]);
+        assert_eq!(app.world().get::<Transform>(nodes[0]).unwrap().translation, Vec3::ZERO);
+        assert!(app.world().resource::<CollapsedSubgraphs>().is_empty());
+    }
+
+    #[test]
+    fn test_despawned_meta_node_gives_back_its_members() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, CollapsePlugin));
+
+        let graph_id = GraphId::new();
+        let nodes: Vec<Entity> = [Vec3::ZERO, Vec3::X * 2.0, Vec3::Y * 4.0].iter()
+            .map(|position| app.world_mut().spawn(NodeVisualBundle::new(NodeId::new(), graph_id, *position)).id())
+            .collect();
+        let internal = app.world_mut().spawn(EdgeVisual {
+            edge_id: EdgeId::new(),
+            graph_id,
+            source_entity: nodes[0],
+            target_entity: nodes[1],
+            weight: 1.0,
+        }).id();
+        let external = app.world_mut().spawn(EdgeVisual {
+            edge_id: EdgeId::new(),
+            graph_id,
+            source_entity: nodes[2],
+            target_entity: nodes[1],
+            weight: 1.0,
+        }).id();
+        app.world_mut().entity_mut(nodes[0]).insert(Selected);
+        app.world_mut().entity_mut(nodes[1]).insert(Selected);
+
+        app.world_mut().send_event(CollapseSelected);
+        app.update();
+        // Collapsing is not reported as a node created, so undo skips it
+        let created = app.world().get_resource::<Events<crate::events::VisualNodeCreated>>();
+        assert!(created.is_none_or(|events| events.is_empty()));
+        let collapsed = app.world().resource::<Events<SubgraphCollapsed>>();
+        assert_eq!(collapsed.iter_current_update_events().next().unwrap().members.len(), 2);
+
+        let (meta_node, _) = app.world_mut().query::<(Entity, &Collapsed)>().single(app.world()).unwrap();
+        app.world_mut().despawn(meta_node);
+        app.update();
+
+        assert!(app.world().resource::<CollapsedSubgraphs>().is_empty());
+        for entity in [nodes[0], nodes[1], internal] {
+            assert!(app.world().get::<CollapsedMember>(entity).is_none());
+        }
+        assert_eq!(app.world().get::<EdgeVisual>(external).unwrap().target_entity, nodes[1]);
+        let expanded = app.world().resource::<Events<SubgraphExpanded>>();
+        assert_eq!(expanded.iter_current_update_events().next().unwrap().members.len(), 2);
+    }
+}
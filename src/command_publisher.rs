@@ -0,0 +1,319 @@
+//! Command Publishing: Telling the domain what the visualization wants
+//!
+//! Commands the visualization asks for with a `RequestDomainCommand` are
+//! queued on the [`AsyncSyncBridge`] by `send_visualization_commands`;
+//! `VisualizationCommand` events received from the domain never are. With
+//! an [`OutboundCommands`] resource present, [`publish_bridge_commands`]
+//! drains that queue and hands each command, encoded as a JSON
+//! [`CommandMessage`], to the configured [`CommandPublisher`] on the
+//! subject [`CommandSubjects`] assigns to its variant.
+//! [`NatsCommandPublisher`] publishes to NATS; other backends implement the
+//! trait.
+//!
+//! Finished drags are requested as `UpdateNodePosition` commands by
+//! [`request_drag_end_commands`], with the position the node was dropped
+//! and snapped to.
+
+use bevy::prelude::*;
+use async_nats::Client;
+use cim_contextgraph::{NodeId, EdgeId, ContextGraphId as GraphId};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use crate::bridge::AsyncSyncBridge;
+use crate::components::NodeVisual;
+use crate::events::{EdgeRelationship, NodeDragEnd, RequestDomainCommand, UpdateNodePosition, VisualizationCommand};
+
+/// Errors that can occur while publishing a command
+#[derive(Debug, thiserror::Error)]
+pub enum PublishError {
+    #[error("failed to encode command: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("command publisher is closed")]
+    Closed,
+}
+
+/// Backend that delivers encoded commands to the domain
+pub trait CommandPublisher: Send + Sync + 'static {
+    /// Publish `payload` on `subject`
+    fn publish(&self, subject: &str, payload: Vec<u8>) -> Result<(), PublishError>;
+}
+
+/// The publisher outbound commands are delivered through
+#[derive(Resource)]
+pub struct OutboundCommands(pub Box<dyn CommandPublisher>);
+
+/// Subject each command variant is published on
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct CommandSubjects {
+    pub create_node: String,
+    pub remove_node: String,
+    pub update_node_position: String,
+    pub create_edge: String,
+    pub remove_edge: String,
+}
+
+impl Default for CommandSubjects {
+    fn default() -> Self {
+        Self {
+            create_node: "cim.graph.command.node.create".to_string(),
+            remove_node: "cim.graph.command.node.remove".to_string(),
+            update_node_position: "cim.graph.command.node.move".to_string(),
+            create_edge: "cim.graph.command.edge.create".to_string(),
+            remove_edge: "cim.graph.command.edge.remove".to_string(),
+        }
+    }
+}
+
+impl CommandSubjects {
+    /// Subject for `command`'s variant
+    pub fn subject_for(&self, command: &VisualizationCommand) -> &str {
+        match command {
+            VisualizationCommand::CreateNode(_) => &self.create_node,
+            VisualizationCommand::RemoveNode(_) => &self.remove_node,
+            VisualizationCommand::UpdateNodePosition(_) => &self.update_node_position,
+            VisualizationCommand::CreateEdge(_) => &self.create_edge,
+            VisualizationCommand::RemoveEdge(_) => &self.remove_edge,
+        }
+    }
+}
+
+/// Wire format of a `VisualizationCommand`, tagged by `command`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command")]
+pub enum CommandMessage {
+    CreateNode {
+        node_id: NodeId,
+        graph_id: GraphId,
+        position: [f32; 3],
+        label: String,
+        metadata: serde_json::Value,
+    },
+    RemoveNode {
+        node_id: NodeId,
+    },
+    UpdateNodePosition {
+        node_id: NodeId,
+        graph_id: GraphId,
+        position: [f32; 3],
+    },
+    CreateEdge {
+        edge_id: EdgeId,
+        graph_id: GraphId,
+        source_node_id: NodeId,
+        target_node_id: NodeId,
+        relationship: EdgeRelationship,
+    },
+    RemoveEdge {
+        edge_id: EdgeId,
+    },
+}
+
+impl From<&VisualizationCommand> for CommandMessage {
+    fn from(command: &VisualizationCommand) -> Self {
+        match command {
+            VisualizationCommand::CreateNode(create) => CommandMessage::CreateNode {
+                node_id: create.node_id,
+                graph_id: create.graph_id,
+                position: create.position.to_array(),
+                label: create.label.clone(),
+                metadata: create.metadata.clone(),
+            },
+            VisualizationCommand::RemoveNode(remove) => CommandMessage::RemoveNode {
+                node_id: remove.node_id,
+            },
+            VisualizationCommand::UpdateNodePosition(update) => CommandMessage::UpdateNodePosition {
+                node_id: update.node_id,
+                graph_id: update.graph_id,
+                position: update.position.to_array(),
+            },
+            VisualizationCommand::CreateEdge(create) => CommandMessage::CreateEdge {
+                edge_id: create.edge_id,
+                graph_id: create.graph_id,
+                source_node_id: create.source_node_id,
+                target_node_id: create.target_node_id,
+                relationship: create.relationship.clone(),
+            },
+            VisualizationCommand::RemoveEdge(remove) => CommandMessage::RemoveEdge {
+                edge_id: remove.edge_id,
+            },
+        }
+    }
+}
+
+/// Publishes commands to NATS from a background task
+pub struct NatsCommandPublisher {
+    sender: mpsc::UnboundedSender<(String, Vec<u8>)>,
+    _publish_handle: tokio::task::JoinHandle<()>,
+}
+
+impl NatsCommandPublisher {
+    /// Create a publisher on `nats_client`; must be called from within a
+    /// Tokio runtime
+    pub fn new(nats_client: Arc<Client>) -> Self {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<(String, Vec<u8>)>();
+        let publish_handle = tokio::spawn(async move {
+            while let Some((subject, payload)) = receiver.recv().await {
+                if let Err(e) = nats_client.publish(subject, payload.into()).await {
+                    error!("Failed to publish visualization command: {}", e);
+                }
+            }
+        });
+
+        Self {
+            sender,
+            _publish_handle: publish_handle,
+        }
+    }
+}
+
+impl CommandPublisher for NatsCommandPublisher {
+    fn publish(&self, subject: &str, payload: Vec<u8>) -> Result<(), PublishError> {
+        self.sender
+            .send((subject.to_string(), payload))
+            .map_err(|_| PublishError::Closed)
+    }
+}
+
+/// Encode `command` and publish it on its subject
+pub fn publish_command(
+    publisher: &dyn CommandPublisher,
+    subjects: &CommandSubjects,
+    command: &VisualizationCommand,
+) -> Result<(), PublishError> {
+    let payload = serde_json::to_vec(&CommandMessage::from(command))?;
+    publisher.publish(subjects.subject_for(command), payload)
+}
+
+/// System that asks the domain to move each dropped node to where it ended
+/// up, after snapping
+pub fn request_drag_end_commands(
+    mut drag_ended: EventReader<NodeDragEnd>,
+    nodes: Query<(&NodeVisual, &Transform)>,
+    mut requests: EventWriter<RequestDomainCommand>,
+) {
+    for event in drag_ended.read() {
+        let Ok((node, transform)) = nodes.get(event.entity) else {
+            continue;
+        };
+        requests.write(RequestDomainCommand(VisualizationCommand::UpdateNodePosition(UpdateNodePosition {
+            node_id: node.node_id,
+            graph_id: node.graph_id,
+            position: transform.translation,
+        })));
+    }
+}
+
+/// System that publishes the commands queued on the bridge, if an outbound
+/// publisher is configured
+pub fn publish_bridge_commands(
+    bridge: Res<AsyncSyncBridge>,
+    subjects: Res<CommandSubjects>,
+    outbound: Option<Res<OutboundCommands>>,
+) {
+    let Some(outbound) = outbound else {
+        return;
+    };
+    for command in bridge.command_receiver().try_iter() {
+        if let Err(e) = publish_command(outbound.0.as_ref(), &subjects, &command) {
+            error!("Failed to publish visualization command: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{CreateEdgeVisual, CreateNodeVisual, RemoveEdgeVisual, RemoveNodeVisual};
+    use std::sync::Mutex;
+
+    /// Publisher that keeps what it was given
+    #[derive(Default)]
+    struct RecordingPublisher(Arc<Mutex<Vec<(String, Vec<u8>)>>>);
+
+    impl CommandPublisher for RecordingPublisher {
+        fn publish(&self, subject: &str, payload: Vec<u8>) -> Result<(), PublishError> {
+            self.0.lock().unwrap().push((subject.to_string(), payload));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_every_variant_round_trips() {
+        let graph_id = GraphId::new();
+        let (node_id, other_id, edge_id) = (NodeId::new(), NodeId::new(), EdgeId::new());
+        let commands = [
+            VisualizationCommand::CreateNode(CreateNodeVisual {
+                node_id,
+                graph_id,
+                position: Vec3::new(1.0, 2.0, 3.0),
+                label: "Order".to_string(),
+                metadata: serde_json::json!({ "tags": ["sales"] }),
+            }),
+            VisualizationCommand::RemoveNode(RemoveNodeVisual { node_id }),
+            VisualizationCommand::UpdateNodePosition(UpdateNodePosition {
+                node_id,
+                graph_id,
+                position: Vec3::new(-4.0, 0.5, 0.0),
+            }),
+            VisualizationCommand::CreateEdge(CreateEdgeVisual {
+                edge_id,
+                graph_id,
+                source_node_id: node_id,
+                target_node_id: other_id,
+                relationship: EdgeRelationship::Custom("Ships".to_string()),
+                weight: None,
+            }),
+            VisualizationCommand::RemoveEdge(RemoveEdgeVisual { edge_id }),
+        ];
+
+        let publisher = RecordingPublisher::default();
+        let subjects = CommandSubjects::default();
+        for command in &commands {
+            publish_command(&publisher, &subjects, command).unwrap();
+        }
+
+        let published = publisher.0.lock().unwrap();
+        assert_eq!(published.len(), commands.len());
+        for (command, (subject, payload)) in commands.iter().zip(published.iter()) {
+            assert_eq!(subject, subjects.subject_for(command));
+            let decoded: CommandMessage = serde_json::from_slice(payload).unwrap();
+            assert_eq!(decoded, CommandMessage::from(command));
+        }
+        let tagged: serde_json::Value = serde_json::from_slice(&published[2].1).unwrap();
+        assert_eq!(tagged["command"], "UpdateNodePosition");
+        assert_eq!(tagged["position"], serde_json::json!([-4.0, 0.5, 0.0]));
+    }
+
+    #[test]
+    fn test_drag_end_is_published_as_position_command() {
+        let mut app = App::new();
+        let publisher = RecordingPublisher::default();
+        let published = publisher.0.clone();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(AsyncSyncBridge::new(16))
+            .init_resource::<CommandSubjects>()
+            .insert_resource(OutboundCommands(Box::new(publisher)))
+            .add_event::<NodeDragEnd>()
+            .add_event::<RequestDomainCommand>()
+            .add_systems(Update, (
+                request_drag_end_commands,
+                crate::bridge::send_visualization_commands,
+                publish_bridge_commands,
+            ).chain());
+
+        let (node_id, graph_id) = (NodeId::new(), GraphId::new());
+        // Snapping has already moved the node off the drop position
+        let entity = app.world_mut()
+            .spawn(crate::components::NodeVisualBundle::new(node_id, graph_id, Vec3::new(2.0, 3.0, 0.0)))
+            .id();
+        app.world_mut().send_event(NodeDragEnd { entity, node_id, final_position: Vec3::new(2.1, 2.9, 0.0) });
+        app.update();
+
+        let published = published.lock().unwrap();
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].0, "cim.graph.command.node.move");
+        let decoded: CommandMessage = serde_json::from_slice(&published[0].1).unwrap();
+        assert_eq!(decoded, CommandMessage::UpdateNodePosition { node_id, graph_id, position: [2.0, 3.0, 0.0] });
+    }
+}
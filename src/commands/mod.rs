@@ -2,14 +2,19 @@
 //!
 //! Commands in ECS are events that trigger systems to perform operations.
 //! Systems act as command handlers that process these commands.
+//!
+//! Nodes and edges are named by their domain ids, the same ids the events
+//! the handlers send carry.
 
-use crate::value_objects::*;
+use crate::value_objects::{EdgeVisualStyle, NodeVisualStyle, Position};
 use bevy::prelude::*;
+use cim_contextgraph::{NodeId, EdgeId, ContextGraphId as GraphId};
 
 /// Command to create a visual node
 #[derive(Event, Debug, Clone)]
 pub struct CreateVisualNode {
     pub node_id: NodeId,
+    pub graph_id: GraphId,
     pub position: Position,
     pub visual_style: NodeVisualStyle,
 }
@@ -18,6 +23,7 @@ pub struct CreateVisualNode {
 #[derive(Event, Debug, Clone)]
 pub struct CreateVisualEdge {
     pub edge_id: EdgeId,
+    pub graph_id: GraphId,
     pub source_node_id: NodeId,
     pub target_node_id: NodeId,
     pub edge_style: EdgeVisualStyle,
@@ -50,22 +56,21 @@ pub struct DeleteVisualEdge {
     pub edge_id: EdgeId,
 }
 
-/// Command to select a node
-#[derive(Event, Debug, Clone)]
-pub struct SelectNode {
-    pub node_id: NodeId,
-    pub multi_select: bool,
-}
+/// Command to select a node, now an event handled by the selection plugins
+#[deprecated(note = "use `events::SelectNode`")]
+pub type SelectNode = crate::events::SelectNode;
 
-/// Command to pan the canvas
+/// Command to pan a graph's canvas
 #[derive(Event, Debug, Clone)]
 pub struct PanCanvas {
+    pub graph_id: GraphId,
     pub delta: Vec2,
 }
 
-/// Command to zoom the canvas
+/// Command to zoom a graph's canvas
 #[derive(Event, Debug, Clone)]
 pub struct ZoomCanvas {
+    pub graph_id: GraphId,
     pub zoom_factor: f32,
     pub focal_point: Option<Vec2>,
 }
@@ -16,6 +16,7 @@ pub struct VisualNodeId(pub Uuid);
 #[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct VisualEdgeId(pub Uuid);
 use serde::{Deserialize, Serialize};
+use crate::value_objects::{EdgeVisualStyle, NodeInteractionState, NodeVisualStyle};
 
 // ============================================================================
 // Graph Visual Components (Objects in the Visual Category)
@@ -28,6 +29,30 @@ pub struct GraphVisual {
     pub layout_type: LayoutType,
 }
 
+/// Part of the world a graph is laid out in, so several graphs can be shown
+/// side by side. Put on the graph's entity; `bounds` is the size of the
+/// box centered on `origin`.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct GraphRegion {
+    pub graph_id: GraphId,
+    pub origin: Vec3,
+    pub bounds: Vec3,
+}
+
+impl GraphRegion {
+    pub fn min(&self) -> Vec3 {
+        self.origin - self.bounds * 0.5
+    }
+
+    pub fn max(&self) -> Vec3 {
+        self.origin + self.bounds * 0.5
+    }
+
+    pub fn contains(&self, position: Vec3) -> bool {
+        position.cmpge(self.min()).all() && position.cmple(self.max()).all()
+    }
+}
+
 /// Visual representation of a node - preserves node identity
 #[derive(Component, Debug, Clone)]
 pub struct NodeVisual {
@@ -42,13 +67,38 @@ pub struct EdgeVisual {
     pub graph_id: GraphId,
     pub source_entity: Entity,
     pub target_entity: Entity,
+    /// Strength of the connection: heavier edges pull their nodes closer in
+    /// the force-directed layout and are drawn thicker. 1.0 is a plain edge.
+    /// Read it through [`EdgeVisual::effective_weight`].
+    pub weight: f32,
+}
+
+/// Lightest an edge can be, so zero, negative or NaN weights neither push
+/// nodes apart nor stop the edge being drawn
+pub const MIN_EDGE_WEIGHT: f32 = 0.1;
+
+impl EdgeVisual {
+    /// The weight the layout and renderers use: `weight`, but at least
+    /// [`MIN_EDGE_WEIGHT`] however the field was set
+    pub fn effective_weight(&self) -> f32 {
+        self.weight.max(MIN_EDGE_WEIGHT)
+    }
 }
 
 // ============================================================================
 // Visual Properties (Additional structure in the visual category)
 // ============================================================================
 
+/// Text shown alongside an edge, usually its relationship
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct EdgeLabel {
+    pub text: String,
+}
+
 /// Visual selection state - exists only in visual category
+///
+/// This marker is the selection state for every node, whichever bundle
+/// spawned it; `NodeInteractionState` only mirrors it.
 #[derive(Component, Debug, Clone, Default)]
 pub struct Selected;
 
@@ -56,6 +106,16 @@ pub struct Selected;
 #[derive(Component, Debug, Clone, Default)]
 pub struct Hovered;
 
+/// Anchors a node during the force-directed layout: it still pushes and
+/// pulls the other nodes but is put back at this position every step
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct AnchoredPosition(pub Vec3);
+
+/// Where a node was last dropped by hand; the manual layout puts it back
+/// there after other layouts moved it
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct ManualPosition(pub Vec3);
+
 /// Visual dragging state - exists only in visual category
 #[derive(Component, Debug, Clone)]
 pub struct Dragging {
@@ -64,12 +124,23 @@ pub struct Dragging {
 }
 
 /// Visual highlight state - exists only in visual category
-#[derive(Component, Debug, Clone)]
+#[derive(Component, Debug, Clone, PartialEq)]
 pub struct Highlighted {
     pub color: Color,
     pub intensity: f32,
 }
 
+/// A temporary highlight. When its timer ends the highlight it replaced is
+/// put back, unless `Highlighted` was changed in the meantime.
+#[derive(Component, Debug, Clone)]
+pub struct TimedHighlight {
+    pub timer: Timer,
+    /// The highlight this one applied
+    pub highlight: Highlighted,
+    /// The highlight the entity had before, if any
+    pub replaced: Option<Highlighted>,
+}
+
 // ============================================================================
 // Layout Types (Morphisms in the visual category)
 // ============================================================================
@@ -82,6 +153,36 @@ pub enum LayoutType {
     Grid,
     Random,
     Manual,
+    Clustered,
+}
+
+impl From<LayoutType> for crate::visualization::LayoutType {
+    fn from(layout_type: LayoutType) -> Self {
+        match layout_type {
+            LayoutType::ForceDirected => Self::ForceDirected,
+            LayoutType::Hierarchical => Self::Hierarchical,
+            LayoutType::Circular => Self::Circular,
+            LayoutType::Grid => Self::Grid,
+            LayoutType::Random => Self::Random,
+            LayoutType::Manual => Self::Manual,
+            LayoutType::Clustered => Self::Clustered,
+        }
+    }
+}
+
+impl From<crate::visualization::LayoutType> for LayoutType {
+    fn from(layout_type: crate::visualization::LayoutType) -> Self {
+        use crate::visualization::LayoutType as Algorithm;
+        match layout_type {
+            Algorithm::ForceDirected => Self::ForceDirected,
+            Algorithm::Hierarchical => Self::Hierarchical,
+            Algorithm::Circular => Self::Circular,
+            Algorithm::Grid => Self::Grid,
+            Algorithm::Random => Self::Random,
+            Algorithm::Manual => Self::Manual,
+            Algorithm::Clustered => Self::Clustered,
+        }
+    }
 }
 
 // ============================================================================
@@ -92,6 +193,8 @@ pub enum LayoutType {
 #[derive(Bundle)]
 pub struct NodeVisualBundle {
     pub node: NodeVisual,
+    /// Mirror of the `Selected`, `Hovered` and `Dragging` markers
+    pub interaction: NodeInteractionState,
     pub transform: Transform,
     pub global_transform: GlobalTransform,
     pub visibility: Visibility,
@@ -103,6 +206,7 @@ impl NodeVisualBundle {
     pub fn new(node_id: NodeId, graph_id: GraphId, position: Vec3) -> Self {
         Self {
             node: NodeVisual { node_id, graph_id },
+            interaction: NodeInteractionState::default(),
             transform: Transform::from_translation(position),
             global_transform: GlobalTransform::default(),
             visibility: Visibility::default(),
@@ -137,6 +241,7 @@ impl EdgeVisualBundle {
                 graph_id,
                 source_entity,
                 target_entity,
+                weight: 1.0,
             },
             edge_state: EdgeState::default(),
             transform: Transform::default(),
@@ -146,6 +251,12 @@ impl EdgeVisualBundle {
             view_visibility: ViewVisibility::default(),
         }
     }
+
+    /// Weight of the edge, at least [`MIN_EDGE_WEIGHT`]
+    pub fn with_weight(mut self, weight: f32) -> Self {
+        self.edge.weight = weight.max(MIN_EDGE_WEIGHT);
+        self
+    }
 }
 
 // ============================================================================
@@ -167,7 +278,7 @@ pub struct TemporaryVisual {
 }
 
 /// Visual style for nodes
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct NodeStyle {
     pub shape: NodeShape,
     pub size: f32,
@@ -188,9 +299,37 @@ impl Default for NodeStyle {
     }
 }
 
+impl From<NodeVisualStyle> for NodeStyle {
+    fn from(style: NodeVisualStyle) -> Self {
+        Self {
+            shape: style.shape,
+            size: style.size,
+            color: style.color,
+            // A transparent border is no border
+            border_color: (style.border_color != Color::NONE).then_some(style.border_color),
+            border_width: style.border_width,
+        }
+    }
+}
+
+impl From<NodeStyle> for NodeVisualStyle {
+    fn from(style: NodeStyle) -> Self {
+        Self {
+            color: style.color,
+            size: style.size,
+            shape: style.shape,
+            border_color: style.border_color.unwrap_or(Color::NONE),
+            border_width: style.border_width,
+        }
+    }
+}
+
 /// Node shape variants
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+///
+/// The one shape enum of the crate; `value_objects::NodeShape` re-exports it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum NodeShape {
+    #[default]
     Circle,
     Square,
     Diamond,
@@ -199,6 +338,9 @@ pub enum NodeShape {
 }
 
 /// Visual style for edges
+///
+/// The one edge style of the crate; `value_objects` and `visualization`
+/// re-export it.
 #[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct EdgeStyle {
     pub curve_type: EdgeCurveType,
@@ -220,6 +362,29 @@ impl Default for EdgeStyle {
     }
 }
 
+impl From<EdgeVisualStyle> for EdgeStyle {
+    fn from(style: EdgeVisualStyle) -> Self {
+        Self {
+            thickness: style.width,
+            color: style.color,
+            arrow_size: style.arrow_size,
+            dashed: style.dashed,
+            ..default()
+        }
+    }
+}
+
+impl From<EdgeStyle> for EdgeVisualStyle {
+    fn from(style: EdgeStyle) -> Self {
+        Self {
+            color: style.color,
+            width: style.thickness,
+            dashed: style.dashed,
+            arrow_size: style.arrow_size,
+        }
+    }
+}
+
 /// Edge state for tracking various edge conditions
 #[derive(Component, Debug, Clone)]
 pub struct EdgeState {
@@ -257,7 +422,8 @@ pub enum EdgeCurveType {
     Step,
 }
 
-/// Component marking an entity as needing layout update
+/// Component asking for the layout of the entity's graph to run again, e.g.
+/// after moving a node by hand. It is removed once the graph is marked.
 #[derive(Component, Default)]
 pub struct NeedsLayout;
 
@@ -268,6 +434,7 @@ pub struct AnimatedTransition {
     pub target_position: Vec3,
     pub progress: f32,
     pub duration: f32,
+    pub easing: crate::easing::Easing,
 }
 
 /// Component for workflow visualization state
@@ -278,6 +445,44 @@ pub struct WorkflowState {
     pub execution_path: Vec<EdgeId>,
 }
 
+/// Execution status of a workflow node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum NodeStatus {
+    Active,
+    Completed,
+    Failed,
+}
+
+impl NodeStatus {
+    pub const ALL: [NodeStatus; 3] = [NodeStatus::Active, NodeStatus::Completed, NodeStatus::Failed];
+
+    /// Status named by a domain status string, case-insensitively; `None`
+    /// for statuses without a badge, such as `pending`
+    pub fn from_domain(status: &str) -> Option<Self> {
+        match status.to_ascii_lowercase().as_str() {
+            "active" | "running" | "in_progress" | "started" => Some(Self::Active),
+            "completed" | "complete" | "done" | "succeeded" => Some(Self::Completed),
+            "failed" | "error" | "errored" => Some(Self::Failed),
+            _ => None,
+        }
+    }
+
+    /// Color the status is shown in
+    pub fn color(&self) -> Color {
+        match self {
+            Self::Active => Color::srgb(1.0, 0.8, 0.0),
+            Self::Completed => Color::srgb(0.0, 0.8, 0.0),
+            Self::Failed => Color::srgb(0.8, 0.0, 0.0),
+        }
+    }
+}
+
+/// Component asking for a status icon next to the node
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct StatusBadge {
+    pub state: NodeStatus,
+}
+
 /// Component for camera focus targets
 #[derive(Component)]
 pub struct CameraFocusTarget {
@@ -316,4 +521,60 @@ mod tests {
         assert_eq!(bundle.edge.source_entity, source);
         assert_eq!(bundle.edge.target_entity, target);
     }
+
+    #[test]
+    fn test_edge_weight_has_a_positive_minimum() {
+        let bundle = |weight| EdgeVisualBundle::new(EdgeId::new(), GraphId::new(), Entity::from_raw(1), Entity::from_raw(2))
+            .with_weight(weight)
+            .edge
+            .weight;
+
+        assert_eq!(bundle(2.5), 2.5);
+        assert_eq!(bundle(0.0), MIN_EDGE_WEIGHT);
+        assert_eq!(bundle(-3.0), MIN_EDGE_WEIGHT);
+        assert_eq!(bundle(f32::NAN), MIN_EDGE_WEIGHT);
+
+        // Set directly, the weight is still clamped where it is used
+        let mut edge = EdgeVisualBundle::new(EdgeId::new(), GraphId::new(), Entity::from_raw(1), Entity::from_raw(2)).edge;
+        edge.weight = -1.0;
+        assert_eq!(edge.effective_weight(), MIN_EDGE_WEIGHT);
+        edge.weight = 4.0;
+        assert_eq!(edge.effective_weight(), 4.0);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_node_styles_convert_both_ways() {
+        use crate::value_objects::LegacyNodeShape;
+
+        let style = NodeVisualStyle {
+            color: Color::srgb(0.2, 0.4, 0.6),
+            size: 1.5,
+            shape: LegacyNodeShape::Hexagon.into(),
+            border_color: Color::WHITE,
+            border_width: 0.1,
+        };
+        let component = NodeStyle::from(style.clone());
+        assert_eq!(component.shape, NodeShape::Hexagon);
+        assert_eq!(component.border_color, Some(Color::WHITE));
+        assert_eq!(NodeVisualStyle::from(component), style);
+
+        let borderless = NodeStyle::from(NodeVisualStyle { border_color: Color::NONE, ..style });
+        assert_eq!(borderless.border_color, None);
+    }
+
+    #[test]
+    fn test_edge_styles_convert_both_ways() {
+        let style = EdgeVisualStyle {
+            color: Color::srgb(0.1, 0.5, 0.9),
+            width: 0.25,
+            dashed: true,
+            arrow_size: 0.4,
+        };
+        let component = EdgeStyle::from(style.clone());
+        assert_eq!(component.thickness, 0.25);
+        assert!(component.dashed);
+        assert_eq!(component.curve_type, EdgeStyle::default().curve_type);
+        assert_eq!(EdgeVisualStyle::from(component), style);
+    }
 }
@@ -0,0 +1,234 @@
+//! Culling: Hiding graph entities outside the camera's view
+//!
+//! Several systems want to hide entities for different reasons, so none of
+//! them writes [`Visibility`] directly. Each reason is a marker component —
+//! [`Culled`] for entities outside the active camera frustum, [`FilteredOut`]
+//! for entities rejected by the event filters, [`OutsideTimeline`] for events
+//! outside the timeline window, [`CollapsedMember`] for nodes and edges
+//! folded into a meta-node, [`HiddenRelationship`] for edges rejected by the
+//! edge filter — and [`apply_hidden_reasons`] derives the visibility from
+//! whichever markers are present. An entity is shown again only once every
+//! reason is gone.
+//!
+//! With [`FreezeCulledLayout`] enabled, the force-directed layout skips
+//! culled nodes entirely, leaving them where they are until they come back
+//! into view.
+
+use bevy::prelude::*;
+use bevy::render::primitives::{Frustum, Sphere as BoundingSphere};
+use bevy::render::view::VisibilitySystems;
+use std::collections::HashSet;
+use crate::components::{EdgeVisual, GraphCamera, NodeVisual};
+
+/// Radius assumed around a node's position when testing it against the
+/// frustum, so nodes don't pop out while still partially on screen
+pub const NODE_CULLING_RADIUS: f32 = 1.0;
+
+/// Hidden because the entity is outside the active camera frustum
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct Culled;
+
+/// Hidden because the entity doesn't match the active event filters
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct FilteredOut;
+
+/// Hidden because the event lies outside the timeline's current window
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct OutsideTimeline;
+
+/// Hidden because the entity is folded into a collapsed meta-node
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CollapsedMember {
+    pub meta_node: Entity,
+}
+
+/// Hidden because the edge's relationship is filtered out
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct HiddenRelationship;
+
+/// Whether the force-directed layout skips culled nodes
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FreezeCulledLayout(pub bool);
+
+/// Plugin that culls off-screen nodes and edges and resolves hidden reasons
+/// into [`Visibility`]
+pub struct CullingPlugin;
+
+impl Plugin for CullingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FreezeCulledLayout>()
+            .add_systems(
+                PostUpdate,
+                (cull_offscreen_entities, apply_hidden_reasons)
+                    .chain()
+                    .after(VisibilitySystems::UpdateFrusta)
+                    .before(VisibilitySystems::VisibilityPropagate),
+            );
+    }
+}
+
+/// System that marks nodes and edges outside the active camera frustum as
+/// [`Culled`]. Graph cameras are preferred over other active cameras.
+pub fn cull_offscreen_entities(
+    mut commands: Commands,
+    cameras: Query<(&Camera, &Frustum, Has<GraphCamera>)>,
+    nodes: Query<(Entity, &GlobalTransform, Has<Culled>), With<NodeVisual>>,
+    edges: Query<(Entity, &EdgeVisual, Has<Culled>)>,
+) {
+    let Some((_, frustum, _)) = cameras.iter()
+        .filter(|(camera, _, _)| camera.is_active)
+        .max_by_key(|(_, _, is_graph_camera)| *is_graph_camera)
+    else {
+        return;
+    };
+
+    let mut set_culled = |entity: Entity, visible: bool, culled: bool| {
+        if visible && culled {
+            commands.entity(entity).remove::<Culled>();
+        } else if !visible && !culled {
+            commands.entity(entity).insert(Culled);
+        }
+    };
+
+    for (entity, transform, culled) in nodes.iter() {
+        let sphere = BoundingSphere {
+            center: transform.translation_vec3a(),
+            radius: NODE_CULLING_RADIUS,
+        };
+        set_culled(entity, frustum.intersects_sphere(&sphere, true), culled);
+    }
+
+    for (entity, edge, culled) in edges.iter() {
+        let (Ok((_, source, _)), Ok((_, target, _))) = (nodes.get(edge.source_entity), nodes.get(edge.target_entity)) else {
+            continue;
+        };
+        let (source, target) = (source.translation_vec3a(), target.translation_vec3a());
+        let sphere = BoundingSphere {
+            center: (source + target) * 0.5,
+            radius: source.distance(target) * 0.5 + NODE_CULLING_RADIUS,
+        };
+        set_culled(entity, frustum.intersects_sphere(&sphere, true), culled);
+    }
+}
+
+/// Filter for entities that just gained a hidden reason
+type HiddenReasonAdded = Or<(
+    Added<Culled>,
+    Added<FilteredOut>,
+    Added<OutsideTimeline>,
+    Added<CollapsedMember>,
+    Added<HiddenRelationship>,
+)>;
+
+/// Which hidden reasons an entity has
+type HiddenReasons = (Has<Culled>, Has<FilteredOut>, Has<OutsideTimeline>, Has<CollapsedMember>, Has<HiddenRelationship>);
+
+/// System that hides entities with any hidden reason and shows them again
+/// once the last reason is removed
+pub fn apply_hidden_reasons(
+    added: Query<Entity, HiddenReasonAdded>,
+    mut removed_culled: RemovedComponents<Culled>,
+    mut removed_filtered: RemovedComponents<FilteredOut>,
+    mut removed_timeline: RemovedComponents<OutsideTimeline>,
+    mut removed_collapsed: RemovedComponents<CollapsedMember>,
+    mut removed_relationship: RemovedComponents<HiddenRelationship>,
+    mut entities: Query<(&mut Visibility, HiddenReasons)>,
+) {
+    let changed: HashSet<Entity> = added.iter()
+        .chain(removed_culled.read())
+        .chain(removed_filtered.read())
+        .chain(removed_timeline.read())
+        .chain(removed_collapsed.read())
+        .chain(removed_relationship.read())
+        .collect();
+
+    for entity in changed {
+        let Ok((mut visibility, (culled, filtered, outside_timeline, collapsed, relationship))) = entities.get_mut(entity) else {
+            continue;
+        };
+        visibility.set_if_neq(if culled || filtered || outside_timeline || collapsed || relationship {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::render::camera::CameraProjection;
+    use cim_contextgraph::{ContextGraphId as GraphId, EdgeId, NodeId};
+    use crate::components::{EdgeVisualBundle, NodeVisualBundle};
+
+    fn culling_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_systems(Update, (cull_offscreen_entities, apply_hidden_reasons).chain());
+
+        // Camera at the origin looking down -Z
+        let projection = PerspectiveProjection::default();
+        let view = Transform::IDENTITY;
+        let clip_from_world = projection.get_clip_from_view() * view.compute_matrix().inverse();
+        app.world_mut().spawn((
+            Camera::default(),
+            Frustum::from_clip_from_world(&clip_from_world),
+            GlobalTransform::from(view),
+            GraphCamera,
+        ));
+        app
+    }
+
+    #[test]
+    fn test_offscreen_nodes_and_edges_are_culled() {
+        let mut app = culling_app();
+        let graph_id = GraphId::new();
+        let spawn_node = |app: &mut App, position: Vec3| {
+            let mut bundle = NodeVisualBundle::new(NodeId::new(), graph_id, position);
+            bundle.global_transform = GlobalTransform::from_translation(position);
+            app.world_mut().spawn(bundle).id()
+        };
+        let in_view = spawn_node(&mut app, Vec3::new(0.0, 0.0, -10.0));
+        let behind = spawn_node(&mut app, Vec3::new(0.0, 0.0, 10.0));
+        let behind_too = spawn_node(&mut app, Vec3::new(2.0, 0.0, 10.0));
+        let crossing = app.world_mut().spawn(EdgeVisualBundle::new(EdgeId::new(), graph_id, in_view, behind)).id();
+        let hidden_edge = app.world_mut().spawn(EdgeVisualBundle::new(EdgeId::new(), graph_id, behind, behind_too)).id();
+        app.update();
+
+        let world = app.world();
+        assert!(world.get::<Culled>(in_view).is_none());
+        assert!(world.get::<Culled>(behind).is_some());
+        assert_eq!(world.get::<Visibility>(behind), Some(&Visibility::Hidden));
+        assert!(world.get::<Culled>(crossing).is_none());
+        assert!(world.get::<Culled>(hidden_edge).is_some());
+
+        // Moving the node into view shows it again
+        app.world_mut().entity_mut(behind).insert(GlobalTransform::from_xyz(0.0, 0.0, -5.0));
+        app.update();
+        assert!(app.world().get::<Culled>(behind).is_none());
+        assert_eq!(app.world().get::<Visibility>(behind), Some(&Visibility::Inherited));
+    }
+
+    #[test]
+    fn test_entity_stays_hidden_until_every_reason_is_gone() {
+        let mut app = culling_app();
+        let node = app.world_mut().spawn((
+            NodeVisual { node_id: NodeId::new(), graph_id: GraphId::new() },
+            GlobalTransform::from_xyz(0.0, 0.0, 10.0),
+            Visibility::default(),
+            FilteredOut,
+        )).id();
+        app.update();
+        assert_eq!(app.world().get::<Visibility>(node), Some(&Visibility::Hidden));
+
+        // Back in view, but still filtered out
+        app.world_mut().entity_mut(node).insert(GlobalTransform::from_xyz(0.0, 0.0, -10.0));
+        app.update();
+        assert!(app.world().get::<Culled>(node).is_none());
+        assert_eq!(app.world().get::<Visibility>(node), Some(&Visibility::Hidden));
+
+        app.world_mut().entity_mut(node).remove::<FilteredOut>();
+        app.update();
+        assert_eq!(app.world().get::<Visibility>(node), Some(&Visibility::Inherited));
+    }
+}
@@ -0,0 +1,161 @@
+//! Easing: Shaping the progress of animations over time
+//!
+//! An [`Easing`] maps linear progress in `0.0..=1.0` onto eased progress,
+//! starting at 0 and arriving at 1. [`AnimatedTransition`] and
+//! [`CameraAnimation`] each carry one, so node moves, layout changes and
+//! camera flights can all be given the same or different motion.
+//!
+//! [`AnimatedTransition`]: crate::components::AnimatedTransition
+//! [`CameraAnimation`]: crate::camera::CameraAnimation
+
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+
+/// How an animation's progress is eased
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Easing {
+    /// Constant speed
+    Linear,
+    /// Slow start, fast arrival
+    EaseInCubic,
+    /// Fast start, gentle arrival
+    #[default]
+    EaseOutCubic,
+    /// Slow start and gentle arrival
+    EaseInOutCubic,
+    /// Overshoots the target a little before settling
+    EaseOutBack,
+    /// Springs past the target and oscillates into place
+    EaseOutElastic,
+    /// Bounces on the target like a dropped ball
+    EaseOutBounce,
+}
+
+impl Easing {
+    /// Every easing, for pickers and tests
+    pub const ALL: [Easing; 7] = [
+        Easing::Linear,
+        Easing::EaseInCubic,
+        Easing::EaseOutCubic,
+        Easing::EaseInOutCubic,
+        Easing::EaseOutBack,
+        Easing::EaseOutElastic,
+        Easing::EaseOutBounce,
+    ];
+
+    /// Eased progress at linear progress `t`, which is clamped to
+    /// `0.0..=1.0`. Overshooting easings leave that range in between but
+    /// always start at 0 and end at 1.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseInCubic => t.powi(3),
+            Easing::EaseOutCubic => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOutCubic => {
+                if t < 0.5 {
+                    4.0 * t.powi(3)
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+            Easing::EaseOutBack => {
+                const OVERSHOOT: f32 = 1.70158;
+                1.0 + (OVERSHOOT + 1.0) * (t - 1.0).powi(3) + OVERSHOOT * (t - 1.0).powi(2)
+            }
+            Easing::EaseOutElastic => {
+                if t == 0.0 || t == 1.0 {
+                    t
+                } else {
+                    2f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * (2.0 * PI / 3.0)).sin() + 1.0
+                }
+            }
+            Easing::EaseOutBounce => ease_out_bounce(t),
+        }
+    }
+}
+
+/// Four arcs of shrinking height, the last ending on the target
+fn ease_out_bounce(t: f32) -> f32 {
+    const STRENGTH: f32 = 7.5625;
+    const WIDTH: f32 = 2.75;
+    if t < 1.0 / WIDTH {
+        STRENGTH * t * t
+    } else if t < 2.0 / WIDTH {
+        let t = t - 1.5 / WIDTH;
+        STRENGTH * t * t + 0.75
+    } else if t < 2.5 / WIDTH {
+        let t = t - 2.25 / WIDTH;
+        STRENGTH * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / WIDTH;
+        STRENGTH * t * t + 0.984375
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_easing_starts_at_zero_and_ends_at_one() {
+        for easing in Easing::ALL {
+            assert!(easing.apply(0.0).abs() < 1e-5, "{easing:?} starts at {}", easing.apply(0.0));
+            assert!((easing.apply(1.0) - 1.0).abs() < 1e-5, "{easing:?} ends at {}", easing.apply(1.0));
+            // Progress outside the animation is clamped
+            assert_eq!(easing.apply(-1.0), easing.apply(0.0));
+            assert_eq!(easing.apply(2.0), easing.apply(1.0));
+        }
+    }
+
+    #[test]
+    fn test_easings_shape_the_midpoint() {
+        assert_eq!(Easing::Linear.apply(0.5), 0.5);
+        assert!(Easing::EaseInCubic.apply(0.5) < 0.5);
+        assert!(Easing::EaseOutCubic.apply(0.5) > 0.5);
+        assert!((Easing::EaseInOutCubic.apply(0.5) - 0.5).abs() < 1e-5);
+        // Back and elastic overshoot the target on the way
+        assert!((0..100).any(|step| Easing::EaseOutBack.apply(step as f32 / 100.0) > 1.0));
+        assert!((0..100).any(|step| Easing::EaseOutElastic.apply(step as f32 / 100.0) > 1.0));
+        assert!((0..=100).all(|step| Easing::EaseOutBounce.apply(step as f32 / 100.0) <= 1.0 + 1e-5));
+    }
+
+    #[test]
+    fn test_default_is_ease_out_cubic() {
+        assert_eq!(Easing::default(), Easing::EaseOutCubic);
+        assert!((Easing::default().apply(0.5) - 0.875).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_curve_values() {
+        let close = |easing: Easing, t: f32, expected: f32| {
+            let value = easing.apply(t);
+            assert!((value - expected).abs() < 1e-4, "{easing:?} at {t} is {value}, expected {expected}");
+        };
+        close(Easing::Linear, 0.25, 0.25);
+        close(Easing::EaseInCubic, 0.5, 0.125);
+        close(Easing::EaseOutCubic, 0.5, 0.875);
+        close(Easing::EaseInOutCubic, 0.25, 0.0625);
+        close(Easing::EaseInOutCubic, 0.75, 0.9375);
+        // Back peaks about 10% past the target
+        let peak = (0..=1000).map(|step| Easing::EaseOutBack.apply(step as f32 / 1000.0)).fold(0.0, f32::max);
+        assert!((peak - 1.1).abs() < 0.01, "back peaks at {peak}");
+        // Elastic overshoots by a quarter on its first swing and then settles
+        close(Easing::EaseOutElastic, 0.1, 1.25);
+        assert!((0..=100).all(|step| (Easing::EaseOutElastic.apply(0.5 + step as f32 / 200.0) - 1.0).abs() < 0.04));
+        // Bounce lands on the target at the end of each arc
+        close(Easing::EaseOutBounce, 1.0 / 2.75, 1.0);
+        close(Easing::EaseOutBounce, 2.0 / 2.75, 1.0);
+        close(Easing::EaseOutBounce, 2.5 / 2.75, 1.0);
+        close(Easing::EaseOutBounce, 1.5 / 2.75, 0.75);
+    }
+
+    #[test]
+    fn test_cubic_easings_never_go_backwards() {
+        for easing in [Easing::Linear, Easing::EaseInCubic, Easing::EaseOutCubic, Easing::EaseInOutCubic] {
+            let values: Vec<f32> = (0..=100).map(|step| easing.apply(step as f32 / 100.0)).collect();
+            assert!(values.windows(2).all(|pair| pair[0] <= pair[1]), "{easing:?} is not monotonic");
+            assert!(values.iter().all(|value| (0.0..=1.0).contains(value)));
+        }
+    }
+}
@@ -0,0 +1,356 @@
+//! Edge Creation: Drawing edges between nodes
+//!
+//! With the interaction mode set to [`InteractionMode::CreateEdge`] (toggled
+//! with [`EdgeCreationConfig::toggle_key`]), pressing the left mouse button
+//! on a node starts a new edge. A preview line follows the cursor until the
+//! button is released: over another node it sends [`RequestEdgeCreation`]
+//! with the default relationship, over empty space it cancels. Holding one
+//! of the menu modifiers while releasing opens a radial menu to pick the
+//! relationship instead.
+//!
+//! Nodes are found through [`PickingState`], so what counts as "over a node"
+//! is the same for edge creation as for hovering and clicking.
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiPlugin, EguiPrimaryContextPass};
+use cim_contextgraph::NodeId;
+use crate::components::{GraphCamera, NodeVisual};
+use crate::events::{EdgeRelationship, RequestEdgeCreation};
+use crate::morphisms::NodeEntityMap;
+use crate::picking::{cursor_position, cursor_ray, egui_wants_keyboard, PickingPlugin, PickingSet, PickingState};
+use crate::resources::{InteractionMode, InteractionState};
+
+/// Radius of the relationship menu, in logical pixels
+const MENU_RADIUS: f32 = 60.0;
+
+/// Keys and relationships used for edge creation
+#[derive(Resource, Debug, Clone)]
+pub struct EdgeCreationConfig {
+    /// Switches between selecting and creating edges, unless a panel has
+    /// keyboard focus
+    pub toggle_key: KeyCode,
+    /// Holding any of these on release opens the relationship menu
+    pub menu_modifiers: Vec<KeyCode>,
+    /// Relationship used when no menu is opened
+    pub default_relationship: EdgeRelationship,
+    /// Relationships offered by the menu
+    pub relationships: Vec<EdgeRelationship>,
+}
+
+impl Default for EdgeCreationConfig {
+    fn default() -> Self {
+        Self {
+            toggle_key: KeyCode::KeyE,
+            menu_modifiers: vec![KeyCode::AltLeft, KeyCode::AltRight],
+            default_relationship: EdgeRelationship::DependsOn,
+            relationships: vec![
+                EdgeRelationship::DependsOn,
+                EdgeRelationship::Contains,
+                EdgeRelationship::References,
+            ],
+        }
+    }
+}
+
+/// Edge being drawn from a node
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EdgeDrag {
+    pub source: Entity,
+    pub source_id: NodeId,
+}
+
+/// Relationship menu waiting for a choice
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RelationshipMenu {
+    pub source_id: NodeId,
+    pub target_id: NodeId,
+    /// Menu center in window logical pixels
+    pub position: Vec2,
+}
+
+/// In-progress edge creation
+#[derive(Resource, Debug, Default, Clone)]
+pub struct EdgeCreation {
+    pub drag: Option<EdgeDrag>,
+    pub menu: Option<RelationshipMenu>,
+}
+
+/// Plugin for drawing edges between nodes with the mouse
+pub struct EdgeCreationPlugin;
+
+impl Plugin for EdgeCreationPlugin {
+    fn build(&self, app: &mut App) {
+        // Drags start and end on the node under the cursor
+        if !app.is_plugin_added::<PickingPlugin>() {
+            app.add_plugins(PickingPlugin);
+        }
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugins(EguiPlugin::default());
+        }
+
+        app.add_event::<RequestEdgeCreation>()
+            .init_resource::<InteractionState>()
+            .init_resource::<EdgeCreationConfig>()
+            .init_resource::<EdgeCreation>()
+            .add_systems(
+                Update,
+                (
+                    toggle_edge_creation_mode.run_if(not(egui_wants_keyboard)),
+                    drag_new_edge,
+                    draw_edge_preview,
+                )
+                    .chain()
+                    .in_set(PickingSet::Selection),
+            )
+            .add_systems(EguiPrimaryContextPass, relationship_menu_ui);
+    }
+}
+
+/// System that switches between selecting and creating edges
+pub fn toggle_edge_creation_mode(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    config: Res<EdgeCreationConfig>,
+    mut interaction: ResMut<InteractionState>,
+    mut creation: ResMut<EdgeCreation>,
+) {
+    if !keyboard.just_pressed(config.toggle_key) {
+        return;
+    }
+    interaction.interaction_mode = if interaction.interaction_mode == InteractionMode::CreateEdge {
+        InteractionMode::Select
+    } else {
+        InteractionMode::CreateEdge
+    };
+    *creation = EdgeCreation::default();
+}
+
+/// Run condition: the interaction mode is [`InteractionMode::CreateEdge`].
+/// Selecting with the mouse is gated on its negation.
+pub fn creating_edges(interaction: Option<Res<InteractionState>>) -> bool {
+    interaction.is_some_and(|interaction| interaction.interaction_mode == InteractionMode::CreateEdge)
+}
+
+/// System that starts an edge on a pressed node and finishes it on release
+#[allow(clippy::too_many_arguments)]
+pub fn drag_new_edge(
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    picking: Res<PickingState>,
+    config: Res<EdgeCreationConfig>,
+    interaction: Res<InteractionState>,
+    mut creation: ResMut<EdgeCreation>,
+    windows: Query<&Window>,
+    node_map: Res<NodeEntityMap>,
+    mut requests: EventWriter<RequestEdgeCreation>,
+) {
+    if interaction.interaction_mode != InteractionMode::CreateEdge {
+        creation.drag = None;
+        return;
+    }
+
+    if mouse_button.just_pressed(MouseButton::Left) && creation.menu.is_none() {
+        creation.drag = picking.hovered.and_then(|source| {
+            node_map.get_node(&source).map(|source_id| EdgeDrag { source, source_id: *source_id })
+        });
+    }
+
+    if !mouse_button.just_released(MouseButton::Left) {
+        return;
+    }
+    let Some(drag) = creation.drag.take() else {
+        return;
+    };
+    // Releasing over empty space, or the source itself, cancels
+    let Some(&target_id) = picking.hovered
+        .filter(|target| *target != drag.source)
+        .and_then(|target| node_map.get_node(&target))
+    else {
+        return;
+    };
+
+    if keyboard.any_pressed(config.menu_modifiers.iter().copied()) {
+        let position = cursor_position(windows.iter()).unwrap_or_default();
+        creation.menu = Some(RelationshipMenu {
+            source_id: drag.source_id,
+            target_id,
+            position,
+        });
+    } else {
+        requests.write(RequestEdgeCreation {
+            source_id: drag.source_id,
+            target_id,
+            relationship: config.default_relationship.clone(),
+        });
+    }
+}
+
+/// System that draws a line from the source node to the cursor, snapping to
+/// the node under it
+pub fn draw_edge_preview(
+    mut gizmos: Gizmos,
+    creation: Res<EdgeCreation>,
+    picking: Res<PickingState>,
+    windows: Query<&Window>,
+    cameras: Query<(&Camera, &GlobalTransform), With<GraphCamera>>,
+    transforms: Query<&GlobalTransform, With<NodeVisual>>,
+) {
+    let Some(drag) = creation.drag else {
+        return;
+    };
+    let Ok(source) = transforms.get(drag.source) else {
+        return;
+    };
+    let start = source.translation();
+
+    let target = picking.hovered
+        .filter(|target| *target != drag.source)
+        .and_then(|target| transforms.get(target).ok());
+    if let Some(target) = target {
+        gizmos.line(start, target.translation(), Color::srgb(0.3, 1.0, 0.4));
+        return;
+    }
+
+    // Otherwise end at the cursor, as deep along its ray as the source
+    let Some(ray) = cursor_position(windows.iter()).and_then(|cursor| cursor_ray(cameras.iter(), cursor)) else {
+        return;
+    };
+    let depth = (start - ray.origin).dot(ray.direction.as_vec3());
+    gizmos.line(start, ray.get_point(depth), Color::srgba(1.0, 1.0, 1.0, 0.6));
+}
+
+/// System that shows the relationship menu and sends the chosen edge
+pub fn relationship_menu_ui(
+    mut contexts: EguiContexts,
+    mut creation: ResMut<EdgeCreation>,
+    config: Res<EdgeCreationConfig>,
+    mut requests: EventWriter<RequestEdgeCreation>,
+) {
+    let Some(menu) = creation.menu else {
+        return;
+    };
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    let mut close = ctx.input(|input| input.key_pressed(egui::Key::Escape));
+    let center = egui::pos2(menu.position.x, menu.position.y);
+    egui::Area::new(egui::Id::new("cim_relationship_menu"))
+        .fixed_pos(center - egui::vec2(MENU_RADIUS, MENU_RADIUS) * 1.5)
+        .show(ctx, |ui| {
+            let (_, area) = ui.allocate_space(egui::vec2(MENU_RADIUS, MENU_RADIUS) * 3.0);
+            let count = config.relationships.len().max(1) as f32;
+            for (i, relationship) in config.relationships.iter().enumerate() {
+                // First choice at the top, the rest clockwise
+                let angle = i as f32 / count * std::f32::consts::TAU - std::f32::consts::FRAC_PI_2;
+                let position = area.center() + egui::vec2(angle.cos(), angle.sin()) * MENU_RADIUS;
+                let rect = egui::Rect::from_center_size(position, egui::vec2(90.0, 24.0));
+                if ui.put(rect, egui::Button::new(relationship.to_string())).clicked() {
+                    requests.write(RequestEdgeCreation {
+                        source_id: menu.source_id,
+                        target_id: menu.target_id,
+                        relationship: relationship.clone(),
+                    });
+                    close = true;
+                }
+            }
+            let cancel = egui::Rect::from_center_size(area.center(), egui::vec2(24.0, 24.0));
+            if ui.put(cancel, egui::Button::new("×")).on_hover_text("Cancel").clicked() {
+                close = true;
+            }
+        });
+
+    if close {
+        creation.menu = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use cim_contextgraph::ContextGraphId as GraphId;
+    use crate::morphisms::NodeEntityMapPlugin;
+
+    fn setup() -> (App, [(Entity, NodeId); 2]) {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, NodeEntityMapPlugin))
+            .init_resource::<ButtonInput<MouseButton>>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<PickingState>()
+            .init_resource::<EdgeCreationConfig>()
+            .init_resource::<EdgeCreation>()
+            .insert_resource(InteractionState {
+                interaction_mode: InteractionMode::CreateEdge,
+                ..default()
+            })
+            .add_event::<RequestEdgeCreation>()
+            .add_systems(Update, drag_new_edge);
+
+        let graph_id = GraphId::new();
+        let nodes = [(); 2].map(|_| {
+            let node_id = NodeId::new();
+            (app.world_mut().spawn(NodeVisual { node_id, graph_id }).id(), node_id)
+        });
+        (app, nodes)
+    }
+
+    /// Press on `from`, release on `to`, and return the requested edges
+    fn drag(app: &mut App, from: Option<Entity>, to: Option<Entity>) -> Vec<RequestEdgeCreation> {
+        app.world_mut().resource_mut::<Events<RequestEdgeCreation>>().clear();
+        app.world_mut().resource_mut::<PickingState>().hovered = from;
+        app.world_mut().resource_mut::<ButtonInput<MouseButton>>().press(MouseButton::Left);
+        app.update();
+        assert_eq!(app.world().resource::<EdgeCreation>().drag.is_some(), from.is_some());
+
+        app.world_mut().resource_mut::<ButtonInput<MouseButton>>().clear();
+        app.world_mut().resource_mut::<PickingState>().hovered = to;
+        app.world_mut().resource_mut::<ButtonInput<MouseButton>>().release(MouseButton::Left);
+        app.update();
+        app.world_mut().resource_mut::<ButtonInput<MouseButton>>().clear();
+
+        assert!(app.world().resource::<EdgeCreation>().drag.is_none());
+        app.world()
+            .resource::<Events<RequestEdgeCreation>>()
+            .iter_current_update_events()
+            .cloned()
+            .collect()
+    }
+
+    #[test]
+    fn test_release_on_node_requests_edge() {
+        let (mut app, [(source, source_id), (target, target_id)]) = setup();
+        let requests = drag(&mut app, Some(source), Some(target));
+        assert_eq!(requests, vec![RequestEdgeCreation {
+            source_id,
+            target_id,
+            relationship: EdgeRelationship::DependsOn,
+        }]);
+
+        // With the modifier held, the menu opens instead
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::AltLeft);
+        assert!(drag(&mut app, Some(source), Some(target)).is_empty());
+        let menu = app.world().resource::<EdgeCreation>().menu.unwrap();
+        assert_eq!((menu.source_id, menu.target_id), (source_id, target_id));
+    }
+
+    #[test]
+    fn test_release_over_empty_space_cancels() {
+        let (mut app, [(source, _), _]) = setup();
+        assert!(drag(&mut app, Some(source), None).is_empty());
+        assert!(drag(&mut app, Some(source), Some(source)).is_empty());
+        assert!(drag(&mut app, None, Some(source)).is_empty());
+        assert!(app.world().resource::<EdgeCreation>().menu.is_none());
+    }
+
+    #[test]
+    fn test_creating_edges_follows_the_interaction_mode() {
+        let mut world = World::new();
+        assert!(!world.run_system_once(creating_edges).unwrap());
+
+        world.init_resource::<InteractionState>();
+        assert!(!world.run_system_once(creating_edges).unwrap());
+
+        world.resource_mut::<InteractionState>().interaction_mode = InteractionMode::CreateEdge;
+        assert!(world.run_system_once(creating_edges).unwrap());
+    }
+}
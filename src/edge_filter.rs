@@ -0,0 +1,197 @@
+//! Edge Filtering: Showing edges by relationship
+//!
+//! [`EdgeFilter`] picks the relationships whose edges are shown: with `show`
+//! set only edges of the listed relationships are, otherwise edges of the
+//! listed relationships are hidden. An edge's relationship is its
+//! [`EdgeRelationship`] component; edges without one are always shown.
+//! Rejected edges are marked [`HiddenRelationship`], so they are hidden
+//! alongside the other hidden reasons, and lose their label too.
+//!
+//! [`EdgeFilterPlugin`] adds a window listing every relationship found in
+//! the graph, each with a checkbox that shows or hides its edges. It brings
+//! in the [`CullingPlugin`], which applies the hidden reasons, unless it is
+//! already added.
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiPlugin, EguiPrimaryContextPass};
+use std::collections::HashSet;
+use crate::components::EdgeVisual;
+use crate::culling::{CullingPlugin, HiddenRelationship};
+use crate::events::EdgeRelationship;
+
+/// Which relationships' edges are shown
+#[derive(Resource, Debug, Clone, Default, PartialEq)]
+pub struct EdgeFilter {
+    pub relationships: HashSet<EdgeRelationship>,
+    /// Show only the listed relationships, instead of hiding them
+    pub show: bool,
+}
+
+impl EdgeFilter {
+    /// Filter showing only edges of `relationships`
+    pub fn only(relationships: impl IntoIterator<Item = EdgeRelationship>) -> Self {
+        Self {
+            relationships: relationships.into_iter().collect(),
+            show: true,
+        }
+    }
+
+    pub fn allows(&self, relationship: &EdgeRelationship) -> bool {
+        self.relationships.contains(relationship) == self.show
+    }
+
+    /// Show the edges of `relationship` if they are hidden, or hide them
+    pub fn toggle(&mut self, relationship: &EdgeRelationship) {
+        if !self.relationships.remove(relationship) {
+            self.relationships.insert(relationship.clone());
+        }
+    }
+}
+
+/// Relationships of the labelled edges, in the order they were found
+#[derive(Resource, Debug, Clone, Default)]
+pub struct DiscoveredRelationships(pub Vec<EdgeRelationship>);
+
+/// Plugin that filters edges by relationship, with a window to pick them
+pub struct EdgeFilterPlugin;
+
+impl Plugin for EdgeFilterPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugins(EguiPlugin::default());
+        }
+        if !app.is_plugin_added::<CullingPlugin>() {
+            app.add_plugins(CullingPlugin);
+        }
+
+        app.init_resource::<EdgeFilter>()
+            .init_resource::<DiscoveredRelationships>()
+            .add_systems(Update, (discover_relationships, apply_edge_filter))
+            .add_systems(EguiPrimaryContextPass, edge_filter_ui);
+    }
+}
+
+/// System that collects the relationships of new and changed edges
+pub fn discover_relationships(
+    mut discovered: ResMut<DiscoveredRelationships>,
+    relationships: Query<&EdgeRelationship, (With<EdgeVisual>, Changed<EdgeRelationship>)>,
+) {
+    for relationship in relationships.iter() {
+        if !discovered.0.contains(relationship) {
+            discovered.0.push(relationship.clone());
+        }
+    }
+}
+
+/// System that marks edges the filter rejects as [`HiddenRelationship`],
+/// whenever the filter or an edge's relationship changes
+#[allow(clippy::type_complexity)]
+pub fn apply_edge_filter(
+    mut commands: Commands,
+    filter: Res<EdgeFilter>,
+    changed: Query<(), (With<EdgeVisual>, Changed<EdgeRelationship>)>,
+    edges: Query<(Entity, Option<&EdgeRelationship>, Has<HiddenRelationship>), With<EdgeVisual>>,
+) {
+    if !filter.is_changed() && changed.is_empty() {
+        return;
+    }
+
+    for (entity, relationship, hidden) in edges.iter() {
+        let allowed = relationship.is_none_or(|relationship| filter.allows(relationship));
+        if allowed && hidden {
+            commands.entity(entity).remove::<HiddenRelationship>();
+        } else if !allowed && !hidden {
+            commands.entity(entity).insert(HiddenRelationship);
+        }
+    }
+}
+
+/// System that draws the edge filter window
+pub fn edge_filter_ui(
+    mut contexts: EguiContexts,
+    mut filter: ResMut<EdgeFilter>,
+    discovered: Res<DiscoveredRelationships>,
+) {
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    egui::Window::new("Edge Filter")
+        .id(egui::Id::new("cim_edge_filter"))
+        .default_open(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            if discovered.0.is_empty() {
+                ui.label("No labelled edges");
+                return;
+            }
+            for relationship in &discovered.0 {
+                let mut shown = filter.allows(relationship);
+                if ui.checkbox(&mut shown, relationship.to_string()).changed() {
+                    filter.toggle(relationship);
+                }
+            }
+            ui.separator();
+            if ui.button("Show all").clicked() {
+                *filter = EdgeFilter::default();
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cim_contextgraph::{ContextGraphId as GraphId, EdgeId};
+    use crate::components::EdgeVisualBundle;
+    use crate::culling::apply_hidden_reasons;
+
+    #[test]
+    fn test_filtering_to_one_relationship_hides_the_others() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<EdgeFilter>()
+            .init_resource::<DiscoveredRelationships>()
+            .add_systems(Update, (discover_relationships, apply_edge_filter, apply_hidden_reasons).chain());
+
+        let graph_id = GraphId::new();
+        let (source, target) = (app.world_mut().spawn_empty().id(), app.world_mut().spawn_empty().id());
+        let mut spawn_edge = |label: Option<&str>| {
+            let mut edge = app.world_mut().spawn(EdgeVisualBundle::new(EdgeId::new(), graph_id, source, target));
+            if let Some(label) = label {
+                edge.insert(EdgeRelationship::from(label));
+            }
+            edge.id()
+        };
+        let depends_on = spawn_edge(Some("DependsOn"));
+        let contains = spawn_edge(Some("Contains"));
+        let deployed_to = spawn_edge(Some("DeployedTo"));
+        let unlabelled = spawn_edge(None);
+        app.update();
+        assert_eq!(
+            app.world().resource::<DiscoveredRelationships>().0,
+            [EdgeRelationship::DependsOn, EdgeRelationship::Contains, EdgeRelationship::Custom("DeployedTo".to_string())],
+        );
+
+        app.insert_resource(EdgeFilter::only([EdgeRelationship::DependsOn]));
+        app.update();
+        let visibility = |app: &App, edge: Entity| *app.world().get::<Visibility>(edge).unwrap();
+        assert_eq!(visibility(&app, depends_on), Visibility::Inherited);
+        assert_eq!(visibility(&app, contains), Visibility::Hidden);
+        assert_eq!(visibility(&app, deployed_to), Visibility::Hidden);
+        assert_eq!(visibility(&app, unlabelled), Visibility::Inherited);
+
+        // Ticking a hidden relationship brings its edges back
+        app.world_mut().resource_mut::<EdgeFilter>().toggle(&EdgeRelationship::Contains);
+        app.update();
+        assert_eq!(visibility(&app, contains), Visibility::Inherited);
+        assert_eq!(visibility(&app, deployed_to), Visibility::Hidden);
+    }
+
+    #[test]
+    fn test_plugin_brings_in_culling() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, EdgeFilterPlugin));
+        assert!(app.is_plugin_added::<CullingPlugin>());
+        assert!(app.world().contains_resource::<crate::culling::FreezeCulledLayout>());
+    }
+}
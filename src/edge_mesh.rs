@@ -0,0 +1,377 @@
+//! Edge Meshes: Edges as real, pickable geometry
+//!
+//! In [`EdgeRenderMode::Mesh`] every [`EdgeVisual`] carries a tube mesh
+//! along the same path the gizmo renderer would draw, sized by
+//! [`EdgeStyle::thickness`] and the edge's weight and colored by its style
+//! (or highlight). Dashed
+//! edges get one tube per dash and edges with an arrow a cone at the tip.
+//! Meshes are rebuilt only for edges whose style or curve changed or whose
+//! endpoint nodes moved, so a settled graph costs nothing per frame.
+//!
+//! Meshed edges can be picked: [`EdgeMesh`] keeps the path and radius the
+//! picking system tests the cursor ray against, so clicking an edge that is
+//! nearer than any node sends [`EdgeClicked`](crate::events::EdgeClicked).
+//!
+//! [`EdgeRenderMode::Gizmos`], the default, removes the meshes again and
+//! leaves edges to `render_edges`, which is lighter for very large graphs.
+
+use bevy::asset::RenderAssetUsages;
+use bevy::pbr::{NotShadowCaster, NotShadowReceiver};
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, MeshAabb, PrimitiveTopology};
+use bevy::render::primitives::Aabb;
+use std::collections::{HashMap, HashSet};
+use crate::components::{EdgeStyle, EdgeVisual, Highlighted};
+use crate::edge_systems::{dash_segments, edge_curvature, edge_visual_path, parallel_edge_curvature, DASH_LENGTH};
+use crate::value_objects::EdgeCurve;
+
+/// Sides of the tube around an edge
+const TUBE_SIDES: usize = 8;
+
+/// How edges are drawn
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EdgeRenderMode {
+    /// One tube mesh per edge, pickable
+    Mesh,
+    /// Immediate-mode gizmo lines, one pixel wide and not pickable
+    #[default]
+    Gizmos,
+}
+
+/// The path an edge's mesh was built along, in world space, which picking
+/// tests the cursor ray against
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct EdgeMesh {
+    pub path: Vec<Vec3>,
+    pub radius: f32,
+}
+
+/// Unlit edge materials, one per color
+#[derive(Resource, Debug, Default)]
+pub struct EdgeMeshMaterials {
+    materials: HashMap<[u32; 4], Handle<StandardMaterial>>,
+}
+
+impl EdgeMeshMaterials {
+    /// Edge material drawing `color`
+    pub fn material(&mut self, color: Color, materials: &mut Assets<StandardMaterial>) -> Handle<StandardMaterial> {
+        let key = color.to_linear().to_f32_array().map(f32::to_bits);
+        self.materials
+            .entry(key)
+            .or_insert_with(|| {
+                materials.add(StandardMaterial {
+                    base_color: color,
+                    unlit: true,
+                    alpha_mode: if color.alpha() < 1.0 { AlphaMode::Blend } else { AlphaMode::Opaque },
+                    ..default()
+                })
+            })
+            .clone()
+    }
+}
+
+/// Plugin that gives edges pickable meshes according to [`EdgeRenderMode`]
+pub struct EdgeMeshPlugin;
+
+impl Plugin for EdgeMeshPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EdgeRenderMode>()
+            .init_resource::<EdgeMeshMaterials>()
+            .add_systems(PostUpdate, update_edge_meshes.after(TransformSystem::TransformPropagate));
+    }
+}
+
+/// Tube of `radius` along `path`, one per dash when `dashed`, plus an
+/// arrowhead cone of `arrow_size` at its end when that is non-zero
+pub fn edge_tube_mesh(path: &[Vec3], dashed: bool, radius: f32, arrow_size: f32) -> Mesh {
+    let strands: Vec<Vec<Vec3>> = if dashed {
+        dash_segments(path, DASH_LENGTH).into_iter().map(|(from, to)| vec![from, to]).collect()
+    } else {
+        vec![path.to_vec()]
+    };
+    let mut positions: Vec<[f32; 3]> = Vec::new();
+    let mut normals: Vec<[f32; 3]> = Vec::new();
+    let mut indices: Vec<u32> = Vec::new();
+
+    // Ring of TUBE_SIDES directions around `tangent`, counter-clockwise
+    // when looking back along it, so the faces below wind outwards
+    let ring = |tangent: Vec3| {
+        let tangent = tangent.try_normalize().unwrap_or(Vec3::Y);
+        let u = tangent.any_orthonormal_vector();
+        let v = tangent.cross(u);
+        (0..TUBE_SIDES).map(move |side| {
+            let angle = side as f32 / TUBE_SIDES as f32 * std::f32::consts::TAU;
+            u * angle.cos() + v * angle.sin()
+        })
+    };
+
+    for points in strands.iter().filter(|points| points.len() >= 2) {
+        let base = positions.len() as u32;
+        for (index, point) in points.iter().enumerate() {
+            let tangent = points[(index + 1).min(points.len() - 1)] - points[index.saturating_sub(1)];
+            for normal in ring(tangent) {
+                positions.push((*point + normal * radius).to_array());
+                normals.push(normal.to_array());
+            }
+        }
+        let sides = TUBE_SIDES as u32;
+        for segment in 0..points.len() as u32 - 1 {
+            for side in 0..sides {
+                let a = base + segment * sides + side;
+                let b = base + segment * sides + (side + 1) % sides;
+                let (c, d) = (a + sides, b + sides);
+                indices.extend([a, b, c, b, d, c]);
+            }
+        }
+    }
+
+    let arrow = match path[..] {
+        [.., before, tip] => (tip - before).try_normalize().map(|heading| (tip, heading)),
+        _ => None,
+    };
+    if let Some((tip, heading)) = arrow.filter(|_| arrow_size > 0.0) {
+        let center = tip - heading * arrow_size;
+        let directions: Vec<Vec3> = ring(heading).collect();
+        let sides = TUBE_SIDES as u32;
+
+        // Cone walls, with their own tip vertex per side for the normals
+        let base = positions.len() as u32;
+        for direction in &directions {
+            let normal = (*direction * arrow_size + heading * arrow_size * 0.5).normalize();
+            positions.push((center + *direction * arrow_size * 0.5).to_array());
+            normals.push(normal.to_array());
+            positions.push(tip.to_array());
+            normals.push(normal.to_array());
+        }
+        for side in 0..sides {
+            let next = (side + 1) % sides;
+            indices.extend([base + side * 2, base + next * 2, base + side * 2 + 1]);
+        }
+
+        // Base cap, facing back along the edge
+        let cap = positions.len() as u32;
+        positions.push(center.to_array());
+        normals.push((-heading).to_array());
+        for direction in &directions {
+            positions.push((center + *direction * arrow_size * 0.5).to_array());
+            normals.push((-heading).to_array());
+        }
+        for side in 0..sides {
+            let next = (side + 1) % sides;
+            indices.extend([cap, cap + 1 + next, cap + 1 + side]);
+        }
+    }
+
+    Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+        .with_inserted_indices(Indices::U32(indices))
+}
+
+/// System that builds, updates and removes edge meshes. Only edges that are
+/// new, restyled, re-curved or attached to a node that moved are rebuilt,
+/// and only new, restyled or (un)highlighted edges are recolored.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn update_edge_meshes(
+    mut commands: Commands,
+    mode: Res<EdgeRenderMode>,
+    mut edge_materials: ResMut<EdgeMeshMaterials>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    edges: Query<(
+        Entity,
+        &EdgeVisual,
+        Option<&EdgeStyle>,
+        Option<&EdgeCurve>,
+        Option<&Highlighted>,
+        Option<&Mesh3d>,
+        Option<&MeshMaterial3d<StandardMaterial>>,
+    )>,
+    restyled: Query<(), Or<(Changed<EdgeVisual>, Changed<EdgeStyle>, Changed<EdgeCurve>)>>,
+    recolored: Query<(), Or<(Changed<EdgeVisual>, Changed<EdgeStyle>, Changed<Highlighted>)>>,
+    moved: Query<Entity, Changed<GlobalTransform>>,
+    nodes: Query<&GlobalTransform>,
+    mut removed: RemovedComponents<EdgeVisual>,
+    mut unhighlighted: RemovedComponents<Highlighted>,
+    meshed: Query<Entity, With<EdgeMesh>>,
+) {
+    let unhighlighted: HashSet<Entity> = unhighlighted.read().collect();
+    if *mode == EdgeRenderMode::Gizmos {
+        for entity in meshed.iter() {
+            commands.entity(entity).remove::<(EdgeMesh, Mesh3d, MeshMaterial3d<StandardMaterial>, Aabb)>();
+        }
+        return;
+    }
+
+    // Adding or removing an edge can change how its neighbours fan out
+    let edges_changed = removed.read().count() > 0 || edges.iter().any(|(entity, ..)| {
+        restyled.contains(entity) && !meshed.contains(entity)
+    });
+    let rebuild_all = mode.is_changed() || edges_changed;
+    let moved: HashSet<Entity> = moved.iter().collect();
+    let dirty: HashSet<Entity> = edges.iter()
+        .filter(|(entity, edge, ..)| {
+            rebuild_all
+                || !meshed.contains(*entity)
+                || restyled.contains(*entity)
+                || moved.contains(&edge.source_entity)
+                || moved.contains(&edge.target_entity)
+        })
+        .map(|(entity, ..)| entity)
+        .collect();
+    let auto_curvature = if dirty.is_empty() {
+        HashMap::new()
+    } else {
+        parallel_edge_curvature(edges.iter().map(|(entity, edge, ..)| (entity, edge)))
+    };
+    let default_style = EdgeStyle::default();
+
+    for (entity, edge, style, curve, highlighted, mesh, material) in edges.iter() {
+        let style = style.unwrap_or(&default_style);
+
+        if material.is_none() || recolored.contains(entity) || unhighlighted.contains(&entity) {
+            let color = highlighted.map_or(style.color, |highlight| highlight.color);
+            let wanted = edge_materials.material(color, &mut materials);
+            if material.is_none_or(|material| material.0 != wanted) {
+                commands.entity(entity).insert(MeshMaterial3d(wanted));
+            }
+        }
+
+        if !dirty.contains(&entity) {
+            continue;
+        }
+        let (Ok(source), Ok(target)) = (nodes.get(edge.source_entity), nodes.get(edge.target_entity)) else {
+            continue;
+        };
+        let path = edge_visual_path(
+            source.translation(),
+            target.translation(),
+            edge.source_entity == edge.target_entity,
+            style.curve_type,
+            edge_curvature(entity, curve, &auto_curvature),
+            curve.and_then(|curve| curve.control_point),
+        );
+        let radius = style.thickness * 0.5 * edge.effective_weight();
+        let tube = edge_tube_mesh(&path, style.dashed, radius, style.arrow_size);
+        let aabb = tube.compute_aabb();
+        match mesh.and_then(|mesh| meshes.get_mut(&mesh.0)) {
+            Some(existing) => *existing = tube,
+            None => {
+                commands.entity(entity).insert((Mesh3d(meshes.add(tube)), NotShadowCaster, NotShadowReceiver));
+            }
+        }
+        let mut entity_commands = commands.entity(entity);
+        entity_commands.insert(EdgeMesh { path, radius });
+        match aabb {
+            Some(aabb) => entity_commands.insert(aabb),
+            None => entity_commands.remove::<Aabb>(),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cim_contextgraph::{EdgeId, NodeId, ContextGraphId as GraphId};
+    use crate::components::NodeVisual;
+
+    #[test]
+    fn test_edge_meshes_follow_moved_nodes_only() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default(), TransformPlugin))
+            .init_asset::<Mesh>()
+            .init_asset::<StandardMaterial>()
+            .insert_resource(EdgeRenderMode::Mesh)
+            .add_plugins(EdgeMeshPlugin);
+
+        let graph_id = GraphId::new();
+        let nodes: Vec<Entity> = [Vec3::ZERO, Vec3::new(4.0, 0.0, 0.0), Vec3::new(0.0, 4.0, 0.0), Vec3::new(4.0, 4.0, 0.0)]
+            .into_iter()
+            .map(|position| {
+                app.world_mut()
+                    .spawn((NodeVisual { node_id: NodeId::new(), graph_id }, Transform::from_translation(position)))
+                    .id()
+            })
+            .collect();
+        let mut spawn_edge = |source: Entity, target: Entity| {
+            app.world_mut().spawn((
+                EdgeVisual { edge_id: EdgeId::new(), graph_id, source_entity: source, target_entity: target, weight: 1.0 },
+                EdgeStyle { thickness: 0.2, ..default() },
+            )).id()
+        };
+        let moving = spawn_edge(nodes[0], nodes[1]);
+        let still = spawn_edge(nodes[2], nodes[3]);
+        app.update();
+
+        let mesh = app.world().get::<EdgeMesh>(moving).unwrap().clone();
+        assert_eq!(mesh.radius, 0.1);
+        assert_eq!(mesh.path.first(), Some(&Vec3::new(0.5, 0.0, 0.0)));
+        assert_eq!(mesh.path.last(), Some(&Vec3::new(3.5, 0.0, 0.0)));
+        assert!(app.world().get::<Mesh3d>(moving).is_some());
+        let built = |app: &App, edge: Entity| app.world().entity(edge).get_change_ticks::<EdgeMesh>().unwrap().changed;
+        let still_built = built(&app, still);
+
+        app.world_mut().get_mut::<Transform>(nodes[1]).unwrap().translation = Vec3::new(4.0, -2.0, 0.0);
+        app.update();
+        assert_ne!(app.world().get::<EdgeMesh>(moving).unwrap().path, mesh.path);
+        assert_eq!(built(&app, still), still_built);
+
+        // Gizmo mode takes the meshes away again
+        *app.world_mut().resource_mut::<EdgeRenderMode>() = EdgeRenderMode::Gizmos;
+        app.update();
+        assert!(app.world().get::<EdgeMesh>(moving).is_none());
+        assert!(app.world().get::<Mesh3d>(still).is_none());
+    }
+
+    #[test]
+    fn test_heavier_edges_get_thicker_tubes_and_keep_their_material() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default(), TransformPlugin))
+            .init_asset::<Mesh>()
+            .init_asset::<StandardMaterial>()
+            .insert_resource(EdgeRenderMode::Mesh)
+            .add_plugins(EdgeMeshPlugin);
+
+        let graph_id = GraphId::new();
+        let source = app.world_mut().spawn((NodeVisual { node_id: NodeId::new(), graph_id }, Transform::default())).id();
+        let target = app.world_mut()
+            .spawn((NodeVisual { node_id: NodeId::new(), graph_id }, Transform::from_xyz(4.0, 0.0, 0.0)))
+            .id();
+        let edge = app.world_mut().spawn((
+            EdgeVisual { edge_id: EdgeId::new(), graph_id, source_entity: source, target_entity: target, weight: 3.0 },
+            EdgeStyle { thickness: 0.2, ..default() },
+        )).id();
+        app.update();
+        assert!((app.world().get::<EdgeMesh>(edge).unwrap().radius - 0.3).abs() < 1e-6);
+
+        let material_changed = |app: &App| {
+            app.world().entity(edge).get_change_ticks::<MeshMaterial3d<StandardMaterial>>().unwrap().changed
+        };
+        let colored = material_changed(&app);
+        app.update();
+        assert_eq!(material_changed(&app), colored);
+
+        // A highlight recolors the edge, and taking it away restores the style
+        let plain = app.world().get::<MeshMaterial3d<StandardMaterial>>(edge).unwrap().0.clone();
+        app.world_mut().entity_mut(edge).insert(Highlighted { color: Color::srgb(1.0, 0.0, 0.0), intensity: 1.0 });
+        app.update();
+        assert_ne!(app.world().get::<MeshMaterial3d<StandardMaterial>>(edge).unwrap().0, plain);
+        app.world_mut().entity_mut(edge).remove::<Highlighted>();
+        app.update();
+        assert_eq!(app.world().get::<MeshMaterial3d<StandardMaterial>>(edge).unwrap().0, plain);
+    }
+
+    #[test]
+    fn test_arrowhead_ends_at_the_edge_tip() {
+        let path = [Vec3::ZERO, Vec3::new(2.0, 0.0, 0.0)];
+        let aabb = edge_tube_mesh(&path, false, 0.05, 0.2).compute_aabb().unwrap();
+        assert!((aabb.max().x - 2.0).abs() < 1e-5);
+        // The cone is wider than the tube
+        assert!(aabb.max().y > 0.09);
+        assert!(edge_tube_mesh(&path, false, 0.05, 0.0).compute_aabb().unwrap().max().y <= 0.05 + 1e-5);
+        // Dashes leave gaps, but the arrow still sits on the tip
+        let dashed = edge_tube_mesh(&path, true, 0.05, 0.2);
+        assert!(dashed.count_vertices() > edge_tube_mesh(&path, false, 0.05, 0.2).count_vertices());
+        assert!((dashed.compute_aabb().unwrap().max().x - 2.0).abs() < 1e-5);
+    }
+}
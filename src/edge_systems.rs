@@ -3,8 +3,92 @@
 //! This module provides systems for updating and managing edge states based on various conditions.
 
 use bevy::prelude::*;
-use crate::components::{EdgeVisual, EdgeState, EdgeStyle, FlowDirection};
+use std::collections::HashMap;
+use crate::components::{EdgeCurveType, EdgeLabel, EdgeVisual, EdgeState, EdgeStyle, FlowDirection, GraphCamera, Highlighted};
+use crate::culling::{CollapsedMember, HiddenRelationship};
+use crate::picking::world_to_screen;
 use crate::resources::ActiveGraph;
+use crate::value_objects::EdgeCurve;
+
+/// Number of line segments used to tessellate curved edges
+const CURVE_SEGMENTS: usize = 16;
+
+/// Curvature added between each pair of parallel edges so they don't overlap
+const PARALLEL_EDGE_CURVATURE: f32 = 0.2;
+
+/// Distance edges stop short of node centers, so arrowheads stay visible
+const EDGE_END_INSET: f32 = 0.5;
+
+/// Height of a self-loop above its node, before any curvature
+const SELF_LOOP_SIZE: f32 = 1.0;
+
+/// Length of each dash (and the gap after it) for dashed edges
+pub(crate) const DASH_LENGTH: f32 = 0.2;
+
+/// Most side-by-side strands a heavy edge is drawn with
+const MAX_EDGE_STRANDS: usize = 5;
+
+/// Gap between the strands of a heavy edge
+const EDGE_STRAND_SPACING: f32 = 0.04;
+
+/// Default [`EdgeLabelDistance`]
+const EDGE_LABEL_MAX_CAMERA_DISTANCE: f32 = 40.0;
+
+/// Whether edge bundling runs, next to the layout choice; bundling is
+/// skipped entirely while this is off
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EdgeBundling(pub bool);
+
+/// Tuning for force-directed edge bundling
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct BundlingConfig {
+    /// How far, from 0 to just below 1, control points are pulled towards
+    /// those of compatible edges
+    pub strength: f32,
+    /// Relaxation passes each time the edges are bundled
+    pub iterations: usize,
+}
+
+impl Default for BundlingConfig {
+    fn default() -> Self {
+        Self {
+            strength: 0.6,
+            iterations: 20,
+        }
+    }
+}
+
+/// Marks an edge whose `EdgeCurve.control_point` was set by bundling, so it
+/// can be cleared when bundling is switched off. Edges with a control point
+/// but without this marker were set explicitly and are never bundled.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct BundledEdge;
+
+/// Global toggle for edge labels
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct ShowEdgeLabels(pub bool);
+
+impl Default for ShowEdgeLabels {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+/// Edge labels further than this from the camera are hidden
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct EdgeLabelDistance(pub f32);
+
+impl Default for EdgeLabelDistance {
+    fn default() -> Self {
+        Self(EDGE_LABEL_MAX_CAMERA_DISTANCE)
+    }
+}
+
+/// Screen-space text entity displaying an edge's `EdgeLabel`
+#[derive(Component, Debug)]
+pub struct EdgeLabelText {
+    pub edge: Entity,
+}
 
 /// System to update edge visualization based on edge state
 pub fn update_edge_visualization(
@@ -27,7 +111,7 @@ pub fn update_edge_visualization(
                 }
                 
                 // Update thickness based on weight
-                edge_style.thickness *= edge_state.weight;
+                edge_style.thickness *= edge_state.weight * edge_visual.effective_weight();
                 
                 // Update arrow size based on flow direction
                 match edge_state.flow_direction {
@@ -117,4 +201,652 @@ pub fn animate_edge_flow(
             edge_style.color.set_alpha(0.5 + intensity * 0.5);
         }
     }
-}
\ No newline at end of file
+}
+
+/// Direction perpendicular to `direction` used to bend curves and arrowheads
+fn bend_axis(direction: Vec3) -> Vec3 {
+    direction.cross(Vec3::Z)
+        .try_normalize()
+        .or_else(|| direction.cross(Vec3::Y).try_normalize())
+        .unwrap_or(Vec3::X)
+}
+
+/// Polyline for an edge from `start` to `end`.
+///
+/// `curvature` bends the edge sideways by that fraction of its length; an
+/// explicit `control_point` (in the XY plane) overrides it for Straight and
+/// Bezier edges. Straight edges with a non-zero curvature or a control point
+/// are drawn as Bezier curves.
+pub fn edge_path(
+    start: Vec3,
+    end: Vec3,
+    curve_type: EdgeCurveType,
+    curvature: f32,
+    control_point: Option<Vec2>,
+) -> Vec<Vec3> {
+    let offset = end - start;
+    let bend = bend_axis(offset) * curvature * offset.length();
+    let midpoint = start.lerp(end, 0.5);
+
+    let sample = |f: &dyn Fn(f32) -> Vec3| -> Vec<Vec3> {
+        (0..=CURVE_SEGMENTS)
+            .map(|i| f(i as f32 / CURVE_SEGMENTS as f32))
+            .collect()
+    };
+
+    match curve_type {
+        EdgeCurveType::Straight if curvature == 0.0 && control_point.is_none() => vec![start, end],
+        EdgeCurveType::Straight | EdgeCurveType::Bezier => {
+            let control = control_point
+                .map(|point| point.extend(midpoint.z))
+                // A quadratic curve peaks halfway to its control point
+                .unwrap_or(midpoint + bend * 2.0);
+            sample(&|t| {
+                let u = 1.0 - t;
+                start * (u * u) + control * (2.0 * u * t) + end * (t * t)
+            })
+        }
+        EdgeCurveType::Arc => {
+            sample(&|t| start.lerp(end, t) + bend * (std::f32::consts::PI * t).sin())
+        }
+        EdgeCurveType::Step => vec![
+            start,
+            Vec3::new(midpoint.x, start.y, start.z),
+            Vec3::new(midpoint.x, end.y, end.z),
+            end,
+        ],
+    }
+}
+
+/// Polyline for a self-loop on the node at `center`: a teardrop above the
+/// node, leaving and re-entering it at the inset. Each step of `curvature`
+/// makes the loop larger, so several loops on one node nest.
+pub fn self_loop_path(center: Vec3, curvature: f32) -> Vec<Vec3> {
+    let size = SELF_LOOP_SIZE * (1.0 + curvature.abs() / PARALLEL_EDGE_CURVATURE * 0.5);
+    let (sin, cos) = std::f32::consts::FRAC_PI_6.sin_cos();
+    let start = center + (Vec3::Y * cos - Vec3::X * sin) * EDGE_END_INSET;
+    let end = center + (Vec3::Y * cos + Vec3::X * sin) * EDGE_END_INSET;
+    let start_control = start + (Vec3::Y - Vec3::X * 0.5) * size * 2.0;
+    let end_control = end + (Vec3::Y + Vec3::X * 0.5) * size * 2.0;
+
+    (0..=CURVE_SEGMENTS)
+        .map(|i| {
+            let t = i as f32 / CURVE_SEGMENTS as f32;
+            let u = 1.0 - t;
+            start * (u * u * u)
+                + start_control * (3.0 * u * u * t)
+                + end_control * (3.0 * u * t * t)
+                + end * (t * t * t)
+        })
+        .collect()
+}
+
+/// Polyline drawn for an edge between nodes at `source` and `target`: a
+/// self-loop when they are the same node, otherwise [`edge_path`] between
+/// points inset from the node centers
+pub fn edge_visual_path(
+    source: Vec3,
+    target: Vec3,
+    self_loop: bool,
+    curve_type: EdgeCurveType,
+    curvature: f32,
+    control_point: Option<Vec2>,
+) -> Vec<Vec3> {
+    if self_loop {
+        return self_loop_path(source, curvature);
+    }
+    let direction = (target - source).normalize_or_zero();
+    let inset = EDGE_END_INSET.min(source.distance(target) / 2.0);
+    edge_path(source + direction * inset, target - direction * inset, curve_type, curvature, control_point)
+}
+
+/// Curvature for the `index`th edge created between a pair of nodes, in
+/// the frame of the pair's lower entity: the first edge is straight, later
+/// ones alternate sides, bending further each time
+pub fn parallel_edge_offset(index: usize) -> f32 {
+    let step = index.div_ceil(2) as f32 * PARALLEL_EDGE_CURVATURE;
+    if index % 2 == 1 { step } else { -step }
+}
+
+/// Sideways offsets of the strands an edge of `weight` is drawn with: one
+/// strand per unit of weight, centered on the edge's path
+pub fn edge_strand_offsets(weight: f32) -> Vec<f32> {
+    let strands = (weight.round().max(1.0) as usize).min(MAX_EDGE_STRANDS);
+    let center = (strands - 1) as f32 / 2.0;
+    (0..strands).map(|strand| (strand as f32 - center) * EDGE_STRAND_SPACING).collect()
+}
+
+/// Split a polyline into dash segments of `dash_length`, separated by gaps
+/// of the same length
+pub fn dash_segments(points: &[Vec3], dash_length: f32) -> Vec<(Vec3, Vec3)> {
+    let mut segments = Vec::new();
+    let mut drawing = true;
+    let mut remaining = dash_length;
+
+    for pair in points.windows(2) {
+        let (mut from, to) = (pair[0], pair[1]);
+        let mut length = from.distance(to);
+        while length > 0.0 {
+            let step = remaining.min(length);
+            let next = from.lerp(to, step / length);
+            if drawing {
+                segments.push((from, next));
+            }
+            from = next;
+            length -= step;
+            remaining -= step;
+            if remaining <= f32::EPSILON {
+                drawing = !drawing;
+                remaining = dash_length;
+            }
+        }
+    }
+    segments
+}
+
+/// Curvature for each edge so that edges sharing the same pair of nodes fan
+/// out instead of overlapping, for edges spawned without an `EdgeCurve`
+pub(crate) fn parallel_edge_curvature<'a>(
+    edges: impl IntoIterator<Item = (Entity, &'a EdgeVisual)>,
+) -> HashMap<Entity, f32> {
+    let mut groups: HashMap<(Entity, Entity), Vec<(Entity, bool)>> = HashMap::new();
+    for (entity, edge) in edges {
+        let reversed = edge.source_entity > edge.target_entity;
+        let key = if reversed {
+            (edge.target_entity, edge.source_entity)
+        } else {
+            (edge.source_entity, edge.target_entity)
+        };
+        groups.entry(key).or_default().push((entity, reversed));
+    }
+
+    let mut curvature = HashMap::new();
+    for mut group in groups.into_values() {
+        group.sort();
+        let center = (group.len() - 1) as f32 / 2.0;
+        for (index, (entity, reversed)) in group.into_iter().enumerate() {
+            // Reversed edges bend the other way in their own frame, which is
+            // the same side in the shared frame
+            let offset = (index as f32 - center) * PARALLEL_EDGE_CURVATURE;
+            curvature.insert(entity, if reversed { -offset } else { offset });
+        }
+    }
+    curvature
+}
+
+/// Bundled control points for edges with the given XY `segments`, starting
+/// from `initial` control points.
+///
+/// Each pass pulls every control point from its initial position towards
+/// the control points of compatible edges — similar direction, length and
+/// position — by up to `strength`. Keeping the pull anchored to the initial
+/// point means edges never collapse onto each other entirely.
+pub fn bundle_control_points(
+    segments: &[(Vec2, Vec2)],
+    initial: &[Vec2],
+    config: &BundlingConfig,
+) -> Vec<Vec2> {
+    let strength = config.strength.clamp(0.0, 0.99);
+    let count = segments.len().min(initial.len());
+
+    let mut compatibility = vec![vec![0.0; count]; count];
+    for i in 0..count {
+        for j in (i + 1)..count {
+            let weight = edge_compatibility(segments[i], segments[j]);
+            compatibility[i][j] = weight;
+            compatibility[j][i] = weight;
+        }
+    }
+
+    let mut control_points = initial[..count].to_vec();
+    for _ in 0..config.iterations {
+        control_points = (0..count)
+            .map(|i| {
+                let total: f32 = compatibility[i].iter().sum();
+                if total <= 0.0 {
+                    return initial[i];
+                }
+                let attraction = compatibility[i].iter()
+                    .zip(&control_points)
+                    .map(|(weight, point)| *point * *weight)
+                    .sum::<Vec2>() / total;
+                initial[i].lerp(attraction, strength * total / (total + 1.0))
+            })
+            .collect();
+    }
+    control_points
+}
+
+/// How strongly two edges should bundle, from 0 to 1: the product of their
+/// angle, scale and position compatibility
+fn edge_compatibility((p0, p1): (Vec2, Vec2), (q0, q1): (Vec2, Vec2)) -> f32 {
+    let (p, q) = (p1 - p0, q1 - q0);
+    let (p_length, q_length) = (p.length(), q.length());
+    if p_length <= f32::EPSILON || q_length <= f32::EPSILON {
+        return 0.0;
+    }
+
+    let angle = (p.dot(q) / (p_length * q_length)).abs();
+    let average = (p_length + q_length) / 2.0;
+    let scale = 2.0 / (average / p_length.min(q_length) + p_length.max(q_length) / average);
+    let midpoint_distance = p0.lerp(p1, 0.5).distance(q0.lerp(q1, 0.5));
+    let position = average / (average + midpoint_distance);
+    angle * scale * position
+}
+
+/// Curvature of an edge: its `EdgeCurve`'s when it has one, otherwise the
+/// automatic fan-out
+pub(crate) fn edge_curvature(entity: Entity, curve: Option<&EdgeCurve>, auto_curvature: &HashMap<Entity, f32>) -> f32 {
+    curve.map_or_else(
+        || auto_curvature.get(&entity).copied().unwrap_or(0.0),
+        |curve| curve.curvature,
+    )
+}
+
+/// System that bundles near-parallel edges by setting their
+/// `EdgeCurve.control_point`, and clears the bundled control points again
+/// when [`EdgeBundling`] is switched off.
+///
+/// Bundling only reruns when edges are added, changed or removed, or one of
+/// their nodes moves. Edges with an explicitly set control point keep it.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn bundle_edges(
+    mut commands: Commands,
+    bundling: Res<EdgeBundling>,
+    config: Res<BundlingConfig>,
+    mut edges: Query<(Entity, &EdgeVisual, Option<&mut EdgeCurve>, Has<BundledEdge>)>,
+    changed_edges: Query<(), Or<(Changed<EdgeVisual>, (Changed<EdgeCurve>, Without<BundledEdge>))>>,
+    mut removed_edges: RemovedComponents<EdgeVisual>,
+    nodes: Query<Ref<GlobalTransform>>,
+) {
+    let edges_removed = removed_edges.read().count() > 0;
+    if !bundling.0 {
+        if !bundling.is_changed() {
+            return;
+        }
+        for (entity, _, curve, bundled) in edges.iter_mut() {
+            if bundled {
+                if let Some(mut curve) = curve {
+                    curve.control_point = None;
+                }
+                commands.entity(entity).remove::<BundledEdge>();
+            }
+        }
+        return;
+    }
+
+    let nodes_moved = edges.iter().any(|(_, edge, ..)| {
+        [edge.source_entity, edge.target_entity].into_iter()
+            .any(|node| nodes.get(node).is_ok_and(|transform| transform.is_changed()))
+    });
+    if !bundling.is_changed() && !config.is_changed() && changed_edges.is_empty() && !edges_removed && !nodes_moved {
+        return;
+    }
+
+    let auto_curvature = parallel_edge_curvature(edges.iter().map(|(entity, edge, ..)| (entity, edge)));
+    let mut bundled = Vec::new();
+    let mut segments = Vec::new();
+    let mut initial = Vec::new();
+    for (entity, edge, curve, is_bundled) in edges.iter() {
+        // Self-loops keep their own shape, as do explicitly placed edges
+        let explicit = !is_bundled && curve.is_some_and(|curve| curve.control_point.is_some());
+        if edge.source_entity == edge.target_entity || explicit {
+            continue;
+        }
+        let (Ok(source), Ok(target)) = (nodes.get(edge.source_entity), nodes.get(edge.target_entity)) else {
+            continue;
+        };
+        let (source, target) = (source.translation(), target.translation());
+        let offset = target - source;
+        let curvature = edge_curvature(entity, curve, &auto_curvature);
+        // Where `edge_path` would put the control point of the fanned-out edge
+        let control = source.lerp(target, 0.5) + bend_axis(offset) * curvature * offset.length() * 2.0;
+
+        bundled.push(entity);
+        segments.push((source.truncate(), target.truncate()));
+        initial.push(control.truncate());
+    }
+
+    let control_points = bundle_control_points(&segments, &initial, &config);
+    for (entity, control_point) in bundled.into_iter().zip(control_points) {
+        let Ok((_, _, curve, is_bundled)) = edges.get_mut(entity) else {
+            continue;
+        };
+        match curve {
+            Some(mut curve) => curve.control_point = Some(control_point),
+            None => {
+                // Keep the fan-out, for when bundling is switched off again
+                commands.entity(entity).insert(EdgeCurve {
+                    control_point: Some(control_point),
+                    curvature: auto_curvature.get(&entity).copied().unwrap_or(0.0),
+                });
+            }
+        }
+        if !is_bundled {
+            commands.entity(entity).insert(BundledEdge);
+        }
+    }
+}
+
+/// System to draw edges with gizmos according to their `EdgeStyle` and
+/// optional `EdgeCurve`. Edges without an `EdgeCurve` that share their
+/// nodes with others are fanned out automatically, and heavier edges are
+/// drawn as several side-by-side strands. Runs in place of the edge meshes
+/// in `EdgeRenderMode::Gizmos`.
+pub fn render_edges(
+    mut gizmos: Gizmos,
+    edges: Query<(Entity, &EdgeVisual, Option<&EdgeStyle>, Option<&EdgeCurve>, Option<&Highlighted>), (Without<CollapsedMember>, Without<HiddenRelationship>)>,
+    nodes: Query<&GlobalTransform>,
+) {
+    let auto_curvature = parallel_edge_curvature(edges.iter().map(|(entity, edge, ..)| (entity, edge)));
+    let default_style = EdgeStyle::default();
+
+    for (entity, edge, style, curve, highlighted) in edges.iter() {
+        let (Ok(source), Ok(target)) = (nodes.get(edge.source_entity), nodes.get(edge.target_entity)) else {
+            continue;
+        };
+        let style = style.unwrap_or(&default_style);
+        let color = highlighted.map_or(style.color, |highlight| highlight.color);
+
+        let points = edge_visual_path(
+            source.translation(),
+            target.translation(),
+            edge.source_entity == edge.target_entity,
+            style.curve_type,
+            edge_curvature(entity, curve, &auto_curvature),
+            curve.and_then(|curve| curve.control_point),
+        );
+
+        let side = match (points.first(), points.last()) {
+            (Some(first), Some(last)) => bend_axis(*last - *first),
+            _ => continue,
+        };
+        for offset in edge_strand_offsets(edge.effective_weight()) {
+            let strand: Vec<Vec3> = points.iter().map(|point| *point + side * offset).collect();
+            if style.dashed {
+                for (from, to) in dash_segments(&strand, DASH_LENGTH) {
+                    gizmos.line(from, to, color);
+                }
+            } else {
+                gizmos.linestrip(strand, color);
+            }
+        }
+
+        // Arrowhead along the final segment
+        if style.arrow_size > 0.0 {
+            if let [.., before, tip] = points[..] {
+                let Some(heading) = (tip - before).try_normalize() else {
+                    continue;
+                };
+                let base = tip - heading * style.arrow_size;
+                let wing = bend_axis(heading) * style.arrow_size * 0.5;
+                gizmos.line(tip, base + wing, color);
+                gizmos.line(tip, base - wing, color);
+                gizmos.line(base + wing, base - wing, color);
+            }
+        }
+    }
+}
+
+/// System to spawn a label text entity for each newly labelled edge
+pub fn spawn_edge_labels(
+    mut commands: Commands,
+    edges: Query<(Entity, &EdgeLabel), Added<EdgeLabel>>,
+) {
+    for (edge, label) in edges.iter() {
+        commands.spawn((
+            Text::new(label.text.clone()),
+            TextFont {
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.85, 0.85, 0.85)),
+            Node {
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            Visibility::Hidden,
+            EdgeLabelText { edge },
+        ));
+    }
+}
+
+/// System to keep edge labels over their edge's midpoint.
+///
+/// Labels are UI text projected from the midpoint each frame, so they always
+/// face the camera. They are hidden when labels are switched off, the edge
+/// is off-screen or filtered out, or the camera is further away than the
+/// [`EdgeLabelDistance`].
+pub fn update_edge_labels(
+    mut commands: Commands,
+    show: Res<ShowEdgeLabels>,
+    max_distance: Res<EdgeLabelDistance>,
+    cameras: Query<(&Camera, &GlobalTransform), With<GraphCamera>>,
+    edges: Query<(&EdgeVisual, &EdgeLabel, Has<HiddenRelationship>)>,
+    nodes: Query<&GlobalTransform>,
+    mut labels: Query<(Entity, &EdgeLabelText, &mut Text, &mut Node, &mut Visibility, &ComputedNode)>,
+) {
+    let camera = cameras.iter().find(|(camera, _)| camera.is_active);
+
+    for (entity, label_text, mut text, mut node, mut visibility, computed) in labels.iter_mut() {
+        let Ok((edge, label, filtered_out)) = edges.get(label_text.edge) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+        if text.0 != label.text {
+            text.0.clone_from(&label.text);
+        }
+
+        let screen_position = camera.filter(|_| show.0 && !filtered_out).and_then(|(camera, camera_transform)| {
+            let source = nodes.get(edge.source_entity).ok()?.translation();
+            let target = nodes.get(edge.target_entity).ok()?.translation();
+            let midpoint = source.lerp(target, 0.5);
+            if camera_transform.translation().distance(midpoint) > max_distance.0 {
+                return None;
+            }
+            let position = world_to_screen(camera, camera_transform, midpoint)?;
+            camera.logical_viewport_rect()?.contains(position).then_some(position)
+        });
+
+        match screen_position {
+            Some(position) => {
+                // Center the text on the midpoint
+                let size = computed.size() * computed.inverse_scale_factor();
+                node.left = Val::Px(position.x - size.x / 2.0);
+                node.top = Val::Px(position.y - size.y / 2.0);
+                visibility.set_if_neq(Visibility::Inherited);
+            }
+            None => {
+                visibility.set_if_neq(Visibility::Hidden);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_straight_edge_is_single_segment() {
+        let path = edge_path(Vec3::ZERO, Vec3::X, EdgeCurveType::Straight, 0.0, None);
+        assert_eq!(path, vec![Vec3::ZERO, Vec3::X]);
+    }
+
+    #[test]
+    fn test_bezier_edge_bends_by_curvature() {
+        let end = Vec3::new(4.0, 0.0, 0.0);
+        let path = edge_path(Vec3::ZERO, end, EdgeCurveType::Bezier, 0.25, None);
+
+        assert_eq!(path.len(), CURVE_SEGMENTS + 1);
+        assert_eq!(path[0], Vec3::ZERO);
+        assert!(path[CURVE_SEGMENTS].distance(end) < 1e-5);
+        let middle = path[CURVE_SEGMENTS / 2];
+        assert!((middle.distance(Vec3::new(2.0, 0.0, 0.0)) - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_self_loops_rise_above_their_node_and_nest() {
+        let center = Vec3::new(1.0, 2.0, 0.0);
+        let first = self_loop_path(center, 0.0);
+        let second = self_loop_path(center, parallel_edge_offset(1));
+
+        assert!(first.iter().all(|point| point.is_finite() && point.y >= center.y));
+        assert!((first[0].distance(center) - EDGE_END_INSET).abs() < 1e-5);
+        assert!((first[CURVE_SEGMENTS].distance(center) - EDGE_END_INSET).abs() < 1e-5);
+        let peak = |path: &[Vec3]| path.iter().map(|point| point.y).fold(f32::MIN, f32::max);
+        assert!(peak(&second) > peak(&first) + 0.1);
+    }
+
+    #[test]
+    fn test_dashes_cover_half_the_length() {
+        let points = [Vec3::ZERO, Vec3::new(1.0, 0.0, 0.0), Vec3::new(2.0, 0.0, 0.0)];
+        let dashes = dash_segments(&points, 0.25);
+
+        let drawn: f32 = dashes.iter().map(|(from, to)| from.distance(*to)).sum();
+        assert!((drawn - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_parallel_edges_fan_out() {
+        let a = Entity::from_raw(1);
+        let b = Entity::from_raw(2);
+        let edge = |source, target| EdgeVisual {
+            edge_id: cim_contextgraph::EdgeId::new(),
+            graph_id: cim_contextgraph::ContextGraphId::new(),
+            source_entity: source,
+            target_entity: target,
+            weight: 1.0,
+        };
+        let edges = [
+            (Entity::from_raw(10), edge(a, b)),
+            (Entity::from_raw(11), edge(b, a)),
+            (Entity::from_raw(12), edge(a, Entity::from_raw(3))),
+        ];
+
+        let curvature = parallel_edge_curvature(edges.iter().map(|(entity, edge)| (*entity, edge)));
+        assert_eq!(curvature[&Entity::from_raw(12)], 0.0);
+        let first = curvature[&Entity::from_raw(10)];
+        let second = curvature[&Entity::from_raw(11)];
+        assert!(first != 0.0);
+        // The bend axis flips with edge direction, so equal curvature puts
+        // a -> b and b -> a on opposite sides
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_edge_labels_follow_edges() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<ShowEdgeLabels>()
+            .init_resource::<EdgeLabelDistance>()
+            .add_systems(Update, (spawn_edge_labels, update_edge_labels).chain());
+
+        let source = app.world_mut().spawn(GlobalTransform::default()).id();
+        let target = app.world_mut().spawn(GlobalTransform::from_xyz(2.0, 0.0, 0.0)).id();
+        let edge = app.world_mut().spawn((
+            EdgeVisual {
+                edge_id: cim_contextgraph::EdgeId::new(),
+                graph_id: cim_contextgraph::ContextGraphId::new(),
+                source_entity: source,
+                target_entity: target,
+                weight: 1.0,
+            },
+            EdgeLabel { text: "DependsOn".to_string() },
+        )).id();
+        app.update();
+
+        let mut labels = app.world_mut().query::<(&EdgeLabelText, &Text)>();
+        let (label, text) = labels.single(app.world()).unwrap();
+        assert_eq!(label.edge, edge);
+        assert_eq!(text.0, "DependsOn");
+
+        app.world_mut().entity_mut(edge).despawn();
+        app.update();
+        assert_eq!(labels.iter(app.world()).count(), 0);
+    }
+
+    #[test]
+    fn test_parallel_edges_keep_distinct_control_points_when_bundled() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(EdgeBundling(true))
+            .init_resource::<BundlingConfig>()
+            .add_systems(Update, bundle_edges);
+
+        let source = app.world_mut().spawn(GlobalTransform::default()).id();
+        let target = app.world_mut().spawn(GlobalTransform::from_xyz(4.0, 0.0, 0.0)).id();
+        let mut spawn_edge = || {
+            app.world_mut().spawn(EdgeVisual {
+                edge_id: cim_contextgraph::EdgeId::new(),
+                graph_id: cim_contextgraph::ContextGraphId::new(),
+                source_entity: source,
+                target_entity: target,
+                weight: 1.0,
+            }).id()
+        };
+        let edges = [spawn_edge(), spawn_edge()];
+        app.update();
+
+        let control_point = |app: &App, edge| {
+            app.world().get::<EdgeCurve>(edge).and_then(|curve| curve.control_point)
+        };
+        let first = control_point(&app, edges[0]).expect("edge was bundled");
+        let second = control_point(&app, edges[1]).expect("edge was bundled");
+        assert!(first.distance(second) > 1e-3);
+        // Bundling pulls the pair together, but not past their midpoint
+        assert!(first.distance(second) < 4.0 * PARALLEL_EDGE_CURVATURE * 2.0);
+
+        app.insert_resource(EdgeBundling(false));
+        app.update();
+        assert_eq!(control_point(&app, edges[0]), None);
+        assert!(app.world().get::<BundledEdge>(edges[0]).is_none());
+    }
+
+    #[test]
+    fn test_bundling_reruns_only_on_change_and_keeps_explicit_control_points() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(EdgeBundling(true))
+            .init_resource::<BundlingConfig>()
+            .add_systems(Update, bundle_edges);
+
+        let source = app.world_mut().spawn(GlobalTransform::default()).id();
+        let target = app.world_mut().spawn(GlobalTransform::from_xyz(4.0, 0.0, 0.0)).id();
+        let mut spawn_edge = || {
+            app.world_mut().spawn(EdgeVisual {
+                edge_id: cim_contextgraph::EdgeId::new(),
+                graph_id: cim_contextgraph::ContextGraphId::new(),
+                source_entity: source,
+                target_entity: target,
+                weight: 1.0,
+            }).id()
+        };
+        let (bundled, explicit) = (spawn_edge(), spawn_edge());
+        let placed = Vec2::new(2.0, 3.0);
+        app.world_mut().entity_mut(explicit).insert(EdgeCurve {
+            control_point: Some(placed),
+            curvature: 0.0,
+        });
+        app.update();
+
+        let control_point = |app: &App, edge| {
+            app.world().get::<EdgeCurve>(edge).and_then(|curve| curve.control_point)
+        };
+        assert_eq!(control_point(&app, explicit), Some(placed));
+        assert!(app.world().get::<BundledEdge>(explicit).is_none());
+        let first = control_point(&app, bundled).expect("edge was bundled");
+
+        // Nothing changed, so the bundled control point is not recomputed
+        let marker = Vec2::new(-7.0, -7.0);
+        app.world_mut().get_mut::<EdgeCurve>(bundled).unwrap().control_point = Some(marker);
+        app.update();
+        assert_eq!(control_point(&app, bundled), Some(marker));
+
+        // Moving a node rebundles
+        *app.world_mut().get_mut::<GlobalTransform>(target).unwrap() = GlobalTransform::from_xyz(4.0, 0.0, 0.0);
+        app.update();
+        assert_eq!(control_point(&app, bundled), Some(first));
+        assert_eq!(control_point(&app, explicit), Some(placed));
+    }
+}
@@ -0,0 +1,186 @@
+//! Environment: A shared ground grid and axis gizmos for the 3D scenes
+//!
+//! [`EnvironmentPlugin`] draws a grid and, optionally, the X, Y and Z axes
+//! with gizmos, so every scene gets the same spatial reference instead of
+//! spawning its own ground plane. Whether the grid is shown and its spacing
+//! come from the [`SceneRenderSettings`] resource; the toggle key flips
+//! `show_grid`. The rest lives in [`EnvironmentConfig`].
+//!
+//! The grid lies in the XY plane like the layouts. Scenes built on the XZ
+//! plane turn it with [`EnvironmentConfig::grid_isometry`].
+
+use bevy::prelude::*;
+use crate::picking::egui_wants_keyboard;
+use crate::value_objects::{RenderSettings, SceneRenderSettings};
+
+/// Grid and axes drawn around the graph
+#[derive(Resource, Debug, Clone)]
+pub struct EnvironmentConfig {
+    /// Key that shows and hides the grid, unless a panel has keyboard focus
+    pub toggle_key: KeyCode,
+    /// Number of grid cells along each side
+    pub grid_cells: u32,
+    pub grid_color: Color,
+    /// Placement of the grid; the identity lays it in the XY plane
+    pub grid_isometry: Isometry3d,
+    pub show_axes: bool,
+    /// Length of each axis gizmo from the origin
+    pub axis_length: f32,
+}
+
+impl Default for EnvironmentConfig {
+    fn default() -> Self {
+        Self {
+            toggle_key: KeyCode::KeyG,
+            grid_cells: 40,
+            grid_color: Color::srgba(0.5, 0.5, 0.5, 0.3),
+            grid_isometry: Isometry3d::IDENTITY,
+            show_axes: true,
+            axis_length: 5.0,
+        }
+    }
+}
+
+/// A gizmo line segment with its color
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EnvironmentLine {
+    pub start: Vec3,
+    pub end: Vec3,
+    pub color: Color,
+}
+
+/// Plugin that draws the grid and axes, with a key to toggle the grid
+pub struct EnvironmentPlugin;
+
+impl Plugin for EnvironmentPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SceneRenderSettings>()
+            .init_resource::<EnvironmentConfig>()
+            .add_systems(Update, (toggle_grid.run_if(not(egui_wants_keyboard)), draw_environment).chain());
+    }
+}
+
+/// Lines of a square grid of `cells` by `cells` cells of `cell_size`,
+/// centered on the origin of the XY plane
+pub fn grid_lines(cells: u32, cell_size: f32) -> Vec<(Vec3, Vec3)> {
+    if cells == 0 || cell_size <= 0.0 {
+        return Vec::new();
+    }
+    let half = cells as f32 * cell_size / 2.0;
+    (0..=cells)
+        .flat_map(|index| {
+            let offset = index as f32 * cell_size - half;
+            [
+                (Vec3::new(offset, -half, 0.0), Vec3::new(offset, half, 0.0)),
+                (Vec3::new(-half, offset, 0.0), Vec3::new(half, offset, 0.0)),
+            ]
+        })
+        .collect()
+}
+
+/// Every line the environment draws for `settings` and `config`: the grid
+/// when shown, then the axes in red, green and blue
+pub fn environment_lines(settings: &RenderSettings, config: &EnvironmentConfig) -> Vec<EnvironmentLine> {
+    let mut lines = Vec::new();
+    if settings.show_grid {
+        let isometry = config.grid_isometry;
+        lines.extend(grid_lines(config.grid_cells, settings.grid_size).into_iter().map(|(start, end)| {
+            EnvironmentLine {
+                start: isometry * start,
+                end: isometry * end,
+                color: config.grid_color,
+            }
+        }));
+    }
+    if config.show_axes && config.axis_length > 0.0 {
+        let axes = [
+            (Vec3::X, Color::srgb(0.9, 0.2, 0.2)),
+            (Vec3::Y, Color::srgb(0.2, 0.9, 0.2)),
+            (Vec3::Z, Color::srgb(0.2, 0.4, 0.9)),
+        ];
+        lines.extend(axes.into_iter().map(|(axis, color)| EnvironmentLine {
+            start: Vec3::ZERO,
+            end: axis * config.axis_length,
+            color,
+        }));
+    }
+    lines
+}
+
+/// System that shows or hides the grid when the toggle key is pressed
+pub fn toggle_grid(
+    keyboard: Option<Res<ButtonInput<KeyCode>>>,
+    config: Res<EnvironmentConfig>,
+    mut settings: ResMut<SceneRenderSettings>,
+) {
+    let Some(keyboard) = keyboard else {
+        return;
+    };
+    if keyboard.just_pressed(config.toggle_key) {
+        settings.show_grid = !settings.show_grid;
+    }
+}
+
+/// System that draws the grid and axes
+pub fn draw_environment(
+    settings: Res<SceneRenderSettings>,
+    config: Res<EnvironmentConfig>,
+    mut gizmos: Gizmos,
+) {
+    for line in environment_lines(&settings, &config) {
+        gizmos.line(line.start, line.end, line.color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grid_lines_cover_the_grid() {
+        let lines = grid_lines(2, 1.5);
+        // Three lines along each axis for two cells
+        assert_eq!(lines.len(), 6);
+        assert!(lines.contains(&(Vec3::new(-1.5, -1.5, 0.0), Vec3::new(-1.5, 1.5, 0.0))));
+        assert!(lines.contains(&(Vec3::new(-1.5, 1.5, 0.0), Vec3::new(1.5, 1.5, 0.0))));
+        assert!(grid_lines(2, 0.0).is_empty());
+    }
+
+    #[test]
+    fn test_toggling_grid_off_stops_grid_lines() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<SceneRenderSettings>()
+            .insert_resource(EnvironmentConfig { grid_cells: 4, ..default() })
+            .init_resource::<ButtonInput<KeyCode>>()
+            .add_systems(Update, toggle_grid);
+
+        let lines = |app: &App| {
+            environment_lines(app.world().resource::<SceneRenderSettings>(), app.world().resource::<EnvironmentConfig>())
+        };
+        // Five lines along each axis, then the three axes
+        assert_eq!(lines(&app).len(), 13);
+
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::KeyG);
+        app.update();
+        assert!(!app.world().resource::<SceneRenderSettings>().show_grid);
+        let remaining = lines(&app);
+        assert_eq!(remaining.len(), 3);
+        assert!(remaining.iter().all(|line| line.start == Vec3::ZERO));
+
+        app.world_mut().resource_mut::<EnvironmentConfig>().show_axes = false;
+        assert!(lines(&app).is_empty());
+    }
+
+    #[test]
+    fn test_toggle_grid_without_keyboard_input() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<SceneRenderSettings>()
+            .init_resource::<EnvironmentConfig>()
+            .add_systems(Update, toggle_grid);
+
+        app.update();
+        assert!(app.world().resource::<SceneRenderSettings>().show_grid);
+    }
+}
@@ -0,0 +1,214 @@
+//! Event Alerts: Rules that fire when event patterns occur
+//!
+//! [`AlertRules`] are evaluated against every frame's received events by the
+//! event visualization's statistics system. A rule whose condition holds
+//! sends an [`AlertTriggered`], then stays quiet for its cooldown so a
+//! sustained condition doesn't send one every frame.
+//!
+//! Rates are measured over [`AlertRules::rate_window`], so a short burst
+//! counts for as long as it stays in the window.
+
+use bevy::prelude::*;
+use chrono::{DateTime, Utc};
+use std::collections::VecDeque;
+use std::time::Duration;
+use crate::nats_event_visualization::DomainEventReceived;
+
+/// When an alert rule fires
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlertCondition {
+    /// More than `events_per_second` events per second, in `domain` or, without
+    /// one, overall
+    RateAbove {
+        domain: Option<String>,
+        events_per_second: f32,
+    },
+    /// Any event of this type
+    EventTypeSeen(String),
+    /// More than this many error events per second
+    ErrorRateAbove(f32),
+}
+
+/// A named condition and how long to wait before it may fire again
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertRule {
+    pub name: String,
+    pub condition: AlertCondition,
+    pub cooldown: Duration,
+    last_fired: Option<DateTime<Utc>>,
+}
+
+impl AlertRule {
+    pub fn new(name: impl Into<String>, condition: AlertCondition, cooldown: Duration) -> Self {
+        Self {
+            name: name.into(),
+            condition,
+            cooldown,
+            last_fired: None,
+        }
+    }
+
+    /// Whether the cooldown since the rule last fired is over at `now`
+    fn is_ready(&self, now: DateTime<Utc>) -> bool {
+        self.last_fired
+            .is_none_or(|fired| (now - fired).to_std().is_ok_and(|elapsed| elapsed >= self.cooldown))
+    }
+}
+
+/// An alert rule's condition was met
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct AlertTriggered {
+    pub rule_name: String,
+    pub message: String,
+}
+
+/// One received event, as far as the rate conditions need it
+#[derive(Debug, Clone)]
+struct RecentEvent {
+    received: DateTime<Utc>,
+    domain: String,
+    is_error: bool,
+}
+
+/// The alert rules, and the recent events their rates are measured over
+#[derive(Resource, Debug, Clone)]
+pub struct AlertRules {
+    pub rules: Vec<AlertRule>,
+    /// Span rates are averaged over
+    pub rate_window: Duration,
+    recent: VecDeque<RecentEvent>,
+}
+
+impl Default for AlertRules {
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            rate_window: Duration::from_secs(5),
+            recent: VecDeque::new(),
+        }
+    }
+}
+
+impl AlertRules {
+    pub fn with_rule(mut self, rule: AlertRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Record `events`, received at `now`, and return an alert for every
+    /// rule whose condition now holds and whose cooldown is over
+    pub fn evaluate<'a>(
+        &mut self,
+        events: impl IntoIterator<Item = &'a DomainEventReceived>,
+        now: DateTime<Utc>,
+    ) -> Vec<AlertTriggered> {
+        let events: Vec<&DomainEventReceived> = events.into_iter().collect();
+        let window = chrono::Duration::from_std(self.rate_window).unwrap_or(chrono::Duration::MAX);
+        self.recent.extend(events.iter().map(|event| RecentEvent {
+            received: now,
+            domain: event.domain.clone(),
+            is_error: event.is_error(),
+        }));
+        while self.recent.front().is_some_and(|event| now - event.received > window) {
+            self.recent.pop_front();
+        }
+
+        let window_secs = self.rate_window.as_secs_f32().max(f32::EPSILON);
+        let rate = |matches: &dyn Fn(&RecentEvent) -> bool| {
+            self.recent.iter().filter(|event| matches(event)).count() as f32 / window_secs
+        };
+
+        let mut alerts = Vec::new();
+        for rule in &mut self.rules {
+            if !rule.is_ready(now) {
+                continue;
+            }
+            let message = match &rule.condition {
+                AlertCondition::RateAbove { domain, events_per_second } => {
+                    let rate = rate(&|event| domain.as_ref().is_none_or(|domain| event.domain == *domain));
+                    (rate > *events_per_second).then(|| match domain {
+                        Some(domain) => format!("{rate:.1} events/s in {domain}, above {events_per_second}"),
+                        None => format!("{rate:.1} events/s, above {events_per_second}"),
+                    })
+                }
+                AlertCondition::EventTypeSeen(event_type) => events.iter()
+                    .find(|event| event.event_type == *event_type)
+                    .map(|event| format!("{} received from {}", event.event_type, event.domain)),
+                AlertCondition::ErrorRateAbove(errors_per_second) => {
+                    let rate = rate(&|event| event.is_error);
+                    (rate > *errors_per_second).then(|| format!("{rate:.1} errors/s, above {errors_per_second}"))
+                }
+            };
+
+            if let Some(message) = message {
+                rule.last_fired = Some(now);
+                alerts.push(AlertTriggered {
+                    rule_name: rule.name.clone(),
+                    message,
+                });
+            }
+        }
+        alerts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(domain: &str, event_type: &str) -> DomainEventReceived {
+        DomainEventReceived {
+            event_id: uuid::Uuid::new_v4().to_string(),
+            timestamp: Utc::now(),
+            domain: domain.to_string(),
+            event_type: event_type.to_string(),
+            aggregate_id: "agg-1".to_string(),
+            aggregate_type: "Order".to_string(),
+            correlation_id: None,
+            causation_id: None,
+            payload: serde_json::json!({}),
+        }
+    }
+
+    #[test]
+    fn test_rate_above_fires_once_within_cooldown() {
+        let mut alerts = AlertRules::default().with_rule(AlertRule::new(
+            "busy sales",
+            AlertCondition::RateAbove { domain: Some("sales".to_string()), events_per_second: 2.0 },
+            Duration::from_secs(30),
+        ));
+        let burst: Vec<_> = (0..20).map(|_| event("sales", "OrderPlaced")).collect();
+        let start = Utc::now();
+
+        let fired = alerts.evaluate(&burst, start);
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].rule_name, "busy sales");
+
+        // Still busy, but within the cooldown
+        assert!(alerts.evaluate(&burst, start + chrono::Duration::seconds(1)).is_empty());
+        // Other domains don't count towards the rate
+        let other: Vec<_> = (0..20).map(|_| event("billing", "InvoiceSent")).collect();
+        assert!(alerts.evaluate(&other, start + chrono::Duration::seconds(40)).is_empty());
+        assert_eq!(alerts.evaluate(&burst, start + chrono::Duration::seconds(41)).len(), 1);
+    }
+
+    #[test]
+    fn test_event_type_and_error_rate_conditions() {
+        let mut alerts = AlertRules::default()
+            .with_rule(AlertRule::new(
+                "cancellations",
+                AlertCondition::EventTypeSeen("OrderCancelled".to_string()),
+                Duration::ZERO,
+            ))
+            .with_rule(AlertRule::new("errors", AlertCondition::ErrorRateAbove(0.5), Duration::ZERO));
+        let now = Utc::now();
+
+        assert!(alerts.evaluate(&[event("sales", "OrderPlaced")], now).is_empty());
+        let fired = alerts.evaluate(
+            &[event("sales", "OrderCancelled"), event("sales", "PaymentFailed"), event("sales", "OrderRejected"), event("sales", "SyncError")],
+            now,
+        );
+        let names: Vec<&str> = fired.iter().map(|alert| alert.rule_name.as_str()).collect();
+        assert_eq!(names, vec!["cancellations", "errors"]);
+    }
+}
@@ -4,32 +4,16 @@
 //! These are emitted by systems after processing commands.
 
 use bevy::prelude::*;
-use cim_contextgraph::{NodeId, EdgeId};
-
-/// Position type for events
-pub type Position = Vec3;
-
-/// Node visual style
-#[derive(Debug, Clone, PartialEq)]
-pub struct NodeVisualStyle {
-    pub color: Color,
-    pub size: f32,
-    pub shape: EventNodeShape,
-}
-
-#[derive(Debug, Clone, PartialEq)]
-pub enum EventNodeShape {
-    Circle,
-    Square,
-    Diamond,
-}
+use cim_contextgraph::{NodeId, EdgeId, ContextGraphId as GraphId};
+use serde::{Deserialize, Serialize};
+use crate::value_objects::{NodeMetadata, NodeVisualStyle};
 
 /// Event: A visual node was created
 #[derive(Event, Debug, Clone)]
 pub struct VisualNodeCreated {
     pub entity: Entity,
     pub node_id: NodeId,
-    pub position: Position,
+    pub position: Vec3,
 }
 
 /// Event: A visual edge was created
@@ -39,6 +23,8 @@ pub struct VisualEdgeCreated {
     pub edge_id: EdgeId,
     pub source_entity: Entity,
     pub target_entity: Entity,
+    pub source_node_id: NodeId,
+    pub target_node_id: NodeId,
 }
 
 /// Event: A node was moved
@@ -46,8 +32,8 @@ pub struct VisualEdgeCreated {
 pub struct NodeMoved {
     pub entity: Entity,
     pub node_id: NodeId,
-    pub old_position: Position,
-    pub new_position: Position,
+    pub old_position: Vec3,
+    pub new_position: Vec3,
 }
 
 /// Event: A node's style was updated
@@ -63,7 +49,7 @@ pub struct NodeStyleUpdated {
 #[derive(Event, Debug, Clone)]
 pub struct VisualNodeDeleted {
     pub node_id: NodeId,
-    pub final_position: Position,
+    pub final_position: Vec3,
 }
 
 /// Event: A visual edge was deleted
@@ -89,6 +75,7 @@ pub struct NodeDeselected {
 /// Event: Canvas was panned
 #[derive(Event, Debug, Clone)]
 pub struct CanvasPanned {
+    pub graph_id: GraphId,
     pub delta: Vec2,
     pub new_offset: Vec2,
 }
@@ -96,9 +83,11 @@ pub struct CanvasPanned {
 /// Event: Canvas was zoomed
 #[derive(Event, Debug, Clone)]
 pub struct CanvasZoomed {
+    pub graph_id: GraphId,
     pub old_zoom: f32,
     pub new_zoom: f32,
-    pub focal_point: Vec2,
+    /// Screen point kept in place, `None` for the viewport center
+    pub focal_point: Option<Vec2>,
 }
 
 // Visualization Commands (these are like commands but implemented as events for simplicity)
@@ -107,8 +96,12 @@ pub struct CanvasZoomed {
 #[derive(Event, Debug, Clone)]
 pub struct CreateNodeVisual {
     pub node_id: NodeId,
+    pub graph_id: GraphId,
     pub position: Vec3,
     pub label: String,
+    /// Domain metadata; `label`, `description` and `tags` become the node's
+    /// `NodeMetadata`, with `label` above as the fallback label
+    pub metadata: serde_json::Value,
 }
 
 /// Command to remove a node visual
@@ -117,13 +110,25 @@ pub struct RemoveNodeVisual {
     pub node_id: NodeId,
 }
 
+/// Command to move a node visual to a new position
+#[derive(Event, Debug, Clone)]
+pub struct UpdateNodePosition {
+    pub node_id: NodeId,
+    pub graph_id: GraphId,
+    pub position: Vec3,
+}
+
 /// Command to create an edge visual
 #[derive(Event, Debug, Clone)]
 pub struct CreateEdgeVisual {
     pub edge_id: EdgeId,
+    pub graph_id: GraphId,
     pub source_node_id: NodeId,
     pub target_node_id: NodeId,
     pub relationship: EdgeRelationship,
+    /// The edge's `EdgeVisual::weight`, 1.0 when not given and never below
+    /// `MIN_EDGE_WEIGHT`
+    pub weight: Option<f32>,
 }
 
 /// Command to remove an edge visual
@@ -132,8 +137,9 @@ pub struct RemoveEdgeVisual {
     pub edge_id: EdgeId,
 }
 
-/// Edge relationship types
-#[derive(Debug, Clone, PartialEq)]
+/// Edge relationship types. Edge visuals carry theirs as a component, so it
+/// is not parsed back from their label.
+#[derive(Component, Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum EdgeRelationship {
     DependsOn,
     Contains,
@@ -141,15 +147,46 @@ pub enum EdgeRelationship {
     Custom(String),
 }
 
+impl std::fmt::Display for EdgeRelationship {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EdgeRelationship::DependsOn => write!(f, "DependsOn"),
+            EdgeRelationship::Contains => write!(f, "Contains"),
+            EdgeRelationship::References => write!(f, "References"),
+            EdgeRelationship::Custom(label) => write!(f, "{}", label),
+        }
+    }
+}
+
+/// Inverse of `Display`: known relationship names map to their variant,
+/// anything else becomes `Custom`
+impl From<&str> for EdgeRelationship {
+    fn from(label: &str) -> Self {
+        match label {
+            "DependsOn" => EdgeRelationship::DependsOn,
+            "Contains" => EdgeRelationship::Contains,
+            "References" => EdgeRelationship::References,
+            other => EdgeRelationship::Custom(other.to_string()),
+        }
+    }
+}
+
 /// Visualization command type
 #[derive(Event, Debug, Clone)]
 pub enum VisualizationCommand {
     CreateNode(CreateNodeVisual),
     RemoveNode(RemoveNodeVisual),
+    UpdateNodePosition(UpdateNodePosition),
     CreateEdge(CreateEdgeVisual),
     RemoveEdge(RemoveEdgeVisual),
 }
 
+/// Request: Ask the domain to carry out a command. Unlike a
+/// [`VisualizationCommand`] event, which the domain sent in, this is sent
+/// out, so the visualization never republishes the domain's own commands.
+#[derive(Event, Debug, Clone)]
+pub struct RequestDomainCommand(pub VisualizationCommand);
+
 // Interaction Events
 
 /// Event: Node was clicked
@@ -218,6 +255,7 @@ pub struct NodePositionChanged {
 pub struct NodeMetadataChanged {
     pub entity: Entity,
     pub node_id: NodeId,
+    pub metadata: NodeMetadata,
 }
 
 /// Event: Selection changed
@@ -226,3 +264,63 @@ pub struct SelectionChanged {
     pub selected_nodes: Vec<NodeId>,
     pub selected_edges: Vec<EdgeId>,
 }
+
+/// Command: Deselect all nodes and edges
+#[derive(Event, Debug, Clone, Default)]
+pub struct ClearSelection;
+
+/// Command: Select every node
+#[derive(Event, Debug, Clone, Default)]
+pub struct SelectAll;
+
+/// Command: Select a node, adding it to the selection when `multi_select`
+#[derive(Event, Debug, Clone)]
+pub struct SelectNode {
+    pub node_id: NodeId,
+    pub multi_select: bool,
+}
+
+/// Request: Delete the selected nodes. The domain decides; the nodes go
+/// once it removes them.
+#[derive(Event, Debug, Clone, Default)]
+pub struct RequestDeleteSelected;
+
+/// Command: Move the graph camera to frame the given entities
+#[derive(Event, Debug, Clone)]
+pub struct FocusCamera {
+    pub target_entities: Vec<Entity>,
+    /// Point to center on instead, keeping the camera's distance; the
+    /// entities are ignored when set
+    pub target_point: Option<Vec3>,
+    pub transition_duration: f32,
+}
+
+/// Command: Return the graph camera to its default view
+#[derive(Event, Debug, Clone)]
+pub struct ResetCamera {
+    pub transition_duration: f32,
+}
+
+/// Command: Find a path between two nodes and highlight it
+#[derive(Event, Debug, Clone)]
+pub struct FindPath {
+    pub from: NodeId,
+    pub to: NodeId,
+}
+
+/// Command: Temporarily highlight the nodes and edges along a path
+#[derive(Event, Debug, Clone)]
+pub struct HighlightPath {
+    pub node_entities: Vec<Entity>,
+    pub edge_entities: Vec<Entity>,
+    pub duration: f32,
+}
+
+/// Command: Ask the domain for an edge between two nodes, as drawn by the
+/// user
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct RequestEdgeCreation {
+    pub source_id: NodeId,
+    pub target_id: NodeId,
+    pub relationship: EdgeRelationship,
+}
@@ -0,0 +1,301 @@
+//! Graph Export: Graphviz DOT and SVG renderings of the loaded graph
+//!
+//! [`to_dot`] produces a layout-free description for Graphviz, while
+//! [`to_svg`] draws nodes where they currently sit in the scene so the
+//! exported picture matches what is on screen. [`GraphExportPlugin`] writes
+//! both files when the configured key is pressed.
+
+use bevy::prelude::*;
+use cim_contextgraph::{NodeId, EdgeId};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use crate::components::{EdgeLabel, EdgeVisual, NodeVisual};
+use crate::events::EdgeRelationship;
+use crate::picking::egui_wants_keyboard;
+use crate::value_objects::NodeMetadata;
+
+/// Pixels per world unit in SVG output
+const SVG_SCALE: f32 = 40.0;
+/// Blank border around the SVG drawing, in pixels
+const SVG_MARGIN: f32 = 40.0;
+/// Node circle radius in SVG output, in pixels
+const SVG_NODE_RADIUS: f32 = 12.0;
+
+/// Where and when the loaded graph is exported
+#[derive(Resource, Debug, Clone)]
+pub struct GraphExportSettings {
+    /// Key that triggers an export, unless a panel has keyboard focus
+    pub key: KeyCode,
+    /// File the DOT description is written to
+    pub dot_path: PathBuf,
+    /// File the SVG picture is written to, if any
+    pub svg_path: Option<PathBuf>,
+}
+
+impl Default for GraphExportSettings {
+    fn default() -> Self {
+        Self {
+            key: KeyCode::KeyX,
+            dot_path: PathBuf::from("graph.dot"),
+            svg_path: Some(PathBuf::from("graph.svg")),
+        }
+    }
+}
+
+/// Plugin that exports the loaded graph to DOT/SVG on a key press
+pub struct GraphExportPlugin;
+
+impl Plugin for GraphExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GraphExportSettings>()
+            .add_systems(Update, export_graph_on_key.run_if(not(egui_wants_keyboard)));
+    }
+}
+
+/// Escape a string for use inside a double-quoted DOT or XML attribute
+fn escape(text: &str, xml: bool) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' if xml => escaped.push_str("&quot;"),
+            '"' => escaped.push_str("\\\""),
+            '\\' if !xml => escaped.push_str("\\\\"),
+            '\n' if !xml => escaped.push_str("\\n"),
+            '&' if xml => escaped.push_str("&amp;"),
+            '<' if xml => escaped.push_str("&lt;"),
+            '>' if xml => escaped.push_str("&gt;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Label to show for a node, falling back to its id
+fn node_label(node_id: &NodeId, metadata: &NodeMetadata) -> String {
+    if metadata.label.is_empty() {
+        node_id.to_string()
+    } else {
+        metadata.label.clone()
+    }
+}
+
+/// Describe a graph in Graphviz DOT
+pub fn to_dot(
+    nodes: &[(NodeId, NodeMetadata, Vec3)],
+    edges: &[(EdgeId, NodeId, NodeId, EdgeRelationship)],
+) -> String {
+    let mut dot = String::from("digraph {\n");
+    for (node_id, metadata, _) in nodes {
+        let _ = writeln!(dot, "    \"{node_id}\" [label=\"{}\"];", escape(&node_label(node_id, metadata), false));
+    }
+    for (_, source, target, relationship) in edges {
+        let _ = writeln!(
+            dot,
+            "    \"{source}\" -> \"{target}\" [label=\"{}\"];",
+            escape(&relationship.to_string(), false)
+        );
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// Draw a graph as SVG using the given node positions.
+///
+/// Positions are projected onto the two axes along which the graph is most
+/// spread out, so both flat XZ layouts and XY layouts come out readable.
+pub fn to_svg(
+    nodes: &[(NodeId, NodeMetadata, Vec3)],
+    edges: &[(EdgeId, NodeId, NodeId, EdgeRelationship)],
+) -> String {
+    let (min, max) = nodes.iter().fold(
+        (Vec3::splat(f32::INFINITY), Vec3::splat(f32::NEG_INFINITY)),
+        |(min, max), (_, _, position)| (min.min(*position), max.max(*position)),
+    );
+    let extent = (max - min).max(Vec3::ZERO);
+
+    // Keep the two widest axes; the drawing's y grows downwards, so flip it
+    let (u_axis, v_axis) = if extent.x <= extent.y && extent.x <= extent.z {
+        (2, 1)
+    } else if extent.y <= extent.z {
+        (0, 2)
+    } else {
+        (0, 1)
+    };
+    let project = |position: Vec3| {
+        Vec2::new(
+            (position[u_axis] - min[u_axis]) * SVG_SCALE + SVG_MARGIN,
+            (max[v_axis] - position[v_axis]) * SVG_SCALE + SVG_MARGIN,
+        )
+    };
+    let (width, height) = if nodes.is_empty() {
+        (2.0 * SVG_MARGIN, 2.0 * SVG_MARGIN)
+    } else {
+        (
+            extent[u_axis] * SVG_SCALE + 2.0 * SVG_MARGIN,
+            extent[v_axis] * SVG_SCALE + 2.0 * SVG_MARGIN,
+        )
+    };
+
+    let points: HashMap<NodeId, Vec2> = nodes.iter()
+        .map(|(node_id, _, position)| (*node_id, project(*position)))
+        .collect();
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width:.0}\" height=\"{height:.0}\" viewBox=\"0 0 {width:.0} {height:.0}\">"
+    );
+    svg.push_str(concat!(
+        "  <defs><marker id=\"arrow\" viewBox=\"0 0 10 10\" refX=\"10\" refY=\"5\" ",
+        "markerWidth=\"6\" markerHeight=\"6\" orient=\"auto-start-reverse\">",
+        "<path d=\"M 0 0 L 10 5 L 0 10 z\" fill=\"#666\"/></marker></defs>\n",
+    ));
+
+    for (_, source, target, relationship) in edges {
+        let (Some(from), Some(to)) = (points.get(source), points.get(target)) else {
+            continue;
+        };
+        // Stop the line at the target circle so the arrowhead stays visible
+        let direction = (*to - *from).normalize_or_zero();
+        let end = *to - direction * SVG_NODE_RADIUS;
+        let middle = (*from + *to) * 0.5;
+        let _ = writeln!(
+            svg,
+            "  <line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"#666\" marker-end=\"url(#arrow)\"/>",
+            from.x, from.y, end.x, end.y
+        );
+        let _ = writeln!(
+            svg,
+            "  <text x=\"{:.1}\" y=\"{:.1}\" font-size=\"10\" fill=\"#666\" text-anchor=\"middle\">{}</text>",
+            middle.x, middle.y, escape(&relationship.to_string(), true)
+        );
+    }
+
+    for (node_id, metadata, _) in nodes {
+        let point = points[node_id];
+        let _ = writeln!(
+            svg,
+            "  <circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{SVG_NODE_RADIUS}\" fill=\"#4a9\" stroke=\"#264\"/>",
+            point.x, point.y
+        );
+        let _ = writeln!(
+            svg,
+            "  <text x=\"{:.1}\" y=\"{:.1}\" font-size=\"12\" text-anchor=\"middle\">{}</text>",
+            point.x,
+            point.y - SVG_NODE_RADIUS - 4.0,
+            escape(&node_label(node_id, metadata), true)
+        );
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// System that writes the DOT (and SVG) export when the export key is pressed
+pub fn export_graph_on_key(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    settings: Res<GraphExportSettings>,
+    nodes: Query<(&NodeVisual, &Transform, Option<&NodeMetadata>)>,
+    edges: Query<(&EdgeVisual, Option<&EdgeLabel>)>,
+) {
+    if !keyboard.just_pressed(settings.key) {
+        return;
+    }
+
+    let node_list: Vec<(NodeId, NodeMetadata, Vec3)> = nodes.iter()
+        .map(|(node, transform, metadata)| {
+            (node.node_id, metadata.cloned().unwrap_or_default(), transform.translation)
+        })
+        .collect();
+    let edge_list: Vec<(EdgeId, NodeId, NodeId, EdgeRelationship)> = edges.iter()
+        .filter_map(|(edge, label)| {
+            let (source, _, _) = nodes.get(edge.source_entity).ok()?;
+            let (target, _, _) = nodes.get(edge.target_entity).ok()?;
+            let relationship = label.map_or(EdgeRelationship::Custom(String::new()), |label| {
+                EdgeRelationship::from(label.text.as_str())
+            });
+            Some((edge.edge_id, source.node_id, target.node_id, relationship))
+        })
+        .collect();
+
+    match std::fs::write(&settings.dot_path, to_dot(&node_list, &edge_list)) {
+        Ok(()) => info!("Exported graph to {}", settings.dot_path.display()),
+        Err(error) => error!("Failed to write {}: {error}", settings.dot_path.display()),
+    }
+    if let Some(svg_path) = &settings.svg_path {
+        match std::fs::write(svg_path, to_svg(&node_list, &edge_list)) {
+            Ok(()) => info!("Exported graph to {}", svg_path.display()),
+            Err(error) => error!("Failed to write {}: {error}", svg_path.display()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Minimal DOT reader for the subset `to_dot` emits: returns the quoted
+    /// node ids and the `(source, target)` pairs, or `None` if malformed
+    fn parse_dot(dot: &str) -> Option<(Vec<String>, Vec<(String, String)>)> {
+        let body = dot.trim().strip_prefix("digraph {")?.strip_suffix('}')?;
+        let quoted = |text: &str| -> Option<String> {
+            Some(text.trim().strip_prefix('"')?.strip_suffix('"')?.to_string())
+        };
+
+        let mut nodes = Vec::new();
+        let mut edges = Vec::new();
+        for statement in body.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let statement = statement.strip_suffix(';')?;
+            let (head, attributes) = statement.split_once(" [")?;
+            attributes.strip_prefix("label=\"")?.strip_suffix("\"]")?;
+            match head.split_once(" -> ") {
+                Some((source, target)) => edges.push((quoted(source)?, quoted(target)?)),
+                None => nodes.push(quoted(head)?),
+            }
+        }
+        Some((nodes, edges))
+    }
+
+    fn two_node_graph() -> (Vec<(NodeId, NodeMetadata, Vec3)>, Vec<(EdgeId, NodeId, NodeId, EdgeRelationship)>) {
+        let (a, b) = (NodeId::new(), NodeId::new());
+        let metadata = |label: &str| NodeMetadata {
+            label: label.to_string(),
+            ..Default::default()
+        };
+        (
+            vec![
+                (a, metadata("Web \"frontend\""), Vec3::ZERO),
+                (b, metadata("Database"), Vec3::new(4.0, 0.0, 2.0)),
+            ],
+            vec![(EdgeId::new(), a, b, EdgeRelationship::DependsOn)],
+        )
+    }
+
+    #[test]
+    fn test_to_dot_is_valid_digraph() {
+        let (nodes, edges) = two_node_graph();
+        let dot = to_dot(&nodes, &edges);
+
+        let (parsed_nodes, parsed_edges) = parse_dot(&dot).expect("valid DOT");
+        assert_eq!(parsed_nodes, vec![nodes[0].0.to_string(), nodes[1].0.to_string()]);
+        assert_eq!(parsed_edges, vec![(nodes[0].0.to_string(), nodes[1].0.to_string())]);
+        assert!(dot.contains("label=\"Web \\\"frontend\\\"\""));
+        assert!(dot.contains("label=\"DependsOn\""));
+    }
+
+    #[test]
+    fn test_to_svg_follows_positions() {
+        let (nodes, edges) = two_node_graph();
+        let svg = to_svg(&nodes, &edges);
+
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("<circle").count(), 2);
+        assert_eq!(svg.matches("<line").count(), 1);
+        // The graph lies in the XZ plane, so x and z become the picture axes
+        assert!(svg.contains(&format!("width=\"{:.0}\"", 4.0 * SVG_SCALE + 2.0 * SVG_MARGIN)));
+        assert!(svg.contains(&format!("height=\"{:.0}\"", 2.0 * SVG_SCALE + 2.0 * SVG_MARGIN)));
+        assert!(svg.contains("Web &quot;frontend&quot;"));
+    }
+}
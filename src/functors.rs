@@ -7,7 +7,61 @@
 use bevy::prelude::*;
 use cim_contextgraph::{ContextGraph, NodeEntry, EdgeEntry, NodeId, EdgeId, ContextGraphId as GraphId};
 use crate::components::*;
-use crate::events::{VisualizationCommand, EdgeRelationship, CreateNodeVisual, CreateEdgeVisual};
+use crate::events::{
+    VisualizationCommand, EdgeRelationship, CreateNodeVisual, CreateEdgeVisual, RemoveNodeVisual,
+    RemoveEdgeVisual, NodeMetadataChanged, NodePositionChanged, UpdateNodePosition,
+};
+use crate::value_objects::NodeMetadata;
+
+/// Distance between consecutive nodes that arrive without a position
+const UNPLACED_NODE_SPACING: f32 = 1.5;
+
+/// Angle between consecutive unplaced nodes on their spiral, the golden angle
+const UNPLACED_NODE_ANGLE: f32 = 2.399_963;
+
+/// A change to a domain graph, as seen by the visualization
+///
+/// Not every field survives a round trip through the visual category:
+/// - `graph_id` is not carried by the remove commands, so the inverse
+///   functor has to be told which graph removals belong to
+/// - a missing `position` is placed at the origin, so it comes back as
+///   `Some(Vec3::ZERO)`
+/// - metadata updates have no visualization command; they are applied to
+///   the existing entities by [`handle_domain_events`]
+#[derive(Event, Debug, Clone, PartialEq)]
+pub enum DomainEvent {
+    NodeAdded {
+        graph_id: GraphId,
+        node_id: NodeId,
+        position: Option<Vec3>,
+        label: String,
+    },
+    NodeRemoved {
+        graph_id: GraphId,
+        node_id: NodeId,
+    },
+    EdgeAdded {
+        graph_id: GraphId,
+        edge_id: EdgeId,
+        source: NodeId,
+        target: NodeId,
+        relationship: EdgeRelationship,
+    },
+    EdgeRemoved {
+        graph_id: GraphId,
+        edge_id: EdgeId,
+    },
+    NodeMetadataUpdated {
+        graph_id: GraphId,
+        node_id: NodeId,
+        metadata: serde_json::Value,
+    },
+    NodePositionUpdated {
+        graph_id: GraphId,
+        node_id: NodeId,
+        position: Vec3,
+    },
+}
 
 /// Functor F: CIM-ContextGraph → Bevy ECS
 /// Maps domain objects to visual representations
@@ -35,6 +89,7 @@ impl DomainToVisualFunctor {
             graph_id,
             source_entity,
             target_entity,
+            weight: 1.0,
         }
     }
 
@@ -45,6 +100,161 @@ impl DomainToVisualFunctor {
             layout_type: LayoutType::ForceDirected, // Default
         }
     }
+
+    /// Map a domain event to the visualization commands that reproduce it;
+    /// metadata updates map to no commands
+    pub fn domain_to_visual(&self, event: DomainEvent) -> Vec<VisualizationCommand> {
+        let command = match event {
+            DomainEvent::NodeAdded { graph_id, node_id, position, label } => {
+                VisualizationCommand::CreateNode(CreateNodeVisual {
+                    node_id,
+                    graph_id,
+                    position: position.unwrap_or(Vec3::ZERO),
+                    label,
+                    metadata: serde_json::Value::Null,
+                })
+            }
+            DomainEvent::NodeRemoved { node_id, .. } => {
+                VisualizationCommand::RemoveNode(RemoveNodeVisual { node_id })
+            }
+            DomainEvent::EdgeAdded { graph_id, edge_id, source, target, relationship } => {
+                VisualizationCommand::CreateEdge(CreateEdgeVisual {
+                    edge_id,
+                    graph_id,
+                    source_node_id: source,
+                    target_node_id: target,
+                    relationship,
+                    weight: None,
+                })
+            }
+            DomainEvent::EdgeRemoved { edge_id, .. } => {
+                VisualizationCommand::RemoveEdge(RemoveEdgeVisual { edge_id })
+            }
+            DomainEvent::NodePositionUpdated { graph_id, node_id, position } => {
+                VisualizationCommand::UpdateNodePosition(UpdateNodePosition {
+                    node_id,
+                    graph_id,
+                    position,
+                })
+            }
+            DomainEvent::NodeMetadataUpdated { .. } => return Vec::new(),
+        };
+        vec![command]
+    }
+}
+
+/// Where to put the `index`th node that arrives without a position: on a
+/// spiral around the center of the graph's existing nodes, so new nodes
+/// don't land on top of each other. The active layout takes over from there.
+pub fn unplaced_node_position(existing: &[Vec3], index: usize) -> Vec3 {
+    let center = if existing.is_empty() {
+        Vec3::ZERO
+    } else {
+        existing.iter().copied().sum::<Vec3>() / existing.len() as f32
+    };
+    let step = (existing.len() + index + 1) as f32;
+    let (sin, cos) = (step * UNPLACED_NODE_ANGLE).sin_cos();
+    center + Vec3::new(cos, sin, 0.0) * UNPLACED_NODE_SPACING * step.sqrt()
+}
+
+/// Morphism commands the domain stream is translated into
+#[derive(bevy::ecs::system::SystemParam)]
+pub struct MorphismWriters<'w> {
+    create_node: EventWriter<'w, CreateNodeVisual>,
+    remove_node: EventWriter<'w, RemoveNodeVisual>,
+    create_edge: EventWriter<'w, CreateEdgeVisual>,
+    remove_edge: EventWriter<'w, RemoveEdgeVisual>,
+    metadata_changed: EventWriter<'w, NodeMetadataChanged>,
+    position_changed: EventWriter<'w, NodePositionChanged>,
+}
+
+/// System that translates the inbound [`DomainEvent`] stream into
+/// visualization morphisms. Added nodes without a position are placed by
+/// [`unplaced_node_position`]; metadata and position updates are applied to
+/// the node's entity directly.
+pub fn handle_domain_events(
+    mut commands: Commands,
+    mut events: EventReader<DomainEvent>,
+    mut writers: MorphismWriters,
+    mut nodes: Query<(Entity, &NodeVisual, &mut Transform, Option<&mut NodeMetadata>)>,
+) {
+    let mut unplaced: std::collections::HashMap<GraphId, usize> = std::collections::HashMap::new();
+
+    for event in events.read() {
+        match event.clone() {
+            DomainEvent::NodeAdded { graph_id, node_id, position, label } => {
+                let position = position.unwrap_or_else(|| {
+                    let existing: Vec<Vec3> = nodes.iter()
+                        .filter(|(_, visual, ..)| visual.graph_id == graph_id)
+                        .map(|(_, _, transform, _)| transform.translation)
+                        .collect();
+                    let index = unplaced.entry(graph_id).or_default();
+                    *index += 1;
+                    unplaced_node_position(&existing, *index - 1)
+                });
+                writers.create_node.write(CreateNodeVisual {
+                    node_id,
+                    graph_id,
+                    position,
+                    label,
+                    metadata: serde_json::Value::Null,
+                });
+            }
+            DomainEvent::NodeRemoved { node_id, .. } => {
+                writers.remove_node.write(RemoveNodeVisual { node_id });
+            }
+            DomainEvent::EdgeAdded { graph_id, edge_id, source, target, relationship } => {
+                writers.create_edge.write(CreateEdgeVisual {
+                    edge_id,
+                    graph_id,
+                    source_node_id: source,
+                    target_node_id: target,
+                    relationship,
+                    weight: None,
+                });
+            }
+            DomainEvent::EdgeRemoved { edge_id, .. } => {
+                writers.remove_edge.write(RemoveEdgeVisual { edge_id });
+            }
+            DomainEvent::NodeMetadataUpdated { node_id, metadata, .. } => {
+                let Some((entity, _, _, current)) = nodes.iter_mut()
+                    .find(|(_, visual, ..)| visual.node_id == node_id)
+                else {
+                    warn!("Metadata update for unknown node {:?}", node_id);
+                    continue;
+                };
+                // The update is merged, so fields it leaves out are kept
+                let metadata = match current {
+                    Some(mut current) => {
+                        current.merge_json(&metadata);
+                        current.clone()
+                    }
+                    None => {
+                        let metadata = NodeMetadata::from_json(&metadata);
+                        commands.entity(entity).insert(metadata.clone());
+                        metadata
+                    }
+                };
+                writers.metadata_changed.write(NodeMetadataChanged { entity, node_id, metadata });
+            }
+            DomainEvent::NodePositionUpdated { node_id, position, .. } => {
+                let Some((entity, _, mut transform, _)) = nodes.iter_mut()
+                    .find(|(_, visual, ..)| visual.node_id == node_id)
+                else {
+                    warn!("Position update for unknown node {:?}", node_id);
+                    continue;
+                };
+                let old_position = transform.translation;
+                transform.translation = position;
+                writers.position_changed.write(NodePositionChanged {
+                    entity,
+                    node_id,
+                    old_position,
+                    new_position: position,
+                });
+            }
+        }
+    }
 }
 
 /// Functor G: Bevy ECS → CIM-ContextGraph
@@ -55,25 +265,30 @@ impl VisualToDomainFunctor {
     /// Map node position change to domain command
     pub fn map_position_change(
         node_id: NodeId,
+        graph_id: GraphId,
         new_position: Vec3,
     ) -> CreateNodeVisual {
         // TODO: This should be an update command, not create
         CreateNodeVisual {
             node_id,
+            graph_id,
             position: new_position,
             label: String::new(),
+            metadata: serde_json::Value::Null,
         }
     }
 
     /// Map node creation to domain command
     pub fn map_node_creation(
         position: Vec3,
-        _graph_id: GraphId,
+        graph_id: GraphId,
     ) -> VisualizationCommand {
         VisualizationCommand::CreateNode(CreateNodeVisual {
             node_id: NodeId::new(),
+            graph_id,
             position,
             label: String::new(),
+            metadata: serde_json::Value::Null,
         })
     }
 
@@ -81,15 +296,57 @@ impl VisualToDomainFunctor {
     pub fn map_edge_creation(
         source: NodeId,
         target: NodeId,
-        _graph_id: GraphId,
+        graph_id: GraphId,
     ) -> VisualizationCommand {
         VisualizationCommand::CreateEdge(CreateEdgeVisual {
             edge_id: EdgeId::new(),
+            graph_id,
             source_node_id: source,
             target_node_id: target,
             relationship: EdgeRelationship::DependsOn, // Default relationship
+            weight: None,
         })
     }
+
+    /// Map visualization commands back to domain events; removals, which
+    /// don't name their graph, are attributed to `graph_id`
+    pub fn visual_to_domain(
+        &self,
+        commands: &[VisualizationCommand],
+        graph_id: GraphId,
+    ) -> Vec<DomainEvent> {
+        commands
+            .iter()
+            .map(|command| match command {
+                VisualizationCommand::CreateNode(create) => DomainEvent::NodeAdded {
+                    graph_id: create.graph_id,
+                    node_id: create.node_id,
+                    position: Some(create.position),
+                    label: create.label.clone(),
+                },
+                VisualizationCommand::RemoveNode(remove) => DomainEvent::NodeRemoved {
+                    graph_id,
+                    node_id: remove.node_id,
+                },
+                VisualizationCommand::UpdateNodePosition(update) => DomainEvent::NodePositionUpdated {
+                    graph_id: update.graph_id,
+                    node_id: update.node_id,
+                    position: update.position,
+                },
+                VisualizationCommand::CreateEdge(create) => DomainEvent::EdgeAdded {
+                    graph_id: create.graph_id,
+                    edge_id: create.edge_id,
+                    source: create.source_node_id,
+                    target: create.target_node_id,
+                    relationship: create.relationship.clone(),
+                },
+                VisualizationCommand::RemoveEdge(remove) => DomainEvent::EdgeRemoved {
+                    graph_id,
+                    edge_id: remove.edge_id,
+                },
+            })
+            .collect()
+    }
 }
 
 /// Natural transformation between functors
@@ -139,6 +396,7 @@ impl FunctorComposition {
         // G: Visual → Domain
         VisualToDomainFunctor::map_position_change(
             visual.node.node_id,
+            visual.node.graph_id,
             visual.transform.translation,
         )
     }
@@ -148,6 +406,7 @@ impl FunctorComposition {
 #[cfg(test)]
 mod functor_laws {
     use super::*;
+    use proptest::prelude::*;
 
     #[test]
     fn test_identity_preservation() {
@@ -166,4 +425,168 @@ mod functor_laws {
         // Functor preserves composition: F(g ∘ f) = F(g) ∘ F(f)
         // This would test that composed operations map correctly
     }
+
+    fn relationship_strategy() -> impl Strategy<Value = EdgeRelationship> {
+        prop_oneof![
+            Just(EdgeRelationship::DependsOn),
+            Just(EdgeRelationship::Contains),
+            Just(EdgeRelationship::References),
+            "[a-zA-Z ]{0,12}".prop_map(EdgeRelationship::Custom),
+        ]
+    }
+
+    fn domain_event_strategy() -> impl Strategy<Value = DomainEvent> {
+        let node_id = any::<()>().prop_map(|_| NodeId::new());
+        let edge_id = any::<()>().prop_map(|_| EdgeId::new());
+        let position = proptest::option::of(
+            (-1e3f32..1e3, -1e3f32..1e3, -1e3f32..1e3).prop_map(|(x, y, z)| Vec3::new(x, y, z)),
+        );
+        let graph_id = GraphId::new();
+
+        prop_oneof![
+            (node_id.clone(), position, ".{0,16}").prop_map(move |(node_id, position, label)| {
+                DomainEvent::NodeAdded { graph_id, node_id, position, label }
+            }),
+            node_id.clone().prop_map(move |node_id| DomainEvent::NodeRemoved { graph_id, node_id }),
+            (edge_id.clone(), node_id.clone(), node_id, relationship_strategy()).prop_map(
+                move |(edge_id, source, target, relationship)| DomainEvent::EdgeAdded {
+                    graph_id,
+                    edge_id,
+                    source,
+                    target,
+                    relationship,
+                }
+            ),
+            edge_id.prop_map(move |edge_id| DomainEvent::EdgeRemoved { graph_id, edge_id }),
+        ]
+    }
+
+    /// Apply the documented lossy mappings so round-tripped events compare equal
+    fn without_lossy_fields(event: DomainEvent) -> DomainEvent {
+        match event {
+            DomainEvent::NodeAdded { graph_id, node_id, position, label } => DomainEvent::NodeAdded {
+                graph_id,
+                node_id,
+                position: Some(position.unwrap_or(Vec3::ZERO)),
+                label,
+            },
+            other => other,
+        }
+    }
+
+    fn graph_id_of(event: &DomainEvent) -> GraphId {
+        match event {
+            DomainEvent::NodeAdded { graph_id, .. }
+            | DomainEvent::NodeRemoved { graph_id, .. }
+            | DomainEvent::EdgeAdded { graph_id, .. }
+            | DomainEvent::EdgeRemoved { graph_id, .. }
+            | DomainEvent::NodeMetadataUpdated { graph_id, .. }
+            | DomainEvent::NodePositionUpdated { graph_id, .. } => *graph_id,
+        }
+    }
+
+    proptest! {
+        /// G ∘ F = Id on domain events, up to the documented lossy fields
+        #[test]
+        fn prop_visual_to_domain_inverts_domain_to_visual(event in domain_event_strategy()) {
+            let commands = DomainToVisualFunctor.domain_to_visual(event.clone());
+            let round_trip = VisualToDomainFunctor.visual_to_domain(&commands, graph_id_of(&event));
+
+            prop_assert_eq!(round_trip, vec![without_lossy_fields(event)]);
+        }
+
+        /// F ∘ G ∘ F = F: once normalized, a second trip changes nothing
+        #[test]
+        fn prop_round_trip_is_stable(event in domain_event_strategy()) {
+            let graph_id = graph_id_of(&event);
+            let once = VisualToDomainFunctor
+                .visual_to_domain(&DomainToVisualFunctor.domain_to_visual(event), graph_id);
+            let twice = VisualToDomainFunctor.visual_to_domain(
+                &DomainToVisualFunctor.domain_to_visual(once[0].clone()),
+                graph_id,
+            );
+
+            prop_assert_eq!(once, twice);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_domain_events_become_visuals() {
+        use crate::events::{NodeMetadataChanged, VisualEdgeCreated, VisualNodeCreated};
+        use crate::morphisms::{create_edge_visual, create_node_visual, NodeEntityMap};
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<NodeEntityMap>()
+            .add_event::<DomainEvent>()
+            .add_event::<CreateNodeVisual>()
+            .add_event::<RemoveNodeVisual>()
+            .add_event::<CreateEdgeVisual>()
+            .add_event::<RemoveEdgeVisual>()
+            .add_event::<VisualNodeCreated>()
+            .add_event::<VisualEdgeCreated>()
+            .add_event::<NodeMetadataChanged>()
+            .add_event::<NodePositionChanged>()
+            .add_systems(Update, (handle_domain_events, create_node_visual, create_edge_visual).chain());
+
+        let graph_id = GraphId::new();
+        let (source, target) = (NodeId::new(), NodeId::new());
+        for node_id in [source, target] {
+            app.world_mut().send_event(DomainEvent::NodeAdded {
+                graph_id,
+                node_id,
+                position: None,
+                label: "Service".to_string(),
+            });
+        }
+        app.world_mut().send_event(DomainEvent::EdgeAdded {
+            graph_id,
+            edge_id: EdgeId::new(),
+            source,
+            target,
+            relationship: EdgeRelationship::DependsOn,
+        });
+        app.update();
+
+        let mut nodes = app.world_mut().query::<(&NodeVisual, &Transform, &NodeMetadata)>();
+        let positions: Vec<Vec3> = nodes.iter(app.world()).map(|(_, transform, _)| transform.translation).collect();
+        assert_eq!(positions.len(), 2);
+        assert!(positions[0].distance(positions[1]) > 1.0);
+        assert_eq!(app.world_mut().query::<&EdgeVisual>().iter(app.world()).count(), 1);
+
+        app.world_mut().send_event(DomainEvent::NodeMetadataUpdated {
+            graph_id,
+            node_id: source,
+            metadata: serde_json::json!({ "tags": ["database"], "owner": "ops", "tier": 1 }),
+        });
+        app.world_mut().send_event(DomainEvent::NodeMetadataUpdated {
+            graph_id,
+            node_id: source,
+            metadata: serde_json::json!({ "tier": null, "region": "eu" }),
+        });
+        app.world_mut().send_event(DomainEvent::NodePositionUpdated {
+            graph_id,
+            node_id: target,
+            position: Vec3::new(5.0, 0.0, 0.0),
+        });
+        app.update();
+
+        for (visual, transform, metadata) in nodes.iter(app.world()) {
+            if visual.node_id == source {
+                assert_eq!(metadata.label, "Service");
+                assert_eq!(metadata.tags, vec!["database".to_string()]);
+                // Later updates are merged into earlier ones
+                assert_eq!(metadata.attributes.get("owner"), Some(&serde_json::json!("ops")));
+                assert_eq!(metadata.attributes.get("region"), Some(&serde_json::json!("eu")));
+                assert!(!metadata.attributes.contains_key("tier"));
+            } else {
+                assert_eq!(transform.translation, Vec3::new(5.0, 0.0, 0.0));
+            }
+        }
+    }
 }
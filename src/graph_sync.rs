@@ -0,0 +1,241 @@
+//! Graph Sync: Applying a domain graph as a diff
+//!
+//! Rebuilding every node visual whenever the domain graph changes loses
+//! selection, hover and animation state and churns every entity. A
+//! [`GraphSync`] remembers what it last applied for one graph and, given the
+//! desired nodes, spawns only the new ones, despawns only the ones that went
+//! away, together with the edges attached to them, and updates moved or
+//! relabeled ones in place. Entities are tracked in the shared
+//! [`NodeEntityMap`]; nodes already mapped by something else, such as
+//! `create_node_visual`, are adopted rather than spawned again.
+
+use bevy::prelude::*;
+use cim_contextgraph::{NodeId, EdgeId, ContextGraphId as GraphId};
+use std::collections::{HashMap, HashSet};
+use crate::components::{EdgeVisual, NodeVisualBundle};
+use crate::morphisms::NodeEntityMap;
+use crate::value_objects::NodeMetadata;
+
+/// What one [`GraphSync::sync_graph`] call changed
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GraphSyncReport {
+    /// Nodes given a new entity
+    pub spawned: Vec<(NodeId, Entity)>,
+    /// Nodes whose entity was despawned
+    pub despawned: Vec<NodeId>,
+    /// Edges despawned because one of their nodes was
+    pub despawned_edges: Vec<EdgeId>,
+    /// Nodes whose position or metadata was updated in place
+    pub updated: Vec<(NodeId, Entity)>,
+}
+
+impl GraphSyncReport {
+    /// Whether the sync changed nothing
+    pub fn is_empty(&self) -> bool {
+        self.spawned.is_empty()
+            && self.despawned.is_empty()
+            && self.despawned_edges.is_empty()
+            && self.updated.is_empty()
+    }
+}
+
+/// Last applied state of one graph's nodes
+#[derive(Resource, Debug, Clone)]
+pub struct GraphSync {
+    pub graph_id: GraphId,
+    synced: HashMap<NodeId, (Vec3, NodeMetadata)>,
+}
+
+impl GraphSync {
+    pub fn new(graph_id: GraphId) -> Self {
+        Self {
+            graph_id,
+            synced: HashMap::new(),
+        }
+    }
+
+    /// Number of nodes currently synced
+    pub fn len(&self) -> usize {
+        self.synced.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.synced.is_empty()
+    }
+
+    /// Bring the node visuals in line with `desired`. Edges in `edges` that
+    /// start or end at a despawned node are despawned with it.
+    pub fn sync_graph(
+        &mut self,
+        commands: &mut Commands,
+        node_map: &mut NodeEntityMap,
+        edges: &Query<(Entity, &EdgeVisual)>,
+        desired: &[(NodeId, Vec3, NodeMetadata)],
+    ) -> GraphSyncReport {
+        let mut report = GraphSyncReport::default();
+
+        // Nodes synced before but no longer wanted
+        let wanted: HashSet<NodeId> = desired.iter().map(|(node_id, _, _)| *node_id).collect();
+        let removed: Vec<NodeId> = self.synced.keys()
+            .filter(|node_id| !wanted.contains(node_id))
+            .copied()
+            .collect();
+        let mut removed_entities = HashSet::new();
+        for node_id in removed {
+            self.synced.remove(&node_id);
+            if let Some(entity) = node_map.remove(&node_id) {
+                commands.entity(entity).despawn();
+                removed_entities.insert(entity);
+            }
+            report.despawned.push(node_id);
+        }
+        if !removed_entities.is_empty() {
+            for (entity, edge) in edges.iter() {
+                if removed_entities.contains(&edge.source_entity) || removed_entities.contains(&edge.target_entity) {
+                    commands.entity(entity).despawn();
+                    report.despawned_edges.push(edge.edge_id);
+                }
+            }
+        }
+
+        for (node_id, position, metadata) in desired {
+            let Some(&entity) = node_map.get(node_id) else {
+                let entity = commands.spawn((
+                    NodeVisualBundle::new(*node_id, self.graph_id, *position),
+                    metadata.clone(),
+                )).id();
+                node_map.insert(*node_id, entity);
+                self.synced.insert(*node_id, (*position, metadata.clone()));
+                report.spawned.push((*node_id, entity));
+                continue;
+            };
+
+            let previous = self.synced.get(node_id);
+            let moved = previous.is_none_or(|(synced, _)| synced != position);
+            let relabeled = previous.is_none_or(|(_, synced)| synced != metadata);
+            if moved {
+                // Only the translation, so scale and rotation are kept
+                let position = *position;
+                commands.entity(entity).queue(move |mut entity: EntityWorldMut| {
+                    if let Some(mut transform) = entity.get_mut::<Transform>() {
+                        transform.translation = position;
+                    }
+                });
+            }
+            if relabeled {
+                commands.entity(entity).insert(metadata.clone());
+            }
+            if moved || relabeled {
+                self.synced.insert(*node_id, (*position, metadata.clone()));
+                report.updated.push((*node_id, entity));
+            }
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(label: &str, position: Vec3) -> (NodeId, Vec3, NodeMetadata) {
+        let metadata = NodeMetadata {
+            label: label.to_string(),
+            ..default()
+        };
+        (NodeId::new(), position, metadata)
+    }
+
+    /// Run one sync in `app` and return its report
+    fn sync(app: &mut App, desired: Vec<(NodeId, Vec3, NodeMetadata)>) -> GraphSyncReport {
+        let report = app.world_mut().run_system_cached_with(
+            |In(desired): In<Vec<(NodeId, Vec3, NodeMetadata)>>,
+             mut commands: Commands,
+             mut node_map: ResMut<NodeEntityMap>,
+             edges: Query<(Entity, &EdgeVisual)>,
+             mut graph_sync: ResMut<GraphSync>| {
+                graph_sync.sync_graph(&mut commands, &mut node_map, &edges, &desired)
+            },
+            desired,
+        ).unwrap();
+        app.update();
+        report
+    }
+
+    fn setup() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<NodeEntityMap>()
+            .insert_resource(GraphSync::new(GraphId::new()));
+        app
+    }
+
+    #[test]
+    fn test_syncing_identical_graph_changes_nothing() {
+        let mut app = setup();
+        let graph = vec![node("a", Vec3::ZERO), node("b", Vec3::X), node("c", Vec3::Y)];
+
+        let first = sync(&mut app, graph.clone());
+        assert_eq!(first.spawned.len(), 3);
+        let entities: Vec<Entity> = app.world_mut().query::<Entity>().iter(app.world()).collect();
+
+        let second = sync(&mut app, graph);
+        assert!(second.is_empty());
+        let after: Vec<Entity> = app.world_mut().query::<Entity>().iter(app.world()).collect();
+        assert_eq!(entities, after);
+    }
+
+    #[test]
+    fn test_sync_applies_only_the_difference() {
+        let mut app = setup();
+        let (a, b, c) = (node("a", Vec3::ZERO), node("b", Vec3::X), node("c", Vec3::Y));
+        sync(&mut app, vec![a.clone(), b.clone()]);
+        let a_entity = *app.world().resource::<NodeEntityMap>().get(&a.0).unwrap();
+        let b_entity = *app.world().resource::<NodeEntityMap>().get(&b.0).unwrap();
+        app.world_mut().get_mut::<Transform>(a_entity).unwrap().scale = Vec3::splat(2.0);
+
+        let moved_a = (a.0, Vec3::new(5.0, 0.0, 0.0), a.2.clone());
+        let report = sync(&mut app, vec![moved_a, c.clone()]);
+        assert_eq!(report.updated, vec![(a.0, a_entity)]);
+        assert_eq!(report.despawned, vec![b.0]);
+        assert_eq!(report.spawned.len(), 1);
+        assert_eq!(report.spawned[0].0, c.0);
+
+        let transform = app.world().get::<Transform>(a_entity).unwrap();
+        assert_eq!(transform.translation, Vec3::new(5.0, 0.0, 0.0));
+        assert_eq!(transform.scale, Vec3::splat(2.0));
+        assert!(app.world().get_entity(b_entity).is_err());
+        assert!(app.world().resource::<NodeEntityMap>().get(&b.0).is_none());
+        assert_eq!(app.world().resource::<GraphSync>().len(), 2);
+    }
+
+    #[test]
+    fn test_removed_nodes_take_their_edges_along() {
+        let mut app = setup();
+        let (a, b, c) = (node("a", Vec3::ZERO), node("b", Vec3::X), node("c", Vec3::Y));
+        sync(&mut app, vec![a.clone(), b.clone(), c.clone()]);
+        let entity = |app: &App, node_id: &NodeId| *app.world().resource::<NodeEntityMap>().get(node_id).unwrap();
+        let (a_entity, b_entity, c_entity) = (entity(&app, &a.0), entity(&app, &b.0), entity(&app, &c.0));
+
+        let graph_id = app.world().resource::<GraphSync>().graph_id;
+        let mut spawn_edge = |source, target| {
+            let edge_id = EdgeId::new();
+            let entity = app.world_mut()
+                .spawn(crate::components::EdgeVisualBundle::new(edge_id, graph_id, source, target))
+                .id();
+            (edge_id, entity)
+        };
+        let (a_to_b, a_to_b_entity) = spawn_edge(a_entity, b_entity);
+        let (c_to_b, c_to_b_entity) = spawn_edge(c_entity, b_entity);
+        let (_, a_to_c_entity) = spawn_edge(a_entity, c_entity);
+
+        let report = sync(&mut app, vec![a, c]);
+        assert_eq!(report.despawned, vec![b.0]);
+        let despawned_edges: HashSet<EdgeId> = report.despawned_edges.into_iter().collect();
+        assert_eq!(despawned_edges, HashSet::from([a_to_b, c_to_b]));
+        assert!(app.world().get_entity(a_to_b_entity).is_err());
+        assert!(app.world().get_entity(c_to_b_entity).is_err());
+        assert!(app.world().get_entity(a_to_c_entity).is_ok());
+    }
+}
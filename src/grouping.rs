@@ -0,0 +1,267 @@
+//! Grouping: Outlining nodes that share a tag
+//!
+//! With a [`GroupBy`] resource present, every group of nodes whose
+//! [`NodeMetadata`] carries the grouped tag is outlined by its convex hull
+//! in the XY plane, shaded towards the inside and labelled with the tag.
+//! Groups are recomputed each frame, so they follow nodes as they move.
+//! Groups of one or two nodes are drawn as a circle around them.
+
+use bevy::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+use crate::components::{GraphCamera, NodeVisual};
+use crate::picking::world_to_screen;
+use crate::value_objects::NodeMetadata;
+
+/// Distance a group's outline keeps from the nodes inside it
+const GROUP_PADDING: f32 = 1.0;
+
+/// Number of outlines drawn from the hull towards its center to shade the
+/// region; gizmos have no filled polygons
+const GROUP_SHADING_RINGS: usize = 6;
+
+/// Hue step between consecutive groups, the golden angle, so any number of
+/// groups get well separated colors
+const GROUP_HUE_STEP: f32 = 137.508;
+
+/// Which tags nodes are grouped by
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub enum GroupBy {
+    /// One group of the nodes carrying this tag
+    Tag(String),
+    /// One group per distinct tag; a node with several tags is in several
+    /// groups
+    AllTags,
+}
+
+/// Screen-space text entity displaying a group's tag
+#[derive(Component, Debug)]
+pub struct TagGroupLabel {
+    pub tag: String,
+}
+
+/// Plugin that draws tag groups selected by [`GroupBy`]
+pub struct GroupingPlugin;
+
+impl Plugin for GroupingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (draw_tag_groups, update_tag_group_labels));
+    }
+}
+
+/// Convex hull of `points`, counter-clockwise, without collinear points.
+/// Fewer than three distinct points are returned as they are.
+pub fn convex_hull_2d(points: &[Vec2]) -> Vec<Vec2> {
+    let mut points = points.to_vec();
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+
+    // Andrew's monotone chain: lower hull left to right, then upper hull
+    // right to left
+    let mut hull: Vec<Vec2> = Vec::with_capacity(points.len() * 2);
+    for pass in [&points[..], &points.iter().rev().copied().collect::<Vec<_>>()[..]] {
+        let start = hull.len();
+        for &point in pass {
+            while hull.len() >= start + 2 {
+                let (a, b) = (hull[hull.len() - 2], hull[hull.len() - 1]);
+                if (b - a).perp_dot(point - a) > 0.0 {
+                    break;
+                }
+                hull.pop();
+            }
+            hull.push(point);
+        }
+        // The last point starts the other half
+        hull.pop();
+    }
+    hull
+}
+
+/// Positions of the nodes in each group, keyed by tag
+pub fn tag_groups<'a>(
+    group_by: &GroupBy,
+    nodes: impl IntoIterator<Item = (&'a NodeMetadata, Vec3)>,
+) -> BTreeMap<String, Vec<Vec3>> {
+    let mut groups: BTreeMap<String, Vec<Vec3>> = BTreeMap::new();
+    for (metadata, position) in nodes {
+        for tag in &metadata.tags {
+            let grouped = match group_by {
+                GroupBy::Tag(grouped) => grouped == tag,
+                GroupBy::AllTags => true,
+            };
+            if grouped {
+                groups.entry(tag.clone()).or_default().push(position);
+            }
+        }
+    }
+    groups
+}
+
+/// Color of the group at `index` among the sorted groups
+fn group_color(index: usize) -> Color {
+    Color::hsva((index as f32 * GROUP_HUE_STEP) % 360.0, 0.6, 0.9, 0.35)
+}
+
+/// System that outlines and shades each tag group
+pub fn draw_tag_groups(
+    group_by: Option<Res<GroupBy>>,
+    nodes: Query<(&NodeMetadata, &GlobalTransform), With<NodeVisual>>,
+    mut gizmos: Gizmos,
+) {
+    let Some(group_by) = group_by else {
+        return;
+    };
+
+    let groups = tag_groups(&group_by, nodes.iter().map(|(metadata, transform)| {
+        (metadata, transform.translation())
+    }));
+    for (index, positions) in groups.values().enumerate() {
+        let color = group_color(index);
+        let z = positions.iter().map(|position| position.z).sum::<f32>() / positions.len() as f32;
+        let points: Vec<Vec2> = positions.iter().map(|position| position.truncate()).collect();
+        let hull = convex_hull_2d(&points);
+        let center = hull.iter().copied().sum::<Vec2>() / hull.len() as f32;
+
+        for ring in 0..GROUP_SHADING_RINGS {
+            let shrink = ring as f32 / GROUP_SHADING_RINGS as f32;
+            let ring_color = color.with_alpha(color.alpha() * (1.0 - shrink));
+
+            if hull.len() < 3 {
+                let radius = hull.iter().map(|point| point.distance(center)).fold(0.0, f32::max)
+                    + GROUP_PADDING;
+                gizmos.circle(
+                    Isometry3d::from_translation(center.extend(z)),
+                    radius * (1.0 - shrink),
+                    ring_color,
+                );
+                continue;
+            }
+
+            let outline = hull.iter()
+                .chain(hull.first())
+                .map(|&point| {
+                    let padded = point + (point - center).normalize_or_zero() * GROUP_PADDING;
+                    center.lerp(padded, 1.0 - shrink).extend(z)
+                });
+            gizmos.linestrip(outline, ring_color);
+        }
+    }
+}
+
+/// System that keeps one label per tag group, centered above the group
+pub fn update_tag_group_labels(
+    mut commands: Commands,
+    group_by: Option<Res<GroupBy>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<GraphCamera>>,
+    nodes: Query<(&NodeMetadata, &GlobalTransform), With<NodeVisual>>,
+    mut labels: Query<(Entity, &TagGroupLabel, &mut Node, &mut Visibility, &ComputedNode)>,
+) {
+    let groups = group_by.map(|group_by| {
+        tag_groups(&group_by, nodes.iter().map(|(metadata, transform)| {
+            (metadata, transform.translation())
+        }))
+    }).unwrap_or_default();
+    let camera = cameras.iter().find(|(camera, _)| camera.is_active);
+
+    let mut labelled: HashMap<&str, Entity> = HashMap::new();
+    for (entity, label, mut node, mut visibility, computed) in labels.iter_mut() {
+        let Some(positions) = groups.get(&label.tag) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+        labelled.insert(&label.tag, entity);
+
+        let top = positions.iter()
+            .copied()
+            .max_by(|a, b| a.y.total_cmp(&b.y))
+            .map(|top| top + Vec3::Y * GROUP_PADDING);
+        let screen_position = camera.zip(top).and_then(|((camera, camera_transform), top)| {
+            world_to_screen(camera, camera_transform, top)
+        });
+
+        match screen_position {
+            Some(position) => {
+                // Centered horizontally, just above the group
+                let size = computed.size() * computed.inverse_scale_factor();
+                node.left = Val::Px(position.x - size.x / 2.0);
+                node.top = Val::Px(position.y - size.y);
+                visibility.set_if_neq(Visibility::Inherited);
+            }
+            None => {
+                visibility.set_if_neq(Visibility::Hidden);
+            }
+        }
+    }
+
+    for (index, tag) in groups.keys().enumerate() {
+        if labelled.contains_key(tag.as_str()) {
+            continue;
+        }
+        commands.spawn((
+            Text::new(tag.clone()),
+            TextFont {
+                font_size: 14.0,
+                ..default()
+            },
+            TextColor(group_color(index).with_alpha(1.0)),
+            Node {
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            Visibility::Hidden,
+            TagGroupLabel { tag: tag.clone() },
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convex_hull_drops_inner_and_collinear_points() {
+        let points = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(2.0, 0.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(2.0, 2.0),
+            Vec2::new(0.0, 2.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(0.0, 2.0),
+        ];
+        assert_eq!(
+            convex_hull_2d(&points),
+            vec![Vec2::new(0.0, 0.0), Vec2::new(2.0, 0.0), Vec2::new(2.0, 2.0), Vec2::new(0.0, 2.0)]
+        );
+    }
+
+    #[test]
+    fn test_convex_hull_of_few_points() {
+        assert!(convex_hull_2d(&[]).is_empty());
+        assert_eq!(convex_hull_2d(&[Vec2::ONE, Vec2::ONE]), vec![Vec2::ONE]);
+        let line = [Vec2::ZERO, Vec2::X, Vec2::X * 2.0];
+        assert_eq!(convex_hull_2d(&line), vec![Vec2::ZERO, Vec2::X * 2.0]);
+    }
+
+    #[test]
+    fn test_tag_groups() {
+        let tagged = |tags: &[&str]| NodeMetadata {
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            ..default()
+        };
+        let database = tagged(&["database"]);
+        let both = tagged(&["database", "cache"]);
+        let untagged = tagged(&[]);
+        let nodes = [(&database, Vec3::X), (&both, Vec3::Y), (&untagged, Vec3::Z)];
+
+        let groups = tag_groups(&GroupBy::Tag("database".to_string()), nodes);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups["database"], vec![Vec3::X, Vec3::Y]);
+
+        let groups = tag_groups(&GroupBy::AllTags, nodes);
+        assert_eq!(groups.keys().collect::<Vec<_>>(), ["cache", "database"]);
+        assert_eq!(groups["cache"], vec![Vec3::Y]);
+    }
+}
@@ -3,11 +3,47 @@
 //! In ECS, command handlers are systems that process commands and emit events.
 //! These systems enforce business rules and maintain aggregate invariants.
 
-use crate::aggregate::*;
-use crate::commands::*;
-use crate::events::*;
-use crate::value_objects::*;
+use crate::aggregate::VisualNodeAggregate;
+use crate::animation::{AnimationCompleted, AnimationPlugin};
+use crate::camera::CameraAnimation;
+use crate::commands::{CreateVisualNode, DeleteVisualNode, MoveNode, PanCanvas, UpdateNodeStyle, ZoomCanvas};
+use crate::components::{AnimatedTransition, GraphCamera, GraphRegion, GraphVisual, NodeStyle, NodeVisual};
+use crate::easing::Easing;
+use crate::events::{CanvasPanned, CanvasZoomed, NodeMoved, NodeStyleUpdated, VisualNodeCreated, VisualNodeDeleted};
+use crate::hover::{set_base_material, BaseMaterial};
+use crate::morphisms::{NodeEntityMap, NodeEntityMapPlugin};
+use crate::resources::ActiveGraph;
+use crate::selection::SelectionCommandsPlugin;
+use crate::value_objects::{CanvasState, NodeVisualStyle, Viewport};
 use bevy::prelude::*;
+use cim_contextgraph::ContextGraphId as GraphId;
+
+/// Smallest zoom factor a canvas can be zoomed out to
+pub const MIN_CANVAS_ZOOM: f32 = 0.1;
+
+/// Largest zoom factor a canvas can be zoomed in to
+pub const MAX_CANVAS_ZOOM: f32 = 10.0;
+
+/// Configuration for animated node moves
+#[derive(Resource, Debug, Clone)]
+pub struct MoveAnimationConfig {
+    /// Duration of an animated move in seconds
+    pub duration: f32,
+    pub easing: Easing,
+}
+
+impl Default for MoveAnimationConfig {
+    fn default() -> Self {
+        Self { duration: 0.5, easing: Easing::default() }
+    }
+}
+
+/// A move whose `NodeMoved` event is sent once its animation completes
+#[derive(Component, Debug, Clone)]
+pub struct PendingNodeMove {
+    pub old_position: Vec3,
+    pub new_position: Vec3,
+}
 
 /// System that handles CreateVisualNode commands
 pub fn handle_create_visual_node(
@@ -17,129 +53,613 @@ pub fn handle_create_visual_node(
 ) {
     for event in create_events.read() {
         // Create the aggregate entity with its components
+        let position: Vec3 = event.position.clone().into();
         let entity = commands
-            .spawn(VisualNodeAggregate::new(
-                event.node_id.clone(),
-                event.position.clone(),
-            ))
+            .spawn(
+                VisualNodeAggregate::new(event.node_id, event.graph_id, position)
+                    .with_style(event.visual_style.clone()),
+            )
             .id();
 
         // Emit domain event
-        created_events.send(VisualNodeCreated {
+        created_events.write(VisualNodeCreated {
             entity,
-            node_id: event.node_id.clone(),
-            position: event.position.clone(),
+            node_id: event.node_id,
+            position,
         });
     }
 }
 
 /// System that handles MoveNode commands
+///
+/// Animated moves glide the node's transform to the new position;
+/// `NodeMoved` is sent by [`complete_animated_moves`] once it arrives.
 pub fn handle_move_node(
+    mut commands: Commands,
+    config: Res<MoveAnimationConfig>,
+    node_map: Res<NodeEntityMap>,
     mut move_events: EventReader<MoveNode>,
     mut moved_events: EventWriter<NodeMoved>,
-    mut query: Query<(Entity, &NodeId, &mut Position)>,
+    mut nodes: Query<&mut Transform, With<NodeVisual>>,
 ) {
     for event in move_events.read() {
-        // Find the node entity
-        for (entity, node_id, mut position) in query.iter_mut() {
-            if node_id == &event.node_id {
-                let old_position = position.clone();
-
-                // Apply business rule: validate position is within bounds
-                if event.new_position.is_valid() {
-                    *position = event.new_position.clone();
-
-                    // Emit domain event
-                    moved_events.send(NodeMoved {
-                        entity,
-                        node_id: node_id.clone(),
-                        old_position,
-                        new_position: event.new_position.clone(),
-                    });
-                }
-                break;
-            }
+        // Apply business rule: validate position is within bounds
+        if !event.new_position.is_valid() {
+            continue;
+        }
+        let Some(&entity) = node_map.get(&event.node_id) else {
+            continue;
+        };
+        let Ok(mut transform) = nodes.get_mut(entity) else {
+            continue;
+        };
+
+        let old_position = transform.translation;
+        let new_position: Vec3 = event.new_position.clone().into();
+        if event.animate {
+            commands.entity(entity).insert((
+                AnimatedTransition {
+                    start_position: old_position,
+                    target_position: new_position,
+                    progress: 0.0,
+                    duration: config.duration,
+                    easing: config.easing,
+                },
+                PendingNodeMove { old_position, new_position },
+            ));
+            continue;
+        }
+
+        transform.translation = new_position;
+
+        // Emit domain event
+        moved_events.write(NodeMoved {
+            entity,
+            node_id: event.node_id,
+            old_position,
+            new_position,
+        });
+    }
+}
+
+/// System that sends `NodeMoved` for animated moves whose transition has
+/// completed
+pub fn complete_animated_moves(
+    mut commands: Commands,
+    mut completed_events: EventReader<AnimationCompleted>,
+    mut moved_events: EventWriter<NodeMoved>,
+    query: Query<(&NodeVisual, &PendingNodeMove)>,
+) {
+    for &AnimationCompleted { entity } in completed_events.read() {
+        let Ok((node, pending)) = query.get(entity) else {
+            continue;
+        };
+        moved_events.write(NodeMoved {
+            entity,
+            node_id: node.node_id,
+            old_position: pending.old_position,
+            new_position: pending.new_position,
+        });
+        commands.entity(entity).remove::<PendingNodeMove>();
+    }
+}
+
+/// System that handles UpdateNodeStyle commands
+pub fn handle_update_node_style(
+    node_map: Res<NodeEntityMap>,
+    mut style_events: EventReader<UpdateNodeStyle>,
+    mut updated_events: EventWriter<NodeStyleUpdated>,
+    mut query: Query<&mut NodeStyle>,
+) {
+    for event in style_events.read() {
+        let Some(&entity) = node_map.get(&event.node_id) else {
+            continue;
+        };
+        let Ok(mut style) = query.get_mut(entity) else {
+            continue;
+        };
+
+        let old_style = NodeVisualStyle::from(style.clone());
+        if old_style == event.new_style {
+            continue;
         }
+        *style = NodeStyle::from(event.new_style.clone());
+
+        // Emit domain event
+        updated_events.write(NodeStyleUpdated {
+            entity,
+            node_id: event.node_id,
+            old_style,
+            new_style: event.new_style.clone(),
+        });
+    }
+}
+
+/// System that applies updated node styles to the node's material and scale
+///
+/// Materials are often shared between nodes, so the node is given its own
+/// copy in the new color. A highlighted node gets it as its base material,
+/// so the highlight is redone from, and ends on, the styled color.
+pub fn apply_node_style(
+    mut updated_events: EventReader<NodeStyleUpdated>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut query: Query<(&mut MeshMaterial3d<StandardMaterial>, Option<&mut BaseMaterial>, &mut Transform)>,
+) {
+    for event in updated_events.read() {
+        let Ok((mut current, mut highlighted, mut transform)) = query.get_mut(event.entity) else {
+            continue;
+        };
+        transform.scale = Vec3::splat(event.new_style.size);
+
+        let material = highlighted.as_ref().map_or(&current.0, |base| &base.0);
+        let Some(original) = materials.get(material).cloned() else {
+            continue;
+        };
+        let own = materials.add(StandardMaterial { base_color: event.new_style.color, ..original });
+        set_base_material(&mut current, highlighted.as_deref_mut(), own);
     }
 }
 
 /// System that handles DeleteVisualNode commands
 pub fn handle_delete_visual_node(
     mut commands: Commands,
+    node_map: Res<NodeEntityMap>,
     mut delete_events: EventReader<DeleteVisualNode>,
     mut deleted_events: EventWriter<VisualNodeDeleted>,
-    query: Query<(Entity, &NodeId, &Position)>,
+    query: Query<&Transform, With<NodeVisual>>,
 ) {
     for event in delete_events.read() {
         // Find and remove the node entity
-        for (entity, node_id, position) in query.iter() {
-            if node_id == &event.node_id {
-                // Store final state before deletion
-                let final_position = position.clone();
-
-                // Remove the entity (aggregate)
-                commands.entity(entity).despawn();
-
-                // Emit domain event
-                deleted_events.send(VisualNodeDeleted {
-                    node_id: node_id.clone(),
-                    final_position,
-                });
-                break;
-            }
+        let Some(&entity) = node_map.get(&event.node_id) else {
+            continue;
+        };
+        let Ok(transform) = query.get(entity) else {
+            continue;
+        };
+
+        // Remove the entity (aggregate)
+        commands.entity(entity).despawn();
+
+        // Emit domain event
+        deleted_events.write(VisualNodeDeleted {
+            node_id: event.node_id,
+            final_position: transform.translation,
+        });
+    }
+}
+
+/// System that handles PanCanvas commands, panning the canvas of the
+/// command's graph
+pub fn handle_pan_canvas(
+    mut pan_events: EventReader<PanCanvas>,
+    mut panned_events: EventWriter<CanvasPanned>,
+    mut canvases: Query<(&GraphVisual, &mut CanvasState)>,
+) {
+    for event in pan_events.read() {
+        for (_, mut canvas) in canvases.iter_mut().filter(|(graph, _)| graph.graph_id == event.graph_id) {
+            canvas.offset += event.delta;
+
+            // Emit domain event
+            panned_events.write(CanvasPanned {
+                graph_id: event.graph_id,
+                delta: event.delta,
+                new_offset: canvas.offset,
+            });
         }
     }
 }
 
-/// System that handles SelectNode commands
-pub fn handle_select_node(
-    mut select_events: EventReader<SelectNode>,
-    mut selected_events: EventWriter<NodeSelected>,
-    mut deselected_events: EventWriter<NodeDeselected>,
-    mut query: Query<(Entity, &NodeId, &mut InteractionState)>,
+/// System that handles ZoomCanvas commands, zooming the canvas of the
+/// command's graph
+///
+/// The zoom is clamped to `MIN_CANVAS_ZOOM..=MAX_CANVAS_ZOOM`. The focal
+/// point, or the viewport center without one, stays where it is on screen.
+/// Zoom factors that are not finite and positive are ignored.
+pub fn handle_zoom_canvas(
+    mut zoom_events: EventReader<ZoomCanvas>,
+    mut zoomed_events: EventWriter<CanvasZoomed>,
+    mut canvases: Query<(&GraphVisual, &mut CanvasState, Option<&Viewport>)>,
 ) {
-    for event in select_events.read() {
-        // Handle multi-select logic
-        if !event.multi_select {
-            // Deselect all other nodes
-            for (entity, node_id, mut state) in query.iter_mut() {
-                if state.is_selected && node_id != &event.node_id {
-                    state.is_selected = false;
-                    deselected_events.send(NodeDeselected {
-                        entity,
-                        node_id: node_id.clone(),
-                    });
-                }
+    for event in zoom_events.read() {
+        if !event.zoom_factor.is_finite() || event.zoom_factor <= 0.0 {
+            warn!("Ignoring ZoomCanvas with zoom factor {}", event.zoom_factor);
+            continue;
+        }
+
+        for (_, mut canvas, viewport) in canvases.iter_mut().filter(|(graph, ..)| graph.graph_id == event.graph_id) {
+            let old_zoom = canvas.zoom;
+            let new_zoom = (old_zoom * event.zoom_factor).clamp(MIN_CANVAS_ZOOM, MAX_CANVAS_ZOOM);
+            if new_zoom == old_zoom {
+                continue;
             }
+
+            let focal_point = event.focal_point.unwrap_or_else(|| {
+                viewport.map_or(Vec2::ZERO, |viewport| Vec2::new(viewport.width, viewport.height) / 2.0)
+            });
+            canvas.offset = focal_point - (focal_point - canvas.offset) * (new_zoom / old_zoom);
+            canvas.zoom = new_zoom;
+
+            // Emit domain event
+            zoomed_events.write(CanvasZoomed {
+                graph_id: event.graph_id,
+                old_zoom,
+                new_zoom,
+                focal_point: event.focal_point,
+            });
         }
+    }
+}
 
-        // Select the target node
-        for (entity, node_id, mut state) in query.iter_mut() {
-            if node_id == &event.node_id && !state.is_selected {
-                state.is_selected = true;
-                selected_events.send(NodeSelected {
-                    entity,
-                    node_id: node_id.clone(),
-                });
+/// System that moves the graph cameras along with the canvas of the active
+/// graph, or of any graph while none is active
+///
+/// Pans move the camera so the canvas point under the viewport center
+/// follows the drag. Zooms dolly perspective cameras towards the canvas
+/// point under the focal point and scale orthographic ones around it; a
+/// zoom without a focal point centers on the graph's [`GraphRegion`] if it
+/// has one. Cameras in a `CameraAnimation` are left to the animation.
+pub fn sync_canvas_camera(
+    mut panned_events: EventReader<CanvasPanned>,
+    mut zoomed_events: EventReader<CanvasZoomed>,
+    active_graph: Option<Res<ActiveGraph>>,
+    regions: Query<&GraphRegion>,
+    mut cameras: Query<(&Camera, &mut Transform, &mut Projection), (With<GraphCamera>, Without<CameraAnimation>)>,
+) {
+    let active = active_graph.and_then(|active| active.graph_id);
+    let followed = |graph_id: &GraphId| active.is_none_or(|active| active == *graph_id);
+    let panned: Vec<Vec2> = panned_events.read()
+        .filter(|event| followed(&event.graph_id))
+        .map(|event| event.delta)
+        .collect();
+    let zoomed: Vec<CanvasZoomed> = zoomed_events.read()
+        .filter(|event| followed(&event.graph_id))
+        .cloned()
+        .collect();
+    if panned.is_empty() && zoomed.is_empty() {
+        return;
+    }
+
+    for (camera, mut transform, mut projection) in cameras.iter_mut() {
+        let center = camera.logical_viewport_size().map(|size| size / 2.0);
+
+        for &delta in &panned {
+            let Some(center) = center else {
                 break;
+            };
+            if let (Some(from), Some(to)) = (
+                canvas_point(camera, &transform, center),
+                canvas_point(camera, &transform, center + delta),
+            ) {
+                transform.translation += from - to;
+            }
+        }
+
+        for event in &zoomed {
+            let region = regions.iter().find(|region| region.graph_id == event.graph_id);
+            let focal_point = match (event.focal_point, region) {
+                (None, Some(region)) => Some(region.origin),
+                (focal_point, _) => focal_point.or(center)
+                    .and_then(|screen| canvas_point(camera, &transform, screen))
+                    .or_else(|| view_axis_canvas_point(&transform)),
+            };
+            if let Some(focal_point) = focal_point {
+                zoom_camera(&mut transform, &mut projection, focal_point, event.new_zoom / event.old_zoom);
             }
         }
     }
 }
 
+/// Where the ray through `screen`, in logical pixels, meets the canvas
+/// plane at z = 0
+fn canvas_point(camera: &Camera, transform: &Transform, screen: Vec2) -> Option<Vec3> {
+    let ray = camera.viewport_to_world(&GlobalTransform::from(*transform), screen).ok()?;
+    let distance = ray.intersect_plane(Vec3::ZERO, InfinitePlane3d::new(Vec3::Z))?;
+    Some(ray.get_point(distance))
+}
+
+/// Where the camera's view axis meets the canvas plane, for cameras that
+/// have not been rendered yet
+fn view_axis_canvas_point(transform: &Transform) -> Option<Vec3> {
+    let ray = Ray3d::new(transform.translation, transform.forward());
+    let distance = ray.intersect_plane(Vec3::ZERO, InfinitePlane3d::new(Vec3::Z))?;
+    Some(ray.get_point(distance))
+}
+
+/// Magnifies the view by `ratio` around `focal_point`: perspective cameras
+/// move towards it, orthographic ones shrink their scale and keep their
+/// depth
+fn zoom_camera(transform: &mut Transform, projection: &mut Projection, focal_point: Vec3, ratio: f32) {
+    let offset = transform.translation - focal_point;
+    match projection {
+        Projection::Perspective(_) => {
+            transform.translation = focal_point + offset / ratio;
+        }
+        Projection::Orthographic(orthographic) => {
+            let forward = transform.forward();
+            let depth = forward * offset.dot(*forward);
+            orthographic.scale /= ratio;
+            transform.translation = focal_point + depth + (offset - depth) / ratio;
+        }
+        _ => {}
+    }
+}
+
 /// Plugin that registers all command handlers
 pub struct CommandHandlerPlugin;
 
 impl Plugin for CommandHandlerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
+        if !app.is_plugin_added::<AnimationPlugin>() {
+            app.add_plugins(AnimationPlugin);
+        }
+        if !app.is_plugin_added::<NodeEntityMapPlugin>() {
+            app.add_plugins(NodeEntityMapPlugin);
+        }
+        // SelectNode commands, for apps without the full selection plugin
+        if !app.is_plugin_added::<SelectionCommandsPlugin>() {
+            app.add_plugins(SelectionCommandsPlugin);
+        }
+
+        app.init_resource::<MoveAnimationConfig>()
+            .add_event::<CreateVisualNode>()
+            .add_event::<MoveNode>()
+            .add_event::<UpdateNodeStyle>()
+            .add_event::<DeleteVisualNode>()
+            .add_event::<PanCanvas>()
+            .add_event::<ZoomCanvas>()
+            .add_event::<VisualNodeCreated>()
+            .add_event::<NodeMoved>()
+            .add_event::<NodeStyleUpdated>()
+            .add_event::<VisualNodeDeleted>()
+            .add_event::<CanvasPanned>()
+            .add_event::<CanvasZoomed>()
+            .add_systems(
             Update,
             (
                 handle_create_visual_node,
                 handle_move_node,
+                complete_animated_moves,
+                (handle_update_node_style, apply_node_style).chain(),
                 handle_delete_visual_node,
-                handle_select_node,
-            ),
+                (handle_pan_canvas, handle_zoom_canvas, sync_canvas_camera).chain(),
+            )
+                .in_set(crate::plugin::CimSet::Commands),
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::NodeShape;
+    use crate::value_objects::Position;
+    use bevy::time::TimeUpdateStrategy;
+    use cim_contextgraph::{NodeId, ContextGraphId as GraphId};
+    use std::time::Duration;
+
+    #[test]
+    fn test_animated_move_ends_at_target_through_plugin() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(100)))
+            .init_resource::<Assets<StandardMaterial>>()
+            .add_plugins(CommandHandlerPlugin);
+
+        let node_id = NodeId::new();
+        let entity = app.world_mut().spawn(VisualNodeAggregate::new(node_id, GraphId::new(), Vec3::ZERO)).id();
+        app.update();
+
+        let target = Vec3::new(4.0, -2.0, 0.0);
+        app.world_mut().send_event(MoveNode { node_id, new_position: Position::new(4.0, -2.0, 0.0), animate: true });
+        let mut moves = Vec::new();
+        for frame in 0..10 {
+            app.update();
+            if frame == 1 {
+                let halfway = app.world().get::<Transform>(entity).unwrap().translation;
+                assert!(halfway != Vec3::ZERO && halfway != target);
+            }
+            moves.extend(app.world().resource::<Events<NodeMoved>>()
+                .iter_current_update_events()
+                .map(|event| (event.old_position, event.new_position)));
+        }
+        assert_eq!(app.world().get::<Transform>(entity).unwrap().translation, target);
+        assert!(app.world().get::<PendingNodeMove>(entity).is_none());
+        assert_eq!(moves, vec![(Vec3::ZERO, target)]);
+
+        // Instant moves arrive, and are reported, in the same frame
+        app.world_mut().send_event(MoveNode { node_id, new_position: Position::new(0.0, 1.0, 0.0), animate: false });
+        app.update();
+        assert_eq!(app.world().get::<Transform>(entity).unwrap().translation, Vec3::Y);
+        let moved: Vec<Vec3> = app.world().resource::<Events<NodeMoved>>()
+            .iter_current_update_events()
+            .map(|event| event.new_position)
+            .collect();
+        assert_eq!(moved, vec![Vec3::Y]);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_select_node_command_is_handled_through_plugin() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<Assets<StandardMaterial>>()
+            .add_plugins(CommandHandlerPlugin);
+
+        let node_id = NodeId::new();
+        let entity = app.world_mut().spawn(VisualNodeAggregate::new(node_id, GraphId::new(), Vec3::ZERO)).id();
+        app.update();
+
+        app.world_mut().send_event(crate::commands::SelectNode { node_id, multi_select: false });
+        app.update();
+        assert!(app.world().get::<crate::components::Selected>(entity).is_some());
+    }
+
+    #[test]
+    fn test_update_node_style_changes_visual_once() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, NodeEntityMapPlugin))
+            .init_resource::<Assets<StandardMaterial>>()
+            .add_event::<UpdateNodeStyle>()
+            .add_event::<NodeStyleUpdated>()
+            .add_systems(Update, (handle_update_node_style, apply_node_style).chain());
+
+        let node_id = NodeId::new();
+        let highlighted_id = NodeId::new();
+        let (shared, variant) = {
+            let mut materials = app.world_mut().resource_mut::<Assets<StandardMaterial>>();
+            (materials.add(StandardMaterial::default()), materials.add(StandardMaterial::default()))
+        };
+        let entity = app.world_mut().spawn((
+            VisualNodeAggregate::new(node_id, GraphId::new(), Vec3::ZERO),
+            MeshMaterial3d(shared.clone()),
+        )).id();
+        let highlighted = app.world_mut().spawn((
+            VisualNodeAggregate::new(highlighted_id, GraphId::new(), Vec3::ZERO),
+            MeshMaterial3d(variant.clone()),
+            BaseMaterial(shared.clone()),
+        )).id();
+
+        let new_style = NodeVisualStyle {
+            color: Color::srgb(1.0, 0.0, 0.0),
+            size: 2.0,
+            shape: NodeShape::Diamond,
+            border_color: Color::NONE,
+            border_width: 0.0,
+        };
+        app.world_mut().send_event(UpdateNodeStyle { node_id, new_style: new_style.clone() });
+        app.update();
+
+        let world = app.world();
+        assert_eq!(NodeVisualStyle::from(world.get::<NodeStyle>(entity).unwrap().clone()), new_style);
+        assert_eq!(world.get::<Transform>(entity).unwrap().scale, Vec3::splat(2.0));
+        let materials = world.resource::<Assets<StandardMaterial>>();
+        let own = &world.get::<MeshMaterial3d<StandardMaterial>>(entity).unwrap().0;
+        assert_ne!(own, &shared);
+        assert_eq!(materials.get(own).unwrap().base_color, new_style.color);
+        assert_eq!(materials.get(&shared).unwrap().base_color, StandardMaterial::default().base_color);
+
+        let updates: Vec<&NodeStyleUpdated> = world.resource::<Events<NodeStyleUpdated>>()
+            .iter_current_update_events()
+            .collect();
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].entity, entity);
+        assert_eq!(updates[0].old_style, NodeStyle::default().into());
+
+        // A highlighted node keeps its highlight and gets the styled base
+        app.world_mut().send_event(UpdateNodeStyle { node_id: highlighted_id, new_style: new_style.clone() });
+        app.update();
+        let world = app.world();
+        assert_eq!(world.get::<MeshMaterial3d<StandardMaterial>>(highlighted).unwrap().0, variant);
+        let base = &world.get::<BaseMaterial>(highlighted).unwrap().0;
+        assert_ne!(base, &shared);
+        assert_eq!(world.resource::<Assets<StandardMaterial>>().get(base).unwrap().base_color, new_style.color);
+    }
+
+    #[test]
+    fn test_zoom_is_clamped_around_focal_point() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_event::<ZoomCanvas>()
+            .add_event::<CanvasZoomed>()
+            .add_systems(Update, handle_zoom_canvas);
+
+        let graph_id = GraphId::new();
+        let canvas = app.world_mut()
+            .spawn(crate::aggregate::GraphCanvasAggregate::new(graph_id))
+            .id();
+        let other = app.world_mut()
+            .spawn(crate::aggregate::GraphCanvasAggregate::new(GraphId::new()))
+            .id();
+        let focal_point = Vec2::new(100.0, 50.0);
+        let canvas_point = |state: &CanvasState| (focal_point - state.offset) / state.zoom;
+        let before = canvas_point(&CanvasState::default());
+
+        app.world_mut().send_event(ZoomCanvas { graph_id, zoom_factor: 1000.0, focal_point: Some(focal_point) });
+        app.update();
+        let state = app.world().get::<CanvasState>(canvas).unwrap().clone();
+        assert_eq!(state.zoom, MAX_CANVAS_ZOOM);
+        assert!(canvas_point(&state).distance(before) < 1e-4);
+
+        app.world_mut().send_event(ZoomCanvas { graph_id, zoom_factor: 1e-6, focal_point: Some(focal_point) });
+        app.update();
+        let state = app.world().get::<CanvasState>(canvas).unwrap().clone();
+        assert_eq!(state.zoom, MIN_CANVAS_ZOOM);
+        assert!(canvas_point(&state).distance(before) < 1e-3);
+
+        // Other graphs' canvases are left alone
+        assert_eq!(app.world().get::<CanvasState>(other).unwrap(), &CanvasState::default());
+
+        // Zooming out further is a no-op
+        app.world_mut().send_event(ZoomCanvas { graph_id, zoom_factor: 0.5, focal_point: None });
+        app.update();
+        assert_eq!(app.world().get::<CanvasState>(canvas).unwrap(), &state);
+
+        // Factors that are not finite and positive are ignored
+        for zoom_factor in [f32::NAN, f32::INFINITY, 0.0, -2.0] {
+            app.world_mut().send_event(ZoomCanvas { graph_id, zoom_factor, focal_point: Some(focal_point) });
+            app.update();
+            assert_eq!(app.world().get::<CanvasState>(canvas).unwrap(), &state);
+        }
+    }
+
+    #[test]
+    fn test_zoom_dollies_graph_cameras_unless_animated() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_event::<ZoomCanvas>()
+            .add_event::<PanCanvas>()
+            .add_event::<CanvasZoomed>()
+            .add_event::<CanvasPanned>()
+            .add_systems(Update, (handle_pan_canvas, handle_zoom_canvas, sync_canvas_camera).chain());
+
+        let graph_id = GraphId::new();
+        app.world_mut().spawn(crate::aggregate::GraphCanvasAggregate::new(graph_id));
+        let home = Transform::from_xyz(2.0, 1.0, 10.0).looking_to(Vec3::NEG_Z, Vec3::Y);
+        let camera = app.world_mut().spawn((Camera3d::default(), home, GraphCamera)).id();
+        let animated = app.world_mut().spawn((
+            Camera3d::default(),
+            home,
+            GraphCamera,
+            CameraAnimation {
+                from_position: home.translation,
+                from_target: Vec3::new(2.0, 1.0, 0.0),
+                to_position: home.translation,
+                to_target: Vec3::new(2.0, 1.0, 0.0),
+                elapsed: 0.0,
+                duration: 1.0,
+                easing: Easing::Linear,
+            },
+        )).id();
+
+        // Not rendered yet, so the camera dollies along its view axis
+        app.world_mut().send_event(ZoomCanvas { graph_id, zoom_factor: 2.0, focal_point: None });
+        app.update();
+        assert!(app.world().get::<Transform>(camera).unwrap().translation.distance(Vec3::new(2.0, 1.0, 5.0)) < 1e-5);
+        assert_eq!(app.world().get::<Transform>(animated).unwrap().translation, home.translation);
+
+        // Nothing moves the camera back on later frames
+        app.update();
+        assert!(app.world().get::<Transform>(camera).unwrap().translation.distance(Vec3::new(2.0, 1.0, 5.0)) < 1e-5);
+
+        // The camera only follows the active graph's canvas
+        let inactive = GraphId::new();
+        app.world_mut().spawn(crate::aggregate::GraphCanvasAggregate::new(inactive));
+        app.insert_resource(ActiveGraph { graph_id: Some(graph_id) });
+        app.world_mut().send_event(ZoomCanvas { graph_id: inactive, zoom_factor: 2.0, focal_point: None });
+        app.update();
+        assert!(app.world().get::<Transform>(camera).unwrap().translation.distance(Vec3::new(2.0, 1.0, 5.0)) < 1e-5);
+    }
+
+    #[test]
+    fn test_orthographic_zoom_keeps_focal_point_and_depth() {
+        let mut transform = Transform::from_xyz(0.0, 0.0, 10.0).looking_to(Vec3::NEG_Z, Vec3::Y);
+        let mut projection = Projection::Orthographic(OrthographicProjection::default_3d());
+        zoom_camera(&mut transform, &mut projection, Vec3::new(4.0, 2.0, 0.0), 2.0);
+
+        assert!(transform.translation.distance(Vec3::new(2.0, 1.0, 10.0)) < 1e-5);
+        let Projection::Orthographic(orthographic) = projection else {
+            panic!("projection changed kind");
+        };
+        assert_eq!(orthographic.scale, 0.5);
+    }
+}
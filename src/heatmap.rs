@@ -0,0 +1,295 @@
+//! Heatmap: Coloring nodes by a numeric metadata field
+//!
+//! With a [`ColorScale`] resource present, every node whose
+//! [`NodeMetadata`] has a number at [`ColorScale::field`] is colored by
+//! mapping that number through the scale's [`Gradient`]. Nodes without the
+//! field keep their color. An unset `min` or `max` is taken from the values
+//! currently present, so the full gradient is always in use.
+//!
+//! Materials are often shared between nodes, so a colored node is given its
+//! own copy of its material and keeps the original in [`HeatmapMaterial`].
+//! It gets the original back when it loses the field or the [`ColorScale`]
+//! is removed.
+
+use bevy::prelude::*;
+use std::collections::HashMap;
+use crate::components::NodeVisual;
+use crate::hover::{set_base_material, BaseMaterial};
+use crate::value_objects::NodeMetadata;
+
+/// Colors at increasing positions from 0 to 1, interpolated in linear RGB
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gradient {
+    stops: Vec<(f32, Color)>,
+}
+
+impl Gradient {
+    /// Gradient through `stops`, which are sorted by position
+    pub fn new(stops: impl IntoIterator<Item = (f32, Color)>) -> Self {
+        let mut stops: Vec<(f32, Color)> = stops.into_iter().collect();
+        stops.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        Self { stops }
+    }
+
+    /// Blue through green and yellow to red
+    pub fn heat() -> Self {
+        Self::new([
+            (0.0, Color::srgb(0.2, 0.3, 0.9)),
+            (0.35, Color::srgb(0.2, 0.8, 0.3)),
+            (0.7, Color::srgb(0.95, 0.85, 0.2)),
+            (1.0, Color::srgb(0.9, 0.2, 0.15)),
+        ])
+    }
+
+    /// Color at `t`, clamped to the first and last stop
+    pub fn sample(&self, t: f32) -> Color {
+        let Some(&(first_position, first_color)) = self.stops.first() else {
+            return Color::WHITE;
+        };
+        if t <= first_position || t.is_nan() {
+            return first_color;
+        }
+
+        for pair in self.stops.windows(2) {
+            let ((from, from_color), (to, to_color)) = (pair[0], pair[1]);
+            if t <= to {
+                let factor = if to > from { (t - from) / (to - from) } else { 1.0 };
+                let (from_color, to_color) = (from_color.to_linear(), to_color.to_linear());
+                return (from_color * (1.0 - factor) + to_color * factor).into();
+            }
+        }
+        self.stops[self.stops.len() - 1].1
+    }
+}
+
+impl Default for Gradient {
+    fn default() -> Self {
+        Self::heat()
+    }
+}
+
+/// Which metadata field nodes are colored by, and how
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct ColorScale {
+    /// Dot-separated path of a numeric metadata field, e.g.
+    /// `deployment.resources.cpu_cores`
+    pub field: String,
+    pub gradient: Gradient,
+    /// Value shown as the start of the gradient; the smallest value present
+    /// if unset
+    pub min: Option<f32>,
+    /// Value shown as the end of the gradient; the largest value present if
+    /// unset
+    pub max: Option<f32>,
+}
+
+impl ColorScale {
+    /// Scale over `field` with the heat gradient and automatic range
+    pub fn new(field: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            gradient: Gradient::default(),
+            min: None,
+            max: None,
+        }
+    }
+}
+
+/// The material a node had before the heatmap gave it a copy of its own
+#[derive(Component, Debug, Clone)]
+pub struct HeatmapMaterial {
+    pub original: Handle<StandardMaterial>,
+}
+
+/// Plugin that colors nodes by the field selected with [`ColorScale`]
+pub struct HeatmapPlugin;
+
+impl Plugin for HeatmapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                apply_color_scale.run_if(resource_exists::<ColorScale>),
+                restore_original_materials.run_if(not(resource_exists::<ColorScale>)),
+            ),
+        );
+    }
+}
+
+/// System that colors every node with the field through the color scale
+#[allow(clippy::type_complexity)]
+pub fn apply_color_scale(
+    mut commands: Commands,
+    scale: Res<ColorScale>,
+    mut nodes: Query<
+        (
+            Entity,
+            &NodeMetadata,
+            &mut MeshMaterial3d<StandardMaterial>,
+            Option<&mut BaseMaterial>,
+            Option<&HeatmapMaterial>,
+        ),
+        With<NodeVisual>,
+    >,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let values: HashMap<Entity, f32> = nodes.iter()
+        .filter_map(|(entity, metadata, ..)| Some((entity, metadata.number(&scale.field)?)))
+        .collect();
+    let min = scale.min.unwrap_or_else(|| values.values().copied().fold(f32::INFINITY, f32::min));
+    let max = scale.max.unwrap_or_else(|| values.values().copied().fold(f32::NEG_INFINITY, f32::max));
+
+    for (entity, _, mut current, mut highlighted, heatmap) in nodes.iter_mut() {
+        let material = highlighted.as_ref().map_or(&current.0, |base| &base.0).clone();
+        let Some(&value) = values.get(&entity) else {
+            // No longer colored by the heatmap
+            if let Some(heatmap) = heatmap {
+                set_base_material(&mut current, highlighted.as_deref_mut(), heatmap.original.clone());
+                commands.entity(entity).remove::<HeatmapMaterial>();
+            }
+            continue;
+        };
+
+        // A single value, or min == max, sits in the middle of the gradient
+        let t = if max > min { (value - min) / (max - min) } else { 0.5 };
+        let color = scale.gradient.sample(t);
+        if heatmap.is_some() {
+            // Only touched when the color moves, so the asset isn't marked
+            // as modified every frame
+            if materials.get(&material).is_some_and(|own| own.base_color != color) {
+                if let Some(own) = materials.get_mut(&material) {
+                    own.base_color = color;
+                }
+            }
+            continue;
+        }
+        let Some(original) = materials.get(&material).cloned() else {
+            continue;
+        };
+        let own = materials.add(StandardMaterial { base_color: color, ..original });
+        set_base_material(&mut current, highlighted.as_deref_mut(), own);
+        commands.entity(entity).insert(HeatmapMaterial { original: material });
+    }
+}
+
+/// System that gives nodes their original materials back once the
+/// [`ColorScale`] is removed
+pub fn restore_original_materials(
+    mut commands: Commands,
+    mut nodes: Query<(Entity, &mut MeshMaterial3d<StandardMaterial>, Option<&mut BaseMaterial>, &HeatmapMaterial)>,
+) {
+    for (entity, mut current, mut highlighted, heatmap) in nodes.iter_mut() {
+        set_base_material(&mut current, highlighted.as_deref_mut(), heatmap.original.clone());
+        commands.entity(entity).remove::<HeatmapMaterial>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gradient_sample() {
+        let gradient = Gradient::new([(1.0, Color::WHITE), (0.0, Color::BLACK)]);
+        assert_eq!(gradient.sample(-1.0), Color::BLACK);
+        assert_eq!(gradient.sample(2.0), Color::WHITE);
+        let middle = gradient.sample(0.5).to_linear();
+        assert!((middle.red - 0.5).abs() < 1e-5 && (middle.blue - 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_nodes_are_colored_over_the_value_range() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<StandardMaterial>()
+            .insert_resource(ColorScale::new("deployment.cpu_cores"))
+            .add_plugins(HeatmapPlugin);
+
+        let mut spawn_node = |metadata: serde_json::Value| {
+            let material = app.world_mut()
+                .resource_mut::<Assets<StandardMaterial>>()
+                .add(StandardMaterial::from_color(Color::WHITE));
+            let entity = app.world_mut().spawn((
+                NodeVisual {
+                    node_id: cim_contextgraph::NodeId::new(),
+                    graph_id: cim_contextgraph::ContextGraphId::new(),
+                },
+                NodeMetadata::from_json(&metadata),
+                MeshMaterial3d(material.clone()),
+            )).id();
+            (entity, material)
+        };
+        let low = spawn_node(serde_json::json!({ "deployment": { "cpu_cores": 2 } }));
+        let middle = spawn_node(serde_json::json!({ "deployment": { "cpu_cores": 5 } }));
+        let high = spawn_node(serde_json::json!({ "deployment": { "cpu_cores": 8 } }));
+        let unrelated = spawn_node(serde_json::json!({ "label": "Load balancer" }));
+        app.update();
+
+        let world = app.world();
+        let materials = world.resource::<Assets<StandardMaterial>>();
+        let color = |(entity, _): (Entity, Handle<StandardMaterial>)| {
+            let material = &world.get::<MeshMaterial3d<StandardMaterial>>(entity).unwrap().0;
+            materials.get(material).unwrap().base_color
+        };
+        let gradient = Gradient::heat();
+        assert_eq!(color(low.clone()), gradient.sample(0.0));
+        assert_eq!(color(middle.clone()), gradient.sample(0.5));
+        assert_eq!(color(high), gradient.sample(1.0));
+        assert_eq!(color(unrelated), Color::WHITE);
+        // The materials the nodes were spawned with are left alone
+        assert_eq!(materials.get(&low.1).unwrap().base_color, Color::WHITE);
+        assert_eq!(materials.get(&middle.1).unwrap().base_color, Color::WHITE);
+    }
+
+    #[test]
+    fn test_shared_material_is_copied_and_restored() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<StandardMaterial>()
+            .insert_resource(ColorScale::new("load"))
+            .add_plugins(HeatmapPlugin);
+
+        let shared = app.world_mut()
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(StandardMaterial::from_color(Color::WHITE));
+        let mut spawn_node = |load: f32| {
+            app.world_mut().spawn((
+                NodeVisual {
+                    node_id: cim_contextgraph::NodeId::new(),
+                    graph_id: cim_contextgraph::ContextGraphId::new(),
+                },
+                NodeMetadata::from_json(&serde_json::json!({ "load": load })),
+                MeshMaterial3d(shared.clone()),
+            )).id()
+        };
+        let (low, high) = (spawn_node(1.0), spawn_node(3.0));
+        // A highlighted node keeps its base material aside
+        let highlighted = app.world_mut().spawn((
+            NodeVisual {
+                node_id: cim_contextgraph::NodeId::new(),
+                graph_id: cim_contextgraph::ContextGraphId::new(),
+            },
+            NodeMetadata::from_json(&serde_json::json!({ "load": 2.0 })),
+            MeshMaterial3d(Handle::<StandardMaterial>::default()),
+            BaseMaterial(shared.clone()),
+        )).id();
+        app.update();
+
+        let material = |app: &App, entity: Entity| app.world().get::<MeshMaterial3d<StandardMaterial>>(entity).unwrap().0.clone();
+        let (low_material, high_material) = (material(&app, low), material(&app, high));
+        assert!(low_material != shared && high_material != shared && low_material != high_material);
+        let materials = app.world().resource::<Assets<StandardMaterial>>();
+        assert_eq!(materials.get(&shared).unwrap().base_color, Color::WHITE);
+        assert_eq!(materials.get(&low_material).unwrap().base_color, Gradient::heat().sample(0.0));
+        let highlighted_base = app.world().get::<BaseMaterial>(highlighted).unwrap().0.clone();
+        assert_eq!(materials.get(&highlighted_base).unwrap().base_color, Gradient::heat().sample(0.5));
+        assert_eq!(material(&app, highlighted), Handle::default());
+
+        app.world_mut().remove_resource::<ColorScale>();
+        app.update();
+        assert_eq!(material(&app, low), shared);
+        assert_eq!(material(&app, high), shared);
+        assert_eq!(app.world().get::<BaseMaterial>(highlighted).unwrap().0, shared);
+        assert!(app.world().get::<HeatmapMaterial>(low).is_none());
+    }
+}
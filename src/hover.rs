@@ -0,0 +1,364 @@
+//! Hover Highlighting: Showing which node is under the cursor
+//!
+//! [`hover`] turns the picking [`NodeHovered`] and [`NodeUnhovered`] events
+//! into the [`Hovered`] marker; `PickingPlugin` runs it right after picking,
+//! so the marker is always current. With [`HoverPlugin`] added, hovered and
+//! selected nodes are also drawn with a highlighted material: brighter and
+//! glowing while hovered, glowing in the selection color while selected, and
+//! both at once for a hovered selected node.
+//!
+//! Materials are often shared between nodes, so they are never modified.
+//! Each node is switched to a highlighted copy of its material instead, one
+//! copy per material and highlight, and back once the highlight ends. The
+//! copies of a material that is modified are made again from the new
+//! version.
+
+use bevy::prelude::*;
+use std::collections::{HashMap, HashSet};
+use crate::components::{Hovered, NodeVisual, Selected};
+use crate::events::{NodeHovered, NodeUnhovered};
+use crate::picking::{PickingPlugin, PickingSet};
+
+/// How hovered and selected nodes are highlighted
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct HoverStyle {
+    /// How much lighter a hovered node's color gets
+    pub hover_brightness: f32,
+    /// Glow added while hovered
+    pub hover_glow: LinearRgba,
+    /// Glow added while selected
+    pub selected_glow: LinearRgba,
+}
+
+impl Default for HoverStyle {
+    fn default() -> Self {
+        Self {
+            hover_brightness: 0.15,
+            hover_glow: LinearRgba::rgb(0.25, 0.25, 0.25),
+            selected_glow: LinearRgba::rgb(0.6, 0.45, 0.0),
+        }
+    }
+}
+
+/// Which highlight a node is shown with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Highlight {
+    Hovered,
+    Selected,
+    HoveredSelected,
+}
+
+impl Highlight {
+    /// Highlight for a node's interaction markers, if any
+    pub fn of(hovered: bool, selected: bool) -> Option<Self> {
+        match (hovered, selected) {
+            (true, true) => Some(Highlight::HoveredSelected),
+            (true, false) => Some(Highlight::Hovered),
+            (false, true) => Some(Highlight::Selected),
+            (false, false) => None,
+        }
+    }
+
+    /// `material` as it looks with this highlight
+    pub fn apply(self, material: &StandardMaterial, style: &HoverStyle) -> StandardMaterial {
+        let hovered = matches!(self, Highlight::Hovered | Highlight::HoveredSelected);
+        let selected = matches!(self, Highlight::Selected | Highlight::HoveredSelected);
+
+        let mut highlighted = material.clone();
+        if hovered {
+            highlighted.base_color = material.base_color.lighter(style.hover_brightness);
+            highlighted.emissive += style.hover_glow;
+        }
+        if selected {
+            highlighted.emissive += style.selected_glow;
+        }
+        highlighted
+    }
+}
+
+/// The material a highlighted node had before its highlight
+#[derive(Component, Debug, Clone)]
+pub struct BaseMaterial(pub Handle<StandardMaterial>);
+
+/// Sets the material a node is shown with when not highlighted: the one
+/// kept aside by the hover highlight while there is one
+pub fn set_base_material(
+    current: &mut MeshMaterial3d<StandardMaterial>,
+    highlighted: Option<&mut BaseMaterial>,
+    material: Handle<StandardMaterial>,
+) {
+    match highlighted {
+        Some(base) => base.0 = material,
+        None => current.0 = material,
+    }
+}
+
+/// Highlighted copies of node materials
+#[derive(Resource, Debug, Default)]
+pub struct HighlightMaterials {
+    variants: HashMap<(AssetId<StandardMaterial>, Highlight), Handle<StandardMaterial>>,
+    handed_out: HashSet<AssetId<StandardMaterial>>,
+}
+
+impl HighlightMaterials {
+    /// Highlighted copy of `base`, made on first use
+    pub fn variant(
+        &mut self,
+        base: &Handle<StandardMaterial>,
+        highlight: Highlight,
+        style: &HoverStyle,
+        materials: &mut Assets<StandardMaterial>,
+    ) -> Option<Handle<StandardMaterial>> {
+        if let Some(variant) = self.variants.get(&(base.id(), highlight)) {
+            return Some(variant.clone());
+        }
+        let variant = materials.add(highlight.apply(materials.get(base)?, style));
+        self.handed_out.insert(variant.id());
+        self.variants.insert((base.id(), highlight), variant.clone());
+        Some(variant)
+    }
+
+    /// Whether `material` is one of the highlighted copies
+    pub fn is_variant(&self, material: &Handle<StandardMaterial>) -> bool {
+        self.handed_out.contains(&material.id())
+    }
+
+    /// Drop the copies of `base`, e.g. after it was modified. Nodes showing
+    /// one keep it until they are given a new copy.
+    pub fn forget(&mut self, base: AssetId<StandardMaterial>) {
+        self.variants.retain(|(variant_base, _), _| *variant_base != base);
+    }
+
+    /// Stop tracking `material` once it is removed, after the last node
+    /// showing a forgotten copy let go of it
+    pub fn release(&mut self, material: AssetId<StandardMaterial>) {
+        self.handed_out.remove(&material);
+    }
+
+    /// Drop every copy, e.g. after changing the [`HoverStyle`]
+    pub fn clear(&mut self, materials: &mut Assets<StandardMaterial>) {
+        for (_, variant) in self.variants.drain() {
+            materials.remove(&variant);
+        }
+        self.handed_out.clear();
+    }
+}
+
+/// Plugin that draws hovered and selected nodes highlighted
+pub struct HoverPlugin;
+
+impl Plugin for HoverPlugin {
+    fn build(&self, app: &mut App) {
+        // Picking keeps the Hovered markers up to date
+        if !app.is_plugin_added::<PickingPlugin>() {
+            app.add_plugins(PickingPlugin);
+        }
+
+        app.init_resource::<HoverStyle>()
+            .init_resource::<HighlightMaterials>()
+            .add_systems(Update, highlight_nodes.after(PickingSet::Selection));
+    }
+}
+
+/// System that adds [`Hovered`] to hovered nodes and removes it from
+/// unhovered ones
+pub fn hover(
+    mut commands: Commands,
+    mut hovered: EventReader<NodeHovered>,
+    mut unhovered: EventReader<NodeUnhovered>,
+) {
+    // Unhovers first, so a node hovered again in the same frame keeps it
+    for event in unhovered.read() {
+        if let Ok(mut entity) = commands.get_entity(event.entity) {
+            entity.remove::<Hovered>();
+        }
+    }
+    for event in hovered.read() {
+        if let Ok(mut entity) = commands.get_entity(event.entity) {
+            entity.insert(Hovered);
+        }
+    }
+}
+
+/// System that switches nodes between their material and its highlighted
+/// copies as they are hovered and selected
+#[allow(clippy::type_complexity)]
+pub fn highlight_nodes(
+    mut commands: Commands,
+    mut highlights: ResMut<HighlightMaterials>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    style: Res<HoverStyle>,
+    mut material_events: EventReader<AssetEvent<StandardMaterial>>,
+    nodes: Query<
+        (Entity, &MeshMaterial3d<StandardMaterial>, Option<&BaseMaterial>, Has<Hovered>, Has<Selected>),
+        With<NodeVisual>,
+    >,
+) {
+    if style.is_changed() && !style.is_added() {
+        highlights.clear(&mut materials);
+    }
+    for event in material_events.read() {
+        match event {
+            AssetEvent::Modified { id } => highlights.forget(*id),
+            AssetEvent::Removed { id } => highlights.release(*id),
+            _ => {}
+        }
+    }
+
+    for (entity, current, base, hovered, selected) in nodes.iter() {
+        let highlight = Highlight::of(hovered, selected);
+        if highlight.is_none() && base.is_none() {
+            continue;
+        }
+
+        // A material swapped in by something else becomes the new base
+        let base = match base {
+            Some(base) if highlights.is_variant(&current.0) || base.0 == current.0 => base.0.clone(),
+            _ => current.0.clone(),
+        };
+
+        let Some(highlight) = highlight else {
+            commands.entity(entity)
+                .insert(MeshMaterial3d(base))
+                .remove::<BaseMaterial>();
+            continue;
+        };
+        let Some(variant) = highlights.variant(&base, highlight, &style, &mut materials) else {
+            continue;
+        };
+        if current.0 != variant {
+            commands.entity(entity).insert((MeshMaterial3d(variant), BaseMaterial(base)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cim_contextgraph::{NodeId, ContextGraphId as GraphId};
+
+    #[test]
+    fn test_hover_events_toggle_the_marker() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_event::<NodeHovered>()
+            .add_event::<NodeUnhovered>()
+            .add_systems(Update, hover);
+
+        let node_id = NodeId::new();
+        let entity = app.world_mut().spawn(NodeVisual { node_id, graph_id: GraphId::new() }).id();
+        app.world_mut().send_event(NodeHovered { entity, node_id });
+        app.update();
+        assert!(app.world().get::<Hovered>(entity).is_some());
+
+        app.world_mut().send_event(NodeUnhovered { entity, node_id });
+        app.update();
+        assert!(app.world().get::<Hovered>(entity).is_none());
+    }
+
+    #[test]
+    fn test_highlight_composes_and_restores() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<StandardMaterial>()
+            .init_resource::<HoverStyle>()
+            .init_resource::<HighlightMaterials>()
+            .add_systems(Update, highlight_nodes);
+
+        let shared = app.world_mut().resource_mut::<Assets<StandardMaterial>>().add(StandardMaterial::default());
+        let graph_id = GraphId::new();
+        let mut spawn_node = |app: &mut App| {
+            app.world_mut().spawn((
+                NodeVisual { node_id: NodeId::new(), graph_id },
+                MeshMaterial3d(shared.clone()),
+            )).id()
+        };
+        let node = spawn_node(&mut app);
+        let other = spawn_node(&mut app);
+
+        let material = |app: &App, entity| app.world().get::<MeshMaterial3d<StandardMaterial>>(entity).unwrap().0.clone();
+        let emissive = |app: &App, entity| {
+            let handle = material(app, entity);
+            app.world().resource::<Assets<StandardMaterial>>().get(&handle).unwrap().emissive
+        };
+
+        app.world_mut().entity_mut(node).insert(Hovered);
+        app.update();
+        let hovered = emissive(&app, node);
+        assert_ne!(material(&app, node), shared);
+        assert_eq!(material(&app, other), shared);
+
+        app.world_mut().entity_mut(node).insert(Selected);
+        app.update();
+        let both = emissive(&app, node);
+        assert!(both.red > hovered.red && both.green > hovered.green);
+
+        app.world_mut().entity_mut(node).remove::<(Hovered, Selected)>();
+        app.update();
+        assert_eq!(material(&app, node), shared);
+        assert!(app.world().get::<BaseMaterial>(node).is_none());
+        assert_eq!(emissive(&app, node), StandardMaterial::default().emissive);
+    }
+
+    #[test]
+    fn test_modified_base_gets_new_highlight() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<StandardMaterial>()
+            .init_resource::<HoverStyle>()
+            .init_resource::<HighlightMaterials>()
+            .add_systems(Update, highlight_nodes);
+
+        let base = app.world_mut().resource_mut::<Assets<StandardMaterial>>().add(StandardMaterial::default());
+        let node = app.world_mut().spawn((
+            NodeVisual { node_id: NodeId::new(), graph_id: GraphId::new() },
+            MeshMaterial3d(base.clone()),
+            Hovered,
+        )).id();
+        let base_color = |app: &App| {
+            let handle = &app.world().get::<MeshMaterial3d<StandardMaterial>>(node).unwrap().0;
+            app.world().resource::<Assets<StandardMaterial>>().get(handle).unwrap().base_color
+        };
+        app.update();
+        let white = base_color(&app);
+
+        let red = Color::srgb(1.0, 0.0, 0.0);
+        app.world_mut().resource_mut::<Assets<StandardMaterial>>().get_mut(&base).unwrap().base_color = red;
+        // Asset events are sent at the end of the frame they happen in
+        app.update();
+        app.update();
+        assert_ne!(base_color(&app), white);
+        assert_eq!(base_color(&app), red.lighter(HoverStyle::default().hover_brightness));
+        assert_eq!(app.world().get::<BaseMaterial>(node).unwrap().0, base);
+    }
+
+    #[test]
+    fn test_replaced_copies_are_released() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<StandardMaterial>()
+            .init_resource::<HoverStyle>()
+            .init_resource::<HighlightMaterials>()
+            .add_systems(Update, highlight_nodes);
+
+        let base = app.world_mut().resource_mut::<Assets<StandardMaterial>>().add(StandardMaterial::default());
+        let node = app.world_mut().spawn((
+            NodeVisual { node_id: NodeId::new(), graph_id: GraphId::new() },
+            MeshMaterial3d(base.clone()),
+            Hovered,
+        )).id();
+        app.update();
+        assert_eq!(app.world().resource::<HighlightMaterials>().handed_out.len(), 1);
+
+        // Each change to the base makes a new copy; the old one is let go
+        // once the node moved on to the new one
+        for shade in [0.2, 0.4, 0.6] {
+            app.world_mut().resource_mut::<Assets<StandardMaterial>>().get_mut(&base).unwrap().base_color = Color::srgb(shade, shade, shade);
+            for _ in 0..3 {
+                app.update();
+            }
+        }
+        assert_eq!(app.world().resource::<HighlightMaterials>().handed_out.len(), 1);
+        assert!(app.world().get::<BaseMaterial>(node).is_some());
+    }
+}
@@ -0,0 +1,258 @@
+//! Graph Import: Loading whole graphs into the visualization
+//!
+//! [`load_context_graph`] walks a `cim_contextgraph` graph and
+//! [`load_graph_document`] a plain JSON [`GraphDocument`], for callers
+//! without the domain crate. Both send the usual `CreateNodeVisual` and
+//! `CreateEdgeVisual` commands followed by a `SetLayoutAlgorithm` for the
+//! chosen layout, so imported entities are indistinguishable from ones
+//! created by the domain.
+//!
+//! Nodes without a position start on a circle in the XY plane, like every
+//! other layout, which gives the layout algorithms distinct points to work
+//! from.
+
+use bevy::prelude::*;
+use cim_contextgraph::{ContextGraph, NodeId, EdgeId, ContextGraphId as GraphId};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::events::{CreateEdgeVisual, CreateNodeVisual, EdgeRelationship};
+use crate::layout::SetLayoutAlgorithm;
+use crate::visualization::LayoutType;
+
+/// Radius of the circle unpositioned nodes start on
+const INITIAL_RADIUS: f32 = 10.0;
+
+/// Errors that can occur while importing a graph document
+#[derive(Debug, thiserror::Error)]
+pub enum ImportError {
+    #[error("invalid graph document: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("edge {edge} refers to unknown node {node}")]
+    UnknownNode { edge: String, node: String },
+}
+
+/// A node of a [`GraphDocument`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DocumentNode {
+    /// Document-local id; a UUID is used as the node id as-is
+    pub id: String,
+    #[serde(default)]
+    pub label: String,
+    #[serde(default)]
+    pub position: Option<[f32; 3]>,
+    /// Every other field, kept as node metadata
+    #[serde(flatten)]
+    pub metadata: serde_json::Map<String, serde_json::Value>,
+}
+
+/// An edge of a [`GraphDocument`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DocumentEdge {
+    #[serde(default)]
+    pub id: Option<String>,
+    pub source_id: String,
+    pub target_id: String,
+    /// Relationship name, as in `EdgeRelationship`'s `Display`
+    #[serde(default, alias = "relationship")]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub weight: Option<f32>,
+}
+
+/// A graph described in JSON, independent of the domain crates
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GraphDocument {
+    #[serde(default)]
+    pub graph_id: Option<GraphId>,
+    #[serde(default)]
+    pub nodes: Vec<DocumentNode>,
+    #[serde(default)]
+    pub edges: Vec<DocumentEdge>,
+}
+
+impl GraphDocument {
+    pub fn from_json(json: &str) -> Result<Self, ImportError> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+/// A document id that is itself a UUID, read as a domain id
+fn parse_id<T: serde::de::DeserializeOwned>(id: &str) -> Option<T> {
+    serde_json::from_value(serde_json::Value::String(id.to_string())).ok()
+}
+
+/// Starting position of the `index`th of `count` nodes
+fn initial_position(index: usize, count: usize) -> Vec3 {
+    let angle = index as f32 / count.max(1) as f32 * std::f32::consts::TAU;
+    Vec3::new(angle.cos(), angle.sin(), 0.0) * INITIAL_RADIUS
+}
+
+/// Send creation commands for every node and edge of `graph`, then request
+/// `layout` for it. Node values become the node metadata and edge values
+/// name the relationship.
+pub fn load_context_graph<N, E>(graph: &ContextGraph<N, E>, commands: &mut Commands, layout: LayoutType) -> GraphId
+where
+    N: Serialize,
+    E: std::fmt::Display,
+{
+    let count = graph.nodes.len();
+    for (index, node) in graph.nodes.values().enumerate() {
+        let metadata = serde_json::to_value(&node.value).unwrap_or_default();
+        let label = metadata.get("label")
+            .and_then(serde_json::Value::as_str)
+            .map_or_else(|| node.id.to_string(), str::to_string);
+        commands.send_event(CreateNodeVisual {
+            node_id: node.id,
+            graph_id: graph.id,
+            position: initial_position(index, count),
+            label,
+            metadata,
+        });
+    }
+
+    for edge in graph.edges.values() {
+        commands.send_event(CreateEdgeVisual {
+            edge_id: edge.id,
+            graph_id: graph.id,
+            source_node_id: edge.source,
+            target_node_id: edge.target,
+            relationship: EdgeRelationship::from(edge.value.to_string().as_str()),
+            weight: None,
+        });
+    }
+
+    commands.send_event(SetLayoutAlgorithm { graph_id: graph.id, layout_type: layout });
+    graph.id
+}
+
+/// Send creation commands for every node and edge of `document`, then
+/// request `layout` for it. Returns the graph id, which is new unless the
+/// document names one.
+pub fn load_graph_document(
+    document: &GraphDocument,
+    commands: &mut Commands,
+    layout: LayoutType,
+) -> Result<GraphId, ImportError> {
+    let graph_id = document.graph_id.unwrap_or_default();
+    let node_ids: HashMap<&str, NodeId> = document.nodes.iter()
+        .map(|node| {
+            let node_id = parse_id(&node.id).unwrap_or_else(NodeId::new);
+            (node.id.as_str(), node_id)
+        })
+        .collect();
+
+    // Resolve every edge first, so a bad document sends nothing
+    let edges = document.edges.iter()
+        .map(|edge| {
+            let resolve = |node: &str| {
+                node_ids.get(node).copied().ok_or_else(|| ImportError::UnknownNode {
+                    edge: edge.id.clone().unwrap_or_default(),
+                    node: node.to_string(),
+                })
+            };
+            Ok((edge, resolve(&edge.source_id)?, resolve(&edge.target_id)?))
+        })
+        .collect::<Result<Vec<_>, ImportError>>()?;
+
+    let count = document.nodes.len();
+    for (index, node) in document.nodes.iter().enumerate() {
+        let mut metadata = node.metadata.clone();
+        metadata.insert("label".to_string(), node.label.clone().into());
+        commands.send_event(CreateNodeVisual {
+            node_id: node_ids[node.id.as_str()],
+            graph_id,
+            position: node.position.map_or_else(|| initial_position(index, count), Vec3::from_array),
+            label: node.label.clone(),
+            metadata: serde_json::Value::Object(metadata),
+        });
+    }
+
+    for (edge, source_node_id, target_node_id) in edges {
+        let edge_id = edge.id.as_deref().and_then(parse_id).unwrap_or_else(EdgeId::new);
+        commands.send_event(CreateEdgeVisual {
+            edge_id,
+            graph_id,
+            source_node_id,
+            target_node_id,
+            relationship: edge.label.as_deref().map_or(EdgeRelationship::References, EdgeRelationship::from),
+            weight: edge.weight,
+        });
+    }
+
+    commands.send_event(SetLayoutAlgorithm { graph_id, layout_type: layout });
+    Ok(graph_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::*;
+    use crate::morphisms::{create_edge_visual, create_node_visual, NodeEntityMap};
+    use crate::projections::{update_graph_projection, GraphViewProjection};
+
+    const DOCUMENT: &str = r#"{
+        "nodes": [
+            { "id": "a", "label": "Orders", "position": [0.0, 0.0, 0.0], "color": [0.8, 0.2, 0.2, 1.0] },
+            { "id": "b", "label": "Billing" },
+            { "id": "c", "label": "Shipping", "tags": ["logistics"] }
+        ],
+        "edges": [
+            { "id": "e1", "source_id": "a", "target_id": "b", "label": "DependsOn" },
+            { "source_id": "b", "target_id": "c" }
+        ]
+    }"#;
+
+    #[test]
+    fn test_document_import_builds_the_graph() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<GraphViewProjection>()
+            .init_resource::<NodeEntityMap>()
+            .add_event::<CreateNodeVisual>()
+            .add_event::<CreateEdgeVisual>()
+            .add_event::<SetLayoutAlgorithm>()
+            .add_event::<VisualNodeCreated>()
+            .add_event::<VisualEdgeCreated>()
+            .add_event::<NodeMetadataChanged>()
+            .add_event::<NodeMoved>()
+            .add_event::<NodeSelected>()
+            .add_event::<NodeDeselected>()
+            .add_event::<VisualNodeDeleted>()
+            .add_event::<VisualEdgeDeleted>()
+            .add_systems(Update, (create_node_visual, create_edge_visual, update_graph_projection).chain());
+
+        let document = GraphDocument::from_json(DOCUMENT).unwrap();
+        let mut commands = app.world_mut().commands();
+        let graph_id = load_graph_document(&document, &mut commands, LayoutType::Circular).unwrap();
+        app.world_mut().flush();
+        app.update();
+
+        let projection = app.world().resource::<GraphViewProjection>();
+        assert_eq!(projection.nodes.len(), 3);
+        assert_eq!(projection.edges.len(), 2);
+        let labels: Vec<&str> = projection.nodes.values().map(|node| node.metadata.label.as_str()).collect();
+        assert!(labels.contains(&"Billing"));
+        assert!(projection.nodes.values().any(|node| node.metadata.tags == ["logistics"]));
+        let mut transforms = app.world_mut().query_filtered::<&Transform, With<crate::components::NodeVisual>>();
+        assert!(transforms.iter(app.world()).all(|transform| transform.translation.z == 0.0));
+        let on_circle = transforms.iter(app.world())
+            .filter(|transform| (transform.translation.length() - INITIAL_RADIUS).abs() < 1e-4)
+            .count();
+        assert_eq!(on_circle, 2);
+
+        let layout: Vec<_> = app.world().resource::<Events<SetLayoutAlgorithm>>().iter_current_update_events().collect();
+        assert_eq!(layout.len(), 1);
+        assert_eq!((layout[0].graph_id, layout[0].layout_type), (graph_id, LayoutType::Circular));
+    }
+
+    #[test]
+    fn test_edge_to_unknown_node_is_rejected() {
+        let document = GraphDocument::from_json(
+            r#"{ "nodes": [{ "id": "a" }], "edges": [{ "id": "e", "source_id": "a", "target_id": "z" }] }"#,
+        ).unwrap();
+        let mut world = World::new();
+        let mut commands = world.commands();
+        let error = load_graph_document(&document, &mut commands, LayoutType::Grid).unwrap_err();
+        assert!(matches!(error, ImportError::UnknownNode { node, .. } if node == "z"));
+    }
+}
@@ -0,0 +1,269 @@
+//! Inspector: Details of the selected nodes and events
+//!
+//! [`InspectorPlugin`] docks a panel on the right that shows everything known
+//! about whatever is [`Selected`]: the metadata and position of domain
+//! nodes, and the full received event for event visuals, with its payload
+//! as an expandable JSON tree. An event's correlation and causation IDs are
+//! links: the correlation highlights its events, the causation focuses and
+//! selects the causing event.
+//!
+//! The selection is read into [`InspectorState`] in `Update`, so the panel
+//! only draws and other UIs can show the same details.
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiPlugin, EguiPrimaryContextPass};
+use cim_contextgraph::{NodeId, ContextGraphId as GraphId};
+use crate::components::{NodeVisual, Selected};
+use crate::nats_event_visualization::{DomainEventReceived, EventStore, EventVisual, EventVisualizationCommand};
+use crate::value_objects::NodeMetadata;
+
+/// One selected item, as shown by the inspector
+#[derive(Debug, Clone, PartialEq)]
+pub enum Inspected {
+    Node {
+        node_id: NodeId,
+        graph_id: GraphId,
+        position: Vec3,
+        metadata: Option<NodeMetadata>,
+    },
+    Event(DomainEventReceived),
+}
+
+/// What the inspector currently shows
+#[derive(Resource, Debug, Clone, Default)]
+pub struct InspectorState {
+    /// Selected events first, then selected nodes
+    pub inspected: Vec<Inspected>,
+}
+
+/// Plugin that shows the selection in a panel on the right
+pub struct InspectorPlugin;
+
+impl Plugin for InspectorPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugins(EguiPlugin::default());
+        }
+
+        app.init_resource::<InspectorState>()
+            .add_event::<EventVisualizationCommand>()
+            .add_systems(Update, update_inspector)
+            .add_systems(EguiPrimaryContextPass, inspector_ui);
+    }
+}
+
+/// System that reads the selected nodes and events into the
+/// [`InspectorState`]. Events are looked up in the [`EventStore`], when
+/// there is one, for their payload.
+pub fn update_inspector(
+    mut state: ResMut<InspectorState>,
+    event_store: Option<Res<EventStore>>,
+    events: Query<&EventVisual, With<Selected>>,
+    nodes: Query<(&NodeVisual, &GlobalTransform, Option<&NodeMetadata>), With<Selected>>,
+) {
+    let selected_events = events.iter()
+        .filter_map(|event| event_store.as_ref()?.get(&event.event_id))
+        .map(Inspected::Event);
+    let selected_nodes = nodes.iter().map(|(node, transform, metadata)| Inspected::Node {
+        node_id: node.node_id,
+        graph_id: node.graph_id,
+        position: transform.translation(),
+        metadata: metadata.cloned(),
+    });
+    let inspected: Vec<Inspected> = selected_events.chain(selected_nodes).collect();
+    if state.inspected != inspected {
+        state.inspected = inspected;
+    }
+}
+
+/// System that draws the inspector panel while something is selected
+pub fn inspector_ui(
+    mut contexts: EguiContexts,
+    state: Res<InspectorState>,
+    mut commands: EventWriter<EventVisualizationCommand>,
+) {
+    if state.inspected.is_empty() {
+        return;
+    }
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    egui::SidePanel::right("cim_inspector")
+        .resizable(true)
+        .default_width(320.0)
+        .show(ctx, |ui| {
+            ui.heading("Inspector");
+            if state.inspected.len() > 1 {
+                ui.label(format!("{} selected", state.inspected.len()));
+            }
+            ui.separator();
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                let open = state.inspected.len() == 1;
+                for (index, inspected) in state.inspected.iter().enumerate() {
+                    let title = match inspected {
+                        Inspected::Node { node_id, metadata, .. } => metadata.as_ref()
+                            .filter(|metadata| !metadata.label.is_empty())
+                            .map_or_else(|| format!("Node {node_id}"), |metadata| metadata.label.clone()),
+                        Inspected::Event(event) => format!("{} / {}", event.domain, event.event_type),
+                    };
+                    egui::CollapsingHeader::new(title)
+                        .id_salt(("cim_inspected", index))
+                        .default_open(open)
+                        .show(ui, |ui| match inspected {
+                            Inspected::Node { node_id, graph_id, position, metadata } => {
+                                node_details(ui, *node_id, *graph_id, *position, metadata.as_ref());
+                            }
+                            Inspected::Event(event) => event_details(ui, event, &mut commands),
+                        });
+                }
+            });
+        });
+}
+
+fn node_details(ui: &mut egui::Ui, node_id: NodeId, graph_id: GraphId, position: Vec3, metadata: Option<&NodeMetadata>) {
+    egui::Grid::new(("cim_node_details", node_id)).num_columns(2).show(ui, |ui| {
+        ui.label("Node");
+        ui.label(node_id.to_string());
+        ui.end_row();
+        ui.label("Graph");
+        ui.label(graph_id.to_string());
+        ui.end_row();
+        ui.label("Position");
+        ui.label(format!("({:.2}, {:.2}, {:.2})", position.x, position.y, position.z));
+        ui.end_row();
+    });
+
+    let Some(metadata) = metadata else {
+        return;
+    };
+    let value = serde_json::to_value(metadata).unwrap_or_default();
+    json_tree(ui, "metadata", &value, egui::Id::new(("cim_node_metadata", node_id)));
+}
+
+fn event_details(ui: &mut egui::Ui, event: &DomainEventReceived, commands: &mut EventWriter<EventVisualizationCommand>) {
+    egui::Grid::new(("cim_event_details", &event.event_id)).num_columns(2).show(ui, |ui| {
+        ui.label("Event");
+        ui.label(&event.event_id);
+        ui.end_row();
+        ui.label("Time");
+        ui.label(event.timestamp.format("%Y-%m-%d %H:%M:%S%.3f").to_string());
+        ui.end_row();
+        ui.label("Aggregate");
+        ui.label(format!("{} {}", event.aggregate_type, event.aggregate_id));
+        ui.end_row();
+
+        ui.label("Correlation");
+        match &event.correlation_id {
+            Some(correlation_id) => {
+                if ui.link(correlation_id).on_hover_text("Highlight this correlation").clicked() {
+                    commands.write(EventVisualizationCommand::ShowCorrelation(correlation_id.clone()));
+                }
+            }
+            None => {
+                ui.weak("none");
+            }
+        }
+        ui.end_row();
+
+        ui.label("Caused by");
+        match &event.causation_id {
+            Some(causation_id) => {
+                if ui.link(causation_id).on_hover_text("Focus the causing event").clicked() {
+                    commands.write(EventVisualizationCommand::FocusEvent(causation_id.clone()));
+                }
+            }
+            None => {
+                ui.weak("none");
+            }
+        }
+        ui.end_row();
+    });
+
+    json_tree(ui, "payload", &event.payload, egui::Id::new(("cim_event_payload", &event.event_id)));
+}
+
+/// Draw `value` as a tree, objects and arrays as collapsible branches
+fn json_tree(ui: &mut egui::Ui, key: &str, value: &serde_json::Value, id: egui::Id) {
+    let children: Vec<(String, &serde_json::Value)> = match value {
+        serde_json::Value::Object(map) => map.iter().map(|(key, value)| (key.clone(), value)).collect(),
+        serde_json::Value::Array(items) => items.iter().enumerate().map(|(index, value)| (index.to_string(), value)).collect(),
+        leaf => {
+            ui.horizontal_wrapped(|ui| {
+                ui.strong(key);
+                ui.monospace(leaf.to_string());
+            });
+            return;
+        }
+    };
+
+    let summary = if value.is_object() {
+        format!("{key} {{{}}}", children.len())
+    } else {
+        format!("{key} [{}]", children.len())
+    };
+    egui::CollapsingHeader::new(summary)
+        .id_salt(id)
+        .default_open(children.len() <= 8)
+        .show(ui, |ui| {
+            for (child_key, child) in children {
+                json_tree(ui, &child_key, child, id.with(&child_key));
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_inspector_follows_the_selection() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<InspectorState>()
+            .insert_resource(EventStore::new())
+            .add_systems(Update, update_inspector);
+
+        let event = DomainEventReceived {
+            event_id: "e-1".to_string(),
+            timestamp: Utc::now(),
+            domain: "workflow".to_string(),
+            event_type: "StepCompleted".to_string(),
+            aggregate_id: "wf-1".to_string(),
+            aggregate_type: "Workflow".to_string(),
+            correlation_id: Some("corr-1".to_string()),
+            causation_id: None,
+            payload: serde_json::json!({ "step": { "name": "review", "attempt": 2 } }),
+        };
+        app.world().resource::<EventStore>().add_event(event.clone());
+        let event_entity = app.world_mut().spawn(EventVisual::from_event(&event)).id();
+
+        let metadata = NodeMetadata { label: "Orders".to_string(), ..default() };
+        let node_id = NodeId::new();
+        let node = app.world_mut().spawn((
+            NodeVisual { node_id, graph_id: GraphId::new() },
+            GlobalTransform::from_translation(Vec3::new(1.0, 2.0, 3.0)),
+            metadata.clone(),
+        )).id();
+        app.update();
+        assert!(app.world().resource::<InspectorState>().inspected.is_empty());
+
+        app.world_mut().entity_mut(event_entity).insert(Selected);
+        app.world_mut().entity_mut(node).insert(Selected);
+        app.update();
+        let inspected = &app.world().resource::<InspectorState>().inspected;
+        assert_eq!(inspected.len(), 2);
+        assert_eq!(inspected[0], Inspected::Event(event));
+        assert!(matches!(
+            &inspected[1],
+            Inspected::Node { node_id: id, position, metadata: Some(m), .. }
+                if *id == node_id && *position == Vec3::new(1.0, 2.0, 3.0) && *m == metadata
+        ));
+
+        app.world_mut().entity_mut(event_entity).remove::<Selected>();
+        app.update();
+        assert_eq!(app.world().resource::<InspectorState>().inspected.len(), 1);
+    }
+}
@@ -0,0 +1,378 @@
+//! Instanced Rendering: Batching nodes that share a shape
+//!
+//! Nodes carrying an [`InstancedShape`] are rendered by this module. In
+//! [`RenderMode::Individual`] each node gets its own mesh entity, sharing
+//! mesh and material handles. In [`RenderMode::Instanced`] all nodes of the
+//! same graph and shape are merged into one [`NodeBatch`] mesh with
+//! per-instance transforms baked in and colors stored as vertex colors, so
+//! the whole batch is a single draw call.
+//!
+//! Nodes that need their own draw for interaction feedback (hovered,
+//! selected or marked [`UniquePicking`]) always fall back to an individual
+//! mesh.
+//!
+//! A batch is only rebuilt when one of its nodes changed, joined or left,
+//! and its [`Aabb`] is refreshed with it so it is culled by its real extent.
+
+use bevy::asset::RenderAssetUsages;
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, MeshBuilder, Meshable, PrimitiveTopology, VertexAttributeValues};
+use bevy::render::primitives::Aabb;
+use cim_contextgraph::ContextGraphId as GraphId;
+use std::collections::{HashMap, HashSet};
+use crate::components::{Hovered, NodeShape, NodeVisual, Selected};
+
+/// How nodes with an [`InstancedShape`] are drawn
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RenderMode {
+    /// One mesh entity (and draw) per node
+    #[default]
+    Individual,
+    /// One merged mesh per graph and shape
+    Instanced,
+}
+
+/// Appearance of a node rendered by this module
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct InstancedShape {
+    pub shape: NodeShape,
+    pub color: Color,
+    pub size: f32,
+}
+
+/// Keeps a node out of batches so it always has its own mesh entity
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct UniquePicking;
+
+/// A merged mesh drawing every batched node of one graph and shape
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeBatch {
+    pub graph_id: GraphId,
+    pub shape: NodeShape,
+}
+
+/// Shared handles used by instanced rendering
+#[derive(Resource, Debug, Default)]
+pub struct InstancingAssets {
+    shapes: HashMap<NodeShape, Handle<Mesh>>,
+    materials: HashMap<[u32; 4], Handle<StandardMaterial>>,
+    batch_material: Option<Handle<StandardMaterial>>,
+    batches: HashMap<NodeBatch, (Entity, Handle<Mesh>)>,
+}
+
+impl InstancingAssets {
+    fn shape_mesh(&mut self, meshes: &mut Assets<Mesh>, shape: NodeShape) -> Handle<Mesh> {
+        self.shapes
+            .entry(shape)
+            .or_insert_with(|| meshes.add(unit_shape_mesh(shape)))
+            .clone()
+    }
+
+    fn color_material(&mut self, materials: &mut Assets<StandardMaterial>, color: Color) -> Handle<StandardMaterial> {
+        let key = color.to_linear().to_f32_array().map(f32::to_bits);
+        self.materials
+            .entry(key)
+            .or_insert_with(|| materials.add(color))
+            .clone()
+    }
+
+    /// White material, so the batch's vertex colors show through unchanged
+    fn batch_material(&mut self, materials: &mut Assets<StandardMaterial>) -> Handle<StandardMaterial> {
+        self.batch_material
+            .get_or_insert_with(|| materials.add(Color::WHITE))
+            .clone()
+    }
+
+    /// Number of batch entities currently alive
+    pub fn batch_count(&self) -> usize {
+        self.batches.len()
+    }
+}
+
+/// Plugin that renders [`InstancedShape`] nodes according to [`RenderMode`]
+pub struct InstancingPlugin;
+
+impl Plugin for InstancingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RenderMode>()
+            .init_resource::<InstancingAssets>()
+            .add_systems(PostUpdate, update_node_batches.after(TransformSystem::TransformPropagate));
+    }
+}
+
+/// Unit-sized mesh for a node shape
+pub fn unit_shape_mesh(shape: NodeShape) -> Mesh {
+    match shape {
+        NodeShape::Circle => Sphere::new(0.5).mesh().uv(16, 8),
+        NodeShape::Square => Cuboid::from_length(1.0).mesh().build(),
+        NodeShape::Diamond => Sphere::new(0.5).mesh().uv(4, 2),
+        NodeShape::Triangle => Cone::new(0.5, 1.0).mesh().resolution(3).build(),
+        NodeShape::Hexagon => Cylinder::new(0.5, 0.5).mesh().resolution(6).build(),
+    }
+}
+
+/// Merge copies of `base`, one per `(transform, color)` instance, into a
+/// single mesh with vertex colors
+pub fn merge_instances(base: &Mesh, instances: &[(Transform, Color)]) -> Mesh {
+    let positions = match base.attribute(Mesh::ATTRIBUTE_POSITION) {
+        Some(VertexAttributeValues::Float32x3(positions)) => positions.as_slice(),
+        _ => &[],
+    };
+    let normals = match base.attribute(Mesh::ATTRIBUTE_NORMAL) {
+        Some(VertexAttributeValues::Float32x3(normals)) => normals.as_slice(),
+        _ => &[],
+    };
+    let base_indices: Vec<u32> = match base.indices() {
+        Some(indices) => indices.iter().map(|index| index as u32).collect(),
+        None => (0..positions.len() as u32).collect(),
+    };
+
+    let vertex_count = positions.len() * instances.len();
+    let mut merged_positions = Vec::with_capacity(vertex_count);
+    let mut merged_normals = Vec::with_capacity(vertex_count);
+    let mut merged_colors = Vec::with_capacity(vertex_count);
+    let mut merged_indices = Vec::with_capacity(base_indices.len() * instances.len());
+
+    for (transform, color) in instances {
+        let offset = merged_positions.len() as u32;
+        let matrix = transform.compute_matrix();
+        let color = color.to_linear().to_f32_array();
+
+        merged_positions.extend(positions.iter().map(|p| matrix.transform_point3(Vec3::from(*p)).to_array()));
+        merged_normals.extend(normals.iter().map(|n| {
+            (transform.rotation * Vec3::from(*n)).to_array()
+        }));
+        merged_colors.extend(std::iter::repeat_n(color, positions.len()));
+        merged_indices.extend(base_indices.iter().map(|index| index + offset));
+    }
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default());
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, merged_positions);
+    if !normals.is_empty() {
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, merged_normals);
+    }
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, merged_colors);
+    mesh.insert_indices(Indices::U32(merged_indices));
+    mesh
+}
+
+/// System that assigns individual meshes or rebuilds batch meshes for every
+/// [`InstancedShape`] node
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn update_node_batches(
+    mut commands: Commands,
+    mode: Res<RenderMode>,
+    mut assets: ResMut<InstancingAssets>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    nodes: Query<(
+        Entity,
+        &NodeVisual,
+        Ref<InstancedShape>,
+        Ref<GlobalTransform>,
+        Has<Mesh3d>,
+        Has<Hovered>,
+        Has<Selected>,
+        Has<UniquePicking>,
+    )>,
+    mut removed_nodes: RemovedComponents<InstancedShape>,
+) {
+    let mut batches: HashMap<NodeBatch, Vec<(Transform, Color)>> = HashMap::new();
+    let mut dirty: HashSet<NodeBatch> = HashSet::new();
+    let any_removed = removed_nodes.read().count() > 0;
+
+    for (entity, node, shape, transform, has_mesh, hovered, selected, unique) in nodes.iter() {
+        let key = NodeBatch { graph_id: node.graph_id, shape: shape.shape };
+        let batched = *mode == RenderMode::Instanced && !(hovered || selected || unique);
+
+        if batched {
+            let (_, rotation, translation) = transform.to_scale_rotation_translation();
+            batches.entry(key).or_default().push((
+                Transform {
+                    translation,
+                    rotation,
+                    scale: Vec3::splat(shape.size),
+                },
+                shape.color,
+            ));
+            if has_mesh {
+                commands.entity(entity).remove::<(Mesh3d, MeshMaterial3d<StandardMaterial>)>();
+                dirty.insert(key);
+            }
+            if shape.is_changed() || transform.is_changed() {
+                dirty.insert(key);
+            }
+        } else {
+            if !has_mesh || shape.is_changed() {
+                let mesh = assets.shape_mesh(&mut meshes, shape.shape);
+                let material = assets.color_material(&mut materials, shape.color);
+                commands.entity(entity).insert((Mesh3d(mesh), MeshMaterial3d(material)));
+            }
+            // A node without a mesh was batched until now
+            if !has_mesh && assets.batches.contains_key(&key) {
+                dirty.insert(key);
+            }
+        }
+    }
+
+    // Drop batches that no longer have members
+    let stale: Vec<NodeBatch> = assets.batches.keys()
+        .filter(|key| !batches.contains_key(key))
+        .copied()
+        .collect();
+    for key in stale {
+        if let Some((entity, handle)) = assets.batches.remove(&key) {
+            commands.entity(entity).despawn();
+            meshes.remove(&handle);
+        }
+    }
+
+    for (key, instances) in batches {
+        if !(dirty.contains(&key) || any_removed || !assets.batches.contains_key(&key)) {
+            continue;
+        }
+        let base = assets.shape_mesh(&mut meshes, key.shape);
+        let Some(merged) = meshes.get(&base).map(|base| merge_instances(base, &instances)) else {
+            continue;
+        };
+        // Bounds are only computed for new meshes, so they are kept in line
+        // with the batch here
+        let aabb = merged.compute_aabb().unwrap_or_default();
+
+        match assets.batches.get(&key) {
+            Some((entity, handle)) => {
+                if let Some(mesh) = meshes.get_mut(handle) {
+                    *mesh = merged;
+                }
+                commands.entity(*entity).insert(aabb);
+            }
+            None => {
+                let handle = meshes.add(merged);
+                let material = assets.batch_material(&mut materials);
+                let entity = commands.spawn((
+                    Mesh3d(handle.clone()),
+                    MeshMaterial3d(material),
+                    Transform::default(),
+                    aabb,
+                    key,
+                )).id();
+                assets.batches.insert(key, (entity, handle));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_instances_bakes_transforms_and_colors() {
+        let base = unit_shape_mesh(NodeShape::Square);
+        let base_vertices = base.count_vertices();
+        let base_indices = base.indices().unwrap().len();
+        let instances = [
+            (Transform::from_xyz(10.0, 0.0, 0.0), Color::srgb(1.0, 0.0, 0.0)),
+            (Transform::from_xyz(-10.0, 0.0, 0.0).with_scale(Vec3::splat(2.0)), Color::WHITE),
+        ];
+
+        let merged = merge_instances(&base, &instances);
+        assert_eq!(merged.count_vertices(), base_vertices * 2);
+        assert_eq!(merged.indices().unwrap().len(), base_indices * 2);
+
+        let Some(VertexAttributeValues::Float32x3(positions)) = merged.attribute(Mesh::ATTRIBUTE_POSITION) else {
+            panic!("merged mesh has positions");
+        };
+        assert!(positions[..base_vertices].iter().all(|p| (p[0] - 10.0).abs() <= 0.5));
+        assert!(positions[base_vertices..].iter().all(|p| (p[0] + 10.0).abs() <= 1.0));
+
+        // Second copy's indices point at the second copy's vertices
+        let max_index = merged.indices().unwrap().iter().max().unwrap();
+        assert_eq!(max_index, base_vertices * 2 - 1);
+        assert!(merged.attribute(Mesh::ATTRIBUTE_COLOR).is_some());
+    }
+
+    #[test]
+    fn test_instanced_mode_batches_by_graph_and_shape() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<Assets<Mesh>>()
+            .init_resource::<Assets<StandardMaterial>>()
+            .insert_resource(RenderMode::Instanced)
+            .init_resource::<InstancingAssets>()
+            .add_systems(Update, update_node_batches);
+
+        let graph_id = GraphId::new();
+        let shape = InstancedShape {
+            shape: NodeShape::Circle,
+            color: Color::srgb(0.2, 0.6, 0.9),
+            size: 1.0,
+        };
+        let nodes: Vec<Entity> = (0..5)
+            .map(|i| {
+                app.world_mut().spawn((
+                    NodeVisual { node_id: cim_contextgraph::NodeId::new(), graph_id },
+                    shape,
+                    GlobalTransform::from_xyz(i as f32 * 2.0, 0.0, 0.0),
+                )).id()
+            })
+            .collect();
+        app.world_mut().entity_mut(nodes[0]).insert(Selected);
+        app.update();
+
+        let mut batches = app.world_mut().query::<&NodeBatch>();
+        assert_eq!(batches.iter(app.world()).count(), 1);
+        assert_eq!(app.world().resource::<InstancingAssets>().batch_count(), 1);
+        // The selected node keeps its own mesh for interaction feedback
+        assert!(app.world().get::<Mesh3d>(nodes[0]).is_some());
+        assert!(nodes[1..].iter().all(|node| app.world().get::<Mesh3d>(*node).is_none()));
+
+        // Back to individual rendering: batches go away, every node has a mesh
+        app.insert_resource(RenderMode::Individual);
+        app.update();
+        assert_eq!(batches.iter(app.world()).count(), 0);
+        assert!(nodes.iter().all(|node| app.world().get::<Mesh3d>(*node).is_some()));
+    }
+
+    #[test]
+    fn test_batch_is_rebuilt_only_on_change() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<Assets<Mesh>>()
+            .init_resource::<Assets<StandardMaterial>>()
+            .insert_resource(RenderMode::Instanced)
+            .init_resource::<InstancingAssets>()
+            .add_systems(Update, update_node_batches);
+
+        let graph_id = GraphId::new();
+        let shape = InstancedShape { shape: NodeShape::Square, color: Color::WHITE, size: 1.0 };
+        let nodes: Vec<Entity> = (0..3)
+            .map(|i| {
+                app.world_mut().spawn((
+                    NodeVisual { node_id: cim_contextgraph::NodeId::new(), graph_id },
+                    shape,
+                    GlobalTransform::from_xyz(i as f32 * 4.0, 0.0, 0.0),
+                )).id()
+            })
+            .collect();
+        // A node drawn on its own does not keep its batch rebuilding
+        app.world_mut().entity_mut(nodes[0]).insert(Selected);
+        app.update();
+        app.update();
+
+        let batch = app.world_mut().query_filtered::<Entity, With<NodeBatch>>().single(app.world()).unwrap();
+        let aabb = |app: &App| *app.world().get::<Aabb>(batch).unwrap();
+        let rebuilt = |app: &App| app.world().entity(batch).get_ref::<Aabb>().unwrap().last_changed();
+        assert_eq!(aabb(&app).min().x, 3.5);
+        assert_eq!(aabb(&app).max().x, 8.5);
+        let built = rebuilt(&app);
+        app.update();
+        assert_eq!(rebuilt(&app), built);
+
+        // Moving a batched node rebuilds the batch and its bounds
+        *app.world_mut().get_mut::<GlobalTransform>(nodes[2]).unwrap() = GlobalTransform::from_xyz(20.0, 0.0, 0.0);
+        app.update();
+        assert_ne!(rebuilt(&app), built);
+        assert_eq!(aabb(&app).max().x, 20.5);
+    }
+}
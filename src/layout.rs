@@ -1,13 +1,62 @@
 //! Graph layout algorithms for visualization
 //!
 //! This module implements various layout algorithms to position nodes in the graph visualization.
+//!
+//! Layouts only run for graphs in [`GraphLayoutState::dirty`]. A graph is
+//! marked dirty when its nodes or edges are added or removed, a node of it is
+//! dropped or moved, its layout algorithm changes, or an entity of it is
+//! given [`NeedsLayout`]; a fixed layout is applied once and a force-directed
+//! one runs until it settles, so an idle graph costs nothing.
+//!
+//! Layouts keep nodes inside the [`WorldBounds`]: force-directed nodes are
+//! gently pulled towards the center of the bounds and never stepped outside
+//! them, and fixed layouts too large for them are scaled down to fit, so
+//! they keep their shape. [`recenter_graph`] brings a drifted graph back to the
+//! origin on a key press.
+//!
+//! A graph whose entity has a [`GraphRegion`] is laid out in that region
+//! instead, whether it is the active graph or not: fixed layouts are
+//! centered on the region's origin, force-directed nodes are pulled towards
+//! it, and no node leaves the region. Graphs in separate regions can
+//! therefore be shown side by side without their nodes mixing.
+//!
+//! The clustered layout groups nodes by a [`NodeMetadata`] key, as set in
+//! [`ClusterConfig`](crate::resources::ClusterConfig), see
+//! [`clustered_positions`].
 
 use bevy::prelude::*;
-use crate::components::{NodeVisual, EdgeVisual};
+use crate::components::{AnchoredPosition, AnimatedTransition, GraphRegion, GraphVisual, ManualPosition, NeedsLayout, NodeVisual, EdgeVisual, Selected};
+use crate::culling::{CollapsedMember, Culled, FreezeCulledLayout};
+use crate::environment::EnvironmentConfig;
+use crate::events::{EdgeRelationship, FocusCamera, NodeDragEnd, NodeDragging, NodePositionChanged};
 use crate::resources::{GraphLayoutConfig, ActiveGraph};
+use crate::value_objects::{NodeMetadata, RenderSettings, SceneRenderSettings};
 use crate::visualization::{LayoutType, VisualizationHints};
 use cim_contextgraph::ContextGraphId as GraphId;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::f32::consts::{PI, TAU};
+use std::hash::Hash;
+
+/// A force-directed layout whose largest step in a frame is below this has
+/// settled
+const SETTLED_STEP: f32 = 1e-4;
+
+/// Pull of the [`WorldBounds`] center on force-directed nodes, per unit of
+/// distance; weak next to the edge springs, so it only reins in drifters
+const CENTERING_STRENGTH: f32 = 0.02;
+
+/// Seconds the camera takes to frame a recentered graph
+const RECENTER_FOCUS_DURATION: f32 = 0.5;
+
+/// Steps of the force-directed pass laying out each cluster
+const CLUSTER_LAYOUT_ITERATIONS: usize = 100;
+
+/// Edges as the layouts see them, with their relationship
+pub type LayoutEdges<'w, 's> = Query<'w, 's, (&'static EdgeVisual, Option<&'static EdgeRelationship>)>;
+
+/// Nodes as the layouts see them; nodes folded into a meta-node are left
+/// where they are
+pub type LayoutNodes<'w, 's, D> = Query<'w, 's, D, (With<NodeVisual>, Without<CollapsedMember>)>;
 
 /// Resource to track the current layout algorithm for each graph
 #[derive(Resource, Default)]
@@ -16,65 +65,292 @@ pub struct GraphLayoutState {
     pub layout_algorithms: HashMap<GraphId, LayoutType>,
     /// Visualization hints for each graph
     pub visualization_hints: HashMap<GraphId, VisualizationHints>,
+    /// Graphs whose nodes are gliding to a new layout, with the layout and
+    /// the nodes still on their way
+    pub transitions: HashMap<GraphId, (LayoutType, Vec<Entity>)>,
+    /// Graphs whose layout needs to run again
+    pub dirty: HashSet<GraphId>,
+}
+
+/// Box the layouts keep nodes inside
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct WorldBounds {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Default for WorldBounds {
+    fn default() -> Self {
+        Self {
+            min: Vec3::splat(-500.0),
+            max: Vec3::splat(500.0),
+        }
+    }
+}
+
+impl WorldBounds {
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    /// The point inside the bounds closest to `position`
+    pub fn clamp(&self, position: Vec3) -> Vec3 {
+        position.max(self.min).min(self.max)
+    }
+
+    pub fn contains(&self, position: Vec3) -> bool {
+        position.cmpge(self.min).all() && position.cmple(self.max).all()
+    }
+
+    /// Fixed layout `positions`, relative to `origin`, placed around it and
+    /// scaled down uniformly as far as needed to fit inside the bounds. An
+    /// `origin` outside the bounds still leaves nodes clamped to them.
+    pub fn fit<T>(&self, origin: Vec3, positions: Vec<(T, Vec3)>) -> Vec<(T, Vec3)> {
+        let room_above = (self.max - origin).max(Vec3::ZERO);
+        let room_below = (origin - self.min).max(Vec3::ZERO);
+        let scale = positions.iter()
+            .flat_map(|(_, position)| position.to_array().into_iter().zip(room_above.to_array().into_iter().zip(room_below.to_array())))
+            .map(|(offset, (above, below))| {
+                if offset > above {
+                    above / offset
+                } else if -offset > below {
+                    below / -offset
+                } else {
+                    1.0
+                }
+            })
+            .fold(1.0, f32::min);
+        positions.into_iter()
+            .map(|(item, position)| (item, self.clamp(origin + position * scale)))
+            .collect()
+    }
+}
+
+impl From<&GraphRegion> for WorldBounds {
+    fn from(region: &GraphRegion) -> Self {
+        Self { min: region.min(), max: region.max() }
+    }
+}
+
+/// Where `graph_id` is laid out: the point its fixed layouts are centered
+/// on and the bounds its nodes are kept inside. Those of its
+/// [`GraphRegion`] if it has one, or the origin and `bounds`.
+pub fn layout_frame(graph_id: &GraphId, regions: &Query<&GraphRegion>, bounds: &WorldBounds) -> (Vec3, WorldBounds) {
+    regions.iter()
+        .find(|region| &region.graph_id == graph_id)
+        .map_or((Vec3::ZERO, *bounds), |region| (region.origin, region.into()))
+}
+
+/// Event: A graph's nodes finished gliding to the layout set by a
+/// [`SetLayoutAlgorithm`]
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct LayoutCompleted {
+    pub graph_id: GraphId,
+    pub layout_type: LayoutType,
+}
+
+/// System that marks graphs dirty whose structure changed since it last
+/// ran, and takes [`NeedsLayout`] off the entities asking for a layout.
+/// Changes that can't be traced to a graph, such as removals, mark the
+/// active graph.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn mark_layout_dirty(
+    mut commands: Commands,
+    mut layout_state: ResMut<GraphLayoutState>,
+    active_graph: Res<ActiveGraph>,
+    layout_config: Res<GraphLayoutConfig>,
+    freeze_culled: Res<FreezeCulledLayout>,
+    changed_nodes: Query<&NodeVisual, Or<(Added<NodeVisual>, Changed<AnchoredPosition>)>>,
+    changed_regions: Query<&GraphRegion, Changed<GraphRegion>>,
+    added_edges: Query<&EdgeVisual, Added<EdgeVisual>>,
+    requested: Query<(Entity, Option<&NodeVisual>, Option<&EdgeVisual>, Option<&GraphVisual>), With<NeedsLayout>>,
+    mut removed_nodes: RemovedComponents<NodeVisual>,
+    mut removed_edges: RemovedComponents<EdgeVisual>,
+    mut removed_anchors: RemovedComponents<AnchoredPosition>,
+    mut removed_culled: RemovedComponents<Culled>,
+    mut removed_collapsed: RemovedComponents<CollapsedMember>,
+) {
+    let mut dirty: Vec<GraphId> = changed_nodes.iter().map(|node| node.graph_id)
+        .chain(added_edges.iter().map(|edge| edge.graph_id))
+        .chain(changed_regions.iter().map(|region| region.graph_id))
+        .collect();
+    for (entity, node, edge, graph) in requested.iter() {
+        let graph_id = node.map(|node| node.graph_id)
+            .or(edge.map(|edge| edge.graph_id))
+            .or(graph.map(|graph| graph.graph_id))
+            .or(active_graph.graph_id);
+        dirty.extend(graph_id);
+        commands.entity(entity).remove::<NeedsLayout>();
+    }
+
+    let removed = removed_nodes.read().count()
+        + removed_edges.read().count()
+        + removed_anchors.read().count()
+        + removed_culled.read().count()
+        + removed_collapsed.read().count();
+    let settings_changed = active_graph.is_changed() || layout_config.is_changed() || freeze_culled.is_changed();
+    if removed > 0 || settings_changed {
+        dirty.extend(active_graph.graph_id);
+    }
+
+    if !dirty.is_empty() {
+        layout_state.dirty.extend(dirty);
+    }
 }
 
-/// System to apply layout algorithms based on visualization hints
+/// System to apply layout algorithms based on visualization hints, for the
+/// active graph and every graph with a [`GraphRegion`] while they are dirty.
+/// Fixed layouts are held off while the graph's nodes glide to them, then
+/// applied once; force-directed layouts run until they settle.
+#[allow(clippy::too_many_arguments)]
 pub fn apply_layout_algorithm(
-    mut nodes: Query<(Entity, &NodeVisual, &mut Transform)>,
-    edges: Query<&EdgeVisual>,
+    mut nodes: LayoutNodes<(Entity, &NodeVisual, &mut Transform)>,
+    edges: LayoutEdges,
+    metadata: Query<&NodeMetadata>,
     layout_config: Res<GraphLayoutConfig>,
     active_graph: Res<ActiveGraph>,
-    layout_state: Res<GraphLayoutState>,
+    mut layout_state: ResMut<GraphLayoutState>,
+    freeze_culled: Res<FreezeCulledLayout>,
+    culled: Query<(), With<Culled>>,
+    anchors: Query<&AnchoredPosition>,
+    regions: Query<&GraphRegion>,
+    bounds: Res<WorldBounds>,
     time: Res<Time>,
 ) {
-    if let Some(graph_id) = &active_graph.graph_id {
-        // Get the layout algorithm for this graph
-        let layout_type = layout_state
-            .layout_algorithms
-            .get(graph_id)
-            .copied()
-            .unwrap_or(LayoutType::ForceDirected);
-        
-        match layout_type {
-            LayoutType::ForceDirected => apply_force_directed_layout(
-                &mut nodes,
-                &edges,
-                &layout_config,
-                graph_id,
-                &time,
-            ),
-            LayoutType::Hierarchical => apply_hierarchical_layout(
-                &mut nodes,
-                &edges,
-                &layout_config,
-                graph_id,
-            ),
-            LayoutType::Circular => apply_circular_layout(
-                &mut nodes,
-                &layout_config,
-                graph_id,
-            ),
-            LayoutType::Grid => apply_grid_layout(
-                &mut nodes,
-                &layout_config,
-                graph_id,
-            ),
-            LayoutType::Random => apply_random_layout(
-                &mut nodes,
+    let mut graphs: Vec<GraphId> = Vec::new();
+    for graph_id in active_graph.graph_id.into_iter().chain(regions.iter().map(|region| region.graph_id)) {
+        if layout_state.dirty.contains(&graph_id) && !graphs.contains(&graph_id) {
+            graphs.push(graph_id);
+        }
+    }
+
+    for graph_id in &graphs {
+        let (origin, bounds) = layout_frame(graph_id, &regions, &bounds);
+        let done = apply_graph_layout(
+            graph_id,
+            origin,
+            &bounds,
+            &mut nodes,
+            &edges,
+            &metadata,
+            &layout_config,
+            &layout_state,
+            freeze_culled.0.then_some(&culled),
+            &anchors,
+            &time,
+        );
+        if done {
+            layout_state.dirty.remove(graph_id);
+        }
+    }
+}
+
+/// One step of `graph_id`'s layout, centered on `origin` and inside
+/// `bounds`. Returns whether the layout is done.
+#[allow(clippy::too_many_arguments)]
+fn apply_graph_layout(
+    graph_id: &GraphId,
+    origin: Vec3,
+    bounds: &WorldBounds,
+    nodes: &mut LayoutNodes<(Entity, &NodeVisual, &mut Transform)>,
+    edges: &LayoutEdges,
+    metadata: &Query<&NodeMetadata>,
+    layout_config: &GraphLayoutConfig,
+    layout_state: &GraphLayoutState,
+    frozen: Option<&Query<(), With<Culled>>>,
+    anchors: &Query<&AnchoredPosition>,
+    time: &Time,
+) -> bool {
+    // Get the layout algorithm for this graph
+    let layout_type = layout_state
+        .layout_algorithms
+        .get(graph_id)
+        .copied()
+        .unwrap_or(LayoutType::ForceDirected);
+
+    match layout_type {
+        LayoutType::ForceDirected => {
+            let largest_step = apply_force_directed_layout(
+                nodes,
+                edges,
+                layout_config,
                 graph_id,
-            ),
+                frozen,
+                anchors,
+                bounds,
+                time,
+            );
+            // A frame without time passing says nothing about convergence
+            time.delta_secs() > 0.0 && largest_step < SETTLED_STEP
+        }
+        LayoutType::Manual => true,
+        _ if layout_state.transitions.contains_key(graph_id) => false,
+        _ => {
+            let graph_nodes: Vec<Entity> = nodes.iter()
+                .filter(|(_, node_visual, _)| &node_visual.graph_id == graph_id)
+                .map(|(entity, ..)| entity)
+                .collect();
+            let positions = layout_positions(layout_type, &graph_nodes, edges, metadata, layout_config);
+            for (entity, position) in bounds.fit(origin, positions) {
+                if let Ok((_, _, mut transform)) = nodes.get_mut(entity) {
+                    transform.translation = position;
+                }
+            }
+            true
+        }
+    }
+}
+
+/// Positions a fixed layout puts `nodes` at. Force-directed and manual
+/// layouts have none; they move nodes from where they are.
+///
+/// Nodes are placed in entity order, so the same nodes always get the same
+/// places however they were queried. Their `metadata` is only read by the
+/// clustered layout.
+pub fn layout_positions(
+    layout_type: LayoutType,
+    nodes: &[Entity],
+    edges: &LayoutEdges,
+    metadata: &Query<&NodeMetadata>,
+    config: &GraphLayoutConfig,
+) -> Vec<(Entity, Vec3)> {
+    let mut nodes = nodes.to_vec();
+    nodes.sort();
+    let nodes = &nodes[..];
+    match layout_type {
+        LayoutType::Hierarchical => hierarchical_positions(nodes, edges, config),
+        LayoutType::Circular => nodes.iter().copied().zip(circular_positions(nodes.len(), config)).collect(),
+        LayoutType::Grid => nodes.iter().copied().zip(grid_positions(nodes.len(), config)).collect(),
+        LayoutType::Random => nodes.iter().copied().zip(random_positions(nodes.len(), config)).collect(),
+        LayoutType::Clustered => {
+            let clusters: HashMap<Entity, String> = nodes.iter()
+                .filter_map(|entity| Some((*entity, cluster_key(metadata.get(*entity).ok()?, &config.clusters.key)?)))
+                .collect();
+            let edges: Vec<(Entity, Entity)> = edges.iter()
+                .map(|(edge_visual, _)| (edge_visual.source_entity, edge_visual.target_entity))
+                .collect();
+            clustered_positions(nodes, &edges, &clusters, config)
         }
+        LayoutType::ForceDirected | LayoutType::Manual => Vec::new(),
     }
 }
 
-/// Apply force-directed layout algorithm
+/// Apply force-directed layout algorithm. Nodes matched by `frozen` neither
+/// exert nor receive forces; anchored nodes exert them but stay at their
+/// anchor. Each step moves a node at most `config.max_layout_step`, so
+/// nearly coincident nodes can't fling each other to infinity, and never
+/// out of `bounds`. Returns the largest step any node took.
+#[allow(clippy::too_many_arguments)]
 fn apply_force_directed_layout(
-    nodes: &mut Query<(Entity, &NodeVisual, &mut Transform)>,
-    edges: &Query<&EdgeVisual>,
+    nodes: &mut LayoutNodes<(Entity, &NodeVisual, &mut Transform)>,
+    edges: &LayoutEdges,
     config: &GraphLayoutConfig,
     graph_id: &GraphId,
+    frozen: Option<&Query<(), With<Culled>>>,
+    anchors: &Query<&AnchoredPosition>,
+    bounds: &WorldBounds,
     time: &Time,
-) {
+) -> f32 {
     // Collect all nodes for the current graph with their entities
     let mut node_entities: Vec<Entity> = Vec::new();
     let mut node_positions: HashMap<Entity, Vec3> = HashMap::new();
@@ -82,6 +358,9 @@ fn apply_force_directed_layout(
     
     // First pass: collect node data
     for (entity, node_visual, transform) in nodes.iter() {
+        if frozen.is_some_and(|frozen| frozen.contains(entity)) {
+            continue;
+        }
         if &node_visual.graph_id == graph_id {
             node_entities.push(entity);
             node_positions.insert(entity, transform.translation);
@@ -101,186 +380,301 @@ fn apply_force_directed_layout(
             let diff = pos_a - pos_b;
             let distance = diff.length().max(0.1);
             let force_magnitude = config.force_directed_strength / (distance * distance);
-            let force = diff.normalize() * force_magnitude;
+            // Coincident nodes have no direction between them; push them apart along X
+            let force = diff.try_normalize().unwrap_or(Vec3::X) * force_magnitude;
             
             node_forces.entry(entity_a).and_modify(|f| *f += force);
             node_forces.entry(entity_b).and_modify(|f| *f -= force);
         }
     }
     
-    // Apply attractive forces along edges
-    for edge_visual in edges.iter() {
+    // Apply attractive forces along edges, each pulling towards the rest
+    // length of its relationship, harder the heavier the edge
+    for (edge_visual, relationship) in edges.iter() {
         if let (Some(pos_a), Some(pos_b)) = (
             node_positions.get(&edge_visual.source_entity),
             node_positions.get(&edge_visual.target_entity),
         ) {
             let diff = *pos_b - *pos_a;
             let distance = diff.length().max(0.1);
-            let force_magnitude = config.force_directed_distance * (distance - 100.0);
-            let force = diff.normalize() * force_magnitude;
+            let force_magnitude = config.force_directed_distance
+                * edge_visual.effective_weight()
+                * (distance - config.edge_length_for(relationship));
+            let force = diff.normalize_or_zero() * force_magnitude;
             
             node_forces.entry(edge_visual.source_entity).and_modify(|f| *f += force);
             node_forces.entry(edge_visual.target_entity).and_modify(|f| *f -= force);
         }
     }
     
+    // Pull every node gently towards the center of the bounds
+    let center = bounds.center();
+    for (entity, force) in node_forces.iter_mut() {
+        *force += (center - node_positions[entity]) * CENTERING_STRENGTH;
+    }
+    
     // Apply forces to update positions
     let delta_time = time.delta_secs();
+    let mut largest_step: f32 = 0.0;
     for (entity, node_visual, mut transform) in nodes.iter_mut() {
         if &node_visual.graph_id == graph_id {
             if let Some(force) = node_forces.get(&entity) {
-                transform.translation += *force * delta_time * 0.1;
+                if let Ok(AnchoredPosition(anchor)) = anchors.get(entity) {
+                    if transform.translation != *anchor {
+                        transform.translation = *anchor;
+                    }
+                    continue;
+                }
+                let step = (*force * delta_time * 0.1).clamp_length_max(config.max_layout_step);
+                let position = bounds.clamp(transform.translation + step);
+                // A node held at the bounds has settled as far as it can
+                largest_step = largest_step.max(position.distance(transform.translation));
+                transform.translation = position;
+                debug_assert!(transform.translation.is_finite(), "layout produced a non-finite position");
             }
         }
     }
+    largest_step
 }
 
-/// Apply hierarchical layout algorithm
-fn apply_hierarchical_layout(
-    nodes: &mut Query<(Entity, &NodeVisual, &mut Transform)>,
-    edges: &Query<&EdgeVisual>,
-    config: &GraphLayoutConfig,
-    graph_id: &GraphId,
-) {
-    // Simple hierarchical layout - arrange nodes in layers
-    let mut layers: HashMap<Entity, usize> = HashMap::new();
-    let mut nodes_by_layer: HashMap<usize, Vec<Entity>> = HashMap::new();
-    
-    // Collect nodes for this graph
-    let mut graph_nodes: Vec<Entity> = Vec::new();
-    for (entity, node_visual, _) in nodes.iter() {
-        if &node_visual.graph_id == graph_id {
-            graph_nodes.push(entity);
-            layers.insert(entity, 0);
-        }
-    }
-    
-    // Simple layer assignment (could be improved with proper topological sort)
-    let mut changed = true;
-    while changed {
-        changed = false;
-        for edge_visual in edges.iter() {
-            if let (Some(&source_layer), Some(target_layer)) = (
-                layers.get(&edge_visual.source_entity),
-                layers.get_mut(&edge_visual.target_entity),
-            ) {
-                if *target_layer <= source_layer {
-                    *target_layer = source_layer + 1;
-                    changed = true;
-                }
+/// System that snaps nodes with a non-finite [`Transform`] back to the origin,
+/// so one exploded node can't poison the rest of the layout
+pub fn reset_exploded_nodes(mut nodes: Query<(&NodeVisual, &mut Transform)>) {
+    for (node_visual, mut transform) in nodes.iter_mut() {
+        if !transform.is_finite() {
+            warn!("Resetting node {:?} with non-finite transform {:?}", node_visual.node_id, *transform);
+            transform.translation = Vec3::ZERO;
+            if !transform.rotation.is_finite() {
+                transform.rotation = Quat::IDENTITY;
+            }
+            if !transform.scale.is_finite() {
+                transform.scale = Vec3::ONE;
             }
         }
     }
+}
+
+/// Hierarchical layout: nodes in layers by their depth along the edges
+fn hierarchical_positions(
+    nodes: &[Entity],
+    edges: &LayoutEdges,
+    config: &GraphLayoutConfig,
+) -> Vec<(Entity, Vec3)> {
+    // Simple hierarchical layout - arrange nodes in layers
+    let edges: Vec<(Entity, Entity)> = edges.iter()
+        .map(|(edge_visual, _)| (edge_visual.source_entity, edge_visual.target_entity))
+        .collect();
+    let layers = assign_layers(nodes, &edges);
+    let mut nodes_by_layer: HashMap<usize, Vec<Entity>> = HashMap::new();
     
-    // Group nodes by layer
-    for (entity, &layer) in layers.iter() {
-        nodes_by_layer.entry(layer).or_insert_with(Vec::new).push(*entity);
+    // Group nodes by layer, in node order
+    for entity in nodes {
+        nodes_by_layer.entry(layers[entity]).or_default().push(*entity);
     }
     
     // Position nodes by layer
+    let mut positions = Vec::with_capacity(nodes.len());
     for (layer, entities) in nodes_by_layer.iter() {
         let count = entities.len() as f32;
         for (i, entity) in entities.iter().enumerate() {
-            if let Ok((_, _, mut transform)) = nodes.get_mut(*entity) {
-                let x = (i as f32 - count / 2.0) * config.grid_spacing;
-                let y = *layer as f32 * config.hierarchical_layer_spacing;
-                transform.translation = Vec3::new(x, y, 0.0);
-            }
+            let x = (i as f32 - count / 2.0) * config.grid_spacing;
+            let y = *layer as f32 * config.hierarchical_layer_spacing;
+            positions.push((*entity, Vec3::new(x, y, 0.0)));
         }
     }
+    positions
 }
 
-/// Apply circular layout algorithm
-fn apply_circular_layout(
-    nodes: &mut Query<(Entity, &NodeVisual, &mut Transform)>,
-    config: &GraphLayoutConfig,
-    graph_id: &GraphId,
-) {
-    let mut node_count = 0;
-    let mut node_entities: Vec<Entity> = Vec::new();
-    
-    // Count nodes for this graph
-    for (entity, node_visual, _) in nodes.iter() {
-        if &node_visual.graph_id == graph_id {
-            node_entities.push(entity);
-            node_count += 1;
+/// Layer of each of `nodes` in a layered layout: sources are in layer 0 and
+/// every edge's target is in a layer below its source. Edges to or from
+/// other nodes and loops are ignored.
+///
+/// Nodes are placed in topological order. When only cycles are left, the
+/// first waiting node is placed anyway and the edges still leading into it
+/// are dropped as back-edges, so cyclic input is laid out too.
+pub fn assign_layers<T: Copy + Eq + Hash>(nodes: &[T], edges: &[(T, T)]) -> HashMap<T, usize> {
+    let mut layers: HashMap<T, usize> = nodes.iter().map(|node| (*node, 0)).collect();
+    let mut outgoing: HashMap<T, Vec<T>> = HashMap::new();
+    let mut incoming: HashMap<T, usize> = HashMap::new();
+    for (source, target) in edges {
+        if source != target && layers.contains_key(source) && layers.contains_key(target) {
+            outgoing.entry(*source).or_default().push(*target);
+            *incoming.entry(*target).or_default() += 1;
         }
     }
-    
-    if node_count == 0 {
-        return;
-    }
-    
-    // Position nodes in a circle
-    let angle_step = std::f32::consts::TAU / node_count as f32;
-    
-    for (index, entity) in node_entities.iter().enumerate() {
-        if let Ok((_, _, mut transform)) = nodes.get_mut(*entity) {
-            let angle = index as f32 * angle_step;
-            let x = angle.cos() * config.circular_radius;
-            let y = angle.sin() * config.circular_radius;
-            transform.translation = Vec3::new(x, y, 0.0);
+
+    let mut placed: HashSet<T> = HashSet::new();
+    let mut ready: VecDeque<T> = nodes.iter().filter(|node| !incoming.contains_key(*node)).copied().collect();
+    let mut waiting = nodes.iter();
+    while placed.len() < layers.len() {
+        let Some(node) = ready.pop_front().or_else(|| waiting.find(|node| !placed.contains(*node)).copied()) else {
+            break;
+        };
+        if !placed.insert(node) {
+            continue;
         }
-    }
-}
 
-/// Apply grid layout algorithm
-fn apply_grid_layout(
-    nodes: &mut Query<(Entity, &NodeVisual, &mut Transform)>,
-    config: &GraphLayoutConfig,
-    graph_id: &GraphId,
-) {
-    let mut node_count = 0;
-    let mut node_entities: Vec<Entity> = Vec::new();
-    
-    // Count nodes for this graph
-    for (entity, node_visual, _) in nodes.iter() {
-        if &node_visual.graph_id == graph_id {
-            node_entities.push(entity);
-            node_count += 1;
+        let layer = layers[&node];
+        for target in outgoing.get(&node).into_iter().flatten() {
+            // Back-edges into nodes placed already are dropped
+            if placed.contains(target) {
+                continue;
+            }
+            if let Some(target_layer) = layers.get_mut(target) {
+                *target_layer = (*target_layer).max(layer + 1);
+            }
+            if let Some(remaining) = incoming.get_mut(target) {
+                *remaining -= 1;
+                if *remaining == 0 {
+                    ready.push_back(*target);
+                }
+            }
         }
     }
-    
-    if node_count == 0 {
-        return;
-    }
-    
+    layers
+}
+
+/// Circular layout: `count` nodes evenly around a circle
+fn circular_positions(count: usize, config: &GraphLayoutConfig) -> Vec<Vec3> {
+    let angle_step = std::f32::consts::TAU / count.max(1) as f32;
+    (0..count)
+        .map(|index| {
+            let angle = index as f32 * angle_step;
+            Vec3::new(angle.cos(), angle.sin(), 0.0) * config.circular_radius
+        })
+        .collect()
+}
+
+/// Grid layout: `count` nodes in rows of a square grid
+fn grid_positions(count: usize, config: &GraphLayoutConfig) -> Vec<Vec3> {
     // Calculate grid dimensions
-    let grid_size = (node_count as f32).sqrt().ceil() as usize;
-    
-    // Position nodes in a grid
-    for (index, entity) in node_entities.iter().enumerate() {
-        if let Ok((_, _, mut transform)) = nodes.get_mut(*entity) {
+    let grid_size = (count as f32).sqrt().ceil().max(1.0) as usize;
+    (0..count)
+        .map(|index| {
             let row = index / grid_size;
             let col = index % grid_size;
             
             let x = (col as f32 - grid_size as f32 / 2.0) * config.grid_spacing;
             let y = (row as f32 - grid_size as f32 / 2.0) * config.grid_spacing;
-            
-            transform.translation = Vec3::new(x, y, 0.0);
+            Vec3::new(x, y, 0.0)
+        })
+        .collect()
+}
+
+/// The cluster `metadata` puts its node in for the [`ClusterConfig::key`](crate::resources::ClusterConfig::key)
+/// `key`: the attribute's value, strings as they are and other values as
+/// JSON, or for `"tags"` the first tag
+pub fn cluster_key(metadata: &NodeMetadata, key: &str) -> Option<String> {
+    if key == "tags" {
+        return metadata.tags.first().cloned();
+    }
+    metadata.attributes.get(key).map(|value| match value {
+        serde_json::Value::String(text) => text.clone(),
+        other => other.to_string(),
+    })
+}
+
+/// Clustered layout: `nodes` grouped by their entry in `clusters`, the
+/// centers of the groups around a circle and each group laid out by a
+/// force-directed pass of its own within [`ClusterConfig::cluster_radius`](crate::resources::ClusterConfig::cluster_radius)
+/// of its center. Centers are kept two and a half cluster diameters apart,
+/// and only edges inside a cluster pull its nodes together, so edges
+/// between clusters are longer than edges within one.
+pub fn clustered_positions(
+    nodes: &[Entity],
+    edges: &[(Entity, Entity)],
+    clusters: &HashMap<Entity, String>,
+    config: &GraphLayoutConfig,
+) -> Vec<(Entity, Vec3)> {
+    let radius = config.clusters.cluster_radius.max(0.0);
+    // Nodes without a cluster form one of their own, sorted first
+    let mut groups: BTreeMap<Option<&str>, Vec<Entity>> = BTreeMap::new();
+    for entity in nodes {
+        groups.entry(clusters.get(entity).map(String::as_str)).or_default().push(*entity);
+    }
+
+    let count = groups.len();
+    let ring = if count > 1 {
+        config.circular_radius.max(2.5 * radius / (PI / count as f32).sin())
+    } else {
+        0.0
+    };
+    groups.values()
+        .enumerate()
+        .flat_map(|(index, members)| {
+            let angle = index as f32 * TAU / count as f32;
+            let center = Vec3::new(angle.cos(), angle.sin(), 0.0) * ring;
+            let local = cluster_layout(members, edges, radius);
+            members.iter().zip(local).map(move |(entity, position)| (*entity, center + position))
+        })
+        .collect()
+}
+
+/// Positions of `members` around their cluster's center: spread on a
+/// circle, then moved apart by repulsion, together along `edges` between
+/// them and towards the center, and finally kept within `radius`
+fn cluster_layout(members: &[Entity], edges: &[(Entity, Entity)], radius: f32) -> Vec<Vec3> {
+    let count = members.len();
+    if count < 2 {
+        return vec![Vec3::ZERO; count];
+    }
+    let index: HashMap<Entity, usize> = members.iter().enumerate().map(|(i, entity)| (*entity, i)).collect();
+    let links: Vec<(usize, usize)> = edges.iter()
+        .filter_map(|(source, target)| Some((*index.get(source)?, *index.get(target)?)))
+        .filter(|(source, target)| source != target)
+        .collect();
+
+    let rest = radius * 0.5;
+    let mut positions: Vec<Vec3> = (0..count)
+        .map(|i| {
+            let angle = i as f32 * TAU / count as f32;
+            Vec3::new(angle.cos(), angle.sin(), 0.0) * rest
+        })
+        .collect();
+    for _ in 0..CLUSTER_LAYOUT_ITERATIONS {
+        let mut forces: Vec<Vec3> = positions.iter().map(|position| -*position).collect();
+        for i in 0..count {
+            for j in (i + 1)..count {
+                let diff = positions[i] - positions[j];
+                let distance = diff.length().max(0.01);
+                let push = diff / distance * (rest * rest / distance);
+                forces[i] += push;
+                forces[j] -= push;
+            }
+        }
+        for &(source, target) in &links {
+            let diff = positions[target] - positions[source];
+            let distance = diff.length().max(0.01);
+            let pull = diff / distance * (distance - rest);
+            forces[source] += pull;
+            forces[target] -= pull;
+        }
+        for (position, force) in positions.iter_mut().zip(&forces) {
+            *position += (*force * 0.1).clamp_length_max(radius * 0.1);
         }
     }
+    positions.into_iter().map(|position| position.clamp_length_max(radius)).collect()
 }
 
-/// Apply random layout algorithm
-fn apply_random_layout(
-    nodes: &mut Query<(Entity, &NodeVisual, &mut Transform)>,
-    graph_id: &GraphId,
-) {
+/// Random layout: `count` nodes scattered through a box about as wide as
+/// the grid layout of the same nodes, and a fifth as deep
+fn random_positions(count: usize, config: &GraphLayoutConfig) -> Vec<Vec3> {
     use rand::Rng;
     let mut rng = rand::thread_rng();
-    
-    for (_, node_visual, mut transform) in nodes.iter_mut() {
-        if &node_visual.graph_id == graph_id {
-            let x = rng.gen_range(-500.0..500.0);
-            let y = rng.gen_range(-500.0..500.0);
-            let z = rng.gen_range(-100.0..100.0);
-            transform.translation = Vec3::new(x, y, z);
-        }
-    }
+    let half_width = (count as f32).sqrt().ceil().max(1.0) * config.grid_spacing / 2.0;
+    let half_depth = half_width / 5.0;
+    (0..count)
+        .map(|_| {
+            let x = rng.gen_range(-half_width..=half_width);
+            let y = rng.gen_range(-half_width..=half_width);
+            let z = rng.gen_range(-half_depth..=half_depth);
+            Vec3::new(x, y, z)
+        })
+        .collect()
 }
 
-/// System to update layout algorithm from visualization hints
+/// System to pick the layout algorithm from visualization hints for graphs
+/// that don't have one set yet
 pub fn update_layout_from_hints(
     mut layout_state: ResMut<GraphLayoutState>,
     active_graph: Res<ActiveGraph>,
@@ -288,26 +682,1195 @@ pub fn update_layout_from_hints(
     if let Some(graph_id) = &active_graph.graph_id {
         // Check if we have visualization hints for this graph
         if let Some(hints) = layout_state.visualization_hints.get(graph_id) {
-            // Update the layout algorithm
-            layout_state.layout_algorithms.insert(*graph_id, hints.layout_algorithm);
+            let algorithm = hints.layout_algorithm;
+            if !layout_state.layout_algorithms.contains_key(graph_id) {
+                layout_state.layout_algorithms.insert(*graph_id, algorithm);
+                layout_state.dirty.insert(*graph_id);
+            }
+        }
+    }
+}
+
+/// System that makes a changed [`GraphVisual::layout_type`] the graph's
+/// layout algorithm
+pub fn sync_graph_layout_types(
+    mut layout_state: ResMut<GraphLayoutState>,
+    graphs: Query<&GraphVisual, Changed<GraphVisual>>,
+) {
+    for graph in graphs.iter() {
+        let algorithm = graph.layout_type.into();
+        if layout_state.layout_algorithms.get(&graph.graph_id) != Some(&algorithm) {
+            layout_state.layout_algorithms.insert(graph.graph_id, algorithm);
+            layout_state.dirty.insert(graph.graph_id);
         }
     }
 }
 
 /// Command to set layout algorithm for a graph
-#[derive(Event)]
+#[derive(Event, Debug, Clone)]
 pub struct SetLayoutAlgorithm {
     pub graph_id: GraphId,
     pub layout_type: LayoutType,
 }
 
-/// System to handle layout algorithm change commands
+/// System to handle layout algorithm change commands, keeping the graph
+/// entity's [`GraphVisual::layout_type`] in step. For fixed layouts the
+/// target positions are worked out once and every node of the graph glides
+/// there with an [`AnimatedTransition`], inside the graph's [`GraphRegion`]
+/// or else the [`WorldBounds`]. Switching to the manual layout glides the
+/// nodes placed by hand back to their [`ManualPosition`].
+#[allow(clippy::too_many_arguments)]
 pub fn handle_layout_commands(
+    mut commands: Commands,
     mut layout_state: ResMut<GraphLayoutState>,
     mut events: EventReader<SetLayoutAlgorithm>,
+    mut graphs: Query<&mut GraphVisual>,
+    nodes: LayoutNodes<(Entity, &NodeVisual, &Transform)>,
+    manual_positions: Query<&ManualPosition>,
+    edges: LayoutEdges,
+    metadata: Query<&NodeMetadata>,
+    layout_config: Res<GraphLayoutConfig>,
+    regions: Query<&GraphRegion>,
+    bounds: Res<WorldBounds>,
 ) {
     for event in events.read() {
         layout_state.layout_algorithms.insert(event.graph_id, event.layout_type);
+        layout_state.dirty.insert(event.graph_id);
+        for mut graph in graphs.iter_mut().filter(|graph| graph.graph_id == event.graph_id) {
+            graph.layout_type = event.layout_type.into();
+        }
         info!("Changed layout algorithm for graph {:?} to {:?}", event.graph_id, event.layout_type);
+
+        let graph_nodes: Vec<Entity> = nodes.iter()
+            .filter(|(_, node_visual, _)| node_visual.graph_id == event.graph_id)
+            .map(|(entity, ..)| entity)
+            .collect();
+        let targets: Vec<(Entity, Vec3)> = if event.layout_type == LayoutType::Manual {
+            graph_nodes.iter()
+                .filter_map(|entity| Some((*entity, manual_positions.get(*entity).ok()?.0)))
+                .collect()
+        } else {
+            let (origin, bounds) = layout_frame(&event.graph_id, &regions, &bounds);
+            bounds.fit(origin, layout_positions(event.layout_type, &graph_nodes, &edges, &metadata, &layout_config))
+        };
+        if targets.is_empty() {
+            layout_state.transitions.remove(&event.graph_id);
+            continue;
+        }
+
+        for (entity, target_position) in &targets {
+            let Ok((_, _, transform)) = nodes.get(*entity) else {
+                continue;
+            };
+            commands.entity(*entity).insert(AnimatedTransition {
+                start_position: transform.translation,
+                target_position: *target_position,
+                progress: 0.0,
+                duration: layout_config.transition_duration,
+                easing: layout_config.transition_easing,
+            });
+        }
+        let entities = targets.into_iter().map(|(entity, _)| entity).collect();
+        layout_state.transitions.insert(event.graph_id, (event.layout_type, entities));
+    }
+}
+
+/// System that sends [`LayoutCompleted`] once every node of a layout
+/// transition has arrived
+pub fn finish_layout_transitions(
+    mut layout_state: ResMut<GraphLayoutState>,
+    moving: Query<(), With<AnimatedTransition>>,
+    mut completed: EventWriter<LayoutCompleted>,
+) {
+    if layout_state.transitions.is_empty() {
+        return;
+    }
+    layout_state.transitions.retain(|graph_id, (layout_type, entities)| {
+        entities.retain(|entity| moving.contains(*entity));
+        if !entities.is_empty() {
+            return true;
+        }
+        completed.write(LayoutCompleted { graph_id: *graph_id, layout_type: *layout_type });
+        false
+    });
+}
+
+/// Key that anchors the selected nodes where they are, or releases them
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnchorToggleKey(pub KeyCode);
+
+impl Default for AnchorToggleKey {
+    fn default() -> Self {
+        Self(KeyCode::KeyP)
+    }
+}
+
+/// System that toggles [`AnchoredPosition`] on the selected nodes when
+/// [`AnchorToggleKey`] is pressed. Not run while a panel has keyboard focus.
+pub fn toggle_selected_anchors(
+    mut commands: Commands,
+    keyboard: Option<Res<ButtonInput<KeyCode>>>,
+    key: Res<AnchorToggleKey>,
+    selected: Query<(Entity, &Transform, Has<AnchoredPosition>), With<Selected>>,
+) {
+    if !keyboard.is_some_and(|keyboard| keyboard.just_pressed(key.0)) {
+        return;
+    }
+    for (entity, transform, anchored) in selected.iter() {
+        if anchored {
+            commands.entity(entity).remove::<AnchoredPosition>();
+        } else {
+            commands.entity(entity).insert(AnchoredPosition(transform.translation));
+        }
+    }
+}
+
+/// System that moves the anchor of a dragged node along with it, so the
+/// layout doesn't pull it back to where it was anchored. Once dropped, the
+/// node stays anchored where it landed, after snapping.
+pub fn move_dragged_anchors(
+    mut dragging: EventReader<NodeDragging>,
+    mut drag_ended: EventReader<NodeDragEnd>,
+    mut anchors: Query<(&mut AnchoredPosition, &Transform)>,
+) {
+    for event in dragging.read() {
+        if let Ok((mut anchor, _)) = anchors.get_mut(event.entity) {
+            anchor.set_if_neq(AnchoredPosition(event.current_position));
+        }
+    }
+    for event in drag_ended.read() {
+        if let Ok((mut anchor, transform)) = anchors.get_mut(event.entity) {
+            anchor.set_if_neq(AnchoredPosition(transform.translation));
+        }
     }
-}
\ No newline at end of file
+}
+
+/// Key that moves every node so the graph is centered on the origin
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecenterKey(pub KeyCode);
+
+impl Default for RecenterKey {
+    fn default() -> Self {
+        Self(KeyCode::Home)
+    }
+}
+
+/// System that moves every node, anchors, manual positions and glides
+/// included, so the nodes' centroid is at the origin when [`RecenterKey`]
+/// is pressed. Graphs in a [`GraphRegion`] are already kept in place and
+/// aren't moved. The camera is sent a [`FocusCamera`] on the moved nodes
+/// the frame after, once their global transforms have caught up.
+#[allow(clippy::type_complexity)]
+pub fn recenter_graph(
+    keyboard: Option<Res<ButtonInput<KeyCode>>>,
+    key: Res<RecenterKey>,
+    mut nodes: Query<(
+        Entity,
+        &NodeVisual,
+        &mut Transform,
+        Option<&mut AnchoredPosition>,
+        Option<&mut ManualPosition>,
+        Option<&mut AnimatedTransition>,
+    )>,
+    regions: Query<&GraphRegion>,
+    mut focus_camera: EventWriter<FocusCamera>,
+    mut pending_focus: Local<Vec<Entity>>,
+) {
+    if !pending_focus.is_empty() {
+        focus_camera.write(FocusCamera {
+            target_entities: std::mem::take(&mut *pending_focus),
+            target_point: None,
+            transition_duration: RECENTER_FOCUS_DURATION,
+        });
+    }
+    if !keyboard.is_some_and(|keyboard| keyboard.just_pressed(key.0)) {
+        return;
+    }
+
+    let in_region = |node: &NodeVisual| regions.iter().any(|region| region.graph_id == node.graph_id);
+    let positions: Vec<Vec3> = nodes.iter()
+        .filter(|(_, node, ..)| !in_region(node))
+        .map(|(_, _, transform, ..)| transform.translation)
+        .collect();
+    if positions.is_empty() {
+        return;
+    }
+    let centroid = positions.iter().sum::<Vec3>() / positions.len() as f32;
+    for (entity, node, mut transform, anchor, manual, transition) in nodes.iter_mut() {
+        if in_region(node) {
+            continue;
+        }
+        transform.translation -= centroid;
+        if let Some(mut anchor) = anchor {
+            anchor.0 -= centroid;
+        }
+        if let Some(mut manual) = manual {
+            manual.0 -= centroid;
+        }
+        if let Some(mut transition) = transition {
+            transition.start_position -= centroid;
+            transition.target_position -= centroid;
+        }
+        pending_focus.push(entity);
+    }
+}
+
+/// Number of grid cells drawn along each axis when the snap grid is shown
+const SNAP_GRID_CELLS: u32 = 40;
+
+/// Grid snapping for manually placed nodes
+#[derive(Resource, Debug, Clone)]
+pub struct SnapConfig {
+    pub enabled: bool,
+    /// Spacing of the grid nodes snap to, on the X and Y axes
+    pub grid_size: f32,
+}
+
+impl Default for SnapConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            grid_size: 1.0,
+        }
+    }
+}
+
+/// Position on the X/Y grid closest to `position`; Z is left as is
+pub fn snap_to_grid(position: Vec3, grid_size: f32) -> Vec3 {
+    if grid_size <= 0.0 {
+        return position;
+    }
+    let snapped = (position.truncate() / grid_size).round() * grid_size;
+    snapped.extend(position.z)
+}
+
+/// System that snaps dropped nodes onto the grid and reports the snapped
+/// position through `NodePositionChanged`
+pub fn snap_dragged_nodes(
+    config: Res<SnapConfig>,
+    mut drag_ended: EventReader<NodeDragEnd>,
+    mut position_changed: EventWriter<NodePositionChanged>,
+    mut nodes: Query<&mut Transform, With<NodeVisual>>,
+) {
+    if !config.enabled {
+        drag_ended.clear();
+        return;
+    }
+
+    for event in drag_ended.read() {
+        let Ok(mut transform) = nodes.get_mut(event.entity) else {
+            continue;
+        };
+        let snapped = snap_to_grid(event.final_position, config.grid_size);
+        transform.translation = snapped;
+        position_changed.write(NodePositionChanged {
+            entity: event.entity,
+            node_id: event.node_id,
+            old_position: event.final_position,
+            new_position: snapped,
+        });
+    }
+}
+
+/// System that remembers where dropped nodes ended up, after snapping, as
+/// their [`ManualPosition`]
+pub fn record_manual_positions(
+    mut commands: Commands,
+    mut drag_ended: EventReader<NodeDragEnd>,
+    nodes: Query<&Transform, With<NodeVisual>>,
+) {
+    for event in drag_ended.read() {
+        if let Ok(transform) = nodes.get(event.entity) {
+            commands.entity(event.entity).insert(ManualPosition(transform.translation));
+        }
+    }
+}
+
+/// System that asks for a layout of the graphs whose nodes were dropped or
+/// moved, by giving the nodes [`NeedsLayout`]
+pub fn request_layout_for_moved_nodes(
+    mut commands: Commands,
+    mut drag_ended: EventReader<NodeDragEnd>,
+    mut position_changed: EventReader<NodePositionChanged>,
+    nodes: Query<(), With<NodeVisual>>,
+) {
+    let moved = drag_ended.read().map(|event| event.entity)
+        .chain(position_changed.read().map(|event| event.entity));
+    for entity in moved {
+        if nodes.contains(entity) {
+            commands.entity(entity).insert(NeedsLayout);
+        }
+    }
+}
+
+/// Whether the environment grid already shows the snap grid: it is shown,
+/// has the same spacing and lies in the XY plane the snap grid is drawn in
+fn environment_shows_snap_grid(
+    environment: Option<&SceneRenderSettings>,
+    environment_config: Option<&EnvironmentConfig>,
+    grid_size: f32,
+) -> bool {
+    environment.is_some_and(|environment| environment.show_grid && environment.grid_size == grid_size)
+        && environment_config.is_some_and(|config| config.grid_isometry == Isometry3d::IDENTITY)
+}
+
+/// System that gives new canvases, and every canvas once the snap grid
+/// size changes, the snap grid size. A canvas can set its own grid size in
+/// between.
+pub fn sync_snap_grid_size(config: Res<SnapConfig>, mut settings: Query<Mut<RenderSettings>>) {
+    for mut settings in settings.iter_mut() {
+        if (config.is_changed() || settings.is_added()) && settings.grid_size != config.grid_size {
+            settings.grid_size = config.grid_size;
+        }
+    }
+}
+
+/// System that draws the snap grid while snapping is enabled or a canvas
+/// asks for its grid. An environment grid of the same spacing in the same
+/// plane already shows it.
+pub fn draw_snap_grid(
+    config: Res<SnapConfig>,
+    settings: Query<&RenderSettings>,
+    environment: Option<Res<SceneRenderSettings>>,
+    environment_config: Option<Res<EnvironmentConfig>>,
+    mut gizmos: Gizmos,
+) {
+    let show_grid = config.enabled || settings.iter().any(|settings| settings.show_grid);
+    if environment_shows_snap_grid(environment.as_deref(), environment_config.as_deref(), config.grid_size) {
+        return;
+    }
+
+    if show_grid && config.grid_size > 0.0 {
+        gizmos.grid(
+            Isometry3d::IDENTITY,
+            UVec2::splat(SNAP_GRID_CELLS),
+            Vec2::splat(config.grid_size),
+            Color::srgba(0.5, 0.5, 0.5, 0.3),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::time::TimeUpdateStrategy;
+    use cim_contextgraph::NodeId;
+    use std::time::Duration;
+
+    #[test]
+    fn test_force_layout_skips_culled_nodes_when_frozen() {
+        let graph_id = GraphId::new();
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(100)))
+            .insert_resource(ActiveGraph { graph_id: Some(graph_id) })
+            .insert_resource(GraphLayoutConfig::default())
+            .insert_resource(FreezeCulledLayout(true))
+            .init_resource::<GraphLayoutState>()
+            .init_resource::<WorldBounds>()
+            .add_systems(Update, (mark_layout_dirty, apply_layout_algorithm).chain());
+
+        let mut spawn_node = |position: Vec3| {
+            app.world_mut()
+                .spawn((NodeVisual { node_id: NodeId::new(), graph_id }, Transform::from_translation(position)))
+                .id()
+        };
+        let visible = [spawn_node(Vec3::ZERO), spawn_node(Vec3::X)];
+        let culled = spawn_node(Vec3::new(0.5, 0.0, 0.0));
+        app.world_mut().entity_mut(culled).insert(Culled);
+
+        app.update();
+        app.update();
+
+        let position = |entity| app.world().get::<Transform>(entity).unwrap().translation;
+        assert_eq!(position(culled), Vec3::new(0.5, 0.0, 0.0));
+        // The visible nodes only repel each other, along the X axis
+        assert!(position(visible[0]).x < 0.0);
+        assert!(position(visible[1]).x > 1.0);
+        assert_eq!(position(visible[0]).y, 0.0);
+    }
+
+    #[test]
+    fn test_anchored_node_stays_but_still_pushes() {
+        let graph_id = GraphId::new();
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(100)))
+            .insert_resource(ActiveGraph { graph_id: Some(graph_id) })
+            .insert_resource(GraphLayoutConfig::default())
+            .insert_resource(FreezeCulledLayout(false))
+            .init_resource::<GraphLayoutState>()
+            .init_resource::<WorldBounds>()
+            .init_resource::<AnchorToggleKey>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .add_systems(Update, (toggle_selected_anchors, mark_layout_dirty, apply_layout_algorithm).chain());
+
+        let anchored = app.world_mut()
+            .spawn((NodeVisual { node_id: NodeId::new(), graph_id }, Transform::from_translation(Vec3::ZERO), Selected))
+            .id();
+        let free = app.world_mut()
+            .spawn((NodeVisual { node_id: NodeId::new(), graph_id }, Transform::from_translation(Vec3::X)))
+            .id();
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::KeyP);
+        app.update();
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().clear();
+        assert_eq!(app.world().get::<AnchoredPosition>(anchored), Some(&AnchoredPosition(Vec3::ZERO)));
+        app.update();
+        app.update();
+
+        let position = |entity| app.world().get::<Transform>(entity).unwrap().translation;
+        assert_eq!(position(anchored), Vec3::ZERO);
+        assert!(position(free).x > 1.0);
+    }
+
+    #[test]
+    fn test_dragged_anchored_node_is_not_pulled_back() {
+        let graph_id = GraphId::new();
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(100)))
+            .insert_resource(ActiveGraph { graph_id: Some(graph_id) })
+            .insert_resource(GraphLayoutConfig::default())
+            .init_resource::<FreezeCulledLayout>()
+            .init_resource::<GraphLayoutState>()
+            .init_resource::<WorldBounds>()
+            .init_resource::<SnapConfig>()
+            .add_event::<NodeDragging>()
+            .add_event::<NodeDragEnd>()
+            .add_event::<NodePositionChanged>()
+            .add_systems(
+                Update,
+                (snap_dragged_nodes, move_dragged_anchors, mark_layout_dirty, apply_layout_algorithm).chain(),
+            );
+
+        let node_id = NodeId::new();
+        let anchored = app.world_mut()
+            .spawn((
+                NodeVisual { node_id, graph_id },
+                Transform::from_translation(Vec3::ZERO),
+                AnchoredPosition(Vec3::ZERO),
+            ))
+            .id();
+        app.world_mut().spawn((NodeVisual { node_id: NodeId::new(), graph_id }, Transform::from_translation(Vec3::X)));
+        app.update();
+
+        let position = |app: &App| app.world().get::<Transform>(anchored).unwrap().translation;
+        let dragged_to = Vec3::new(3.0, 2.0, 0.0);
+        app.world_mut().get_mut::<Transform>(anchored).unwrap().translation = dragged_to;
+        app.world_mut().send_event(NodeDragging { entity: anchored, node_id, current_position: dragged_to });
+        app.update();
+        assert_eq!(position(&app), dragged_to);
+
+        let dropped = Vec3::new(4.0, 2.0, 0.0);
+        app.world_mut().get_mut::<Transform>(anchored).unwrap().translation = dropped;
+        app.world_mut().send_event(NodeDragEnd { entity: anchored, node_id, final_position: dropped });
+        app.update();
+        app.update();
+        assert_eq!(position(&app), dropped);
+        assert_eq!(app.world().get::<AnchoredPosition>(anchored), Some(&AnchoredPosition(dropped)));
+    }
+
+    #[test]
+    fn test_coincident_nodes_stay_finite() {
+        let graph_id = GraphId::new();
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(100)))
+            .insert_resource(ActiveGraph { graph_id: Some(graph_id) })
+            .insert_resource(GraphLayoutConfig::default())
+            .init_resource::<FreezeCulledLayout>()
+            .init_resource::<GraphLayoutState>()
+            .init_resource::<WorldBounds>()
+            .add_systems(Update, (mark_layout_dirty, apply_layout_algorithm).chain());
+
+        let nodes: Vec<Entity> = (0..2)
+            .map(|_| app.world_mut().spawn((NodeVisual { node_id: NodeId::new(), graph_id }, Transform::default())).id())
+            .collect();
+        app.update();
+        app.update();
+
+        let max_step = GraphLayoutConfig::default().max_layout_step;
+        for entity in nodes {
+            let position = app.world().get::<Transform>(entity).unwrap().translation;
+            assert!(position.is_finite());
+            assert!(position.length() <= max_step);
+        }
+    }
+
+    #[test]
+    fn test_default_force_layout_settles_twenty_nodes() {
+        let graph_id = GraphId::new();
+        let config = GraphLayoutConfig::default();
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(16)))
+            .insert_resource(ActiveGraph { graph_id: Some(graph_id) })
+            .insert_resource(config.clone())
+            .init_resource::<FreezeCulledLayout>()
+            .init_resource::<GraphLayoutState>()
+            .init_resource::<WorldBounds>()
+            .add_systems(Update, (mark_layout_dirty, apply_layout_algorithm).chain());
+
+        // A ring with a few chords, starting bunched up near the origin
+        let nodes: Vec<Entity> = (0..20)
+            .map(|i| {
+                let angle = i as f32 * 0.7;
+                let position = Vec3::new(angle.cos(), angle.sin(), 0.0) * (0.2 + i as f32 * 0.05);
+                app.world_mut().spawn((NodeVisual { node_id: NodeId::new(), graph_id }, Transform::from_translation(position))).id()
+            })
+            .collect();
+        let edges = (0..20).map(|i| (i, (i + 1) % 20)).chain([(0, 10), (5, 15)]);
+        for (source, target) in edges {
+            app.world_mut().spawn(EdgeVisual {
+                edge_id: cim_contextgraph::EdgeId::new(),
+                graph_id,
+                source_entity: nodes[source],
+                target_entity: nodes[target],
+                weight: 1.0,
+            });
+        }
+
+        let positions = |app: &App| -> Vec<Vec3> {
+            nodes.iter().map(|node| app.world().get::<Transform>(*node).unwrap().translation).collect()
+        };
+        for _ in 0..600 {
+            app.update();
+        }
+        let settled = positions(&app);
+        app.update();
+        let next = positions(&app);
+
+        let extent = config.edge_length * 20.0;
+        for (position, next) in settled.iter().zip(&next) {
+            assert!(position.is_finite() && position.length() < extent, "node exploded to {position}");
+            assert!(position.distance(*next) < config.max_layout_step * 0.1, "layout hasn't settled");
+        }
+        for (i, a) in settled.iter().enumerate() {
+            for b in &settled[i + 1..] {
+                assert!(a.distance(*b) > 0.5, "nodes collapsed onto each other");
+            }
+        }
+    }
+
+    #[test]
+    fn test_relationships_settle_at_their_own_edge_lengths() {
+        let graph_id = GraphId::new();
+        let config = GraphLayoutConfig::builder()
+            .relationship_edge_length(EdgeRelationship::Contains, 2.0)
+            .relationship_edge_length(EdgeRelationship::References, 8.0)
+            .build();
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(16)))
+            .insert_resource(ActiveGraph { graph_id: Some(graph_id) })
+            .insert_resource(config)
+            .init_resource::<FreezeCulledLayout>()
+            .init_resource::<GraphLayoutState>()
+            .init_resource::<WorldBounds>()
+            .add_systems(Update, (mark_layout_dirty, apply_layout_algorithm).chain());
+
+        // Two pairs far enough apart not to push each other around
+        let mut spawn_pair = |offset: Vec3, relationship: EdgeRelationship| {
+            let [source, target] = [Vec3::ZERO, Vec3::new(5.0, 0.0, 0.0)].map(|position| {
+                app.world_mut()
+                    .spawn((NodeVisual { node_id: NodeId::new(), graph_id }, Transform::from_translation(offset + position)))
+                    .id()
+            });
+            app.world_mut().spawn((
+                EdgeVisual { edge_id: cim_contextgraph::EdgeId::new(), graph_id, source_entity: source, target_entity: target, weight: 1.0 },
+                relationship,
+            ));
+            [source, target]
+        };
+        let contains = spawn_pair(Vec3::ZERO, EdgeRelationship::Contains);
+        let references = spawn_pair(Vec3::new(0.0, 200.0, 0.0), EdgeRelationship::References);
+        for _ in 0..600 {
+            app.update();
+        }
+
+        let length = |app: &App, [source, target]: [Entity; 2]| {
+            let position = |entity| app.world().get::<Transform>(entity).unwrap().translation;
+            position(source).distance(position(target))
+        };
+        let (contains, references) = (length(&app, contains), length(&app, references));
+        // Repulsion holds each pair a little beyond its rest length
+        assert!((2.0..4.0).contains(&contains), "Contains settled at {contains}");
+        assert!((8.0..9.0).contains(&references), "References settled at {references}");
+    }
+
+    #[test]
+    fn test_heavy_edges_settle_shorter_than_light_ones() {
+        let graph_id = GraphId::new();
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(16)))
+            .insert_resource(ActiveGraph { graph_id: Some(graph_id) })
+            .init_resource::<GraphLayoutConfig>()
+            .init_resource::<FreezeCulledLayout>()
+            .init_resource::<GraphLayoutState>()
+            .init_resource::<WorldBounds>()
+            .add_systems(Update, (mark_layout_dirty, apply_layout_algorithm).chain());
+
+        let mut spawn_pair = |offset: Vec3, weight: f32| {
+            let [source, target] = [Vec3::ZERO, Vec3::new(5.0, 0.0, 0.0)].map(|position| {
+                app.world_mut()
+                    .spawn((NodeVisual { node_id: NodeId::new(), graph_id }, Transform::from_translation(offset + position)))
+                    .id()
+            });
+            app.world_mut().spawn(EdgeVisual {
+                edge_id: cim_contextgraph::EdgeId::new(),
+                graph_id,
+                source_entity: source,
+                target_entity: target,
+                weight,
+            });
+            [source, target]
+        };
+        let light = spawn_pair(Vec3::ZERO, 0.2);
+        let heavy = spawn_pair(Vec3::new(0.0, 200.0, 0.0), 5.0);
+        for _ in 0..1200 {
+            app.update();
+        }
+
+        let length = |app: &App, [source, target]: [Entity; 2]| {
+            let position = |entity| app.world().get::<Transform>(entity).unwrap().translation;
+            position(source).distance(position(target))
+        };
+        let (light, heavy) = (length(&app, light), length(&app, heavy));
+        // Repulsion stretches the light edge well past its rest length
+        assert!(heavy < light - 1.0, "heavy settled at {heavy}, light at {light}");
+        assert!((5.0..5.5).contains(&heavy), "heavy settled at {heavy}");
+    }
+
+    #[test]
+    fn test_settled_graph_is_left_alone_until_a_node_is_added() {
+        let graph_id = GraphId::new();
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(16)))
+            .insert_resource(ActiveGraph { graph_id: Some(graph_id) })
+            .init_resource::<GraphLayoutConfig>()
+            .init_resource::<FreezeCulledLayout>()
+            .init_resource::<GraphLayoutState>()
+            .init_resource::<WorldBounds>()
+            .add_systems(Update, (mark_layout_dirty, apply_layout_algorithm).chain());
+
+        let mut spawn_node = |app: &mut App, position: Vec3| {
+            app.world_mut()
+                .spawn((NodeVisual { node_id: NodeId::new(), graph_id }, Transform::from_translation(position)))
+                .id()
+        };
+        let nodes = [spawn_node(&mut app, Vec3::ZERO), spawn_node(&mut app, Vec3::new(3.0, 0.0, 0.0))];
+        app.world_mut().spawn(EdgeVisual {
+            edge_id: cim_contextgraph::EdgeId::new(),
+            graph_id,
+            source_entity: nodes[0],
+            target_entity: nodes[1],
+            weight: 1.0,
+        });
+        let dirty = |app: &App| app.world().resource::<GraphLayoutState>().dirty.contains(&graph_id);
+        let mut frames = 0;
+        while dirty(&app) || frames == 0 {
+            app.update();
+            frames += 1;
+            assert!(frames < 5000, "layout never settled");
+        }
+
+        let written = |app: &App| nodes.map(|node| app.world().entity(node).get_change_ticks::<Transform>().unwrap().changed);
+        let settled = written(&app);
+        for _ in 0..10 {
+            app.update();
+        }
+        assert_eq!(written(&app), settled);
+
+        spawn_node(&mut app, Vec3::new(1.0, 1.0, 0.0));
+        app.update();
+        assert_ne!(written(&app), settled);
+    }
+
+    #[test]
+    fn test_layout_steps_keep_nodes_inside_the_bounds() {
+        let graph_id = GraphId::new();
+        let bounds = WorldBounds { min: Vec3::splat(-5.0), max: Vec3::new(5.0, 5.0, 1.0) };
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(100)))
+            .insert_resource(ActiveGraph { graph_id: Some(graph_id) })
+            .insert_resource(GraphLayoutConfig::default())
+            .init_resource::<FreezeCulledLayout>()
+            .init_resource::<GraphLayoutState>()
+            .insert_resource(bounds)
+            .add_systems(Update, (mark_layout_dirty, apply_layout_algorithm).chain());
+
+        // Strewn well outside the bounds, and close enough to repel hard
+        let nodes: Vec<Entity> = (0..12)
+            .map(|i| {
+                let position = Vec3::new(i as f32 * 3.0 - 40.0, 20.0 - i as f32 * 0.1, 8.0);
+                app.world_mut().spawn((NodeVisual { node_id: NodeId::new(), graph_id }, Transform::from_translation(position))).id()
+            })
+            .collect();
+        let outside = |app: &App| -> Vec<Vec3> {
+            nodes.iter()
+                .map(|node| app.world().get::<Transform>(*node).unwrap().translation)
+                .filter(|position| !bounds.contains(*position))
+                .collect()
+        };
+
+        app.update();
+        app.update();
+        assert_eq!(outside(&app), Vec::<Vec3>::new());
+
+        app.world_mut().resource_mut::<GraphLayoutState>().layout_algorithms.insert(graph_id, LayoutType::Random);
+        app.world_mut().resource_mut::<GraphLayoutState>().dirty.insert(graph_id);
+        app.update();
+        assert_eq!(outside(&app), Vec::<Vec3>::new());
+    }
+
+    #[test]
+    fn test_fixed_layouts_are_scaled_to_fit_the_bounds() {
+        let bounds = WorldBounds { min: Vec3::splat(-5.0), max: Vec3::splat(5.0) };
+        let positions = vec![(0, Vec3::new(10.0, 0.0, 0.0)), (1, Vec3::new(-4.0, 6.0, 0.0)), (2, Vec3::new(1.0, -2.0, 0.0))];
+
+        // Scaled by half, not flattened onto the faces of the bounds
+        let fitted = bounds.fit(Vec3::ZERO, positions.clone());
+        assert_eq!(fitted, vec![(0, Vec3::new(5.0, 0.0, 0.0)), (1, Vec3::new(-2.0, 3.0, 0.0)), (2, Vec3::new(0.5, -1.0, 0.0))]);
+
+        // Less room on one side of the origin scales further
+        let fitted = bounds.fit(Vec3::new(3.0, 0.0, 0.0), positions.clone());
+        assert_eq!(fitted[0], (0, Vec3::new(5.0, 0.0, 0.0)));
+        assert!(fitted.iter().all(|(_, position)| bounds.contains(*position)));
+
+        // Layouts that already fit are left alone
+        let small = vec![(0, Vec3::new(1.0, 2.0, 0.0))];
+        assert_eq!(bounds.fit(Vec3::ZERO, small.clone()), small);
+    }
+
+    #[test]
+    fn test_graphs_in_regions_lay_out_side_by_side() {
+        let (left, right) = (GraphId::new(), GraphId::new());
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(100)))
+            .insert_resource(ActiveGraph { graph_id: Some(left) })
+            .insert_resource(GraphLayoutConfig::default())
+            .init_resource::<FreezeCulledLayout>()
+            .init_resource::<GraphLayoutState>()
+            .init_resource::<WorldBounds>()
+            .add_systems(Update, (mark_layout_dirty, apply_layout_algorithm).chain());
+
+        let size = Vec3::new(20.0, 20.0, 2.0);
+        let regions = [
+            GraphRegion { graph_id: left, origin: Vec3::new(-15.0, 0.0, 0.0), bounds: size },
+            GraphRegion { graph_id: right, origin: Vec3::new(15.0, 0.0, 0.0), bounds: size },
+        ];
+        for region in regions {
+            app.world_mut().spawn(region);
+        }
+        app.world_mut().resource_mut::<GraphLayoutState>().layout_algorithms.insert(right, LayoutType::Circular);
+
+        // Both graphs start out piled up around the same point
+        let nodes: Vec<(GraphId, Entity)> = (0..16)
+            .map(|i| {
+                let graph_id = if i % 2 == 0 { left } else { right };
+                let position = Vec3::new(i as f32 * 0.1, (i % 3) as f32 * 0.1, 0.0);
+                (graph_id, app.world_mut().spawn((NodeVisual { node_id: NodeId::new(), graph_id }, Transform::from_translation(position))).id())
+            })
+            .collect();
+
+        for _ in 0..20 {
+            app.update();
+        }
+        for (graph_id, entity) in nodes {
+            let position = app.world().get::<Transform>(entity).unwrap().translation;
+            let region = regions.iter().find(|region| region.graph_id == graph_id).unwrap();
+            assert!(region.contains(position), "{position} outside its region");
+            assert_eq!(position.x < 0.0, graph_id == left);
+        }
+        // The inactive graph's fixed layout ran once, like the active one's would
+        assert!(!app.world().resource::<GraphLayoutState>().dirty.contains(&right));
+    }
+
+    #[test]
+    fn test_recenter_moves_centroid_to_origin_and_frames_nodes() {
+        let graph_id = GraphId::new();
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<RecenterKey>()
+            .add_event::<FocusCamera>()
+            .add_systems(Update, recenter_graph);
+
+        let spawn_node = |app: &mut App, position: Vec3| {
+            app.world_mut().spawn((NodeVisual { node_id: NodeId::new(), graph_id }, Transform::from_translation(position))).id()
+        };
+        let free = spawn_node(&mut app, Vec3::new(100.0, 50.0, 0.0));
+        let anchored = spawn_node(&mut app, Vec3::new(120.0, 70.0, 0.0));
+        app.world_mut().entity_mut(anchored).insert(AnchoredPosition(Vec3::new(120.0, 70.0, 0.0)));
+        app.world_mut().entity_mut(free).insert(ManualPosition(Vec3::new(100.0, 50.0, 0.0)));
+
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::Home);
+        app.update();
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().clear();
+
+        let position = |app: &App, entity| app.world().get::<Transform>(entity).unwrap().translation;
+        assert_eq!(position(&app, free), Vec3::new(-10.0, -10.0, 0.0));
+        assert_eq!(position(&app, anchored), Vec3::new(10.0, 10.0, 0.0));
+        assert_eq!(app.world().get::<AnchoredPosition>(anchored), Some(&AnchoredPosition(Vec3::new(10.0, 10.0, 0.0))));
+        assert_eq!(app.world().get::<ManualPosition>(free), Some(&ManualPosition(Vec3::new(-10.0, -10.0, 0.0))));
+
+        let mut cursor = app.world().resource::<Events<FocusCamera>>().get_cursor();
+        app.update();
+        let focused: Vec<Vec<Entity>> = cursor.read(app.world().resource::<Events<FocusCamera>>())
+            .map(|focus| focus.target_entities.clone())
+            .collect();
+        assert_eq!(focused.len(), 1);
+        assert!(focused[0].contains(&free) && focused[0].contains(&anchored));
+    }
+
+    #[test]
+    fn test_reset_exploded_nodes() {
+        let mut app = App::new();
+        app.add_systems(Update, reset_exploded_nodes);
+        let node = app.world_mut().spawn((
+            NodeVisual { node_id: NodeId::new(), graph_id: GraphId::new() },
+            Transform::from_xyz(f32::NAN, 1.0, f32::INFINITY).with_scale(Vec3::splat(2.0)),
+        )).id();
+        app.update();
+
+        let transform = app.world().get::<Transform>(node).unwrap();
+        assert_eq!(transform.translation, Vec3::ZERO);
+        assert_eq!(transform.scale, Vec3::splat(2.0));
+    }
+
+    #[test]
+    fn test_graph_visual_layout_type_drives_the_layout() {
+        let graph_id = GraphId::new();
+        let radius = GraphLayoutConfig::default().circular_radius;
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(ActiveGraph { graph_id: Some(graph_id) })
+            .insert_resource(GraphLayoutConfig::default())
+            .insert_resource(FreezeCulledLayout(false))
+            .init_resource::<GraphLayoutState>()
+            .init_resource::<WorldBounds>()
+            .add_event::<SetLayoutAlgorithm>()
+            .add_systems(Update, (
+                update_layout_from_hints,
+                handle_layout_commands,
+                sync_graph_layout_types,
+                mark_layout_dirty,
+                apply_layout_algorithm,
+            ).chain());
+
+        let graph = app.world_mut().spawn(GraphVisual {
+            graph_id,
+            layout_type: crate::components::LayoutType::Manual,
+        }).id();
+        let nodes: Vec<Entity> = (0..4)
+            .map(|i| app.world_mut().spawn((
+                NodeVisual { node_id: NodeId::new(), graph_id },
+                Transform::from_xyz(i as f32 * 3.0, 1.0, 0.0),
+            )).id())
+            .collect();
+        app.update();
+        let position = |app: &App, entity| app.world().get::<Transform>(entity).unwrap().translation;
+        assert_eq!(position(&app, nodes[2]), Vec3::new(6.0, 1.0, 0.0));
+
+        app.world_mut().get_mut::<GraphVisual>(graph).unwrap().layout_type = crate::components::LayoutType::Circular;
+        app.update();
+        for node in &nodes {
+            assert!((position(&app, *node).length() - radius).abs() < 1e-3);
+        }
+
+        // Commands update the graph entity too
+        app.world_mut().send_event(SetLayoutAlgorithm { graph_id, layout_type: LayoutType::Grid });
+        app.update();
+        assert_eq!(app.world().get::<GraphVisual>(graph).unwrap().layout_type, crate::components::LayoutType::Grid);
+        assert_eq!(app.world().resource::<GraphLayoutState>().layout_algorithms.get(&graph_id), Some(&LayoutType::Grid));
+    }
+
+    #[test]
+    fn test_layout_change_glides_nodes_to_new_layout() {
+        let graph_id = GraphId::new();
+        let config = GraphLayoutConfig::default();
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, crate::animation::AnimationPlugin))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(100)))
+            .insert_resource(ActiveGraph { graph_id: Some(graph_id) })
+            .insert_resource(config.clone())
+            .insert_resource(FreezeCulledLayout(false))
+            .init_resource::<GraphLayoutState>()
+            .init_resource::<WorldBounds>()
+            .add_event::<SetLayoutAlgorithm>()
+            .add_event::<LayoutCompleted>()
+            .add_systems(Update, (
+                finish_layout_transitions,
+                handle_layout_commands,
+                mark_layout_dirty,
+                apply_layout_algorithm,
+            ).chain());
+        app.world_mut().resource_mut::<GraphLayoutState>().layout_algorithms.insert(graph_id, LayoutType::Manual);
+
+        let starts: Vec<Vec3> = (0..4).map(|i| Vec3::new(i as f32, 1.0, 0.0)).collect();
+        let nodes: Vec<Entity> = starts.iter()
+            .map(|start| app.world_mut().spawn((NodeVisual { node_id: NodeId::new(), graph_id }, Transform::from_translation(*start))).id())
+            .collect();
+        app.world_mut().send_event(SetLayoutAlgorithm { graph_id, layout_type: LayoutType::Circular });
+        app.update();
+
+        let mut targets = Vec::new();
+        for (node, start) in nodes.iter().zip(&starts) {
+            let transition = app.world().get::<AnimatedTransition>(*node).expect("node glides to the new layout");
+            assert_eq!(transition.start_position, *start);
+            assert!((transition.target_position.length() - config.circular_radius).abs() < 1e-3);
+            targets.push(transition.target_position);
+        }
+        let expected = circular_positions(nodes.len(), &config);
+        assert!(expected.iter().all(|position| targets.contains(position)));
+
+        let mut completed = Vec::new();
+        for _ in 0..(config.transition_duration * 10.0) as usize + 3 {
+            app.update();
+            completed.extend(app.world().resource::<Events<LayoutCompleted>>().iter_current_update_events().cloned());
+        }
+        assert_eq!(completed, vec![LayoutCompleted { graph_id, layout_type: LayoutType::Circular }]);
+        for (node, target) in nodes.iter().zip(&targets) {
+            assert_eq!(app.world().get::<Transform>(*node).unwrap().translation, *target);
+        }
+    }
+
+    #[test]
+    fn test_manual_layout_writes_nothing_and_restores_dropped_positions() {
+        let graph_id = GraphId::new();
+        let config = GraphLayoutConfig::default();
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, crate::animation::AnimationPlugin))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(100)))
+            .insert_resource(ActiveGraph { graph_id: Some(graph_id) })
+            .insert_resource(config.clone())
+            .insert_resource(FreezeCulledLayout(false))
+            .init_resource::<GraphLayoutState>()
+            .init_resource::<WorldBounds>()
+            .add_event::<SetLayoutAlgorithm>()
+            .add_event::<LayoutCompleted>()
+            .add_event::<NodeDragEnd>()
+            .add_systems(Update, (
+                record_manual_positions,
+                finish_layout_transitions,
+                handle_layout_commands,
+                mark_layout_dirty,
+                apply_layout_algorithm,
+            ).chain());
+        app.world_mut().resource_mut::<GraphLayoutState>().layout_algorithms.insert(graph_id, LayoutType::Manual);
+
+        let nodes: Vec<Entity> = (0..3)
+            .map(|i| app.world_mut().spawn((NodeVisual { node_id: NodeId::new(), graph_id }, Transform::from_xyz(i as f32 * 0.1, 0.0, 0.0))).id())
+            .collect();
+        app.update();
+
+        // Even while dirty, the manual layout leaves every transform alone
+        let last_changed = |app: &App| -> Vec<_> {
+            nodes.iter().map(|node| app.world().entity(*node).get_ref::<Transform>().unwrap().last_changed()).collect()
+        };
+        let before = last_changed(&app);
+        app.world_mut().resource_mut::<GraphLayoutState>().dirty.insert(graph_id);
+        app.update();
+        assert_eq!(last_changed(&app), before);
+        assert!(!app.world().resource::<GraphLayoutState>().dirty.contains(&graph_id));
+
+        let dropped = Vec3::new(7.0, -3.0, 0.0);
+        let node_id = app.world().get::<NodeVisual>(nodes[0]).unwrap().node_id;
+        app.world_mut().get_mut::<Transform>(nodes[0]).unwrap().translation = dropped;
+        app.world_mut().send_event(NodeDragEnd { entity: nodes[0], node_id, final_position: dropped });
+        app.update();
+        assert_eq!(app.world().get::<ManualPosition>(nodes[0]), Some(&ManualPosition(dropped)));
+
+        let frames = (config.transition_duration * 10.0) as usize + 3;
+        app.world_mut().send_event(SetLayoutAlgorithm { graph_id, layout_type: LayoutType::Circular });
+        for _ in 0..frames {
+            app.update();
+        }
+        let circular: Vec<Vec3> = nodes.iter().map(|node| app.world().get::<Transform>(*node).unwrap().translation).collect();
+        assert_ne!(circular[0], dropped);
+
+        // Back to manual: the dropped node returns, the others stay put
+        app.world_mut().send_event(SetLayoutAlgorithm { graph_id, layout_type: LayoutType::Manual });
+        for _ in 0..frames {
+            app.update();
+        }
+        let positions: Vec<Vec3> = nodes.iter().map(|node| app.world().get::<Transform>(*node).unwrap().translation).collect();
+        assert_eq!(positions[0], dropped);
+        assert_eq!(positions[1..], circular[1..]);
+    }
+
+    #[test]
+    fn test_random_layout_is_scaled_to_the_node_spacing() {
+        let config = GraphLayoutConfig::default();
+        let positions = random_positions(16, &config);
+        assert_eq!(positions.len(), 16);
+        // Sixteen nodes make a four by four grid
+        let half_width = 2.0 * config.grid_spacing;
+        for position in positions {
+            assert!(position.x.abs() <= half_width && position.y.abs() <= half_width);
+            assert!(position.z.abs() <= half_width / 5.0);
+        }
+    }
+
+    #[test]
+    fn test_drag_end_snaps_onto_grid_line() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(SnapConfig { enabled: true, grid_size: 2.0 })
+            .add_event::<NodeDragEnd>()
+            .add_event::<NodePositionChanged>()
+            .add_systems(Update, snap_dragged_nodes);
+
+        let node_id = NodeId::new();
+        let node = app.world_mut()
+            .spawn((NodeVisual { node_id, graph_id: GraphId::new() }, Transform::from_xyz(3.9, -0.1, 0.5)))
+            .id();
+        app.world_mut().send_event(NodeDragEnd { entity: node, node_id, final_position: Vec3::new(3.9, -0.1, 0.5) });
+        app.update();
+
+        let snapped = Vec3::new(4.0, 0.0, 0.5);
+        assert_eq!(app.world().get::<Transform>(node).unwrap().translation, snapped);
+        let changes: Vec<Vec3> = app.world().resource::<Events<NodePositionChanged>>()
+            .iter_current_update_events()
+            .map(|event| event.new_position)
+            .collect();
+        assert_eq!(changes, vec![snapped]);
+    }
+
+    #[test]
+    fn test_snap_grid_is_drawn_unless_the_environment_grid_matches() {
+        let environment = SceneRenderSettings(RenderSettings { antialiasing: true, show_grid: true, grid_size: 2.0 });
+        let flat = EnvironmentConfig::default();
+        assert!(environment_shows_snap_grid(Some(&environment), Some(&flat), 2.0));
+        assert!(!environment_shows_snap_grid(Some(&environment), Some(&flat), 1.0));
+        assert!(!environment_shows_snap_grid(Some(&environment), None, 2.0));
+
+        // An environment grid turned onto the XZ plane doesn't cover the snap grid
+        let ground = EnvironmentConfig {
+            grid_isometry: Isometry3d::from_rotation(Quat::from_rotation_x(std::f32::consts::FRAC_PI_2)),
+            ..default()
+        };
+        assert!(!environment_shows_snap_grid(Some(&environment), Some(&ground), 2.0));
+    }
+
+    #[test]
+    fn test_snap_grid_size_is_only_synced_on_change() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(SnapConfig { grid_size: 2.0, ..default() })
+            .add_systems(Update, sync_snap_grid_size);
+        let canvas = app.world_mut().spawn(RenderSettings::default()).id();
+        let grid_size = |app: &App| app.world().get::<RenderSettings>(canvas).unwrap().grid_size;
+
+        app.update();
+        assert_eq!(grid_size(&app), 2.0);
+
+        // A canvas may pick its own size until the snap config changes
+        app.world_mut().get_mut::<RenderSettings>(canvas).unwrap().grid_size = 5.0;
+        app.update();
+        assert_eq!(grid_size(&app), 5.0);
+
+        app.world_mut().resource_mut::<SnapConfig>().grid_size = 3.0;
+        app.update();
+        assert_eq!(grid_size(&app), 3.0);
+    }
+
+    #[test]
+    fn test_moved_nodes_mark_their_graph_dirty() {
+        let graph_id = GraphId::new();
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(ActiveGraph::default())
+            .insert_resource(GraphLayoutConfig::default())
+            .init_resource::<FreezeCulledLayout>()
+            .init_resource::<GraphLayoutState>()
+            .add_event::<NodeDragEnd>()
+            .add_event::<NodePositionChanged>()
+            .add_systems(Update, (request_layout_for_moved_nodes, mark_layout_dirty).chain());
+
+        let node_id = NodeId::new();
+        let node = app.world_mut().spawn((NodeVisual { node_id, graph_id }, Transform::default())).id();
+        app.update();
+        let take_dirty = |app: &mut App| std::mem::take(&mut app.world_mut().resource_mut::<GraphLayoutState>().dirty);
+        take_dirty(&mut app);
+
+        app.world_mut().send_event(NodeDragEnd { entity: node, node_id, final_position: Vec3::X });
+        app.update();
+        assert!(take_dirty(&mut app).contains(&graph_id));
+        assert!(app.world().get::<NeedsLayout>(node).is_none());
+
+        app.world_mut().send_event(NodePositionChanged { entity: node, node_id, old_position: Vec3::X, new_position: Vec3::Y });
+        app.update();
+        assert!(take_dirty(&mut app).contains(&graph_id));
+
+        app.update();
+        assert!(take_dirty(&mut app).is_empty());
+    }
+
+    #[test]
+    fn test_assign_layers_ends_on_cycles() {
+        // Layers are the longest path from a source
+        let layers = assign_layers(&[1, 2, 3], &[(1, 2), (2, 3), (1, 3)]);
+        assert_eq!((layers[&1], layers[&2], layers[&3]), (0, 1, 2));
+
+        // A node naming itself is not moved by it
+        let layers = assign_layers(&[1, 2], &[(1, 1), (1, 2)]);
+        assert_eq!((layers[&1], layers[&2]), (0, 1));
+
+        // A 2-cycle below a source keeps one of its edges
+        let layers = assign_layers(&[1, 2, 3], &[(1, 2), (2, 3), (3, 2)]);
+        assert_eq!((layers[&1], layers[&2], layers[&3]), (0, 1, 2));
+
+        // A 2-cycle on its own starts from its first node
+        let layers = assign_layers(&["a", "b"], &[("a", "b"), ("b", "a")]);
+        assert_eq!((layers["a"], layers["b"]), (0, 1));
+    }
+
+    #[test]
+    fn test_clustered_layout_keeps_shared_keys_together() {
+        let metadata = |team: &str| NodeMetadata {
+            tags: vec![format!("tag-{team}")],
+            attributes: serde_json::Map::from_iter([("team".to_string(), serde_json::json!(team))]),
+            ..default()
+        };
+        assert_eq!(cluster_key(&metadata("red"), "team").as_deref(), Some("red"));
+        assert_eq!(cluster_key(&metadata("red"), "tags").as_deref(), Some("tag-red"));
+        assert_eq!(cluster_key(&metadata("red"), "owner"), None);
+
+        let nodes: Vec<Entity> = (0..12).map(Entity::from_raw).collect();
+        let teams = ["red", "green", "blue"];
+        let clusters: HashMap<Entity, String> = nodes.iter()
+            .enumerate()
+            .map(|(index, entity)| (*entity, cluster_key(&metadata(teams[index % 3]), "team").unwrap()))
+            .collect();
+        // A chain within each cluster and a few edges across
+        let edges: Vec<(Entity, Entity)> = (0..9).map(|index| (nodes[index], nodes[index + 3]))
+            .chain([(nodes[0], nodes[1]), (nodes[4], nodes[8])])
+            .collect();
+        let config = GraphLayoutConfig::default();
+        let positions: HashMap<Entity, Vec3> = clustered_positions(&nodes, &edges, &clusters, &config).into_iter().collect();
+        assert_eq!(positions.len(), nodes.len());
+
+        let mut closest_apart = f32::MAX;
+        let mut furthest_together = 0.0f32;
+        for a in &nodes {
+            for b in &nodes {
+                if a == b {
+                    continue;
+                }
+                let distance = positions[a].distance(positions[b]);
+                if clusters[a] == clusters[b] {
+                    furthest_together = furthest_together.max(distance);
+                } else {
+                    closest_apart = closest_apart.min(distance);
+                }
+            }
+        }
+        assert!(furthest_together < closest_apart, "{furthest_together} within, {closest_apart} across");
+        assert!(furthest_together <= 2.0 * config.clusters.cluster_radius + 1e-4);
+    }
+}
@@ -7,21 +7,61 @@
 //! The functor preserves the categorical structure while enabling
 //! high-performance visualization of domain graphs in Bevy applications.
 
+pub mod aggregate;
+pub mod animation;
 pub mod bridge;
+pub mod camera;
+pub mod collapse;
+pub mod command_publisher;
+pub mod commands;
 pub mod components;
+pub mod culling;
 // pub mod deployment_visualization; // Disabled: depends on non-existent cim-domain-graph
+pub mod easing;
+pub mod edge_creation;
+pub mod edge_filter;
+pub mod edge_mesh;
 pub mod edge_systems;
+pub mod environment;
+pub mod event_alerts;
 pub mod events;
+pub mod export;
 pub mod functors;
+pub mod graph_sync;
+pub mod grouping;
+pub mod handlers;
+pub mod heatmap;
+pub mod hover;
+pub mod import;
+pub mod inspector;
+pub mod instancing;
 pub mod layout;
+pub mod lod;
+pub mod minimap;
 pub mod morphisms;
 pub mod nats_component_bridge;
 pub mod nats_event_visualization;
 pub mod nats_event_filter_ui;
 pub mod nats_event_visualization_ui;
+pub mod node_search;
+pub mod outline;
+pub mod picking;
 pub mod plugin;
+pub mod projections;
+pub mod queries;
 pub mod resources;
+pub mod screenshot;
+pub mod selection;
+pub mod serialization;
+pub mod statistics_hud;
+pub mod status_badge;
+pub mod timeline;
+pub mod undo;
+pub mod validation;
+pub mod value_objects;
 pub mod visualization;
+pub mod viz_handle;
+pub mod workflow_replay;
 
 // Re-export commonly used types
 pub use components::*;
@@ -29,19 +69,120 @@ pub use events::*;
 pub use plugin::*;
 pub use resources::*;
 
+// Node styles are shared by events and commands
+pub use value_objects::NodeVisualStyle;
+
 // Re-export bridge types selectively to avoid conflicts
-pub use bridge::{AsyncSyncBridge, BridgeError};
+pub use bridge::{AsyncSyncBridge, BridgeError, BridgeStatus, BridgeStatusChanged};
+
+// Re-export aggregates
+pub use aggregate::{GraphCanvasAggregate, VisualEdgeAggregate, VisualNodeAggregate};
+
+// Re-export visual commands; nodes are selected with `events::SelectNode`
+pub use commands::{CreateVisualEdge, CreateVisualNode, DeleteVisualEdge, DeleteVisualNode, MoveNode, PanCanvas, UpdateNodeStyle, ZoomCanvas};
+
+// Re-export command handling
+pub use handlers::{CommandHandlerPlugin, MoveAnimationConfig};
+
+// Re-export outbound command publishing
+pub use command_publisher::{CommandMessage, CommandPublisher, CommandSubjects, NatsCommandPublisher, OutboundCommands, PublishError};
+
+// Re-export camera animation
+pub use camera::{CameraAnimation, CameraAnimationPlugin, CameraEasing, CameraHome};
+pub use easing::Easing;
+pub use animation::{AnimationCompleted, AnimationPlugin};
+
+// Re-export graph export
+pub use export::{GraphExportPlugin, GraphExportSettings, to_dot, to_svg};
+
+// Re-export screenshots
+pub use screenshot::{CaptureScreenshot, GraphScreenshotPlugin, ScreenshotError, ScreenshotSaved};
 
 // Re-export functor types
-pub use functors::{DomainToVisualFunctor, VisualToDomainFunctor};
+pub use functors::{DomainEvent, DomainToVisualFunctor, VisualToDomainFunctor};
+
+// Re-export path queries
+pub use queries::{find_path, graph_structure, k_nearest_nodes, GraphStructure, QueryHandlerPlugin};
+
+// Re-export graph import
+pub use import::{load_context_graph, load_graph_document, GraphDocument, ImportError};
+
+// Re-export graph snapshots
+pub use serialization::{GraphSnapshot, SnapshotError, serialize_graph, spawn_from_snapshot};
+
+// Re-export undo/redo
+pub use undo::{GraphOperation, Redo, Undo, UndoConfig, UndoHistory, UndoRedoPlugin};
+
+// Re-export graph validation
+pub use validation::{DanglingEdge, GraphValidationConfig, GraphValidationPlugin, GraphValidationReport, OrphanEdge, ValidateGraph};
+
+// Re-export graph diffing
+pub use graph_sync::{GraphSync, GraphSyncReport};
+
+// Re-export tag grouping
+pub use grouping::{convex_hull_2d, GroupBy, GroupingPlugin};
+
+// Re-export node shape meshes
+pub use visualization::shapes::{NodeShapePlugin, ShapeMeshCache};
+
+// Re-export heatmap coloring
+pub use heatmap::{ColorScale, Gradient, HeatmapMaterial, HeatmapPlugin};
+
+// Re-export level of detail
+pub use lod::{LodConfig, LodPlugin};
+
+// Re-export the grid and axes
+pub use environment::{EnvironmentConfig, EnvironmentPlugin};
+
+// Re-export minimap
+pub use minimap::{MinimapConfig, MinimapCorner, MinimapPlugin};
+
+// Re-export node search
+pub use node_search::{search_nodes, MatchQuality, NodeMatch, NodeSearch, NodeSearchConfig, NodeSearchPlugin};
+
+// Re-export statistics HUD
+pub use statistics_hud::{format_count, StatisticsHud, StatisticsHudConfig, StatisticsHudPlugin};
+
+// Re-export status badges
+pub use status_badge::{StatusBadgePlugin, StatusIcons};
+
+// Re-export subgraph collapsing
+pub use collapse::{Collapsed, CollapsedSubgraphs, CollapsePlugin, CollapseSelected, ExpandNode, SubgraphCollapsed, SubgraphExpanded};
+
+// Re-export culling
+pub use culling::{CollapsedMember, Culled, CullingPlugin, FilteredOut, FreezeCulledLayout, HiddenRelationship, OutsideTimeline};
+
+// Re-export instanced rendering
+pub use instancing::{InstancedShape, InstancingPlugin, RenderMode, UniquePicking};
+
+// Re-export picking and selection
+pub use picking::{cursor_position, cursor_ray, pick_nearest, ray_polyline_distance, region_hit, screen_ray, world_to_screen, PickResult, PickingPlugin, PickingSet, PickingState};
+pub use hover::{HoverPlugin, HoverStyle};
+pub use outline::{OutlineConfig, OutlinePlugin};
+pub use selection::{BoxSelection, SelectionCommandsPlugin, SelectionPlugin};
+pub use edge_creation::{EdgeCreation, EdgeCreationConfig, EdgeCreationPlugin};
+pub use edge_mesh::{EdgeMeshPlugin, EdgeRenderMode};
+pub use edge_filter::{DiscoveredRelationships, EdgeFilter, EdgeFilterPlugin};
+pub use inspector::{Inspected, InspectorPlugin, InspectorState};
 
 // Re-export NATS event visualization
-pub use nats_event_visualization::{NatsEventVisualizationPlugin, DomainEventReceived, EventVisualizationCommand};
-pub use nats_event_visualization_ui::{EventVisualizationUIPlugin, EventFilters, EventStatistics};
-pub use nats_event_filter_ui::{NatsEventFilterUIPlugin, EventFilterState, TimeRange};
+pub use nats_event_visualization::{NatsEventVisualizationPlugin, MockEventSource, EventFeed, feed_events, RetentionPolicy, EventEvicted, DomainEventReceived, EventStatistics, StatisticsConfig, StatisticsSummary, EventVisualizationCommand, ColorMode, ActiveCorrelation, Paused, ConnectionPulse, ConnectionType, ConnectionVisibility, DecayCurve, DomainColors, EventDecay, color_for_correlation};
+pub use nats_event_visualization_ui::{EventVisualizationUIPlugin, EventFilters};
+pub use nats_event_filter_ui::{NatsEventFilterUIPlugin, EventFilterState, FilterableEvent, SearchMatcher, TimeRange};
+pub use event_alerts::{AlertCondition, AlertRule, AlertRules, AlertTriggered};
+pub use timeline::{TimelinePlugin, TimelineState};
+
+// Re-export the imperative facade
+pub use viz_handle::VizHandle;
+
+// Re-export workflow replay
+pub use workflow_replay::{event_matches_node, WorkflowReplay, WorkflowReplayPlugin};
 
 // Re-export NATS component bridge for isomorphic architecture
 pub use nats_component_bridge::{
-    NatsComponentBridge, NatsComponentPlugin, NatsSyncedEntity, PendingComponentUpdate,
-    process_nats_component_events, apply_component_updates,
+    NatsComponentBridge, NatsComponentPlugin, NatsSyncedEntity, PendingComponentUpdate, PendingComponentUpdates,
+    PendingComponentRemoval, SyncedComponentRemoved, ComponentConflict, ComponentSyncConfig,
+    ComponentPublishBuffer, VERSION_HEADER, ORIGIN_HEADER,
+    process_nats_component_events, apply_component_updates, apply_component_removals,
+    buffer_drag_updates, publish_buffered_updates, request_delete_commands,
 };
@@ -0,0 +1,244 @@
+//! Level of Detail: Cheaper node rendering for distant or crowded graphs
+//!
+//! Nodes marked with [`LodSphere`] have their mesh swapped between shared,
+//! progressively coarser spheres depending on their distance from the active
+//! [`GraphCamera`]. Labels marked with [`LodLabel`] follow their node on
+//! screen and are hidden in the bands that don't show labels. When the graph
+//! holds more than [`LodConfig::crowded_node_count`] nodes every node drops
+//! one band further.
+
+use bevy::prelude::*;
+use std::collections::HashMap;
+use crate::components::GraphCamera;
+use crate::picking::world_to_screen;
+
+/// One distance band of the level-of-detail configuration
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LodBand {
+    /// Nodes up to this camera distance use this band
+    pub max_distance: f32,
+    /// Longitude segments of the sphere mesh; latitude segments are half this
+    pub sphere_sectors: u32,
+    /// Whether node labels are shown in this band
+    pub show_labels: bool,
+}
+
+/// Distance bands used to pick node detail, nearest first
+#[derive(Resource, Debug, Clone)]
+pub struct LodConfig {
+    /// Bands ordered by increasing `max_distance`; nodes beyond the last band
+    /// use the last band
+    pub bands: Vec<LodBand>,
+    /// Above this many nodes every node uses the next coarser band
+    pub crowded_node_count: usize,
+}
+
+impl Default for LodConfig {
+    fn default() -> Self {
+        Self {
+            bands: vec![
+                LodBand { max_distance: 15.0, sphere_sectors: 32, show_labels: true },
+                LodBand { max_distance: 40.0, sphere_sectors: 12, show_labels: true },
+                LodBand { max_distance: f32::INFINITY, sphere_sectors: 6, show_labels: false },
+            ],
+            crowded_node_count: 300,
+        }
+    }
+}
+
+impl LodConfig {
+    /// Band index for a node at `distance` in a graph of `node_count` nodes
+    pub fn band_index(&self, distance: f32, node_count: usize) -> usize {
+        let last = self.bands.len().saturating_sub(1);
+        let by_distance = self.bands.iter()
+            .position(|band| distance <= band.max_distance)
+            .unwrap_or(last);
+        let crowding = usize::from(node_count > self.crowded_node_count);
+        (by_distance + crowding).min(last)
+    }
+}
+
+/// A node rendered as a sphere whose mesh follows the LOD bands
+#[derive(Component, Debug, Clone, Copy)]
+pub struct LodSphere {
+    pub radius: f32,
+}
+
+/// The band a [`LodSphere`] is currently rendered with
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LodLevel(pub usize);
+
+/// A UI label that follows `target` on screen and obeys its LOD band
+#[derive(Component, Debug, Clone, Copy)]
+pub struct LodLabel {
+    pub target: Entity,
+    /// World-space offset from the target's position
+    pub offset: Vec3,
+}
+
+/// Sphere meshes shared by all nodes, keyed by radius and sector count
+#[derive(Resource, Debug, Default)]
+pub struct LodMeshes {
+    spheres: HashMap<(u32, u32), Handle<Mesh>>,
+}
+
+impl LodMeshes {
+    /// The shared sphere mesh for `radius` and `sectors`, created on first use
+    pub fn sphere(&mut self, meshes: &mut Assets<Mesh>, radius: f32, sectors: u32) -> Handle<Mesh> {
+        self.spheres
+            .entry((radius.to_bits(), sectors))
+            .or_insert_with(|| meshes.add(Sphere::new(radius).mesh().uv(sectors, (sectors / 2).max(2))))
+            .clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.spheres.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spheres.is_empty()
+    }
+}
+
+/// Plugin that applies level of detail to [`LodSphere`] nodes and their labels
+pub struct LodPlugin;
+
+impl Plugin for LodPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LodConfig>()
+            .init_resource::<LodMeshes>()
+            .add_systems(Update, (update_node_lod, update_lod_labels).chain());
+    }
+}
+
+/// System that picks each node's band and swaps in the matching shared mesh
+pub fn update_node_lod(
+    mut commands: Commands,
+    config: Res<LodConfig>,
+    mut lod_meshes: ResMut<LodMeshes>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<GraphCamera>>,
+    mut nodes: Query<(Entity, &LodSphere, &GlobalTransform, Option<&mut Mesh3d>, Option<&mut LodLevel>)>,
+) {
+    if config.bands.is_empty() {
+        return;
+    }
+    let Some((_, camera_transform)) = cameras.iter().find(|(camera, _)| camera.is_active) else {
+        return;
+    };
+    let camera_position = camera_transform.translation();
+    let node_count = nodes.iter().len();
+
+    for (entity, sphere, transform, mesh, level) in nodes.iter_mut() {
+        let band = config.band_index(camera_position.distance(transform.translation()), node_count);
+        if level.as_deref() == Some(&LodLevel(band)) {
+            continue;
+        }
+
+        let handle = lod_meshes.sphere(&mut meshes, sphere.radius, config.bands[band].sphere_sectors);
+        match mesh {
+            Some(mut mesh) => mesh.0 = handle,
+            None => {
+                commands.entity(entity).insert(Mesh3d(handle));
+            }
+        }
+        match level {
+            Some(mut level) => level.0 = band,
+            None => {
+                commands.entity(entity).insert(LodLevel(band));
+            }
+        }
+    }
+}
+
+/// System that positions node labels over their node and hides them when
+/// their node's band doesn't show labels or the node is off-screen.
+/// Labels whose node is gone are despawned.
+pub fn update_lod_labels(
+    mut commands: Commands,
+    config: Res<LodConfig>,
+    cameras: Query<(&Camera, &GlobalTransform), With<GraphCamera>>,
+    nodes: Query<(&GlobalTransform, Option<&LodLevel>)>,
+    mut labels: Query<(Entity, &LodLabel, &mut Node, &mut Visibility, &ComputedNode)>,
+) {
+    let camera = cameras.iter().find(|(camera, _)| camera.is_active);
+
+    for (entity, label, mut node, mut visibility, computed) in labels.iter_mut() {
+        let Ok((transform, level)) = nodes.get(label.target) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+
+        let shows_label = level
+            .and_then(|level| config.bands.get(level.0))
+            .is_none_or(|band| band.show_labels);
+        let screen_position = camera.filter(|_| shows_label).and_then(|(camera, camera_transform)| {
+            let position = world_to_screen(camera, camera_transform, transform.translation() + label.offset)?;
+            camera.logical_viewport_rect()?.contains(position).then_some(position)
+        });
+
+        match screen_position {
+            Some(position) => {
+                let size = computed.size() * computed.inverse_scale_factor();
+                node.left = Val::Px(position.x - size.x / 2.0);
+                node.top = Val::Px(position.y - size.y);
+                visibility.set_if_neq(Visibility::Inherited);
+            }
+            None => {
+                visibility.set_if_neq(Visibility::Hidden);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_band_index_by_distance_and_crowding() {
+        let config = LodConfig::default();
+        assert_eq!(config.band_index(5.0, 10), 0);
+        assert_eq!(config.band_index(20.0, 10), 1);
+        assert_eq!(config.band_index(1000.0, 10), 2);
+
+        // Crowded graphs drop a band, but never past the coarsest
+        assert_eq!(config.band_index(5.0, 1000), 1);
+        assert_eq!(config.band_index(1000.0, 1000), 2);
+    }
+
+    #[test]
+    fn test_nodes_share_meshes_per_band() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<Assets<Mesh>>()
+            .init_resource::<LodConfig>()
+            .init_resource::<LodMeshes>()
+            .add_systems(Update, update_node_lod);
+
+        app.world_mut().spawn((Camera::default(), GlobalTransform::IDENTITY, GraphCamera));
+        let spawn_node = |app: &mut App, z: f32| {
+            app.world_mut()
+                .spawn((LodSphere { radius: 0.5 }, GlobalTransform::from_xyz(0.0, 0.0, z)))
+                .id()
+        };
+        let near = [spawn_node(&mut app, 5.0), spawn_node(&mut app, -8.0)];
+        let far = spawn_node(&mut app, 100.0);
+        app.update();
+
+        let world = app.world();
+        assert_eq!(world.get::<LodLevel>(near[0]), Some(&LodLevel(0)));
+        assert_eq!(world.get::<LodLevel>(far), Some(&LodLevel(2)));
+        assert_eq!(world.get::<Mesh3d>(near[0]), world.get::<Mesh3d>(near[1]));
+        assert_ne!(world.get::<Mesh3d>(near[0]), world.get::<Mesh3d>(far));
+        assert_eq!(world.resource::<LodMeshes>().len(), 2);
+        assert_eq!(world.resource::<Assets<Mesh>>().len(), 2);
+
+        // Moving the far node close swaps it to the shared near mesh
+        app.world_mut().entity_mut(far).insert(GlobalTransform::from_xyz(0.0, 0.0, 1.0));
+        app.update();
+        let world = app.world();
+        assert_eq!(world.get::<Mesh3d>(far), world.get::<Mesh3d>(near[0]));
+        assert_eq!(world.resource::<Assets<Mesh>>().len(), 2);
+    }
+}
@@ -0,0 +1,279 @@
+//! Minimap: An overview of the whole graph in a corner of the window
+//!
+//! [`MinimapPlugin`] draws every node, scaled to fit, in a small egui panel
+//! together with the outline of what the graph camera currently sees.
+//! Clicking the minimap recenters the camera on that point by sending
+//! [`FocusCamera`] with a target point. Like the layouts, the minimap shows
+//! the XY plane.
+//!
+//! Node positions and the view outline are sampled every
+//! [`MinimapConfig::refresh_interval`] seconds rather than every frame.
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiPlugin, EguiPrimaryContextPass};
+use crate::camera::CameraAnimationPlugin;
+use crate::components::{GraphCamera, NodeVisual};
+use crate::events::FocusCamera;
+use crate::picking::screen_ray;
+
+/// Blank border kept around the nodes, as a fraction of the graph's extent
+const MINIMAP_PADDING: f32 = 0.1;
+
+/// Radius of a node dot, in minimap pixels
+const MINIMAP_NODE_RADIUS: f32 = 2.0;
+
+/// Window corner the minimap is anchored to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MinimapCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+/// Size and placement of the minimap
+#[derive(Resource, Debug, Clone)]
+pub struct MinimapConfig {
+    /// Size of the panel, in logical pixels
+    pub size: Vec2,
+    pub corner: MinimapCorner,
+    /// Distance from the window edges, in logical pixels
+    pub margin: f32,
+    /// Seconds between samples of the node positions and camera view
+    pub refresh_interval: f32,
+    /// Duration of the camera transition after a click
+    pub transition_duration: f32,
+}
+
+impl Default for MinimapConfig {
+    fn default() -> Self {
+        Self {
+            size: Vec2::new(200.0, 150.0),
+            corner: MinimapCorner::default(),
+            margin: 10.0,
+            refresh_interval: 0.1,
+            transition_duration: 0.4,
+        }
+    }
+}
+
+/// Last sample of what the minimap shows
+#[derive(Resource, Debug, Clone, Default)]
+pub struct MinimapState {
+    /// Node positions in the XY plane
+    pub nodes: Vec<Vec2>,
+    /// Corners of the camera's view on the XY plane, if every corner of the
+    /// view reaches it
+    pub view: Option<[Vec2; 4]>,
+    /// Seconds since the last sample; `None` until the first one
+    since_refresh: Option<f32>,
+}
+
+/// Maps world XY positions into the minimap and back, keeping the aspect
+/// ratio. Minimap coordinates are pixels from the top-left corner.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MinimapTransform {
+    world_center: Vec2,
+    scale: f32,
+    size: Vec2,
+}
+
+impl MinimapTransform {
+    /// Transform fitting all `points`, with some padding, into a minimap of
+    /// `size` pixels
+    pub fn fit(points: impl IntoIterator<Item = Vec2>, size: Vec2) -> Self {
+        let bounds = points.into_iter().fold(None, |bounds: Option<Rect>, point| {
+            Some(bounds.map_or(Rect::from_center_size(point, Vec2::ZERO), |bounds| {
+                bounds.union_point(point)
+            }))
+        });
+        let Some(bounds) = bounds else {
+            return Self { world_center: Vec2::ZERO, scale: 1.0, size };
+        };
+
+        // A single node, or nodes on a line, still get a usable scale
+        let extent = (bounds.size() * (1.0 + 2.0 * MINIMAP_PADDING)).max(Vec2::ONE);
+        let scale = (size / extent).min_element();
+        Self { world_center: bounds.center(), scale, size }
+    }
+
+    /// Minimap pixel position of a world position
+    pub fn to_minimap(&self, world: Vec2) -> Vec2 {
+        // World Y points up, minimap Y down
+        (world - self.world_center) * self.scale * Vec2::new(1.0, -1.0) + self.size / 2.0
+    }
+
+    /// World position shown at a minimap pixel position
+    pub fn to_world(&self, minimap: Vec2) -> Vec2 {
+        (minimap - self.size / 2.0) * Vec2::new(1.0, -1.0) / self.scale + self.world_center
+    }
+}
+
+/// Plugin that shows a clickable minimap of the graph
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugins(EguiPlugin::default());
+        }
+        // Clicks are handled by the camera animation
+        if !app.is_plugin_added::<CameraAnimationPlugin>() {
+            app.add_plugins(CameraAnimationPlugin);
+        }
+
+        app.init_resource::<MinimapConfig>()
+            .init_resource::<MinimapState>()
+            .add_systems(Update, refresh_minimap)
+            .add_systems(EguiPrimaryContextPass, draw_minimap);
+    }
+}
+
+/// System that samples node positions and the graph camera's view once per
+/// refresh interval
+pub fn refresh_minimap(
+    time: Res<Time>,
+    config: Res<MinimapConfig>,
+    mut state: ResMut<MinimapState>,
+    nodes: Query<&GlobalTransform, With<NodeVisual>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<GraphCamera>>,
+) {
+    if let Some(since_refresh) = &mut state.since_refresh {
+        *since_refresh += time.delta_secs();
+        if *since_refresh < config.refresh_interval {
+            return;
+        }
+    }
+    state.since_refresh = Some(0.0);
+
+    state.nodes = nodes.iter()
+        .map(|transform| transform.translation().truncate())
+        .collect();
+
+    state.view = cameras.iter()
+        .find(|(camera, _)| camera.is_active)
+        .and_then(|(camera, camera_transform)| {
+            let viewport = camera.logical_viewport_rect()?;
+            let corners = [
+                viewport.min,
+                Vec2::new(viewport.max.x, viewport.min.y),
+                viewport.max,
+                Vec2::new(viewport.min.x, viewport.max.y),
+            ];
+            let mut view = [Vec2::ZERO; 4];
+            for (corner, point) in corners.into_iter().zip(&mut view) {
+                let ray = screen_ray(camera, camera_transform, corner)?;
+                let distance = ray.intersect_plane(Vec3::ZERO, InfinitePlane3d::new(Vec3::Z))?;
+                *point = ray.get_point(distance).truncate();
+            }
+            Some(view)
+        });
+}
+
+/// System that draws the minimap and recenters the graph camera on clicks
+pub fn draw_minimap(
+    mut contexts: EguiContexts,
+    config: Res<MinimapConfig>,
+    state: Res<MinimapState>,
+    mut focus: EventWriter<FocusCamera>,
+) {
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    let (align, offset) = match config.corner {
+        MinimapCorner::TopLeft => (egui::Align2::LEFT_TOP, egui::vec2(config.margin, config.margin)),
+        MinimapCorner::TopRight => (egui::Align2::RIGHT_TOP, egui::vec2(-config.margin, config.margin)),
+        MinimapCorner::BottomLeft => (egui::Align2::LEFT_BOTTOM, egui::vec2(config.margin, -config.margin)),
+        MinimapCorner::BottomRight => (egui::Align2::RIGHT_BOTTOM, egui::vec2(-config.margin, -config.margin)),
+    };
+    let transform = MinimapTransform::fit(state.nodes.iter().copied(), config.size);
+
+    egui::Area::new(egui::Id::new("cim_minimap"))
+        .anchor(align, offset)
+        .show(ctx, |ui| {
+            let (response, painter) = ui.allocate_painter(
+                egui::vec2(config.size.x, config.size.y),
+                egui::Sense::click(),
+            );
+            let origin = response.rect.min;
+            let to_screen = |world: Vec2| {
+                let point = transform.to_minimap(world);
+                origin + egui::vec2(point.x, point.y)
+            };
+
+            painter.rect_filled(response.rect, egui::CornerRadius::same(4), egui::Color32::from_black_alpha(180));
+            for node in &state.nodes {
+                painter.circle_filled(to_screen(*node), MINIMAP_NODE_RADIUS, egui::Color32::LIGHT_GRAY);
+            }
+            if let Some(view) = state.view {
+                painter.add(egui::Shape::closed_line(
+                    view.iter().map(|corner| to_screen(*corner)).collect(),
+                    egui::Stroke::new(1.0, egui::Color32::YELLOW),
+                ));
+            }
+
+            if response.clicked() {
+                if let Some(position) = response.interact_pointer_pos() {
+                    let clicked = position - origin;
+                    let world = transform.to_world(Vec2::new(clicked.x, clicked.y));
+                    focus.write(FocusCamera {
+                        target_entities: Vec::new(),
+                        target_point: Some(world.extend(0.0)),
+                        transition_duration: config.transition_duration,
+                    });
+                }
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::time::TimeUpdateStrategy;
+    use std::time::Duration;
+
+    #[test]
+    fn test_minimap_transform_fits_and_round_trips() {
+        let size = Vec2::new(200.0, 100.0);
+        let points = [Vec2::new(-10.0, -2.0), Vec2::new(10.0, 2.0), Vec2::new(0.0, 5.0)];
+        let transform = MinimapTransform::fit(points, size);
+
+        for point in points {
+            let minimap = transform.to_minimap(point);
+            assert!(minimap.cmpge(Vec2::ZERO).all() && minimap.cmple(size).all());
+            assert!(transform.to_world(minimap).distance(point) < 1e-4);
+        }
+        // Higher in the world is higher up in the minimap
+        assert!(transform.to_minimap(Vec2::new(0.0, 5.0)).y < transform.to_minimap(Vec2::ZERO).y);
+    }
+
+    #[test]
+    fn test_minimap_refresh_is_throttled() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(40)))
+            .insert_resource(MinimapConfig { refresh_interval: 0.1, ..default() })
+            .init_resource::<MinimapState>()
+            .add_systems(Update, refresh_minimap);
+
+        let node = app.world_mut().spawn((
+            NodeVisual {
+                node_id: cim_contextgraph::NodeId::new(),
+                graph_id: cim_contextgraph::ContextGraphId::new(),
+            },
+            GlobalTransform::from_xyz(3.0, 4.0, 0.0),
+        )).id();
+        app.update();
+        assert_eq!(app.world().resource::<MinimapState>().nodes, vec![Vec2::new(3.0, 4.0)]);
+
+        app.world_mut().entity_mut(node).insert(GlobalTransform::from_xyz(-1.0, 0.0, 0.0));
+        app.update();
+        app.update();
+        assert_eq!(app.world().resource::<MinimapState>().nodes, vec![Vec2::new(3.0, 4.0)]);
+
+        app.update();
+        assert_eq!(app.world().resource::<MinimapState>().nodes, vec![Vec2::new(-1.0, 0.0)]);
+    }
+}
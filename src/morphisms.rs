@@ -7,19 +7,106 @@
 use bevy::prelude::*;
 use cim_contextgraph::{NodeId, EdgeId, ContextGraphId as GraphId};
 use crate::events::*;
+use crate::edge_systems::parallel_edge_offset;
+use crate::value_objects::{EdgeCurve, NodeMetadata};
 use std::collections::HashMap;
 
-/// Resource for tracking node entity mappings
-#[derive(Resource, Default)]
-pub struct NodeEntityMap(HashMap<NodeId, Entity>);
+/// Resource for tracking node entity mappings, in both directions.
+///
+/// With [`NodeEntityMapPlugin`] added, [`sync_node_entity_map`] keeps it in
+/// line with the `NodeVisual` entities however they are spawned and
+/// despawned, so it can be relied on without maintaining it by hand.
+#[derive(Resource, Default, Debug)]
+pub struct NodeEntityMap {
+    node_to_entity: HashMap<NodeId, Entity>,
+    entity_to_node: HashMap<Entity, NodeId>,
+}
 
 impl NodeEntityMap {
+    /// Map `node_id` to `entity`, replacing any earlier mapping of either
     pub fn insert(&mut self, node_id: NodeId, entity: Entity) {
-        self.0.insert(node_id, entity);
+        if let Some(previous) = self.node_to_entity.insert(node_id, entity) {
+            if previous != entity {
+                self.entity_to_node.remove(&previous);
+            }
+        }
+        if let Some(previous) = self.entity_to_node.insert(entity, node_id) {
+            if previous != node_id {
+                self.node_to_entity.remove(&previous);
+            }
+        }
     }
 
     pub fn get(&self, node_id: &NodeId) -> Option<&Entity> {
-        self.0.get(node_id)
+        self.node_to_entity.get(node_id)
+    }
+
+    /// The node an entity shows
+    pub fn get_node(&self, entity: &Entity) -> Option<&NodeId> {
+        self.entity_to_node.get(entity)
+    }
+
+    pub fn remove(&mut self, node_id: &NodeId) -> Option<Entity> {
+        let entity = self.node_to_entity.remove(node_id)?;
+        self.entity_to_node.remove(&entity);
+        Some(entity)
+    }
+
+    pub fn remove_entity(&mut self, entity: &Entity) -> Option<NodeId> {
+        let node_id = self.entity_to_node.remove(entity)?;
+        self.node_to_entity.remove(&node_id);
+        Some(node_id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.node_to_entity.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.node_to_entity.is_empty()
+    }
+}
+
+/// Plugin that keeps the [`NodeEntityMap`] in sync with the node visuals
+pub struct NodeEntityMapPlugin;
+
+impl Plugin for NodeEntityMapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NodeEntityMap>()
+            .add_event::<VisualNodeCreated>()
+            .add_event::<VisualNodeDeleted>()
+            // Before Update, so every system there sees last frame's changes
+            .add_systems(PreUpdate, sync_node_entity_map);
+    }
+}
+
+/// System that maps new node visuals and unmaps despawned ones
+pub fn sync_node_entity_map(
+    mut node_map: ResMut<NodeEntityMap>,
+    mut created: EventReader<VisualNodeCreated>,
+    mut deleted: EventReader<VisualNodeDeleted>,
+    mut removed: RemovedComponents<crate::components::NodeVisual>,
+    added: Query<(Entity, &crate::components::NodeVisual), Added<crate::components::NodeVisual>>,
+    nodes: Query<&crate::components::NodeVisual>,
+) {
+    for entity in removed.read() {
+        node_map.remove_entity(&entity);
+    }
+    for event in deleted.read() {
+        // The node may have been created again since
+        let stale = node_map.get(&event.node_id).is_some_and(|entity| !nodes.contains(*entity));
+        if stale {
+            node_map.remove(&event.node_id);
+        }
+    }
+
+    for event in created.read() {
+        if let Ok(node) = nodes.get(event.entity) {
+            node_map.insert(node.node_id, event.entity);
+        }
+    }
+    for (entity, node) in added.iter() {
+        node_map.insert(node.node_id, entity);
     }
 }
 
@@ -178,17 +265,27 @@ pub enum DomainCommand {
 /// System to create node visuals from events
 pub fn create_node_visual(
     mut commands: Commands,
+    mut node_map: ResMut<NodeEntityMap>,
     mut events: EventReader<CreateNodeVisual>,
     mut visual_created: EventWriter<VisualNodeCreated>,
+    mut metadata_changed: EventWriter<NodeMetadataChanged>,
 ) {
     for event in events.read() {
+        let mut metadata = NodeMetadata::from_json(&event.metadata);
+        if metadata.label.is_empty() {
+            metadata.label = event.label.clone();
+        }
+
         let entity = commands.spawn((
             crate::components::NodeVisualBundle::new(
                 event.node_id,
-                GraphId::new(), // TODO: Add graph_id to CreateNodeVisual event
+                event.graph_id,
                 event.position,
             ),
+            metadata.clone(),
         )).id();
+        // Mapped right away, so edges created this frame can resolve it
+        node_map.insert(event.node_id, entity);
         
         // Emit visual created event
         visual_created.write(VisualNodeCreated {
@@ -196,54 +293,101 @@ pub fn create_node_visual(
             node_id: event.node_id,
             position: event.position,
         });
+        metadata_changed.write(NodeMetadataChanged {
+            entity,
+            node_id: event.node_id,
+            metadata,
+        });
     }
 }
 
-/// System to remove node visuals from events
+/// System to remove node visuals from events, announcing each removal with
+/// a `VisualNodeDeleted`. Every visual of the node is despawned, including
+/// ones spawned outside `create_node_visual` that the map doesn't point to.
 pub fn remove_node_visual(
     mut commands: Commands,
+    mut node_map: ResMut<NodeEntityMap>,
     mut events: EventReader<RemoveNodeVisual>,
-    query: Query<(Entity, &crate::components::NodeVisual)>,
+    nodes: Query<(Entity, &crate::components::NodeVisual)>,
+    transforms: Query<&Transform>,
+    mut visual_deleted: EventWriter<VisualNodeDeleted>,
 ) {
     for event in events.read() {
-        // Find entities with matching node ID
-        for (entity, node_visual) in query.iter() {
-            if node_visual.node_id == event.node_id {
-                commands.entity(entity).despawn();
-            }
+        let mapped = node_map.remove(&event.node_id);
+        let others = nodes.iter()
+            .filter(|(entity, node)| node.node_id == event.node_id && Some(*entity) != mapped)
+            .map(|(entity, _)| entity);
+        let entities: Vec<Entity> = mapped.into_iter().chain(others).collect();
+        let Some(&first) = entities.first() else {
+            continue;
+        };
+
+        let final_position = transforms.get(first).map_or(Vec3::ZERO, |transform| transform.translation);
+        for entity in entities {
+            commands.entity(entity).despawn();
         }
+        visual_deleted.write(VisualNodeDeleted { node_id: event.node_id, final_position });
     }
 }
 
-/// System to create edge visuals from events
+/// System to create edge visuals from events. Each edge gets an
+/// `EdgeCurve` counting the edges already between its nodes, so parallel
+/// edges fan out and self-loops nest instead of overlapping. Edges whose
+/// nodes have no visual are skipped and, with graph validation, reported as
+/// orphans.
 pub fn create_edge_visual(
     mut commands: Commands,
+    node_map: Res<NodeEntityMap>,
     mut events: EventReader<CreateEdgeVisual>,
-    nodes: Query<(Entity, &crate::components::NodeVisual)>,
     mut visual_created: EventWriter<VisualEdgeCreated>,
+    existing_edges: Query<&crate::components::EdgeVisual>,
+    mut orphans: Option<ResMut<crate::validation::OrphanEdges>>,
 ) {
-    for event in events.read() {
-        // Find source and target entities by node ID
-        let mut source_entity = None;
-        let mut target_entity = None;
+    if events.is_empty() {
+        return;
+    }
 
-        for (entity, node_visual) in nodes.iter() {
-            if node_visual.node_id == event.source_node_id {
-                source_entity = Some(entity);
-            }
-            if node_visual.node_id == event.target_node_id {
-                target_entity = Some(entity);
-            }
-        }
+    // Edges already between each pair of nodes, in either direction
+    let node_pair = |source: Entity, target: Entity| (source.min(target), source.max(target));
+    let mut pair_counts: HashMap<(Entity, Entity), usize> = HashMap::new();
+    for edge in existing_edges.iter() {
+        *pair_counts.entry(node_pair(edge.source_entity, edge.target_entity)).or_default() += 1;
+    }
+
+    for event in events.read() {
+        let source_entity = node_map.get(&event.source_node_id).copied();
+        let target_entity = node_map.get(&event.target_node_id).copied();
 
         if let (Some(source), Some(target)) = (source_entity, target_entity) {
+            let count = pair_counts.entry(node_pair(source, target)).or_default();
+            let index = *count;
+            *count += 1;
+            // Self-loops nest, other edges fan out to alternating sides.
+            // The bend axis flips with edge direction, so reversed edges
+            // negate their offset to stay on the same side of the pair.
+            let curvature = if source == target {
+                index as f32 * parallel_edge_offset(1)
+            } else if source > target {
+                -parallel_edge_offset(index)
+            } else {
+                parallel_edge_offset(index)
+            };
+
             let entity = commands.spawn((
                 crate::components::EdgeVisualBundle::new(
                     event.edge_id,
-                    GraphId::new(), // TODO: Add graph_id to CreateEdgeVisual event
+                    event.graph_id,
                     source,
                     target,
-                ),
+                ).with_weight(event.weight.unwrap_or(1.0)),
+                crate::components::EdgeLabel {
+                    text: event.relationship.to_string(),
+                },
+                event.relationship.clone(),
+                EdgeCurve {
+                    control_point: None,
+                    curvature,
+                },
             )).id();
             
             // Emit visual created event
@@ -252,7 +396,27 @@ pub fn create_edge_visual(
                 edge_id: event.edge_id,
                 source_entity: source,
                 target_entity: target,
+                source_node_id: event.source_node_id,
+                target_node_id: event.target_node_id,
             });
+        } else {
+            let mut missing: Vec<NodeId> = [(event.source_node_id, source_entity), (event.target_node_id, target_entity)]
+                .into_iter()
+                .filter(|(_, entity)| entity.is_none())
+                .map(|(node_id, _)| node_id)
+                .collect();
+            missing.dedup();
+            warn!("Edge {:?} skipped: no visual for nodes {:?}", event.edge_id, missing);
+            // Reported by the next graph validation
+            if let Some(orphans) = orphans.as_mut() {
+                orphans.0.push(crate::validation::OrphanEdge {
+                    edge_id: event.edge_id,
+                    graph_id: event.graph_id,
+                    source_node_id: event.source_node_id,
+                    target_node_id: event.target_node_id,
+                    missing,
+                });
+            }
         }
     }
 }
@@ -262,15 +426,200 @@ pub fn remove_edge_visual(
     mut commands: Commands,
     mut events: EventReader<RemoveEdgeVisual>,
     query: Query<(Entity, &crate::components::EdgeVisual)>,
+    mut visual_deleted: EventWriter<VisualEdgeDeleted>,
 ) {
     for event in events.read() {
         // Find entities with matching edge ID
+        let mut removed = false;
         for (entity, edge_visual) in query.iter() {
             if edge_visual.edge_id == event.edge_id {
                 commands.entity(entity).despawn();
+                removed = true;
             }
         }
+        if removed {
+            visual_deleted.write(VisualEdgeDeleted { edge_id: event.edge_id });
+        }
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_node_visual_attaches_metadata() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<NodeEntityMap>()
+            .add_event::<CreateNodeVisual>()
+            .add_event::<VisualNodeCreated>()
+            .add_event::<NodeMetadataChanged>()
+            .add_systems(Update, create_node_visual);
+
+        let node_id = NodeId::new();
+        let graph_id = GraphId::new();
+        app.world_mut().send_event(CreateNodeVisual {
+            node_id,
+            graph_id,
+            position: Vec3::ZERO,
+            label: "fallback".to_string(),
+            metadata: serde_json::json!({"label": "X", "tags": ["service", 3]}),
+        });
+        app.update();
+
+        let mut nodes = app.world_mut().query::<(&crate::components::NodeVisual, &NodeMetadata)>();
+        let (node, metadata) = nodes.single(app.world()).unwrap();
+        assert_eq!(node.node_id, node_id);
+        assert_eq!(node.graph_id, graph_id);
+        assert_eq!(metadata.label, "X");
+        assert_eq!(metadata.tags, vec!["service".to_string()]);
+
+        let events = app.world().resource::<Events<NodeMetadataChanged>>();
+        let changed: Vec<_> = events.iter_current_update_events().collect();
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].metadata, *metadata);
+    }
+
+    #[test]
+    fn test_label_falls_back_to_command_label() {
+        let metadata = NodeMetadata::from_json(&serde_json::Value::Null);
+        assert_eq!(metadata, NodeMetadata::default());
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<NodeEntityMap>()
+            .add_event::<CreateNodeVisual>()
+            .add_event::<VisualNodeCreated>()
+            .add_event::<NodeMetadataChanged>()
+            .add_systems(Update, create_node_visual);
+        app.world_mut().send_event(CreateNodeVisual {
+            node_id: NodeId::new(),
+            graph_id: GraphId::new(),
+            position: Vec3::ZERO,
+            label: "Plain".to_string(),
+            metadata: serde_json::Value::Null,
+        });
+        app.update();
+
+        let mut nodes = app.world_mut().query::<&NodeMetadata>();
+        assert_eq!(nodes.single(app.world()).unwrap().label, "Plain");
+    }
+
+    #[test]
+    fn test_node_entity_map_follows_spawns_and_despawns() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, NodeEntityMapPlugin));
+
+        let node_id = NodeId::new();
+        let entity = app.world_mut()
+            .spawn(crate::components::NodeVisualBundle::new(node_id, GraphId::new(), Vec3::ZERO))
+            .id();
+        app.update();
+        let node_map = app.world().resource::<NodeEntityMap>();
+        assert_eq!(node_map.get(&node_id), Some(&entity));
+        assert_eq!(node_map.get_node(&entity), Some(&node_id));
+
+        // Despawned without touching the map
+        app.world_mut().despawn(entity);
+        app.update();
+        let node_map = app.world().resource::<NodeEntityMap>();
+        assert!(node_map.get(&node_id).is_none());
+        assert!(node_map.get_node(&entity).is_none());
+        assert!(node_map.is_empty());
+    }
 
+    #[test]
+    fn test_removing_a_node_despawns_every_visual_of_it() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<NodeEntityMap>()
+            .add_event::<CreateNodeVisual>()
+            .add_event::<RemoveNodeVisual>()
+            .add_event::<VisualNodeCreated>()
+            .add_event::<VisualNodeDeleted>()
+            .add_event::<NodeMetadataChanged>()
+            .add_systems(Update, (create_node_visual, remove_node_visual).chain());
+
+        let node_id = NodeId::new();
+        let graph_id = GraphId::new();
+        app.world_mut().send_event(CreateNodeVisual {
+            node_id,
+            graph_id,
+            position: Vec3::X,
+            label: String::new(),
+            metadata: serde_json::Value::Null,
+        });
+        app.update();
+        // A second visual of the same node, as a demo spawning its own would
+        app.world_mut().spawn(crate::components::NodeVisualBundle::new(node_id, graph_id, Vec3::Y));
+
+        app.world_mut().send_event(RemoveNodeVisual { node_id });
+        app.update();
+
+        let mut nodes = app.world_mut().query::<&crate::components::NodeVisual>();
+        assert_eq!(nodes.iter(app.world()).count(), 0);
+        let deleted: Vec<_> = app.world().resource::<Events<VisualNodeDeleted>>()
+            .iter_current_update_events()
+            .map(|event| (event.node_id, event.final_position))
+            .collect();
+        assert_eq!(deleted, vec![(node_id, Vec3::X)]);
+    }
+
+    #[test]
+    fn test_parallel_edges_and_self_loops_get_distinct_paths() {
+        use crate::components::{EdgeCurveType, EdgeVisual};
+        use crate::edge_systems::edge_visual_path;
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<NodeEntityMap>()
+            .add_event::<CreateEdgeVisual>()
+            .add_event::<VisualEdgeCreated>()
+            .add_systems(Update, create_edge_visual);
+
+        let graph_id = GraphId::new();
+        let positions = [Vec3::ZERO, Vec3::new(4.0, 0.0, 0.0)];
+        let node_ids = positions.map(|position| {
+            let node_id = NodeId::new();
+            let entity = app.world_mut().spawn(GlobalTransform::from_translation(position)).id();
+            app.world_mut().resource_mut::<NodeEntityMap>().insert(node_id, entity);
+            node_id
+        });
+        for (source, target) in [(0, 1), (0, 1), (0, 0)] {
+            app.world_mut().send_event(CreateEdgeVisual {
+                edge_id: EdgeId::new(),
+                graph_id,
+                source_node_id: node_ids[source],
+                target_node_id: node_ids[target],
+                relationship: EdgeRelationship::DependsOn,
+                weight: None,
+            });
+        }
+        app.update();
+
+        let mut edges = app.world_mut().query::<(&EdgeVisual, &crate::value_objects::EdgeCurve)>();
+        let paths: Vec<Vec<Vec3>> = edges.iter(app.world())
+            .map(|(edge, curve)| {
+                let position = |entity| app.world().get::<GlobalTransform>(entity).unwrap().translation();
+                edge_visual_path(
+                    position(edge.source_entity),
+                    position(edge.target_entity),
+                    edge.source_entity == edge.target_entity,
+                    EdgeCurveType::Straight,
+                    curve.curvature,
+                    curve.control_point,
+                )
+            })
+            .collect();
+        assert_eq!(paths.len(), 3);
+
+        let length = |path: &[Vec3]| path.windows(2).map(|pair| pair[0].distance(pair[1])).sum::<f32>();
+        let (self_loops, parallel): (Vec<_>, Vec<_>) = paths.iter()
+            .partition(|path| path[0].distance(*path.last().unwrap()) < 1.0);
+        assert_eq!((self_loops.len(), parallel.len()), (1, 2));
+        assert_ne!(parallel[0], parallel[1]);
+        let self_loop_length = length(self_loops[0]);
+        assert!(self_loop_length.is_finite() && self_loop_length > 1.0);
+    }
+}
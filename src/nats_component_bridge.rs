@@ -2,28 +2,78 @@
 //!
 //! This module provides the Bevy side of the isomorphic component architecture,
 //! receiving component events from NATS and applying them to Bevy entities.
+//!
+//! Messages on `cim.component.>` are JSON-encoded [`ComponentEvent`]s, tagged
+//! by variant name. `Added` and `Updated` set a component and carry the full
+//! component data; `Removed` only names the component type to drop:
+//!
+//! ```json
+//! {"Updated": {"entity_id": "…", "component_data": {"component_type": "Label", "data": {"text": "A"}}}}
+//! {"Removed": {"entity_id": "…", "component_type": "Label"}}
+//! ```
+//!
+//! Publishers version their changes per entity with the
+//! [`VERSION_HEADER`] message header. Updates older than the version an
+//! entity already has are rejected with a [`ComponentConflict`]; messages
+//! without the header are treated as the next version. Updates to one entity
+//! arriving in the same frame are queued and applied in arrival order, so
+//! none of them is lost.
+//!
+//! Local changes are published back on `cim.component.<entity_id>`. Dragging
+//! a node changes its position every frame, so outgoing updates are buffered
+//! per entity and component in a [`ComponentPublishBuffer`] and only the
+//! latest is published once per [`ComponentSyncConfig::debounce`] window.
+//! Ending a drag drops the node's pending updates, since the final position
+//! goes out as a command. Published messages carry the bridge's
+//! [`ORIGIN_HEADER`] so the bridge ignores its own echoes.
+//!
+//! Interactions that change the graph itself are domain decisions, so they
+//! are requested as `RequestDomainCommand`s instead and published by the
+//! configured `CommandPublisher`, like every other command the
+//! visualization asks for: a finished drag by `request_drag_end_commands`,
+//! and a [`RequestDeleteSelected`] as one `RemoveNode` per selected node by
+//! [`request_delete_commands`].
 
 use bevy::prelude::*;
 use cim_domain::{ComponentEvent, EcsComponentData};
-use async_nats::Client;
+use async_nats::{Client, HeaderMap};
 use futures::StreamExt;
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc;
 use uuid::Uuid;
+use crate::components::{NodeVisual, Selected};
+use crate::events::{
+    NodeDragEnd, NodeDragging, RemoveNodeVisual, RequestDeleteSelected, RequestDomainCommand, VisualizationCommand,
+};
+
+/// NATS header carrying the entity version a component change was made at
+pub const VERSION_HEADER: &str = "Cim-Component-Version";
+
+/// NATS header identifying the bridge that published a component change
+pub const ORIGIN_HEADER: &str = "Cim-Component-Origin";
 
 /// Resource for managing NATS component synchronization in Bevy
 #[derive(Resource)]
 pub struct NatsComponentBridge {
-    /// Channel to receive component events from NATS
-    event_receiver: mpsc::UnboundedReceiver<ComponentEvent>,
+    /// Channel to receive component events from NATS, with their version
+    /// header if present
+    event_receiver: mpsc::UnboundedReceiver<(ComponentEvent, Option<u64>)>,
+    /// Channel to send component events and their version to NATS
+    event_sender: mpsc::UnboundedSender<(ComponentEvent, u64)>,
     /// Handle to the subscription task
     _subscription_handle: tokio::task::JoinHandle<()>,
+    /// Handle to the publishing task
+    _publish_handle: tokio::task::JoinHandle<()>,
 }
 
 impl NatsComponentBridge {
     /// Create a new NATS component bridge
     pub async fn new(nats_client: Arc<Client>) -> Result<Self, Box<dyn std::error::Error>> {
         let (tx, rx) = mpsc::unbounded_channel();
+        let (out_tx, mut out_rx) = mpsc::unbounded_channel::<(ComponentEvent, u64)>();
+        let origin = Uuid::new_v4().to_string();
         
         // Subscribe to component events
         let mut subscription = nats_client
@@ -31,22 +81,62 @@ impl NatsComponentBridge {
             .await?;
         
         // Spawn task to forward events to channel
+        let own_origin = origin.clone();
         let handle = tokio::spawn(async move {
             while let Some(message) = subscription.next().await {
+                let is_own = message.headers.as_ref()
+                    .and_then(|headers| headers.get(ORIGIN_HEADER))
+                    .is_some_and(|value| value.as_str() == own_origin);
+                if is_own {
+                    continue;
+                }
                 if let Ok(event) = serde_json::from_slice::<ComponentEvent>(&message.payload) {
-                    let _ = tx.send(event);
+                    let version = message.headers.as_ref()
+                        .and_then(|headers| headers.get(VERSION_HEADER))
+                        .and_then(|value| value.as_str().parse().ok());
+                    let _ = tx.send((event, version));
+                }
+            }
+        });
+        
+        // Spawn task to publish outgoing events
+        let publish_handle = tokio::spawn(async move {
+            while let Some((event, version)) = out_rx.recv().await {
+                let entity_id = match &event {
+                    ComponentEvent::Added { entity_id, .. }
+                    | ComponentEvent::Updated { entity_id, .. }
+                    | ComponentEvent::Removed { entity_id, .. } => *entity_id,
+                };
+                let Ok(payload) = serde_json::to_vec(&event) else {
+                    continue;
+                };
+                let mut headers = HeaderMap::new();
+                headers.insert(VERSION_HEADER, version.to_string().as_str());
+                headers.insert(ORIGIN_HEADER, origin.as_str());
+                if let Err(e) = nats_client
+                    .publish_with_headers(format!("cim.component.{entity_id}"), headers, payload.into())
+                    .await
+                {
+                    error!("Failed to publish component event: {}", e);
                 }
             }
         });
         
         Ok(Self {
             event_receiver: rx,
+            event_sender: out_tx,
             _subscription_handle: handle,
+            _publish_handle: publish_handle,
         })
     }
     
-    /// Receive pending component events (non-blocking)
-    pub fn receive_events(&mut self) -> Vec<ComponentEvent> {
+    /// Queue a component event for publishing at the given entity version
+    pub fn publish(&self, event: ComponentEvent, version: u64) {
+        let _ = self.event_sender.send((event, version));
+    }
+    
+    /// Receive pending component events and their versions (non-blocking)
+    pub fn receive_events(&mut self) -> Vec<(ComponentEvent, Option<u64>)> {
         let mut events = Vec::new();
         while let Ok(event) = self.event_receiver.try_recv() {
             events.push(event);
@@ -60,13 +150,127 @@ impl NatsComponentBridge {
 pub struct NatsSyncedEntity {
     /// The UUID that maps to the DDD entity
     pub entity_id: Uuid,
+    /// Version of the last update applied to this entity
+    pub version: u64,
 }
 
-/// Marker component for entities that need component updates
-#[derive(Component)]
+/// A component update waiting to be applied
+#[derive(Debug, Clone)]
 pub struct PendingComponentUpdate {
     /// The component data to apply
     pub component_data: EcsComponentData,
+    /// Entity version this update was made at
+    pub version: u64,
+}
+
+/// Component updates queued for a synced entity, in arrival order
+#[derive(Component, Debug, Default)]
+pub struct PendingComponentUpdates {
+    pub updates: Vec<PendingComponentUpdate>,
+}
+
+/// Component types queued for removal from a synced entity
+#[derive(Component, Debug, Default)]
+pub struct PendingComponentRemoval {
+    pub component_types: Vec<String>,
+}
+
+/// Local event sent after a synced component was removed, so projections can
+/// drop what they derived from it
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct SyncedComponentRemoved {
+    pub entity: Entity,
+    pub entity_id: Uuid,
+    pub component_type: String,
+}
+
+/// Local event sent when an update older than its entity's current version
+/// is rejected
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct ComponentConflict {
+    pub entity: Entity,
+    pub entity_id: Uuid,
+    pub component_type: String,
+    /// Version of the rejected update
+    pub rejected_version: u64,
+    /// Version the entity already has
+    pub current_version: u64,
+}
+
+/// Configuration for outgoing component synchronization
+#[derive(Resource, Debug, Clone)]
+pub struct ComponentSyncConfig {
+    /// How long outgoing updates to the same component are coalesced before
+    /// the latest one is published
+    pub debounce: Duration,
+}
+
+impl Default for ComponentSyncConfig {
+    fn default() -> Self {
+        Self {
+            debounce: Duration::from_millis(50),
+        }
+    }
+}
+
+/// An outgoing component update waiting to be published
+#[derive(Debug, Clone)]
+struct BufferedUpdate {
+    entity_id: Uuid,
+    component_data: EcsComponentData,
+    /// When the first update of this window was buffered
+    buffered_at: Duration,
+}
+
+/// Outgoing component updates, coalesced per entity and component type
+#[derive(Resource, Debug, Default)]
+pub struct ComponentPublishBuffer {
+    pending: HashMap<(Entity, String), BufferedUpdate>,
+}
+
+impl ComponentPublishBuffer {
+    /// Buffer an update, replacing any pending update to the same component
+    /// while keeping the start of its debounce window
+    pub fn push(&mut self, entity: Entity, entity_id: Uuid, component_data: EcsComponentData, now: Duration) {
+        let key = (entity, component_data.component_type.clone());
+        match self.pending.get_mut(&key) {
+            Some(buffered) => buffered.component_data = component_data,
+            None => {
+                self.pending.insert(key, BufferedUpdate {
+                    entity_id,
+                    component_data,
+                    buffered_at: now,
+                });
+            }
+        }
+    }
+
+    /// Drop every pending update of `entity`
+    pub fn discard_entity(&mut self, entity: Entity) {
+        self.pending.retain(|(pending_entity, _), _| *pending_entity != entity);
+    }
+
+    /// Remove and return the updates whose debounce window has passed
+    pub fn take_ready(&mut self, now: Duration, debounce: Duration) -> Vec<(Entity, Uuid, EcsComponentData)> {
+        let ready: Vec<(Entity, String)> = self.pending.iter()
+            .filter(|(_, buffered)| now.saturating_sub(buffered.buffered_at) >= debounce)
+            .map(|(key, _)| key.clone())
+            .collect();
+        ready.into_iter()
+            .filter_map(|key| {
+                let buffered = self.pending.remove(&key)?;
+                Some((key.0, buffered.entity_id, buffered.component_data))
+            })
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
 }
 
 /// System to process component events from NATS
@@ -76,80 +280,208 @@ pub fn process_nats_component_events(
     query: Query<(Entity, &NatsSyncedEntity)>,
 ) {
     let events = bridge.receive_events();
+    let mut updates: HashMap<Uuid, Vec<PendingComponentUpdate>> = HashMap::new();
+    let mut removals: HashMap<Entity, Vec<String>> = HashMap::new();
     
-    for event in events {
+    for (event, version) in events {
         match event {
             ComponentEvent::Added { entity_id, component_data } |
             ComponentEvent::Updated { entity_id, component_data } => {
-                // Find the Bevy entity with this UUID
-                let bevy_entity = query
-                    .iter()
-                    .find(|(_, synced)| synced.entity_id == entity_id)
-                    .map(|(entity, _)| entity);
-                
-                if let Some(entity) = bevy_entity {
-                    // Mark entity for component update
-                    commands.entity(entity).insert(PendingComponentUpdate {
-                        component_data,
-                    });
-                } else {
-                    // Create new entity with sync marker
-                    commands.spawn((
-                        NatsSyncedEntity { entity_id },
-                        PendingComponentUpdate { component_data },
-                    ));
-                }
+                // Unversioned messages are treated as the next version after
+                // the entity's, or after the last one queued this frame
+                let queued = updates.entry(entity_id).or_default();
+                let current = queued.last()
+                    .map(|update| update.version)
+                    .or_else(|| query.iter()
+                        .find(|(_, synced)| synced.entity_id == entity_id)
+                        .map(|(_, synced)| synced.version))
+                    .unwrap_or(0);
+                queued.push(PendingComponentUpdate { component_data, version: version.unwrap_or(current + 1) });
             }
             
             ComponentEvent::Removed { entity_id, component_type } => {
-                // Handle component removal if needed
                 if let Some((entity, _)) = query
                     .iter()
                     .find(|(_, synced)| synced.entity_id == entity_id)
                 {
-                    info!("Component {} removed from entity {:?}", component_type, entity);
-                    // Component-specific removal logic would go here
+                    removals.entry(entity).or_default().push(component_type);
                 }
             }
         }
     }
+
+    for (entity_id, updates) in updates {
+        // Find the Bevy entity with this UUID
+        let bevy_entity = query
+            .iter()
+            .find(|(_, synced)| synced.entity_id == entity_id);
+
+        if let Some((entity, _)) = bevy_entity {
+            commands.entity(entity).insert(PendingComponentUpdates { updates });
+        } else {
+            // Create new entity with sync marker
+            commands.spawn((
+                NatsSyncedEntity { entity_id, version: 0 },
+                PendingComponentUpdates { updates },
+            ));
+        }
+    }
+
+    for (entity, component_types) in removals {
+        commands.entity(entity).insert(PendingComponentRemoval { component_types });
+    }
 }
 
-/// System to apply pending component updates
-/// This is where you'd map EcsComponentData to actual Bevy components
+/// System to apply pending component updates, in the order they arrived
+/// This is where you'd map EcsComponentData to actual Bevy components.
+/// Updates older than the entity's version are dropped and reported as a
+/// [`ComponentConflict`].
 pub fn apply_component_updates(
     mut commands: Commands,
-    query: Query<(Entity, &PendingComponentUpdate), With<NatsSyncedEntity>>,
+    mut query: Query<(Entity, &mut NatsSyncedEntity, &PendingComponentUpdates)>,
+    mut conflicts: EventWriter<ComponentConflict>,
 ) {
-    for (entity, pending) in query.iter() {
-        // Map component types to Bevy components
-        match pending.component_data.component_type.as_str() {
-            "Position3D" => {
-                if let Ok(pos) = serde_json::from_value::<Position3D>(pending.component_data.data.clone()) {
-                    commands.entity(entity).insert(Transform::from_translation(
-                        Vec3::new(pos.x, pos.y, pos.z)
-                    ));
-                }
+    for (entity, mut synced, queued) in query.iter_mut() {
+        // Remove the pending update marker
+        commands.entity(entity).remove::<PendingComponentUpdates>();
+
+        for pending in &queued.updates {
+            if pending.version < synced.version {
+                warn!(
+                    "Rejected stale {} update v{} for entity {} at v{}",
+                    pending.component_data.component_type, pending.version, synced.entity_id, synced.version
+                );
+                conflicts.write(ComponentConflict {
+                    entity,
+                    entity_id: synced.entity_id,
+                    component_type: pending.component_data.component_type.clone(),
+                    rejected_version: pending.version,
+                    current_version: synced.version,
+                });
+                continue;
             }
-            "Label" => {
-                if let Ok(label) = serde_json::from_value::<LabelData>(pending.component_data.data.clone()) {
-                    commands.entity(entity).insert(Name::new(label.text));
+            synced.version = pending.version;
+
+            // Map component types to Bevy components
+            match pending.component_data.component_type.as_str() {
+                "Position3D" => {
+                    if let Ok(pos) = serde_json::from_value::<Position3D>(pending.component_data.data.clone()) {
+                        commands.entity(entity).insert(Transform::from_translation(
+                            Vec3::new(pos.x, pos.y, pos.z)
+                        ));
+                    }
                 }
-            }
-            "WorkflowStateComponent" => {
-                if let Ok(state) = serde_json::from_value::<WorkflowState>(pending.component_data.data.clone()) {
-                    // Apply workflow-specific visualization
-                    info!("Workflow state updated: {:?}", state);
-                    // You would add workflow visualization components here
+                "Label" => {
+                    if let Ok(label) = serde_json::from_value::<LabelData>(pending.component_data.data.clone()) {
+                        commands.entity(entity).insert(Name::new(label.text));
+                    }
+                }
+                "WorkflowStateComponent" => {
+                    if let Ok(state) = serde_json::from_value::<WorkflowState>(pending.component_data.data.clone()) {
+                        // Apply workflow-specific visualization
+                        info!("Workflow state updated: {:?}", state);
+                        // You would add workflow visualization components here
+                    }
+                }
+                _ => {
+                    warn!("Unknown component type: {}", pending.component_data.component_type);
                 }
             }
-            _ => {
-                warn!("Unknown component type: {}", pending.component_data.component_type);
+        }
+    }
+}
+
+/// System to apply pending component removals, the inverse of
+/// [`apply_component_updates`]
+pub fn apply_component_removals(
+    mut commands: Commands,
+    removals: Query<(Entity, &NatsSyncedEntity, &PendingComponentRemoval)>,
+    mut removed_events: EventWriter<SyncedComponentRemoved>,
+) {
+    for (entity, synced, pending) in removals.iter() {
+        for component_type in &pending.component_types {
+            // Inverse of the update mapping
+            match component_type.as_str() {
+                "Position3D" => {
+                    commands.entity(entity).remove::<Transform>();
+                }
+                "Label" => {
+                    commands.entity(entity).remove::<Name>();
+                }
+                "WorkflowStateComponent" => {}
+                _ => {
+                    warn!("Unknown component type: {}", component_type);
+                    continue;
+                }
             }
+            removed_events.write(SyncedComponentRemoved {
+                entity,
+                entity_id: synced.entity_id,
+                component_type: component_type.clone(),
+            });
+        }
+
+        commands.entity(entity).remove::<PendingComponentRemoval>();
+    }
+}
+
+/// System that buffers position updates of dragged synced nodes. The end of
+/// a drag drops the node's pending updates; the final position goes out
+/// as a command.
+pub fn buffer_drag_updates(
+    time: Res<Time>,
+    mut buffer: ResMut<ComponentPublishBuffer>,
+    mut dragging: EventReader<NodeDragging>,
+    mut drag_ended: EventReader<NodeDragEnd>,
+    synced: Query<&NatsSyncedEntity>,
+) {
+    let now = time.elapsed();
+    let position = |position: Vec3| EcsComponentData {
+        component_type: "Position3D".to_string(),
+        data: serde_json::json!({ "x": position.x, "y": position.y, "z": position.z }),
+    };
+
+    for event in dragging.read() {
+        if let Ok(entity) = synced.get(event.entity) {
+            buffer.push(event.entity, entity.entity_id, position(event.current_position), now);
+        }
+    }
+    for event in drag_ended.read() {
+        buffer.discard_entity(event.entity);
+    }
+}
+
+/// System that publishes buffered updates once their debounce window has
+/// passed, bumping the entity's version for each
+pub fn publish_buffered_updates(
+    time: Res<Time>,
+    config: Res<ComponentSyncConfig>,
+    mut buffer: ResMut<ComponentPublishBuffer>,
+    bridge: Res<NatsComponentBridge>,
+    mut synced: Query<&mut NatsSyncedEntity>,
+) {
+    for (entity, entity_id, component_data) in buffer.take_ready(time.elapsed(), config.debounce) {
+        let Ok(mut synced) = synced.get_mut(entity) else {
+            continue;
+        };
+        synced.version += 1;
+        bridge.publish(ComponentEvent::Updated { entity_id, component_data }, synced.version);
+    }
+}
+
+/// System that asks the domain to remove every selected node when their
+/// deletion is requested
+pub fn request_delete_commands(
+    mut delete_requested: EventReader<RequestDeleteSelected>,
+    selected: Query<&NodeVisual, With<Selected>>,
+    mut requests: EventWriter<RequestDomainCommand>,
+) {
+    for _ in delete_requested.read() {
+        for node in selected.iter() {
+            requests.write(RequestDomainCommand(VisualizationCommand::RemoveNode(RemoveNodeVisual {
+                node_id: node.node_id,
+            })));
         }
-        
-        // Remove the pending update marker
-        commands.entity(entity).remove::<PendingComponentUpdate>();
     }
 }
 
@@ -173,10 +505,21 @@ impl Plugin for NatsComponentPlugin {
         ).expect("Failed to create NATS component bridge");
         
         app.insert_resource(bridge)
+            .add_event::<SyncedComponentRemoved>()
+            .add_event::<ComponentConflict>()
+            .add_event::<NodeDragging>()
+            .add_event::<NodeDragEnd>()
+            .add_event::<RequestDeleteSelected>()
+            .add_event::<RequestDomainCommand>()
+            .init_resource::<ComponentSyncConfig>()
+            .init_resource::<ComponentPublishBuffer>()
             .add_systems(Update, (
                 process_nats_component_events,
                 apply_component_updates,
-            ).chain());
+                apply_component_removals,
+            ).chain())
+            .add_systems(Update, (buffer_drag_updates, publish_buffered_updates).chain())
+            .add_systems(Update, request_delete_commands);
     }
 }
 
@@ -208,7 +551,202 @@ mod tests {
     #[test]
     fn test_nats_synced_entity() {
         let entity_id = Uuid::new_v4();
-        let synced = NatsSyncedEntity { entity_id };
+        let synced = NatsSyncedEntity { entity_id, version: 0 };
         assert_eq!(synced.entity_id, entity_id);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_removed_event_wire_format() {
+        let entity_id = Uuid::new_v4();
+        let json = serde_json::json!({
+            "Removed": { "entity_id": entity_id, "component_type": "Label" }
+        });
+
+        match serde_json::from_value::<ComponentEvent>(json).unwrap() {
+            ComponentEvent::Removed { entity_id: id, component_type } => {
+                assert_eq!(id, entity_id);
+                assert_eq!(component_type, "Label");
+            }
+            other => panic!("expected a removal, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_apply_component_removals() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_event::<SyncedComponentRemoved>()
+            .add_systems(Update, apply_component_removals);
+
+        let entity_id = Uuid::new_v4();
+        let entity = app.world_mut().spawn((
+            NatsSyncedEntity { entity_id, version: 0 },
+            Name::new("A"),
+            Transform::from_xyz(1.0, 2.0, 3.0),
+            PendingComponentRemoval { component_types: vec!["Label".to_string()] },
+        )).id();
+        app.update();
+
+        let world = app.world();
+        assert!(world.get::<Name>(entity).is_none());
+        assert!(world.get::<Transform>(entity).is_some());
+        assert!(world.get::<PendingComponentRemoval>(entity).is_none());
+
+        let removed: Vec<&SyncedComponentRemoved> = world.resource::<Events<SyncedComponentRemoved>>()
+            .iter_current_update_events()
+            .collect();
+        assert_eq!(removed, vec![&SyncedComponentRemoved {
+            entity,
+            entity_id,
+            component_type: "Label".to_string(),
+        }]);
+    }
+
+    #[test]
+    fn test_stale_update_is_rejected() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_event::<ComponentConflict>()
+            .add_systems(Update, apply_component_updates);
+
+        let label = |text: &str, version| PendingComponentUpdate {
+            component_data: EcsComponentData {
+                component_type: "Label".to_string(),
+                data: serde_json::json!({ "text": text }),
+            },
+            version,
+        };
+        let entity_id = Uuid::new_v4();
+        let entity = app.world_mut()
+            .spawn((NatsSyncedEntity { entity_id, version: 0 }, PendingComponentUpdates { updates: vec![label("v3", 3)] }))
+            .id();
+        app.update();
+
+        app.world_mut().entity_mut(entity).insert(PendingComponentUpdates { updates: vec![label("v2", 2)] });
+        app.update();
+
+        let world = app.world();
+        assert_eq!(world.get::<Name>(entity).map(Name::as_str), Some("v3"));
+        assert_eq!(world.get::<NatsSyncedEntity>(entity).unwrap().version, 3);
+        assert!(world.get::<PendingComponentUpdates>(entity).is_none());
+
+        let conflicts: Vec<&ComponentConflict> = world.resource::<Events<ComponentConflict>>()
+            .iter_current_update_events()
+            .collect();
+        assert_eq!(conflicts, vec![&ComponentConflict {
+            entity,
+            entity_id,
+            component_type: "Label".to_string(),
+            rejected_version: 2,
+            current_version: 3,
+        }]);
+    }
+
+    #[test]
+    fn test_same_frame_updates_are_all_applied() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let _guard = runtime.enter();
+        let (incoming, event_receiver) = mpsc::unbounded_channel();
+        let (event_sender, _outgoing) = mpsc::unbounded_channel();
+        let bridge = NatsComponentBridge {
+            event_receiver,
+            event_sender,
+            _subscription_handle: tokio::spawn(async {}),
+            _publish_handle: tokio::spawn(async {}),
+        };
+
+        let mut app = App::new();
+        app.insert_resource(bridge)
+            .add_event::<ComponentConflict>()
+            .add_systems(Update, (process_nats_component_events, apply_component_updates).chain());
+
+        let entity_id = Uuid::new_v4();
+        let update = |component_type: &str, data: serde_json::Value| ComponentEvent::Updated {
+            entity_id,
+            component_data: EcsComponentData { component_type: component_type.to_string(), data },
+        };
+        // Two components of a new entity, then an update that is already
+        // outdated, all in one frame
+        incoming.send((update("Label", serde_json::json!({ "text": "A" })), None)).unwrap();
+        incoming.send((update("Position3D", serde_json::json!({ "x": 1.0, "y": 2.0, "z": 0.0 })), Some(5))).unwrap();
+        incoming.send((update("Label", serde_json::json!({ "text": "B" })), Some(4))).unwrap();
+        app.update();
+
+        let world = app.world_mut();
+        let (entity, synced) = world.query::<(Entity, &NatsSyncedEntity)>().single(world).unwrap();
+        assert_eq!(synced.version, 5);
+        assert_eq!(world.get::<Name>(entity).map(Name::as_str), Some("A"));
+        assert_eq!(world.get::<Transform>(entity).unwrap().translation, Vec3::new(1.0, 2.0, 0.0));
+
+        let conflicts: Vec<(u64, u64)> = world.resource::<Events<ComponentConflict>>()
+            .iter_current_update_events()
+            .map(|conflict| (conflict.rejected_version, conflict.current_version))
+            .collect();
+        assert_eq!(conflicts, vec![(4, 5)]);
+    }
+
+    #[test]
+    fn test_drag_updates_are_coalesced() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_event::<NodeDragging>()
+            .add_event::<NodeDragEnd>()
+            .init_resource::<ComponentPublishBuffer>()
+            .add_systems(Update, buffer_drag_updates);
+
+        let entity_id = Uuid::new_v4();
+        let entity = app.world_mut().spawn(NatsSyncedEntity { entity_id, version: 0 }).id();
+        let node_id = cim_contextgraph::NodeId::new();
+        for x in 0..5 {
+            app.world_mut().send_event(NodeDragging { entity, node_id, current_position: Vec3::X * x as f32 });
+        }
+        app.update();
+
+        let debounce = ComponentSyncConfig::default().debounce;
+        let mut buffer = app.world_mut().resource_mut::<ComponentPublishBuffer>();
+        assert_eq!(buffer.len(), 1);
+        assert!(buffer.take_ready(Duration::ZERO, debounce).is_empty());
+
+        // Only the latest position is published once the window has passed
+        let ready = buffer.take_ready(debounce, debounce);
+        assert_eq!(ready.len(), 1);
+        assert_eq!((ready[0].0, ready[0].1), (entity, entity_id));
+        assert_eq!(ready[0].2.data["x"], 4.0);
+        assert!(buffer.is_empty());
+
+        // Ending the drag drops what is pending; the command carries the
+        // final position
+        app.world_mut().send_event(NodeDragging { entity, node_id, current_position: Vec3::Y });
+        app.world_mut().send_event(NodeDragEnd { entity, node_id, final_position: Vec3::Y });
+        app.update();
+        assert!(app.world().resource::<ComponentPublishBuffer>().is_empty());
+    }
+
+    #[test]
+    fn test_delete_request_asks_to_remove_each_selected_node() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_event::<RequestDeleteSelected>()
+            .add_event::<RequestDomainCommand>()
+            .add_systems(Update, request_delete_commands);
+
+        let graph_id = cim_contextgraph::ContextGraphId::new();
+        let selected: Vec<cim_contextgraph::NodeId> = (0..2).map(|_| cim_contextgraph::NodeId::new()).collect();
+        for node_id in &selected {
+            app.world_mut().spawn((NodeVisual { node_id: *node_id, graph_id }, Selected));
+        }
+        app.world_mut().spawn(NodeVisual { node_id: cim_contextgraph::NodeId::new(), graph_id });
+        app.world_mut().send_event(RequestDeleteSelected);
+        app.update();
+
+        let removed: std::collections::HashSet<cim_contextgraph::NodeId> = app.world()
+            .resource::<Events<RequestDomainCommand>>()
+            .iter_current_update_events()
+            .map(|RequestDomainCommand(command)| match command {
+                VisualizationCommand::RemoveNode(remove) => remove.node_id,
+                other => panic!("unexpected command {other:?}"),
+            })
+            .collect();
+        assert_eq!(removed, selected.into_iter().collect());
+    }
+}
@@ -4,10 +4,18 @@
 //! NATS events in real-time, with statistics and advanced filtering options.
 
 use bevy::prelude::*;
-use bevy_egui::{egui, EguiContexts, EguiPlugin};
+use bevy_egui::{egui, EguiContexts, EguiPlugin, EguiPrimaryContextPass};
+use egui_plot::{Line, Plot, PlotPoints, Polygon};
+use std::borrow::Cow;
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
 use chrono::{DateTime, Utc, Duration};
+use regex::{Regex, RegexBuilder};
 use serde::{Deserialize, Serialize};
+use crate::culling::FilteredOut;
+use crate::nats_event_visualization::{
+    DomainEventReceived, EventStatistics, EventStore, EventVisual, RetentionPolicy, StatisticsSummary,
+};
 
 /// Plugin for NATS event filtering UI
 pub struct NatsEventFilterUIPlugin;
@@ -16,23 +24,36 @@ impl Plugin for NatsEventFilterUIPlugin {
     fn build(&self, app: &mut App) {
         // Only add EguiPlugin if not already added
         if !app.is_plugin_added::<EguiPlugin>() {
-            app.add_plugins(EguiPlugin);
+            app.add_plugins(EguiPlugin::default());
         }
         
-        app.insert_resource(EventFilterState::default())
-           .insert_resource(EventStatistics::default())
-           .insert_resource(FilterPresets::default())
-           .add_systems(Update, (
-               update_event_statistics,
+        let preset_storage = PresetStorage::default();
+        let presets = match FilterPresets::load_from(&preset_storage.path) {
+            Ok(presets) => presets,
+            Err(PresetError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => FilterPresets::new(),
+            Err(e) => {
+                warn!("Failed to load filter presets from {}: {}", preset_storage.path.display(), e);
+                FilterPresets::new()
+            }
+        };
+        
+        // Filters are applied by the visualization plugin, which also owns
+        // the filter state so commands can change it without this UI
+        app.init_resource::<EventFilterState>()
+           .init_resource::<SearchMatcher>()
+           .insert_resource(presets)
+           .insert_resource(preset_storage)
+           .insert_resource(ExportSettings::default())
+           .add_systems(EguiPrimaryContextPass, (
                render_filter_ui,
                render_statistics_panel,
-               apply_filters,
            ));
     }
 }
 
 /// State for event filtering
-#[derive(Resource, Default, Debug, Clone)]
+#[derive(Resource, Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct EventFilterState {
     /// Filter by domain
     pub domain_filters: HashSet<String>,
@@ -48,6 +69,9 @@ pub struct EventFilterState {
     
     /// Search query for event content
     pub search_query: String,
+
+    /// Whether the search query is a regular expression
+    pub search_is_regex: bool,
     
     /// Show only correlated events
     pub only_correlated: bool,
@@ -62,8 +86,199 @@ pub struct EventFilterState {
     pub active_preset: Option<String>,
 }
 
+impl EventFilterState {
+    /// Check if an event passes the current filters, compiling the search
+    /// query for just this check
+    pub fn matches(&self, event: &impl FilterableEvent) -> bool {
+        self.matches_with(event, &SearchMatcher::from_filter(self))
+    }
+
+    /// Check if an event passes the current filters, searching with an
+    /// already compiled `search`
+    pub fn matches_with(&self, event: &impl FilterableEvent, search: &SearchMatcher) -> bool {
+        if !self.domain_filters.is_empty() && !self.domain_filters.contains(event.domain()) {
+            return false;
+        }
+
+        if !self.event_type_filters.is_empty() && !self.event_type_filters.contains(event.event_type()) {
+            return false;
+        }
+
+        if !self.aggregate_type_filters.is_empty()
+            && !self.aggregate_type_filters.contains(event.aggregate_type())
+        {
+            return false;
+        }
+
+        if !self.time_range.is_in_range(event.timestamp()) {
+            return false;
+        }
+
+        if self.only_correlated && !event.is_correlated() {
+            return false;
+        }
+
+        if self.only_errors && !event.is_error() {
+            return false;
+        }
+
+        if !search.is_empty() && !search.is_match(&event.searchable_text()) {
+            return false;
+        }
+
+        true
+    }
+}
+
+/// An event the filters can be checked against, either as received or as
+/// its visual
+pub trait FilterableEvent {
+    fn domain(&self) -> &str;
+    fn event_type(&self) -> &str;
+    fn aggregate_type(&self) -> &str;
+    fn timestamp(&self) -> DateTime<Utc>;
+    fn is_correlated(&self) -> bool;
+    fn is_error(&self) -> bool;
+    /// Lowercased text the search query is matched against
+    fn searchable_text(&self) -> Cow<'_, str>;
+}
+
+impl FilterableEvent for DomainEventReceived {
+    fn domain(&self) -> &str {
+        &self.domain
+    }
+
+    fn event_type(&self) -> &str {
+        &self.event_type
+    }
+
+    fn aggregate_type(&self) -> &str {
+        &self.aggregate_type
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn is_correlated(&self) -> bool {
+        self.correlation_id.is_some()
+    }
+
+    fn is_error(&self) -> bool {
+        DomainEventReceived::is_error(self)
+    }
+
+    fn searchable_text(&self) -> Cow<'_, str> {
+        Cow::Owned(crate::nats_event_visualization::searchable_text(self))
+    }
+}
+
+impl FilterableEvent for EventVisual {
+    fn domain(&self) -> &str {
+        &self.domain
+    }
+
+    fn event_type(&self) -> &str {
+        &self.event_type
+    }
+
+    fn aggregate_type(&self) -> &str {
+        &self.aggregate_type
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn is_correlated(&self) -> bool {
+        self.correlation_id.is_some()
+    }
+
+    fn is_error(&self) -> bool {
+        self.is_error
+    }
+
+    fn searchable_text(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.searchable_text)
+    }
+}
+
+/// How a search query is matched
+#[derive(Debug, Clone, Default)]
+enum SearchPattern {
+    #[default]
+    Any,
+    /// Lowercased substring
+    Literal(String),
+    /// Case-insensitive regular expression
+    Regex(Regex),
+}
+
+/// The compiled search query of the [`EventFilterState`], kept as a resource
+/// so a regex is only rebuilt when the query changes. A regex that doesn't
+/// compile is searched for as a literal instead, with the error kept for the
+/// UI to show.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct SearchMatcher {
+    query: String,
+    is_regex: bool,
+    pattern: SearchPattern,
+    error: Option<String>,
+}
+
+impl SearchMatcher {
+    pub fn new(query: &str, is_regex: bool) -> Self {
+        let query = query.trim();
+        let literal = || SearchPattern::Literal(query.to_lowercase());
+        let (pattern, error) = if query.is_empty() {
+            (SearchPattern::Any, None)
+        } else if is_regex {
+            match RegexBuilder::new(query).case_insensitive(true).build() {
+                Ok(regex) => (SearchPattern::Regex(regex), None),
+                Err(e) => (literal(), Some(e.to_string())),
+            }
+        } else {
+            (literal(), None)
+        };
+        Self {
+            query: query.to_string(),
+            is_regex,
+            pattern,
+            error,
+        }
+    }
+
+    pub fn from_filter(filter_state: &EventFilterState) -> Self {
+        Self::new(&filter_state.search_query, filter_state.search_is_regex)
+    }
+
+    /// Whether this matcher was compiled from the filter's current query
+    pub fn is_current(&self, filter_state: &EventFilterState) -> bool {
+        self.query == filter_state.search_query.trim() && self.is_regex == filter_state.search_is_regex
+    }
+
+    /// Whether there is no query, so every event matches
+    pub fn is_empty(&self) -> bool {
+        matches!(self.pattern, SearchPattern::Any)
+    }
+
+    /// Whether an event's lowercased `searchable_text` matches the query
+    pub fn is_match(&self, searchable_text: &str) -> bool {
+        match &self.pattern {
+            SearchPattern::Any => true,
+            SearchPattern::Literal(query) => searchable_text.contains(query.as_str()),
+            SearchPattern::Regex(regex) => regex.is_match(searchable_text),
+        }
+    }
+
+    /// Why the regex didn't compile, when it didn't
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+}
+
 /// Time range for filtering
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum TimeRange {
     LastMinute,
     LastFiveMinutes,
@@ -100,104 +315,45 @@ impl TimeRange {
     }
 }
 
-/// Statistics about events
-#[derive(Resource, Default)]
-pub struct EventStatistics {
-    /// Total events received
-    pub total_events: u64,
-    
-    /// Events per domain
-    pub events_by_domain: HashMap<String, u64>,
-    
-    /// Events per type
-    pub events_by_type: HashMap<String, u64>,
-    
-    /// Events per aggregate type
-    pub events_by_aggregate: HashMap<String, u64>,
-    
-    /// Event rate history (events per second over time)
-    pub event_rate_history: VecDeque<(DateTime<Utc>, f32)>,
-    
-    /// Error count
-    pub error_count: u64,
-    
-    /// Average event size
-    pub avg_event_size: f32,
-    
-    /// Peak event rate
-    pub peak_event_rate: f32,
-    
-    /// Correlation chains
-    pub correlation_chains: HashMap<String, Vec<String>>,
-    
-    /// Last update time
-    pub last_update: DateTime<Utc>,
+/// Filter presets for common scenarios
+#[derive(Resource)]
+pub struct FilterPresets {
+    presets: HashMap<String, EventFilterState>,
+    /// Names of user-defined presets; only these are persisted
+    custom: HashSet<String>,
 }
 
-impl EventStatistics {
-    /// Update statistics with new event
-    pub fn update(&mut self, event: &super::nats_event_visualization::DomainEventReceived) {
-        self.total_events += 1;
-        
-        // Update domain count
-        *self.events_by_domain.entry(event.domain.clone()).or_insert(0) += 1;
-        
-        // Update event type count
-        *self.events_by_type.entry(event.event_type.clone()).or_insert(0) += 1;
-        
-        // Update aggregate type count
-        *self.events_by_aggregate.entry(event.aggregate_type.clone()).or_insert(0) += 1;
-        
-        // Update correlation chains
-        if let Some(correlation_id) = &event.correlation_id {
-            self.correlation_chains
-                .entry(correlation_id.clone())
-                .or_insert_with(Vec::new)
-                .push(event.event_id.clone());
-        }
-        
-        // Update event size (approximate from JSON)
-        let event_size = event.payload.to_string().len() as f32;
-        self.avg_event_size = (self.avg_event_size * (self.total_events - 1) as f32 + event_size) 
-                            / self.total_events as f32;
-        
-        self.last_update = Utc::now();
-    }
-    
-    /// Calculate current event rate
-    pub fn current_event_rate(&self) -> f32 {
-        if let Some((last_time, last_rate)) = self.event_rate_history.back() {
-            *last_rate
-        } else {
-            0.0
-        }
-    }
-    
-    /// Get top domains by event count
-    pub fn top_domains(&self, n: usize) -> Vec<(String, u64)> {
-        let mut domains: Vec<_> = self.events_by_domain.iter()
-            .map(|(k, v)| (k.clone(), *v))
-            .collect();
-        domains.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
-        domains.truncate(n);
-        domains
-    }
-    
-    /// Get top event types by count
-    pub fn top_event_types(&self, n: usize) -> Vec<(String, u64)> {
-        let mut types: Vec<_> = self.events_by_type.iter()
-            .map(|(k, v)| (k.clone(), *v))
-            .collect();
-        types.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
-        types.truncate(n);
-        types
+impl Default for FilterPresets {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-/// Filter presets for common scenarios
-#[derive(Resource, Default)]
-pub struct FilterPresets {
-    presets: HashMap<String, EventFilterState>,
+/// Errors that can occur while persisting filter presets
+#[derive(Debug, thiserror::Error)]
+pub enum PresetError {
+    #[error("invalid preset file: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("failed to access preset file: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Where custom presets are stored and the name typed into the save field
+#[derive(Resource, Debug, Clone)]
+pub struct PresetStorage {
+    /// File custom presets are saved to and loaded from
+    pub path: PathBuf,
+    /// Name entered in the "Save current as preset" field
+    pub pending_name: String,
+}
+
+impl Default for PresetStorage {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from("filter_presets.json"),
+            pending_name: String::new(),
+        }
+    }
 }
 
 impl FilterPresets {
@@ -224,7 +380,35 @@ impl FilterPresets {
         recent.time_range = TimeRange::LastMinute;
         presets.insert("Recent Activity".to_string(), recent);
         
-        Self { presets }
+        Self { presets, custom: HashSet::new() }
+    }
+    
+    /// Add or replace a user-defined preset
+    pub fn insert(&mut self, name: String, state: EventFilterState) {
+        self.custom.insert(name.clone());
+        self.presets.insert(name, state);
+    }
+    
+    /// Save the user-defined presets as JSON
+    pub fn save_to(&self, path: impl AsRef<Path>) -> Result<(), PresetError> {
+        let custom: HashMap<&String, &EventFilterState> = self.presets.iter()
+            .filter(|(name, _)| self.custom.contains(*name))
+            .collect();
+        let json = serde_json::to_string_pretty(&custom)?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+    
+    /// Load user-defined presets from JSON, merged over the built-in presets
+    pub fn load_from(path: impl AsRef<Path>) -> Result<Self, PresetError> {
+        let json = std::fs::read_to_string(path)?;
+        let custom: HashMap<String, EventFilterState> = serde_json::from_str(&json)?;
+        
+        let mut presets = Self::new();
+        for (name, state) in custom {
+            presets.insert(name, state);
+        }
+        Ok(presets)
     }
     
     pub fn get(&self, name: &str) -> Option<&EventFilterState> {
@@ -236,48 +420,142 @@ impl FilterPresets {
     }
 }
 
-/// Update event statistics
-fn update_event_statistics(
-    mut events: EventReader<super::nats_event_visualization::DomainEventReceived>,
-    mut stats: ResMut<EventStatistics>,
-    time: Res<Time>,
-) {
-    let mut event_count = 0;
-    
-    for event in events.read() {
-        stats.update(event);
-        event_count += 1;
+/// Output format for exported events
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+impl ExportFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+        }
     }
-    
-    // Update event rate
-    if event_count > 0 {
-        let current_time = Utc::now();
-        let rate = event_count as f32 / time.delta_secs();
-        
-        stats.event_rate_history.push_back((current_time, rate));
-        
-        // Keep only last 60 seconds of history
-        while stats.event_rate_history.len() > 60 {
-            stats.event_rate_history.pop_front();
+}
+
+/// Errors that can occur while exporting events
+#[derive(Debug, thiserror::Error)]
+pub enum ExportError {
+    #[error("failed to serialize events: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("failed to write export file: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Settings for the filter window's export button
+#[derive(Resource, Debug, Clone)]
+pub struct ExportSettings {
+    /// Directory export files are written to
+    pub output_dir: PathBuf,
+    /// Format used by the export button
+    pub format: ExportFormat,
+    /// Outcome of the last export, shown in the filter window
+    pub last_result: Option<String>,
+}
+
+impl Default for ExportSettings {
+    fn default() -> Self {
+        Self {
+            output_dir: PathBuf::from("exports"),
+            format: ExportFormat::Json,
+            last_result: None,
         }
-        
-        // Update peak rate
-        if rate > stats.peak_event_rate {
-            stats.peak_event_rate = rate;
+    }
+}
+
+/// Serialize events to pretty JSON or a flat CSV
+///
+/// CSV columns: event_id, timestamp, domain, event_type, aggregate_id, correlation_id
+pub fn export_events(events: &[DomainEventReceived], format: ExportFormat) -> Result<String, ExportError> {
+    match format {
+        ExportFormat::Json => {
+            let values: Vec<serde_json::Value> = events.iter()
+                .map(|event| serde_json::json!({
+                    "event_id": event.event_id,
+                    "timestamp": event.timestamp.to_rfc3339(),
+                    "domain": event.domain,
+                    "event_type": event.event_type,
+                    "aggregate_id": event.aggregate_id,
+                    "aggregate_type": event.aggregate_type,
+                    "correlation_id": event.correlation_id,
+                    "causation_id": event.causation_id,
+                    "payload": event.payload,
+                }))
+                .collect();
+            Ok(serde_json::to_string_pretty(&values)?)
+        }
+        ExportFormat::Csv => {
+            let mut csv = String::from("event_id,timestamp,domain,event_type,aggregate_id,correlation_id\n");
+            for event in events {
+                let row = [
+                    event.event_id.as_str(),
+                    &event.timestamp.to_rfc3339(),
+                    event.domain.as_str(),
+                    event.event_type.as_str(),
+                    event.aggregate_id.as_str(),
+                    event.correlation_id.as_deref().unwrap_or(""),
+                ]
+                .iter()
+                .map(|field| csv_field(field))
+                .collect::<Vec<_>>()
+                .join(",");
+                csv.push_str(&row);
+                csv.push('\n');
+            }
+            Ok(csv)
         }
     }
 }
 
+/// Quote a CSV field if it contains a separator, quote or newline
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Export events into a timestamped file inside `output_dir`
+pub fn write_export(
+    events: &[DomainEventReceived],
+    format: ExportFormat,
+    output_dir: &Path,
+) -> Result<PathBuf, ExportError> {
+    let contents = export_events(events, format)?;
+    std::fs::create_dir_all(output_dir)?;
+    let path = output_dir.join(format!(
+        "events-{}.{}",
+        Utc::now().format("%Y%m%d-%H%M%S"),
+        format.extension()
+    ));
+    std::fs::write(&path, contents)?;
+    Ok(path)
+}
+
 /// Render the filter UI
+#[allow(clippy::too_many_arguments)]
 fn render_filter_ui(
     mut contexts: EguiContexts,
     mut filter_state: ResMut<EventFilterState>,
-    presets: Res<FilterPresets>,
+    search: Res<SearchMatcher>,
+    mut presets: ResMut<FilterPresets>,
+    mut preset_storage: ResMut<PresetStorage>,
     stats: Res<EventStatistics>,
+    event_store: Res<EventStore>,
+    mut export_settings: ResMut<ExportSettings>,
 ) {
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
     egui::Window::new("Event Filters")
         .default_pos(egui::pos2(10.0, 100.0))
-        .show(contexts.ctx_mut(), |ui| {
+        .show(ctx, |ui| {
             // Preset selector
             ui.horizontal(|ui| {
                 ui.label("Preset:");
@@ -294,6 +572,24 @@ fn render_filter_ui(
                 }
             });
             
+            // Save the current filters as a named preset
+            ui.horizontal(|ui| {
+                ui.label("Save as:");
+                ui.text_edit_singleline(&mut preset_storage.pending_name);
+                let name = preset_storage.pending_name.trim().to_string();
+                if ui.add_enabled(!name.is_empty(), egui::Button::new("Save preset")).clicked() {
+                    let mut preset = filter_state.clone();
+                    preset.active_preset = None;
+                    presets.insert(name.clone(), preset);
+                    filter_state.active_preset = Some(name);
+                    preset_storage.pending_name.clear();
+                    
+                    if let Err(e) = presets.save_to(&preset_storage.path) {
+                        error!("Failed to save filter presets to {}: {}", preset_storage.path.display(), e);
+                    }
+                }
+            });
+            
             ui.separator();
             
             // Domain filters
@@ -338,6 +634,12 @@ fn render_filter_ui(
             ui.horizontal(|ui| {
                 ui.label("Search:");
                 ui.text_edit_singleline(&mut filter_state.search_query);
+                ui.checkbox(&mut filter_state.search_is_regex, ".*")
+                    .on_hover_text("Search with a regular expression");
+                if let Some(error) = search.error() {
+                    ui.colored_label(egui::Color32::RED, "invalid regex")
+                        .on_hover_text(format!("Searching as plain text instead:\n{error}"));
+                }
             });
             
             // Options
@@ -357,99 +659,152 @@ fn render_filter_ui(
                     filter_state.min_event_rate = None;
                 }
             });
+            
+            ui.separator();
+            
+            // Export the currently matching events
+            ui.horizontal(|ui| {
+                ui.label("Export:");
+                ui.selectable_value(&mut export_settings.format, ExportFormat::Json, "JSON");
+                ui.selectable_value(&mut export_settings.format, ExportFormat::Csv, "CSV");
+                if ui.button("Export").clicked() {
+                    let search = SearchMatcher::from_filter(&filter_state);
+                    let matching: Vec<DomainEventReceived> = event_store.get_all_events()
+                        .into_iter()
+                        .filter(|event| filter_state.matches_with(event, &search))
+                        .collect();
+                    let result = write_export(&matching, export_settings.format, &export_settings.output_dir);
+                    export_settings.last_result = Some(match result {
+                        Ok(path) => format!("Exported {} events to {}", matching.len(), path.display()),
+                        Err(e) => {
+                            error!("Event export failed: {}", e);
+                            format!("Export failed: {e}")
+                        }
+                    });
+                }
+            });
+            if let Some(result) = &export_settings.last_result {
+                ui.label(result);
+            }
+        });
+}
+
+/// Controls for the retention policy, which only touch the resource when a
+/// value actually changes
+fn retention_controls(ui: &mut egui::Ui, retention: &mut ResMut<RetentionPolicy>) {
+    let mut max_count = retention.max_count;
+    let mut max_age = retention.max_age.as_secs();
+    egui::Grid::new("stats_retention")
+        .num_columns(2)
+        .spacing([40.0, 4.0])
+        .show(ui, |ui| {
+            ui.label("Max Events:");
+            ui.add(egui::DragValue::new(&mut max_count).range(1..=100_000).speed(1.0));
+            ui.end_row();
+
+            ui.label("Max Age:");
+            ui.add(egui::DragValue::new(&mut max_age).range(1..=86_400).suffix(" s"));
+            ui.end_row();
         });
+
+    if max_count != retention.max_count {
+        retention.max_count = max_count;
+    }
+    if max_age != retention.max_age.as_secs() {
+        retention.max_age = std::time::Duration::from_secs(max_age);
+    }
 }
 
 /// Render the statistics panel
 fn render_statistics_panel(
     mut contexts: EguiContexts,
     stats: Res<EventStatistics>,
+    summary: Res<StatisticsSummary>,
+    retention: Option<ResMut<RetentionPolicy>>,
 ) {
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
     egui::Window::new("Event Statistics")
         .default_pos(egui::pos2(300.0, 100.0))
-        .show(contexts.ctx_mut(), |ui| {
+        .show(ctx, |ui| {
             ui.heading("Overview");
             
             egui::Grid::new("stats_overview")
                 .num_columns(2)
                 .spacing([40.0, 4.0])
                 .show(ui, |ui| {
-                    ui.label("Total Events:");
-                    ui.label(format!("{}", stats.total_events));
-                    ui.end_row();
-                    
-                    ui.label("Current Rate:");
-                    ui.label(format!("{:.1} events/s", stats.current_event_rate()));
-                    ui.end_row();
-                    
-                    ui.label("Peak Rate:");
-                    ui.label(format!("{:.1} events/s", stats.peak_event_rate));
-                    ui.end_row();
-                    
-                    ui.label("Error Count:");
-                    ui.label(format!("{}", stats.error_count));
-                    ui.end_row();
-                    
-                    ui.label("Avg Event Size:");
-                    ui.label(format!("{:.0} bytes", stats.avg_event_size));
-                    ui.end_row();
+                    for (label, value) in &summary.overview {
+                        ui.label(*label);
+                        ui.label(value);
+                        ui.end_row();
+                    }
                 });
             
+            if let Some(mut retention) = retention {
+                ui.separator();
+                ui.heading("Retention");
+                retention_controls(ui, &mut retention);
+            }
+
             ui.separator();
             
             // Top domains
             ui.heading("Top Domains");
-            for (domain, count) in stats.top_domains(5) {
-                ui.horizontal(|ui| {
-                    ui.label(format!("{}: ", domain));
-                    ui.label(format!("{} events", count));
-                    
-                    // Show percentage
-                    let percentage = (count as f32 / stats.total_events as f32) * 100.0;
-                    ui.label(format!("({:.1}%)", percentage));
-                });
+            for domain in &summary.top_domains {
+                ui.label(domain);
             }
             
             ui.separator();
             
             // Top event types
             ui.heading("Top Event Types");
-            for (event_type, count) in stats.top_event_types(5) {
-                ui.horizontal(|ui| {
-                    ui.label(format!("{}: ", event_type));
-                    ui.label(format!("{} events", count));
-                });
+            for event_type in &summary.top_event_types {
+                ui.label(event_type);
             }
             
             ui.separator();
             
-            // Event rate graph (simple text representation)
+            // Event rate graph
             ui.heading("Event Rate History");
             if !stats.event_rate_history.is_empty() {
-                // Simple sparkline using Unicode blocks
                 let max_rate = stats.event_rate_history.iter()
                     .map(|(_, rate)| *rate)
-                    .max_by(|a, b| a.partial_cmp(b).unwrap())
-                    .unwrap_or(1.0);
-                
-                let sparkline: String = stats.event_rate_history.iter()
-                    .map(|(_, rate)| {
-                        let normalized = rate / max_rate;
-                        match (normalized * 8.0) as u8 {
-                            0 => ' ',
-                            1 => '▁',
-                            2 => '▂',
-                            3 => '▃',
-                            4 => '▄',
-                            5 => '▅',
-                            6 => '▆',
-                            7 => '▇',
-                            _ => '█',
+                    .fold(0.0_f32, f32::max);
+
+                let max_points = ui.available_width().max(2.0) as usize;
+                let points = rate_plot_points(&stats.event_rate_history, Utc::now(), max_points);
+                let x_min = points.first().map(|[x, _]| *x).unwrap_or(0.0);
+                let x_max = points.last().map(|[x, _]| *x).unwrap_or(0.0);
+                let peak = max_rate as f64;
+
+                Plot::new("event_rate_plot")
+                    .height(120.0)
+                    .allow_drag(false)
+                    .allow_zoom(false)
+                    .allow_scroll(false)
+                    .include_y(0.0)
+                    .x_axis_label("Seconds ago")
+                    .y_axis_label("Events/s")
+                    .x_axis_formatter(|mark, _range| format!("{:.0}", -mark.value))
+                    .show(ui, |plot_ui| {
+                        if peak > 0.0 {
+                            let peak_band = vec![
+                                [x_min, peak * 0.9],
+                                [x_max, peak * 0.9],
+                                [x_max, peak],
+                                [x_min, peak],
+                            ];
+                            plot_ui.polygon(
+                                Polygon::new("Peak", PlotPoints::from(peak_band))
+                                    .fill_color(egui::Color32::from_rgba_unmultiplied(255, 120, 80, 40))
+                                    .width(0.0),
+                            );
                         }
-                    })
-                    .collect();
-                
-                ui.monospace(&sparkline);
+                        plot_ui.line(Line::new("Events/s", PlotPoints::from(points)));
+                    });
+
                 ui.label(format!("Max: {:.1} events/s", max_rate));
             }
             
@@ -460,50 +815,60 @@ fn render_statistics_panel(
             ui.label(format!("{} chains", stats.correlation_chains.len()));
             
             // Show largest chains
-            let mut chains: Vec<_> = stats.correlation_chains.iter()
-                .map(|(id, events)| (id, events.len()))
-                .collect();
-            chains.sort_by_key(|(_, len)| std::cmp::Reverse(*len));
-            
-            for (correlation_id, length) in chains.iter().take(3) {
-                ui.label(format!("{}: {} events", &correlation_id[..8], length));
+            for chain in &summary.largest_chains {
+                ui.label(chain);
             }
         });
 }
 
-/// Apply filters to events
-fn apply_filters(
+/// Convert the rate history into plot points of `[-seconds_ago, events/s]`,
+/// downsampled to at most `max_points`. Each bucket keeps its highest rate so
+/// that short spikes remain visible after downsampling.
+fn rate_plot_points(
+    history: &VecDeque<(DateTime<Utc>, f32)>,
+    now: DateTime<Utc>,
+    max_points: usize,
+) -> Vec<[f64; 2]> {
+    let max_points = max_points.max(1);
+    let bucket_size = history.len().div_ceil(max_points).max(1);
+
+    let samples: Vec<_> = history.iter().collect();
+    samples
+        .chunks(bucket_size)
+        .map(|bucket| {
+            let seconds_ago = bucket.iter()
+                .map(|(time, _)| (now - *time).num_milliseconds() as f64 / 1000.0)
+                .sum::<f64>() / bucket.len() as f64;
+            let rate = bucket.iter()
+                .map(|(_, rate)| *rate)
+                .fold(0.0_f32, f32::max);
+            [-seconds_ago, rate as f64]
+        })
+        .collect()
+}
+
+/// Recompile the [`SearchMatcher`] when the search query or its regex
+/// toggle changes
+pub(crate) fn update_search_matcher(filter_state: Res<EventFilterState>, mut search: ResMut<SearchMatcher>) {
+    if !search.is_current(&filter_state) {
+        *search = SearchMatcher::from_filter(&filter_state);
+    }
+}
+
+/// Apply filters to events by marking rejected ones as [`FilteredOut`]
+pub(crate) fn apply_filters(
+    mut commands: Commands,
     filter_state: Res<EventFilterState>,
-    mut visibility_query: Query<(&super::nats_event_visualization::EventVisual, &mut Visibility)>,
+    search: Res<SearchMatcher>,
+    events: Query<(Entity, &EventVisual, Has<FilteredOut>)>,
 ) {
-    for (event_visual, mut visibility) in visibility_query.iter_mut() {
-        let mut should_show = true;
-        
-        // Apply domain filter
-        if !filter_state.domain_filters.is_empty() {
-            should_show &= filter_state.domain_filters.contains(&event_visual.domain);
-        }
-        
-        // Apply event type filter
-        if !filter_state.event_type_filters.is_empty() {
-            should_show &= filter_state.event_type_filters.contains(&event_visual.event_type);
-        }
-        
-        // Apply time range filter
-        should_show &= filter_state.time_range.is_in_range(event_visual.timestamp);
-        
-        // Apply correlation filter
-        if filter_state.only_correlated {
-            should_show &= event_visual.correlation_id.is_some();
+    for (entity, event_visual, filtered_out) in events.iter() {
+        let should_show = filter_state.matches_with(event_visual, &search);
+        if should_show && filtered_out {
+            commands.entity(entity).remove::<FilteredOut>();
+        } else if !should_show && !filtered_out {
+            commands.entity(entity).insert(FilteredOut);
         }
-        
-        // TODO: Apply search query filter (would need access to full event data)
-        
-        *visibility = if should_show {
-            Visibility::Visible
-        } else {
-            Visibility::Hidden
-        };
     }
 }
 
@@ -544,4 +909,140 @@ mod tests {
         assert_eq!(stats.events_by_domain.get("Sales"), Some(&1));
         assert_eq!(stats.events_by_type.get("OrderPlaced"), Some(&1));
     }
-}
\ No newline at end of file
+    
+    fn export_test_event(event_id: &str, domain: &str) -> DomainEventReceived {
+        DomainEventReceived {
+            event_id: event_id.to_string(),
+            timestamp: Utc::now(),
+            domain: domain.to_string(),
+            event_type: "OrderPlaced".to_string(),
+            aggregate_id: "order,1".to_string(),
+            aggregate_type: "Order".to_string(),
+            correlation_id: Some("corr123".to_string()),
+            causation_id: None,
+            payload: serde_json::json!({"total": 42}),
+        }
+    }
+    
+    #[test]
+    fn test_export_csv() {
+        let events = vec![export_test_event("e1", "Sales")];
+        let csv = export_events(&events, ExportFormat::Csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        
+        assert_eq!(lines[0], "event_id,timestamp,domain,event_type,aggregate_id,correlation_id");
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].starts_with("e1,"));
+        // Fields containing the separator are quoted
+        assert!(lines[1].contains("\"order,1\""));
+        assert!(lines[1].ends_with(",corr123"));
+    }
+    
+    #[test]
+    fn test_export_json_round_trips() {
+        let events = vec![export_test_event("e1", "Sales"), export_test_event("e2", "Billing")];
+        let json = export_events(&events, ExportFormat::Json).unwrap();
+        let parsed: Vec<serde_json::Value> = serde_json::from_str(&json).unwrap();
+        
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[1]["domain"], "Billing");
+        assert_eq!(parsed[0]["payload"]["total"], 42);
+    }
+    
+    #[test]
+    fn test_custom_time_range_round_trips() {
+        let range = TimeRange::Custom {
+            start: Utc::now() - Duration::hours(2),
+            end: Utc::now(),
+        };
+        
+        let json = serde_json::to_string(&range).unwrap();
+        let restored: TimeRange = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, range);
+    }
+    
+    #[test]
+    fn test_presets_save_and_load_merge_with_builtins() {
+        let path = std::env::temp_dir().join(format!("presets-{}.json", uuid::Uuid::new_v4()));
+        
+        let mut custom = EventFilterState::default();
+        custom.domain_filters.insert("Sales".to_string());
+        custom.time_range = TimeRange::Custom {
+            start: Utc::now() - Duration::minutes(30),
+            end: Utc::now(),
+        };
+        
+        let mut presets = FilterPresets::new();
+        presets.insert("Sales Window".to_string(), custom.clone());
+        presets.save_to(&path).unwrap();
+        
+        let loaded = FilterPresets::load_from(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        
+        assert_eq!(loaded.get("Sales Window"), Some(&custom));
+        // Built-ins are still available alongside the custom preset
+        assert!(loaded.get("Errors Only").is_some());
+        assert_eq!(loaded.list().len(), FilterPresets::new().list().len() + 1);
+    }
+    
+    #[test]
+    fn test_filter_state_matches() {
+        let mut filters = EventFilterState::default();
+        filters.domain_filters.insert("Sales".to_string());
+        
+        assert!(filters.matches(&export_test_event("e1", "Sales")));
+        assert!(!filters.matches(&export_test_event("e2", "Billing")));
+        
+        filters.search_query = "TOTAL=42".to_string();
+        assert!(filters.matches(&export_test_event("e1", "Sales")));
+        filters.search_query = "missing".to_string();
+        assert!(!filters.matches(&export_test_event("e1", "Sales")));
+    }
+
+    #[test]
+    fn test_regex_search_matches_event_types() {
+        let event = |event_type: &str| DomainEventReceived {
+            event_type: event_type.to_string(),
+            ..export_test_event("e1", "Sales")
+        };
+        let mut filters = EventFilterState {
+            search_query: "Order(Placed|Cancelled)".to_string(),
+            search_is_regex: true,
+            ..default()
+        };
+        let search = SearchMatcher::from_filter(&filters);
+        assert!(search.error().is_none());
+        assert!(filters.matches_with(&event("OrderPlaced"), &search));
+        assert!(filters.matches_with(&event("OrderCancelled"), &search));
+        assert!(!filters.matches_with(&event("OrderShipped"), &search));
+
+        // An invalid regex is searched for literally
+        filters.search_query = "Order(".to_string();
+        let search = SearchMatcher::from_filter(&filters);
+        assert!(search.error().is_some());
+        assert!(!filters.matches_with(&event("OrderPlaced"), &search));
+        filters.search_is_regex = false;
+        assert!(!search.is_current(&filters));
+    }
+
+    #[test]
+    fn test_rate_plot_points_downsample_keeps_peak() {
+        let now = Utc::now();
+        let history: VecDeque<_> = (0..60)
+            .map(|i| {
+                let rate = if i == 17 { 50.0 } else { 1.0 };
+                (now - Duration::seconds(60 - i), rate)
+            })
+            .collect();
+
+        let points = rate_plot_points(&history, now, 10);
+        assert_eq!(points.len(), 10);
+        assert!(points.iter().all(|[x, _]| *x <= 0.0));
+        assert!(points.windows(2).all(|w| w[0][0] < w[1][0]));
+        assert_eq!(points.iter().filter(|[_, y]| *y == 50.0).count(), 1);
+
+        let full = rate_plot_points(&history, now, 200);
+        assert_eq!(full.len(), 60);
+        assert_eq!(full[0], [-60.0, 1.0]);
+    }
+}
@@ -6,85 +6,217 @@
 //! - Causation chains
 //! - Event correlation
 //! - Real-time event monitoring
+//!
+//! Events are placed by a force-directed layout, except for the correlation
+//! shown with `ShowCorrelation`: its events are laid out as a causation
+//! tree, root cause at the top and effects below, while the rest are dimmed.
 
 use bevy::prelude::*;
 use bevy::render::mesh::{Mesh, Meshable};
 use async_nats::Client;
 use futures::StreamExt;
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use parking_lot::RwLock;
+use bevy::render::primitives::Aabb;
+use crate::bridge::{report_bridge_status, BridgeError, BridgeStatus, BridgeStatusChanged};
+use crate::camera::CameraAnimationPlugin;
+use crate::components::{GraphCamera, Selected};
+use crate::culling::CullingPlugin;
+use crate::edge_filter::EdgeFilter;
+use crate::events::FocusCamera;
+use crate::event_alerts::{AlertRules, AlertTriggered};
+use crate::layout::assign_layers;
+use crate::lod::{LodLabel, LodMeshes, LodPlugin, LodSphere};
+use crate::nats_event_filter_ui::{apply_filters, update_search_matcher, EventFilterState, SearchMatcher};
+use crate::picking::{cursor_position, nearest_node_hit, screen_ray};
 
 /// Plugin for NATS event visualization
 pub struct NatsEventVisualizationPlugin {
     /// NATS client for subscribing to events
     pub nats_client: Arc<Client>,
-    /// Maximum number of events to visualize at once
-    pub max_events: usize,
-    /// Event retention duration (seconds)
-    pub retention_seconds: u64,
+    /// How many events are kept, and for how long
+    pub retention: RetentionPolicy,
+    /// Domain colors added to, or replacing, the built-in palette
+    pub domain_colors: HashMap<String, Color>,
 }
 
 impl Default for NatsEventVisualizationPlugin {
     fn default() -> Self {
         Self {
             nats_client: Arc::new(Client::new()), // This would need to be properly initialized
-            max_events: 100,
-            retention_seconds: 300, // 5 minutes
+            retention: RetentionPolicy::default(),
+            domain_colors: HashMap::new(),
         }
     }
 }
 
 impl Plugin for NatsEventVisualizationPlugin {
     fn build(&self, app: &mut App) {
-        // Resources
-        app.insert_resource(EventVisualizationConfig {
-            max_events: self.max_events,
-            retention_seconds: self.retention_seconds,
-        })
-        .insert_resource(EventStore::new(self.max_events))
-        .insert_resource(EventFlowGraph::new())
-        .insert_resource(DomainColors::default());
-
-        // Events
-        app.add_event::<DomainEventReceived>()
-           .add_event::<EventVisualizationCommand>();
-
-        // Systems
-        app.add_systems(Startup, setup_event_visualization)
-           .add_systems(Update, (
-               process_incoming_events,
-               update_event_positions,
-               create_event_visuals,
-               update_event_connections,
-               handle_event_interactions,
-               cleanup_old_events,
-           ).chain());
-
-        // Start NATS subscription
-        let nats_client = self.nats_client.clone();
-        let (tx, rx) = mpsc::channel(1000);
-        
-        app.insert_resource(EventReceiver(Arc::new(RwLock::new(rx))));
-        
+        let tx = add_event_visualization(app, self.retention, &self.domain_colors);
+
+        // Disconnected until the subscription task reports otherwise
+        let (status_tx, status_rx) = mpsc::unbounded_channel();
+        app.insert_resource(BridgeStatus::Disconnected)
+            .insert_resource(BridgeStatusReceiver(Arc::new(RwLock::new(status_rx))));
+
         // Spawn async task to subscribe to NATS events
         let runtime = tokio::runtime::Handle::current();
-        runtime.spawn(subscribe_to_domain_events(nats_client, tx));
+        runtime.spawn(subscribe_to_domain_events(self.nats_client.clone(), tx, status_tx));
     }
 }
 
-/// Configuration for event visualization
-#[derive(Resource)]
-struct EventVisualizationConfig {
-    max_events: usize,
-    retention_seconds: u64,
+/// Plugin running the event visualization on events fed in through
+/// [`EventFeed`] instead of a NATS subscription, so it can be driven in
+/// tests and headless tools without a server
+#[derive(Default)]
+pub struct MockEventSource {
+    /// How many events are kept, and for how long
+    pub retention: RetentionPolicy,
+}
+
+impl Plugin for MockEventSource {
+    fn build(&self, app: &mut App) {
+        let tx = add_event_visualization(app, self.retention, &HashMap::new());
+        app.insert_resource(EventFeed(tx))
+            .insert_resource(BridgeStatus::Connected);
+    }
+}
+
+/// Sender into the channel `process_incoming_events` reads, in place of
+/// the NATS subscription. Removing it disconnects the feed.
+#[derive(Resource, Clone)]
+pub struct EventFeed(mpsc::Sender<DomainEventReceived>);
+
+impl EventFeed {
+    /// Queue `event` for the next frames
+    pub fn send(&self, event: DomainEventReceived) -> Result<(), BridgeError> {
+        self.0.try_send(event).map_err(|e| match e {
+            mpsc::error::TrySendError::Full(_) => BridgeError::ChannelFull,
+            mpsc::error::TrySendError::Closed(_) => BridgeError::ChannelDisconnected,
+        })
+    }
+}
+
+/// Queue `events` on the [`EventFeed`] of an app using [`MockEventSource`].
+/// They are visualized by the following updates, at most
+/// `CATCH_UP_EVENTS_PER_FRAME` per frame.
+pub fn feed_events(app: &mut App, events: Vec<DomainEventReceived>) {
+    let feed = app.world().resource::<EventFeed>().clone();
+    for event in events {
+        if let Err(e) = feed.send(event) {
+            warn!("Failed to feed event: {:?}", e);
+        }
+    }
+}
+
+/// Add the event visualization resources and systems, returning the
+/// sender of the channel the events are read from
+fn add_event_visualization(
+    app: &mut App,
+    retention: RetentionPolicy,
+    domain_colors: &HashMap<String, Color>,
+) -> mpsc::Sender<DomainEventReceived> {
+    // Resources
+    app.insert_resource(retention)
+    .insert_resource(EventStore::new())
+    .insert_resource(EventStatistics::default())
+    .init_resource::<StatisticsConfig>()
+    .init_resource::<StatisticsSummary>()
+    .insert_resource(EventFlowGraph::new())
+    .insert_resource(DomainColors::with_overrides(domain_colors.clone()))
+    .init_resource::<ColorMode>()
+    .init_resource::<ActiveCorrelation>()
+    .init_resource::<EventDecay>()
+    .init_resource::<ConnectionVisibility>()
+    .init_resource::<ConnectionPulse>()
+    .init_resource::<EventFilterState>()
+    .init_resource::<SearchMatcher>()
+    .init_resource::<AlertRules>()
+    .init_resource::<Paused>();
+
+    if !app.is_plugin_added::<LodPlugin>() {
+        app.add_plugins(LodPlugin);
+    }
+    if !app.is_plugin_added::<CameraAnimationPlugin>() {
+        app.add_plugins(CameraAnimationPlugin);
+    }
+    // Filtered events are hidden through the shared hidden-reason markers
+    if !app.is_plugin_added::<CullingPlugin>() {
+        app.add_plugins(CullingPlugin);
+    }
+
+    // Events
+    app.add_event::<DomainEventReceived>()
+       .add_event::<EventVisualizationCommand>()
+       .add_event::<EventEvicted>()
+       .add_event::<AlertTriggered>()
+       .add_event::<BridgeStatusChanged>();
+
+    // Systems
+    app.add_systems(Startup, setup_event_visualization)
+       .add_systems(Update, (
+           receive_bridge_status,
+           process_incoming_events,
+           enforce_retention,
+           update_event_statistics,
+           handle_event_commands,
+           update_event_positions,
+           layout_correlation_chain,
+           create_event_visuals,
+           recolor_events,
+           decay_events,
+           update_event_connections,
+           handle_event_interactions,
+           despawn_evicted_events,
+       ).chain())
+       .add_systems(Update, (update_search_matcher, apply_filters).chain().after(handle_event_commands));
+
+    let (tx, rx) = mpsc::channel(1000);
+    app.insert_resource(EventReceiver(Arc::new(RwLock::new(rx))));
+    tx
+}
+
+/// How many received events are kept, and for how long. Events beyond
+/// either limit are evicted from the [`EventStore`] by `enforce_retention`,
+/// which announces each one with an [`EventEvicted`]. The policy is checked
+/// every frame, so changing the resource at runtime applies straight away.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct RetentionPolicy {
+    /// Most events kept; the oldest arrivals go first
+    pub max_count: usize,
+    /// Longest an event is kept after its timestamp
+    pub max_age: Duration,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            max_count: 100,
+            max_age: Duration::from_secs(300), // 5 minutes
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// Whether an event with `timestamp` is past `max_age` at `now`
+    pub fn is_expired(&self, timestamp: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        (now - timestamp).to_std().is_ok_and(|age| age > self.max_age)
+    }
+}
+
+/// An event was removed from the [`EventStore`] by the [`RetentionPolicy`]
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct EventEvicted {
+    pub event_id: String,
 }
 
 /// Domain event that was received from NATS
-#[derive(Event, Debug, Clone)]
+#[derive(Event, Debug, Clone, PartialEq)]
 pub struct DomainEventReceived {
     pub event_id: String,
     pub timestamp: DateTime<Utc>,
@@ -97,6 +229,18 @@ pub struct DomainEventReceived {
     pub payload: serde_json::Value,
 }
 
+impl DomainEventReceived {
+    /// Event type suffixes that mark an event as reporting a failure
+    const ERROR_SUFFIXES: [&'static str; 3] = ["Failed", "Rejected", "Error"];
+
+    /// Whether the event reports a failure: its type ends in `Failed`,
+    /// `Rejected` or `Error`, or its payload has an `error` field
+    pub fn is_error(&self) -> bool {
+        Self::ERROR_SUFFIXES.iter().any(|suffix| self.event_type.ends_with(suffix))
+            || self.payload.get("error").is_some_and(|error| !error.is_null())
+    }
+}
+
 /// Commands for controlling event visualization
 #[derive(Event, Debug)]
 pub enum EventVisualizationCommand {
@@ -106,33 +250,49 @@ pub enum EventVisualizationCommand {
     ShowCorrelation(String),
     /// Filter events by domain
     FilterByDomain(String),
-    /// Clear all filters
+    /// Clear all filters: the event filters, the active correlation, the
+    /// hidden connection types and the graph's edge filter
     ClearFilters,
     /// Pause/resume event processing
     TogglePause,
 }
 
-/// Store for received events
-#[derive(Resource)]
+/// Store for received events, in arrival order
+#[derive(Resource, Default)]
 pub struct EventStore {
     events: Arc<RwLock<VecDeque<DomainEventReceived>>>,
-    max_events: usize,
 }
 
 impl EventStore {
-    pub fn new(max_events: usize) -> Self {
-        Self {
-            events: Arc::new(RwLock::new(VecDeque::with_capacity(max_events))),
-            max_events,
-        }
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn add_event(&self, event: DomainEventReceived) {
+        self.events.write().push_back(event);
     }
 
-    fn add_event(&self, event: DomainEventReceived) {
+    /// Remove the events `policy` no longer keeps at `now`, returning their
+    /// IDs: expired ones wherever they are, then the oldest arrivals until
+    /// at most `max_count` remain
+    pub fn evict(&self, policy: &RetentionPolicy, now: DateTime<Utc>) -> Vec<String> {
         let mut events = self.events.write();
-        if events.len() >= self.max_events {
-            events.pop_front();
-        }
-        events.push_back(event);
+        let mut evicted = Vec::new();
+        events.retain(|event| {
+            let expired = policy.is_expired(event.timestamp, now);
+            if expired {
+                evicted.push(event.event_id.clone());
+            }
+            !expired
+        });
+        let excess = events.len().saturating_sub(policy.max_count);
+        evicted.extend(events.drain(..excess).map(|event| event.event_id));
+        evicted
+    }
+
+    /// The stored event with `event_id`, if it is still kept
+    pub fn get(&self, event_id: &str) -> Option<DomainEventReceived> {
+        self.events.read().iter().find(|e| e.event_id == event_id).cloned()
     }
 
     pub fn get_all_events(&self) -> Vec<DomainEventReceived> {
@@ -147,15 +307,367 @@ impl EventStore {
             .cloned()
             .collect()
     }
+
+    /// Get all events sharing a correlation ID, in arrival order
+    pub fn get_by_correlation(&self, correlation_id: &str) -> Vec<DomainEventReceived> {
+        self.events.read()
+            .iter()
+            .filter(|e| e.correlation_id.as_deref() == Some(correlation_id))
+            .cloned()
+            .collect()
+    }
+
+    /// Reconstruct the causation chain an event belongs to, root cause first.
+    ///
+    /// Walks `causation_id` links upwards to the root, then follows the
+    /// events caused by it downwards. Links to events no longer in the store
+    /// end the walk.
+    pub fn get_causation_chain(&self, event_id: &str) -> Vec<DomainEventReceived> {
+        let events = self.events.read();
+        let by_id: HashMap<&str, &DomainEventReceived> = events.iter()
+            .map(|e| (e.event_id.as_str(), e))
+            .collect();
+
+        let Some(start) = by_id.get(event_id) else {
+            return Vec::new();
+        };
+
+        // Walk up to the root cause
+        let mut visited: HashSet<&str> = HashSet::new();
+        let mut ancestors = vec![*start];
+        visited.insert(start.event_id.as_str());
+        let mut current = *start;
+        while let Some(parent) = current.causation_id.as_deref().and_then(|id| by_id.get(id)) {
+            if !visited.insert(parent.event_id.as_str()) {
+                break;
+            }
+            ancestors.push(*parent);
+            current = *parent;
+        }
+        ancestors.reverse();
+
+        // Walk down through the events caused by the starting event
+        let mut chain = ancestors;
+        let mut frontier = vec![start.event_id.as_str()];
+        while !frontier.is_empty() {
+            let mut next = Vec::new();
+            for event in events.iter() {
+                if let Some(cause) = event.causation_id.as_deref() {
+                    if frontier.contains(&cause) && visited.insert(event.event_id.as_str()) {
+                        chain.push(event);
+                        next.push(event.event_id.as_str());
+                    }
+                }
+            }
+            frontier = next;
+        }
+
+        chain.into_iter().cloned().collect()
+    }
+}
+
+/// Statistics about the event stream, shared by the event UI plugins
+#[derive(Resource, Debug)]
+pub struct EventStatistics {
+    /// Total events received
+    pub total_events: u64,
+    /// Events per domain
+    pub events_by_domain: HashMap<String, u64>,
+    /// Events per type
+    pub events_by_type: HashMap<String, u64>,
+    /// Events per aggregate type
+    pub events_by_aggregate: HashMap<String, u64>,
+    /// Event rate history (events per second over time)
+    pub event_rate_history: VecDeque<(DateTime<Utc>, f32)>,
+    /// Average events per second over the last minute
+    pub events_per_second: f32,
+    /// Peak event rate
+    pub peak_event_rate: f32,
+    /// Error count
+    pub error_count: u64,
+    /// Average event size
+    pub avg_event_size: f32,
+    /// Latest event IDs grouped by correlation ID, for the most recently
+    /// started correlations only
+    pub correlation_chains: HashMap<String, Vec<String>>,
+    /// Number of distinct causation IDs seen; only the most recent are
+    /// remembered, so one seen again after it was forgotten counts again
+    pub causation_chains: u32,
+    /// Busiest domain
+    pub busiest_domain: Option<(String, u64)>,
+    /// Most common event type
+    pub most_common_event: Option<(String, u64)>,
+    /// Last update time
+    pub last_update: DateTime<Utc>,
+    causation_ids: HashSet<String>,
+    recent_counts: VecDeque<(DateTime<Utc>, u64)>,
+    /// Keys of `correlation_chains`, oldest first
+    correlation_order: VecDeque<String>,
+    /// Entries of `causation_ids`, oldest first
+    causation_order: VecDeque<String>,
+}
+
+impl Default for EventStatistics {
+    fn default() -> Self {
+        Self {
+            total_events: 0,
+            events_by_domain: HashMap::new(),
+            events_by_type: HashMap::new(),
+            events_by_aggregate: HashMap::new(),
+            event_rate_history: VecDeque::new(),
+            events_per_second: 0.0,
+            peak_event_rate: 0.0,
+            error_count: 0,
+            avg_event_size: 0.0,
+            correlation_chains: HashMap::new(),
+            causation_chains: 0,
+            busiest_domain: None,
+            most_common_event: None,
+            last_update: DateTime::<Utc>::MIN_UTC,
+            causation_ids: HashSet::new(),
+            recent_counts: VecDeque::new(),
+            correlation_order: VecDeque::new(),
+            causation_order: VecDeque::new(),
+        }
+    }
+}
+
+impl EventStatistics {
+    /// Number of rate samples kept in `event_rate_history`
+    const RATE_HISTORY_LEN: usize = 60;
+    /// Number of correlations kept in `correlation_chains`; the oldest is
+    /// dropped when a new one starts
+    const MAX_CORRELATION_CHAINS: usize = 500;
+    /// Number of event IDs kept per correlation chain
+    const MAX_CHAIN_LEN: usize = 100;
+    /// Number of causation IDs remembered for `causation_chains`; the
+    /// oldest is forgotten when a new one is seen
+    const MAX_CAUSATION_IDS: usize = 5000;
+
+    /// Update statistics with new event
+    pub fn update(&mut self, event: &DomainEventReceived) {
+        self.total_events += 1;
+
+        let domain_count = self.events_by_domain.entry(event.domain.clone()).or_insert(0);
+        *domain_count += 1;
+        if self.busiest_domain.as_ref().is_none_or(|(_, count)| *domain_count > *count) {
+            self.busiest_domain = Some((event.domain.clone(), *domain_count));
+        }
+
+        let type_count = self.events_by_type.entry(event.event_type.clone()).or_insert(0);
+        *type_count += 1;
+        if self.most_common_event.as_ref().is_none_or(|(_, count)| *type_count > *count) {
+            self.most_common_event = Some((event.event_type.clone(), *type_count));
+        }
+
+        *self.events_by_aggregate.entry(event.aggregate_type.clone()).or_insert(0) += 1;
+
+        if event.is_error() {
+            self.error_count += 1;
+        }
+
+        if let Some(correlation_id) = &event.correlation_id {
+            if !self.correlation_chains.contains_key(correlation_id) {
+                self.correlation_order.push_back(correlation_id.clone());
+                while self.correlation_order.len() > Self::MAX_CORRELATION_CHAINS {
+                    if let Some(oldest) = self.correlation_order.pop_front() {
+                        self.correlation_chains.remove(&oldest);
+                    }
+                }
+            }
+            let chain = self.correlation_chains.entry(correlation_id.clone()).or_default();
+            chain.push(event.event_id.clone());
+            if chain.len() > Self::MAX_CHAIN_LEN {
+                chain.remove(0);
+            }
+        }
+
+        if let Some(causation_id) = &event.causation_id {
+            if self.causation_ids.insert(causation_id.clone()) {
+                self.causation_chains += 1;
+                self.causation_order.push_back(causation_id.clone());
+                while self.causation_order.len() > Self::MAX_CAUSATION_IDS {
+                    if let Some(oldest) = self.causation_order.pop_front() {
+                        self.causation_ids.remove(&oldest);
+                    }
+                }
+            }
+        }
+
+        // Update event size (approximate from JSON)
+        let event_size = event.payload.to_string().len() as f32;
+        self.avg_event_size = (self.avg_event_size * (self.total_events - 1) as f32 + event_size)
+                            / self.total_events as f32;
+
+        self.last_update = Utc::now();
+    }
+
+    /// Record the number of events seen in a frame of `delta_secs` length
+    pub fn record_frame(&mut self, now: DateTime<Utc>, event_count: u64, delta_secs: f32) {
+        if event_count > 0 {
+            let rate = event_count as f32 / delta_secs.max(f32::EPSILON);
+            self.event_rate_history.push_back((now, rate));
+            while self.event_rate_history.len() > Self::RATE_HISTORY_LEN {
+                self.event_rate_history.pop_front();
+            }
+            if rate > self.peak_event_rate {
+                self.peak_event_rate = rate;
+            }
+            self.recent_counts.push_back((now, event_count));
+        }
+
+        let one_minute_ago = now - chrono::Duration::seconds(60);
+        while self.recent_counts.front().is_some_and(|(time, _)| *time <= one_minute_ago) {
+            self.recent_counts.pop_front();
+        }
+        let recent: u64 = self.recent_counts.iter().map(|(_, count)| count).sum();
+        self.events_per_second = recent as f32 / 60.0;
+    }
+
+    /// Number of distinct correlation groups seen
+    pub fn correlation_groups(&self) -> usize {
+        self.correlation_chains.len()
+    }
+
+    /// Calculate current event rate
+    pub fn current_event_rate(&self) -> f32 {
+        self.event_rate_history.back()
+            .map(|(_, rate)| *rate)
+            .unwrap_or(0.0)
+    }
+
+    /// Get top domains by event count
+    pub fn top_domains(&self, n: usize) -> Vec<(String, u64)> {
+        let mut domains: Vec<_> = self.events_by_domain.iter()
+            .map(|(k, v)| (k.clone(), *v))
+            .collect();
+        domains.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        domains.truncate(n);
+        domains
+    }
+
+    /// Get top event types by count
+    pub fn top_event_types(&self, n: usize) -> Vec<(String, u64)> {
+        let mut types: Vec<_> = self.events_by_type.iter()
+            .map(|(k, v)| (k.clone(), *v))
+            .collect();
+        types.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        types.truncate(n);
+        types
+    }
+}
+
+/// How often the aggregates derived from [`EventStatistics`] are refreshed
+#[derive(Resource, Debug, Clone)]
+pub struct StatisticsConfig {
+    /// Seconds between recomputes of the rates, top lists and panel text.
+    /// Event counters are still updated every frame.
+    pub refresh_interval: f32,
+}
+
+impl Default for StatisticsConfig {
+    fn default() -> Self {
+        Self { refresh_interval: 1.0 }
+    }
+}
+
+/// Aggregates and panel text derived from [`EventStatistics`], recomputed
+/// every [`StatisticsConfig::refresh_interval`] seconds so the panels don't
+/// sort and format on every frame
+#[derive(Resource, Debug, Clone, Default)]
+pub struct StatisticsSummary {
+    /// Overview rows of label and formatted value
+    pub overview: Vec<(&'static str, String)>,
+    /// Busiest domains, formatted with their count and share
+    pub top_domains: Vec<String>,
+    /// Most common event types, formatted with their count
+    pub top_event_types: Vec<String>,
+    /// Largest correlation chains, formatted with their length
+    pub largest_chains: Vec<String>,
+    /// Text of the statistics display
+    pub text: String,
+    /// Number of recomputes so far
+    pub recomputes: u64,
+    /// Events counted since the last recompute
+    pending_events: u64,
+    /// Seconds since the last recompute; `None` until the first one
+    since_refresh: Option<f32>,
+}
+
+impl StatisticsSummary {
+    /// Number of entries in each top list
+    const TOP_LEN: usize = 5;
+    /// Number of correlation chains listed
+    const CHAINS_LEN: usize = 3;
+
+    /// Recompute the aggregates and text from `stats`
+    pub fn refresh(&mut self, stats: &EventStatistics) {
+        self.overview = vec![
+            ("Total Events:", stats.total_events.to_string()),
+            ("Current Rate:", format!("{:.1} events/s", stats.current_event_rate())),
+            ("Peak Rate:", format!("{:.1} events/s", stats.peak_event_rate)),
+            ("Error Count:", stats.error_count.to_string()),
+            ("Avg Event Size:", format!("{:.0} bytes", stats.avg_event_size)),
+        ];
+        self.top_domains = stats.top_domains(Self::TOP_LEN)
+            .into_iter()
+            .map(|(domain, count)| {
+                let percentage = count as f32 / stats.total_events as f32 * 100.0;
+                format!("{domain}: {count} events ({percentage:.1}%)")
+            })
+            .collect();
+        self.top_event_types = stats.top_event_types(Self::TOP_LEN)
+            .into_iter()
+            .map(|(event_type, count)| format!("{event_type}: {count} events"))
+            .collect();
+
+        let mut chains: Vec<_> = stats.correlation_chains.iter()
+            .map(|(id, events)| (id, events.len()))
+            .collect();
+        chains.sort_by_key(|(_, len)| std::cmp::Reverse(*len));
+        self.largest_chains = chains.into_iter()
+            .take(Self::CHAINS_LEN)
+            .map(|(id, len)| format!("{}: {len} events", id.get(..8).unwrap_or(id)))
+            .collect();
+
+        self.text = format!(
+            "Total Events: {}\n\
+             Events/sec: {:.1}\n\
+             Causation Chains: {}\n\
+             Correlation Groups: {}\n\
+             \n\
+             Busiest Domain:\n  {} ({})\n\
+             \n\
+             Most Common Event:\n  {} ({})\n\
+             \n\
+             Domains: {}\n\
+             Event Types: {}",
+            stats.total_events,
+            stats.events_per_second,
+            stats.causation_chains,
+            stats.correlation_groups(),
+            stats.busiest_domain.as_ref().map(|(d, _)| d.as_str()).unwrap_or("N/A"),
+            stats.busiest_domain.as_ref().map(|(_, c)| c).unwrap_or(&0),
+            stats.most_common_event.as_ref().map(|(e, _)| e.as_str()).unwrap_or("N/A"),
+            stats.most_common_event.as_ref().map(|(_, c)| c).unwrap_or(&0),
+            stats.events_by_domain.len(),
+            stats.events_by_type.len(),
+        );
+        self.recomputes += 1;
+    }
 }
 
 /// Graph structure for event relationships
 #[derive(Resource, Default)]
 struct EventFlowGraph {
-    /// Adjacency list of event relationships
-    edges: HashMap<String, Vec<String>>,
+    /// Adjacency list of event relationships, with the kind of each
+    edges: HashMap<String, Vec<(String, ConnectionType)>>,
     /// Node positions for force-directed layout
     positions: HashMap<String, Vec3>,
+    /// Latest event of each correlation, linked to the next one
+    latest_by_correlation: HashMap<String, String>,
+    /// Latest event received, linked to the next one
+    latest_event: Option<String>,
 }
 
 impl EventFlowGraph {
@@ -163,19 +675,96 @@ impl EventFlowGraph {
         Self::default()
     }
 
-    fn add_edge(&mut self, from: String, to: String) {
-        self.edges.entry(from).or_default().push(to);
+    /// Add an edge unless the two events are already connected, so each
+    /// pair is drawn once with its strongest relationship
+    fn add_edge(&mut self, from: String, to: String, connection_type: ConnectionType) {
+        if self.edges.get(&from).is_some_and(|to_ids| to_ids.iter().any(|(id, _)| *id == to)) {
+            return;
+        }
+        self.edges.entry(from).or_default().push((to, connection_type));
+    }
+
+    /// Connect a newly received event to its cause, to the previous event
+    /// of its correlation and to the event received just before it
+    fn link_event(&mut self, event: &DomainEventReceived) {
+        // An event cannot cause itself
+        if let Some(causation_id) = event.causation_id.as_ref().filter(|cause| **cause != event.event_id) {
+            self.add_edge(causation_id.clone(), event.event_id.clone(), ConnectionType::Causation);
+        }
+        if let Some(correlation_id) = &event.correlation_id {
+            if let Some(previous) = self.latest_by_correlation.insert(correlation_id.clone(), event.event_id.clone()) {
+                self.add_edge(previous, event.event_id.clone(), ConnectionType::Correlation);
+            }
+        }
+        if let Some(previous) = self.latest_event.replace(event.event_id.clone()) {
+            self.add_edge(previous, event.event_id.clone(), ConnectionType::Temporal);
+        }
     }
 
     fn get_connected(&self, event_id: &str) -> Vec<String> {
-        self.edges.get(event_id).cloned().unwrap_or_default()
+        self.edges.get(event_id)
+            .map(|to_ids| to_ids.iter().map(|(id, _)| id.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Forget the given events: their positions, their outgoing edges and
+    /// every edge pointing at them. Sources left without edges are dropped.
+    fn remove_events(&mut self, event_ids: &HashSet<String>) {
+        self.positions.retain(|id, _| !event_ids.contains(id));
+        self.edges.retain(|from, to_ids| {
+            if event_ids.contains(from) {
+                return false;
+            }
+            to_ids.retain(|(to, _)| !event_ids.contains(to));
+            !to_ids.is_empty()
+        });
+        self.latest_by_correlation.retain(|_, id| !event_ids.contains(id));
+        if self.latest_event.as_ref().is_some_and(|id| event_ids.contains(id)) {
+            self.latest_event = None;
+        }
     }
 }
 
-/// Domain colors for visual differentiation
-#[derive(Resource)]
-struct DomainColors {
+/// Which kinds of event connections are drawn
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectionVisibility {
+    pub causation: bool,
+    pub correlation: bool,
+    pub temporal: bool,
+}
+
+impl Default for ConnectionVisibility {
+    fn default() -> Self {
+        Self {
+            causation: true,
+            correlation: true,
+            temporal: true,
+        }
+    }
+}
+
+impl ConnectionVisibility {
+    /// Whether connections of `connection_type` are drawn
+    pub fn shows(&self, connection_type: ConnectionType) -> bool {
+        match connection_type {
+            ConnectionType::Causation => self.causation,
+            ConnectionType::Correlation => self.correlation,
+            ConnectionType::Temporal => self.temporal,
+        }
+    }
+}
+
+/// Hue step between generated domain colors: the golden ratio of a full
+/// turn, so consecutive domains land far apart however many there are
+const GOLDEN_RATIO_HUE_STEP: f32 = 360.0 * 0.618_034;
+
+/// Domain colors for visual differentiation. Domains without a color get
+/// one generated on first use, which is kept for the rest of the session.
+#[derive(Resource, Debug, Clone)]
+pub struct DomainColors {
     colors: HashMap<String, Color>,
+    /// Number of colors generated so far
+    generated: usize,
 }
 
 impl Default for DomainColors {
@@ -194,32 +783,259 @@ impl Default for DomainColors {
         colors.insert("nix".to_string(), Color::srgb(0.4, 0.6, 0.8));
         colors.insert("conceptual_spaces".to_string(), Color::srgb(0.8, 0.3, 0.8));
         colors.insert("identity".to_string(), Color::srgb(0.6, 0.8, 0.4));
-        Self { colors }
+        Self { colors, generated: 0 }
+    }
+}
+
+impl DomainColors {
+    /// The built-in palette with `overrides` added or replacing entries
+    pub fn with_overrides(overrides: impl IntoIterator<Item = (String, Color)>) -> Self {
+        let mut domain_colors = Self::default();
+        domain_colors.colors.extend(overrides);
+        domain_colors
+    }
+
+    /// Set the color of `domain`
+    pub fn set(&mut self, domain: impl Into<String>, color: Color) {
+        self.colors.insert(domain.into(), color);
+    }
+
+    /// Color of `domain`, if it has one yet
+    pub fn get(&self, domain: &str) -> Option<Color> {
+        self.colors.get(domain).copied()
+    }
+
+    /// Color of `domain`, generating and remembering one for a domain seen
+    /// for the first time
+    pub fn color_for(&mut self, domain: &str) -> Color {
+        if let Some(color) = self.colors.get(domain) {
+            return *color;
+        }
+        let hue = (self.generated as f32 * GOLDEN_RATIO_HUE_STEP) % 360.0;
+        let color = Color::hsv(hue, 0.7, 0.9);
+        self.generated += 1;
+        self.colors.insert(domain.to_string(), color);
+        color
+    }
+}
+
+/// Color of events without a correlation id
+const UNCORRELATED_COLOR: Color = Color::srgb(0.5, 0.5, 0.5);
+
+/// How much non-matching events are darkened while a correlation is shown
+const DIMMED_FACTOR: f32 = 0.2;
+
+/// What event spheres are colored by
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorMode {
+    #[default]
+    ByDomain,
+    ByCorrelation,
+    ByAggregateType,
+}
+
+/// The correlation shown by `ShowCorrelation`; other events are dimmed
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ActiveCorrelation(pub Option<String>);
+
+/// How an event's intensity falls off over its retention period
+#[derive(Debug, Clone, Copy)]
+pub enum DecayCurve {
+    /// Full intensity until cleanup
+    None,
+    /// Straight line from full to minimum intensity
+    Linear,
+    /// Halves every `half_life` of the retention period (0 to 1)
+    Exponential { half_life: f32 },
+    /// Any easing of the age fraction; returns 1 for new and 0 for expired
+    Custom(fn(f32) -> f32),
+}
+
+impl DecayCurve {
+    /// Intensity, from 1 down to 0, of an event `age` of the way (0 to 1)
+    /// through its retention period
+    pub fn intensity(&self, age: f32) -> f32 {
+        let age = age.clamp(0.0, 1.0);
+        let intensity = match self {
+            DecayCurve::None => 1.0,
+            DecayCurve::Linear => 1.0 - age,
+            DecayCurve::Exponential { half_life } => 0.5_f32.powf(age / half_life.max(f32::EPSILON)),
+            DecayCurve::Custom(curve) => curve(age),
+        };
+        intensity.clamp(0.0, 1.0)
+    }
+}
+
+/// Fading of events by age, so recent events glow and old ones dim before
+/// cleanup removes them
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct EventDecay {
+    pub curve: DecayCurve,
+    /// Intensity of an event about to be cleaned up, so it stays visible
+    pub min_intensity: f32,
+}
+
+impl Default for EventDecay {
+    fn default() -> Self {
+        Self {
+            curve: DecayCurve::Exponential { half_life: 0.3 },
+            min_intensity: 0.15,
+        }
+    }
+}
+
+impl EventDecay {
+    /// Intensity of an event `age_seconds` old with `retention_seconds` of
+    /// retention, between `min_intensity` and 1
+    pub fn intensity(&self, age_seconds: f32, retention_seconds: f32) -> f32 {
+        let age = if retention_seconds > 0.0 { age_seconds / retention_seconds } else { 1.0 };
+        self.min_intensity + (1.0 - self.min_intensity) * self.curve.intensity(age)
+    }
+}
+
+/// Stable color for a string key: an FNV-1a hash picks the HSV hue, so the
+/// same key gets the same color across frames, runs and machines
+fn hashed_color(key: &str) -> Color {
+    let hash = key.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    Color::hsv((hash % 360) as f32, 0.75, 0.9)
+}
+
+/// Color shared by every event of a correlation
+pub fn color_for_correlation(id: &str) -> Color {
+    hashed_color(id)
+}
+
+/// Color of an event under `mode`, dimmed if another correlation is active
+fn event_color(
+    event: &EventVisual,
+    mode: ColorMode,
+    active: &ActiveCorrelation,
+    domain_colors: &mut DomainColors,
+) -> Color {
+    let color = match mode {
+        ColorMode::ByDomain => domain_colors.color_for(&event.domain),
+        ColorMode::ByCorrelation => event.correlation_id
+            .as_deref()
+            .map_or(UNCORRELATED_COLOR, color_for_correlation),
+        ColorMode::ByAggregateType => hashed_color(&event.aggregate_type),
+    };
+
+    match &active.0 {
+        Some(active) if event.correlation_id.as_ref() != Some(active) => {
+            color.mix(&Color::BLACK, 1.0 - DIMMED_FACTOR)
+        }
+        _ => color,
     }
 }
 
 /// Component for event visual entities
 #[derive(Component)]
-struct EventVisual {
-    event_id: String,
-    domain: String,
-    event_type: String,
-    timestamp: DateTime<Utc>,
-    correlation_id: Option<String>,
+pub struct EventVisual {
+    pub event_id: String,
+    pub domain: String,
+    pub event_type: String,
+    pub aggregate_id: String,
+    pub aggregate_type: String,
+    pub timestamp: DateTime<Utc>,
+    pub correlation_id: Option<String>,
+    /// Whether the event reports a failure (see `DomainEventReceived::is_error`)
+    pub is_error: bool,
+    /// Lowercased text the filter UI searches against (see `searchable_text`)
+    pub searchable_text: String,
+}
+
+impl EventVisual {
+    pub fn from_event(event: &DomainEventReceived) -> Self {
+        Self {
+            event_id: event.event_id.clone(),
+            domain: event.domain.clone(),
+            event_type: event.event_type.clone(),
+            aggregate_id: event.aggregate_id.clone(),
+            aggregate_type: event.aggregate_type.clone(),
+            timestamp: event.timestamp,
+            correlation_id: event.correlation_id.clone(),
+            is_error: event.is_error(),
+            searchable_text: searchable_text(event),
+        }
+    }
+}
+
+/// Upper bound for the precomputed search text of a single event
+const MAX_SEARCHABLE_TEXT_LEN: usize = 4096;
+
+/// Build the lowercased search text for an event: domain, type, aggregate and
+/// the flattened payload as `key=value` pairs, truncated to `MAX_SEARCHABLE_TEXT_LEN`.
+pub fn searchable_text(event: &DomainEventReceived) -> String {
+    let mut text = format!(
+        "{} {} {} {}",
+        event.domain, event.event_type, event.aggregate_type, event.aggregate_id
+    );
+    flatten_payload("", &event.payload, &mut text);
+
+    if text.len() > MAX_SEARCHABLE_TEXT_LEN {
+        let mut end = MAX_SEARCHABLE_TEXT_LEN;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+    }
+    text.to_lowercase()
+}
+
+fn flatten_payload(prefix: &str, value: &serde_json::Value, out: &mut String) {
+    if out.len() > MAX_SEARCHABLE_TEXT_LEN {
+        return;
+    }
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map {
+                let path = if prefix.is_empty() { key.clone() } else { format!("{prefix}.{key}") };
+                flatten_payload(&path, value, out);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                flatten_payload(prefix, item, out);
+            }
+        }
+        serde_json::Value::String(s) => out.push_str(&format!(" {prefix}={s}")),
+        serde_json::Value::Null => out.push_str(&format!(" {prefix}")),
+        other => out.push_str(&format!(" {prefix}={other}")),
+    }
 }
 
-/// Component for event connection lines
+/// Component for event connection lines; one entity per drawn part
 #[derive(Component)]
 struct EventConnection {
     from_event: String,
     to_event: String,
     connection_type: ConnectionType,
+    /// Event entities at either end
+    from: Entity,
+    to: Entity,
+    part: ConnectionPart,
 }
 
-#[derive(Debug, Clone)]
-enum ConnectionType {
+/// The piece of a connection an entity draws
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum ConnectionPart {
+    Body,
+    Arrowhead,
+    Pulse,
+    Dash(usize),
+}
+
+/// Relationship between two connected events
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConnectionType {
+    /// The later event was caused by the earlier one; drawn solid
     Causation,
+    /// Consecutive events of the same correlation; drawn dashed in the
+    /// correlation's color
     Correlation,
+    /// Events received one after the other; drawn faint
     Temporal,
 }
 
@@ -227,6 +1043,15 @@ enum ConnectionType {
 #[derive(Resource)]
 struct EventReceiver(Arc<RwLock<mpsc::Receiver<DomainEventReceived>>>);
 
+/// Statuses reported by the NATS subscription task
+#[derive(Resource)]
+struct BridgeStatusReceiver(Arc<RwLock<mpsc::UnboundedReceiver<BridgeStatus>>>);
+
+/// While set, incoming events stay in the NATS channel instead of being
+/// visualized
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Paused(pub bool);
+
 /// Setup event visualization
 fn setup_event_visualization(
     mut commands: Commands,
@@ -247,6 +1072,7 @@ fn setup_event_visualization(
     commands.spawn((
         Camera3d::default(),
         Transform::from_xyz(0.0, 20.0, 40.0).looking_at(Vec3::ZERO, Vec3::Y),
+        GraphCamera,
     ));
 
     // Add ground plane for reference
@@ -277,23 +1103,46 @@ fn setup_event_visualization(
     ));
 }
 
-/// Process incoming events from NATS
+/// Events taken from the NATS channel per frame
+const EVENTS_PER_FRAME: usize = 10;
+
+/// Events taken per frame while working through a backlog, e.g. after a pause
+const CATCH_UP_EVENTS_PER_FRAME: usize = 50;
+
+/// Process incoming events from NATS.
+///
+/// While [`Paused`], the channel isn't read at all: events queue up in the
+/// bounded channel and, once it is full, the subscription task waits on
+/// `send`, so nothing is dropped and the visualized set stays frozen. After
+/// resuming, the backlog is drained in capped batches.
+///
+/// Once every sender of the channel is gone the bridge is reported
+/// [`BridgeStatus::Disconnected`], unless it already reported an error, such
+/// as a failed subscription, which is kept.
 fn process_incoming_events(
+    paused: Res<Paused>,
     event_receiver: Res<EventReceiver>,
     event_store: Res<EventStore>,
     mut event_writer: EventWriter<DomainEventReceived>,
     mut event_graph: ResMut<EventFlowGraph>,
+    mut bridge_status: ResMut<BridgeStatus>,
+    mut status_changed: EventWriter<BridgeStatusChanged>,
 ) {
+    if paused.0 {
+        return;
+    }
     let mut receiver = event_receiver.0.write();
-    
-    // Process up to 10 events per frame to avoid blocking
-    for _ in 0..10 {
+    let batch_size = if receiver.len() > EVENTS_PER_FRAME {
+        CATCH_UP_EVENTS_PER_FRAME
+    } else {
+        EVENTS_PER_FRAME
+    };
+
+    for _ in 0..batch_size {
         match receiver.try_recv() {
             Ok(event) => {
                 // Update event graph
-                if let Some(causation_id) = &event.causation_id {
-                    event_graph.add_edge(causation_id.clone(), event.event_id.clone());
-                }
+                event_graph.link_event(&event);
                 
                 // Store and emit event
                 event_store.add_event(event.clone());
@@ -301,27 +1150,91 @@ fn process_incoming_events(
             }
             Err(mpsc::error::TryRecvError::Empty) => break,
             Err(mpsc::error::TryRecvError::Disconnected) => {
-                warn!("NATS event receiver disconnected");
+                if matches!(*bridge_status, BridgeStatus::Error(_)) {
+                    break;
+                }
+                if report_bridge_status(&mut bridge_status, BridgeStatus::Disconnected, &mut status_changed) {
+                    warn!("NATS event receiver disconnected");
+                }
                 break;
             }
         }
     }
 }
 
+/// Pass on the statuses reported by the NATS subscription task
+fn receive_bridge_status(
+    receiver: Option<Res<BridgeStatusReceiver>>,
+    mut bridge_status: ResMut<BridgeStatus>,
+    mut status_changed: EventWriter<BridgeStatusChanged>,
+) {
+    let Some(receiver) = receiver else {
+        return;
+    };
+    while let Ok(status) = receiver.0.write().try_recv() {
+        report_bridge_status(&mut bridge_status, status, &mut status_changed);
+    }
+}
+
+/// Feed received events into the shared statistics. The counters take
+/// every event straight away; the rates and the [`StatisticsSummary`] are
+/// recomputed once per refresh interval, over the events counted since.
+fn update_event_statistics(
+    mut events: EventReader<DomainEventReceived>,
+    mut stats: ResMut<EventStatistics>,
+    mut summary: ResMut<StatisticsSummary>,
+    config: Res<StatisticsConfig>,
+    mut alert_rules: ResMut<AlertRules>,
+    mut alerts: EventWriter<AlertTriggered>,
+    time: Res<Time>,
+) {
+    let received: Vec<&DomainEventReceived> = events.read().collect();
+    for event in &received {
+        stats.update(event);
+    }
+
+    let now = Utc::now();
+    if !alert_rules.rules.is_empty() {
+        alerts.write_batch(alert_rules.evaluate(received.iter().copied(), now));
+    }
+
+    // Counting alone doesn't change what the panels show
+    let pending = summary.bypass_change_detection();
+    pending.pending_events += received.len() as u64;
+    let since_refresh = pending.since_refresh.map(|since| since + time.delta_secs());
+    if since_refresh.is_some_and(|since| since < config.refresh_interval) {
+        pending.since_refresh = since_refresh;
+        return;
+    }
+    stats.record_frame(now, pending.pending_events, since_refresh.unwrap_or(time.delta_secs()));
+    pending.pending_events = 0;
+    pending.since_refresh = Some(0.0);
+    summary.refresh(&stats);
+}
+
+/// Radius of event spheres
+const EVENT_SPHERE_RADIUS: f32 = 0.5;
+
+/// Sphere detail used until the LOD system picks a band
+const EVENT_SPHERE_SECTORS: u32 = 12;
+
 /// Create visual representations for new events
 fn create_event_visuals(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    mut lod_meshes: ResMut<LodMeshes>,
     mut event_reader: EventReader<DomainEventReceived>,
-    domain_colors: Res<DomainColors>,
+    mut domain_colors: ResMut<DomainColors>,
+    color_mode: Res<ColorMode>,
+    active_correlation: Res<ActiveCorrelation>,
     mut event_graph: ResMut<EventFlowGraph>,
 ) {
+    // Looking a color up is not a change; only a newly generated one is
+    let generated = domain_colors.generated;
     for event in event_reader.read() {
-        let color = domain_colors.colors
-            .get(&event.domain)
-            .copied()
-            .unwrap_or(Color::srgb(0.5, 0.5, 0.5));
+        let visual = EventVisual::from_event(event);
+        let color = event_color(&visual, *color_mode, &active_correlation, domain_colors.bypass_change_detection());
 
         // Calculate initial position (will be updated by force-directed layout)
         let initial_pos = Vec3::new(
@@ -332,69 +1245,263 @@ fn create_event_visuals(
 
         event_graph.positions.insert(event.event_id.clone(), initial_pos);
 
-        // Spawn event sphere
-        commands.spawn((
-            Mesh3d(meshes.add(Sphere::new(0.5).mesh())),
+        // Spawn event sphere; the LOD system refines the mesh from here
+        let sphere = commands.spawn((
+            Mesh3d(lod_meshes.sphere(&mut meshes, EVENT_SPHERE_RADIUS, EVENT_SPHERE_SECTORS)),
+            LodSphere { radius: EVENT_SPHERE_RADIUS },
             MeshMaterial3d(materials.add(StandardMaterial {
                 base_color: color,
                 emissive: color.into(),
+                // Faded by `decay_events`
+                alpha_mode: AlphaMode::Blend,
                 ..default()
             })),
             Transform::from_translation(initial_pos),
-            EventVisual {
-                event_id: event.event_id.clone(),
-                domain: event.domain.clone(),
-                event_type: event.event_type.clone(),
-                timestamp: event.timestamp,
-                correlation_id: event.correlation_id.clone(),
-            },
-        ));
+            visual,
+        )).id();
 
-        // Spawn event label
+        // Spawn event label, positioned over the sphere by the LOD system
         commands.spawn((
             Text::new(format!("{}\n{}", event.domain, event.event_type)),
             Node {
                 position_type: PositionType::Absolute,
                 ..default()
             },
-            Transform::from_translation(initial_pos + Vec3::Y * 1.0),
+            Visibility::Hidden,
+            LodLabel {
+                target: sphere,
+                offset: Vec3::Y,
+            },
         ));
     }
+    if domain_colors.generated != generated {
+        domain_colors.set_changed();
+    }
 }
 
-/// Update event positions using force-directed layout
+/// Recolor existing events when the color mode, shown correlation or domain
+/// colors change
+fn recolor_events(
+    color_mode: Res<ColorMode>,
+    active_correlation: Res<ActiveCorrelation>,
+    mut domain_colors: ResMut<DomainColors>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    events: Query<(&EventVisual, &MeshMaterial3d<StandardMaterial>)>,
+) {
+    if !color_mode.is_changed() && !active_correlation.is_changed() && !domain_colors.is_changed() {
+        return;
+    }
+    let generated = domain_colors.generated;
+    for (event, material) in events.iter() {
+        if let Some(material) = materials.get_mut(&material.0) {
+            let color = event_color(event, *color_mode, &active_correlation, domain_colors.bypass_change_detection());
+            material.base_color = color;
+            material.emissive = color.into();
+        }
+    }
+    if domain_colors.generated != generated {
+        domain_colors.set_changed();
+    }
+}
+
+/// Fade events by age: the emissive glow and the alpha follow the
+/// configured [`EventDecay`] curve over the retention period. Materials are
+/// only written while their event is still fading.
+fn decay_events(
+    retention: Res<RetentionPolicy>,
+    decay: Res<EventDecay>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    events: Query<(&EventVisual, &MeshMaterial3d<StandardMaterial>)>,
+) {
+    let now = Utc::now();
+    for (event, material) in events.iter() {
+        let age_seconds = (now - event.timestamp).num_milliseconds() as f32 / 1000.0;
+        let intensity = decay.intensity(age_seconds, retention.max_age.as_secs_f32());
+        let Some(current) = materials.get(&material.0) else {
+            continue;
+        };
+        let color = current.base_color.with_alpha(intensity);
+        let emissive = (color.to_linear() * intensity).with_alpha(1.0);
+        if current.base_color == color && current.emissive == emissive {
+            continue;
+        }
+        if let Some(material) = materials.get_mut(&material.0) {
+            material.base_color = color;
+            material.emissive = emissive;
+        }
+    }
+}
+
+/// Duration of the camera move to a focused event
+const FOCUS_TRANSITION_SECONDS: f32 = 0.5;
+
+/// Apply visualization commands. Focusing an event also makes it the only
+/// selected event.
+#[allow(clippy::too_many_arguments)]
+fn handle_event_commands(
+    mut entity_commands: Commands,
+    mut commands: EventReader<EventVisualizationCommand>,
+    mut focus: EventWriter<FocusCamera>,
+    mut color_mode: ResMut<ColorMode>,
+    mut active_correlation: ResMut<ActiveCorrelation>,
+    mut filter_state: ResMut<EventFilterState>,
+    mut connection_visibility: ResMut<ConnectionVisibility>,
+    mut edge_filter: Option<ResMut<EdgeFilter>>,
+    mut paused: ResMut<Paused>,
+    events: Query<(Entity, &EventVisual, Has<Selected>)>,
+) {
+    for command in commands.read() {
+        match command {
+            EventVisualizationCommand::FocusEvent(event_id) => {
+                if let Some((entity, _, _)) = events.iter().find(|(_, event, _)| &event.event_id == event_id) {
+                    for (other, _, selected) in events.iter() {
+                        if selected && other != entity {
+                            entity_commands.entity(other).remove::<Selected>();
+                        }
+                    }
+                    entity_commands.entity(entity).insert(Selected);
+                    focus.write(FocusCamera {
+                        target_entities: vec![entity],
+                        target_point: None,
+                        transition_duration: FOCUS_TRANSITION_SECONDS,
+                    });
+                }
+            }
+            EventVisualizationCommand::ShowCorrelation(correlation_id) => {
+                *color_mode = ColorMode::ByCorrelation;
+                active_correlation.0 = Some(correlation_id.clone());
+            }
+            EventVisualizationCommand::FilterByDomain(domain) => {
+                filter_state.domain_filters = HashSet::from([domain.clone()]);
+            }
+            EventVisualizationCommand::ClearFilters => {
+                *filter_state = EventFilterState::default();
+                active_correlation.0 = None;
+                *connection_visibility = ConnectionVisibility::default();
+                if let Some(edge_filter) = edge_filter.as_mut() {
+                    **edge_filter = EdgeFilter::default();
+                }
+            }
+            EventVisualizationCommand::TogglePause => {
+                paused.0 = !paused.0;
+            }
+        }
+    }
+}
+
+/// Strength of the inverse-square repulsion between events
+const REPULSION_STRENGTH: f32 = 10.0;
+
+/// Repulsive force on an event displaced by `delta` from another event
+fn repulsion(delta: Vec3) -> Vec3 {
+    let distance = delta.length().max(0.1);
+    delta.normalize_or_zero() * (REPULSION_STRENGTH / (distance * distance))
+}
+
+/// Repulsive force on each position from every other position, comparing
+/// all pairs
+pub fn repulsion_forces_naive(positions: &[Vec3]) -> Vec<Vec3> {
+    let mut forces = vec![Vec3::ZERO; positions.len()];
+    for i in 0..positions.len() {
+        for j in (i + 1)..positions.len() {
+            let force = repulsion(positions[j] - positions[i]);
+            forces[i] -= force;
+            forces[j] += force;
+        }
+    }
+    forces
+}
+
+/// Cell size giving roughly `4 * sqrt(n)` cells over the positions'
+/// bounding box, which balances exact neighbor pairs against cell-to-cell
+/// work in [`repulsion_forces_grid`]
+pub fn repulsion_cell_size(positions: &[Vec3]) -> f32 {
+    let Some(first) = positions.first() else {
+        return 1.0;
+    };
+    let (min, max) = positions.iter().fold((*first, *first), |(min, max), position| {
+        (min.min(*position), max.max(*position))
+    });
+    let extent = (max - min).max(Vec3::ONE);
+    let target_cells = 4.0 * (positions.len() as f32).sqrt();
+    (extent.x * extent.y * extent.z / target_cells).cbrt()
+}
+
+/// Repulsive force on each position using a uniform spatial hash grid.
+/// Pairs in neighboring cells are compared exactly; every other cell acts
+/// as a single point at its centroid, weighted by its event count. The cost
+/// is linear in the number of positions plus quadratic in the number of
+/// occupied cells, instead of quadratic in the number of positions.
+pub fn repulsion_forces_grid(positions: &[Vec3]) -> Vec<Vec3> {
+    let cell_size = repulsion_cell_size(positions);
+    let cell_of = |position: Vec3| (position / cell_size).floor().as_ivec3();
+    let mut cell_indices: HashMap<IVec3, usize> = HashMap::new();
+    let mut cells: Vec<(IVec3, Vec<usize>)> = Vec::new();
+    for (index, position) in positions.iter().enumerate() {
+        let cell = cell_of(*position);
+        let slot = *cell_indices.entry(cell).or_insert_with(|| {
+            cells.push((cell, Vec::new()));
+            cells.len() - 1
+        });
+        cells[slot].1.push(index);
+    }
+    let centroids: Vec<Vec3> = cells.iter()
+        .map(|(_, members)| members.iter().map(|&index| positions[index]).sum::<Vec3>() / members.len() as f32)
+        .collect();
+
+    let mut forces = vec![Vec3::ZERO; positions.len()];
+    let mut far_forces = vec![Vec3::ZERO; cells.len()];
+    for (a, (cell_a, members_a)) in cells.iter().enumerate() {
+        for (b, (cell_b, members_b)) in cells.iter().enumerate().skip(a) {
+            if (*cell_b - *cell_a).abs().max_element() > 1 {
+                // Far apart: cell to cell through the centroids
+                let force = repulsion(centroids[a] - centroids[b]);
+                far_forces[a] += force * members_b.len() as f32;
+                far_forces[b] -= force * members_a.len() as f32;
+                continue;
+            }
+            // Neighbors: every pair exactly
+            for (k, &i) in members_a.iter().enumerate() {
+                let others = if a == b { &members_b[k + 1..] } else { &members_b[..] };
+                for &j in others {
+                    let force = repulsion(positions[i] - positions[j]);
+                    forces[i] += force;
+                    forces[j] -= force;
+                }
+            }
+        }
+    }
+
+    for ((_, members), far_force) in cells.iter().zip(far_forces) {
+        for &index in members {
+            forces[index] += far_force;
+        }
+    }
+    forces
+}
+
+/// Update event positions using force-directed layout. Events of the
+/// shown correlation are left to [`layout_correlation_chain`].
 fn update_event_positions(
     mut event_graph: ResMut<EventFlowGraph>,
     mut query: Query<(&EventVisual, &mut Transform)>,
+    active_correlation: Res<ActiveCorrelation>,
     time: Res<Time>,
 ) {
     let dt = time.delta_secs();
-    let mut forces: HashMap<String, Vec3> = HashMap::new();
 
-    // Calculate repulsive forces between all events
-    let positions: Vec<(String, Vec3)> = query.iter()
+    // Calculate repulsive forces between nearby events
+    let (ids, positions): (Vec<String>, Vec<Vec3>) = query.iter()
         .map(|(ev, t)| (ev.event_id.clone(), t.translation))
+        .unzip();
+    let mut forces: HashMap<String, Vec3> = ids.into_iter()
+        .zip(repulsion_forces_grid(&positions))
         .collect();
 
-    for i in 0..positions.len() {
-        for j in (i + 1)..positions.len() {
-            let (id1, pos1) = &positions[i];
-            let (id2, pos2) = &positions[j];
-            
-            let delta = *pos2 - *pos1;
-            let distance = delta.length().max(0.1);
-            let force_magnitude = 10.0 / (distance * distance);
-            let force = delta.normalize() * force_magnitude;
-
-            *forces.entry(id1.clone()).or_default() -= force;
-            *forces.entry(id2.clone()).or_default() += force;
-        }
-    }
-
-    // Calculate attractive forces for connected events
+    // Calculate attractive forces for causally connected events
     for (from_id, to_ids) in &event_graph.edges {
         if let Some(from_pos) = event_graph.positions.get(from_id) {
-            for to_id in to_ids {
+            for (to_id, _) in to_ids.iter().filter(|(_, kind)| *kind == ConnectionType::Causation) {
                 if let Some(to_pos) = event_graph.positions.get(to_id) {
                     let delta = *to_pos - *from_pos;
                     let distance = delta.length().max(0.1);
@@ -410,6 +1517,9 @@ fn update_event_positions(
 
     // Apply forces
     for (event, mut transform) in query.iter_mut() {
+        if active_correlation.0.is_some() && event.correlation_id == active_correlation.0 {
+            continue;
+        }
         if let Some(force) = forces.get(&event.event_id) {
             let velocity = *force * dt;
             transform.translation += velocity;
@@ -420,76 +1530,342 @@ fn update_event_positions(
     }
 }
 
-/// Update visual connections between events
+/// Vertical distance between the layers of a causation tree
+const CHAIN_LAYER_SPACING: f32 = 2.0;
+
+/// Horizontal distance between events in the same layer of a causation tree
+const CHAIN_SIBLING_SPACING: f32 = 1.5;
+
+/// Positions of `events`, ids with timestamps, as a causation tree: each
+/// event one layer below its cause, and events of a layer centered side by
+/// side in timestamp order
+fn causation_tree_positions(
+    events: &[(&str, DateTime<Utc>)],
+    causations: &[(&str, &str)],
+) -> HashMap<String, Vec3> {
+    let ids: Vec<&str> = events.iter().map(|(id, _)| *id).collect();
+    let layers = assign_layers(&ids, causations);
+
+    let mut by_layer: HashMap<usize, Vec<(&str, DateTime<Utc>)>> = HashMap::new();
+    for (id, timestamp) in events {
+        by_layer.entry(layers[id]).or_default().push((id, *timestamp));
+    }
+    let mut positions = HashMap::new();
+    for (layer, mut members) in by_layer {
+        members.sort_by(|(a_id, a), (b_id, b)| (a, a_id).cmp(&(b, b_id)));
+        let width = (members.len() - 1) as f32 * CHAIN_SIBLING_SPACING;
+        for (index, (id, _)) in members.into_iter().enumerate() {
+            let offset = Vec3::new(
+                index as f32 * CHAIN_SIBLING_SPACING - width / 2.0,
+                -(layer as f32) * CHAIN_LAYER_SPACING,
+                0.0,
+            );
+            positions.insert(id.to_string(), offset);
+        }
+    }
+    positions
+}
+
+/// Lay out the events of the shown correlation as a causation tree from
+/// the causation edges of the [`EventFlowGraph`], keeping its earliest
+/// event where it is
+fn layout_correlation_chain(
+    mut event_graph: ResMut<EventFlowGraph>,
+    active_correlation: Res<ActiveCorrelation>,
+    mut query: Query<(&EventVisual, &mut Transform)>,
+) {
+    let Some(correlation_id) = &active_correlation.0 else {
+        return;
+    };
+    let chain: Vec<(&str, DateTime<Utc>, Vec3)> = query.iter()
+        .filter(|(event, _)| event.correlation_id.as_ref() == Some(correlation_id))
+        .map(|(event, transform)| (event.event_id.as_str(), event.timestamp, transform.translation))
+        .collect();
+    let Some(&(earliest, _, anchor)) = chain.iter().min_by(|a, b| (a.1, a.0).cmp(&(b.1, b.0))) else {
+        return;
+    };
+
+    let events: Vec<(&str, DateTime<Utc>)> = chain.iter().map(|(id, timestamp, _)| (*id, *timestamp)).collect();
+    let causations: Vec<(&str, &str)> = event_graph.edges.iter()
+        .flat_map(|(from, to_ids)| {
+            to_ids.iter()
+                .filter(|(_, kind)| *kind == ConnectionType::Causation)
+                .map(move |(to, _)| (from.as_str(), to.as_str()))
+        })
+        .collect();
+    let positions = causation_tree_positions(&events, &causations);
+    let shift = anchor - positions[earliest];
+
+    for (event, mut transform) in query.iter_mut() {
+        if let Some(position) = positions.get(&event.event_id) {
+            let position = *position + shift;
+            if transform.translation != position {
+                transform.translation = position;
+            }
+            event_graph.positions.insert(event.event_id.clone(), position);
+        }
+    }
+}
+
+/// Animated pulses running along causation connections from cause to
+/// effect. Off by default, as it adds an entity per connection.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct ConnectionPulse {
+    pub enabled: bool,
+    /// Seconds a pulse takes from cause to effect
+    pub period_seconds: f32,
+}
+
+impl Default for ConnectionPulse {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            period_seconds: 1.5,
+        }
+    }
+}
+
+/// How connections are drawn
+#[derive(bevy::ecs::system::SystemParam)]
+struct ConnectionStyle<'w> {
+    visibility: Res<'w, ConnectionVisibility>,
+    pulse: Res<'w, ConnectionPulse>,
+    time: Res<'w, Time>,
+}
+
+/// Radius of the arrowhead at the effect end of a causation connection
+const ARROWHEAD_RADIUS: f32 = 0.15;
+
+/// Length of the arrowhead at the effect end of a causation connection
+const ARROWHEAD_LENGTH: f32 = 0.4;
+
+/// Radius of the sphere pulsing along a causation connection
+const PULSE_RADIUS: f32 = 0.1;
+
+/// Length of one dash of a correlation connection
+const DASH_LENGTH: f32 = 0.4;
+
+/// Gap between the dashes of a correlation connection
+const DASH_GAP: f32 = 0.25;
+
+/// Meshes and materials shared by all connection entities. Bodies are
+/// unit-length cylinders stretched to the distance between their events.
+struct ConnectionAssets {
+    causation: (Handle<Mesh>, Handle<StandardMaterial>),
+    temporal: (Handle<Mesh>, Handle<StandardMaterial>),
+    arrowhead: (Handle<Mesh>, Handle<StandardMaterial>),
+    pulse: (Handle<Mesh>, Handle<StandardMaterial>),
+    dash: Handle<Mesh>,
+    correlation_materials: HashMap<Option<String>, Handle<StandardMaterial>>,
+}
+
+impl ConnectionAssets {
+    fn new(meshes: &mut Assets<Mesh>, materials: &mut Assets<StandardMaterial>) -> Self {
+        let mut faint = |color: Color| materials.add(StandardMaterial {
+            base_color: color,
+            alpha_mode: AlphaMode::Blend,
+            ..default()
+        });
+        let causation_material = faint(Color::srgba(0.8, 0.8, 0.8, 0.5));
+        let temporal_material = faint(Color::srgba(0.6, 0.6, 0.6, 0.15));
+        Self {
+            causation: (meshes.add(Cylinder::new(0.05, 1.0).mesh()), causation_material),
+            temporal: (meshes.add(Cylinder::new(0.02, 1.0).mesh()), temporal_material),
+            arrowhead: (
+                meshes.add(Cone::new(ARROWHEAD_RADIUS, ARROWHEAD_LENGTH).mesh()),
+                materials.add(StandardMaterial::from_color(Color::srgb(0.9, 0.9, 0.9))),
+            ),
+            pulse: (
+                meshes.add(Sphere::new(PULSE_RADIUS).mesh()),
+                materials.add(StandardMaterial {
+                    base_color: Color::WHITE,
+                    emissive: LinearRgba::rgb(4.0, 4.0, 4.0),
+                    ..default()
+                }),
+            ),
+            dash: meshes.add(Cylinder::new(0.035, DASH_LENGTH).mesh()),
+            correlation_materials: HashMap::new(),
+        }
+    }
+
+    /// Dash material in the color of `correlation_id`
+    fn correlation_material(&mut self, correlation_id: Option<&str>, materials: &mut Assets<StandardMaterial>) -> Handle<StandardMaterial> {
+        self.correlation_materials
+            .entry(correlation_id.map(str::to_string))
+            .or_insert_with(|| {
+                let color = correlation_id
+                    .map_or(UNCORRELATED_COLOR, color_for_correlation)
+                    .with_alpha(0.6);
+                materials.add(StandardMaterial {
+                    base_color: color,
+                    alpha_mode: AlphaMode::Blend,
+                    ..default()
+                })
+            })
+            .clone()
+    }
+}
+
+/// A connection part as it should be drawn this frame
+struct ConnectionPiece<'a> {
+    from_event: &'a str,
+    to_event: &'a str,
+    connection_type: ConnectionType,
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+    transform: Transform,
+}
+
+/// Update visual connections between events: causation solid with an
+/// arrowhead at the effect, correlation dashed in the correlation's color
+/// and temporal faint, each kind only if enabled in
+/// [`ConnectionVisibility`]. Connection entities are kept and moved from
+/// frame to frame; only parts that appear or disappear are spawned or
+/// despawned.
+#[allow(clippy::too_many_arguments)]
 fn update_event_connections(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     event_graph: Res<EventFlowGraph>,
-    event_positions: Query<(&EventVisual, &Transform)>,
-    connections: Query<Entity, With<EventConnection>>,
+    style: ConnectionStyle,
+    event_positions: Query<(Entity, &EventVisual, &Transform), Without<EventConnection>>,
+    mut connections: Query<(Entity, &EventConnection, &mut Transform)>,
+    mut assets: Local<Option<ConnectionAssets>>,
 ) {
-    // Remove old connections
-    for entity in connections.iter() {
-        commands.entity(entity).despawn_recursive();
-    }
-
-    // Create new connections
-    let pos_map: HashMap<String, Vec3> = event_positions.iter()
-        .map(|(ev, t)| (ev.event_id.clone(), t.translation))
+    let assets = assets.get_or_insert_with(|| ConnectionAssets::new(&mut meshes, &mut materials));
+    let event_map: HashMap<&str, (Entity, Vec3, Option<&str>)> = event_positions.iter()
+        .map(|(entity, ev, t)| (ev.event_id.as_str(), (entity, t.translation, ev.correlation_id.as_deref())))
         .collect();
+    let pulse_phase = (style.time.elapsed_secs() / style.pulse.period_seconds.max(0.01)).fract();
 
+    let mut wanted: HashMap<(Entity, Entity, ConnectionPart), ConnectionPiece> = HashMap::new();
     for (from_id, to_ids) in &event_graph.edges {
-        if let Some(from_pos) = pos_map.get(from_id) {
-            for to_id in to_ids {
-                if let Some(to_pos) = pos_map.get(to_id) {
-                    let midpoint = (*from_pos + *to_pos) / 2.0;
-                    let direction = *to_pos - *from_pos;
-                    let distance = direction.length();
-                    
-                    if distance > 0.01 {
-                        let rotation = Quat::from_rotation_arc(Vec3::Y, direction.normalize());
-                        
-                        commands.spawn((
-                            Mesh3d(meshes.add(Cylinder::new(0.05, distance).mesh())),
-                            MeshMaterial3d(materials.add(StandardMaterial {
-                                base_color: Color::srgba(0.8, 0.8, 0.8, 0.5),
-                                alpha_mode: AlphaMode::Blend,
-                                ..default()
-                            })),
-                            Transform::from_translation(midpoint)
+        let Some(&(from, from_pos, correlation_id)) = event_map.get(from_id.as_str()) else {
+            continue;
+        };
+        for (to_id, connection_type) in to_ids {
+            if !style.visibility.shows(*connection_type) {
+                continue;
+            }
+            let Some(&(to, to_pos, _)) = event_map.get(to_id.as_str()) else {
+                continue;
+            };
+            let direction = to_pos - from_pos;
+            let distance = direction.length();
+            if distance <= 0.01 {
+                continue;
+            }
+            let rotation = Quat::from_rotation_arc(Vec3::Y, direction / distance);
+            let mut add = |part: ConnectionPart, (mesh, material): (Handle<Mesh>, Handle<StandardMaterial>), transform: Transform| {
+                wanted.insert((from, to, part), ConnectionPiece {
+                    from_event: from_id,
+                    to_event: to_id,
+                    connection_type: *connection_type,
+                    mesh,
+                    material,
+                    transform,
+                });
+            };
+
+            match connection_type {
+                ConnectionType::Causation | ConnectionType::Temporal => {
+                    let body = if *connection_type == ConnectionType::Causation {
+                        assets.causation.clone()
+                    } else {
+                        assets.temporal.clone()
+                    };
+                    add(
+                        ConnectionPart::Body,
+                        body,
+                        Transform::from_translation((from_pos + to_pos) / 2.0)
+                            .with_rotation(rotation)
+                            .with_scale(Vec3::new(1.0, distance, 1.0)),
+                    );
+                    if *connection_type != ConnectionType::Causation {
+                        continue;
+                    }
+
+                    // Arrowhead with its tip on the effect's surface
+                    let unit = direction / distance;
+                    let tip = to_pos - unit * EVENT_SPHERE_RADIUS;
+                    add(
+                        ConnectionPart::Arrowhead,
+                        assets.arrowhead.clone(),
+                        Transform::from_translation(tip - unit * ARROWHEAD_LENGTH / 2.0).with_rotation(rotation),
+                    );
+
+                    if style.pulse.enabled {
+                        add(
+                            ConnectionPart::Pulse,
+                            assets.pulse.clone(),
+                            Transform::from_translation(from_pos.lerp(to_pos, pulse_phase)),
+                        );
+                    }
+                }
+                ConnectionType::Correlation => {
+                    let material = assets.correlation_material(correlation_id, &mut materials);
+                    let dash_count = ((distance + DASH_GAP) / (DASH_LENGTH + DASH_GAP)).floor().max(1.0) as usize;
+                    for i in 0..dash_count {
+                        let center = (i as f32 * (DASH_LENGTH + DASH_GAP) + DASH_LENGTH / 2.0).min(distance);
+                        add(
+                            ConnectionPart::Dash(i),
+                            (assets.dash.clone(), material.clone()),
+                            Transform::from_translation(from_pos + direction / distance * center)
                                 .with_rotation(rotation),
-                            EventConnection {
-                                from_event: from_id.clone(),
-                                to_event: to_id.clone(),
-                                connection_type: ConnectionType::Causation,
-                            },
-                        ));
+                        );
                     }
                 }
             }
         }
     }
+
+    // Move the parts still wanted and drop the rest
+    for (entity, connection, mut transform) in connections.iter_mut() {
+        match wanted.remove(&(connection.from, connection.to, connection.part)) {
+            Some(piece) if piece.connection_type == connection.connection_type => {
+                transform.set_if_neq(piece.transform);
+            }
+            _ => commands.entity(entity).despawn(),
+        }
+    }
+
+    for ((from, to, part), piece) in wanted {
+        commands.spawn((
+            Mesh3d(piece.mesh),
+            MeshMaterial3d(piece.material),
+            piece.transform,
+            EventConnection {
+                from_event: piece.from_event.to_string(),
+                to_event: piece.to_event.to_string(),
+                connection_type: piece.connection_type,
+                from,
+                to,
+                part,
+            },
+        ));
+    }
 }
 
 /// Handle mouse interactions with events
 fn handle_event_interactions(
+    mut commands: EventWriter<EventVisualizationCommand>,
     buttons: Res<ButtonInput<MouseButton>>,
     windows: Query<&Window>,
     cameras: Query<(&Camera, &GlobalTransform)>,
-    events: Query<(&EventVisual, &Transform)>,
+    events: Query<(Entity, &EventVisual, &GlobalTransform, Option<&Aabb>)>,
 ) {
     if buttons.just_pressed(MouseButton::Left) {
-        if let Ok(window) = windows.single() {
-            if let Some(cursor_pos) = window.cursor_position() {
-                for (camera, camera_transform) in cameras.iter() {
-                    if let Some(ray) = camera.viewport_to_world(camera_transform, cursor_pos) {
-                        for (event, transform) in events.iter() {
-                            if ray_intersects_sphere(ray.origin, ray.direction.as_vec3(), transform.translation, 0.5) {
-                                info!("Clicked event: {} - {}", event.domain, event.event_type);
-                                // TODO: Implement focus/details view
-                            }
-                        }
+        if let Some(cursor_pos) = cursor_position(windows.iter()) {
+            for (camera, camera_transform) in cameras.iter() {
+                if let Some(ray) = screen_ray(camera, camera_transform, cursor_pos) {
+                    let hit = nearest_node_hit(
+                        ray,
+                        events.iter().map(|(entity, _, transform, aabb)| (entity, transform, aabb)),
+                    );
+                    if let Some((_, event, _, _)) = hit.and_then(|(entity, _)| events.get(entity).ok()) {
+                        info!("Clicked event: {} - {}", event.domain, event.event_type);
+                        commands.write(EventVisualizationCommand::FocusEvent(event.event_id.clone()));
                     }
                 }
             }
@@ -497,24 +1873,37 @@ fn handle_event_interactions(
     }
 }
 
-/// Clean up old events based on retention policy
-fn cleanup_old_events(
+/// Apply the retention policy to the event store, announcing every
+/// evicted event
+fn enforce_retention(
+    retention: Res<RetentionPolicy>,
+    event_store: Res<EventStore>,
+    mut evicted: EventWriter<EventEvicted>,
+) {
+    for event_id in event_store.evict(&retention, Utc::now()) {
+        evicted.write(EventEvicted { event_id });
+    }
+}
+
+/// Remove the visuals of evicted events and their connections
+fn despawn_evicted_events(
     mut commands: Commands,
-    config: Res<EventVisualizationConfig>,
+    mut evicted: EventReader<EventEvicted>,
     events: Query<(Entity, &EventVisual)>,
     connections: Query<(Entity, &EventConnection)>,
+    mut event_graph: ResMut<EventFlowGraph>,
 ) {
-    let cutoff = Utc::now() - chrono::Duration::seconds(config.retention_seconds as i64);
-    
-    let mut removed_events = Vec::new();
-    
-    // Remove old event visuals
+    let removed_events: HashSet<String> = evicted.read().map(|event| event.event_id.clone()).collect();
+    if removed_events.is_empty() {
+        return;
+    }
+
     for (entity, event) in events.iter() {
-        if event.timestamp < cutoff {
+        if removed_events.contains(&event.event_id) {
             commands.entity(entity).despawn();
-            removed_events.push(event.event_id.clone());
         }
     }
+    event_graph.remove_events(&removed_events);
     
     // Remove connections involving removed events
     for (entity, connection) in connections.iter() {
@@ -525,10 +1914,12 @@ fn cleanup_old_events(
     }
 }
 
-/// Subscribe to domain events from NATS
+/// Subscribe to domain events from NATS, reporting the subscription's
+/// state on `status`
 async fn subscribe_to_domain_events(
     client: Arc<Client>,
     tx: mpsc::Sender<DomainEventReceived>,
+    status: mpsc::UnboundedSender<BridgeStatus>,
 ) {
     // Subscribe to all domain events
     let subject = "*.*.event.v1"; // Pattern: domain.aggregate.event.version
@@ -536,6 +1927,7 @@ async fn subscribe_to_domain_events(
     match client.subscribe(subject).await {
         Ok(mut subscriber) => {
             info!("Subscribed to NATS events on: {}", subject);
+            let _ = status.send(BridgeStatus::Connected);
             
             while let Some(msg) = subscriber.next().await {
                 // Parse subject to extract domain and event type
@@ -575,27 +1967,21 @@ async fn subscribe_to_domain_events(
                         
                         if let Err(e) = tx.send(event).await {
                             error!("Failed to send event to visualization: {}", e);
+                            return;
                         }
                     }
                 }
             }
+            warn!("NATS subscription on {} ended", subject);
+            let _ = status.send(BridgeStatus::Disconnected);
         }
         Err(e) => {
             error!("Failed to subscribe to NATS events: {}", e);
+            let _ = status.send(BridgeError::Subscription(e.to_string()).into());
         }
     }
 }
 
-/// Helper function to check ray-sphere intersection
-fn ray_intersects_sphere(ray_origin: Vec3, ray_dir: Vec3, sphere_center: Vec3, sphere_radius: f32) -> bool {
-    let oc = ray_origin - sphere_center;
-    let a = ray_dir.dot(ray_dir);
-    let b = 2.0 * oc.dot(ray_dir);
-    let c = oc.dot(oc) - sphere_radius * sphere_radius;
-    let discriminant = b * b - 4.0 * a * c;
-    discriminant >= 0.0
-}
-
 /// Helper to generate random float
 mod rand {
     pub fn random<T>() -> T 
@@ -605,4 +1991,904 @@ mod rand {
         use rand::Rng;
         rand::thread_rng().gen()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_event(event_id: &str, causation_id: Option<&str>) -> DomainEventReceived {
+        DomainEventReceived {
+            event_id: event_id.to_string(),
+            timestamp: Utc::now(),
+            domain: "workflow".to_string(),
+            event_type: "StepCompleted".to_string(),
+            aggregate_id: "wf-1".to_string(),
+            aggregate_type: "Workflow".to_string(),
+            correlation_id: Some("corr-1".to_string()),
+            causation_id: causation_id.map(|s| s.to_string()),
+            payload: serde_json::json!({}),
+        }
+    }
+
+    #[test]
+    fn test_get_by_correlation() {
+        let store = EventStore::new();
+        store.add_event(test_event("a", None));
+        let mut other = test_event("b", None);
+        other.correlation_id = Some("corr-2".to_string());
+        store.add_event(other);
+        store.add_event(test_event("c", Some("a")));
+
+        let ids: Vec<String> = store.get_by_correlation("corr-1")
+            .into_iter()
+            .map(|e| e.event_id)
+            .collect();
+        assert_eq!(ids, vec!["a", "c"]);
+    }
+
+    #[test]
+    fn test_causation_chain_is_root_first() {
+        let store = EventStore::new();
+        // Insert out of order to make sure ordering comes from the links
+        store.add_event(test_event("effect", Some("middle")));
+        store.add_event(test_event("root", None));
+        store.add_event(test_event("middle", Some("root")));
+
+        for start in ["root", "middle", "effect"] {
+            let ids: Vec<String> = store.get_causation_chain(start)
+                .into_iter()
+                .map(|e| e.event_id)
+                .collect();
+            assert_eq!(ids, vec!["root", "middle", "effect"], "chain from {start}");
+        }
+
+        assert!(store.get_causation_chain("missing").is_empty());
+    }
+
+    #[test]
+    fn test_searchable_text_includes_payload() {
+        let mut event = test_event("a", None);
+        event.payload = serde_json::json!({"order": {"sku": "ABC-42", "qty": 3}});
+
+        let text = searchable_text(&event);
+        assert!(text.contains("workflow"));
+        assert!(text.contains("order.sku=abc-42"));
+        assert!(text.contains("order.qty=3"));
+    }
+
+    #[test]
+    fn test_searchable_text_is_bounded() {
+        let mut event = test_event("a", None);
+        event.payload = serde_json::json!({"blob": "x".repeat(100_000)});
+
+        assert!(searchable_text(&event).len() <= MAX_SEARCHABLE_TEXT_LEN);
+    }
+
+    #[test]
+    fn test_statistics_report_top_domains() {
+        let mut stats = EventStatistics::default();
+        for (id, domain) in [("1", "sales"), ("2", "inventory"), ("3", "sales"), ("4", "billing"), ("5", "sales"), ("6", "inventory")] {
+            let mut event = test_event(id, None);
+            event.domain = domain.to_string();
+            stats.update(&event);
+        }
+
+        assert_eq!(stats.total_events, 6);
+        assert_eq!(
+            stats.top_domains(2),
+            vec![("sales".to_string(), 3), ("inventory".to_string(), 2)]
+        );
+        assert_eq!(stats.busiest_domain, Some(("sales".to_string(), 3)));
+        assert_eq!(stats.most_common_event, Some(("StepCompleted".to_string(), 6)));
+        assert_eq!(stats.correlation_groups(), 1);
+    }
+
+    #[test]
+    fn test_statistics_keep_a_bounded_number_of_chains() {
+        let mut stats = EventStatistics::default();
+        for index in 0..=EventStatistics::MAX_CORRELATION_CHAINS {
+            let mut event = test_event(&index.to_string(), None);
+            event.correlation_id = Some(format!("corr-{index}"));
+            stats.update(&event);
+        }
+        assert_eq!(stats.correlation_groups(), EventStatistics::MAX_CORRELATION_CHAINS);
+        assert!(!stats.correlation_chains.contains_key("corr-0"));
+        assert!(stats.correlation_chains.contains_key("corr-1"));
+
+        for index in 0..EventStatistics::MAX_CHAIN_LEN + 5 {
+            let mut event = test_event(&format!("long-{index}"), None);
+            event.correlation_id = Some("corr-1".to_string());
+            stats.update(&event);
+        }
+        let chain = &stats.correlation_chains["corr-1"];
+        assert_eq!(chain.len(), EventStatistics::MAX_CHAIN_LEN);
+        assert_eq!(chain.last().map(String::as_str), Some(format!("long-{}", EventStatistics::MAX_CHAIN_LEN + 4).as_str()));
+
+        for index in 0..=EventStatistics::MAX_CAUSATION_IDS {
+            let mut event = test_event(&format!("caused-{index}"), None);
+            event.causation_id = Some(format!("cause-{index}"));
+            stats.update(&event);
+        }
+        assert_eq!(stats.causation_ids.len(), EventStatistics::MAX_CAUSATION_IDS);
+        assert!(!stats.causation_ids.contains("cause-0"));
+        assert_eq!(stats.causation_chains as usize, EventStatistics::MAX_CAUSATION_IDS + 1);
+    }
+
+    #[test]
+    fn test_statistics_rate_window() {
+        let mut stats = EventStatistics::default();
+        let now = Utc::now();
+        stats.record_frame(now - chrono::Duration::seconds(90), 600, 1.0);
+        stats.record_frame(now, 30, 0.5);
+
+        assert_eq!(stats.current_event_rate(), 60.0);
+        assert_eq!(stats.peak_event_rate, 600.0);
+        assert_eq!(stats.events_per_second, 0.5);
+    }
+
+    #[test]
+    fn test_statistics_recompute_once_per_interval() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(EventStatistics::default())
+            .init_resource::<StatisticsConfig>()
+            .init_resource::<StatisticsSummary>()
+            .init_resource::<AlertRules>()
+            .add_event::<DomainEventReceived>()
+            .add_event::<AlertTriggered>()
+            .add_systems(Update, update_event_statistics);
+
+        let send = |app: &mut App, count: usize| {
+            for index in 0..count {
+                let mut event = test_event(&index.to_string(), None);
+                event.domain = if index % 3 == 0 { "sales" } else { "billing" }.to_string();
+                app.world_mut().send_event(event);
+            }
+            app.update();
+        };
+
+        send(&mut app, 1000);
+        assert_eq!(app.world().resource::<EventStatistics>().total_events, 1000);
+        let summary = app.world().resource::<StatisticsSummary>();
+        assert_eq!(summary.recomputes, 1);
+        assert_eq!(summary.top_domains[0], "billing: 666 events (66.6%)");
+        assert!(summary.text.starts_with("Total Events: 1000\n"));
+
+        // Within the interval the counters move on, the summary waits
+        send(&mut app, 1000);
+        assert_eq!(app.world().resource::<EventStatistics>().total_events, 2000);
+        let summary = app.world().resource::<StatisticsSummary>();
+        assert_eq!(summary.recomputes, 1);
+        assert!(summary.text.starts_with("Total Events: 1000\n"));
+    }
+
+    #[test]
+    fn test_rejected_events_count_as_errors() {
+        let rejected = DomainEventReceived {
+            event_type: "DocumentRejected".to_string(),
+            ..test_event("rejected", None)
+        };
+        let with_error = DomainEventReceived {
+            payload: serde_json::json!({ "error": "timeout" }),
+            ..test_event("with-error", None)
+        };
+        let accepted = test_event("accepted", None);
+        assert!(rejected.is_error() && with_error.is_error());
+        assert!(!accepted.is_error());
+
+        let mut stats = EventStatistics::default();
+        for event in [&rejected, &with_error, &accepted] {
+            stats.update(event);
+        }
+        assert_eq!(stats.error_count, 2);
+
+        let filters = EventFilterState { only_errors: true, ..default() };
+        assert!(filters.matches(&rejected));
+        assert!(!filters.matches(&accepted));
+        assert!(EventVisual::from_event(&rejected).is_error);
+    }
+
+    #[test]
+    fn test_cleanup_prunes_event_flow_graph() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(EventFlowGraph::new())
+            .add_event::<EventEvicted>()
+            .add_systems(Update, despawn_evicted_events);
+
+        // old-root -> old-child -> new-child, old-root -> new-sibling
+        for event_id in ["old-root", "old-child", "new-child", "new-sibling"] {
+            app.world_mut().spawn(EventVisual::from_event(&test_event(event_id, None)));
+            app.world_mut().resource_mut::<EventFlowGraph>()
+                .positions.insert(event_id.to_string(), Vec3::ZERO);
+        }
+        {
+            let mut graph = app.world_mut().resource_mut::<EventFlowGraph>();
+            graph.add_edge("old-root".to_string(), "old-child".to_string(), ConnectionType::Causation);
+            graph.add_edge("old-root".to_string(), "new-sibling".to_string(), ConnectionType::Causation);
+            graph.add_edge("old-child".to_string(), "new-child".to_string(), ConnectionType::Causation);
+            graph.add_edge("new-sibling".to_string(), "old-child".to_string(), ConnectionType::Causation);
+        }
+        for event_id in ["old-root", "old-child"] {
+            app.world_mut().send_event(EventEvicted { event_id: event_id.to_string() });
+        }
+        app.update();
+
+        let graph = app.world().resource::<EventFlowGraph>();
+        let live: HashSet<&str> = ["new-child", "new-sibling"].into_iter().collect();
+        assert_eq!(graph.positions.keys().map(String::as_str).collect::<HashSet<_>>(), live);
+        for (from, to_ids) in &graph.edges {
+            assert!(graph.positions.contains_key(from), "dangling source {from}");
+            assert!(to_ids.iter().all(|(to, _)| graph.positions.contains_key(to)), "dangling target from {from}");
+        }
+        // new-sibling only pointed at a pruned event, so it has no edges left
+        assert!(graph.get_connected("new-sibling").is_empty());
+        assert!(graph.edges.is_empty());
+    }
+
+    #[test]
+    fn test_decay_curves() {
+        assert_eq!(DecayCurve::None.intensity(0.9), 1.0);
+        assert_eq!(DecayCurve::Linear.intensity(0.25), 0.75);
+        assert!((DecayCurve::Exponential { half_life: 0.5 }.intensity(1.0) - 0.25).abs() < 1e-6);
+        assert_eq!(DecayCurve::Custom(|age| 1.0 - age * age).intensity(2.0), 0.0);
+
+        let decay = EventDecay { curve: DecayCurve::Linear, min_intensity: 0.2 };
+        assert_eq!(decay.intensity(0.0, 100.0), 1.0);
+        assert!((decay.intensity(50.0, 100.0) - 0.6).abs() < 1e-6);
+        assert_eq!(decay.intensity(500.0, 100.0), 0.2);
+    }
+
+    #[test]
+    fn test_old_events_are_dimmer() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<StandardMaterial>()
+            .insert_resource(RetentionPolicy { max_count: 100, max_age: Duration::from_secs(100) })
+            .insert_resource(EventDecay { curve: DecayCurve::Linear, min_intensity: 0.0 })
+            .add_systems(Update, decay_events);
+
+        let mut spawn_event = |event_id: &str, age_seconds: i64| {
+            let material = app.world_mut()
+                .resource_mut::<Assets<StandardMaterial>>()
+                .add(StandardMaterial::from_color(Color::WHITE));
+            let visual = EventVisual {
+                timestamp: Utc::now() - chrono::Duration::seconds(age_seconds),
+                ..EventVisual::from_event(&test_event(event_id, None))
+            };
+            app.world_mut().spawn((visual, MeshMaterial3d(material.clone())));
+            material
+        };
+        let new = spawn_event("new", 0);
+        let old = spawn_event("old", 75);
+        app.update();
+
+        let materials = app.world().resource::<Assets<StandardMaterial>>();
+        let (new, old) = (materials.get(&new).unwrap(), materials.get(&old).unwrap());
+        assert!(new.base_color.alpha() > 0.99);
+        assert!((old.base_color.alpha() - 0.25).abs() < 0.01);
+        assert!(old.emissive.red < new.emissive.red);
+        // Repeated frames don't compound the fade
+        app.update();
+        let materials = app.world().resource::<Assets<StandardMaterial>>();
+        assert!(materials.iter().all(|(_, material)| material.emissive.red > 0.2));
+    }
+
+    #[test]
+    fn test_settled_events_leave_their_material_alone() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<StandardMaterial>()
+            .insert_resource(RetentionPolicy { max_count: 100, max_age: Duration::from_secs(100) })
+            .insert_resource(EventDecay { curve: DecayCurve::None, min_intensity: 0.0 })
+            .add_systems(Update, decay_events);
+
+        let material = app.world_mut()
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(StandardMaterial::from_color(Color::WHITE));
+        app.world_mut().spawn((
+            EventVisual::from_event(&test_event("steady", None)),
+            MeshMaterial3d(material.clone()),
+        ));
+        app.update();
+        assert!(app.world().resource::<Assets<StandardMaterial>>().get(&material).unwrap().emissive.red > 0.99);
+
+        let mut cursor = app.world().resource::<Events<AssetEvent<StandardMaterial>>>().get_cursor();
+        cursor.read(app.world().resource::<Events<AssetEvent<StandardMaterial>>>()).count();
+        app.update();
+        let modified = cursor.read(app.world().resource::<Events<AssetEvent<StandardMaterial>>>())
+            .filter(|event| event.is_modified(material.id()))
+            .count();
+        assert_eq!(modified, 0);
+    }
+
+    /// Mean distance over all pairs of positions
+    fn mean_pairwise_distance(positions: &[Vec3]) -> f32 {
+        let mut total = 0.0;
+        let mut pairs = 0;
+        for i in 0..positions.len() {
+            for j in (i + 1)..positions.len() {
+                total += positions[i].distance(positions[j]);
+                pairs += 1;
+            }
+        }
+        total / pairs as f32
+    }
+
+    /// Run the event layout for `steps` frames with the given repulsion
+    fn simulate_layout(mut positions: Vec<Vec3>, steps: usize, repulsion: impl Fn(&[Vec3]) -> Vec<Vec3>) -> Vec<Vec3> {
+        for _ in 0..steps {
+            let mut forces = repulsion(&positions);
+            // Causation chain between consecutive events, as in the live graph
+            for i in 1..positions.len() {
+                let delta = positions[i] - positions[i - 1];
+                let force = delta.normalize_or_zero() * delta.length().max(0.1) * 0.1;
+                forces[i - 1] += force;
+                forces[i] -= force;
+            }
+            for (position, force) in positions.iter_mut().zip(forces) {
+                *position += force * 0.05;
+            }
+        }
+        positions
+    }
+
+    #[test]
+    fn test_grid_repulsion_approximates_naive() {
+        let positions = [Vec3::ZERO, Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 2.0, 0.0), Vec3::new(50.0, 0.0, 0.0), Vec3::new(51.0, 1.0, 0.0)];
+        let naive = repulsion_forces_naive(&positions);
+        let grid = repulsion_forces_grid(&positions);
+
+        for (naive, grid) in naive.iter().zip(&grid) {
+            assert!(naive.distance(*grid) < 1e-3, "{naive} vs {grid}");
+        }
+    }
+
+    #[test]
+    fn test_grid_layout_quality_is_comparable_to_naive() {
+        use ::rand::{Rng, SeedableRng};
+
+        let mut rng = ::rand::rngs::StdRng::seed_from_u64(7);
+        let initial: Vec<Vec3> = (0..200)
+            .map(|_| Vec3::new(rng.gen_range(-10.0..10.0), rng.gen_range(0.0..10.0), rng.gen_range(-10.0..10.0)))
+            .collect();
+
+        let naive = mean_pairwise_distance(&simulate_layout(initial.clone(), 100, repulsion_forces_naive));
+        let grid = mean_pairwise_distance(&simulate_layout(initial, 100, repulsion_forces_grid));
+        let ratio = grid / naive;
+        assert!((0.95..=1.05).contains(&ratio), "naive {naive}, grid {grid}");
+    }
+
+    #[test]
+    fn test_correlation_colors_are_stable() {
+        assert_eq!(color_for_correlation("corr-1"), color_for_correlation("corr-1"));
+        assert_ne!(color_for_correlation("corr-1"), color_for_correlation("corr-2"));
+
+        let mut uncorrelated = EventVisual::from_event(&test_event("a", None));
+        uncorrelated.correlation_id = None;
+        let color = event_color(&uncorrelated, ColorMode::ByCorrelation, &ActiveCorrelation::default(), &mut DomainColors::default());
+        assert_eq!(color, UNCORRELATED_COLOR);
+    }
+
+    #[test]
+    fn test_unknown_domains_get_distinct_cached_colors() {
+        let mut domain_colors = DomainColors::with_overrides([("graph".to_string(), Color::WHITE)]);
+        assert_eq!(domain_colors.color_for("graph"), Color::WHITE);
+        assert_eq!(domain_colors.get("billing"), None);
+
+        let generated: Vec<Color> = ["billing", "shipping", "returns"].iter()
+            .map(|domain| domain_colors.color_for(domain))
+            .collect();
+        assert_eq!(domain_colors.color_for("billing"), generated[0]);
+        assert_eq!(domain_colors.get("shipping"), Some(generated[1]));
+        for (i, a) in generated.iter().enumerate() {
+            for b in &generated[i + 1..] {
+                let hue_distance = (Hsva::from(*a).hue - Hsva::from(*b).hue).abs();
+                assert!(hue_distance.min(360.0 - hue_distance) > 60.0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_domain_colors_change_only_when_a_color_is_generated() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<Assets<Mesh>>()
+            .init_resource::<Assets<StandardMaterial>>()
+            .init_resource::<LodMeshes>()
+            .insert_resource(EventFlowGraph::new())
+            .insert_resource(DomainColors::default())
+            .init_resource::<ColorMode>()
+            .init_resource::<ActiveCorrelation>()
+            .add_event::<DomainEventReceived>()
+            .add_systems(Update, (create_event_visuals, recolor_events).chain());
+        app.update();
+        let last_changed = |app: &App| app.world().resource_ref::<DomainColors>().last_changed();
+        let unchanged = last_changed(&app);
+
+        // "workflow" has a color already
+        app.world_mut().send_event(test_event("a", None));
+        app.update();
+        app.update();
+        assert_eq!(last_changed(&app), unchanged);
+
+        let mut event = test_event("b", None);
+        event.domain = "shipping".to_string();
+        app.world_mut().send_event(event);
+        app.update();
+        assert_ne!(last_changed(&app), unchanged);
+        assert!(app.world().resource::<DomainColors>().get("shipping").is_some());
+    }
+
+    #[test]
+    fn test_show_correlation_colors_chain_and_dims_others() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<Assets<StandardMaterial>>()
+            .insert_resource(DomainColors::default())
+            .init_resource::<ColorMode>()
+            .init_resource::<ActiveCorrelation>()
+            .init_resource::<EventFilterState>()
+            .init_resource::<Paused>()
+            .add_event::<EventVisualizationCommand>()
+            .add_event::<FocusCamera>()
+            .add_systems(Update, (handle_event_commands, recolor_events).chain());
+
+        let mut spawn_event = |event_id: &str, correlation_id: &str| {
+            let mut event = test_event(event_id, None);
+            event.correlation_id = Some(correlation_id.to_string());
+            let material = app.world_mut().resource_mut::<Assets<StandardMaterial>>().add(Color::WHITE);
+            let material_id = material.id();
+            app.world_mut().spawn((EventVisual::from_event(&event), MeshMaterial3d(material)));
+            material_id
+        };
+        let chain = [spawn_event("a", "corr-1"), spawn_event("b", "corr-1")];
+        let other = spawn_event("c", "corr-2");
+        app.update();
+
+        app.world_mut().send_event(EventVisualizationCommand::ShowCorrelation("corr-1".to_string()));
+        app.update();
+
+        assert_eq!(*app.world().resource::<ColorMode>(), ColorMode::ByCorrelation);
+        let materials = app.world().resource::<Assets<StandardMaterial>>();
+        for id in chain {
+            assert_eq!(materials.get(id).unwrap().base_color, color_for_correlation("corr-1"));
+        }
+        let dimmed = materials.get(other).unwrap().base_color.to_srgba();
+        let undimmed = color_for_correlation("corr-2").to_srgba();
+        assert!(dimmed.red + dimmed.green + dimmed.blue < (undimmed.red + undimmed.green + undimmed.blue) * 0.5);
+    }
+
+    #[test]
+    fn test_shown_chain_hangs_down_as_a_causation_tree() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(EventFlowGraph::new())
+            .insert_resource(ActiveCorrelation(Some("corr-1".to_string())))
+            .add_systems(Update, (update_event_positions, layout_correlation_chain).chain());
+
+        let start = Utc::now();
+        let mut chain = Vec::new();
+        for (index, (event_id, cause)) in [("a", None), ("b", Some("a")), ("c", Some("b")), ("d", Some("c"))].into_iter().enumerate() {
+            let mut event = test_event(event_id, cause);
+            event.timestamp = start + chrono::Duration::seconds(index as i64);
+            app.world_mut().resource_mut::<EventFlowGraph>().link_event(&event);
+            // Jumbled, as the force layout would leave them
+            let position = Vec3::new([3.0, -2.0, 5.0, 0.5][index], [1.0, 4.0, -3.0, 2.0][index], 0.0);
+            chain.push(app.world_mut().spawn((EventVisual::from_event(&event), Transform::from_translation(position))).id());
+        }
+        let mut unrelated = test_event("x", None);
+        unrelated.correlation_id = Some("corr-2".to_string());
+        app.world_mut().spawn((EventVisual::from_event(&unrelated), Transform::default()));
+
+        app.update();
+        app.update();
+
+        let positions: Vec<Vec3> = chain.iter().map(|entity| app.world().get::<Transform>(*entity).unwrap().translation).collect();
+        assert_eq!(positions[0], Vec3::new(3.0, 1.0, 0.0));
+        for pair in positions.windows(2) {
+            assert!(pair[1].y < pair[0].y, "{} is not below {}", pair[1], pair[0]);
+            assert_eq!(pair[1].x, pair[0].x);
+        }
+    }
+
+    #[test]
+    fn test_causation_cycles_are_laid_out() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(EventFlowGraph::new())
+            .insert_resource(ActiveCorrelation(Some("corr-1".to_string())))
+            .add_systems(Update, (update_event_positions, layout_correlation_chain).chain());
+
+        // "a" names itself as its cause; "b" and "c" cause each other
+        let start = Utc::now();
+        let mut chain = Vec::new();
+        for (index, (event_id, cause)) in [("a", Some("a")), ("b", Some("c")), ("c", Some("b"))].into_iter().enumerate() {
+            let mut event = test_event(event_id, cause);
+            event.timestamp = start + chrono::Duration::seconds(index as i64);
+            app.world_mut().resource_mut::<EventFlowGraph>().link_event(&event);
+            chain.push(app.world_mut().spawn((EventVisual::from_event(&event), Transform::default())).id());
+        }
+        let graph = app.world().resource::<EventFlowGraph>();
+        assert!(graph.get_connected("a").iter().all(|id| id != "a"));
+
+        app.update();
+        app.update();
+
+        let positions: Vec<Vec3> = chain.iter().map(|entity| app.world().get::<Transform>(*entity).unwrap().translation).collect();
+        assert!(positions.iter().all(|position| position.is_finite()));
+        assert_ne!(positions[1].y, positions[2].y);
+    }
+
+    #[test]
+    fn test_event_commands_focus_filter_and_pause() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<Assets<StandardMaterial>>()
+            .insert_resource(DomainColors::default())
+            .init_resource::<ColorMode>()
+            .init_resource::<ActiveCorrelation>()
+            .init_resource::<EventFilterState>()
+            .init_resource::<SearchMatcher>()
+            .init_resource::<ConnectionVisibility>()
+            .insert_resource(EdgeFilter::only([crate::events::EdgeRelationship::Contains]))
+            .init_resource::<Paused>()
+            .add_event::<EventVisualizationCommand>()
+            .add_event::<FocusCamera>()
+            .add_systems(Update, (
+                handle_event_commands,
+                apply_filters,
+                crate::culling::apply_hidden_reasons,
+            ).chain());
+
+        let mut spawn_event = |event_id: &str, domain: &str| {
+            let mut event = test_event(event_id, None);
+            event.domain = domain.to_string();
+            app.world_mut().spawn((EventVisual::from_event(&event), Visibility::default())).id()
+        };
+        let workflow = spawn_event("a", "workflow");
+        let agent = spawn_event("b", "agent");
+
+        app.world_mut().send_event(EventVisualizationCommand::FocusEvent("b".to_string()));
+        app.world_mut().send_event(EventVisualizationCommand::FilterByDomain("workflow".to_string()));
+        app.world_mut().send_event(EventVisualizationCommand::TogglePause);
+        app.update();
+
+        let focus: Vec<&FocusCamera> = app.world().resource::<Events<FocusCamera>>()
+            .iter_current_update_events()
+            .collect();
+        assert_eq!(focus.len(), 1);
+        assert_eq!(focus[0].target_entities, vec![agent]);
+        assert!(app.world().get::<Selected>(agent).is_some());
+        assert!(app.world().get::<Selected>(workflow).is_none());
+        assert_eq!(app.world().get::<Visibility>(workflow), Some(&Visibility::Inherited));
+        assert_eq!(app.world().get::<Visibility>(agent), Some(&Visibility::Hidden));
+        assert!(app.world().resource::<Paused>().0);
+
+        app.world_mut().resource_mut::<EventFilterState>().only_errors = true;
+        app.world_mut().resource_mut::<ConnectionVisibility>().temporal = false;
+        app.world_mut().send_event(EventVisualizationCommand::ClearFilters);
+        app.world_mut().send_event(EventVisualizationCommand::TogglePause);
+        app.update();
+        assert_eq!(app.world().get::<Visibility>(agent), Some(&Visibility::Inherited));
+        assert_eq!(app.world().get::<Visibility>(workflow), Some(&Visibility::Inherited));
+        assert_eq!(app.world().resource::<EventFilterState>(), &EventFilterState::default());
+        assert_eq!(app.world().resource::<ConnectionVisibility>(), &ConnectionVisibility::default());
+        assert_eq!(app.world().resource::<EdgeFilter>(), &EdgeFilter::default());
+        assert!(!app.world().resource::<Paused>().0);
+    }
+
+    #[test]
+    fn test_connection_types_are_inferred() {
+        let mut graph = EventFlowGraph::new();
+        let mut event = |event_id: &str, correlation_id: &str, causation_id: Option<&str>| {
+            let mut event = test_event(event_id, causation_id);
+            event.correlation_id = Some(correlation_id.to_string());
+            graph.link_event(&event);
+        };
+        event("a", "corr-1", None);
+        event("b", "corr-2", None);
+        event("c", "corr-1", Some("a"));
+        event("d", "corr-1", None);
+
+        let edges: HashSet<(&str, &str, ConnectionType)> = graph.edges.iter()
+            .flat_map(|(from, to_ids)| to_ids.iter().map(move |(to, kind)| (from.as_str(), to.as_str(), *kind)))
+            .collect();
+        // a -> c is causal, so it isn't repeated as a correlation link, and
+        // c -> d is a correlation link rather than a temporal one
+        assert_eq!(edges, HashSet::from([
+            ("a", "c", ConnectionType::Causation),
+            ("a", "b", ConnectionType::Temporal),
+            ("b", "c", ConnectionType::Temporal),
+            ("c", "d", ConnectionType::Correlation),
+        ]));
+
+        graph.remove_events(&HashSet::from(["d".to_string()]));
+        assert!(graph.latest_event.is_none());
+        assert!(graph.latest_by_correlation.get("corr-1").is_none());
+    }
+
+    #[test]
+    fn test_hidden_connection_types_are_not_drawn() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Mesh>()
+            .init_asset::<StandardMaterial>()
+            .insert_resource(EventFlowGraph::new())
+            .insert_resource(ConnectionVisibility { temporal: false, ..default() })
+            .init_resource::<ConnectionPulse>()
+            .add_systems(Update, update_event_connections);
+
+        for (event_id, x) in [("a", 0.0), ("b", 10.0), ("c", 20.0)] {
+            let visual = EventVisual::from_event(&test_event(event_id, None));
+            app.world_mut().spawn((visual, Transform::from_xyz(x, 0.0, 0.0)));
+        }
+        {
+            let mut graph = app.world_mut().resource_mut::<EventFlowGraph>();
+            graph.add_edge("a".to_string(), "b".to_string(), ConnectionType::Causation);
+            graph.add_edge("b".to_string(), "c".to_string(), ConnectionType::Temporal);
+            graph.add_edge("a".to_string(), "c".to_string(), ConnectionType::Correlation);
+        }
+        app.update();
+
+        let mut kinds: HashMap<ConnectionType, usize> = HashMap::new();
+        for connection in app.world_mut().query::<&EventConnection>().iter(app.world()) {
+            *kinds.entry(connection.connection_type).or_default() += 1;
+        }
+        // Body and arrowhead
+        assert_eq!(kinds.get(&ConnectionType::Causation), Some(&2));
+        assert_eq!(kinds.get(&ConnectionType::Temporal), None);
+        // The 20 unit correlation link is split into dashes
+        assert!(kinds.get(&ConnectionType::Correlation).is_some_and(|dashes| *dashes > 20));
+    }
+
+    #[test]
+    fn test_connections_are_moved_not_respawned() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Mesh>()
+            .init_asset::<StandardMaterial>()
+            .insert_resource(EventFlowGraph::new())
+            .init_resource::<ConnectionVisibility>()
+            .init_resource::<ConnectionPulse>()
+            .add_systems(Update, update_event_connections);
+
+        let events: Vec<Entity> = [("cause", 0.0), ("effect", 10.0)].into_iter()
+            .map(|(event_id, x)| {
+                let visual = EventVisual::from_event(&test_event(event_id, None));
+                app.world_mut().spawn((visual, Transform::from_xyz(x, 0.0, 0.0))).id()
+            })
+            .collect();
+        app.world_mut().resource_mut::<EventFlowGraph>()
+            .add_edge("cause".to_string(), "effect".to_string(), ConnectionType::Causation);
+        app.update();
+
+        let parts = |app: &mut App| -> HashSet<Entity> {
+            app.world_mut().query_filtered::<Entity, With<EventConnection>>().iter(app.world()).collect()
+        };
+        let before = parts(&mut app);
+        assert_eq!(before.len(), 2);
+
+        app.world_mut().get_mut::<Transform>(events[1]).unwrap().translation = Vec3::new(20.0, 0.0, 0.0);
+        app.update();
+        assert_eq!(parts(&mut app), before);
+        let body = app.world_mut()
+            .query::<(&EventConnection, &Transform)>()
+            .iter(app.world())
+            .find(|(connection, _)| connection.part == ConnectionPart::Body)
+            .map(|(_, transform)| *transform)
+            .unwrap();
+        assert_eq!(body.translation, Vec3::new(10.0, 0.0, 0.0));
+        assert!((body.scale.y - 20.0).abs() < 1e-4);
+
+        // A connection whose event is gone is taken away
+        app.world_mut().entity_mut(events[1]).despawn();
+        app.update();
+        assert!(parts(&mut app).is_empty());
+    }
+
+    #[test]
+    fn test_causation_arrowhead_points_at_effect() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Mesh>()
+            .init_asset::<StandardMaterial>()
+            .insert_resource(EventFlowGraph::new())
+            .init_resource::<ConnectionVisibility>()
+            .insert_resource(ConnectionPulse { enabled: true, ..default() })
+            .add_systems(Update, update_event_connections);
+
+        let (cause, effect) = (Vec3::ZERO, Vec3::new(0.0, 0.0, 10.0));
+        for (event_id, position) in [("cause", cause), ("effect", effect)] {
+            let visual = EventVisual::from_event(&test_event(event_id, None));
+            app.world_mut().spawn((visual, Transform::from_translation(position)));
+        }
+        app.world_mut().resource_mut::<EventFlowGraph>()
+            .add_edge("cause".to_string(), "effect".to_string(), ConnectionType::Causation);
+        app.update();
+
+        let parts: Vec<(Vec3, Vec3)> = app.world_mut()
+            .query_filtered::<&Transform, With<EventConnection>>()
+            .iter(app.world())
+            .map(|transform| (transform.translation, transform.rotation * Vec3::Y))
+            .collect();
+        assert_eq!(parts.len(), 3);
+        // The arrowhead sits against the effect and points toward it
+        let arrowhead = parts.iter()
+            .find(|(position, _)| position.distance(effect) < EVENT_SPHERE_RADIUS + ARROWHEAD_LENGTH)
+            .expect("arrowhead at the effect");
+        assert!(arrowhead.1.dot(Vec3::Z) > 0.99);
+        // Body at the midpoint, pulse somewhere along the connection
+        assert!(parts.iter().any(|(position, _)| position.distance(Vec3::new(0.0, 0.0, 5.0)) < 1e-4));
+        assert!(parts.iter().all(|(position, _)| position.x.abs() < 1e-4 && position.y.abs() < 1e-4));
+    }
+
+    #[test]
+    fn test_fed_events_are_visualized() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Mesh>()
+            .init_asset::<StandardMaterial>()
+            .init_resource::<ButtonInput<MouseButton>>()
+            .add_plugins(MockEventSource::default());
+
+        feed_events(&mut app, vec![
+            test_event("a", None),
+            test_event("b", Some("a")),
+            test_event("c", Some("b")),
+        ]);
+        app.update();
+
+        let mut ids: Vec<String> = app.world_mut()
+            .query::<&EventVisual>()
+            .iter(app.world())
+            .map(|event| event.event_id.clone())
+            .collect();
+        ids.sort();
+        assert_eq!(ids, vec!["a", "b", "c"]);
+        assert_eq!(app.world().resource::<EventStore>().get_all_events().len(), 3);
+    }
+
+    #[test]
+    fn test_dropped_feed_reports_disconnected_once() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Mesh>()
+            .init_asset::<StandardMaterial>()
+            .init_resource::<ButtonInput<MouseButton>>()
+            .add_plugins(MockEventSource::default());
+        app.update();
+        assert_eq!(*app.world().resource::<BridgeStatus>(), BridgeStatus::Connected);
+
+        let mut cursor = app.world().resource::<Events<BridgeStatusChanged>>().get_cursor();
+        let mut reported = |app: &App| -> Vec<BridgeStatusChanged> {
+            cursor.read(app.world().resource::<Events<BridgeStatusChanged>>()).cloned().collect()
+        };
+
+        app.world_mut().remove_resource::<EventFeed>();
+        app.update();
+        assert_eq!(reported(&app), vec![BridgeStatusChanged { status: BridgeStatus::Disconnected }]);
+        assert_eq!(*app.world().resource::<BridgeStatus>(), BridgeStatus::Disconnected);
+
+        // Still disconnected, but nothing new to report
+        app.update();
+        assert!(reported(&app).is_empty());
+    }
+
+    #[test]
+    fn test_failed_subscription_keeps_its_error() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Mesh>()
+            .init_asset::<StandardMaterial>()
+            .init_resource::<ButtonInput<MouseButton>>()
+            .add_plugins(MockEventSource::default());
+        let (status_tx, status_rx) = mpsc::unbounded_channel();
+        app.insert_resource(BridgeStatusReceiver(Arc::new(RwLock::new(status_rx))));
+        app.update();
+
+        let mut cursor = app.world().resource::<Events<BridgeStatusChanged>>().get_cursor();
+        // What the subscription task does when subscribing fails: report the
+        // error, then drop its sender
+        status_tx.send(BridgeError::Subscription("no responders".to_string()).into()).unwrap();
+        app.world_mut().remove_resource::<EventFeed>();
+        app.update();
+        app.update();
+
+        let error = BridgeStatus::Error("subscription failed: no responders".to_string());
+        assert_eq!(*app.world().resource::<BridgeStatus>(), error);
+        let reported: Vec<BridgeStatusChanged> = cursor
+            .read(app.world().resource::<Events<BridgeStatusChanged>>())
+            .cloned()
+            .collect();
+        assert_eq!(reported, vec![BridgeStatusChanged { status: error }]);
+    }
+
+    #[test]
+    fn test_exceeding_max_count_evicts_the_oldest() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Mesh>()
+            .init_asset::<StandardMaterial>()
+            .init_resource::<ButtonInput<MouseButton>>()
+            .add_plugins(MockEventSource {
+                retention: RetentionPolicy { max_count: 2, ..default() },
+            });
+
+        feed_events(&mut app, vec![test_event("a", None), test_event("b", Some("a"))]);
+        app.update();
+        feed_events(&mut app, vec![test_event("c", Some("b"))]);
+        app.update();
+
+        let evicted: Vec<_> = app.world().resource::<Events<EventEvicted>>().iter_current_update_events().cloned().collect();
+        assert_eq!(evicted, vec![EventEvicted { event_id: "a".to_string() }]);
+        let stored: Vec<String> = app.world().resource::<EventStore>().get_all_events()
+            .into_iter()
+            .map(|event| event.event_id)
+            .collect();
+        assert_eq!(stored, vec!["b", "c"]);
+        let mut visuals: Vec<String> = app.world_mut()
+            .query::<&EventVisual>()
+            .iter(app.world())
+            .map(|event| event.event_id.clone())
+            .collect();
+        visuals.sort();
+        assert_eq!(visuals, vec!["b", "c"]);
+    }
+
+    #[test]
+    fn test_lowering_max_count_at_runtime_trims_the_store() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Mesh>()
+            .init_asset::<StandardMaterial>()
+            .init_resource::<ButtonInput<MouseButton>>()
+            .add_plugins(MockEventSource::default());
+
+        feed_events(&mut app, vec![test_event("a", None), test_event("b", None), test_event("c", None)]);
+        app.update();
+        assert_eq!(app.world().resource::<EventStore>().get_all_events().len(), 3);
+
+        // No new events arrive; the smaller limit alone trims the store
+        app.world_mut().resource_mut::<RetentionPolicy>().max_count = 1;
+        app.update();
+        let stored: Vec<String> = app.world().resource::<EventStore>().get_all_events()
+            .into_iter()
+            .map(|event| event.event_id)
+            .collect();
+        assert_eq!(stored, vec!["c"]);
+        assert_eq!(app.world_mut().query::<&EventVisual>().iter(app.world()).count(), 1);
+    }
+
+    #[test]
+    fn test_paused_events_stay_in_channel() {
+        let (tx, rx) = mpsc::channel(100);
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(EventReceiver(Arc::new(RwLock::new(rx))))
+            .insert_resource(EventStore::new())
+            .insert_resource(EventFlowGraph::new())
+            .insert_resource(Paused(true))
+            .insert_resource(BridgeStatus::Connected)
+            .add_event::<DomainEventReceived>()
+            .add_event::<BridgeStatusChanged>()
+            .add_systems(Update, process_incoming_events);
+
+        for i in 0..80 {
+            tx.try_send(test_event(&i.to_string(), None)).unwrap();
+        }
+        app.update();
+        app.update();
+        assert_eq!(app.world().resource::<EventReceiver>().0.read().len(), 80);
+        assert!(app.world().resource::<EventStore>().get_all_events().is_empty());
+
+        // Resuming catches up in capped batches
+        app.insert_resource(Paused(false));
+        app.update();
+        assert_eq!(app.world().resource::<EventReceiver>().0.read().len(), 80 - CATCH_UP_EVENTS_PER_FRAME);
+        app.update();
+        app.update();
+        assert_eq!(app.world().resource::<EventReceiver>().0.read().len(), 0);
+    }
+}
@@ -4,9 +4,8 @@
 //! for the NATS event visualization system.
 
 use bevy::prelude::*;
-use std::collections::HashMap;
-use chrono::{DateTime, Utc};
-use crate::nats_event_visualization::{DomainEventReceived, EventStore};
+use chrono::Utc;
+use crate::nats_event_visualization::{DomainEventReceived, StatisticsSummary};
 
 /// Plugin for event visualization UI
 pub struct EventVisualizationUIPlugin;
@@ -14,11 +13,9 @@ pub struct EventVisualizationUIPlugin;
 impl Plugin for EventVisualizationUIPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(EventFilters::default())
-           .insert_resource(EventStatistics::default())
            .insert_resource(UIState::default())
            .add_systems(Startup, setup_ui)
            .add_systems(Update, (
-               update_statistics,
                handle_filter_input,
                update_filter_display,
                update_statistics_display,
@@ -100,29 +97,6 @@ impl EventFilters {
     }
 }
 
-/// Statistics about the event stream
-#[derive(Resource, Default, Debug)]
-pub struct EventStatistics {
-    /// Total events received
-    pub total_events: u64,
-    /// Events per domain
-    pub events_by_domain: HashMap<String, u64>,
-    /// Events per type
-    pub events_by_type: HashMap<String, u64>,
-    /// Average events per second over the last minute
-    pub events_per_second: f32,
-    /// Number of causation chains
-    pub causation_chains: u32,
-    /// Number of correlation groups
-    pub correlation_groups: u32,
-    /// Busiest domain
-    pub busiest_domain: Option<(String, u64)>,
-    /// Most common event type
-    pub most_common_event: Option<(String, u64)>,
-    /// Last update time
-    pub last_update: DateTime<Utc>,
-}
-
 /// UI state management
 #[derive(Resource, Default)]
 struct UIState {
@@ -368,71 +342,6 @@ struct FilterInputText(FilterType);
 #[derive(Component)]
 struct StatisticsDisplay;
 
-/// Update statistics based on event store
-fn update_statistics(
-    event_store: Res<EventStore>,
-    mut statistics: ResMut<EventStatistics>,
-    _time: Res<Time>,
-) {
-    // Only update every second to avoid performance impact
-    let now = Utc::now();
-    if (now - statistics.last_update).num_milliseconds() < 1000 {
-        return;
-    }
-
-    statistics.last_update = now;
-
-    // Calculate statistics from event store
-    let events = event_store.get_recent_events(300); // Last 5 minutes
-    
-    statistics.total_events = events.len() as u64;
-    
-    // Reset counters
-    statistics.events_by_domain.clear();
-    statistics.events_by_type.clear();
-    
-    let mut causation_ids = std::collections::HashSet::new();
-    let mut correlation_ids = std::collections::HashSet::new();
-    
-    for event in events {
-        // Count by domain
-        *statistics.events_by_domain.entry(event.domain.clone()).or_insert(0) += 1;
-        
-        // Count by type
-        *statistics.events_by_type.entry(event.event_type.clone()).or_insert(0) += 1;
-        
-        // Track causation chains
-        if let Some(causation_id) = &event.causation_id {
-            causation_ids.insert(causation_id.clone());
-        }
-        
-        // Track correlation groups
-        if let Some(correlation_id) = &event.correlation_id {
-            correlation_ids.insert(correlation_id.clone());
-        }
-    }
-    
-    statistics.causation_chains = causation_ids.len() as u32;
-    statistics.correlation_groups = correlation_ids.len() as u32;
-    
-    // Find busiest domain
-    statistics.busiest_domain = statistics.events_by_domain.iter()
-        .max_by_key(|(_, count)| *count)
-        .map(|(domain, count)| (domain.clone(), *count));
-    
-    // Find most common event
-    statistics.most_common_event = statistics.events_by_type.iter()
-        .max_by_key(|(_, count)| *count)
-        .map(|(event_type, count)| (event_type.clone(), *count));
-    
-    // Calculate events per second (over the last minute)
-    let one_minute_ago = now - chrono::Duration::seconds(60);
-    let recent_count = events.iter()
-        .filter(|e| e.timestamp > one_minute_ago)
-        .count();
-    statistics.events_per_second = recent_count as f32 / 60.0;
-}
-
 /// Handle filter input changes
 fn handle_filter_input(
     keyboard: Res<ButtonInput<KeyCode>>,
@@ -494,34 +403,16 @@ fn update_filter_display(
     }
 }
 
-/// Update statistics display
+/// Update statistics display with the summary text, whenever it is
+/// recomputed
 fn update_statistics_display(
-    statistics: Res<EventStatistics>,
+    summary: Res<StatisticsSummary>,
     mut stats_display: Query<&mut Text, With<StatisticsDisplay>>,
 ) {
+    if !summary.is_changed() {
+        return;
+    }
     if let Ok(mut text) = stats_display.get_single_mut() {
-        text.0 = format!(
-            "Total Events: {}\n\
-             Events/sec: {:.1}\n\
-             Causation Chains: {}\n\
-             Correlation Groups: {}\n\
-             \n\
-             Busiest Domain:\n  {} ({})\n\
-             \n\
-             Most Common Event:\n  {} ({})\n\
-             \n\
-             Domains: {}\n\
-             Event Types: {}",
-            statistics.total_events,
-            statistics.events_per_second,
-            statistics.causation_chains,
-            statistics.correlation_groups,
-            statistics.busiest_domain.as_ref().map(|(d, _)| d.as_str()).unwrap_or("N/A"),
-            statistics.busiest_domain.as_ref().map(|(_, c)| c).unwrap_or(&0),
-            statistics.most_common_event.as_ref().map(|(e, _)| e.as_str()).unwrap_or("N/A"),
-            statistics.most_common_event.as_ref().map(|(_, c)| c).unwrap_or(&0),
-            statistics.events_by_domain.len(),
-            statistics.events_by_type.len(),
-        );
+        text.0.clone_from(&summary.text);
     }
 }
\ No newline at end of file
@@ -0,0 +1,244 @@
+//! Node Search: Finding nodes by their label
+//!
+//! [`NodeSearchPlugin`]'s search box matches its query against the labels
+//! in the [`GraphViewProjection`], so typing doesn't scan the world's
+//! entities.
+//! Matches are listed below the box, best first: exact labels, then labels
+//! starting with the query, then labels with a word starting with it, then
+//! any other label containing it, all ignoring case. Picking a match selects
+//! its node with [`SelectNode`] and frames it with [`FocusCamera`].
+//!
+//! Ctrl+F shows and hides the search box; Enter picks the best match.
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiPlugin, EguiPrimaryContextPass};
+use cim_contextgraph::NodeId;
+use crate::camera::CameraAnimationPlugin;
+use crate::events::{FocusCamera, SelectNode};
+use crate::projections::{GraphViewProjection, ProjectionPlugin};
+use crate::selection::SelectionPlugin;
+
+/// Visibility and result settings of the node search box
+#[derive(Resource, Debug, Clone)]
+pub struct NodeSearchConfig {
+    pub visible: bool,
+    /// Most matches listed at once
+    pub max_results: usize,
+    /// Seconds the camera takes to frame a picked node
+    pub transition_duration: f32,
+}
+
+impl Default for NodeSearchConfig {
+    fn default() -> Self {
+        Self {
+            visible: false,
+            max_results: 10,
+            transition_duration: 0.3,
+        }
+    }
+}
+
+/// How well a label matches the query, best first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MatchQuality {
+    Exact,
+    Prefix,
+    WordStart,
+    Substring,
+}
+
+/// A node whose label matches the search query
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeMatch {
+    pub node_id: NodeId,
+    pub entity: Entity,
+    pub label: String,
+    pub quality: MatchQuality,
+}
+
+/// The current query and its matches
+#[derive(Resource, Debug, Clone, Default)]
+pub struct NodeSearch {
+    pub query: String,
+    pub matches: Vec<NodeMatch>,
+}
+
+/// Plugin that shows a search box for jumping to nodes by label
+pub struct NodeSearchPlugin;
+
+impl Plugin for NodeSearchPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugins(EguiPlugin::default());
+        }
+        // Labels come from the projection; picks are handled by selection
+        // and the camera animation
+        if !app.is_plugin_added::<ProjectionPlugin>() {
+            app.add_plugins(ProjectionPlugin);
+        }
+        if !app.is_plugin_added::<SelectionPlugin>() {
+            app.add_plugins(SelectionPlugin);
+        }
+        if !app.is_plugin_added::<CameraAnimationPlugin>() {
+            app.add_plugins(CameraAnimationPlugin);
+        }
+
+        app.init_resource::<NodeSearchConfig>()
+            .init_resource::<NodeSearch>()
+            .add_systems(Update, toggle_node_search)
+            .add_systems(EguiPrimaryContextPass, draw_node_search);
+    }
+}
+
+/// How `label` matches `query`, which must already be lowercase
+fn match_quality(label: &str, query: &str) -> Option<MatchQuality> {
+    let label = label.to_lowercase();
+    let position = label.find(query)?;
+    if label == query {
+        Some(MatchQuality::Exact)
+    } else if position == 0 {
+        Some(MatchQuality::Prefix)
+    } else if label.match_indices(query).any(|(index, _)| {
+        label[..index].chars().next_back().is_some_and(|before| !before.is_alphanumeric())
+    }) {
+        Some(MatchQuality::WordStart)
+    } else {
+        Some(MatchQuality::Substring)
+    }
+}
+
+/// Up to `limit` nodes of `projection` whose label contains `query`, best
+/// match first and, within the same quality, shortest label first
+pub fn search_nodes(projection: &GraphViewProjection, query: &str, limit: usize) -> Vec<NodeMatch> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    let mut matches: Vec<NodeMatch> = projection.nodes.iter()
+        .filter_map(|(node_id, node)| {
+            let quality = match_quality(&node.metadata.label, &query)?;
+            Some(NodeMatch {
+                node_id: *node_id,
+                entity: node.entity,
+                label: node.metadata.label.clone(),
+                quality,
+            })
+        })
+        .collect();
+    matches.sort_by(|a, b| {
+        (a.quality, a.label.len(), &a.label).cmp(&(b.quality, b.label.len(), &b.label))
+    });
+    matches.truncate(limit);
+    matches
+}
+
+/// System that shows or hides the search box on Ctrl+F
+pub fn toggle_node_search(
+    keyboard: Option<Res<ButtonInput<KeyCode>>>,
+    mut config: ResMut<NodeSearchConfig>,
+) {
+    let Some(keyboard) = keyboard else {
+        return;
+    };
+    if keyboard.just_pressed(KeyCode::KeyF)
+        && keyboard.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+    {
+        config.visible = !config.visible;
+    }
+}
+
+/// System that draws the search box and its matches, selecting and
+/// framing the node picked
+pub fn draw_node_search(
+    mut contexts: EguiContexts,
+    config: Res<NodeSearchConfig>,
+    mut search: ResMut<NodeSearch>,
+    projection: Res<GraphViewProjection>,
+    mut select: EventWriter<SelectNode>,
+    mut focus_camera: EventWriter<FocusCamera>,
+) {
+    if !config.visible {
+        return;
+    }
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    let mut picked = None;
+    egui::Area::new(egui::Id::new("cim_node_search"))
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 10.0))
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut search.query)
+                        .hint_text("Search nodes")
+                        .desired_width(240.0),
+                );
+                // Labels change under a standing query too
+                if response.changed() || projection.is_changed() {
+                    search.matches = search_nodes(&projection, &search.query, config.max_results);
+                }
+                if response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter)) {
+                    picked = search.matches.first().cloned();
+                }
+
+                for found in &search.matches {
+                    if ui.selectable_label(false, &found.label).clicked() {
+                        picked = Some(found.clone());
+                    }
+                }
+                if !search.query.trim().is_empty() && search.matches.is_empty() {
+                    ui.weak("No matching nodes");
+                }
+            });
+        });
+
+    if let Some(found) = picked {
+        select.write(SelectNode { node_id: found.node_id, multi_select: false });
+        focus_camera.write(FocusCamera {
+            target_entities: vec![found.entity],
+            target_point: None,
+            transition_duration: config.transition_duration,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::projections::NodeView;
+    use crate::value_objects::NodeMetadata;
+
+    fn projection(labels: &[&str]) -> GraphViewProjection {
+        let mut projection = GraphViewProjection::default();
+        for (index, label) in labels.iter().enumerate() {
+            projection.nodes.insert(NodeId::new(), NodeView {
+                entity: Entity::from_raw(index as u32),
+                position: Vec3::ZERO,
+                metadata: NodeMetadata { label: label.to_string(), ..default() },
+                is_selected: false,
+            });
+        }
+        projection
+    }
+
+    #[test]
+    fn test_unique_label_finds_exactly_that_node() {
+        let projection = projection(&["Orders", "Billing", "Shipping", "Returns"]);
+        let matches = search_nodes(&projection, "billing", 10);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].label, "Billing");
+        assert_eq!(matches[0].quality, MatchQuality::Exact);
+        assert_eq!(projection.nodes[&matches[0].node_id].entity, matches[0].entity);
+        assert!(search_nodes(&projection, "  ", 10).is_empty());
+    }
+
+    #[test]
+    fn test_matches_are_ranked_by_quality() {
+        let projection = projection(&["Order history", "Back order", "Reorder", "Order", "Orders"]);
+        let labels: Vec<String> = search_nodes(&projection, "ORDER", 10).into_iter().map(|found| found.label).collect();
+        assert_eq!(labels, vec!["Order", "Orders", "Order history", "Back order", "Reorder"]);
+        assert_eq!(search_nodes(&projection, "order", 2).len(), 2);
+    }
+}
@@ -0,0 +1,200 @@
+//! Outlines: Marking selected and highlighted nodes
+//!
+//! A node that is [`Selected`] or [`Highlighted`] gets an inverted hull: a
+//! child sharing the node's mesh, scaled up slightly and drawn unlit with
+//! only its back faces, so it shows as a rim around the node whatever the
+//! node's own color. [`Highlighted`] nodes are outlined in their highlight
+//! color, brightened by its intensity; other selected nodes use
+//! [`OutlineConfig::selection_color`].
+
+use bevy::pbr::{NotShadowCaster, NotShadowReceiver};
+use bevy::prelude::*;
+use bevy::render::render_resource::Face;
+use std::collections::HashMap;
+use crate::components::{Highlighted, NodeVisual, Selected};
+
+/// Look of the outlines
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct OutlineConfig {
+    /// Outline color of selected nodes that are not highlighted
+    pub selection_color: Color,
+    /// How far the hull extends past the node, relative to its size
+    pub thickness: f32,
+}
+
+impl Default for OutlineConfig {
+    fn default() -> Self {
+        Self {
+            selection_color: Color::srgb(1.0, 0.75, 0.1),
+            thickness: 0.08,
+        }
+    }
+}
+
+/// The outline hull of a node, and what it was built from
+#[derive(Component, Debug, Clone)]
+pub struct OutlineShell {
+    pub hull: Entity,
+    color: LinearRgba,
+    mesh: AssetId<Mesh>,
+    thickness: f32,
+}
+
+/// Marks an outline hull
+#[derive(Component, Debug, Clone, Copy)]
+pub struct OutlineHull;
+
+/// Unlit back-face materials, one per outline color
+#[derive(Resource, Debug, Default)]
+pub struct OutlineMaterials {
+    materials: HashMap<[u32; 4], Handle<StandardMaterial>>,
+}
+
+impl OutlineMaterials {
+    /// Outline material drawing `color`
+    pub fn material(&mut self, color: LinearRgba, materials: &mut Assets<StandardMaterial>) -> Handle<StandardMaterial> {
+        let key = color.to_f32_array().map(f32::to_bits);
+        self.materials
+            .entry(key)
+            .or_insert_with(|| {
+                materials.add(StandardMaterial {
+                    base_color: color.into(),
+                    unlit: true,
+                    // Only the far side of the enlarged hull is drawn, so
+                    // the node in front of it stays visible
+                    cull_mode: Some(Face::Front),
+                    ..default()
+                })
+            })
+            .clone()
+    }
+}
+
+/// Plugin that outlines selected and highlighted nodes
+pub struct OutlinePlugin;
+
+impl Plugin for OutlinePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OutlineConfig>()
+            .init_resource::<OutlineMaterials>()
+            .add_systems(PostUpdate, update_outlines);
+    }
+}
+
+/// Outline color for a node, if it should have one
+pub fn outline_color(highlighted: Option<&Highlighted>, selected: bool, config: &OutlineConfig) -> Option<LinearRgba> {
+    match highlighted {
+        Some(highlighted) => {
+            let color = highlighted.color.to_linear();
+            Some((color * highlighted.intensity.max(0.0)).with_alpha(color.alpha))
+        }
+        None if selected => Some(config.selection_color.to_linear()),
+        None => None,
+    }
+}
+
+/// System that adds, updates and removes outline hulls as nodes are
+/// selected and highlighted
+#[allow(clippy::type_complexity)]
+pub fn update_outlines(
+    mut commands: Commands,
+    config: Res<OutlineConfig>,
+    mut outline_materials: ResMut<OutlineMaterials>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    nodes: Query<
+        (Entity, &Mesh3d, Option<&Highlighted>, Has<Selected>, Option<&OutlineShell>),
+        With<NodeVisual>,
+    >,
+    mut hulls: Query<(&mut Mesh3d, &mut MeshMaterial3d<StandardMaterial>, &mut Transform), (With<OutlineHull>, Without<NodeVisual>)>,
+) {
+    for (entity, mesh, highlighted, selected, shell) in nodes.iter() {
+        let color = outline_color(highlighted, selected, &config);
+        let Some(color) = color else {
+            if let Some(shell) = shell {
+                commands.entity(shell.hull).despawn();
+                commands.entity(entity).remove::<OutlineShell>();
+            }
+            continue;
+        };
+
+        let unchanged = shell.is_some_and(|shell| {
+            shell.color == color && shell.mesh == mesh.id() && shell.thickness == config.thickness
+        });
+        if unchanged {
+            continue;
+        }
+
+        let material = outline_materials.material(color, &mut materials);
+        let scale = Transform::from_scale(Vec3::splat(1.0 + config.thickness));
+        let hull = match shell.and_then(|shell| hulls.get_mut(shell.hull).ok().map(|hull| (shell.hull, hull))) {
+            Some((hull, (mut hull_mesh, mut hull_material, mut transform))) => {
+                hull_mesh.0 = mesh.0.clone();
+                hull_material.0 = material;
+                *transform = scale;
+                hull
+            }
+            None => commands.spawn((
+                OutlineHull,
+                Mesh3d(mesh.0.clone()),
+                MeshMaterial3d(material),
+                scale,
+                NotShadowCaster,
+                NotShadowReceiver,
+                ChildOf(entity),
+            )).id(),
+        };
+        commands.entity(entity).insert(OutlineShell {
+            hull,
+            color,
+            mesh: mesh.id(),
+            thickness: config.thickness,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cim_contextgraph::{NodeId, ContextGraphId as GraphId};
+
+    #[test]
+    fn test_outline_follows_selection_and_highlight() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Mesh>()
+            .init_asset::<StandardMaterial>()
+            .add_plugins(OutlinePlugin);
+
+        let mesh = app.world_mut().resource_mut::<Assets<Mesh>>().add(Cuboid::default());
+        let node = app.world_mut().spawn((
+            NodeVisual { node_id: NodeId::new(), graph_id: GraphId::new() },
+            Mesh3d(mesh.clone()),
+        )).id();
+        app.update();
+        assert!(app.world().get::<OutlineShell>(node).is_none());
+
+        let hull_color = |app: &App| {
+            let shell = app.world().get::<OutlineShell>(node).unwrap();
+            let material = app.world().get::<MeshMaterial3d<StandardMaterial>>(shell.hull).unwrap();
+            app.world().resource::<Assets<StandardMaterial>>().get(&material.0).unwrap().base_color
+        };
+
+        app.world_mut().entity_mut(node).insert(Selected);
+        app.update();
+        let hull = app.world().get::<OutlineShell>(node).unwrap().hull;
+        assert_eq!(app.world().get::<Mesh3d>(hull).unwrap().0, mesh);
+        assert_eq!(app.world().get::<ChildOf>(hull).unwrap().parent(), node);
+        assert_eq!(hull_color(&app).to_linear(), OutlineConfig::default().selection_color.to_linear());
+
+        // The highlight color wins, brightened by its intensity
+        app.world_mut().entity_mut(node).insert(Highlighted { color: Color::linear_rgb(0.0, 0.5, 0.0), intensity: 2.0 });
+        app.update();
+        assert_eq!(app.world().get::<OutlineShell>(node).unwrap().hull, hull);
+        assert_eq!(hull_color(&app).to_linear(), LinearRgba::rgb(0.0, 1.0, 0.0));
+
+        app.world_mut().entity_mut(node).remove::<(Selected, Highlighted)>();
+        app.update();
+        assert!(app.world().get::<OutlineShell>(node).is_none());
+        assert!(app.world().get_entity(hull).is_err());
+    }
+}
@@ -0,0 +1,462 @@
+//! Picking: Mapping pointer rays back to visual nodes and edges
+//!
+//! Casts a ray from the cursor through every active [`GraphCamera`] and tests
+//! it against the bounding box of each [`NodeVisual`], in the node's own
+//! space so rotation and non-uniform scale are respected, and against the
+//! path of each meshed edge ([`EdgeMesh`]). [`pick_nearest`] returns the
+//! hit nearest along the ray, node or edge. A node hit is reported through
+//! [`NodeHovered`], [`NodeUnhovered`] and [`NodeClicked`], and marked
+//! [`Hovered`](crate::components::Hovered) by [`crate::hover::hover`]; an
+//! edge hit is clicked through [`EdgeClicked`].
+//!
+//! When graphs are shown side by side in [`GraphRegion`]s, only nodes of the
+//! graph whose region is under the cursor are picked, and clicking in a
+//! region makes its graph the [`ActiveGraph`].
+//!
+//! [`screen_ray`], [`world_to_screen`] and [`cursor_position`] are the one
+//! place screen and world positions are converted, for picking and every
+//! other system that needs it. [`egui_wants_keyboard`] keeps bare-letter
+//! hotkeys from firing while text is typed into a panel.
+
+use bevy::prelude::*;
+use bevy::render::primitives::Aabb;
+use bevy_egui::EguiContext;
+use cim_contextgraph::ContextGraphId as GraphId;
+use crate::components::{EdgeVisual, GraphCamera, GraphRegion, NodeVisual};
+use crate::culling::{CollapsedMember, HiddenRelationship};
+use crate::edge_mesh::EdgeMesh;
+use crate::events::{EdgeClicked, NodeClicked, NodeHovered, NodeUnhovered};
+use crate::hover::hover;
+use crate::morphisms::{NodeEntityMap, NodeEntityMapPlugin};
+use crate::resources::ActiveGraph;
+
+/// Bounds used for nodes that have no computed [`Aabb`] (e.g. no mesh yet)
+const DEFAULT_NODE_HALF_EXTENT: f32 = 0.5;
+
+/// Extra distance around an edge's tube that still counts as hitting it,
+/// so thin edges stay easy to click
+const EDGE_PICK_TOLERANCE: f32 = 0.1;
+
+/// System sets for pointer interaction, in execution order
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PickingSet {
+    /// Raycast against nodes and edges and emit hover/click events
+    Pick,
+    /// Systems that react to picking results, such as selection handling
+    Selection,
+}
+
+/// The node currently under the cursor, if any, and the graph whose
+/// [`GraphRegion`] is
+#[derive(Resource, Debug, Default, Clone, Copy)]
+pub struct PickingState {
+    pub hovered: Option<Entity>,
+    pub graph: Option<GraphId>,
+}
+
+/// Plugin that emits node hover and click events, and edge click events,
+/// from the mouse cursor
+pub struct PickingPlugin;
+
+impl Plugin for PickingPlugin {
+    fn build(&self, app: &mut App) {
+        // Picked entities are resolved to nodes through the map
+        if !app.is_plugin_added::<NodeEntityMapPlugin>() {
+            app.add_plugins(NodeEntityMapPlugin);
+        }
+
+        app.add_event::<NodeClicked>()
+            .add_event::<EdgeClicked>()
+            .add_event::<NodeHovered>()
+            .add_event::<NodeUnhovered>()
+            .init_resource::<PickingState>()
+            .init_resource::<ActiveGraph>()
+            .configure_sets(Update, (PickingSet::Pick, PickingSet::Selection).chain())
+            .add_systems(Update, (pick_nodes_and_edges, hover).chain().in_set(PickingSet::Pick));
+    }
+}
+
+/// Position of the cursor, in logical pixels, in the first window that has
+/// one. Unlike `Query::single` this works with any number of windows.
+pub fn cursor_position<'a>(windows: impl IntoIterator<Item = &'a Window>) -> Option<Vec2> {
+    windows.into_iter().find_map(|window| window.cursor_position())
+}
+
+/// Run condition: an egui widget, such as a search field, has keyboard
+/// focus. Hotkeys without a modifier are gated on its negation.
+pub fn egui_wants_keyboard(mut contexts: Query<&mut EguiContext>) -> bool {
+    contexts.iter_mut().any(|mut context| context.get_mut().wants_keyboard_input())
+}
+
+/// World-space ray from `camera` through `cursor`, a window position in
+/// logical pixels. `None` if the camera has not been rendered yet.
+pub fn screen_ray(camera: &Camera, camera_transform: &GlobalTransform, cursor: Vec2) -> Option<Ray3d> {
+    camera.viewport_to_world(camera_transform, cursor).ok()
+}
+
+/// Window position, in logical pixels, that `world` projects to through
+/// `camera`. `None` if the point is behind the camera or beyond its far
+/// plane; points beside the viewport are still projected.
+pub fn world_to_screen(camera: &Camera, camera_transform: &GlobalTransform, world: Vec3) -> Option<Vec2> {
+    camera.world_to_viewport(camera_transform, world).ok()
+}
+
+/// Ray through `cursor` from the first active camera whose viewport
+/// contains it
+pub fn cursor_ray<'a>(
+    cameras: impl IntoIterator<Item = (&'a Camera, &'a GlobalTransform)>,
+    cursor: Vec2,
+) -> Option<Ray3d> {
+    cameras.into_iter()
+        .filter(|(camera, _)| camera.is_active)
+        .filter(|(camera, _)| {
+            camera.logical_viewport_rect()
+                .is_some_and(|rect| rect.contains(cursor))
+        })
+        .find_map(|(camera, camera_transform)| screen_ray(camera, camera_transform, cursor))
+}
+
+/// Distance along `ray` to the first intersection with `aabb`, where `aabb`
+/// is in the local space of `transform`.
+///
+/// Returns `None` if the ray misses or the box is entirely behind the origin.
+pub fn ray_aabb_distance(ray: Ray3d, transform: &GlobalTransform, aabb: &Aabb) -> Option<f32> {
+    // Bring the ray into the node's local space. The direction is left
+    // unnormalized so that distances along it stay in world units.
+    let world_to_local = transform.affine().inverse();
+    let origin = world_to_local.transform_point3(ray.origin);
+    let direction = world_to_local.transform_vector3(ray.direction.as_vec3());
+
+    let min = Vec3::from(aabb.center - aabb.half_extents);
+    let max = Vec3::from(aabb.center + aabb.half_extents);
+
+    let mut t_near = f32::NEG_INFINITY;
+    let mut t_far = f32::INFINITY;
+    for axis in 0..3 {
+        if direction[axis].abs() < f32::EPSILON {
+            if origin[axis] < min[axis] || origin[axis] > max[axis] {
+                return None;
+            }
+            continue;
+        }
+        let t1 = (min[axis] - origin[axis]) / direction[axis];
+        let t2 = (max[axis] - origin[axis]) / direction[axis];
+        t_near = t_near.max(t1.min(t2));
+        t_far = t_far.min(t1.max(t2));
+    }
+
+    if t_near > t_far || t_far < 0.0 {
+        return None;
+    }
+    Some(t_near.max(0.0))
+}
+
+/// Distance along `ray` to where it passes within `radius` of the polyline
+/// through `points`, taking the nearest approach to each segment.
+///
+/// Returns `None` if the ray never comes that close in front of its origin.
+pub fn ray_polyline_distance(ray: Ray3d, points: &[Vec3], radius: f32) -> Option<f32> {
+    let direction = ray.direction.as_vec3();
+    points.windows(2)
+        .filter_map(|segment| {
+            let (start, end) = (segment[0], segment[1]);
+            let along = end - start;
+            let offset = ray.origin - start;
+            let length_squared = along.length_squared();
+
+            // Closest points between the ray's line and the segment, then
+            // clamped onto the segment and in front of the ray origin
+            let on_segment = if length_squared < f32::EPSILON {
+                0.0
+            } else {
+                let cross = direction.dot(along);
+                let denominator = length_squared - cross * cross;
+                let t = if denominator.abs() < f32::EPSILON {
+                    0.0
+                } else {
+                    (along.dot(offset) - cross * direction.dot(offset)) / denominator
+                };
+                t.clamp(0.0, 1.0)
+            };
+            let on_ray = direction.dot(start + along * on_segment - ray.origin).max(0.0);
+            let on_segment = if length_squared < f32::EPSILON {
+                0.0
+            } else {
+                (along.dot(ray.get_point(on_ray) - start) / length_squared).clamp(0.0, 1.0)
+            };
+
+            let miss = ray.get_point(on_ray).distance(start + along * on_segment);
+            (miss <= radius).then_some(on_ray)
+        })
+        .min_by(f32::total_cmp)
+}
+
+/// Find the nearest entity whose bounds are hit by `ray`
+pub fn nearest_node_hit<'a>(
+    ray: Ray3d,
+    nodes: impl IntoIterator<Item = (Entity, &'a GlobalTransform, Option<&'a Aabb>)>,
+) -> Option<(Entity, f32)> {
+    let default_aabb = Aabb {
+        center: Vec3A::ZERO,
+        half_extents: Vec3A::splat(DEFAULT_NODE_HALF_EXTENT),
+    };
+
+    nodes
+        .into_iter()
+        .filter_map(|(entity, transform, aabb)| {
+            ray_aabb_distance(ray, transform, aabb.unwrap_or(&default_aabb))
+                .map(|distance| (entity, distance))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+}
+
+/// What a ray hit first, and how far along the ray
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PickResult {
+    Node { entity: Entity, distance: f32 },
+    Edge { entity: Entity, distance: f32 },
+}
+
+impl PickResult {
+    pub fn entity(&self) -> Entity {
+        match self {
+            PickResult::Node { entity, .. } | PickResult::Edge { entity, .. } => *entity,
+        }
+    }
+
+    pub fn distance(&self) -> f32 {
+        match self {
+            PickResult::Node { distance, .. } | PickResult::Edge { distance, .. } => *distance,
+        }
+    }
+}
+
+/// The node or edge whose surface `ray` enters first. Edges are given as
+/// their path and the radius within which the ray counts as hitting them.
+pub fn pick_nearest<'a>(
+    ray: Ray3d,
+    nodes: impl IntoIterator<Item = (Entity, &'a GlobalTransform, Option<&'a Aabb>)>,
+    edges: impl IntoIterator<Item = (Entity, &'a [Vec3], f32)>,
+) -> Option<PickResult> {
+    let node = nearest_node_hit(ray, nodes)
+        .map(|(entity, distance)| PickResult::Node { entity, distance });
+    let edge = edges.into_iter()
+        .filter_map(|(entity, path, radius)| {
+            ray_polyline_distance(ray, path, radius).map(|distance| PickResult::Edge { entity, distance })
+        })
+        .min_by(|a, b| a.distance().total_cmp(&b.distance()));
+
+    // A node wins a tie, as an edge ends inside the nodes it joins
+    match (node, edge) {
+        (Some(node), Some(edge)) if edge.distance() < node.distance() => Some(edge),
+        (Some(node), _) => Some(node),
+        (None, edge) => edge,
+    }
+}
+
+/// The graph of the nearest [`GraphRegion`] hit by `ray`
+pub fn region_hit<'a>(ray: Ray3d, regions: impl IntoIterator<Item = &'a GraphRegion>) -> Option<GraphId> {
+    regions.into_iter()
+        .filter_map(|region| {
+            let aabb = Aabb {
+                center: Vec3A::ZERO,
+                half_extents: Vec3A::from(region.bounds * 0.5),
+            };
+            ray_aabb_distance(ray, &GlobalTransform::from_translation(region.origin), &aabb)
+                .map(|distance| (region.graph_id, distance))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(graph_id, _)| graph_id)
+}
+
+/// Nodes that can be picked; nodes folded into a meta-node can't
+type PickableNode = (With<NodeVisual>, Without<CollapsedMember>);
+
+/// Edges that are shown, and so can be picked
+type PickableEdge = (Without<CollapsedMember>, Without<HiddenRelationship>);
+
+/// Raycast from the cursor and emit hover/click events for the nearest node
+/// or edge of the graph under the cursor. Only nodes are hovered; an edge in
+/// front of a node hides it.
+#[allow(clippy::too_many_arguments)]
+fn pick_nodes_and_edges(
+    mut state: ResMut<PickingState>,
+    mut active_graph: ResMut<ActiveGraph>,
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window>,
+    cameras: Query<(&Camera, &GlobalTransform), With<GraphCamera>>,
+    node_map: Res<NodeEntityMap>,
+    nodes: Query<(Entity, &NodeVisual, &GlobalTransform, Option<&Aabb>), PickableNode>,
+    edges: Query<(Entity, &EdgeVisual, &EdgeMesh), PickableEdge>,
+    regions: Query<&GraphRegion>,
+    mut clicked: EventWriter<NodeClicked>,
+    mut edge_clicked: EventWriter<EdgeClicked>,
+    mut hovered: EventWriter<NodeHovered>,
+    mut unhovered: EventWriter<NodeUnhovered>,
+) {
+    let ray = cursor_position(windows.iter()).and_then(|cursor| cursor_ray(cameras.iter(), cursor));
+    state.graph = ray.and_then(|ray| region_hit(ray, regions.iter()));
+
+    let in_graph = |graph_id: GraphId| state.graph.is_none_or(|graph| graph == graph_id);
+    let hit = ray.and_then(|ray| {
+        pick_nearest(
+            ray,
+            nodes.iter()
+                .filter(|(_, node, ..)| in_graph(node.graph_id))
+                .map(|(entity, _, transform, aabb)| (entity, transform, aabb)),
+            edges.iter()
+                .filter(|(_, edge, _)| in_graph(edge.graph_id))
+                .map(|(entity, _, mesh)| (entity, mesh.path.as_slice(), mesh.radius + EDGE_PICK_TOLERANCE)),
+        )
+    });
+    let hit_entity = match hit {
+        Some(PickResult::Node { entity, .. }) => Some(entity),
+        _ => None,
+    };
+
+    if state.hovered != hit_entity {
+        if let Some(previous) = state.hovered {
+            if let Some(&node_id) = node_map.get_node(&previous) {
+                unhovered.write(NodeUnhovered {
+                    entity: previous,
+                    node_id,
+                });
+            }
+        }
+        if let Some(current) = hit_entity {
+            if let Some(&node_id) = node_map.get_node(&current) {
+                hovered.write(NodeHovered {
+                    entity: current,
+                    node_id,
+                });
+            }
+        }
+        state.hovered = hit_entity;
+    }
+
+    if mouse_button.just_pressed(MouseButton::Left) {
+        if state.graph.is_some() && active_graph.graph_id != state.graph {
+            active_graph.graph_id = state.graph;
+        }
+        match hit {
+            Some(PickResult::Node { entity, .. }) => {
+                if let Some(&node_id) = node_map.get_node(&entity) {
+                    clicked.write(NodeClicked {
+                        entity,
+                        node_id,
+                    });
+                }
+            }
+            Some(PickResult::Edge { entity, .. }) => {
+                if let Ok((_, edge, _)) = edges.get(entity) {
+                    edge_clicked.write(EdgeClicked {
+                        entity,
+                        edge_id: edge.edge_id,
+                    });
+                }
+            }
+            None => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit_box() -> Aabb {
+        Aabb {
+            center: Vec3A::ZERO,
+            half_extents: Vec3A::splat(0.5),
+        }
+    }
+
+    fn ray_along_z(x: f32, y: f32) -> Ray3d {
+        Ray3d::new(Vec3::new(x, y, 10.0), Dir3::NEG_Z)
+    }
+
+    #[test]
+    fn test_ray_hits_box_at_front_face() {
+        let transform = GlobalTransform::from_translation(Vec3::new(0.0, 0.0, 2.0));
+        let distance = ray_aabb_distance(ray_along_z(0.0, 0.0), &transform, &unit_box());
+        assert!((distance.unwrap() - 7.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_ray_respects_scale() {
+        let ray = ray_along_z(1.5, 0.0);
+        let unscaled = GlobalTransform::IDENTITY;
+        assert!(ray_aabb_distance(ray, &unscaled, &unit_box()).is_none());
+
+        let scaled = GlobalTransform::from(Transform::from_scale(Vec3::splat(4.0)));
+        let distance = ray_aabb_distance(ray, &scaled, &unit_box());
+        assert!((distance.unwrap() - 8.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_box_behind_ray_is_missed() {
+        let transform = GlobalTransform::from_translation(Vec3::new(0.0, 0.0, 20.0));
+        assert!(ray_aabb_distance(ray_along_z(0.0, 0.0), &transform, &unit_box()).is_none());
+    }
+
+    #[test]
+    fn test_unrendered_camera_projects_nothing() {
+        let camera = Camera::default();
+        let transform = GlobalTransform::from_translation(Vec3::Z * 10.0);
+        assert!(screen_ray(&camera, &transform, Vec2::new(10.0, 10.0)).is_none());
+        assert!(world_to_screen(&camera, &transform, Vec3::ZERO).is_none());
+        assert!(cursor_ray([(&camera, &transform)], Vec2::ZERO).is_none());
+        assert!(cursor_position(&[Window::default()]).is_none());
+    }
+
+    #[test]
+    fn test_ray_passing_close_to_polyline_hits_it() {
+        let polyline = [Vec3::new(-2.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 2.0, -4.0)];
+        let distance = ray_polyline_distance(ray_along_z(-1.0, 0.05), &polyline, 0.1);
+        assert!((distance.unwrap() - 10.0).abs() < 1e-3);
+        // The second segment is further along the ray than the first
+        let distance = ray_polyline_distance(ray_along_z(0.0, 1.0), &polyline, 0.1);
+        assert!((distance.unwrap() - 12.0).abs() < 1e-3);
+
+        assert!(ray_polyline_distance(ray_along_z(-1.0, 0.5), &polyline, 0.1).is_none());
+        assert!(ray_polyline_distance(ray_along_z(-3.0, 0.0), &polyline, 0.1).is_none());
+        let behind = Ray3d::new(Vec3::new(-1.0, 0.0, 10.0), Dir3::Z);
+        assert!(ray_polyline_distance(behind, &polyline, 0.1).is_none());
+    }
+
+    #[test]
+    fn test_nearest_hit_wins() {
+        let far = GlobalTransform::from_translation(Vec3::new(0.0, 0.0, -5.0));
+        let near = GlobalTransform::from_translation(Vec3::new(0.0, 0.0, 3.0));
+        let aabb = unit_box();
+        let far_entity = Entity::from_raw(1);
+        let near_entity = Entity::from_raw(2);
+
+        let hit = nearest_node_hit(
+            ray_along_z(0.0, 0.0),
+            [(far_entity, &far, Some(&aabb)), (near_entity, &near, None)],
+        );
+        assert_eq!(hit.map(|(entity, _)| entity), Some(near_entity));
+    }
+
+    #[test]
+    fn test_nearest_of_overlapping_nodes_and_edges_is_picked() {
+        let far = GlobalTransform::from_translation(Vec3::new(0.0, 0.0, -1.0));
+        let near = GlobalTransform::from_translation(Vec3::new(0.2, 0.0, -0.6));
+        let aabb = unit_box();
+        let (far_node, near_node, edge) = (Entity::from_raw(1), Entity::from_raw(2), Entity::from_raw(3));
+        // The overlapping nodes listed farther first, so the first hit isn't the nearest
+        let nodes = [(far_node, &far, Some(&aabb)), (near_node, &near, Some(&aabb))];
+
+        let path = [Vec3::new(-2.0, 0.0, 5.0), Vec3::new(2.0, 0.0, 5.0)];
+        let behind = [Vec3::new(-2.0, 0.0, -5.0), Vec3::new(2.0, 0.0, -5.0)];
+        let hit = pick_nearest(ray_along_z(0.0, 0.0), nodes, [(edge, behind.as_slice(), 0.1)]);
+        assert_eq!(hit.map(|hit| hit.entity()), Some(near_node));
+        assert!((hit.unwrap().distance() - 10.1).abs() < 1e-4);
+
+        // An edge in front of the nodes is picked instead
+        let hit = pick_nearest(ray_along_z(0.0, 0.0), nodes, [(edge, path.as_slice(), 0.1)]);
+        assert!(matches!(hit, Some(PickResult::Edge { entity, .. }) if entity == edge));
+        assert!(pick_nearest(ray_along_z(5.0, 5.0), nodes, [(edge, path.as_slice(), 0.1)]).is_none());
+    }
+}
@@ -7,7 +7,57 @@ use bevy::prelude::*;
 use crate::events::*;
 use crate::resources::*;
 use crate::bridge::AsyncSyncBridge;
+use crate::handlers::CommandHandlerPlugin;
+use crate::projections::ProjectionPlugin;
+use crate::queries::QueryHandlerPlugin;
 
+/// System sets for the visualization pipeline, in execution order within
+/// `Update`
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CimSet {
+    /// Bridge, morphism and command handler systems that turn commands into
+    /// entities
+    Commands,
+    /// Layout algorithms positioning the nodes
+    Layout,
+    /// Read models updated from the events the earlier sets sent
+    Projection,
+    /// Edge and label systems that draw the current state
+    Render,
+}
+
+/// Plugin that adds the whole domain pipeline in one call: the core
+/// visualization from [`CimVizPlugin`], the command handlers, the
+/// projections and the query handlers, ordered by [`CimSet`]
+pub struct CimDomainPlugin {
+    /// Size of the event channels between domain and visualization
+    pub channel_size: usize,
+}
+
+impl Default for CimDomainPlugin {
+    fn default() -> Self {
+        Self {
+            channel_size: CimVizPlugin::default().channel_size,
+        }
+    }
+}
+
+impl Plugin for CimDomainPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<CimVizPlugin>() {
+            app.add_plugins(CimVizPlugin { channel_size: self.channel_size });
+        }
+        if !app.is_plugin_added::<CommandHandlerPlugin>() {
+            app.add_plugins(CommandHandlerPlugin);
+        }
+        if !app.is_plugin_added::<ProjectionPlugin>() {
+            app.add_plugins(ProjectionPlugin);
+        }
+        if !app.is_plugin_added::<QueryHandlerPlugin>() {
+            app.add_plugins(QueryHandlerPlugin);
+        }
+    }
+}
 
 /// The main plugin that adds all graph visualization functionality
 pub struct CimVizPlugin {
@@ -32,6 +82,8 @@ impl Plugin for CimVizPlugin {
             .add_event::<RemoveEdgeVisual>()
             .add_event::<VisualNodeCreated>()
             .add_event::<VisualEdgeCreated>()
+            .add_event::<VisualNodeDeleted>()
+            .add_event::<VisualEdgeDeleted>()
             .add_event::<NodeClicked>()
             .add_event::<NodeHovered>()
             .add_event::<NodeUnhovered>()
@@ -41,8 +93,10 @@ impl Plugin for CimVizPlugin {
             .add_event::<NodeDragEnd>()
             .add_event::<NodePositionChanged>()
             .add_event::<NodeMetadataChanged>()
+            .add_event::<crate::functors::DomainEvent>()
             // Add any additional events that need to be registered
-            .add_event::<VisualizationCommand>();
+            .add_event::<VisualizationCommand>()
+            .add_event::<crate::events::RequestDomainCommand>();
 
         // Add resources
         app.insert_resource(AsyncSyncBridge::new(self.channel_size))
@@ -57,40 +111,125 @@ impl Plugin for CimVizPlugin {
             .insert_resource(SpatialIndex::default())
             .insert_resource(InteractionState::default());
 
+        app.configure_sets(
+            Update,
+            (CimSet::Commands, CimSet::Layout, CimSet::Projection, CimSet::Render).chain(),
+        );
+
+        // Calls made through the VizHandle are sent as commands first
+        app.init_resource::<crate::viz_handle::VizHandle>()
+            .add_systems(
+                Update,
+                crate::viz_handle::flush_viz_handle
+                    .before(crate::bridge::process_domain_events)
+                    .in_set(CimSet::Commands),
+            );
+
         // Add bridge systems
         app.add_systems(
             Update,
             (
                 crate::bridge::process_domain_events,
                 crate::bridge::send_visualization_commands,
-            ),
+                crate::command_publisher::publish_bridge_commands
+                    .after(crate::bridge::send_visualization_commands),
+            )
+                .in_set(CimSet::Commands),
         );
 
-        // Add morphism systems
+        // Queued commands are published on their variant's subject
+        app.init_resource::<crate::command_publisher::CommandSubjects>();
+
+        // Add morphism systems, chained so edges created in the same frame
+        // as their nodes can resolve them
         app.add_systems(
             Update,
             (
+                crate::functors::handle_domain_events,
                 crate::morphisms::create_node_visual,
                 crate::morphisms::remove_node_visual,
                 crate::morphisms::create_edge_visual,
                 crate::morphisms::remove_edge_visual,
-            ),
+            )
+                .chain()
+                .after(crate::bridge::process_domain_events)
+                .in_set(CimSet::Commands),
         );
         
+        // Node visuals are mapped to their nodes however they are spawned
+        if !app.is_plugin_added::<crate::morphisms::NodeEntityMapPlugin>() {
+            app.add_plugins(crate::morphisms::NodeEntityMapPlugin);
+        }
+
+        // Culling also provides the flag the layout reads for culled nodes
+        if !app.is_plugin_added::<crate::culling::CullingPlugin>() {
+            app.add_plugins(crate::culling::CullingPlugin);
+        }
+
+        // Edges left without their nodes are reported
+        if !app.is_plugin_added::<crate::validation::GraphValidationPlugin>() {
+            app.add_plugins(crate::validation::GraphValidationPlugin);
+        }
+
+        // Selected nodes can be folded into meta-nodes
+        if !app.is_plugin_added::<crate::collapse::CollapsePlugin>() {
+            app.add_plugins(crate::collapse::CollapsePlugin);
+        }
+
+        // Layout changes glide nodes to their new positions
+        if !app.is_plugin_added::<crate::animation::AnimationPlugin>() {
+            app.add_plugins(crate::animation::AnimationPlugin);
+        }
+
         // Add layout systems
         app.insert_resource(crate::layout::GraphLayoutState::default())
+            .init_resource::<crate::layout::SnapConfig>()
+            .init_resource::<crate::layout::AnchorToggleKey>()
+            .init_resource::<crate::layout::WorldBounds>()
+            .init_resource::<crate::layout::RecenterKey>()
             .add_event::<crate::layout::SetLayoutAlgorithm>()
+            .add_event::<crate::layout::LayoutCompleted>()
+            .add_event::<crate::events::FocusCamera>()
             .add_systems(
                 Update,
                 (
-                    crate::layout::update_layout_from_hints,
-                    crate::layout::apply_layout_algorithm,
-                    crate::layout::handle_layout_commands,
-                ),
+                    (
+                        crate::layout::update_layout_from_hints,
+                        crate::layout::finish_layout_transitions,
+                        crate::layout::handle_layout_commands,
+                        crate::layout::sync_graph_layout_types,
+                        crate::layout::toggle_selected_anchors
+                            .run_if(not(crate::picking::egui_wants_keyboard)),
+                        crate::layout::recenter_graph,
+                        crate::layout::mark_layout_dirty,
+                        crate::layout::reset_exploded_nodes,
+                        crate::layout::apply_layout_algorithm,
+                    )
+                        .chain(),
+                    (
+                        crate::layout::snap_dragged_nodes,
+                        crate::layout::record_manual_positions,
+                        crate::layout::move_dragged_anchors
+                            .before(crate::layout::apply_layout_algorithm),
+                        crate::command_publisher::request_drag_end_commands,
+                        crate::layout::request_layout_for_moved_nodes
+                            .before(crate::layout::mark_layout_dirty),
+                    )
+                        .chain(),
+                )
+                    .in_set(CimSet::Layout),
+            )
+            .add_systems(
+                Update,
+                (crate::layout::sync_snap_grid_size, crate::layout::draw_snap_grid)
+                    .chain()
+                    .in_set(CimSet::Render),
             );
             
         // Add edge state systems
         app.add_event::<crate::edge_systems::EdgeStateChanged>()
+            .init_resource::<crate::edge_systems::EdgeBundling>()
+            .init_resource::<crate::edge_systems::BundlingConfig>()
             .add_systems(
                 Update,
                 (
@@ -99,10 +238,33 @@ impl Plugin for CimVizPlugin {
                     crate::edge_systems::update_edge_weights,
                     crate::edge_systems::handle_edge_state_changes,
                     crate::edge_systems::animate_edge_flow,
-                ),
+                    (
+                        crate::edge_systems::bundle_edges,
+                        crate::edge_systems::render_edges
+                            .run_if(resource_equals(crate::edge_mesh::EdgeRenderMode::Gizmos)),
+                    )
+                        .chain(),
+                )
+                    .in_set(CimSet::Render),
             );
 
+        // Edges are gizmo lines unless switched to meshes
+        if !app.is_plugin_added::<crate::edge_mesh::EdgeMeshPlugin>() {
+            app.add_plugins(crate::edge_mesh::EdgeMeshPlugin);
+        }
 
+        // Add edge label systems
+        app.init_resource::<crate::edge_systems::ShowEdgeLabels>()
+            .init_resource::<crate::edge_systems::EdgeLabelDistance>()
+            .add_systems(
+                Update,
+                (
+                    crate::edge_systems::spawn_edge_labels,
+                    crate::edge_systems::update_edge_labels,
+                )
+                    .chain()
+                    .in_set(CimSet::Render),
+            );
     }
 }
 
@@ -153,3 +315,46 @@ fn debug_show_metrics(
         debug!("  Render time: {:.2}ms", metrics.render_time_ms);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::projections::GraphViewProjection;
+    use cim_contextgraph::{ContextGraphId as GraphId, NodeId};
+
+    #[test]
+    fn test_projection_sees_nodes_created_in_the_same_frame() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Mesh>()
+            .init_asset::<StandardMaterial>()
+            .init_asset::<bevy::gizmos::GizmoAsset>()
+            .init_resource::<bevy::gizmos::config::GizmoConfigStore>()
+            .init_gizmo_group::<DefaultGizmoConfigGroup>()
+            .add_plugins(CimDomainPlugin::default());
+
+        let node_id = NodeId::new();
+        app.world_mut().send_event(CreateNodeVisual {
+            node_id,
+            graph_id: GraphId::new(),
+            position: Vec3::new(1.0, 2.0, 3.0),
+            label: "A".to_string(),
+            metadata: serde_json::Value::Null,
+        });
+        app.update();
+
+        let projection = app.world().resource::<GraphViewProjection>();
+        assert_eq!(projection.nodes.get(&node_id).map(|view| view.position), Some(Vec3::new(1.0, 2.0, 3.0)));
+
+        // Command handlers run before the projection in the same frame
+        app.world_mut().send_event(crate::commands::MoveNode {
+            node_id,
+            new_position: crate::value_objects::Position::new(4.0, 5.0, 6.0),
+            animate: false,
+        });
+        app.update();
+
+        let projection = app.world().resource::<GraphViewProjection>();
+        assert_eq!(projection.nodes.get(&node_id).map(|view| view.position), Some(Vec3::new(4.0, 5.0, 6.0)));
+    }
+}
@@ -4,9 +4,11 @@
 //! These act as read models that are updated by systems processing events.
 
 use crate::events::*;
-use crate::value_objects::*;
+use crate::value_objects::NodeMetadata;
 use bevy::prelude::*;
-use std::collections::{HashMap, HashSet};
+use cim_contextgraph::{EdgeId, NodeId};
+use rstar::{PointDistance, RTree, RTreeObject, AABB};
+use std::collections::{HashMap, HashSet, VecDeque};
 
 /// Graph view projection - maintains graph structure for queries
 #[derive(Resource, Default)]
@@ -17,11 +19,94 @@ pub struct GraphViewProjection {
     pub selected_nodes: HashSet<NodeId>,
 }
 
+impl GraphViewProjection {
+    /// Nodes sharing an edge with `node`, each once
+    pub fn neighbors(&self, node: &NodeId) -> Vec<NodeId> {
+        let mut seen = HashSet::new();
+        self.node_edges.get(node)
+            .into_iter()
+            .flatten()
+            .filter_map(|edge_id| self.edges.get(edge_id))
+            .map(|edge| edge.other_end(node))
+            .filter(|neighbor| seen.insert(*neighbor))
+            .collect()
+    }
+
+    /// Number of edges touching `node`
+    pub fn degree(&self, node: &NodeId) -> usize {
+        self.node_edges.get(node).map_or(0, HashSet::len)
+    }
+
+    /// Remove a node and every edge touching it, from both of the edge's ends
+    pub fn remove_node(&mut self, node_id: &NodeId) {
+        self.nodes.remove(node_id);
+        self.selected_nodes.remove(node_id);
+        for edge_id in self.node_edges.remove(node_id).into_iter().flatten() {
+            self.remove_edge(&edge_id);
+        }
+    }
+
+    /// Remove an edge, and the ends it leaves without edges from `node_edges`
+    pub fn remove_edge(&mut self, edge_id: &EdgeId) {
+        let Some(edge) = self.edges.remove(edge_id) else {
+            return;
+        };
+        for node_id in [edge.source_node_id, edge.target_node_id] {
+            if let Some(edges) = self.node_edges.get_mut(&node_id) {
+                edges.remove(edge_id);
+                if edges.is_empty() {
+                    self.node_edges.remove(&node_id);
+                }
+            }
+        }
+    }
+
+    /// Nodes within `depth` edges of any of `roots`, in breadth-first order,
+    /// and the edges between them. Roots not in the projection are skipped.
+    pub fn subgraph(&self, roots: &[NodeId], depth: usize) -> (Vec<NodeId>, Vec<EdgeId>) {
+        let mut reached: HashSet<NodeId> = HashSet::new();
+        let mut nodes = Vec::new();
+        let mut queue = VecDeque::new();
+        for root in roots {
+            if (self.nodes.contains_key(root) || self.node_edges.contains_key(root)) && reached.insert(*root) {
+                nodes.push(*root);
+                queue.push_back((*root, 0));
+            }
+        }
+
+        while let Some((node, distance)) = queue.pop_front() {
+            if distance == depth {
+                continue;
+            }
+            for neighbor in self.neighbors(&node) {
+                if reached.insert(neighbor) {
+                    nodes.push(neighbor);
+                    queue.push_back((neighbor, distance + 1));
+                }
+            }
+        }
+
+        let mut edges: Vec<EdgeId> = nodes.iter()
+            .flat_map(|node| self.node_edges.get(node).into_iter().flatten())
+            .filter(|edge_id| {
+                self.edges.get(*edge_id).is_some_and(|edge| {
+                    reached.contains(&edge.source_node_id) && reached.contains(&edge.target_node_id)
+                })
+            })
+            .copied()
+            .collect();
+        // Edges inside the subgraph are listed under both their ends
+        let mut listed = HashSet::new();
+        edges.retain(|edge_id| listed.insert(*edge_id));
+        (nodes, edges)
+    }
+}
+
 /// View of a node in the projection
 #[derive(Clone, Debug)]
 pub struct NodeView {
     pub entity: Entity,
-    pub position: Position,
+    pub position: Vec3,
     pub metadata: NodeMetadata,
     pub is_selected: bool,
 }
@@ -34,10 +119,22 @@ pub struct EdgeView {
     pub target_node_id: NodeId,
 }
 
+impl EdgeView {
+    /// The end of the edge that is not `node`; `node` itself for a loop
+    pub fn other_end(&self, node: &NodeId) -> NodeId {
+        if self.source_node_id == *node {
+            self.target_node_id
+        } else {
+            self.source_node_id
+        }
+    }
+}
+
 /// System that updates the graph projection from events
 pub fn update_graph_projection(
     mut projection: ResMut<GraphViewProjection>,
     mut node_created: EventReader<VisualNodeCreated>,
+    mut metadata_changed: EventReader<NodeMetadataChanged>,
     mut edge_created: EventReader<VisualEdgeCreated>,
     mut node_moved: EventReader<NodeMoved>,
     mut node_selected: EventReader<NodeSelected>,
@@ -49,33 +146,43 @@ pub fn update_graph_projection(
     for event in node_created.read() {
         let view = NodeView {
             entity: event.entity,
-            position: event.position.clone(),
+            position: event.position,
             metadata: NodeMetadata::default(),
             is_selected: false,
         };
-        projection.nodes.insert(event.node_id.clone(), view);
+        projection.nodes.insert(event.node_id, view);
+    }
+
+    // Handle metadata updates
+    for event in metadata_changed.read() {
+        if let Some(node) = projection.nodes.get_mut(&event.node_id) {
+            node.metadata = event.metadata.clone();
+        }
     }
 
     // Handle edge creation
     for event in edge_created.read() {
         let view = EdgeView {
             entity: event.entity,
-            source_node_id: NodeId(Uuid::new_v4()), // Would need to look up from entity
-            target_node_id: NodeId(Uuid::new_v4()), // Would need to look up from entity
+            source_node_id: event.source_node_id,
+            target_node_id: event.target_node_id,
         };
-        projection.edges.insert(event.edge_id.clone(), view);
+        projection.edges.insert(event.edge_id, view);
+        for node_id in [event.source_node_id, event.target_node_id] {
+            projection.node_edges.entry(node_id).or_default().insert(event.edge_id);
+        }
     }
 
     // Handle node movement
     for event in node_moved.read() {
         if let Some(node) = projection.nodes.get_mut(&event.node_id) {
-            node.position = event.new_position.clone();
+            node.position = event.new_position;
         }
     }
 
     // Handle selection
     for event in node_selected.read() {
-        projection.selected_nodes.insert(event.node_id.clone());
+        projection.selected_nodes.insert(event.node_id);
         if let Some(node) = projection.nodes.get_mut(&event.node_id) {
             node.is_selected = true;
         }
@@ -89,24 +196,103 @@ pub fn update_graph_projection(
         }
     }
 
-    // Handle node deletion
+    // Handle node deletion, taking the node's edges with it
     for event in node_deleted.read() {
-        projection.nodes.remove(&event.node_id);
-        projection.selected_nodes.remove(&event.node_id);
-        projection.node_edges.remove(&event.node_id);
+        projection.remove_node(&event.node_id);
     }
 
     // Handle edge deletion
     for event in edge_deleted.read() {
-        projection.edges.remove(&event.edge_id);
+        projection.remove_edge(&event.edge_id);
+    }
+}
+
+/// A node position stored in the spatial index
+#[derive(Clone, Debug, PartialEq)]
+struct IndexedNode {
+    node_id: NodeId,
+    position: [f32; 3],
+}
+
+impl RTreeObject for IndexedNode {
+    type Envelope = AABB<[f32; 3]>;
+
+    fn envelope(&self) -> Self::Envelope {
+        AABB::from_point(self.position)
+    }
+}
+
+impl PointDistance for IndexedNode {
+    fn distance_2(&self, point: &[f32; 3]) -> f32 {
+        Vec3::from(self.position).distance_squared(Vec3::from(*point))
     }
 }
 
-/// Spatial index projection for efficient spatial queries
+/// Spatial index projection for efficient spatial queries, backed by an R-tree
 #[derive(Resource, Default)]
 pub struct SpatialIndexProjection {
-    // In a real implementation, this would use an R-tree or similar
-    pub node_positions: Vec<(NodeId, Position)>,
+    tree: RTree<IndexedNode>,
+    positions: HashMap<NodeId, Vec3>,
+}
+
+impl SpatialIndexProjection {
+    /// Add a node, replacing any previous entry for it
+    pub fn insert(&mut self, node_id: NodeId, position: Vec3) {
+        self.remove(&node_id);
+        self.tree.insert(IndexedNode {
+            node_id,
+            position: position.to_array(),
+        });
+        self.positions.insert(node_id, position);
+    }
+
+    /// Move an indexed node; unknown nodes are ignored
+    pub fn move_node(&mut self, node_id: NodeId, position: Vec3) {
+        if self.positions.contains_key(&node_id) {
+            self.insert(node_id, position);
+        }
+    }
+
+    /// Remove a node, returning its last position
+    pub fn remove(&mut self, node_id: &NodeId) -> Option<Vec3> {
+        let position = self.positions.remove(node_id)?;
+        self.tree.remove(&IndexedNode {
+            node_id: *node_id,
+            position: position.to_array(),
+        });
+        Some(position)
+    }
+
+    pub fn position(&self, node_id: &NodeId) -> Option<Vec3> {
+        self.positions.get(node_id).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Nodes inside the axis-aligned box spanned by `min` and `max` (inclusive)
+    pub fn query_region(&self, min: Vec3, max: Vec3) -> Vec<NodeId> {
+        let region = AABB::from_corners(min.to_array(), max.to_array());
+        self.tree
+            .locate_in_envelope(&region)
+            .map(|node| node.node_id)
+            .collect()
+    }
+
+    /// The `k` nodes nearest to `point`, closest first, with their distances
+    pub fn nearest(&self, point: Vec3, k: usize) -> Vec<(NodeId, f32)> {
+        let point = point.to_array();
+        self.tree
+            .nearest_neighbor_iter_with_distance_2(&point)
+            .take(k)
+            .map(|(node, distance_2)| (node.node_id, distance_2.sqrt()))
+            .collect()
+    }
 }
 
 /// System that updates spatial index
@@ -117,23 +303,15 @@ pub fn update_spatial_index(
     mut node_deleted: EventReader<VisualNodeDeleted>,
 ) {
     for event in node_created.read() {
-        index
-            .node_positions
-            .push((event.node_id.clone(), event.position.clone()));
+        index.insert(event.node_id, event.position);
     }
 
     for event in node_moved.read() {
-        if let Some(entry) = index
-            .node_positions
-            .iter_mut()
-            .find(|(id, _)| id == &event.node_id)
-        {
-            entry.1 = event.new_position.clone();
-        }
+        index.move_node(event.node_id, event.new_position);
     }
 
     for event in node_deleted.read() {
-        index.node_positions.retain(|(id, _)| id != &event.node_id);
+        index.remove(&event.node_id);
     }
 }
 
@@ -142,8 +320,230 @@ pub struct ProjectionPlugin;
 
 impl Plugin for ProjectionPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<GraphViewProjection>()
+        app.add_event::<VisualNodeCreated>()
+            .add_event::<NodeMetadataChanged>()
+            .add_event::<VisualEdgeCreated>()
+            .add_event::<NodeMoved>()
+            .add_event::<NodeSelected>()
+            .add_event::<NodeDeselected>()
+            .add_event::<VisualNodeDeleted>()
+            .add_event::<VisualEdgeDeleted>()
+            .init_resource::<GraphViewProjection>()
             .init_resource::<SpatialIndexProjection>()
-            .add_systems(Update, (update_graph_projection, update_spatial_index));
+            .add_systems(
+                Update,
+                (update_graph_projection, update_spatial_index).in_set(crate::plugin::CimSet::Projection),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_node(app: &mut App) -> NodeId {
+        let node_id = NodeId::new();
+        let entity = app.world_mut().spawn_empty().id();
+        app.world_mut().send_event(VisualNodeCreated {
+            entity,
+            node_id,
+            position: Vec3::ZERO,
+        });
+        node_id
+    }
+
+    #[test]
+    fn test_edge_is_indexed_under_both_endpoints() {
+        let mut app = App::new();
+        app.add_plugins(ProjectionPlugin);
+
+        let source = create_node(&mut app);
+        let target = create_node(&mut app);
+        let edge_id = EdgeId::new();
+        let source_entity = app.world_mut().spawn_empty().id();
+        let target_entity = app.world_mut().spawn_empty().id();
+        let entity = app.world_mut().spawn_empty().id();
+        app.world_mut().send_event(VisualEdgeCreated {
+            entity,
+            edge_id,
+            source_entity,
+            target_entity,
+            source_node_id: source,
+            target_node_id: target,
+        });
+        app.update();
+
+        let projection = app.world().resource::<GraphViewProjection>();
+        assert_eq!(projection.nodes.len(), 2);
+        assert_eq!(projection.edges[&edge_id].source_node_id, source);
+        assert_eq!(projection.edges[&edge_id].target_node_id, target);
+        assert!(projection.node_edges[&source].contains(&edge_id));
+        assert!(projection.node_edges[&target].contains(&edge_id));
+
+        app.world_mut().send_event(VisualEdgeDeleted { edge_id });
+        app.update();
+
+        let projection = app.world().resource::<GraphViewProjection>();
+        assert!(projection.edges.is_empty());
+        assert!(projection.node_edges.is_empty());
+    }
+
+    fn create_edge(app: &mut App, source: NodeId, target: NodeId) -> EdgeId {
+        let edge_id = EdgeId::new();
+        let entity = app.world_mut().spawn_empty().id();
+        app.world_mut().send_event(VisualEdgeCreated {
+            entity,
+            edge_id,
+            source_entity: entity,
+            target_entity: entity,
+            source_node_id: source,
+            target_node_id: target,
+        });
+        edge_id
+    }
+
+    #[test]
+    fn test_deleting_a_node_drops_its_edges_at_both_ends() {
+        let mut app = App::new();
+        app.add_plugins(ProjectionPlugin);
+
+        let hub = create_node(&mut app);
+        let leaf = create_node(&mut app);
+        let other = create_node(&mut app);
+        create_edge(&mut app, hub, leaf);
+        create_edge(&mut app, other, hub);
+        let kept = create_edge(&mut app, leaf, other);
+        app.update();
+
+        app.world_mut().send_event(VisualNodeDeleted { node_id: hub, final_position: Vec3::ZERO });
+        app.update();
+
+        let projection = app.world().resource::<GraphViewProjection>();
+        assert!(!projection.node_edges.contains_key(&hub));
+        assert_eq!(projection.edges.keys().collect::<Vec<_>>(), vec![&kept]);
+        assert_eq!(projection.node_edges[&leaf], HashSet::from([kept]));
+        assert_eq!(projection.node_edges[&other], HashSet::from([kept]));
+        assert_eq!(projection.neighbors(&leaf), vec![other]);
+    }
+
+    #[test]
+    fn test_star_graph_queries() {
+        let mut app = App::new();
+        app.add_plugins(ProjectionPlugin);
+
+        // A hub with three spokes, one of which leads on to a tail node
+        let hub = create_node(&mut app);
+        let spokes: Vec<NodeId> = (0..3).map(|_| create_node(&mut app)).collect();
+        let tail = create_node(&mut app);
+        let spoke_edges: Vec<EdgeId> = spokes.iter().map(|spoke| create_edge(&mut app, hub, *spoke)).collect();
+        let tail_edge = create_edge(&mut app, tail, spokes[0]);
+        app.update();
+
+        let projection = app.world().resource::<GraphViewProjection>();
+        assert_eq!(projection.degree(&hub), 3);
+        assert_eq!(projection.degree(&spokes[0]), 2);
+        assert_eq!(projection.degree(&NodeId::new()), 0);
+        let neighbors: HashSet<NodeId> = projection.neighbors(&hub).into_iter().collect();
+        assert_eq!(neighbors, spokes.iter().copied().collect());
+        assert_eq!(projection.neighbors(&tail), vec![spokes[0]]);
+
+        let (nodes, edges) = projection.subgraph(&[hub], 0);
+        assert_eq!(nodes, vec![hub]);
+        assert!(edges.is_empty());
+
+        let (nodes, edges) = projection.subgraph(&[hub], 1);
+        assert_eq!(nodes[0], hub);
+        assert_eq!(nodes.iter().copied().collect::<HashSet<_>>(), [hub, spokes[0], spokes[1], spokes[2]].into());
+        assert_eq!(edges.iter().copied().collect::<HashSet<_>>(), spoke_edges.iter().copied().collect());
+
+        let (nodes, edges) = projection.subgraph(&[hub, spokes[1]], 2);
+        assert_eq!(nodes.len(), 5);
+        assert_eq!(nodes.last(), Some(&tail));
+        assert_eq!(edges.len(), 4);
+        assert!(edges.contains(&tail_edge));
+
+        let (nodes, edges) = projection.subgraph(&[NodeId::new()], 3);
+        assert!(nodes.is_empty() && edges.is_empty());
+    }
+
+    #[test]
+    fn test_spatial_index_tracks_moves_and_deletes() {
+        let mut index = SpatialIndexProjection::default();
+        let a = NodeId::new();
+        let b = NodeId::new();
+        index.insert(a, Vec3::new(1.0, 1.0, 0.0));
+        index.insert(b, Vec3::new(10.0, 10.0, 0.0));
+
+        assert_eq!(index.query_region(Vec3::ZERO, Vec3::splat(5.0)), vec![a]);
+
+        index.move_node(b, Vec3::new(2.0, 2.0, 0.0));
+        assert_eq!(index.query_region(Vec3::ZERO, Vec3::splat(5.0)).len(), 2);
+        assert_eq!(index.nearest(Vec3::new(2.0, 2.0, 0.0), 1), vec![(b, 0.0)]);
+
+        assert_eq!(index.remove(&a), Some(Vec3::new(1.0, 1.0, 0.0)));
+        assert_eq!(index.len(), 1);
+        assert_eq!(index.query_region(Vec3::ZERO, Vec3::splat(5.0)), vec![b]);
+    }
+
+    #[test]
+    fn test_spatial_index_matches_linear_scan_on_10k_nodes() {
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        let nodes: Vec<(NodeId, Vec3)> = (0..10_000)
+            .map(|_| {
+                let position = Vec3::new(
+                    rng.gen_range(-500.0..500.0),
+                    rng.gen_range(-500.0..500.0),
+                    rng.gen_range(-50.0..50.0),
+                );
+                (NodeId::new(), position)
+            })
+            .collect();
+
+        let mut index = SpatialIndexProjection::default();
+        for (node_id, position) in &nodes {
+            index.insert(*node_id, *position);
+        }
+
+        let queries: Vec<(Vec3, Vec3)> = (0..200)
+            .map(|_| {
+                let min = Vec3::new(
+                    rng.gen_range(-500.0..450.0),
+                    rng.gen_range(-500.0..450.0),
+                    -50.0,
+                );
+                (min, min + Vec3::new(50.0, 50.0, 100.0))
+            })
+            .collect();
+
+        let linear: Vec<HashSet<NodeId>> = queries.iter()
+            .map(|(min, max)| {
+                nodes.iter()
+                    .filter(|(_, p)| p.cmpge(*min).all() && p.cmple(*max).all())
+                    .map(|(id, _)| *id)
+                    .collect()
+            })
+            .collect();
+        let indexed: Vec<HashSet<NodeId>> = queries.iter()
+            .map(|(min, max)| index.query_region(*min, *max).into_iter().collect())
+            .collect();
+
+        // Every region holds a few dozen nodes on average, so the sets are
+        // compared on real hits rather than on empty results
+        assert!(linear.iter().all(|hits| !hits.is_empty()));
+        assert_eq!(linear, indexed);
+
+        // Nearest neighbors agree with a brute-force sort
+        let point = Vec3::new(12.0, -40.0, 3.0);
+        let mut brute: Vec<(NodeId, f32)> = nodes.iter()
+            .map(|(id, p)| (*id, p.distance(point)))
+            .collect();
+        brute.sort_by(|a, b| a.1.total_cmp(&b.1));
+        let nearest = index.nearest(point, 5);
+        assert_eq!(
+            nearest.iter().map(|(id, _)| *id).collect::<Vec<_>>(),
+            brute[..5].iter().map(|(id, _)| *id).collect::<Vec<_>>()
+        );
     }
 }
@@ -4,40 +4,57 @@
 //! These are wrapped to provide a domain-oriented interface.
 
 use crate::value_objects::*;
+use crate::components::{EdgeVisual, Highlighted, NodeVisual, Selected, TimedHighlight};
+use crate::events::{FindPath, HighlightPath};
+use crate::morphisms::NodeEntityMap;
+use crate::projections::SpatialIndexProjection;
+use bevy::ecs::query::QueryFilter;
 use bevy::prelude::*;
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+
+/// How long a path found through `FindPath` stays highlighted, in seconds
+pub const PATH_HIGHLIGHT_DURATION: f32 = 3.0;
+
+/// Color applied to highlighted path elements
+pub const PATH_HIGHLIGHT_COLOR: Color = Color::srgb(1.0, 0.75, 0.1);
 
 /// Query to find all nodes in a graph
 pub fn query_nodes_in_graph(
     graph_id: GraphId,
-    nodes: Query<(Entity, &NodeId, &Position), With<NodeId>>,
+    nodes: Query<(Entity, &NodeId, &Position, &GraphId)>,
 ) -> Vec<(Entity, NodeId, Position)> {
     nodes
         .iter()
-        .map(|(e, id, pos)| (e, id.clone(), pos.clone()))
+        .filter(|(_, _, _, graph)| **graph == graph_id)
+        .map(|(e, id, pos, _)| (e, id.clone(), pos.clone()))
         .collect()
 }
 
 /// Query to find selected nodes
 pub fn query_selected_nodes(
-    nodes: Query<(Entity, &NodeId, &InteractionState)>,
+    nodes: Query<(Entity, &NodeId), With<Selected>>,
 ) -> Vec<(Entity, NodeId)> {
     nodes
         .iter()
-        .filter(|(_, _, state)| state.is_selected)
-        .map(|(e, id, _)| (e, id.clone()))
+        .map(|(e, id)| (e, id.clone()))
         .collect()
 }
 
-/// Query to find nodes within a region
+/// Query to find nodes within a region of the XY plane, at any depth.
+///
+/// Looked up in the [`SpatialIndexProjection`] rather than by scanning every
+/// node; nodes the index knows but `node_map` doesn't are left out.
 pub fn query_nodes_in_region(
     min: Vec2,
     max: Vec2,
-    nodes: Query<(Entity, &NodeId, &Position)>,
-) -> Vec<(Entity, NodeId)> {
-    nodes
-        .iter()
-        .filter(|(_, _, pos)| pos.x >= min.x && pos.x <= max.x && pos.y >= min.y && pos.y <= max.y)
-        .map(|(e, id, _)| (e, id.clone()))
+    index: &SpatialIndexProjection,
+    node_map: &NodeEntityMap,
+) -> Vec<(Entity, cim_contextgraph::NodeId)> {
+    index
+        .query_region(min.extend(f32::MIN), max.extend(f32::MAX))
+        .into_iter()
+        .filter_map(|node_id| Some((*node_map.get(&node_id)?, node_id)))
         .collect()
 }
 
@@ -53,42 +70,264 @@ pub fn query_edges_for_node(
         .collect()
 }
 
-/// Query system that finds the nearest node to a position
-pub fn find_nearest_node_system(
-    position: Vec2,
+/// Query up to `k` nodes within `max_distance` of `position`, nearest first
+/// by their world position.
+///
+/// `F` narrows the candidate nodes by component, and `predicate` can reject
+/// individual entities, e.g. selected nodes when snapping to the nearest
+/// unselected one.
+pub fn k_nearest_nodes<F: QueryFilter>(
+    position: Vec3,
+    k: usize,
     max_distance: f32,
-    nodes: Query<(Entity, &NodeId, &Position)>,
-) -> Option<(Entity, NodeId, f32)> {
-    let mut nearest = None;
-    let mut min_distance = max_distance;
-
-    for (entity, node_id, node_pos) in nodes.iter() {
-        let distance = Vec2::new(node_pos.x, node_pos.y).distance(position);
-        if distance < min_distance {
-            min_distance = distance;
-            nearest = Some((entity, node_id.clone(), distance));
-        }
-    }
+    nodes: Query<(Entity, &NodeVisual, &GlobalTransform), F>,
+    predicate: Option<&dyn Fn(Entity) -> bool>,
+) -> Vec<(Entity, cim_contextgraph::NodeId, f32)> {
+    let mut nearest: Vec<(Entity, cim_contextgraph::NodeId, f32)> = nodes
+        .iter()
+        .filter(|(entity, _, _)| predicate.is_none_or(|predicate| predicate(*entity)))
+        .map(|(entity, node, transform)| (entity, node.node_id, transform.translation().distance(position)))
+        .filter(|(_, _, distance)| *distance < max_distance)
+        .collect();
 
+    nearest.sort_by(|a, b| a.2.total_cmp(&b.2));
+    nearest.truncate(k);
     nearest
 }
 
+/// Query system that finds the nearest node to a point on the XY plane.
+///
+/// A thin wrapper over [`k_nearest_nodes`], so distances are measured in 3D
+/// and a node's depth counts towards it.
+pub fn find_nearest_node_system(
+    position: Vec2,
+    max_distance: f32,
+    nodes: Query<(Entity, &NodeVisual, &GlobalTransform)>,
+) -> Option<(Entity, cim_contextgraph::NodeId, f32)> {
+    k_nearest_nodes(position.extend(0.0), 1, max_distance, nodes, None).pop()
+}
+
 /// Query to get graph statistics
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct GraphStatistics {
     pub node_count: usize,
     pub edge_count: usize,
     pub selected_count: usize,
+    /// Most edges attached to a single node
+    pub max_degree: usize,
+    /// Nodes without any edges
+    pub isolated_node_count: usize,
+    /// Connected components, ignoring edge direction
+    pub component_count: usize,
 }
 
+/// Statistics over the graph's nodes and edges, whether they carry the
+/// domain ids or are only visuals
+#[allow(clippy::type_complexity)]
 pub fn query_graph_statistics(
-    nodes: Query<&NodeId>,
-    edges: Query<&EdgeId>,
-    selected: Query<&InteractionState>,
+    nodes: Query<Entity, Or<(With<NodeId>, With<NodeVisual>)>>,
+    edges: Query<(), Or<(With<EdgeId>, With<EdgeVisual>)>>,
+    edge_visuals: Query<&EdgeVisual>,
+    selected: Query<(), (With<Selected>, Or<(With<NodeId>, With<NodeVisual>)>)>,
 ) -> GraphStatistics {
+    let node_entities: Vec<Entity> = nodes.iter().collect();
+    let edge_list: Vec<(Entity, Entity)> = edge_visuals
+        .iter()
+        .map(|edge| (edge.source_entity, edge.target_entity))
+        .collect();
+    let structure = graph_structure(&node_entities, &edge_list);
+
     GraphStatistics {
-        node_count: nodes.iter().count(),
+        node_count: node_entities.len(),
         edge_count: edges.iter().count(),
-        selected_count: selected.iter().filter(|s| s.is_selected).count(),
+        selected_count: selected.iter().count(),
+        max_degree: structure.max_degree,
+        isolated_node_count: structure.isolated_node_count,
+        component_count: structure.component_count,
+    }
+}
+
+/// Degree and connectivity figures of a graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GraphStructure {
+    pub max_degree: usize,
+    pub isolated_node_count: usize,
+    pub component_count: usize,
+}
+
+/// Degrees and connected components of `nodes`, computed in one pass over
+/// `edges` with union-find. Edges touching unknown nodes are ignored.
+pub fn graph_structure<N: Copy + Eq + Hash>(nodes: &[N], edges: &[(N, N)]) -> GraphStructure {
+    let index: HashMap<N, usize> = nodes.iter().enumerate().map(|(i, node)| (*node, i)).collect();
+    let mut degrees = vec![0usize; nodes.len()];
+    let mut parents: Vec<usize> = (0..nodes.len()).collect();
+    let mut component_count = nodes.len();
+
+    fn root(parents: &mut [usize], mut i: usize) -> usize {
+        while parents[i] != i {
+            parents[i] = parents[parents[i]];
+            i = parents[i];
+        }
+        i
+    }
+
+    for (source, target) in edges {
+        let (Some(&a), Some(&b)) = (index.get(source), index.get(target)) else {
+            continue;
+        };
+        degrees[a] += 1;
+        degrees[b] += 1;
+        let (root_a, root_b) = (root(&mut parents, a), root(&mut parents, b));
+        if root_a != root_b {
+            parents[root_a] = root_b;
+            component_count -= 1;
+        }
+    }
+
+    GraphStructure {
+        max_degree: degrees.iter().copied().max().unwrap_or(0),
+        isolated_node_count: degrees.iter().filter(|degree| **degree == 0).count(),
+        component_count,
+    }
+}
+
+/// Shortest path from `from` to `to` following directed `(source, target)`
+/// edges, found by breadth-first search.
+///
+/// The returned path includes both endpoints; `None` means `to` is unreachable.
+pub fn find_path<N: Copy + Eq + Hash>(from: N, to: N, edges: &[(N, N)]) -> Option<Vec<N>> {
+    if from == to {
+        return Some(vec![from]);
+    }
+
+    let mut adjacency: HashMap<N, Vec<N>> = HashMap::new();
+    for (source, target) in edges {
+        adjacency.entry(*source).or_default().push(*target);
+    }
+
+    let mut previous: HashMap<N, N> = HashMap::new();
+    let mut queue = VecDeque::from([from]);
+    while let Some(node) = queue.pop_front() {
+        for next in adjacency.get(&node).into_iter().flatten() {
+            if *next == from || previous.contains_key(next) {
+                continue;
+            }
+            previous.insert(*next, node);
+            if *next == to {
+                let mut path = vec![to];
+                let mut current = to;
+                while let Some(prev) = previous.get(&current) {
+                    path.push(*prev);
+                    current = *prev;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            queue.push_back(*next);
+        }
+    }
+
+    None
+}
+
+/// System that answers `FindPath` requests with a `HighlightPath` over the
+/// current edge entities
+pub fn find_path_system(
+    mut requests: EventReader<FindPath>,
+    node_map: Res<NodeEntityMap>,
+    nodes: Query<&NodeVisual>,
+    edges: Query<(Entity, &EdgeVisual)>,
+    mut highlights: EventWriter<HighlightPath>,
+) {
+    if requests.is_empty() {
+        return;
+    }
+
+    let mut edge_entities = HashMap::new();
+    let mut node_entities = HashMap::new();
+    for (entity, edge) in edges.iter() {
+        let (Ok(source), Ok(target)) = (nodes.get(edge.source_entity), nodes.get(edge.target_entity)) else {
+            continue;
+        };
+        edge_entities.entry((source.node_id, target.node_id)).or_insert(entity);
+        node_entities.insert(source.node_id, edge.source_entity);
+        node_entities.insert(target.node_id, edge.target_entity);
+    }
+    let edge_list: Vec<_> = edge_entities.keys().copied().collect();
+
+    for request in requests.read() {
+        let Some(path) = find_path(request.from, request.to, &edge_list) else {
+            info!("No path from {:?} to {:?}", request.from, request.to);
+            continue;
+        };
+
+        highlights.write(HighlightPath {
+            node_entities: path.iter()
+                .filter_map(|node_id| {
+                    node_map.get(node_id).or_else(|| node_entities.get(node_id)).copied()
+                })
+                .collect(),
+            edge_entities: path.windows(2)
+                .filter_map(|pair| edge_entities.get(&(pair[0], pair[1])).copied())
+                .collect(),
+            duration: PATH_HIGHLIGHT_DURATION,
+        });
+    }
+}
+
+/// System that marks the entities of a `HighlightPath` as highlighted for
+/// the path's duration
+pub fn apply_path_highlight(
+    mut commands: Commands,
+    mut events: EventReader<HighlightPath>,
+    current: Query<(Option<&Highlighted>, Option<&TimedHighlight>)>,
+) {
+    for event in events.read() {
+        for entity in event.node_entities.iter().chain(&event.edge_entities) {
+            let Ok((highlighted, timed)) = current.get(*entity) else {
+                continue;
+            };
+            let highlight = Highlighted {
+                color: PATH_HIGHLIGHT_COLOR,
+                intensity: 1.0,
+            };
+            // A path highlighted again keeps what the first one replaced
+            let replaced = match timed {
+                Some(timed) => timed.replaced.clone(),
+                None => highlighted.cloned(),
+            };
+            commands.entity(*entity).insert((
+                highlight.clone(),
+                TimedHighlight {
+                    timer: Timer::from_seconds(event.duration, TimerMode::Once),
+                    highlight,
+                    replaced,
+                },
+            ));
+        }
+    }
+}
+
+/// System that ends temporary highlights once their time is up, leaving
+/// other highlights in place
+pub fn expire_highlights(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut highlights: Query<(Entity, &mut TimedHighlight, Option<&Highlighted>)>,
+) {
+    for (entity, mut timed, highlighted) in highlights.iter_mut() {
+        if !timed.timer.tick(time.delta()).finished() {
+            continue;
+        }
+        let mut entity_commands = commands.entity(entity);
+        entity_commands.remove::<TimedHighlight>();
+        if highlighted != Some(&timed.highlight) {
+            continue;
+        }
+        match timed.replaced.take() {
+            Some(replaced) => entity_commands.insert(replaced),
+            None => entity_commands.remove::<Highlighted>(),
+        };
     }
 }
 
@@ -97,7 +336,231 @@ pub struct QueryHandlerPlugin;
 
 impl Plugin for QueryHandlerPlugin {
     fn build(&self, app: &mut App) {
-        // Query systems can be registered here if needed
-        // Most queries are called directly from other systems
+        // Most queries are called directly from other systems; path finding
+        // is driven by events
+        app.add_event::<FindPath>()
+            .add_event::<HighlightPath>()
+            .init_resource::<NodeEntityMap>()
+            .add_systems(
+                Update,
+                (find_path_system, apply_path_highlight, expire_highlights).chain(),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cim_contextgraph::{ContextGraphId, EdgeId as ContextEdgeId, NodeId as ContextNodeId};
+
+    #[test]
+    fn test_nodes_in_region_come_from_the_spatial_index() {
+        let mut index = SpatialIndexProjection::default();
+        let mut node_map = NodeEntityMap::default();
+        let (inside, deep, outside, unmapped) = (ContextNodeId::new(), ContextNodeId::new(), ContextNodeId::new(), ContextNodeId::new());
+        for (raw, node_id, position) in [
+            (1, inside, Vec3::new(1.0, 1.0, 0.0)),
+            (2, deep, Vec3::new(2.0, 3.0, -40.0)),
+            (3, outside, Vec3::new(8.0, 1.0, 0.0)),
+        ] {
+            index.insert(node_id, position);
+            node_map.insert(node_id, Entity::from_raw(raw));
+        }
+        index.insert(unmapped, Vec3::new(1.0, 2.0, 0.0));
+
+        let mut found = query_nodes_in_region(Vec2::ZERO, Vec2::splat(5.0), &index, &node_map);
+        found.sort_by_key(|(entity, _)| *entity);
+        assert_eq!(found, vec![(Entity::from_raw(1), inside), (Entity::from_raw(2), deep)]);
+    }
+
+    #[test]
+    fn test_path_highlight_expiry_keeps_other_highlights() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .add_event::<HighlightPath>()
+            .add_systems(Update, (apply_path_highlight, expire_highlights).chain());
+
+        let search = Highlighted { color: Color::srgb(0.0, 1.0, 0.0), intensity: 0.5 };
+        let searched = app.world_mut().spawn(search.clone()).id();
+        let plain = app.world_mut().spawn_empty().id();
+        let rehighlighted = app.world_mut().spawn_empty().id();
+        app.world_mut().send_event(HighlightPath {
+            node_entities: vec![searched, plain, rehighlighted],
+            edge_entities: Vec::new(),
+            duration: 1.0,
+        });
+        app.update();
+        assert_eq!(app.world().get::<Highlighted>(searched).map(|h| h.color), Some(PATH_HIGHLIGHT_COLOR));
+
+        // Something else highlights a node while the path is shown
+        app.world_mut().entity_mut(rehighlighted).insert(search.clone());
+        app.world_mut().resource_mut::<Time>().advance_by(std::time::Duration::from_secs(2));
+        app.update();
+
+        assert_eq!(app.world().get::<Highlighted>(searched), Some(&search));
+        assert!(app.world().get::<Highlighted>(plain).is_none());
+        assert_eq!(app.world().get::<Highlighted>(rehighlighted), Some(&search));
+        for entity in [searched, plain, rehighlighted] {
+            assert!(app.world().get::<TimedHighlight>(entity).is_none());
+        }
+    }
+
+    #[test]
+    fn test_find_path_is_shortest() {
+        let edges = [(1, 2), (2, 3), (3, 4), (1, 5), (5, 4)];
+        assert_eq!(find_path(1, 4, &edges), Some(vec![1, 5, 4]));
+        assert_eq!(find_path(2, 2, &edges), Some(vec![2]));
+    }
+
+    #[test]
+    fn test_find_path_follows_edge_direction() {
+        let edges = [(1, 2), (3, 2)];
+        assert_eq!(find_path(1, 3, &edges), None);
+        assert_eq!(find_path(3, 2, &edges), Some(vec![3, 2]));
+    }
+
+    #[test]
+    fn test_graph_statistics_with_isolated_node() {
+        use bevy::ecs::system::RunSystemOnce;
+
+        let mut app = App::new();
+        let graph_id = ContextGraphId::new();
+        let nodes: Vec<Entity> = (0..4)
+            .map(|_| app.world_mut().spawn((NodeId::new(), NodeInteractionState::default())).id())
+            .collect();
+        app.world_mut().entity_mut(nodes[3]).insert(Selected);
+        // A chain of three nodes and one isolated node
+        for pair in nodes[..3].windows(2) {
+            app.world_mut().spawn((
+                EdgeId::new(),
+                EdgeVisual {
+                    edge_id: ContextEdgeId::new(),
+                    graph_id,
+                    source_entity: pair[0],
+                    target_entity: pair[1],
+                    weight: 1.0,
+                },
+            ));
+        }
+
+        let stats = app.world_mut().run_system_once(query_graph_statistics).unwrap();
+        assert_eq!(stats.node_count, 4);
+        assert_eq!(stats.edge_count, 2);
+        assert_eq!(stats.selected_count, 1);
+        assert_eq!(stats.max_degree, 2);
+        assert_eq!(stats.isolated_node_count, 1);
+        assert_eq!(stats.component_count, 2);
+    }
+
+    #[test]
+    fn test_query_nodes_in_graph_skips_other_graphs() {
+        use bevy::ecs::system::RunSystemOnce;
+
+        let mut world = World::new();
+        let (graph, other) = (GraphId::new(), GraphId::new());
+        let node = world.spawn((NodeId::new(), Position::new(1.0, 2.0, 3.0), graph.clone())).id();
+        world.spawn((NodeId::new(), Position::new(0.0, 0.0, 0.0), other));
+
+        let found = world
+            .run_system_once(move |nodes: Query<(Entity, &NodeId, &Position, &GraphId)>| {
+                query_nodes_in_graph(graph.clone(), nodes)
+            })
+            .unwrap();
+        let entities: Vec<Entity> = found.iter().map(|(entity, _, _)| *entity).collect();
+        assert_eq!(entities, vec![node]);
+        assert_eq!(found[0].2, Position::new(1.0, 2.0, 3.0));
+    }
+
+    #[test]
+    fn test_graph_structure_merges_components() {
+        let structure = graph_structure(&[1, 2, 3, 4, 5], &[(1, 2), (3, 4), (4, 3), (2, 1), (5, 9)]);
+        assert_eq!(structure, GraphStructure { max_degree: 2, isolated_node_count: 1, component_count: 3 });
+    }
+
+    #[test]
+    fn test_k_nearest_nodes_sorted_by_distance() {
+        use crate::events::{CreateNodeVisual, NodeMetadataChanged, VisualNodeCreated};
+        use crate::morphisms::create_node_visual;
+        use bevy::ecs::system::RunSystemOnce;
+
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, TransformPlugin))
+            .init_resource::<NodeEntityMap>()
+            .add_event::<CreateNodeVisual>()
+            .add_event::<VisualNodeCreated>()
+            .add_event::<NodeMetadataChanged>()
+            .add_systems(Update, create_node_visual);
+
+        let graph_id = cim_contextgraph::ContextGraphId::new();
+        let positions = [Vec3::new(3.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0), Vec3::new(-2.0, 0.0, 0.0), Vec3::new(50.0, 0.0, 0.0)];
+        let node_ids: Vec<cim_contextgraph::NodeId> = positions.iter().map(|_| cim_contextgraph::NodeId::new()).collect();
+        for (node_id, position) in node_ids.iter().zip(positions) {
+            app.world_mut().send_event(CreateNodeVisual {
+                node_id: *node_id,
+                graph_id,
+                position,
+                label: String::new(),
+                metadata: serde_json::Value::Null,
+            });
+        }
+        app.update();
+        let entity = |index: usize| *app.world().resource::<NodeEntityMap>().get(&node_ids[index]).unwrap();
+        let (far, near, middle) = (entity(0), entity(1), entity(2));
+
+        let nearest = app.world_mut()
+            .run_system_once(|nodes: Query<(Entity, &NodeVisual, &GlobalTransform)>| {
+                k_nearest_nodes(Vec3::ZERO, 5, 10.0, nodes, None)
+            })
+            .unwrap();
+        let entities: Vec<Entity> = nearest.iter().map(|(entity, _, _)| *entity).collect();
+        assert_eq!(entities, vec![near, middle, far]);
+        assert_eq!((nearest[0].1, nearest[0].2), (node_ids[1], 1.0));
+
+        // Excluding the nearest node, e.g. because it is selected
+        let nearest = app.world_mut()
+            .run_system_once(move |nodes: Query<(Entity, &NodeVisual, &GlobalTransform)>| {
+                k_nearest_nodes(Vec3::ZERO, 1, 10.0, nodes, Some(&|entity| entity != near))
+            })
+            .unwrap();
+        assert_eq!(nearest.len(), 1);
+        assert_eq!(nearest[0].0, middle);
+
+        // The wrapper measures in 3D too, so `near` is 1.0 away
+        let nearest = app.world_mut()
+            .run_system_once(|nodes: Query<(Entity, &NodeVisual, &GlobalTransform)>| {
+                find_nearest_node_system(Vec2::ZERO, 10.0, nodes)
+            })
+            .unwrap();
+        assert_eq!(nearest.map(|(entity, _, distance)| (entity, distance)), Some((near, 1.0)));
+    }
+
+    #[test]
+    fn test_find_path_highlights_nodes_and_edges() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins).add_plugins(QueryHandlerPlugin);
+
+        let graph_id = ContextGraphId::new();
+        let ids: Vec<ContextNodeId> = (0..3).map(|_| ContextNodeId::new()).collect();
+        let nodes: Vec<Entity> = ids.iter()
+            .map(|node_id| app.world_mut().spawn(NodeVisual { node_id: *node_id, graph_id }).id())
+            .collect();
+        let edges: Vec<Entity> = nodes.windows(2)
+            .map(|pair| {
+                app.world_mut().spawn(EdgeVisual {
+                    edge_id: ContextEdgeId::new(),
+                    graph_id,
+                    source_entity: pair[0],
+                    target_entity: pair[1],
+                    weight: 1.0,
+                }).id()
+            })
+            .collect();
+
+        app.world_mut().send_event(FindPath { from: ids[0], to: ids[2] });
+        app.update();
+
+        for entity in nodes.iter().chain(&edges) {
+            assert!(app.world().get::<Highlighted>(*entity).is_some());
+        }
     }
 }
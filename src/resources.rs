@@ -6,6 +6,8 @@
 use bevy::prelude::*;
 use cim_contextgraph::{NodeId, EdgeId, ContextGraphId as GraphId};
 use std::collections::HashMap;
+use crate::easing::Easing;
+use crate::events::EdgeRelationship;
 
 /// Resource tracking the currently active graph
 #[derive(Resource, Default)]
@@ -57,28 +59,159 @@ impl Default for VisualizationConfig {
     }
 }
 
-/// Read-only layout configuration
-#[derive(Resource)]
+/// Read-only layout configuration.
+///
+/// The defaults suit graphs of a few dozen nodes viewed from the default
+/// camera, a few units apart; use [`GraphLayoutConfig::builder`] to change
+/// some of them and keep the rest.
+#[derive(Resource, Debug, Clone, PartialEq)]
 pub struct GraphLayoutConfig {
+    /// Repulsion between every pair of nodes, falling off with the square
+    /// of their distance
     pub force_directed_strength: f32,
+    /// Stiffness of the spring pulling connected nodes to `edge_length`
     pub force_directed_distance: f32,
+    /// Length the force-directed springs along edges settle at
+    pub edge_length: f32,
+    /// Rest lengths of edges of these relationships, instead of `edge_length`
+    pub relationship_edge_lengths: HashMap<EdgeRelationship, f32>,
     pub hierarchical_layer_spacing: f32,
     pub circular_radius: f32,
     pub grid_spacing: f32,
+    /// Maximum distance a node moves in one force-directed layout step
+    pub max_layout_step: f32,
+    /// Seconds nodes take to glide to a newly chosen layout
+    pub transition_duration: f32,
+    /// How nodes glide to a newly chosen layout
+    pub transition_easing: Easing,
+    /// How the clustered layout groups nodes
+    pub clusters: ClusterConfig,
 }
 
 impl Default for GraphLayoutConfig {
     fn default() -> Self {
         Self {
-            force_directed_strength: 100.0,
-            force_directed_distance: 0.1,
-            hierarchical_layer_spacing: 100.0,
-            circular_radius: 200.0,
-            grid_spacing: 50.0,
+            force_directed_strength: 50.0,
+            force_directed_distance: 2.0,
+            edge_length: 5.0,
+            relationship_edge_lengths: HashMap::from([
+                (EdgeRelationship::Contains, 3.0),
+                (EdgeRelationship::References, 8.0),
+            ]),
+            hierarchical_layer_spacing: 4.0,
+            circular_radius: 10.0,
+            grid_spacing: 4.0,
+            max_layout_step: 1.0,
+            transition_duration: 0.75,
+            transition_easing: Easing::EaseOutCubic,
+            clusters: ClusterConfig::default(),
+        }
+    }
+}
+
+impl GraphLayoutConfig {
+    /// Start from the defaults, changing only the values that are set
+    pub fn builder() -> GraphLayoutConfigBuilder {
+        GraphLayoutConfigBuilder::default()
+    }
+
+    /// Rest length of an edge with `relationship`, falling back to
+    /// `edge_length` for unlabelled edges and unlisted relationships
+    pub fn edge_length_for(&self, relationship: Option<&EdgeRelationship>) -> f32 {
+        relationship
+            .and_then(|relationship| self.relationship_edge_lengths.get(relationship))
+            .copied()
+            .unwrap_or(self.edge_length)
+    }
+}
+
+/// Grouping of nodes in the clustered layout
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClusterConfig {
+    /// `NodeMetadata` attribute whose value puts nodes in the same cluster;
+    /// `"tags"` clusters them by their first tag. Nodes without one share
+    /// a cluster.
+    pub key: String,
+    /// Distance from its center within which a cluster's nodes are placed
+    pub cluster_radius: f32,
+}
+
+impl Default for ClusterConfig {
+    fn default() -> Self {
+        Self {
+            key: "tags".to_string(),
+            cluster_radius: 5.0,
         }
     }
 }
 
+/// Builder for a [`GraphLayoutConfig`], see [`GraphLayoutConfig::builder`]
+#[derive(Debug, Clone, Default)]
+pub struct GraphLayoutConfigBuilder {
+    config: GraphLayoutConfig,
+}
+
+impl GraphLayoutConfigBuilder {
+    pub fn force_strength(mut self, strength: f32) -> Self {
+        self.config.force_directed_strength = strength;
+        self
+    }
+
+    pub fn spring_stiffness(mut self, stiffness: f32) -> Self {
+        self.config.force_directed_distance = stiffness;
+        self
+    }
+
+    pub fn edge_length(mut self, length: f32) -> Self {
+        self.config.edge_length = length;
+        self
+    }
+
+    pub fn relationship_edge_length(mut self, relationship: EdgeRelationship, length: f32) -> Self {
+        self.config.relationship_edge_lengths.insert(relationship, length);
+        self
+    }
+
+    pub fn layer_spacing(mut self, spacing: f32) -> Self {
+        self.config.hierarchical_layer_spacing = spacing;
+        self
+    }
+
+    pub fn circular_radius(mut self, radius: f32) -> Self {
+        self.config.circular_radius = radius;
+        self
+    }
+
+    pub fn grid_spacing(mut self, spacing: f32) -> Self {
+        self.config.grid_spacing = spacing;
+        self
+    }
+
+    pub fn max_layout_step(mut self, step: f32) -> Self {
+        self.config.max_layout_step = step;
+        self
+    }
+
+    pub fn transition_duration(mut self, seconds: f32) -> Self {
+        self.config.transition_duration = seconds;
+        self
+    }
+
+    pub fn transition_easing(mut self, easing: Easing) -> Self {
+        self.config.transition_easing = easing;
+        self
+    }
+
+    pub fn clusters(mut self, clusters: ClusterConfig) -> Self {
+        self.config.clusters = clusters;
+        self
+    }
+
+    pub fn build(self) -> GraphLayoutConfig {
+        self.config
+    }
+}
+
 /// Configuration for force-directed layout
 #[derive(Debug, Clone)]
 pub struct ForceDirectedConfig {
@@ -288,5 +421,21 @@ mod tests {
         assert!(bbox.contains(Vec3::splat(0.5)));
         assert!(!bbox.contains(Vec3::splat(2.0)));
     }
+
+    #[test]
+    fn test_layout_config_builder_keeps_unset_defaults() {
+        let config = GraphLayoutConfig::builder()
+            .force_strength(80.0)
+            .circular_radius(25.0)
+            .build();
+
+        assert_eq!(config.force_directed_strength, 80.0);
+        assert_eq!(config.circular_radius, 25.0);
+        assert_eq!(config, GraphLayoutConfig {
+            force_directed_strength: 80.0,
+            circular_radius: 25.0,
+            ..GraphLayoutConfig::default()
+        });
+    }
 }
 
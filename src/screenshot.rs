@@ -0,0 +1,231 @@
+//! Screenshots: Saving the current view as an image
+//!
+//! [`CaptureScreenshot`] saves what the primary window, or a render target
+//! image for headless runs, shows to a file whose extension picks the format.
+//! With `frame_graph` set the camera is first sent a [`FocusCamera`] on every
+//! node and the capture waits a frame, and for any camera animation, so the
+//! picture shows the settled view of the whole graph. [`ScreenshotSaved`] is
+//! sent once the file is written.
+
+use bevy::prelude::*;
+use bevy::render::view::screenshot::{Screenshot, ScreenshotCaptured};
+use std::path::{Path, PathBuf};
+use crate::camera::CameraAnimation;
+use crate::components::{GraphCamera, NodeVisual};
+use crate::events::FocusCamera;
+
+/// Frames a framed capture waits for the camera to move
+const FRAMING_SETTLE_FRAMES: u32 = 1;
+
+/// Command: Save the current view to `path`
+#[derive(Event, Debug, Clone)]
+pub struct CaptureScreenshot {
+    pub path: PathBuf,
+    /// Frame the whole graph before capturing
+    pub frame_graph: bool,
+    /// Image to capture instead of the primary window
+    pub target: Option<Handle<Image>>,
+}
+
+impl CaptureScreenshot {
+    /// Capture the primary window as it is
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            frame_graph: false,
+            target: None,
+        }
+    }
+}
+
+/// Event: A screenshot has been written to `path`
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct ScreenshotSaved {
+    pub path: PathBuf,
+}
+
+/// Errors that can occur while saving a screenshot
+#[derive(Debug, thiserror::Error)]
+pub enum ScreenshotError {
+    #[error("captured image cannot be converted: {0}")]
+    Conversion(String),
+    #[error("cannot write {path}: {message}")]
+    Write { path: PathBuf, message: String },
+}
+
+/// Write a captured image to `path`, in the format its extension names
+pub fn save_image(image: Image, path: &Path) -> Result<(), ScreenshotError> {
+    let image = image.try_into_dynamic()
+        .map_err(|error| ScreenshotError::Conversion(error.to_string()))?;
+    // The alpha channel holds brightness with HDR on, so it is dropped
+    image.to_rgb8().save(path).map_err(|error| ScreenshotError::Write {
+        path: path.to_path_buf(),
+        message: error.to_string(),
+    })
+}
+
+/// A capture waiting for its frame
+#[derive(Debug, Clone)]
+struct PendingCapture {
+    request: CaptureScreenshot,
+    frames_to_wait: u32,
+}
+
+/// Captures requested but not taken yet
+#[derive(Resource, Debug, Default)]
+pub struct PendingScreenshots {
+    captures: Vec<PendingCapture>,
+}
+
+impl PendingScreenshots {
+    pub fn len(&self) -> usize {
+        self.captures.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.captures.is_empty()
+    }
+}
+
+/// Plugin that saves screenshots on request
+pub struct GraphScreenshotPlugin;
+
+impl Plugin for GraphScreenshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PendingScreenshots>()
+            .add_event::<CaptureScreenshot>()
+            .add_event::<ScreenshotSaved>()
+            .add_event::<FocusCamera>()
+            .add_systems(Update, (queue_screenshots, take_screenshots).chain());
+    }
+}
+
+/// System that queues requested captures, framing the graph for those that
+/// ask for it
+pub fn queue_screenshots(
+    mut events: EventReader<CaptureScreenshot>,
+    mut pending: ResMut<PendingScreenshots>,
+    nodes: Query<Entity, With<NodeVisual>>,
+    mut focus_camera: EventWriter<FocusCamera>,
+) {
+    for event in events.read() {
+        let frames_to_wait = if event.frame_graph && !nodes.is_empty() {
+            focus_camera.write(FocusCamera {
+                target_entities: nodes.iter().collect(),
+                target_point: None,
+                transition_duration: 0.0,
+            });
+            FRAMING_SETTLE_FRAMES
+        } else {
+            0
+        };
+        pending.captures.push(PendingCapture { request: event.clone(), frames_to_wait });
+    }
+}
+
+/// System that captures the queued screenshots whose frame has come. The
+/// image is written when the renderer hands it back, a few frames later.
+pub fn take_screenshots(
+    mut commands: Commands,
+    mut pending: ResMut<PendingScreenshots>,
+    animating: Query<(), (With<GraphCamera>, With<CameraAnimation>)>,
+) {
+    let camera_settled = animating.is_empty();
+    let mut ready = Vec::new();
+    pending.captures.retain_mut(|capture| {
+        if capture.frames_to_wait > 0 {
+            capture.frames_to_wait -= 1;
+            return true;
+        }
+        if !camera_settled {
+            return true;
+        }
+        ready.push(capture.request.clone());
+        false
+    });
+
+    for request in ready {
+        let screenshot = match request.target {
+            Some(image) => Screenshot::image(image),
+            None => Screenshot::primary_window(),
+        };
+        let path = request.path;
+        commands.spawn(screenshot).observe(
+            move |trigger: Trigger<ScreenshotCaptured>, mut saved: EventWriter<ScreenshotSaved>| {
+                match save_image(trigger.event().0.clone(), &path) {
+                    Ok(()) => {
+                        info!("Screenshot saved to {}", path.display());
+                        saved.write(ScreenshotSaved { path: path.clone() });
+                    }
+                    Err(error) => error!("Cannot save screenshot: {error}"),
+                }
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+    use bevy::render::render_asset::RenderAssetUsages;
+    use cim_contextgraph::{NodeId, ContextGraphId as GraphId};
+    use crate::components::NodeVisualBundle;
+    use crate::easing::Easing;
+
+    #[test]
+    fn test_framed_capture_waits_for_the_camera() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, GraphScreenshotPlugin));
+        let graph_id = GraphId::new();
+        for position in [Vec3::ZERO, Vec3::X * 10.0] {
+            app.world_mut().spawn(NodeVisualBundle::new(NodeId::new(), graph_id, position));
+        }
+        let camera = app.world_mut().spawn((GraphCamera, Transform::default())).id();
+        let screenshots = |app: &mut App| {
+            let world = app.world_mut();
+            world.query::<&Screenshot>().iter(world).count()
+        };
+
+        app.world_mut().send_event(CaptureScreenshot { frame_graph: true, ..CaptureScreenshot::new("graph.png") });
+        app.update();
+        let focus = app.world().resource::<Events<FocusCamera>>();
+        assert_eq!(focus.iter_current_update_events().next().unwrap().target_entities.len(), 2);
+        assert_eq!(screenshots(&mut app), 0);
+
+        // Still moving: the capture holds off until the camera arrives
+        app.world_mut().entity_mut(camera).insert(CameraAnimation {
+            from_position: Vec3::ZERO,
+            from_target: Vec3::NEG_Z,
+            to_position: Vec3::Z,
+            to_target: Vec3::ZERO,
+            elapsed: 0.0,
+            duration: 1.0,
+            easing: Easing::default(),
+        });
+        app.update();
+        assert_eq!(screenshots(&mut app), 0);
+
+        app.world_mut().entity_mut(camera).remove::<CameraAnimation>();
+        app.update();
+        assert_eq!(screenshots(&mut app), 1);
+        assert!(app.world().resource::<PendingScreenshots>().is_empty());
+    }
+
+    #[test]
+    fn test_save_image_writes_a_png() {
+        let image = Image::new_fill(
+            Extent3d { width: 4, height: 2, depth_or_array_layers: 1 },
+            TextureDimension::D2,
+            &[255, 0, 0, 255],
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+        let path = std::env::temp_dir().join(format!("screenshot-{}.png", std::process::id()));
+        save_image(image, &path).unwrap();
+
+        let written = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(&written[1..4], b"PNG");
+    }
+}
@@ -0,0 +1,667 @@
+//! Node Selection: Rubber-band and bulk selection
+//!
+//! Dragging with the left mouse button over empty space draws a screen-space
+//! rectangle; on release every [`NodeVisual`] whose projected position lies
+//! inside it becomes [`Selected`]. Holding Shift adds to the current
+//! selection instead of replacing it. A drag started in a graph's region only
+//! selects nodes of that graph. While edges are being created, dragging draws
+//! edges and never selects. [`ClearSelection`], [`SelectAll`] and
+//! [`SelectNode`] are handled here as well, by [`SelectionCommandsPlugin`],
+//! which works without picking and is also added by the
+//! `CommandHandlerPlugin`. Every change is reported through
+//! [`SelectionChanged`], with a [`NodeSelected`] or [`NodeDeselected`] for
+//! each node that joined or left the selection.
+//!
+//! With a single node selected the keyboard moves the selection along edges:
+//! Tab cycles through the node's neighbors and the arrow keys jump to the
+//! neighbor that lies closest to that direction on screen. Delete or
+//! Backspace asks the domain to delete the selected nodes with a
+//! [`RequestDeleteSelected`].
+//!
+//! The [`Selected`] and [`Hovered`] markers are the only source of truth for
+//! interaction state. The [`NodeInteractionState`] every `NodeVisualBundle`
+//! carries is mirrored from the markers by [`sync_interaction_state`].
+
+use bevy::prelude::*;
+use cim_contextgraph::{NodeId, ContextGraphId as GraphId};
+use crate::components::{Dragging, EdgeVisual, GraphCamera, Hovered, NodeVisual, Selected};
+use crate::events::{
+    ClearSelection, FocusCamera, NodeDeselected, NodeSelected, RequestDeleteSelected, SelectAll, SelectNode,
+    SelectionChanged,
+};
+use crate::edge_creation::creating_edges;
+use crate::morphisms::{NodeEntityMap, NodeEntityMapPlugin};
+use crate::picking::{cursor_position, egui_wants_keyboard, world_to_screen, PickingPlugin, PickingSet, PickingState};
+use crate::resources::Selection;
+use crate::value_objects::NodeInteractionState;
+
+/// In-progress rubber-band drag, in window logical pixels
+#[derive(Resource, Debug, Default, Clone, Copy)]
+pub struct BoxSelection {
+    pub start: Option<Vec2>,
+    pub current: Vec2,
+    /// Graph whose region the drag started in
+    pub graph: Option<GraphId>,
+}
+
+impl BoxSelection {
+    /// The rectangle currently being dragged, if any
+    pub fn rect(&self) -> Option<Rect> {
+        self.start.map(|start| Rect::from_corners(start, self.current))
+    }
+}
+
+/// Settings and Tab-cycling state for keyboard navigation
+#[derive(Resource, Debug, Clone)]
+pub struct KeyboardNavigation {
+    /// Emit `FocusCamera` on the newly focused node
+    pub focus_camera: bool,
+    /// Camera transition duration used when focusing (seconds)
+    pub transition_duration: f32,
+    /// Node whose neighbors Tab is cycling through, and the current index
+    cycle: Option<(Entity, usize)>,
+}
+
+impl Default for KeyboardNavigation {
+    fn default() -> Self {
+        Self {
+            focus_camera: true,
+            transition_duration: 0.3,
+            cycle: None,
+        }
+    }
+}
+
+/// Events reporting a change of the selection
+#[derive(bevy::ecs::system::SystemParam)]
+pub struct SelectionEvents<'w> {
+    changed: EventWriter<'w, SelectionChanged>,
+    node_selected: EventWriter<'w, NodeSelected>,
+    node_deselected: EventWriter<'w, NodeDeselected>,
+}
+
+/// Marks the UI node used to draw the selection rectangle
+#[derive(Component)]
+pub struct SelectionBoxOverlay;
+
+/// Plugin for the selection commands and the interaction state mirrored
+/// from the markers; needs no picking or input
+pub struct SelectionCommandsPlugin;
+
+impl Plugin for SelectionCommandsPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<NodeEntityMapPlugin>() {
+            app.add_plugins(NodeEntityMapPlugin);
+        }
+
+        app.add_event::<SelectionChanged>()
+            .add_event::<NodeSelected>()
+            .add_event::<NodeDeselected>()
+            .add_event::<ClearSelection>()
+            .add_event::<SelectAll>()
+            .add_event::<SelectNode>()
+            .init_resource::<Selection>()
+            .add_systems(Update, handle_selection_commands.in_set(PickingSet::Selection))
+            .add_systems(PostUpdate, sync_interaction_state);
+    }
+}
+
+/// Plugin for rubber-band selection and the selection commands
+pub struct SelectionPlugin;
+
+impl Plugin for SelectionPlugin {
+    fn build(&self, app: &mut App) {
+        // Box selection only starts when no node is under the cursor
+        if !app.is_plugin_added::<PickingPlugin>() {
+            app.add_plugins(PickingPlugin);
+        }
+        if !app.is_plugin_added::<SelectionCommandsPlugin>() {
+            app.add_plugins(SelectionCommandsPlugin);
+        }
+
+        app.add_event::<FocusCamera>()
+            .add_event::<RequestDeleteSelected>()
+            .init_resource::<BoxSelection>()
+            .init_resource::<KeyboardNavigation>()
+            .add_systems(Startup, spawn_selection_box_overlay)
+            .add_systems(
+                Update,
+                (
+                    box_select.run_if(not(creating_edges)),
+                    navigate_selection,
+                    request_delete_selected.run_if(not(egui_wants_keyboard)),
+                    update_selection_box_overlay,
+                )
+                    .chain()
+                    .after(handle_selection_commands)
+                    .in_set(PickingSet::Selection),
+            );
+    }
+}
+
+/// System that mirrors the interaction markers into [`NodeInteractionState`]
+pub fn sync_interaction_state(
+    mut states: Query<(&mut NodeInteractionState, Has<Selected>, Has<Hovered>, Has<Dragging>)>,
+) {
+    for (mut state, is_selected, is_hovered, is_dragging) in states.iter_mut() {
+        state.set_if_neq(NodeInteractionState { is_hovered, is_selected, is_dragging });
+    }
+}
+
+/// Combine the current selection with newly picked nodes
+pub fn merge_selection(
+    current: &[(Entity, NodeId)],
+    picked: Vec<(Entity, NodeId)>,
+    additive: bool,
+) -> Vec<(Entity, NodeId)> {
+    if !additive {
+        return picked;
+    }
+
+    let mut merged = current.to_vec();
+    for (entity, node_id) in picked {
+        if !merged.iter().any(|(existing, _)| *existing == entity) {
+            merged.push((entity, node_id));
+        }
+    }
+    merged
+}
+
+/// Replace the selected nodes, keeping `Selected` markers and the
+/// [`Selection`] resource in sync
+fn apply_selection(
+    commands: &mut Commands,
+    selection: &mut Selection,
+    previously_selected: impl Iterator<Item = Entity>,
+    nodes: Vec<(Entity, NodeId)>,
+    events: &mut SelectionEvents,
+) {
+    for entity in previously_selected {
+        if !nodes.iter().any(|(selected, _)| *selected == entity) {
+            commands.entity(entity).remove::<Selected>();
+        }
+    }
+    for (entity, _) in &nodes {
+        commands.entity(*entity).insert(Selected);
+    }
+
+    for &(entity, node_id) in &selection.nodes {
+        if !nodes.iter().any(|(selected, _)| *selected == entity) {
+            events.node_deselected.write(NodeDeselected { entity, node_id });
+        }
+    }
+    for &(entity, node_id) in &nodes {
+        if !selection.nodes.iter().any(|(selected, _)| *selected == entity) {
+            events.node_selected.write(NodeSelected { entity, node_id });
+        }
+    }
+
+    selection.nodes = nodes;
+    events.changed.write(SelectionChanged {
+        selected_nodes: selection.nodes.iter().map(|(_, id)| *id).collect(),
+        selected_edges: selection.edges.iter().map(|(_, id)| *id).collect(),
+    });
+}
+
+/// Handle `ClearSelection`, `SelectAll` and `SelectNode`
+#[allow(clippy::too_many_arguments)]
+fn handle_selection_commands(
+    mut commands: Commands,
+    mut clear_events: EventReader<ClearSelection>,
+    mut select_all_events: EventReader<SelectAll>,
+    mut select_node_events: EventReader<SelectNode>,
+    mut selection: ResMut<Selection>,
+    node_map: Res<NodeEntityMap>,
+    nodes: Query<(Entity, &NodeVisual)>,
+    selected: Query<Entity, With<Selected>>,
+    mut selection_events: SelectionEvents,
+) {
+    if clear_events.read().count() > 0 {
+        selection.edges.clear();
+        apply_selection(
+            &mut commands,
+            &mut selection,
+            selected.iter(),
+            Vec::new(),
+            &mut selection_events,
+        );
+    }
+
+    if select_all_events.read().count() > 0 {
+        let all = nodes.iter().map(|(entity, node)| (entity, node.node_id)).collect();
+        apply_selection(
+            &mut commands,
+            &mut selection,
+            selected.iter(),
+            all,
+            &mut selection_events,
+        );
+    }
+
+    for event in select_node_events.read() {
+        let Some(&entity) = node_map.get(&event.node_id) else {
+            continue;
+        };
+        let nodes = merge_selection(&selection.nodes, vec![(entity, event.node_id)], event.multi_select);
+        apply_selection(
+            &mut commands,
+            &mut selection,
+            selected.iter(),
+            nodes,
+            &mut selection_events,
+        );
+    }
+}
+
+/// Track left-drags over empty space and select the nodes inside on release
+#[allow(clippy::too_many_arguments)]
+fn box_select(
+    mut commands: Commands,
+    mut box_selection: ResMut<BoxSelection>,
+    mut selection: ResMut<Selection>,
+    picking: Res<PickingState>,
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    windows: Query<&Window>,
+    cameras: Query<(&Camera, &GlobalTransform), With<GraphCamera>>,
+    nodes: Query<(Entity, &NodeVisual, &GlobalTransform)>,
+    selected: Query<Entity, With<Selected>>,
+    mut selection_events: SelectionEvents,
+) {
+    let cursor = cursor_position(windows.iter());
+
+    if mouse_button.just_pressed(MouseButton::Left) && picking.hovered.is_none() {
+        if let Some(cursor) = cursor {
+            box_selection.start = Some(cursor);
+            box_selection.current = cursor;
+            box_selection.graph = picking.graph;
+        }
+    }
+
+    if let Some(cursor) = cursor {
+        if box_selection.start.is_some() {
+            box_selection.current = cursor;
+        }
+    }
+
+    if !mouse_button.just_released(MouseButton::Left) {
+        return;
+    }
+    let Some(rect) = box_selection.rect() else {
+        return;
+    };
+    box_selection.start = None;
+
+    // Project through the camera the drag started in
+    let Some((camera, camera_transform)) = cameras.iter()
+        .filter(|(camera, _)| camera.is_active)
+        .find(|(camera, _)| {
+            camera.logical_viewport_rect()
+                .is_some_and(|viewport| viewport.contains(rect.min) || viewport.contains(rect.max))
+        })
+    else {
+        return;
+    };
+
+    let picked: Vec<_> = nodes.iter()
+        .filter(|(_, node, _)| box_selection.graph.is_none_or(|graph_id| node.graph_id == graph_id))
+        .filter(|(_, _, transform)| {
+            world_to_screen(camera, camera_transform, transform.translation())
+                .is_some_and(|position| rect.contains(position))
+        })
+        .map(|(entity, node, _)| (entity, node.node_id))
+        .collect();
+
+    let additive = keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let nodes = merge_selection(&selection.nodes, picked, additive);
+    apply_selection(
+        &mut commands,
+        &mut selection,
+        selected.iter(),
+        nodes,
+        &mut selection_events,
+    );
+}
+
+/// Entities connected to `node` by an edge, in a stable order
+pub fn neighbors<'a>(node: Entity, edges: impl IntoIterator<Item = &'a EdgeVisual>) -> Vec<Entity> {
+    let mut neighbors: Vec<Entity> = edges
+        .into_iter()
+        .filter_map(|edge| {
+            if edge.source_entity == node {
+                Some(edge.target_entity)
+            } else if edge.target_entity == node {
+                Some(edge.source_entity)
+            } else {
+                None
+            }
+        })
+        .filter(|neighbor| *neighbor != node)
+        .collect();
+    neighbors.sort();
+    neighbors.dedup();
+    neighbors
+}
+
+/// Pick the candidate whose screen position best matches `direction` from
+/// `from`. Candidates behind the direction are ignored; ties in angle go to
+/// the closer node.
+pub fn pick_in_direction(from: Vec2, direction: Vec2, candidates: &[(Entity, Vec2)]) -> Option<Entity> {
+    candidates
+        .iter()
+        .filter_map(|(entity, position)| {
+            let offset = *position - from;
+            let cos = offset.normalize_or_zero().dot(direction);
+            (cos > 0.0).then_some((*entity, cos, offset.length()))
+        })
+        .max_by(|(_, cos_a, dist_a), (_, cos_b, dist_b)| {
+            cos_a.total_cmp(cos_b).then(dist_b.total_cmp(dist_a))
+        })
+        .map(|(entity, _, _)| entity)
+}
+
+/// Move a single selected node along its edges with Tab and the arrow keys
+#[allow(clippy::too_many_arguments)]
+fn navigate_selection(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut navigation: ResMut<KeyboardNavigation>,
+    mut selection: ResMut<Selection>,
+    node_map: Res<NodeEntityMap>,
+    cameras: Query<(&Camera, &GlobalTransform), With<GraphCamera>>,
+    nodes: Query<(&NodeVisual, &GlobalTransform)>,
+    edges: Query<&EdgeVisual>,
+    selected: Query<Entity, With<Selected>>,
+    mut selection_events: SelectionEvents,
+    mut focus_camera: EventWriter<FocusCamera>,
+) {
+    const ARROWS: [(KeyCode, Vec2); 4] = [
+        (KeyCode::ArrowUp, Vec2::NEG_Y),
+        (KeyCode::ArrowDown, Vec2::Y),
+        (KeyCode::ArrowLeft, Vec2::NEG_X),
+        (KeyCode::ArrowRight, Vec2::X),
+    ];
+
+    let tab = keyboard.just_pressed(KeyCode::Tab);
+    let arrow = ARROWS.iter().find(|(key, _)| keyboard.just_pressed(*key));
+    if !tab && arrow.is_none() {
+        return;
+    }
+
+    let [(selected_entity, node_id)] = selection.nodes[..] else {
+        return;
+    };
+    let current = node_map.get(&node_id).copied().unwrap_or(selected_entity);
+
+    let next = if tab {
+        // Keep cycling around the node Tab started from while we're still
+        // on one of its neighbors
+        let anchor = match navigation.cycle {
+            Some((anchor, index)) if neighbors(anchor, edges.iter()).get(index) == Some(&current) => anchor,
+            _ => current,
+        };
+        let ring = neighbors(anchor, edges.iter());
+        if ring.is_empty() {
+            return;
+        }
+
+        let backwards = keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+        let index = match navigation.cycle {
+            Some((cycle_anchor, index)) if cycle_anchor == anchor && anchor != current => {
+                if backwards {
+                    (index + ring.len() - 1) % ring.len()
+                } else {
+                    (index + 1) % ring.len()
+                }
+            }
+            _ if backwards => ring.len() - 1,
+            _ => 0,
+        };
+        navigation.cycle = Some((anchor, index));
+        ring[index]
+    } else {
+        navigation.cycle = None;
+        let Some((_, direction)) = arrow else {
+            return;
+        };
+        let Some((camera, camera_transform)) = cameras.iter().find(|(camera, _)| camera.is_active) else {
+            return;
+        };
+        let project = |entity: Entity| {
+            nodes.get(entity).ok().and_then(|(_, transform)| {
+                world_to_screen(camera, camera_transform, transform.translation())
+            })
+        };
+
+        let Some(from) = project(current) else {
+            return;
+        };
+        let candidates: Vec<_> = neighbors(current, edges.iter())
+            .into_iter()
+            .filter_map(|entity| project(entity).map(|position| (entity, position)))
+            .collect();
+        let Some(next) = pick_in_direction(from, *direction, &candidates) else {
+            return;
+        };
+        next
+    };
+
+    let Ok((node, _)) = nodes.get(next) else {
+        return;
+    };
+    apply_selection(
+        &mut commands,
+        &mut selection,
+        selected.iter(),
+        vec![(next, node.node_id)],
+        &mut selection_events,
+    );
+
+    if navigation.focus_camera {
+        focus_camera.write(FocusCamera {
+            target_entities: vec![next],
+            target_point: None,
+            transition_duration: navigation.transition_duration,
+        });
+    }
+}
+
+/// Ask for the selected nodes to be deleted when Delete or Backspace is
+/// pressed
+fn request_delete_selected(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    selected: Query<(), With<Selected>>,
+    mut delete_requested: EventWriter<RequestDeleteSelected>,
+) {
+    if keyboard.any_just_pressed([KeyCode::Delete, KeyCode::Backspace]) && !selected.is_empty() {
+        delete_requested.write(RequestDeleteSelected);
+    }
+}
+
+/// Spawn the (initially hidden) selection rectangle
+fn spawn_selection_box_overlay(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            border: UiRect::all(Val::Px(1.0)),
+            ..default()
+        },
+        BorderColor(Color::srgba(0.4, 0.7, 1.0, 0.9)),
+        BackgroundColor(Color::srgba(0.4, 0.7, 1.0, 0.15)),
+        Visibility::Hidden,
+        SelectionBoxOverlay,
+    ));
+}
+
+/// Keep the selection rectangle in line with the current drag
+fn update_selection_box_overlay(
+    box_selection: Res<BoxSelection>,
+    mut overlays: Query<(&mut Node, &mut Visibility), With<SelectionBoxOverlay>>,
+) {
+    for (mut node, mut visibility) in overlays.iter_mut() {
+        match box_selection.rect() {
+            Some(rect) => {
+                node.left = Val::Px(rect.min.x);
+                node.top = Val::Px(rect.min.y);
+                node.width = Val::Px(rect.width());
+                node.height = Val::Px(rect.height());
+                *visibility = Visibility::Visible;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_selection_replaces_without_shift() {
+        let a = (Entity::from_raw(1), NodeId::new());
+        let b = (Entity::from_raw(2), NodeId::new());
+
+        assert_eq!(merge_selection(&[a], vec![b], false), vec![b]);
+    }
+
+    #[test]
+    fn test_merge_selection_adds_with_shift() {
+        let a = (Entity::from_raw(1), NodeId::new());
+        let b = (Entity::from_raw(2), NodeId::new());
+
+        assert_eq!(merge_selection(&[a], vec![b, a], true), vec![a, b]);
+    }
+
+    #[test]
+    fn test_box_selection_rect_is_normalized() {
+        let box_selection = BoxSelection {
+            start: Some(Vec2::new(100.0, 80.0)),
+            current: Vec2::new(20.0, 200.0),
+            graph: None,
+        };
+
+        let rect = box_selection.rect().unwrap();
+        assert_eq!(rect.min, Vec2::new(20.0, 80.0));
+        assert_eq!(rect.max, Vec2::new(100.0, 200.0));
+        assert!(BoxSelection::default().rect().is_none());
+    }
+
+    #[test]
+    fn test_interaction_state_mirrors_markers() {
+        let mut app = App::new();
+        app.add_systems(Update, sync_interaction_state);
+        let node = app.world_mut().spawn((NodeInteractionState::default(), Selected, Hovered)).id();
+        app.update();
+        assert_eq!(
+            app.world().get::<NodeInteractionState>(node),
+            Some(&NodeInteractionState { is_hovered: true, is_selected: true, is_dragging: false }),
+        );
+
+        app.world_mut().entity_mut(node).remove::<Selected>();
+        app.update();
+        assert!(!app.world().get::<NodeInteractionState>(node).unwrap().is_selected);
+    }
+
+    #[test]
+    fn test_select_node_reports_nodes_joining_and_leaving() {
+        use crate::components::NodeVisualBundle;
+        use crate::morphisms::NodeEntityMapPlugin;
+
+        let mut app = App::new();
+        app.add_plugins(NodeEntityMapPlugin)
+            .init_resource::<Selection>()
+            .add_event::<SelectionChanged>()
+            .add_event::<NodeSelected>()
+            .add_event::<NodeDeselected>()
+            .add_event::<ClearSelection>()
+            .add_event::<SelectAll>()
+            .add_event::<SelectNode>()
+            .add_systems(Update, handle_selection_commands)
+            .add_systems(PostUpdate, sync_interaction_state);
+
+        let graph_id = GraphId::new();
+        let (first_id, second_id) = (NodeId::new(), NodeId::new());
+        let first = app.world_mut().spawn(NodeVisualBundle::new(first_id, graph_id, Vec3::ZERO)).id();
+        let second = app.world_mut().spawn(NodeVisualBundle::new(second_id, graph_id, Vec3::X)).id();
+        let reported = |app: &App| {
+            let world = app.world();
+            let selected: Vec<Entity> = world.resource::<Events<NodeSelected>>()
+                .iter_current_update_events()
+                .map(|event| event.entity)
+                .collect();
+            let deselected: Vec<Entity> = world.resource::<Events<NodeDeselected>>()
+                .iter_current_update_events()
+                .map(|event| event.entity)
+                .collect();
+            (selected, deselected)
+        };
+
+        app.world_mut().send_event(SelectNode { node_id: first_id, multi_select: false });
+        app.update();
+        assert_eq!(reported(&app), (vec![first], vec![]));
+        assert!(app.world().get::<NodeInteractionState>(first).unwrap().is_selected);
+
+        app.world_mut().send_event(SelectNode { node_id: second_id, multi_select: false });
+        app.update();
+        assert_eq!(reported(&app), (vec![second], vec![first]));
+        assert!(!app.world().get::<NodeInteractionState>(first).unwrap().is_selected);
+        assert!(app.world().get::<NodeInteractionState>(second).unwrap().is_selected);
+    }
+
+    #[test]
+    fn test_delete_key_requests_deleting_the_selection() {
+        let mut app = App::new();
+        app.init_resource::<ButtonInput<KeyCode>>()
+            .add_event::<RequestDeleteSelected>()
+            .add_systems(Update, request_delete_selected);
+        let requests = |app: &App| app.world().resource::<Events<RequestDeleteSelected>>().iter_current_update_events().count();
+
+        // Nothing to delete without a selection
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::Delete);
+        app.update();
+        assert_eq!(requests(&app), 0);
+
+        app.world_mut().spawn(Selected);
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().release(KeyCode::Delete);
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().clear();
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::Delete);
+        app.update();
+        assert_eq!(requests(&app), 1);
+    }
+
+    fn edge(source: u32, target: u32) -> EdgeVisual {
+        EdgeVisual {
+            edge_id: cim_contextgraph::EdgeId::new(),
+            graph_id: cim_contextgraph::ContextGraphId::new(),
+            source_entity: Entity::from_raw(source),
+            target_entity: Entity::from_raw(target),
+            weight: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_neighbors_follow_edges_both_ways() {
+        let edges = [edge(1, 2), edge(3, 1), edge(2, 3), edge(1, 2)];
+
+        assert_eq!(
+            neighbors(Entity::from_raw(1), &edges),
+            vec![Entity::from_raw(2), Entity::from_raw(3)]
+        );
+    }
+
+    #[test]
+    fn test_pick_in_direction() {
+        let right = Entity::from_raw(1);
+        let far_right = Entity::from_raw(2);
+        let up = Entity::from_raw(3);
+        let candidates = [
+            (right, Vec2::new(50.0, 0.0)),
+            (far_right, Vec2::new(200.0, 0.0)),
+            (up, Vec2::new(10.0, -80.0)),
+        ];
+
+        assert_eq!(pick_in_direction(Vec2::ZERO, Vec2::X, &candidates), Some(right));
+        assert_eq!(pick_in_direction(Vec2::ZERO, Vec2::NEG_Y, &candidates), Some(up));
+        assert_eq!(pick_in_direction(Vec2::ZERO, Vec2::NEG_X, &candidates), None);
+    }
+}
@@ -0,0 +1,218 @@
+//! Graph Serialization: Saving and restoring visual graphs
+//!
+//! A [`GraphSnapshot`] captures the nodes of one graph with their current
+//! positions and metadata, plus the edges between them. Restoring a snapshot
+//! goes through the usual `CreateNodeVisual` / `CreateEdgeVisual` commands, so
+//! restored entities are indistinguishable from ones created by the domain.
+
+use bevy::prelude::*;
+use cim_contextgraph::{NodeId, EdgeId, ContextGraphId as GraphId};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use crate::components::{EdgeLabel, EdgeVisual, NodeVisual};
+use crate::events::{CreateEdgeVisual, CreateNodeVisual, EdgeRelationship};
+use crate::value_objects::NodeMetadata;
+
+/// A node as stored in a snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeSnapshot {
+    pub node_id: NodeId,
+    pub position: [f32; 3],
+    pub metadata: NodeMetadata,
+}
+
+/// An edge as stored in a snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EdgeSnapshot {
+    pub edge_id: EdgeId,
+    pub source_node_id: NodeId,
+    pub target_node_id: NodeId,
+    pub relationship: EdgeRelationship,
+    /// Missing in snapshots saved before edges had weights
+    #[serde(default = "default_edge_weight")]
+    pub weight: f32,
+}
+
+fn default_edge_weight() -> f32 {
+    1.0
+}
+
+/// Serializable state of a single visual graph
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphSnapshot {
+    pub graph_id: GraphId,
+    pub nodes: Vec<NodeSnapshot>,
+    pub edges: Vec<EdgeSnapshot>,
+}
+
+/// Errors that can occur while saving or loading snapshots
+#[derive(Debug, thiserror::Error)]
+pub enum SnapshotError {
+    #[error("invalid snapshot file: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("failed to access snapshot file: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl GraphSnapshot {
+    /// Write the snapshot as pretty-printed JSON
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SnapshotError> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Read a snapshot previously written by [`GraphSnapshot::save`]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SnapshotError> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+}
+
+/// Capture every node and edge of `graph_id` currently in the world
+pub fn serialize_graph(world: &World, graph_id: GraphId) -> GraphSnapshot {
+    let mut nodes = Vec::new();
+    let mut node_ids = HashMap::new();
+    for entity in world.iter_entities() {
+        let Some(node) = entity.get::<NodeVisual>() else {
+            continue;
+        };
+        if node.graph_id != graph_id {
+            continue;
+        }
+        let position = entity.get::<Transform>()
+            .map_or(Vec3::ZERO, |transform| transform.translation);
+        nodes.push(NodeSnapshot {
+            node_id: node.node_id,
+            position: position.to_array(),
+            metadata: entity.get::<NodeMetadata>().cloned().unwrap_or_default(),
+        });
+        node_ids.insert(entity.id(), node.node_id);
+    }
+
+    let edges = world.iter_entities()
+        .filter_map(|entity| {
+            let edge = entity.get::<EdgeVisual>()?;
+            if edge.graph_id != graph_id {
+                return None;
+            }
+            Some(EdgeSnapshot {
+                edge_id: edge.edge_id,
+                source_node_id: *node_ids.get(&edge.source_entity)?,
+                target_node_id: *node_ids.get(&edge.target_entity)?,
+                relationship: entity.get::<EdgeLabel>()
+                    .map_or(EdgeRelationship::Custom(String::new()), |label| {
+                        EdgeRelationship::from(label.text.as_str())
+                    }),
+                weight: edge.weight,
+            })
+        })
+        .collect();
+
+    GraphSnapshot { graph_id, nodes, edges }
+}
+
+/// Recreate a snapshot's nodes and edges in its graph by sending creation
+/// commands
+pub fn spawn_from_snapshot(commands: &mut Commands, snapshot: &GraphSnapshot) {
+    for node in &snapshot.nodes {
+        commands.send_event(CreateNodeVisual {
+            node_id: node.node_id,
+            graph_id: snapshot.graph_id,
+            position: Vec3::from_array(node.position),
+            label: node.metadata.label.clone(),
+            metadata: serde_json::to_value(&node.metadata).unwrap_or_default(),
+        });
+    }
+
+    for edge in &snapshot.edges {
+        commands.send_event(CreateEdgeVisual {
+            edge_id: edge.edge_id,
+            graph_id: snapshot.graph_id,
+            source_node_id: edge.source_node_id,
+            target_node_id: edge.target_node_id,
+            relationship: edge.relationship.clone(),
+            weight: Some(edge.weight),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{EdgeVisualBundle, NodeVisualBundle};
+    use crate::events::{NodeMetadataChanged, VisualEdgeCreated, VisualNodeCreated};
+    use crate::morphisms::{create_edge_visual, create_node_visual, NodeEntityMap};
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<NodeEntityMap>()
+            .add_event::<CreateNodeVisual>()
+            .add_event::<CreateEdgeVisual>()
+            .add_event::<VisualNodeCreated>()
+            .add_event::<VisualEdgeCreated>()
+            .add_event::<NodeMetadataChanged>()
+            .add_systems(Update, (create_node_visual, create_edge_visual).chain());
+
+        let graph_id = GraphId::new();
+        let positions = [Vec3::ZERO, Vec3::new(3.0, 0.0, 1.0), Vec3::new(-2.0, 4.0, 0.0)];
+        let nodes: Vec<Entity> = positions.iter()
+            .enumerate()
+            .map(|(index, position)| {
+                let mut metadata = NodeMetadata {
+                    label: format!("Node {index}"),
+                    description: "Restored from a snapshot".to_string(),
+                    tags: vec!["service".to_string()],
+                    ..default()
+                };
+                metadata.attributes.insert("status".to_string(), serde_json::json!("active"));
+                app.world_mut()
+                    .spawn((NodeVisualBundle::new(NodeId::new(), graph_id, *position), metadata))
+                    .id()
+            })
+            .collect();
+        app.world_mut().spawn((
+            EdgeVisualBundle::new(EdgeId::new(), graph_id, nodes[0], nodes[1]),
+            EdgeLabel { text: "DependsOn".to_string() },
+        ));
+        // A node from another graph stays out of the snapshot
+        app.world_mut().spawn(NodeVisualBundle::new(NodeId::new(), GraphId::new(), Vec3::ONE));
+
+        let snapshot = serialize_graph(app.world(), graph_id);
+        assert_eq!(snapshot.nodes.len(), 3);
+        assert_eq!(snapshot.edges.len(), 1);
+        assert_eq!(snapshot.edges[0].relationship, EdgeRelationship::DependsOn);
+
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let restored: GraphSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, snapshot);
+
+        // Clear the world and restore from the snapshot
+        app.world_mut().clear_entities();
+        let mut commands = app.world_mut().commands();
+        spawn_from_snapshot(&mut commands, &restored);
+        app.world_mut().flush();
+        app.update();
+
+        let mut query = app.world_mut().query::<(&NodeVisual, &Transform, &NodeMetadata)>();
+        let restored_nodes: HashMap<NodeId, (Vec3, NodeMetadata)> = query.iter(app.world())
+            .map(|(node, transform, metadata)| (node.node_id, (transform.translation, metadata.clone())))
+            .collect();
+        assert_eq!(restored_nodes.len(), snapshot.nodes.len());
+        for node in &snapshot.nodes {
+            let (position, metadata) = &restored_nodes[&node.node_id];
+            assert_eq!(*position, Vec3::from_array(node.position));
+            assert_eq!(*metadata, node.metadata);
+            assert_eq!(metadata.attributes["status"], "active");
+        }
+
+        let mut edges = app.world_mut().query::<&EdgeVisual>();
+        assert_eq!(edges.iter(app.world()).count(), 1);
+
+        // Restored entities belong to the original graph again
+        let resaved = serialize_graph(app.world(), graph_id);
+        assert_eq!(resaved.nodes.len(), snapshot.nodes.len());
+        assert_eq!(resaved.edges, snapshot.edges);
+    }
+}
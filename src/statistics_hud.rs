@@ -0,0 +1,251 @@
+//! Statistics HUD: Live graph figures in a corner of the window
+//!
+//! [`StatisticsHudPlugin`] shows the node, edge and selection counts of the
+//! [`GraphViewProjection`] together with its degree and connectivity
+//! figures in a corner of the window. They are recomputed whenever the
+//! projection changes. The toggle key shows and hides the HUD; while hidden,
+//! the statistics aren't computed.
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiPlugin, EguiPrimaryContextPass};
+use crate::minimap::MinimapCorner;
+use crate::plugin::CimSet;
+use crate::projections::{GraphViewProjection, ProjectionPlugin};
+use crate::queries::{graph_structure, GraphStatistics};
+
+/// Visibility and placement of the statistics HUD
+#[derive(Resource, Debug, Clone)]
+pub struct StatisticsHudConfig {
+    /// Key that shows and hides the HUD
+    pub toggle_key: KeyCode,
+    pub visible: bool,
+    /// Window corner the HUD is anchored to
+    pub corner: MinimapCorner,
+    /// Distance from the window edges, in logical pixels
+    pub margin: f32,
+}
+
+impl Default for StatisticsHudConfig {
+    fn default() -> Self {
+        Self {
+            toggle_key: KeyCode::F3,
+            visible: true,
+            corner: MinimapCorner::TopRight,
+            margin: 10.0,
+        }
+    }
+}
+
+/// Statistics shown by the HUD, as of the last frame it was visible
+#[derive(Resource, Debug, Clone, Default)]
+pub struct StatisticsHud {
+    pub statistics: GraphStatistics,
+}
+
+/// Plugin that shows live graph statistics, toggled by a key
+pub struct StatisticsHudPlugin;
+
+impl Plugin for StatisticsHudPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugins(EguiPlugin::default());
+        }
+        if !app.is_plugin_added::<ProjectionPlugin>() {
+            app.add_plugins(ProjectionPlugin);
+        }
+
+        app.init_resource::<StatisticsHudConfig>()
+            .init_resource::<StatisticsHud>()
+            .add_systems(Update, (
+                toggle_statistics_hud,
+                update_statistics_hud.run_if(|config: Res<StatisticsHudConfig>| config.visible),
+            ).chain().after(CimSet::Projection))
+            .add_systems(EguiPrimaryContextPass, draw_statistics_hud);
+    }
+}
+
+/// Shorten a count for display: `999`, `1.2k`, `35k`, `4.5M`
+pub fn format_count(count: usize) -> String {
+    const UNITS: [(f64, &str); 3] = [(1e9, "B"), (1e6, "M"), (1e3, "k")];
+
+    let value = count as f64;
+    for (index, (scale, suffix)) in UNITS.iter().enumerate() {
+        if value < *scale {
+            continue;
+        }
+        let scaled = value / scale;
+        // Rounding up to the next unit, e.g. 999,999 as "1M" not "1000k"
+        if index > 0 && scaled >= 999.95 {
+            let (scale, suffix) = UNITS[index - 1];
+            return format!("{}{suffix}", trim_decimal(value / scale));
+        }
+        return format!("{}{suffix}", trim_decimal(scaled));
+    }
+    count.to_string()
+}
+
+/// One decimal below ten and none above, without a trailing `.0`
+fn trim_decimal(value: f64) -> String {
+    if value < 9.95 {
+        let text = format!("{value:.1}");
+        text.strip_suffix(".0").map(str::to_string).unwrap_or(text)
+    } else {
+        format!("{value:.0}")
+    }
+}
+
+/// System that shows or hides the HUD when the toggle key is pressed
+pub fn toggle_statistics_hud(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut config: ResMut<StatisticsHudConfig>,
+) {
+    if keyboard.just_pressed(config.toggle_key) {
+        config.visible = !config.visible;
+    }
+}
+
+/// Statistics over the nodes and edges of `projection`
+pub fn projection_statistics(projection: &GraphViewProjection) -> GraphStatistics {
+    let nodes: Vec<_> = projection.nodes.keys().copied().collect();
+    let edges: Vec<_> = projection.edges.values()
+        .map(|edge| (edge.source_node_id, edge.target_node_id))
+        .collect();
+    let structure = graph_structure(&nodes, &edges);
+
+    GraphStatistics {
+        node_count: nodes.len(),
+        edge_count: edges.len(),
+        selected_count: projection.selected_nodes.len(),
+        max_degree: structure.max_degree,
+        isolated_node_count: structure.isolated_node_count,
+        component_count: structure.component_count,
+    }
+}
+
+/// System that recomputes the [`StatisticsHud`] when the projection
+/// changed, or the HUD was just shown again
+pub fn update_statistics_hud(
+    projection: Res<GraphViewProjection>,
+    config: Res<StatisticsHudConfig>,
+    mut hud: ResMut<StatisticsHud>,
+) {
+    if !projection.is_changed() && !config.is_changed() {
+        return;
+    }
+    let statistics = projection_statistics(&projection);
+    if hud.statistics != statistics {
+        hud.statistics = statistics;
+    }
+}
+
+/// System that draws the HUD while it is visible
+pub fn draw_statistics_hud(
+    mut contexts: EguiContexts,
+    config: Res<StatisticsHudConfig>,
+    hud: Res<StatisticsHud>,
+) {
+    if !config.visible {
+        return;
+    }
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+
+    let margin = config.margin;
+    let (align, offset) = match config.corner {
+        MinimapCorner::TopLeft => (egui::Align2::LEFT_TOP, egui::vec2(margin, margin)),
+        MinimapCorner::TopRight => (egui::Align2::RIGHT_TOP, egui::vec2(-margin, margin)),
+        MinimapCorner::BottomLeft => (egui::Align2::LEFT_BOTTOM, egui::vec2(margin, -margin)),
+        MinimapCorner::BottomRight => (egui::Align2::RIGHT_BOTTOM, egui::vec2(-margin, -margin)),
+    };
+    let statistics = &hud.statistics;
+    let rows = [
+        ("Nodes", statistics.node_count),
+        ("Edges", statistics.edge_count),
+        ("Selected", statistics.selected_count),
+        ("Max degree", statistics.max_degree),
+        ("Components", statistics.component_count),
+        ("Isolated", statistics.isolated_node_count),
+    ];
+    egui::Area::new(egui::Id::new("cim_statistics_hud"))
+        .anchor(align, offset)
+        .interactable(false)
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                egui::Grid::new("cim_statistics_hud_grid").num_columns(2).show(ui, |ui| {
+                    for (label, count) in rows {
+                        ui.label(label);
+                        ui.monospace(format_count(count));
+                        ui.end_row();
+                    }
+                });
+            });
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cim_contextgraph::{EdgeId, NodeId};
+    use crate::events::{NodeSelected, VisualEdgeCreated, VisualNodeCreated};
+
+    #[test]
+    fn test_large_counts_are_shortened() {
+        assert_eq!(format_count(0), "0");
+        assert_eq!(format_count(999), "999");
+        assert_eq!(format_count(1_000), "1k");
+        assert_eq!(format_count(1_234), "1.2k");
+        assert_eq!(format_count(35_400), "35k");
+        assert_eq!(format_count(999_999), "1M");
+        assert_eq!(format_count(4_500_000), "4.5M");
+        assert_eq!(format_count(2_000_000_000), "2B");
+    }
+
+    #[test]
+    fn test_hud_tracks_the_projection_until_hidden() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<ButtonInput<KeyCode>>()
+            .add_plugins(StatisticsHudPlugin);
+
+        let create_node = |app: &mut App| {
+            let (entity, node_id) = (app.world_mut().spawn_empty().id(), NodeId::new());
+            app.world_mut().send_event(VisualNodeCreated { entity, node_id, position: Vec3::ZERO });
+            (entity, node_id)
+        };
+        let nodes: Vec<(Entity, NodeId)> = (0..3).map(|_| create_node(&mut app)).collect();
+        app.world_mut().send_event(NodeSelected { entity: nodes[0].0, node_id: nodes[0].1 });
+        let entity = app.world_mut().spawn_empty().id();
+        app.world_mut().send_event(VisualEdgeCreated {
+            entity,
+            edge_id: EdgeId::new(),
+            source_entity: nodes[0].0,
+            target_entity: nodes[1].0,
+            source_node_id: nodes[0].1,
+            target_node_id: nodes[1].1,
+        });
+        app.update();
+        assert_eq!(app.world().resource::<StatisticsHud>().statistics, GraphStatistics {
+            node_count: 3,
+            edge_count: 1,
+            selected_count: 1,
+            max_degree: 1,
+            isolated_node_count: 1,
+            component_count: 2,
+        });
+
+        // Hidden, the statistics are left as they were
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::F3);
+        app.update();
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().clear();
+        create_node(&mut app);
+        app.update();
+        assert!(!app.world().resource::<StatisticsHudConfig>().visible);
+        assert_eq!(app.world().resource::<StatisticsHud>().statistics.node_count, 3);
+
+        // Shown again, they catch up
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::F3);
+        app.update();
+        assert_eq!(app.world().resource::<StatisticsHud>().statistics.node_count, 4);
+    }
+}
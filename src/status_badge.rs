@@ -0,0 +1,293 @@
+//! Status Badges: Icons for the execution status of workflow nodes
+//!
+//! A node with a [`StatusBadge`] gets a small icon at its upper right: a
+//! spinning arc while active, a checkmark once completed and a cross when
+//! failed. The shape tells the states apart without relying on color, next
+//! to whatever the heatmap or the workflow colors the node with. Icons are
+//! billboards, facing the graph camera from wherever it looks.
+//!
+//! The badge follows the domain: a `status` attribute in a node's
+//! [`NodeMetadata`] sets or clears it as described by
+//! [`NodeStatus::from_domain`]. The textures come from [`StatusIcons`],
+//! which draws a default set and can be given other images per status.
+
+use bevy::pbr::{NotShadowCaster, NotShadowReceiver};
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use std::collections::HashMap;
+use crate::components::{GraphCamera, NodeStatus, NodeVisual, StatusBadge};
+use crate::value_objects::NodeMetadata;
+
+/// Width and height of the default icon textures, in pixels
+const ICON_RESOLUTION: u32 = 32;
+/// Side of the badge quad, in node-local units
+const BADGE_SIZE: f32 = 0.45;
+/// Where the badge sits relative to the node center, as seen by the camera
+const BADGE_OFFSET: Vec3 = Vec3::new(0.6, 0.6, 0.1);
+/// Turns per second of the active spinner
+const SPINNER_SPEED: f32 = 0.75;
+
+/// Icon textures per status, with the materials drawn from them
+#[derive(Resource, Debug)]
+pub struct StatusIcons {
+    pub icons: HashMap<NodeStatus, Handle<Image>>,
+    quad: Handle<Mesh>,
+    /// Materials with the icon image they were made from
+    materials: HashMap<NodeStatus, (AssetId<Image>, Handle<StandardMaterial>)>,
+}
+
+impl FromWorld for StatusIcons {
+    fn from_world(world: &mut World) -> Self {
+        let mut images = world.resource_mut::<Assets<Image>>();
+        let icons = NodeStatus::ALL.iter()
+            .map(|status| (*status, images.add(draw_icon(*status))))
+            .collect();
+        let quad = world.resource_mut::<Assets<Mesh>>().add(Rectangle::new(BADGE_SIZE, BADGE_SIZE));
+        Self {
+            icons,
+            quad,
+            materials: HashMap::new(),
+        }
+    }
+}
+
+impl StatusIcons {
+    /// Material showing the icon of `status`, tinted in the status color
+    fn material(&mut self, status: NodeStatus, materials: &mut Assets<StandardMaterial>) -> Option<Handle<StandardMaterial>> {
+        let icon = self.icons.get(&status)?.clone();
+        if let Some((image, material)) = self.materials.get(&status) {
+            if *image == icon.id() {
+                return Some(material.clone());
+            }
+        }
+        let material = materials.add(StandardMaterial {
+            base_color: status.color(),
+            base_color_texture: Some(icon.clone()),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            double_sided: true,
+            cull_mode: None,
+            ..default()
+        });
+        self.materials.insert(status, (icon.id(), material.clone()));
+        Some(material)
+    }
+}
+
+/// Whether the point at `p`, in -1..1 on both axes with y up, is inked in
+/// the default icon of `status`
+fn icon_covers(status: NodeStatus, p: Vec2) -> bool {
+    const STROKE: f32 = 0.16;
+    let segment_distance = |a: Vec2, b: Vec2| {
+        let t = ((p - a).dot(b - a) / (b - a).length_squared()).clamp(0.0, 1.0);
+        p.distance(a + (b - a) * t)
+    };
+    match status {
+        NodeStatus::Completed => {
+            let (a, b, c) = (Vec2::new(-0.6, 0.0), Vec2::new(-0.2, -0.45), Vec2::new(0.65, 0.5));
+            segment_distance(a, b).min(segment_distance(b, c)) < STROKE
+        }
+        NodeStatus::Failed => {
+            segment_distance(Vec2::splat(-0.55), Vec2::splat(0.55))
+                .min(segment_distance(Vec2::new(-0.55, 0.55), Vec2::new(0.55, -0.55))) < STROKE
+        }
+        NodeStatus::Active => {
+            // A ring with a quarter cut out, which shows the spin
+            let in_ring = (p.length() - 0.6).abs() < STROKE;
+            in_ring && !(p.x > 0.0 && p.y > 0.0)
+        }
+    }
+}
+
+/// White-on-transparent default icon of `status`
+pub fn draw_icon(status: NodeStatus) -> Image {
+    let size = ICON_RESOLUTION;
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for row in 0..size {
+        for column in 0..size {
+            let p = Vec2::new(column as f32 + 0.5, row as f32 + 0.5) / size as f32 * 2.0 - Vec2::ONE;
+            let alpha = if icon_covers(status, Vec2::new(p.x, -p.y)) { 255 } else { 0 };
+            data.extend_from_slice(&[255, 255, 255, alpha]);
+        }
+    }
+    Image::new(
+        Extent3d { width: size, height: size, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    )
+}
+
+/// The icon shown for a node's badge
+#[derive(Component, Debug, Clone, Copy)]
+pub struct ShownStatusBadge {
+    pub icon: Entity,
+    pub state: NodeStatus,
+}
+
+/// Marks a badge icon
+#[derive(Component, Debug, Clone, Copy)]
+pub struct StatusIcon {
+    pub state: NodeStatus,
+}
+
+/// Plugin that shows status badges on nodes
+pub struct StatusBadgePlugin;
+
+impl Plugin for StatusBadgePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<StatusIcons>()
+            .add_systems(Update, badges_from_metadata)
+            .add_systems(PostUpdate, (update_status_badges, face_status_badges).chain());
+    }
+}
+
+/// Nodes whose metadata changed since the system last ran
+type ChangedMetadata = (With<NodeVisual>, Changed<NodeMetadata>);
+
+/// System that sets or clears badges from the `status` metadata attribute
+/// of nodes whose metadata changed. Nodes without the attribute keep their
+/// badge, so badges can be given by hand too.
+pub fn badges_from_metadata(
+    mut commands: Commands,
+    nodes: Query<(Entity, &NodeMetadata), ChangedMetadata>,
+) {
+    for (entity, metadata) in nodes.iter() {
+        let Some(status) = metadata.attributes.get("status").and_then(serde_json::Value::as_str) else {
+            continue;
+        };
+        match NodeStatus::from_domain(status) {
+            Some(state) => { commands.entity(entity).insert(StatusBadge { state }); }
+            None => { commands.entity(entity).remove::<StatusBadge>(); }
+        }
+    }
+}
+
+/// System that adds, retextures and removes badge icons as node badges
+/// change
+#[allow(clippy::type_complexity)]
+pub fn update_status_badges(
+    mut commands: Commands,
+    mut icons: ResMut<StatusIcons>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    nodes: Query<(Entity, Option<&StatusBadge>, Option<&ShownStatusBadge>), With<NodeVisual>>,
+    mut shown_icons: Query<(&mut MeshMaterial3d<StandardMaterial>, &mut StatusIcon)>,
+) {
+    for (entity, badge, shown) in nodes.iter() {
+        let Some(badge) = badge else {
+            if let Some(shown) = shown {
+                commands.entity(shown.icon).despawn();
+                commands.entity(entity).remove::<ShownStatusBadge>();
+            }
+            continue;
+        };
+        if shown.is_some_and(|shown| shown.state == badge.state) {
+            continue;
+        }
+        let Some(material) = icons.material(badge.state, &mut materials) else {
+            continue;
+        };
+
+        let icon = match shown.and_then(|shown| shown_icons.get_mut(shown.icon).ok().map(|icon| (shown.icon, icon))) {
+            Some((icon, (mut icon_material, mut marker))) => {
+                icon_material.0 = material;
+                marker.state = badge.state;
+                icon
+            }
+            None => commands.spawn((
+                StatusIcon { state: badge.state },
+                Mesh3d(icons.quad.clone()),
+                MeshMaterial3d(material),
+                Transform::from_translation(BADGE_OFFSET),
+                NotShadowCaster,
+                NotShadowReceiver,
+                ChildOf(entity),
+            )).id(),
+        };
+        commands.entity(entity).insert(ShownStatusBadge { icon, state: badge.state });
+    }
+}
+
+/// System that turns badge icons towards the graph camera, keeps them at
+/// the node's upper right as seen from it, and spins active ones
+pub fn face_status_badges(
+    time: Res<Time>,
+    cameras: Query<&GlobalTransform, With<GraphCamera>>,
+    parents: Query<&GlobalTransform, Without<StatusIcon>>,
+    mut icons: Query<(&StatusIcon, &ChildOf, &mut Transform)>,
+) {
+    let camera_rotation = cameras.iter().next()
+        .map_or(Quat::IDENTITY, |camera| camera.compute_transform().rotation);
+    let spin = Quat::from_rotation_z(-time.elapsed_secs() * SPINNER_SPEED * std::f32::consts::TAU);
+
+    for (icon, child_of, mut transform) in icons.iter_mut() {
+        let parent_rotation = parents.get(child_of.parent())
+            .map_or(Quat::IDENTITY, |parent| parent.compute_transform().rotation);
+        let facing = parent_rotation.inverse() * camera_rotation;
+        transform.translation = facing * BADGE_OFFSET;
+        transform.rotation = match icon.state {
+            NodeStatus::Active => facing * spin,
+            _ => facing,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cim_contextgraph::{NodeId, ContextGraphId as GraphId};
+
+    #[test]
+    fn test_badge_follows_domain_status() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Mesh>()
+            .init_asset::<StandardMaterial>()
+            .init_asset::<Image>()
+            .add_plugins(StatusBadgePlugin);
+
+        let node = app.world_mut().spawn((
+            NodeVisual { node_id: NodeId::new(), graph_id: GraphId::new() },
+            NodeMetadata::from_json(&serde_json::json!({ "label": "Review", "status": "Completed" })),
+        )).id();
+        app.update();
+        let shown = *app.world().get::<ShownStatusBadge>(node).unwrap();
+        assert_eq!(shown.state, NodeStatus::Completed);
+        assert_eq!(app.world().get::<ChildOf>(shown.icon).unwrap().parent(), node);
+
+        let icon_texture = |app: &App| {
+            let material = app.world().get::<MeshMaterial3d<StandardMaterial>>(shown.icon).unwrap();
+            app.world().resource::<Assets<StandardMaterial>>().get(&material.0).unwrap().base_color_texture.clone()
+        };
+        assert_eq!(icon_texture(&app), app.world().resource::<StatusIcons>().icons.get(&NodeStatus::Completed).cloned());
+
+        // The same icon switches to the cross
+        app.world_mut().get_mut::<NodeMetadata>(node).unwrap()
+            .attributes.insert("status".to_string(), serde_json::json!("failed"));
+        app.update();
+        assert_eq!(app.world().get::<ShownStatusBadge>(node).unwrap().icon, shown.icon);
+        assert_eq!(icon_texture(&app), app.world().resource::<StatusIcons>().icons.get(&NodeStatus::Failed).cloned());
+
+        // Statuses without a badge clear it
+        app.world_mut().get_mut::<NodeMetadata>(node).unwrap()
+            .attributes.insert("status".to_string(), serde_json::json!("pending"));
+        app.update();
+        assert!(app.world().get::<StatusBadge>(node).is_none());
+        assert!(app.world().get::<ShownStatusBadge>(node).is_none());
+        assert!(app.world().get_entity(shown.icon).is_err());
+    }
+
+    #[test]
+    fn test_default_icons_differ_in_shape() {
+        let inked = |status: NodeStatus| -> Vec<bool> {
+            draw_icon(status).data.unwrap().chunks(4).map(|pixel| pixel[3] > 0).collect()
+        };
+        let (active, completed, failed) = (inked(NodeStatus::Active), inked(NodeStatus::Completed), inked(NodeStatus::Failed));
+        assert!(active.contains(&true) && completed.contains(&true) && failed.contains(&true));
+        assert_ne!(active, completed);
+        assert_ne!(completed, failed);
+        assert_ne!(active, failed);
+    }
+}
@@ -0,0 +1,251 @@
+//! Timeline: Scrubbing through the event stream
+//!
+//! [`TimelineState`] holds a time cursor. Events after the cursor, or older
+//! than the trailing window before it, are marked [`OutsideTimeline`] and
+//! hidden. While playing, the cursor advances by `speed` seconds per second,
+//! so the default state, starting now at speed 1, follows the live stream.
+//!
+//! [`TimelinePlugin`] adds a panel to play, pause, step from event to event
+//! and drag the cursor across every event in the [`EventStore`], whether it
+//! arrived live or was loaded from a recorded session.
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiPlugin, EguiPrimaryContextPass};
+use chrono::{DateTime, Duration, Utc};
+use crate::culling::{CullingPlugin, OutsideTimeline};
+use crate::nats_event_visualization::{EventStore, EventVisual};
+
+/// Position and playback of the timeline
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct TimelineState {
+    /// Latest moment shown
+    pub cursor: DateTime<Utc>,
+    /// How far back from the cursor events are shown
+    pub window: Duration,
+    pub playing: bool,
+    /// Timeline seconds per real second while playing
+    pub speed: f32,
+}
+
+impl Default for TimelineState {
+    fn default() -> Self {
+        Self {
+            cursor: Utc::now(),
+            window: Duration::minutes(5),
+            playing: true,
+            speed: 1.0,
+        }
+    }
+}
+
+impl TimelineState {
+    /// Whether an event at `timestamp` is inside the window ending at the
+    /// cursor
+    pub fn contains(&self, timestamp: DateTime<Utc>) -> bool {
+        timestamp <= self.cursor && timestamp >= self.cursor - self.window
+    }
+
+    /// Move the cursor forward by `delta_secs` real seconds, if playing
+    pub fn advance(&mut self, delta_secs: f32) {
+        if self.playing {
+            let micros = (delta_secs as f64 * self.speed as f64 * 1_000_000.0) as i64;
+            self.cursor += Duration::microseconds(micros);
+        }
+    }
+
+    /// Move the cursor to the first of `timestamps` after it; returns
+    /// whether there was one
+    pub fn step_forward(&mut self, timestamps: impl IntoIterator<Item = DateTime<Utc>>) -> bool {
+        let next = timestamps.into_iter().filter(|timestamp| *timestamp > self.cursor).min();
+        next.map(|next| self.cursor = next).is_some()
+    }
+
+    /// Move the cursor to the last of `timestamps` before it; returns
+    /// whether there was one
+    pub fn step_back(&mut self, timestamps: impl IntoIterator<Item = DateTime<Utc>>) -> bool {
+        let previous = timestamps.into_iter().filter(|timestamp| *timestamp < self.cursor).max();
+        previous.map(|previous| self.cursor = previous).is_some()
+    }
+}
+
+/// Plugin that adds the timeline panel and hides events outside its window
+pub struct TimelinePlugin;
+
+impl Plugin for TimelinePlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugins(EguiPlugin::default());
+        }
+        // Hidden reasons are resolved into visibility by the culling plugin
+        if !app.is_plugin_added::<CullingPlugin>() {
+            app.add_plugins(CullingPlugin);
+        }
+
+        app.init_resource::<TimelineState>()
+            .add_systems(Update, (advance_timeline.run_if(timeline_playing), apply_timeline).chain())
+            .add_systems(EguiPrimaryContextPass, timeline_ui);
+    }
+}
+
+/// Run condition: the timeline is playing
+pub fn timeline_playing(timeline: Res<TimelineState>) -> bool {
+    timeline.playing
+}
+
+/// System that plays the timeline
+pub fn advance_timeline(time: Res<Time>, mut timeline: ResMut<TimelineState>) {
+    timeline.advance(time.delta_secs());
+}
+
+/// System that marks events outside the timeline window as
+/// [`OutsideTimeline`]
+pub fn apply_timeline(
+    mut commands: Commands,
+    timeline: Res<TimelineState>,
+    events: Query<(Entity, &EventVisual, Has<OutsideTimeline>)>,
+) {
+    for (entity, event, outside) in events.iter() {
+        let inside = timeline.contains(event.timestamp);
+        if inside && outside {
+            commands.entity(entity).remove::<OutsideTimeline>();
+        } else if !inside && !outside {
+            commands.entity(entity).insert(OutsideTimeline);
+        }
+    }
+}
+
+/// System that draws the timeline panel
+pub fn timeline_ui(
+    mut contexts: EguiContexts,
+    mut timeline: ResMut<TimelineState>,
+    event_store: Res<EventStore>,
+) {
+    let Ok(ctx) = contexts.ctx_mut() else {
+        return;
+    };
+    let timestamps: Vec<DateTime<Utc>> = event_store.get_all_events()
+        .iter()
+        .map(|event| event.timestamp)
+        .collect();
+
+    egui::TopBottomPanel::bottom("cim_timeline").show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            if ui.button("Previous").on_hover_text("Previous event").clicked() {
+                timeline.playing = false;
+                timeline.step_back(timestamps.iter().copied());
+            }
+            let play_label = if timeline.playing { "Pause" } else { "Play" };
+            if ui.button(play_label).clicked() {
+                timeline.playing = !timeline.playing;
+            }
+            if ui.button("Next").on_hover_text("Next event").clicked() {
+                timeline.playing = false;
+                timeline.step_forward(timestamps.iter().copied());
+            }
+
+            ui.separator();
+            ui.add(egui::Slider::new(&mut timeline.speed, 0.1..=10.0)
+                .logarithmic(true)
+                .text("speed")
+                .suffix("x"));
+            let mut window_seconds = timeline.window.num_seconds();
+            if ui.add(egui::Slider::new(&mut window_seconds, 1..=3600)
+                .logarithmic(true)
+                .text("window")
+                .suffix(" s"))
+                .changed()
+            {
+                timeline.window = Duration::seconds(window_seconds);
+            }
+            ui.separator();
+            ui.label(timeline.cursor.format("%Y-%m-%d %H:%M:%S%.3f").to_string());
+        });
+
+        // The cursor slider spans every event, and the cursor itself
+        let start = timestamps.iter().copied().min().map_or(timeline.cursor, |first| first.min(timeline.cursor));
+        let end = timestamps.iter().copied().max().map_or(timeline.cursor, |last| last.max(timeline.cursor));
+        let span = (end - start).num_milliseconds().max(1) as f64 / 1000.0;
+        let mut offset = (timeline.cursor - start).num_milliseconds() as f64 / 1000.0;
+        ui.spacing_mut().slider_width = ui.available_width();
+        if ui.add(egui::Slider::new(&mut offset, 0.0..=span).show_value(false)).changed() {
+            timeline.playing = false;
+            timeline.cursor = start + Duration::milliseconds((offset * 1000.0) as i64);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nats_event_visualization::DomainEventReceived;
+
+    fn at(seconds: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000 + seconds, 0).unwrap()
+    }
+
+    #[test]
+    fn test_timeline_playback_and_stepping() {
+        let mut timeline = TimelineState {
+            cursor: at(0),
+            window: Duration::seconds(10),
+            playing: false,
+            speed: 4.0,
+        };
+        timeline.advance(1.0);
+        assert_eq!(timeline.cursor, at(0));
+        timeline.playing = true;
+        timeline.advance(0.5);
+        assert_eq!(timeline.cursor, at(2));
+
+        let events = [at(-5), at(1), at(7)];
+        assert!(timeline.step_forward(events));
+        assert_eq!(timeline.cursor, at(7));
+        assert!(!timeline.step_forward(events));
+        assert!(timeline.step_back(events));
+        assert_eq!(timeline.cursor, at(1));
+        assert!(timeline.contains(at(-5)) && !timeline.contains(at(7)) && !timeline.contains(at(-10)));
+    }
+
+    #[test]
+    fn test_events_outside_the_window_are_hidden() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TimelineState {
+                cursor: at(100),
+                window: Duration::seconds(30),
+                playing: false,
+                speed: 1.0,
+            })
+            .add_systems(Update, (apply_timeline, crate::culling::apply_hidden_reasons).chain());
+
+        let mut spawn_event = |event_id: &str, timestamp: DateTime<Utc>| {
+            let event = DomainEventReceived {
+                event_id: event_id.to_string(),
+                timestamp,
+                domain: "workflow".to_string(),
+                event_type: "StepCompleted".to_string(),
+                aggregate_id: "wf-1".to_string(),
+                aggregate_type: "Workflow".to_string(),
+                correlation_id: None,
+                causation_id: None,
+                payload: serde_json::json!({}),
+            };
+            app.world_mut().spawn((EventVisual::from_event(&event), Visibility::default())).id()
+        };
+        let too_old = spawn_event("old", at(50));
+        let shown = spawn_event("shown", at(90));
+        let future = spawn_event("future", at(120));
+        app.update();
+
+        let visibility = |app: &App, entity| *app.world().get::<Visibility>(entity).unwrap();
+        assert_eq!(visibility(&app, too_old), Visibility::Hidden);
+        assert_eq!(visibility(&app, shown), Visibility::Inherited);
+        assert_eq!(visibility(&app, future), Visibility::Hidden);
+
+        // Scrubbing forward brings the later event into view
+        app.world_mut().resource_mut::<TimelineState>().cursor = at(125);
+        app.update();
+        assert_eq!(visibility(&app, future), Visibility::Inherited);
+        assert_eq!(visibility(&app, shown), Visibility::Hidden);
+    }
+}
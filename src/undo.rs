@@ -0,0 +1,636 @@
+//! Undo/Redo: Reversible history of graph edits
+//!
+//! [`UndoRedoPlugin`] records node creation, deletion and moves and edge
+//! creation and deletion from the domain events those edits send. Undoing an
+//! operation replays its inverse through the usual command events —
+//! `RemoveNodeVisual` for a created node, `CreateNodeVisual` for a deleted
+//! one — so the rest of the pipeline sees an ordinary edit. Moves have no
+//! command event and are applied to the node's [`Transform`] directly.
+//!
+//! A deleted node is recorded with the edges it was connected to, and
+//! undoing the deletion brings those edges back with it.
+//!
+//! Moves are recorded from `NodeMoved`, `NodeDragEnd` and
+//! `NodePositionChanged`. The events one move sends in a frame, such as a
+//! drop and the snap that follows it, are recorded as a single operation.
+//!
+//! The events sent while replaying are not recorded again, and the history
+//! keeps at most [`UndoConfig::max_depth`] operations.
+
+use bevy::prelude::*;
+use cim_contextgraph::{NodeId, EdgeId, ContextGraphId as GraphId};
+use std::collections::{HashMap, HashSet, VecDeque};
+use crate::components::{EdgeVisual, NodeVisual};
+use crate::events::{
+    CreateEdgeVisual, CreateNodeVisual, EdgeRelationship, NodeDragEnd, NodeMoved, NodePositionChanged,
+    RemoveEdgeVisual, RemoveNodeVisual, VisualEdgeCreated, VisualEdgeDeleted,
+    VisualNodeCreated, VisualNodeDeleted,
+};
+use crate::plugin::CimSet;
+use crate::value_objects::NodeMetadata;
+
+/// Command: Undo the most recent operation
+#[derive(Event, Debug, Clone)]
+pub struct Undo;
+
+/// Command: Redo the most recently undone operation
+#[derive(Event, Debug, Clone)]
+pub struct Redo;
+
+/// How much history is kept
+#[derive(Resource, Debug, Clone)]
+pub struct UndoConfig {
+    /// Oldest operations are dropped beyond this many
+    pub max_depth: usize,
+}
+
+impl Default for UndoConfig {
+    fn default() -> Self {
+        Self { max_depth: 100 }
+    }
+}
+
+/// Everything needed to create a node again
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeRecord {
+    pub node_id: NodeId,
+    pub graph_id: GraphId,
+    pub position: Vec3,
+    pub metadata: NodeMetadata,
+    /// Edges connected to the node when it was deleted, restored with it
+    pub edges: Vec<EdgeRecord>,
+}
+
+/// Everything needed to create an edge again
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgeRecord {
+    pub edge_id: EdgeId,
+    pub graph_id: GraphId,
+    pub source_node_id: NodeId,
+    pub target_node_id: NodeId,
+    pub relationship: EdgeRelationship,
+    pub weight: f32,
+}
+
+/// A reversible graph edit
+#[derive(Debug, Clone, PartialEq)]
+pub enum GraphOperation {
+    CreateNode(NodeRecord),
+    DeleteNode(NodeRecord),
+    MoveNode { node_id: NodeId, from: Vec3, to: Vec3 },
+    CreateEdge(EdgeRecord),
+    DeleteEdge(EdgeRecord),
+}
+
+impl GraphOperation {
+    /// The operation that reverts this one
+    pub fn inverse(&self) -> Self {
+        match self {
+            GraphOperation::CreateNode(node) => GraphOperation::DeleteNode(node.clone()),
+            GraphOperation::DeleteNode(node) => GraphOperation::CreateNode(node.clone()),
+            GraphOperation::MoveNode { node_id, from, to } => GraphOperation::MoveNode {
+                node_id: *node_id,
+                from: *to,
+                to: *from,
+            },
+            GraphOperation::CreateEdge(edge) => GraphOperation::DeleteEdge(edge.clone()),
+            GraphOperation::DeleteEdge(edge) => GraphOperation::CreateEdge(edge.clone()),
+        }
+    }
+
+    /// Keys of the domain events this operation sends when replayed
+    fn echoes(&self) -> Vec<Echo> {
+        match self {
+            GraphOperation::CreateNode(node) => std::iter::once(Echo::NodeCreated(node.node_id))
+                .chain(node.edges.iter().map(|edge| Echo::EdgeCreated(edge.edge_id)))
+                .collect(),
+            GraphOperation::DeleteNode(node) => std::iter::once(Echo::NodeDeleted(node.node_id))
+                .chain(node.edges.iter().map(|edge| Echo::EdgeDeleted(edge.edge_id)))
+                .collect(),
+            GraphOperation::MoveNode { node_id, .. } => vec![Echo::NodeMoved(*node_id)],
+            GraphOperation::CreateEdge(edge) => vec![Echo::EdgeCreated(edge.edge_id)],
+            GraphOperation::DeleteEdge(edge) => vec![Echo::EdgeDeleted(edge.edge_id)],
+        }
+    }
+}
+
+/// Domain event expected back from a replayed operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Echo {
+    NodeCreated(NodeId),
+    NodeDeleted(NodeId),
+    NodeMoved(NodeId),
+    EdgeCreated(EdgeId),
+    EdgeDeleted(EdgeId),
+}
+
+/// Recorded operations and the nodes and edges they refer to
+#[derive(Resource, Debug, Default)]
+pub struct UndoHistory {
+    undo: VecDeque<GraphOperation>,
+    redo: Vec<GraphOperation>,
+    /// Live nodes and edges, so deletions can be recorded with everything
+    /// needed to restore them
+    nodes: HashMap<NodeId, NodeRecord>,
+    edges: HashMap<EdgeId, EdgeRecord>,
+    /// Events the current frame's replay will send, not to be recorded
+    echoes: HashSet<Echo>,
+}
+
+impl UndoHistory {
+    /// Record a new operation, dropping the oldest beyond `max_depth`.
+    /// Anything that could be redone is discarded.
+    pub fn record(&mut self, operation: GraphOperation, max_depth: usize) {
+        self.redo.clear();
+        self.undo.push_back(operation);
+        while self.undo.len() > max_depth {
+            self.undo.pop_front();
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Number of operations that can be undone
+    pub fn len(&self) -> usize {
+        self.undo.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.undo.is_empty()
+    }
+}
+
+/// Plugin that records graph edits and undoes/redoes them with
+/// Ctrl+Z / Ctrl+Shift+Z
+pub struct UndoRedoPlugin;
+
+impl Plugin for UndoRedoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<UndoConfig>()
+            .init_resource::<UndoHistory>()
+            .add_event::<Undo>()
+            .add_event::<Redo>()
+            .add_event::<CreateNodeVisual>()
+            .add_event::<RemoveNodeVisual>()
+            .add_event::<CreateEdgeVisual>()
+            .add_event::<RemoveEdgeVisual>()
+            .add_event::<VisualNodeCreated>()
+            .add_event::<VisualNodeDeleted>()
+            .add_event::<NodeMoved>()
+            .add_event::<NodeDragEnd>()
+            .add_event::<NodePositionChanged>()
+            .add_event::<VisualEdgeCreated>()
+            .add_event::<VisualEdgeDeleted>()
+            .add_systems(
+                Update,
+                (
+                    (undo_redo_keys, replay_history).chain().before(CimSet::Commands),
+                    // After the layout, so a drop and its snap are seen together
+                    record_graph_operations
+                        .after(CimSet::Commands)
+                        .after(CimSet::Layout)
+                        .before(CimSet::Projection),
+                ),
+            );
+    }
+}
+
+/// System that sends [`Undo`] on Ctrl+Z and [`Redo`] on Ctrl+Shift+Z
+pub fn undo_redo_keys(
+    keyboard: Option<Res<ButtonInput<KeyCode>>>,
+    mut undo: EventWriter<Undo>,
+    mut redo: EventWriter<Redo>,
+) {
+    let Some(keyboard) = keyboard else {
+        return;
+    };
+    if !keyboard.just_pressed(KeyCode::KeyZ)
+        || !keyboard.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+    {
+        return;
+    }
+
+    if keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        redo.write(Redo);
+    } else {
+        undo.write(Undo);
+    }
+}
+
+/// Command events an operation is replayed through
+#[derive(bevy::ecs::system::SystemParam)]
+pub struct ReplayWriters<'w> {
+    create_node: EventWriter<'w, CreateNodeVisual>,
+    remove_node: EventWriter<'w, RemoveNodeVisual>,
+    create_edge: EventWriter<'w, CreateEdgeVisual>,
+    remove_edge: EventWriter<'w, RemoveEdgeVisual>,
+    position_changed: EventWriter<'w, NodePositionChanged>,
+}
+
+/// System that replays the inverse of undone operations and the redone
+/// operations themselves
+pub fn replay_history(
+    mut commands: Commands,
+    mut history: ResMut<UndoHistory>,
+    mut undo: EventReader<Undo>,
+    mut redo: EventReader<Redo>,
+    mut writers: ReplayWriters,
+    mut nodes: Query<(Entity, &NodeVisual, &mut Transform)>,
+    edges: Query<(Entity, &EdgeVisual)>,
+) {
+    let mut replay = Vec::new();
+    for _ in undo.read() {
+        if let Some(operation) = history.undo.pop_back() {
+            replay.push(operation.inverse());
+            history.redo.push(operation);
+        }
+    }
+    for _ in redo.read() {
+        if let Some(operation) = history.redo.pop() {
+            replay.push(operation.clone());
+            history.undo.push_back(operation);
+        }
+    }
+
+    for operation in replay {
+        history.echoes.extend(operation.echoes());
+        match operation {
+            GraphOperation::CreateNode(node) => {
+                writers.create_node.write(CreateNodeVisual {
+                    node_id: node.node_id,
+                    graph_id: node.graph_id,
+                    position: node.position,
+                    label: node.metadata.label.clone(),
+                    metadata: serde_json::to_value(&node.metadata).unwrap_or_default(),
+                });
+                for edge in &node.edges {
+                    // Edges left dangling by the deletion still point at the
+                    // old node entity and are replaced
+                    for (entity, _) in edges.iter().filter(|(_, visual)| visual.edge_id == edge.edge_id) {
+                        commands.entity(entity).despawn();
+                    }
+                    writers.create_edge.write(create_edge_command(edge));
+                }
+            }
+            GraphOperation::DeleteNode(node) => {
+                for edge in &node.edges {
+                    writers.remove_edge.write(RemoveEdgeVisual { edge_id: edge.edge_id });
+                }
+                writers.remove_node.write(RemoveNodeVisual { node_id: node.node_id });
+            }
+            GraphOperation::MoveNode { node_id, to, .. } => {
+                let Some((entity, _, mut transform)) = nodes.iter_mut()
+                    .find(|(_, visual, _)| visual.node_id == node_id)
+                else {
+                    continue;
+                };
+                let old_position = transform.translation;
+                transform.translation = to;
+                if let Some(node) = history.nodes.get_mut(&node_id) {
+                    node.position = to;
+                }
+                writers.position_changed.write(NodePositionChanged {
+                    entity,
+                    node_id,
+                    old_position,
+                    new_position: to,
+                });
+            }
+            GraphOperation::CreateEdge(edge) => {
+                writers.create_edge.write(create_edge_command(&edge));
+            }
+            GraphOperation::DeleteEdge(edge) => {
+                writers.remove_edge.write(RemoveEdgeVisual { edge_id: edge.edge_id });
+            }
+        }
+    }
+}
+
+/// The command that creates a recorded edge again
+fn create_edge_command(edge: &EdgeRecord) -> CreateEdgeVisual {
+    CreateEdgeVisual {
+        edge_id: edge.edge_id,
+        graph_id: edge.graph_id,
+        source_node_id: edge.source_node_id,
+        target_node_id: edge.target_node_id,
+        relationship: edge.relationship.clone(),
+        weight: Some(edge.weight),
+    }
+}
+
+/// Domain events that are recorded as operations
+#[derive(bevy::ecs::system::SystemParam)]
+pub struct RecordedEvents<'w, 's> {
+    node_created: EventReader<'w, 's, VisualNodeCreated>,
+    node_deleted: EventReader<'w, 's, VisualNodeDeleted>,
+    node_moved: EventReader<'w, 's, NodeMoved>,
+    drag_ended: EventReader<'w, 's, NodeDragEnd>,
+    position_changed: EventReader<'w, 's, NodePositionChanged>,
+    edge_created: EventReader<'w, 's, VisualEdgeCreated>,
+    edge_deleted: EventReader<'w, 's, VisualEdgeDeleted>,
+}
+
+/// System that records domain events as operations, skipping the ones sent
+/// by this frame's replay
+pub fn record_graph_operations(
+    config: Res<UndoConfig>,
+    mut history: ResMut<UndoHistory>,
+    mut events: RecordedEvents,
+    nodes: Query<(&NodeVisual, Option<&NodeMetadata>)>,
+    edges: Query<(&EdgeVisual, Option<&EdgeRelationship>)>,
+) {
+    let history = &mut *history;
+    let mut recorded = Vec::new();
+
+    for event in events.node_created.read() {
+        let Ok((visual, metadata)) = nodes.get(event.entity) else {
+            continue;
+        };
+        let node = NodeRecord {
+            node_id: event.node_id,
+            graph_id: visual.graph_id,
+            position: event.position,
+            metadata: metadata.cloned().unwrap_or_default(),
+            edges: Vec::new(),
+        };
+        history.nodes.insert(event.node_id, node.clone());
+        if !history.echoes.remove(&Echo::NodeCreated(event.node_id)) {
+            recorded.push(GraphOperation::CreateNode(node));
+        }
+    }
+
+    // Moves of one node this frame, from where it started to where it ended
+    let mut moves: Vec<(NodeId, Vec3, Vec3)> = Vec::new();
+    let mut add_move = |node_id: NodeId, from: Vec3, to: Vec3| {
+        match moves.iter_mut().find(|(moved, ..)| *moved == node_id) {
+            Some((_, _, end)) => *end = to,
+            None => moves.push((node_id, from, to)),
+        }
+    };
+    for event in events.node_moved.read() {
+        add_move(event.node_id, event.old_position, event.new_position);
+    }
+    for event in events.drag_ended.read() {
+        // A drag only says where the node ended up; it started where the
+        // node was last recorded
+        if let Some(node) = history.nodes.get(&event.node_id) {
+            add_move(event.node_id, node.position, event.final_position);
+        }
+    }
+    let mut replayed = HashSet::new();
+    for event in events.position_changed.read() {
+        if history.echoes.remove(&Echo::NodeMoved(event.node_id)) {
+            replayed.insert(event.node_id);
+        }
+        add_move(event.node_id, event.old_position, event.new_position);
+    }
+    for (node_id, from, to) in moves {
+        if let Some(node) = history.nodes.get_mut(&node_id) {
+            node.position = to;
+        }
+        if from != to && !replayed.contains(&node_id) {
+            recorded.push(GraphOperation::MoveNode { node_id, from, to });
+        }
+    }
+
+    for event in events.edge_created.read() {
+        let Ok((visual, relationship)) = edges.get(event.entity) else {
+            continue;
+        };
+        let edge = EdgeRecord {
+            edge_id: event.edge_id,
+            graph_id: visual.graph_id,
+            source_node_id: event.source_node_id,
+            target_node_id: event.target_node_id,
+            relationship: relationship.cloned().unwrap_or(EdgeRelationship::Custom(String::new())),
+            weight: visual.weight,
+        };
+        history.edges.insert(event.edge_id, edge.clone());
+        if !history.echoes.remove(&Echo::EdgeCreated(event.edge_id)) {
+            recorded.push(GraphOperation::CreateEdge(edge));
+        }
+    }
+
+    for event in events.edge_deleted.read() {
+        let Some(edge) = history.edges.remove(&event.edge_id) else {
+            continue;
+        };
+        if !history.echoes.remove(&Echo::EdgeDeleted(event.edge_id)) {
+            recorded.push(GraphOperation::DeleteEdge(edge));
+        }
+    }
+
+    for event in events.node_deleted.read() {
+        let Some(mut node) = history.nodes.remove(&event.node_id) else {
+            continue;
+        };
+        node.position = event.final_position;
+        if history.echoes.remove(&Echo::NodeDeleted(event.node_id)) {
+            continue;
+        }
+
+        // The node's edges go with it, whether they were deleted this frame
+        // or are left dangling
+        let touches = |edge: &EdgeRecord| {
+            edge.source_node_id == event.node_id || edge.target_node_id == event.node_id
+        };
+        recorded.retain(|operation| match operation {
+            GraphOperation::DeleteEdge(edge) if touches(edge) => {
+                node.edges.push(edge.clone());
+                false
+            }
+            _ => true,
+        });
+        let connected: Vec<EdgeId> = history.edges.values()
+            .filter(|edge| touches(edge))
+            .map(|edge| edge.edge_id)
+            .collect();
+        node.edges.extend(connected.iter().filter_map(|edge_id| history.edges.remove(edge_id)));
+        recorded.push(GraphOperation::DeleteNode(node));
+    }
+
+    // Replayed commands are handled within the frame, so anything still
+    // expected was never sent
+    history.echoes.clear();
+
+    for operation in recorded {
+        history.record(operation, config.max_depth);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::morphisms::{
+        create_edge_visual, create_node_visual, remove_edge_visual, remove_node_visual, NodeEntityMap,
+    };
+    use crate::events::NodeMetadataChanged;
+
+    #[test]
+    fn test_history_is_capped() {
+        let mut history = UndoHistory::default();
+        let node_id = NodeId::new();
+        for step in 0..5 {
+            let to = Vec3::splat(step as f32);
+            history.record(GraphOperation::MoveNode { node_id, from: Vec3::ZERO, to }, 3);
+        }
+        assert_eq!(history.len(), 3);
+        assert_eq!(
+            history.undo.front(),
+            Some(&GraphOperation::MoveNode { node_id, from: Vec3::ZERO, to: Vec3::splat(2.0) })
+        );
+    }
+
+    #[test]
+    fn test_create_undo_redo_restores_node() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugins(UndoRedoPlugin)
+            .init_resource::<NodeEntityMap>()
+            .add_event::<NodeMetadataChanged>()
+            .add_systems(Update, (create_node_visual, remove_node_visual).chain().in_set(CimSet::Commands));
+
+        let node_id = NodeId::new();
+        let position = Vec3::new(1.0, 2.0, 0.0);
+        app.world_mut().send_event(CreateNodeVisual {
+            node_id,
+            graph_id: GraphId::new(),
+            position,
+            label: "Order".to_string(),
+            metadata: serde_json::json!({ "tags": ["sales"] }),
+        });
+        app.update();
+        assert_eq!(app.world().resource::<UndoHistory>().len(), 1);
+
+        let find_node = |app: &mut App| {
+            app.world_mut()
+                .query::<(&NodeVisual, &Transform, &NodeMetadata)>()
+                .iter(app.world())
+                .find(|(visual, _, _)| visual.node_id == node_id)
+                .map(|(_, transform, metadata)| (transform.translation, metadata.clone()))
+        };
+        let created = find_node(&mut app).expect("node was created");
+
+        app.world_mut().send_event(Undo);
+        app.update();
+        assert!(find_node(&mut app).is_none());
+        assert!(app.world().resource::<UndoHistory>().can_redo());
+
+        app.world_mut().send_event(Redo);
+        app.update();
+        assert_eq!(find_node(&mut app), Some(created));
+
+        // The redo replayed the creation without recording it again
+        let history = app.world().resource::<UndoHistory>();
+        assert_eq!(history.len(), 1);
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn test_drop_and_snap_are_undone_as_one_move() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugins(UndoRedoPlugin)
+            .init_resource::<NodeEntityMap>()
+            .add_event::<NodeMetadataChanged>()
+            .add_systems(Update, create_node_visual.in_set(CimSet::Commands));
+
+        let node_id = NodeId::new();
+        app.world_mut().send_event(CreateNodeVisual {
+            node_id,
+            graph_id: GraphId::new(),
+            position: Vec3::ZERO,
+            label: "Order".to_string(),
+            metadata: serde_json::Value::Null,
+        });
+        app.update();
+        let entity = app.world().resource::<NodeEntityMap>().get(&node_id).copied().unwrap();
+
+        let (dropped, snapped) = (Vec3::new(3.9, 0.1, 0.0), Vec3::new(4.0, 0.0, 0.0));
+        app.world_mut().get_mut::<Transform>(entity).unwrap().translation = snapped;
+        app.world_mut().send_event(NodeDragEnd { entity, node_id, final_position: dropped });
+        app.world_mut().send_event(NodePositionChanged { entity, node_id, old_position: dropped, new_position: snapped });
+        app.update();
+        let history = app.world().resource::<UndoHistory>();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.undo.back(), Some(&GraphOperation::MoveNode { node_id, from: Vec3::ZERO, to: snapped }));
+
+        // The position change the undo sends is not recorded as a new move
+        app.world_mut().send_event(Undo);
+        app.update();
+        assert_eq!(app.world().get::<Transform>(entity).unwrap().translation, Vec3::ZERO);
+        let history = app.world().resource::<UndoHistory>();
+        assert_eq!(history.len(), 1);
+        assert!(history.can_redo());
+    }
+
+    #[test]
+    fn test_undoing_a_node_deletion_restores_its_edges() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugins(UndoRedoPlugin)
+            .init_resource::<NodeEntityMap>()
+            .add_event::<NodeMetadataChanged>()
+            .add_systems(
+                Update,
+                (create_node_visual, remove_node_visual, create_edge_visual, remove_edge_visual)
+                    .chain()
+                    .in_set(CimSet::Commands),
+            );
+
+        let graph_id = GraphId::new();
+        let (source, target, edge_id) = (NodeId::new(), NodeId::new(), EdgeId::new());
+        for (node_id, x) in [(source, 0.0), (target, 4.0)] {
+            app.world_mut().send_event(CreateNodeVisual {
+                node_id,
+                graph_id,
+                position: Vec3::new(x, 0.0, 0.0),
+                label: "Order".to_string(),
+                metadata: serde_json::Value::Null,
+            });
+        }
+        app.world_mut().send_event(CreateEdgeVisual {
+            edge_id,
+            graph_id,
+            source_node_id: source,
+            target_node_id: target,
+            relationship: EdgeRelationship::DependsOn,
+            weight: None,
+        });
+        app.update();
+
+        app.world_mut().send_event(RemoveNodeVisual { node_id: target });
+        app.update();
+        let history = app.world().resource::<UndoHistory>();
+        match history.undo.back() {
+            Some(GraphOperation::DeleteNode(node)) => {
+                assert_eq!(node.node_id, target);
+                assert_eq!(node.edges.iter().map(|edge| edge.edge_id).collect::<Vec<_>>(), vec![edge_id]);
+            }
+            other => panic!("expected the deletion to be recorded, got {other:?}"),
+        }
+
+        app.world_mut().send_event(Undo);
+        app.update();
+        let target_entity = app.world().resource::<NodeEntityMap>().get(&target).copied()
+            .expect("node was restored");
+        let restored: Vec<_> = app.world_mut()
+            .query::<&EdgeVisual>()
+            .iter(app.world())
+            .filter(|edge| edge.edge_id == edge_id)
+            .map(|edge| edge.target_entity)
+            .collect();
+        assert_eq!(restored, vec![target_entity]);
+
+        // The restored node and edge were not recorded as new edits
+        let history = app.world().resource::<UndoHistory>();
+        assert_eq!(history.len(), 3);
+        assert!(history.can_redo());
+    }
+}
@@ -0,0 +1,203 @@
+//! Graph Validation: Reporting edges that lost or never found their nodes
+//!
+//! Two kinds of edges are reported in a [`GraphValidationReport`]:
+//!
+//! - **Dangling** edges are `EdgeVisual` entities whose source or target is
+//!   no longer a node, typically because the node was despawned.
+//! - **Orphan** edges were never created, because `create_edge_visual` found
+//!   no visual for one of their endpoints. They are kept in [`OrphanEdges`]
+//!   until the next validation picks them up.
+//!
+//! Validation runs on [`ValidateGraph`], which always answers with a report,
+//! and on its own after nodes are removed or edges are orphaned, when it only
+//! reports problems. With [`GraphValidationConfig::despawn_dangling`] set,
+//! dangling edges are despawned once reported.
+
+use bevy::prelude::*;
+use cim_contextgraph::{EdgeId, NodeId, ContextGraphId as GraphId};
+use crate::components::{EdgeVisual, NodeVisual};
+use crate::events::VisualEdgeDeleted;
+
+/// Command: Check every edge and send a report
+#[derive(Event, Debug, Clone, Default)]
+pub struct ValidateGraph;
+
+/// An edge entity whose end no longer is a node
+#[derive(Debug, Clone, PartialEq)]
+pub struct DanglingEdge {
+    pub entity: Entity,
+    pub edge_id: EdgeId,
+    /// The source and/or target entity that is gone
+    pub missing: Vec<Entity>,
+}
+
+/// An edge that was never created for want of its nodes
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrphanEdge {
+    pub edge_id: EdgeId,
+    pub graph_id: GraphId,
+    pub source_node_id: NodeId,
+    pub target_node_id: NodeId,
+    /// The endpoints that had no visual
+    pub missing: Vec<NodeId>,
+}
+
+/// Event: What a validation found
+#[derive(Event, Debug, Clone, Default, PartialEq)]
+pub struct GraphValidationReport {
+    pub dangling_edges: Vec<DanglingEdge>,
+    pub orphan_edges: Vec<OrphanEdge>,
+}
+
+impl GraphValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.dangling_edges.is_empty() && self.orphan_edges.is_empty()
+    }
+}
+
+/// Edges skipped on creation since the last validation
+#[derive(Resource, Debug, Clone, Default)]
+pub struct OrphanEdges(pub Vec<OrphanEdge>);
+
+/// How validation deals with what it finds
+#[derive(Resource, Debug, Clone, Default)]
+pub struct GraphValidationConfig {
+    /// Despawn dangling edges after reporting them
+    pub despawn_dangling: bool,
+}
+
+/// Dangling edges among `edges`, given which entities are nodes
+pub fn dangling_edges<'a>(
+    edges: impl IntoIterator<Item = (Entity, &'a EdgeVisual)>,
+    is_node: impl Fn(Entity) -> bool,
+) -> Vec<DanglingEdge> {
+    edges.into_iter()
+        .filter_map(|(entity, edge)| {
+            let mut missing: Vec<Entity> = [edge.source_entity, edge.target_entity].into_iter()
+                .filter(|end| !is_node(*end))
+                .collect();
+            missing.dedup();
+            (!missing.is_empty()).then_some(DanglingEdge { entity, edge_id: edge.edge_id, missing })
+        })
+        .collect()
+}
+
+/// Plugin that reports dangling and orphan edges
+pub struct GraphValidationPlugin;
+
+impl Plugin for GraphValidationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OrphanEdges>()
+            .init_resource::<GraphValidationConfig>()
+            .add_event::<ValidateGraph>()
+            .add_event::<GraphValidationReport>()
+            .add_event::<VisualEdgeDeleted>()
+            // After Update, so edges skipped there are included
+            .add_systems(PostUpdate, validate_graph);
+    }
+}
+
+/// System that validates the edges when asked to, or when nodes were
+/// removed or edges orphaned since it last ran
+#[allow(clippy::too_many_arguments)]
+pub fn validate_graph(
+    mut commands: Commands,
+    mut requests: EventReader<ValidateGraph>,
+    mut removed_nodes: RemovedComponents<NodeVisual>,
+    mut orphans: ResMut<OrphanEdges>,
+    config: Res<GraphValidationConfig>,
+    edges: Query<(Entity, &EdgeVisual)>,
+    nodes: Query<(), With<NodeVisual>>,
+    mut reports: EventWriter<GraphValidationReport>,
+    mut edge_deleted: EventWriter<VisualEdgeDeleted>,
+) {
+    let requested = requests.read().count() > 0;
+    let nodes_removed = removed_nodes.read().count() > 0;
+    if !requested && !nodes_removed && orphans.0.is_empty() {
+        return;
+    }
+
+    let report = GraphValidationReport {
+        dangling_edges: dangling_edges(edges.iter(), |entity| nodes.contains(entity)),
+        orphan_edges: std::mem::take(&mut orphans.0),
+    };
+    if config.despawn_dangling {
+        for dangling in &report.dangling_edges {
+            commands.entity(dangling.entity).despawn();
+            edge_deleted.write(VisualEdgeDeleted { edge_id: dangling.edge_id });
+        }
+    }
+    if requested || !report.is_valid() {
+        if !report.is_valid() {
+            warn!(
+                "Graph validation found {} dangling and {} orphan edges",
+                report.dangling_edges.len(),
+                report.orphan_edges.len(),
+            );
+        }
+        reports.write(report);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::EdgeVisualBundle;
+    use crate::events::{CreateEdgeVisual, EdgeRelationship, VisualEdgeCreated};
+    use crate::morphisms::{create_edge_visual, NodeEntityMap};
+
+    #[test]
+    fn test_edges_to_missing_nodes_are_reported() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, GraphValidationPlugin))
+            .insert_resource(GraphValidationConfig { despawn_dangling: true })
+            .init_resource::<NodeEntityMap>()
+            .add_event::<CreateEdgeVisual>()
+            .add_event::<VisualEdgeCreated>()
+            .add_systems(Update, create_edge_visual);
+
+        let graph_id = GraphId::new();
+        let (present, removed, never_created) = (NodeId::new(), NodeId::new(), NodeId::new());
+        let mut spawn_node = |node_id: NodeId| {
+            let entity = app.world_mut().spawn(NodeVisual { node_id, graph_id }).id();
+            app.world_mut().resource_mut::<NodeEntityMap>().insert(node_id, entity);
+            entity
+        };
+        let (present_entity, removed_entity) = (spawn_node(present), spawn_node(removed));
+        let kept = app.world_mut().spawn(EdgeVisualBundle::new(EdgeId::new(), graph_id, present_entity, present_entity)).id();
+        let dangling_id = EdgeId::new();
+        let dangling = app.world_mut().spawn(EdgeVisualBundle::new(dangling_id, graph_id, present_entity, removed_entity)).id();
+        app.update();
+        assert!(app.world().resource::<Events<GraphValidationReport>>().is_empty());
+
+        app.world_mut().despawn(removed_entity);
+        let orphan_id = EdgeId::new();
+        app.world_mut().send_event(CreateEdgeVisual {
+            edge_id: orphan_id,
+            graph_id,
+            source_node_id: present,
+            target_node_id: never_created,
+            relationship: EdgeRelationship::DependsOn,
+            weight: None,
+        });
+        app.update();
+
+        let reports: Vec<GraphValidationReport> = app.world().resource::<Events<GraphValidationReport>>()
+            .iter_current_update_events()
+            .cloned()
+            .collect();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].dangling_edges, [DanglingEdge { entity: dangling, edge_id: dangling_id, missing: vec![removed_entity] }]);
+        assert_eq!(reports[0].orphan_edges.len(), 1);
+        assert_eq!(reports[0].orphan_edges[0].edge_id, orphan_id);
+        assert_eq!(reports[0].orphan_edges[0].missing, [never_created]);
+        assert!(app.world().get_entity(dangling).is_err());
+        assert!(app.world().get_entity(kept).is_ok());
+
+        // Asked again, the now clean graph gets a clean report
+        app.world_mut().send_event(ValidateGraph);
+        app.update();
+        let report = app.world().resource::<Events<GraphValidationReport>>().iter_current_update_events().last().cloned();
+        assert_eq!(report, Some(GraphValidationReport::default()));
+    }
+}
@@ -55,25 +55,20 @@ impl Position {
     }
 }
 
-/// Node visual appearance
-#[derive(Component, Debug, Clone, PartialEq, Default)]
-pub struct NodeVisual {
-    pub color: Color,
-    pub size: f32,
-    pub shape: NodeShape,
+impl From<Position> for Vec3 {
+    fn from(position: Position) -> Self {
+        Vec3::new(position.x, position.y, position.z)
+    }
 }
 
-/// Edge visual appearance
-#[derive(Component, Debug, Clone, PartialEq, Default)]
-pub struct EdgeVisual {
-    pub color: Color,
-    pub width: f32,
-    pub style: EdgeStyle,
-}
+/// Node shapes, shared with the visual components
+pub use crate::components::NodeShape;
 
-/// Node shapes
+/// The shapes value objects had before sharing [`NodeShape`]
+#[deprecated(note = "use `NodeShape`, which has every variant of this enum")]
+#[allow(deprecated)]
 #[derive(Debug, Clone, PartialEq, Default)]
-pub enum NodeShape {
+pub enum LegacyNodeShape {
     #[default]
     Circle,
     Square,
@@ -81,29 +76,146 @@ pub enum NodeShape {
     Hexagon,
 }
 
-/// Edge styles
-#[derive(Debug, Clone, PartialEq, Default)]
-pub enum EdgeStyle {
-    #[default]
-    Solid,
-    Dashed,
-    Dotted,
+#[allow(deprecated)]
+impl From<LegacyNodeShape> for NodeShape {
+    fn from(shape: LegacyNodeShape) -> Self {
+        match shape {
+            LegacyNodeShape::Circle => NodeShape::Circle,
+            LegacyNodeShape::Square => NodeShape::Square,
+            LegacyNodeShape::Diamond => NodeShape::Diamond,
+            LegacyNodeShape::Hexagon => NodeShape::Hexagon,
+        }
+    }
 }
 
-/// Interaction state
-#[derive(Component, Debug, Clone, Default)]
-pub struct InteractionState {
+/// Node visual appearance, from before the `NodeStyle` component carried it
+#[deprecated(note = "use the `NodeStyle` component, or `NodeVisualStyle` in commands and events")]
+#[allow(deprecated)]
+#[derive(Component, Debug, Clone, PartialEq, Default)]
+pub struct NodeVisual {
+    pub color: Color,
+    pub size: f32,
+    pub shape: NodeShape,
+}
+
+#[allow(deprecated)]
+impl NodeVisual {
+    /// The style this visual renders with; visuals have no border
+    pub fn style(&self) -> NodeVisualStyle {
+        NodeVisualStyle {
+            color: self.color,
+            size: self.size,
+            shape: self.shape,
+            border_color: Color::NONE,
+            border_width: 0.0,
+        }
+    }
+}
+
+#[allow(deprecated)]
+impl From<NodeVisual> for crate::components::NodeStyle {
+    fn from(visual: NodeVisual) -> Self {
+        visual.style().into()
+    }
+}
+
+/// Edge styles, shared with the visual components
+pub use crate::components::EdgeStyle;
+
+/// Interaction state of one node, mirrored from the `Selected`, `Hovered`
+/// and `Dragging` markers. The app-wide pointer state is the
+/// [`InteractionState`](crate::resources::InteractionState) resource.
+#[derive(Component, Debug, Clone, Default, PartialEq)]
+pub struct NodeInteractionState {
     pub is_hovered: bool,
     pub is_selected: bool,
     pub is_dragging: bool,
 }
 
 /// Node metadata
-#[derive(Component, Debug, Clone, Default)]
+#[derive(Component, Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
 pub struct NodeMetadata {
     pub label: String,
     pub description: String,
     pub tags: Vec<String>,
+    /// Every other domain metadata field, serialized alongside the ones above
+    #[serde(flatten)]
+    pub attributes: serde_json::Map<String, serde_json::Value>,
+}
+
+impl NodeMetadata {
+    /// Read `label`, `description` and `tags` from domain metadata JSON;
+    /// missing or mistyped fields are left empty. Any other fields are kept
+    /// as `attributes`.
+    pub fn from_json(value: &serde_json::Value) -> Self {
+        let text = |key: &str| {
+            value.get(key)
+                .and_then(serde_json::Value::as_str)
+                .unwrap_or_default()
+                .to_string()
+        };
+        let tags = value.get("tags")
+            .and_then(serde_json::Value::as_array)
+            .map(|tags| {
+                tags.iter()
+                    .filter_map(serde_json::Value::as_str)
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let attributes = value.as_object()
+            .map(|fields| {
+                fields.iter()
+                    .filter(|(key, _)| !matches!(key.as_str(), "label" | "description" | "tags"))
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            label: text("label"),
+            description: text("description"),
+            tags,
+            attributes,
+        }
+    }
+
+    /// Merge a metadata update in domain metadata JSON. Fields the update
+    /// has replace the current ones, and a `null` attribute removes it; the
+    /// rest are kept. An empty label keeps the current one.
+    pub fn merge_json(&mut self, update: &serde_json::Value) {
+        let Some(fields) = update.as_object() else {
+            return;
+        };
+        let update = Self::from_json(update);
+        if !update.label.is_empty() {
+            self.label = update.label;
+        }
+        if fields.contains_key("description") {
+            self.description = update.description;
+        }
+        if fields.contains_key("tags") {
+            self.tags = update.tags;
+        }
+        for (key, value) in update.attributes {
+            if value.is_null() {
+                self.attributes.remove(&key);
+            } else {
+                self.attributes.insert(key, value);
+            }
+        }
+    }
+
+    /// Numeric attribute at a dot-separated path, e.g.
+    /// `deployment.resources.cpu_cores`
+    pub fn number(&self, field: &str) -> Option<f32> {
+        let mut path = field.split('.');
+        let first = self.attributes.get(path.next()?)?;
+        path.try_fold(first, |value, key| value.get(key))?
+            .as_f64()
+            .map(|number| number as f32)
+    }
 }
 
 /// Source node reference
@@ -122,12 +234,23 @@ pub struct EdgeCurve {
 }
 
 /// Canvas state
-#[derive(Component, Debug, Clone, Default)]
+///
+/// A canvas point `p` is shown at `p * zoom + offset`.
+#[derive(Component, Debug, Clone, PartialEq)]
 pub struct CanvasState {
     pub offset: Vec2,
     pub zoom: f32,
 }
 
+impl Default for CanvasState {
+    fn default() -> Self {
+        Self {
+            offset: Vec2::ZERO,
+            zoom: 1.0,
+        }
+    }
+}
+
 /// Viewport settings
 #[derive(Component, Debug, Clone, Default)]
 pub struct Viewport {
@@ -135,7 +258,7 @@ pub struct Viewport {
     pub height: f32,
 }
 
-/// Render settings
+/// Render settings of a canvas
 #[derive(Component, Debug, Clone, Default)]
 pub struct RenderSettings {
     pub antialiasing: bool,
@@ -143,6 +266,21 @@ pub struct RenderSettings {
     pub grid_size: f32,
 }
 
+/// Render settings for the whole scene, kept apart from the per-canvas
+/// [`RenderSettings`] components
+#[derive(Resource, Debug, Clone, Deref, DerefMut)]
+pub struct SceneRenderSettings(pub RenderSettings);
+
+impl Default for SceneRenderSettings {
+    fn default() -> Self {
+        Self(RenderSettings {
+            antialiasing: true,
+            show_grid: true,
+            grid_size: 1.0,
+        })
+    }
+}
+
 /// Visual style for nodes
 #[derive(Debug, Clone, PartialEq)]
 pub struct NodeVisualStyle {
@@ -153,11 +291,12 @@ pub struct NodeVisualStyle {
     pub border_width: f32,
 }
 
-/// Visual style for edges
+/// Visual style for edges, as commands carry it; the [`EdgeStyle`]
+/// component is made from it
 #[derive(Debug, Clone, PartialEq)]
 pub struct EdgeVisualStyle {
     pub color: Color,
     pub width: f32,
-    pub style: EdgeStyle,
+    pub dashed: bool,
     pub arrow_size: f32,
 }
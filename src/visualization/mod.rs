@@ -1,5 +1,7 @@
 //! Visualization support for ContextGraphs
 
+pub mod shapes;
+
 use cim_contextgraph::{NodeId, EdgeId};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -12,23 +14,14 @@ pub enum LayoutType {
     Circular,
     Grid,
     Random,
+    /// Nodes stay where they are placed
+    Manual,
+    /// Nodes grouped by a metadata key into clusters around a circle
+    Clustered,
 }
 
-/// Visual style for nodes
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct NodeStyle {
-    pub color: String,
-    pub shape: String,
-    pub size: f32,
-}
-
-/// Visual style for edges
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct EdgeStyle {
-    pub color: String,
-    pub width: f32,
-    pub style: String, // solid, dashed, dotted
-}
+/// Visual styles of nodes and edges, shared with the visual components
+pub use crate::components::{EdgeStyle, NodeStyle};
 
 /// Interaction modes for graph manipulation
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
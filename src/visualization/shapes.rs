@@ -0,0 +1,108 @@
+//! Node shape meshes
+//!
+//! [`ShapeMeshCache`] hands out one unit-sized mesh per [`NodeShape`], so
+//! nodes of the same shape share a mesh handle instead of each allocating
+//! their own. Sizes are applied through the node's `Transform` scale, never
+//! baked into the mesh. With [`NodeShapePlugin`] added, every entity
+//! carrying a [`NodeStyle`] is given the mesh for its shape and scaled to
+//! its size.
+
+use bevy::prelude::*;
+use std::collections::HashMap;
+use crate::components::{NodeShape, NodeStyle};
+use crate::instancing::unit_shape_mesh;
+
+/// Unit meshes already built, keyed by shape
+#[derive(Resource, Debug, Default)]
+pub struct ShapeMeshCache {
+    meshes: HashMap<NodeShape, Handle<Mesh>>,
+}
+
+impl ShapeMeshCache {
+    /// Unit mesh for `shape`, built on first use and shared afterwards
+    pub fn shape_to_mesh(&mut self, shape: NodeShape, meshes: &mut Assets<Mesh>) -> Handle<Mesh> {
+        self.meshes
+            .entry(shape)
+            .or_insert_with(|| meshes.add(unit_shape_mesh(shape)))
+            .clone()
+    }
+
+    /// Number of distinct meshes built so far
+    pub fn len(&self) -> usize {
+        self.meshes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.meshes.is_empty()
+    }
+}
+
+/// Plugin that meshes nodes according to their [`NodeStyle`]
+pub struct NodeShapePlugin;
+
+impl Plugin for NodeShapePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShapeMeshCache>()
+            .add_systems(Update, apply_node_style_shapes);
+    }
+}
+
+/// System that gives nodes whose [`NodeStyle`] was added or changed the
+/// cached mesh for its shape and the scale for its size
+pub fn apply_node_style_shapes(
+    mut commands: Commands,
+    mut cache: ResMut<ShapeMeshCache>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut styled: Query<(Entity, &NodeStyle, Option<&Mesh3d>, Option<&mut Transform>), Changed<NodeStyle>>,
+) {
+    for (entity, style, current, transform) in styled.iter_mut() {
+        let mesh = cache.shape_to_mesh(style.shape, &mut meshes);
+        if current.is_none_or(|current| current.0 != mesh) {
+            commands.entity(entity).insert(Mesh3d(mesh));
+        }
+        if let Some(mut transform) = transform {
+            let scale = Vec3::splat(style.size);
+            if transform.scale != scale {
+                transform.scale = scale;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identical_shapes_share_a_mesh() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Mesh>()
+            .add_plugins(NodeShapePlugin);
+
+        let style = |shape, size| (NodeStyle { shape, size, ..default() }, Transform::default());
+        let a = app.world_mut().spawn(style(NodeShape::Hexagon, 1.0)).id();
+        let larger = app.world_mut().spawn(style(NodeShape::Hexagon, 2.0)).id();
+        let square = app.world_mut().spawn(style(NodeShape::Square, 1.0)).id();
+        app.update();
+
+        let mesh = |app: &App, entity| app.world().get::<Mesh3d>(entity).unwrap().0.clone();
+        assert_eq!(mesh(&app, a), mesh(&app, larger));
+        assert_ne!(mesh(&app, a), mesh(&app, square));
+        assert_eq!(app.world().resource::<ShapeMeshCache>().len(), 2);
+        assert_eq!(app.world().resource::<Assets<Mesh>>().len(), 2);
+
+        // The size is only in the transform, so it is applied once
+        assert_eq!(app.world().get::<Transform>(larger).unwrap().scale, Vec3::splat(2.0));
+
+        // Restyling swaps the mesh and rescales
+        {
+            let mut restyled = app.world_mut().get_mut::<NodeStyle>(a).unwrap();
+            restyled.shape = NodeShape::Square;
+            restyled.size = 3.0;
+        }
+        app.update();
+        assert_eq!(mesh(&app, a), mesh(&app, square));
+        assert_eq!(app.world().get::<Transform>(a).unwrap().scale, Vec3::splat(3.0));
+    }
+}
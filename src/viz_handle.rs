@@ -0,0 +1,192 @@
+//! Viz Handle: An imperative facade over the visualization commands
+//!
+//! [`VizHandle`] lets integrators build a graph with plain method calls
+//! instead of sending Bevy events: `add_node`, `add_edge`, `remove_node` and
+//! `set_layout` queue the matching commands, which [`flush_viz_handle`]
+//! sends at the start of the next frame. From there on they go through the
+//! same morphisms, projections and layouts as commands from the domain.
+//!
+//! A handle works on one graph, a new one unless created with
+//! [`VizHandle::for_graph`]. It becomes the active graph if no other is.
+
+use bevy::prelude::*;
+use cim_contextgraph::{EdgeId, NodeId, ContextGraphId as GraphId};
+use std::collections::HashMap;
+use crate::events::{CreateEdgeVisual, CreateNodeVisual, EdgeRelationship, RemoveEdgeVisual, RemoveNodeVisual};
+use crate::layout::SetLayoutAlgorithm;
+use crate::resources::ActiveGraph;
+use crate::visualization::LayoutType;
+
+/// A command queued on the handle
+#[derive(Debug, Clone)]
+enum QueuedCommand {
+    CreateNode(CreateNodeVisual),
+    CreateEdge(CreateEdgeVisual),
+    RemoveNode(RemoveNodeVisual),
+    RemoveEdge(RemoveEdgeVisual),
+    SetLayout(SetLayoutAlgorithm),
+}
+
+/// Imperative access to one graph of the visualization
+#[derive(Resource, Debug)]
+pub struct VizHandle {
+    graph_id: GraphId,
+    queued: Vec<QueuedCommand>,
+    /// Ends of the edges added through the handle, so removing a node
+    /// takes its edges with it
+    edges: HashMap<EdgeId, (NodeId, NodeId)>,
+}
+
+impl Default for VizHandle {
+    fn default() -> Self {
+        Self::for_graph(GraphId::new())
+    }
+}
+
+impl VizHandle {
+    pub fn for_graph(graph_id: GraphId) -> Self {
+        Self {
+            graph_id,
+            queued: Vec::new(),
+            edges: HashMap::new(),
+        }
+    }
+
+    pub fn graph_id(&self) -> GraphId {
+        self.graph_id
+    }
+
+    /// Commands waiting for the next frame
+    pub fn pending(&self) -> usize {
+        self.queued.len()
+    }
+
+    pub fn add_node(&mut self, id: NodeId, position: impl Into<Vec3>, label: impl Into<String>) -> &mut Self {
+        let label = label.into();
+        self.queued.push(QueuedCommand::CreateNode(CreateNodeVisual {
+            node_id: id,
+            graph_id: self.graph_id,
+            position: position.into(),
+            metadata: serde_json::json!({ "label": &label }),
+            label,
+        }));
+        self
+    }
+
+    pub fn add_edge(&mut self, id: EdgeId, source: NodeId, target: NodeId, relationship: EdgeRelationship) -> &mut Self {
+        self.edges.insert(id, (source, target));
+        self.queued.push(QueuedCommand::CreateEdge(CreateEdgeVisual {
+            edge_id: id,
+            graph_id: self.graph_id,
+            source_node_id: source,
+            target_node_id: target,
+            relationship,
+            weight: None,
+        }));
+        self
+    }
+
+    /// Remove a node together with the edges added to or from it
+    pub fn remove_node(&mut self, id: NodeId) -> &mut Self {
+        let edges: Vec<EdgeId> = self.edges.iter()
+            .filter(|(_, (source, target))| *source == id || *target == id)
+            .map(|(edge_id, _)| *edge_id)
+            .collect();
+        for edge_id in edges {
+            self.remove_edge(edge_id);
+        }
+        self.queued.push(QueuedCommand::RemoveNode(RemoveNodeVisual { node_id: id }));
+        self
+    }
+
+    pub fn remove_edge(&mut self, id: EdgeId) -> &mut Self {
+        self.edges.remove(&id);
+        self.queued.push(QueuedCommand::RemoveEdge(RemoveEdgeVisual { edge_id: id }));
+        self
+    }
+
+    /// Switch the graph to `layout`; accepts the component `LayoutType` too
+    pub fn set_layout(&mut self, layout: impl Into<LayoutType>) -> &mut Self {
+        self.queued.push(QueuedCommand::SetLayout(SetLayoutAlgorithm {
+            graph_id: self.graph_id,
+            layout_type: layout.into(),
+        }));
+        self
+    }
+}
+
+/// The command writers [`flush_viz_handle`] sends through
+#[derive(bevy::ecs::system::SystemParam)]
+pub struct VizCommandWriters<'w> {
+    create_node: EventWriter<'w, CreateNodeVisual>,
+    create_edge: EventWriter<'w, CreateEdgeVisual>,
+    remove_node: EventWriter<'w, RemoveNodeVisual>,
+    remove_edge: EventWriter<'w, RemoveEdgeVisual>,
+    set_layout: EventWriter<'w, SetLayoutAlgorithm>,
+}
+
+/// System that sends the commands queued on the [`VizHandle`], in the order
+/// they were queued
+pub fn flush_viz_handle(
+    mut handle: ResMut<VizHandle>,
+    mut active_graph: ResMut<ActiveGraph>,
+    mut writers: VizCommandWriters,
+) {
+    if handle.queued.is_empty() {
+        return;
+    }
+    if active_graph.graph_id.is_none() {
+        active_graph.graph_id = Some(handle.graph_id);
+    }
+
+    for command in handle.queued.drain(..) {
+        match command {
+            QueuedCommand::CreateNode(command) => { writers.create_node.write(command); }
+            QueuedCommand::CreateEdge(command) => { writers.create_edge.write(command); }
+            QueuedCommand::RemoveNode(command) => { writers.remove_node.write(command); }
+            QueuedCommand::RemoveEdge(command) => { writers.remove_edge.write(command); }
+            QueuedCommand::SetLayout(command) => { writers.set_layout.write(command); }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin::CimDomainPlugin;
+    use crate::projections::GraphViewProjection;
+
+    #[test]
+    fn test_graph_built_through_the_handle_reaches_the_projection() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Mesh>()
+            .init_asset::<StandardMaterial>()
+            .init_asset::<bevy::gizmos::GizmoAsset>()
+            .init_resource::<bevy::gizmos::config::GizmoConfigStore>()
+            .init_gizmo_group::<DefaultGizmoConfigGroup>()
+            .add_plugins(CimDomainPlugin::default());
+
+        let nodes: Vec<NodeId> = (0..3).map(|_| NodeId::new()).collect();
+        let mut handle = app.world_mut().resource_mut::<VizHandle>();
+        handle.add_node(nodes[0], [0.0, 0.0, 0.0], "Orders")
+            .add_node(nodes[1], [4.0, 0.0, 0.0], "Billing")
+            .add_node(nodes[2], [0.0, 4.0, 0.0], "Shipping")
+            .add_edge(EdgeId::new(), nodes[0], nodes[1], EdgeRelationship::DependsOn)
+            .add_edge(EdgeId::new(), nodes[0], nodes[2], EdgeRelationship::Contains)
+            .set_layout(LayoutType::Circular);
+        let graph_id = handle.graph_id();
+        app.update();
+
+        let projection = app.world().resource::<GraphViewProjection>();
+        assert_eq!((projection.nodes.len(), projection.edges.len()), (3, 2));
+        assert_eq!(projection.nodes[&nodes[1]].metadata.label, "Billing");
+        assert_eq!(app.world().resource::<ActiveGraph>().graph_id, Some(graph_id));
+        assert_eq!(app.world().resource::<VizHandle>().pending(), 0);
+
+        app.world_mut().resource_mut::<VizHandle>().remove_node(nodes[2]);
+        app.update();
+        let projection = app.world().resource::<GraphViewProjection>();
+        assert_eq!((projection.nodes.len(), projection.edges.len()), (2, 1));
+    }
+}
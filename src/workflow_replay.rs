@@ -0,0 +1,248 @@
+//! Workflow Replay: Driving workflow nodes from recorded domain events
+//!
+//! [`WorkflowReplayPlugin`] plays back a recorded list of
+//! [`DomainEventReceived`] on a virtual clock that starts at the earliest
+//! event and runs `speed` times as fast as real time. When the clock reaches
+//! an event, the event is sent again as if it had just arrived, and the
+//! workflow nodes it belongs to are highlighted with a [`HighlightPath`],
+//! together with the edge from the step activated before them.
+//!
+//! An event belongs to a node whose metadata has the event's
+//! `aggregate_id` as an attribute or as its label, or whose label is the
+//! `step_name` in the event's payload.
+
+use bevy::prelude::*;
+use crate::components::{EdgeVisual, NodeVisual};
+use crate::events::HighlightPath;
+use crate::nats_event_visualization::DomainEventReceived;
+use crate::queries::QueryHandlerPlugin;
+use crate::value_objects::NodeMetadata;
+
+/// Seconds a replayed step stays highlighted
+const STEP_HIGHLIGHT_DURATION: f32 = 1.5;
+
+/// Recorded events and how far the replay has got through them
+#[derive(Resource, Debug, Clone)]
+pub struct WorkflowReplay {
+    /// Events in timestamp order
+    events: Vec<DomainEventReceived>,
+    /// Virtual seconds per real second
+    pub speed: f32,
+    pub playing: bool,
+    /// Virtual seconds since the first event
+    elapsed: f64,
+    /// Index of the next event to replay
+    next: usize,
+    /// Nodes activated by the last replayed event that had any
+    last_activated: Vec<Entity>,
+}
+
+impl WorkflowReplay {
+    pub fn new(mut events: Vec<DomainEventReceived>, speed: f32) -> Self {
+        // Stable, so events recorded at the same moment keep their order
+        events.sort_by_key(|event| event.timestamp);
+        Self {
+            events,
+            speed,
+            playing: true,
+            elapsed: 0.0,
+            next: 0,
+            last_activated: Vec::new(),
+        }
+    }
+
+    /// Events not replayed yet
+    pub fn remaining(&self) -> usize {
+        self.events.len() - self.next
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next == self.events.len()
+    }
+
+    /// Start over from the first event
+    pub fn restart(&mut self) {
+        self.elapsed = 0.0;
+        self.next = 0;
+        self.last_activated.clear();
+    }
+
+    /// Move the virtual clock on by `delta_secs` real seconds, if playing,
+    /// and take the events it reached
+    fn advance(&mut self, delta_secs: f32) -> &[DomainEventReceived] {
+        let Some(start) = self.events.first().map(|event| event.timestamp) else {
+            return &[];
+        };
+        if self.playing {
+            self.elapsed += delta_secs as f64 * self.speed as f64;
+        }
+
+        let from = self.next;
+        while let Some(event) = self.events.get(self.next) {
+            let offset = (event.timestamp - start).num_microseconds().unwrap_or(i64::MAX) as f64 / 1_000_000.0;
+            if offset > self.elapsed {
+                break;
+            }
+            self.next += 1;
+        }
+        &self.events[from..self.next]
+    }
+}
+
+/// Whether `event` belongs to the node with `metadata`
+pub fn event_matches_node(event: &DomainEventReceived, metadata: &NodeMetadata) -> bool {
+    let aggregate_id = Some(event.aggregate_id.as_str());
+    metadata.attributes.get("aggregate_id").and_then(serde_json::Value::as_str) == aggregate_id
+        || (!metadata.label.is_empty() && Some(metadata.label.as_str()) == aggregate_id)
+        || (!metadata.label.is_empty()
+            && event.payload.get("step_name").and_then(serde_json::Value::as_str) == Some(metadata.label.as_str()))
+}
+
+/// Plugin that replays recorded events against the workflow nodes
+pub struct WorkflowReplayPlugin {
+    pub events: Vec<DomainEventReceived>,
+    pub speed: f32,
+}
+
+impl Plugin for WorkflowReplayPlugin {
+    fn build(&self, app: &mut App) {
+        // Highlights are applied and expired by the query handlers
+        if !app.is_plugin_added::<QueryHandlerPlugin>() {
+            app.add_plugins(QueryHandlerPlugin);
+        }
+
+        app.insert_resource(WorkflowReplay::new(self.events.clone(), self.speed))
+            .add_event::<DomainEventReceived>()
+            .add_systems(Update, replay_workflow_events);
+    }
+}
+
+/// System that sends the events the replay clock reached and highlights
+/// their workflow nodes
+pub fn replay_workflow_events(
+    time: Res<Time>,
+    mut replay: ResMut<WorkflowReplay>,
+    nodes: Query<(Entity, &NodeMetadata), With<NodeVisual>>,
+    edges: Query<(Entity, &EdgeVisual)>,
+    mut received: EventWriter<DomainEventReceived>,
+    mut highlights: EventWriter<HighlightPath>,
+) {
+    if replay.is_finished() {
+        return;
+    }
+
+    let reached = replay.advance(time.delta_secs()).to_vec();
+    for event in reached {
+        let activated: Vec<Entity> = nodes.iter()
+            .filter(|(_, metadata)| event_matches_node(&event, metadata))
+            .map(|(entity, _)| entity)
+            .collect();
+        if !activated.is_empty() {
+            let connects = |a: Entity, b: Entity| {
+                replay.last_activated.contains(&a) && activated.contains(&b)
+            };
+            let edge_entities = edges.iter()
+                .filter(|(_, edge)| {
+                    connects(edge.source_entity, edge.target_entity) || connects(edge.target_entity, edge.source_entity)
+                })
+                .map(|(entity, _)| entity)
+                .collect();
+            highlights.write(HighlightPath {
+                node_entities: activated.clone(),
+                edge_entities,
+                duration: STEP_HIGHLIGHT_DURATION,
+            });
+            replay.last_activated = activated;
+        }
+        received.write(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::time::TimeUpdateStrategy;
+    use chrono::{DateTime, Duration, Utc};
+    use cim_contextgraph::{EdgeId, NodeId, ContextGraphId as GraphId};
+    use std::time::Duration as StdDuration;
+
+    fn event(id: &str, timestamp: DateTime<Utc>, step: &str) -> DomainEventReceived {
+        DomainEventReceived {
+            event_id: id.to_string(),
+            timestamp,
+            domain: "workflow".to_string(),
+            event_type: "StepExecuted".to_string(),
+            aggregate_id: "wf-1".to_string(),
+            aggregate_type: "Workflow".to_string(),
+            correlation_id: None,
+            causation_id: None,
+            payload: serde_json::json!({ "step_name": step }),
+        }
+    }
+
+    #[test]
+    fn test_events_replay_in_timestamp_order_at_real_speed() {
+        let start = Utc::now();
+        let recorded = vec![
+            event("third", start + Duration::seconds(2), "Ship"),
+            event("first", start, "Receive"),
+            event("second", start + Duration::seconds(1), "Pack"),
+        ];
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(StdDuration::from_millis(250)))
+            .add_plugins(WorkflowReplayPlugin { events: recorded, speed: 1.0 });
+
+        let graph_id = GraphId::new();
+        let steps: Vec<Entity> = ["Receive", "Pack", "Ship"].iter()
+            .map(|label| {
+                app.world_mut().spawn((
+                    NodeVisual { node_id: NodeId::new(), graph_id },
+                    NodeMetadata { label: label.to_string(), ..default() },
+                )).id()
+            })
+            .collect();
+        let edge = app.world_mut().spawn(EdgeVisual {
+            edge_id: EdgeId::new(),
+            graph_id,
+            source_entity: steps[0],
+            target_entity: steps[1],
+            weight: 1.0,
+        }).id();
+
+        let mut received_cursor = app.world().resource::<Events<DomainEventReceived>>().get_cursor();
+        let mut highlight_cursor = app.world().resource::<Events<HighlightPath>>().get_cursor();
+        let mut replayed = Vec::new();
+        let mut highlighted = Vec::new();
+        let mut step = |app: &mut App| {
+            app.update();
+            let world = app.world();
+            let ids: Vec<String> = received_cursor.read(world.resource::<Events<DomainEventReceived>>())
+                .map(|event| event.event_id.clone())
+                .collect();
+            highlighted.extend(highlight_cursor.read(world.resource::<Events<HighlightPath>>()).cloned());
+            replayed.extend(ids.clone());
+            ids
+        };
+
+        // No time passes in the first frame, so only the first event is due;
+        // after that each event comes a second after the one before
+        assert_eq!(step(&mut app), ["first"]);
+        for _ in 0..3 {
+            assert!(step(&mut app).is_empty());
+        }
+        assert_eq!(step(&mut app), ["second"]);
+        for _ in 0..3 {
+            assert!(step(&mut app).is_empty());
+        }
+        assert_eq!(step(&mut app), ["third"]);
+        assert_eq!(replayed, ["first", "second", "third"]);
+        assert!(app.world().resource::<WorkflowReplay>().is_finished());
+
+        let nodes: Vec<Vec<Entity>> = highlighted.iter().map(|path| path.node_entities.clone()).collect();
+        assert_eq!(nodes, [vec![steps[0]], vec![steps[1]], vec![steps[2]]]);
+        // Moving on from one step to the next lights up the edge between them
+        assert_eq!(highlighted[1].edge_entities, [edge]);
+        assert!(highlighted[2].edge_entities.is_empty());
+    }
+}
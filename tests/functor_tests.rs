@@ -31,8 +31,10 @@ fn test_visual_to_domain_functor_preserves_operations() {
     // Create a visual command
     let command = CreateNodeVisual {
         node_id,
+        graph_id: GraphId::new(),
         position: new_position,
         label: "Test Node".to_string(),
+        metadata: serde_json::Value::Null,
     };
 
     // Verify the command preserves the operation semantics
@@ -77,8 +79,10 @@ fn test_bridge_channel_communication() {
     // Test command sending (Bevy → Domain)
     let command = VisualizationCommand::CreateNode(CreateNodeVisual {
         node_id: uuid::Uuid::new_v4(),
+        graph_id: GraphId::new(),
         position: Vec3::ZERO,
         label: "Test".to_string(),
+        metadata: serde_json::Value::Null,
     });
 
     assert!(bridge.send_command(command.clone()).is_ok());
@@ -86,8 +90,10 @@ fn test_bridge_channel_communication() {
     // Test event receiving (Domain → Bevy)
     let event = VisualizationCommand::CreateNode(CreateNodeVisual {
         node_id: uuid::Uuid::new_v4(),
+        graph_id: GraphId::new(),
         position: Vec3::ONE,
         label: "Test Event".to_string(),
+        metadata: serde_json::Value::Null,
     });
 
     let sender = bridge.domain_sender();
@@ -104,8 +110,10 @@ fn test_event_morphism_categories() {
     // Creation morphisms
     let create_node = CreateNodeVisual {
         node_id: uuid::Uuid::new_v4(),
+        graph_id: GraphId::new(),
         position: Vec3::ZERO,
         label: "Test".to_string(),
+        metadata: serde_json::Value::Null,
     };
 
     // Deletion morphisms
@@ -174,8 +182,10 @@ mod integration_tests {
         let bridge_ref = app.world().resource::<AsyncSyncBridge>();
         let test_command = VisualizationCommand::CreateNode(CreateNodeVisual {
             node_id: uuid::Uuid::new_v4(),
+            graph_id: GraphId::new(),
             position: Vec3::ZERO,
             label: "Test".to_string(),
+            metadata: serde_json::Value::Null,
         });
         assert!(bridge_ref.send_command(test_command).is_ok());
     }